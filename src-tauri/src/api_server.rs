@@ -0,0 +1,194 @@
+use crate::commands;
+use crate::error::ClarityError;
+use crate::state::AppState;
+use axum::extract::{Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+// 本地 REST API：只监听 127.0.0.1，每个请求都要带 Authorization: Bearer <token>
+// 接口直接调用对应的 Tauri 命令，返回的数据和前端看到的一致
+
+#[derive(Clone)]
+struct ServerState {
+    app: AppHandle,
+    token: Arc<str>,
+}
+
+// 正在运行的服务，停止时等待已经建立的连接处理完
+pub struct ApiServerHandle {
+    pub port: u16,
+    shutdown: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl ApiServerHandle {
+    pub async fn stop(self) {
+        let _ = self.shutdown.send(());
+        if let Err(e) = self.task.await {
+            log::error!("API server task panicked: {:?}", e);
+        }
+        log::info!("API server on port {} stopped", self.port);
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RangeQuery {
+    start_time: Option<String>, // RFC 3339
+    end_time: Option<String>,
+    limit: Option<i64>,
+    tags: Option<String>, // 逗号分隔，只用于摘要
+}
+
+fn status_code(error: &ClarityError) -> StatusCode {
+    match error {
+        ClarityError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+        ClarityError::NotFound(_) => StatusCode::NOT_FOUND,
+        ClarityError::Conflict(_) => StatusCode::CONFLICT,
+        ClarityError::Permission(_) => StatusCode::FORBIDDEN,
+        ClarityError::Locked(_) => StatusCode::LOCKED,
+        ClarityError::ConsentRequired(_) => StatusCode::FORBIDDEN,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn respond<T: Serialize>(result: Result<T, ClarityError>) -> Response {
+    match result {
+        Ok(value) => Json(value).into_response(),
+        // 错误的格式和命令返回给前端的一样：{ kind, message, retryable }
+        Err(error) => (status_code(&error), Json(error)).into_response(),
+    }
+}
+
+// 逐字节比较全部内容，避免通过响应时间猜出令牌
+fn token_matches(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn require_token(
+    State(server): State<ServerState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| token_matches(token.trim(), &server.token));
+    if !authorized {
+        let error = ClarityError::Permission("Missing or invalid API token".to_string());
+        return (StatusCode::UNAUTHORIZED, Json(error)).into_response();
+    }
+    next.run(request).await
+}
+
+async fn status(State(server): State<ServerState>) -> Response {
+    respond(commands::get_status(server.app.state::<AppState>()).await)
+}
+
+async fn traces(State(server): State<ServerState>, Query(query): Query<RangeQuery>) -> Response {
+    respond(
+        commands::get_traces(
+            server.app.state::<AppState>(),
+            query.start_time,
+            query.end_time,
+            query.limit,
+        )
+        .await,
+    )
+}
+
+async fn summaries(State(server): State<ServerState>, Query(query): Query<RangeQuery>) -> Response {
+    let tags = query.tags.map(|tags| {
+        tags.split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    });
+    respond(
+        commands::get_summaries(
+            server.app.state::<AppState>(),
+            query.start_time,
+            query.end_time,
+            tags,
+            query.limit,
+        )
+        .await,
+    )
+}
+
+async fn statistics(State(server): State<ServerState>) -> Response {
+    respond(commands::get_today_statistics(server.app.state::<AppState>()).await)
+}
+
+async fn api_statistics(
+    State(server): State<ServerState>,
+    Query(query): Query<RangeQuery>,
+) -> Response {
+    respond(
+        commands::get_api_statistics(
+            server.app.state::<AppState>(),
+            query.start_time,
+            query.end_time,
+        )
+        .await,
+    )
+}
+
+// 在 127.0.0.1:port 启动服务，端口被占用等错误直接返回
+pub async fn start(app: AppHandle, port: u16, token: String) -> Result<ApiServerHandle, String> {
+    let server = ServerState {
+        app,
+        token: Arc::from(token),
+    };
+    let router = Router::new()
+        .route("/api/status", get(status))
+        .route("/api/traces", get(traces))
+        .route("/api/summaries", get(summaries))
+        .route("/api/statistics", get(statistics))
+        .route("/api/statistics/api", get(api_statistics))
+        .layer(middleware::from_fn_with_state(
+            server.clone(),
+            require_token,
+        ))
+        .with_state(server);
+
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .map_err(|e| format!("Failed to listen on {}: {}", address, e))?;
+
+    let (shutdown, shutdown_signal) = oneshot::channel();
+    let task = tokio::spawn(async move {
+        let result = axum::serve(listener, router)
+            .with_graceful_shutdown(async {
+                let _ = shutdown_signal.await;
+            })
+            .await;
+        if let Err(e) = result {
+            log::error!("API server error: {}", e);
+        }
+    });
+
+    log::info!("API server listening on {}", address);
+    Ok(ApiServerHandle {
+        port,
+        shutdown,
+        task,
+    })
+}
@@ -0,0 +1,88 @@
+use crate::db;
+use chrono::Local;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+// 累计多少秒后写入一次数据库，避免每次截图都写
+const APP_USAGE_FLUSH_SECONDS: i64 = 30;
+
+// 当前获得焦点的窗口（位置和大小为桌面坐标）
+#[derive(Debug, Clone)]
+pub struct FocusedWindow {
+    pub app: String,
+    pub title: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// 获取当前获得焦点的窗口（阻塞调用，获取失败或没有焦点窗口时返回 None，例如部分 Wayland 桌面）
+pub fn focused_window() -> Option<FocusedWindow> {
+    let windows = xcap::Window::all().ok()?;
+    let window = windows
+        .iter()
+        .find(|w| w.is_focused().unwrap_or(false) && !w.is_minimized().unwrap_or(false))?;
+    Some(FocusedWindow {
+        app: window.app_name().unwrap_or_default().trim().to_string(),
+        title: window.title().unwrap_or_default(),
+        x: window.x().unwrap_or(0),
+        y: window.y().unwrap_or(0),
+        width: window.width().unwrap_or(0),
+        height: window.height().unwrap_or(0),
+    })
+}
+
+// 获取当前获得焦点的窗口所属的应用名称（获取失败或没有焦点窗口时返回 None）
+pub fn focused_app_name() -> Option<String> {
+    focused_window()
+        .map(|window| window.app)
+        .filter(|name| !name.is_empty())
+}
+
+// 由截图循环使用，在内存中按 (应用, 日期) 累计使用时长，定期写入 app_usage 表
+#[derive(Default)]
+pub struct AppUsageTracker {
+    pending: HashMap<(String, String), i64>,
+    pending_seconds: i64,
+}
+
+impl AppUsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 记录当前焦点应用使用了 seconds 秒，累计足够时长后写入数据库
+    pub async fn record(&mut self, db_pool: &SqlitePool, seconds: i64) {
+        let app = tokio::task::spawn_blocking(focused_app_name)
+            .await
+            .ok()
+            .flatten();
+        if let Some(app) = app {
+            let date = Local::now().format("%Y-%m-%d").to_string();
+            *self.pending.entry((app, date)).or_insert(0) += seconds;
+            self.pending_seconds += seconds;
+        }
+
+        if self.pending_seconds >= APP_USAGE_FLUSH_SECONDS {
+            self.flush(db_pool).await;
+        }
+    }
+
+    // 把累计的使用时长写入数据库（录制停止时也要调用）
+    pub async fn flush(&mut self, db_pool: &SqlitePool) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let entries: Vec<(String, String, i64)> = self
+            .pending
+            .drain()
+            .map(|((app, date), seconds)| (app, date, seconds))
+            .collect();
+        self.pending_seconds = 0;
+        if let Err(e) = db::add_app_usage(db_pool, &entries).await {
+            log::error!("Failed to save app usage: {}", e);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use crate::commands;
+use crate::logging;
+use crate::onboarding;
+use crate::settings;
+use crate::state::AppState;
+use chrono::{Local, NaiveDate};
+
+const USAGE: &str = "Usage: clarity [--headless] [--ephemeral] <command> [options]
+
+Commands:
+  record [--consent LIST]         Record the screen until Ctrl+C (summaries are generated as usual).
+                                  LIST records consent without the desktop onboarding, comma-separated:
+                                  screen_capture (required to record) and ai_upload (to summarize)
+  summarize [--date YYYY-MM-DD]   Generate the daily summary (defaults to today) and print it
+  export [--date YYYY-MM-DD] [--to YYYY-MM-DD] [--dir PATH]
+                                  Export summaries to Markdown (defaults to today and the
+                                  folder configured in settings)
+  help                            Show this message
+
+Options:
+  --ephemeral                     Keep everything in memory and a temporary folder,
+                                  deleted on exit (also CLARITY_EPHEMERAL=1)
+
+Without a command Clarity starts the desktop app.";
+
+// 临时模式参数（桌面应用和命令行模式都支持）
+const EPHEMERAL_FLAG: &str = "--ephemeral";
+
+// 命令行模式的子命令
+#[derive(Debug)]
+enum Command {
+    Record {
+        consent: Vec<String>, // 开始录制前记录为已同意
+    },
+    Summarize {
+        date: NaiveDate,
+    },
+    Export {
+        start: NaiveDate,
+        end: NaiveDate,
+        dir: Option<String>,
+    },
+    Help,
+}
+
+fn parse_date(value: Option<String>) -> Result<Option<NaiveDate>, String> {
+    value
+        .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
+        .transpose()
+        .map_err(|e| format!("Invalid date format: {}", e))
+}
+
+fn parse_consent(value: Option<String>) -> Result<Vec<String>, String> {
+    let consent: Vec<String> = value
+        .unwrap_or_default()
+        .split(',')
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect();
+    match consent.iter().find(|c| !onboarding::is_valid_consent(c)) {
+        Some(c) => Err(format!("Unknown consent: {}", c)),
+        None => Ok(consent),
+    }
+}
+
+// 是否以临时模式启动：数据库只在内存中，截图写入临时目录，退出后全部删除
+pub fn ephemeral_requested(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == EPHEMERAL_FLAG)
+        || std::env::var("CLARITY_EPHEMERAL").is_ok_and(|v| v == "1" || v == "true")
+}
+
+// 按启动参数创建状态
+pub async fn init_state(ephemeral: bool) -> Result<AppState, sqlx::Error> {
+    if ephemeral {
+        AppState::ephemeral().await
+    } else {
+        AppState::new().await
+    }
+}
+
+// 解析命令行参数，没有子命令时返回 None（启动桌面应用）
+fn parse_args(args: &[String]) -> Result<Option<Command>, String> {
+    let headless = args.iter().any(|arg| arg == "--headless");
+    let mut rest = args
+        .iter()
+        .filter(|arg| arg.as_str() != "--headless" && arg.as_str() != EPHEMERAL_FLAG)
+        .cloned();
+
+    let name = match rest.next() {
+        Some(name) => name,
+        None if headless => return Err("Missing command".to_string()),
+        None => return Ok(None),
+    };
+
+    let mut date = None;
+    let mut to = None;
+    let mut dir = None;
+    let mut consent = None;
+    while let Some(arg) = rest.next() {
+        let slot = match arg.as_str() {
+            "--date" => &mut date,
+            "--to" => &mut to,
+            "--dir" => &mut dir,
+            "--consent" => &mut consent,
+            _ => return Err(format!("Unknown option: {}", arg)),
+        };
+        *slot = Some(
+            rest.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))?,
+        );
+    }
+
+    let today = Local::now().date_naive();
+    let command = match name.as_str() {
+        "record" => Command::Record {
+            consent: parse_consent(consent)?,
+        },
+        "summarize" => Command::Summarize {
+            date: parse_date(date)?.unwrap_or(today),
+        },
+        "export" => {
+            let start = parse_date(date)?.unwrap_or(today);
+            Command::Export {
+                start,
+                end: parse_date(to)?.unwrap_or(start),
+                dir,
+            }
+        }
+        "help" | "--help" | "-h" => Command::Help,
+        // 不认识的参数（例如系统启动时附带的参数）交给桌面应用处理
+        _ if !headless => return Ok(None),
+        _ => return Err(format!("Unknown command: {}", name)),
+    };
+    Ok(Some(command))
+}
+
+// 带子命令启动时在命令行模式运行（不创建窗口），返回退出码；没有子命令时返回 None
+pub fn run(args: Vec<String>) -> Option<i32> {
+    let command = match parse_args(args.get(1..).unwrap_or_default()) {
+        Ok(Some(command)) => command,
+        Ok(None) => return None,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return Some(2);
+        }
+    };
+    if let Command::Help = command {
+        println!("{}", USAGE);
+        return Some(0);
+    }
+
+    logging::init();
+    log::info!("Clarity starting in headless mode: {:?}", command);
+
+    let ephemeral = ephemeral_requested(&args);
+    let result = tauri::async_runtime::block_on(async move {
+        let state = init_state(ephemeral)
+            .await
+            .map_err(|e| format!("Failed to initialize database: {}", e))?;
+        let log_level = settings::load_log_level_from_db(&state.db_pool)
+            .await
+            .unwrap_or_else(|_| logging::DEFAULT_LOG_LEVEL.to_string());
+        logging::set_level(&log_level);
+
+        match command {
+            Command::Record { consent } => record(&state, &consent).await,
+            Command::Summarize { date } => summarize(&state, date).await,
+            Command::Export { start, end, dir } => export(&state, start, end, dir).await,
+            Command::Help => Ok(()),
+        }
+    });
+
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            log::error!("{}", e);
+            eprintln!("Error: {}", e);
+            Some(1)
+        }
+    }
+}
+
+// 录制直到收到 Ctrl+C，退出时和桌面应用一样收尾
+async fn record(state: &AppState, consent: &[String]) -> Result<(), String> {
+    for c in consent {
+        commands::set_consent(state, c, true).await?;
+    }
+
+    commands::start_summary_worker(state).await;
+    commands::start_summary_loop(state).await;
+
+    // 上次异常退出时记录录制空白
+    commands::resume_recording_after_launch(state).await;
+    if !state.recording.is_active() {
+        commands::begin_recording(state)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    println!(
+        "Recording to {}, press Ctrl+C to stop",
+        state.storage_path.lock().await.display()
+    );
+
+    tokio::signal::ctrl_c()
+        .await
+        .map_err(|e| format!("Failed to wait for Ctrl+C: {}", e))?;
+    println!("Stopping recording");
+    commands::shutdown(state).await;
+    Ok(())
+}
+
+async fn summarize(state: &AppState, date: NaiveDate) -> Result<(), String> {
+    if *state.incognito.lock().await {
+        return Err("Incognito mode is on, nothing is sent to AI providers".to_string());
+    }
+    if !*state.ai_upload_consent.lock().await {
+        return Err(
+            "Consent to upload to AI providers has not been given (see --consent)".to_string(),
+        );
+    }
+    let api_key = state.gemini_api_key.lock().await.clone();
+    let model = state.ai_model.lock().await.clone();
+    let daily_summary = commands::build_daily_summary(
+        &state.db_pool,
+        api_key,
+        &model,
+        &date.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+    println!("{}", daily_summary.content);
+    Ok(())
+}
+
+async fn export(
+    state: &AppState,
+    start: NaiveDate,
+    end: NaiveDate,
+    dir: Option<String>,
+) -> Result<(), String> {
+    let written = commands::export_markdown_range(&state.db_pool, start, end, dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    for path in written {
+        println!("{}", path);
+    }
+    Ok(())
+}
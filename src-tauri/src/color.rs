@@ -0,0 +1,133 @@
+// 截图颜色管理：xcap 返回的像素处于显示器的色彩空间，
+// 在 Display P3 或 HDR 屏幕上按 sRGB 解释会显得发灰或过亮，编码前统一转换为 sRGB
+
+// Display P3 → sRGB 的线性转换矩阵（两者白点均为 D65）
+const P3_TO_SRGB: [[f32; 3]; 3] = [
+    [1.224_940, -0.224_940, 0.0],
+    [-0.042_057, 1.042_057, 0.0],
+    [-0.019_638, -0.078_636, 1.098_274],
+];
+
+// HDR 模式下 SDR 内容会被系统提亮，用这个系数压回参考白
+const HDR_EXPOSURE: f32 = 0.8;
+
+// 编码查找表的精度
+const ENCODE_LUT_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorProfile {
+    Srgb,
+    DisplayP3,
+    Hdr,
+}
+
+impl ColorProfile {
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "display_p3" => ColorProfile::DisplayP3,
+            "hdr" => ColorProfile::Hdr,
+            _ => ColorProfile::Srgb,
+        }
+    }
+
+    pub fn is_valid_setting(value: &str) -> bool {
+        matches!(value, "srgb" | "display_p3" | "hdr")
+    }
+}
+
+// sRGB 传递函数：编码值 → 线性值
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// sRGB 传递函数：线性值 → 编码值
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// 高光柔和压缩，避免提亮后的高光直接截断
+fn tone_map(value: f32) -> f32 {
+    let shoulder = 0.8;
+    if value <= shoulder {
+        value
+    } else {
+        let excess = value - shoulder;
+        shoulder + (1.0 - shoulder) * (excess / (excess + (1.0 - shoulder)))
+    }
+}
+
+// 预先计算好查找表的颜色转换器，逐像素调用
+pub struct ColorConverter {
+    profile: ColorProfile,
+    decode_lut: [f32; 256],
+    encode_lut: Vec<u8>,
+}
+
+impl ColorConverter {
+    pub fn new(profile: ColorProfile) -> Self {
+        let mut decode_lut = [0.0; 256];
+        for (i, value) in decode_lut.iter_mut().enumerate() {
+            *value = srgb_to_linear(i as f32 / 255.0);
+        }
+
+        let encode_lut = (0..ENCODE_LUT_SIZE)
+            .map(|i| {
+                let linear = i as f32 / (ENCODE_LUT_SIZE - 1) as f32;
+                (linear_to_srgb(linear) * 255.0).round() as u8
+            })
+            .collect();
+
+        Self {
+            profile,
+            decode_lut,
+            encode_lut,
+        }
+    }
+
+    pub fn profile(&self) -> ColorProfile {
+        self.profile
+    }
+
+    fn encode(&self, linear: f32) -> u8 {
+        let index = (linear.clamp(0.0, 1.0) * (ENCODE_LUT_SIZE - 1) as f32).round() as usize;
+        self.encode_lut[index]
+    }
+
+    // 转换单个像素，sRGB 模式下原样返回
+    pub fn convert(&self, r: u8, g: u8, b: u8) -> [u8; 3] {
+        if self.profile == ColorProfile::Srgb {
+            return [r, g, b];
+        }
+
+        let linear = [
+            self.decode_lut[r as usize],
+            self.decode_lut[g as usize],
+            self.decode_lut[b as usize],
+        ];
+
+        let mut out = [0.0f32; 3];
+        for (row, value) in P3_TO_SRGB.iter().zip(out.iter_mut()) {
+            *value = row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2];
+        }
+
+        if self.profile == ColorProfile::Hdr {
+            for value in out.iter_mut() {
+                *value = tone_map(*value * HDR_EXPOSURE);
+            }
+        }
+
+        [
+            self.encode(out[0]),
+            self.encode(out[1]),
+            self.encode(out[2]),
+        ]
+    }
+}
@@ -0,0 +1,148 @@
+use crate::api_server;
+use crate::error::ClarityError;
+use crate::settings;
+use crate::state::AppState;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+// 默认端口（只监听 127.0.0.1）
+pub const DEFAULT_API_SERVER_PORT: u16 = 47600;
+
+// 令牌的随机字节数
+const API_TOKEN_BYTES: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerSettings {
+    pub enabled: bool, // 默认关闭
+    pub port: u16,
+    pub token: String, // 请求时放在 Authorization: Bearer <token> 中
+    pub running: bool, // 服务是否正在运行（开启后端口被占用时为 false）
+}
+
+// 生成随机令牌（URL 安全的 base64），也用作 webhook 的签名密钥
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; API_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// 读取令牌，还没有时生成一个并保存
+async fn load_or_create_token(state: &AppState) -> Result<String, ClarityError> {
+    match settings::load_api_server_token_from_db(&state.db_pool).await {
+        Ok(token) if !token.is_empty() => Ok(token),
+        _ => {
+            let token = generate_token();
+            settings::save_api_server_token_to_db(&state.db_pool, &token)
+                .await
+                .map_err(ClarityError::from)?;
+            Ok(token)
+        }
+    }
+}
+
+async fn load_api_server_settings(state: &AppState) -> Result<ApiServerSettings, ClarityError> {
+    Ok(ApiServerSettings {
+        enabled: settings::load_api_server_enabled_from_db(&state.db_pool)
+            .await
+            .unwrap_or(false),
+        port: settings::load_api_server_port_from_db(&state.db_pool)
+            .await
+            .unwrap_or(DEFAULT_API_SERVER_PORT),
+        token: load_or_create_token(state).await?,
+        running: state.api_server.lock().await.is_some(),
+    })
+}
+
+// 检查端口（导入配置时也使用）
+pub(crate) fn validate_api_server_port(port: u16) -> Result<(), ClarityError> {
+    if port < 1024 {
+        return Err(ClarityError::InvalidInput(
+            "Port must be between 1024 and 65535".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// 按当前设置启动或停止本地 REST API（先停止正在运行的服务）
+pub(crate) async fn apply_api_server_settings(
+    app: &AppHandle,
+    state: &AppState,
+) -> Result<(), ClarityError> {
+    let api_server_settings = load_api_server_settings(state).await?;
+
+    let mut server = state.api_server.lock().await;
+    if let Some(running) = server.take() {
+        running.stop().await;
+    }
+    // 端口被其他程序占用时无法启动
+    if api_server_settings.enabled {
+        *server = Some(
+            api_server::start(
+                app.clone(),
+                api_server_settings.port,
+                api_server_settings.token,
+            )
+            .await
+            .map_err(ClarityError::Conflict)?,
+        );
+    }
+    Ok(())
+}
+
+// 启动时按设置开启本地 REST API，失败时只记录日志
+pub async fn start_api_server_if_enabled(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    if let Err(e) = apply_api_server_settings(app, &state).await {
+        log::error!("Failed to start API server: {}", e);
+    }
+}
+
+// 获取本地 REST API 设置
+#[tauri::command]
+pub async fn get_api_server_settings(
+    state: State<'_, AppState>,
+) -> Result<ApiServerSettings, ClarityError> {
+    load_api_server_settings(&state).await
+}
+
+// 开启或关闭本地 REST API，立即生效
+#[tauri::command]
+pub async fn set_api_server_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+    port: u16,
+) -> Result<ApiServerSettings, ClarityError> {
+    validate_api_server_port(port)?;
+
+    settings::save_api_server_settings_to_db(&state.db_pool, enabled, port)
+        .await
+        .map_err(ClarityError::from)?;
+    log::info!(
+        "API server settings updated: enabled={}, port={}",
+        enabled,
+        port
+    );
+
+    apply_api_server_settings(&app, &state).await?;
+    load_api_server_settings(&state).await
+}
+
+// 重新生成令牌，旧令牌立即失效
+#[tauri::command]
+pub async fn regenerate_api_server_token(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ApiServerSettings, ClarityError> {
+    settings::save_api_server_token_to_db(&state.db_pool, &generate_token())
+        .await
+        .map_err(ClarityError::from)?;
+    log::info!("API server token regenerated");
+
+    apply_api_server_settings(&app, &state).await?;
+    load_api_server_settings(&state).await
+}
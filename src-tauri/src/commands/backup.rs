@@ -0,0 +1,437 @@
+use crate::commands::lock;
+use crate::db;
+use crate::error::ClarityError;
+use crate::keychain;
+use crate::state::AppState;
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Runtime, State};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+// 备份中数据库和截图的路径
+const BACKUP_DB_NAME: &str = "clarity.db";
+const BACKUP_RECORDINGS_DIR: &str = "recordings/";
+
+// 每处理这么多个文件发送一次进度
+const BACKUP_PROGRESS_INTERVAL: usize = 100;
+
+// 加密数据库密码的最短长度
+const MIN_DATABASE_PASSPHRASE_CHARS: usize = 8;
+
+// 数据库加密状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseEncryption {
+    pub available: bool, // 当前构建是否支持 SQLCipher
+    pub enabled: bool,   // 钥匙串中是否保存了数据库密码
+}
+
+// 备份和恢复进度（"backup-progress" 事件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupProgress {
+    pub stage: String, // "database", "recordings" or "restoring"
+    pub current: usize,
+    pub total: usize,
+}
+
+fn emit_backup_progress<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    stage: &str,
+    current: usize,
+    total: usize,
+) {
+    let _ = app_handle.emit(
+        "backup-progress",
+        BackupProgress {
+            stage: stage.to_string(),
+            current,
+            total,
+        },
+    );
+}
+
+// 收集存储目录中日期在范围内的截图文件（包括缩略图），返回相对存储目录的路径
+pub fn collect_recordings(
+    storage_path: &Path,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(storage_path)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(date) = NaiveDate::parse_from_str(&name, "%Y-%m-%d") else {
+            continue;
+        };
+        if start_date.is_some_and(|start| date < start) || end_date.is_some_and(|end| date > end) {
+            continue;
+        }
+
+        let mut dirs = vec![entry.path()];
+        while let Some(dir) = dirs.pop() {
+            for item in std::fs::read_dir(&dir)? {
+                let path = item?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if let Ok(relative) = path.strip_prefix(storage_path) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn write_backup_zip<R: Runtime>(
+    zip_path: &Path,
+    db_snapshot: &Path,
+    storage_path: &Path,
+    recordings: &[PathBuf],
+    app_handle: &AppHandle<R>,
+) -> Result<(), String> {
+    let file = File::create(zip_path)
+        .map_err(|e| format!("Failed to create {}: {}", zip_path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let zip_error = |e: zip::result::ZipError| format!("Failed to write backup: {}", e);
+    let io_error = |e: io::Error| format!("Failed to write backup: {}", e);
+
+    emit_backup_progress(app_handle, "database", 0, 1);
+    zip.start_file(
+        BACKUP_DB_NAME,
+        SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+    )
+    .map_err(zip_error)?;
+    io::copy(&mut File::open(db_snapshot).map_err(io_error)?, &mut zip).map_err(io_error)?;
+    emit_backup_progress(app_handle, "database", 1, 1);
+
+    // 截图已经是 JPEG，不再压缩
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for (i, relative) in recordings.iter().enumerate() {
+        let name = format!(
+            "{}{}",
+            BACKUP_RECORDINGS_DIR,
+            relative.to_string_lossy().replace('\\', "/")
+        );
+        zip.start_file(name, stored).map_err(zip_error)?;
+        io::copy(
+            &mut File::open(storage_path.join(relative)).map_err(io_error)?,
+            &mut zip,
+        )
+        .map_err(io_error)?;
+
+        if (i + 1) % BACKUP_PROGRESS_INTERVAL == 0 || i + 1 == recordings.len() {
+            emit_backup_progress(app_handle, "recordings", i + 1, recordings.len());
+        }
+    }
+
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+// 临时文件（数据库快照等），离开作用域时删除，出错提前返回时也不会留下
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn open_backup_zip(zip_path: &Path) -> Result<ZipArchive<File>, ClarityError> {
+    let file = File::open(zip_path)
+        .map_err(|e| format!("Failed to open {}: {}", zip_path.display(), e))?;
+    ZipArchive::new(file)
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid backup archive: {}", e)))
+}
+
+// 把备份中的数据库解压到 dest
+fn extract_backup_database(zip_path: &Path, dest: &Path) -> Result<(), ClarityError> {
+    let mut archive = open_backup_zip(zip_path)?;
+    let mut entry = archive.by_name(BACKUP_DB_NAME).map_err(|_| {
+        ClarityError::InvalidInput("Backup does not contain a database".to_string())
+    })?;
+
+    let io_error = |e: io::Error| format!("Failed to restore backup: {}", e);
+    io::copy(&mut entry, &mut File::create(dest).map_err(io_error)?).map_err(io_error)?;
+    Ok(())
+}
+
+// 把备份中的截图解压到存储目录（已存在的文件跳过），返回恢复的文件数量
+fn extract_backup_recordings<R: Runtime>(
+    zip_path: &Path,
+    storage_path: &Path,
+    app_handle: &AppHandle<R>,
+) -> Result<usize, ClarityError> {
+    let mut archive = open_backup_zip(zip_path)?;
+
+    let io_error = |e: io::Error| format!("Failed to restore backup: {}", e);
+    let total = archive.len();
+    let mut restored_files = 0;
+    for i in 0..total {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| ClarityError::InvalidInput(format!("Invalid backup archive: {}", e)))?;
+        // 忽略包含 .. 等不安全路径的条目
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        let Ok(relative) = name.strip_prefix(BACKUP_RECORDINGS_DIR) else {
+            continue;
+        };
+        let target = storage_path.join(relative);
+        if entry.is_dir() || target.exists() {
+            continue;
+        }
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        io::copy(&mut entry, &mut File::create(&target).map_err(io_error)?).map_err(io_error)?;
+        restored_files += 1;
+
+        if (i + 1) % BACKUP_PROGRESS_INTERVAL == 0 || i + 1 == total {
+            emit_backup_progress(app_handle, "restoring", i + 1, total);
+        }
+    }
+
+    Ok(restored_files)
+}
+
+// 把备份中的数据库放到待恢复路径（重启后生效），恢复后的数据库必须能用钥匙串中的密码打开：
+// 当前数据库已加密而备份未加密时，先用当前的密码加密；备份已加密时检查密码是否相同
+async fn stage_restored_database(extracted: &Path) -> Result<(), ClarityError> {
+    let staging_path = db::restore_staging_path();
+    let _ = tokio::fs::remove_file(&staging_path).await;
+
+    let database_key = if cfg!(feature = "sqlcipher") {
+        keychain::load_database_key().await
+    } else {
+        None
+    };
+    let plaintext = db::is_plaintext_database(extracted)
+        .map_err(|e| format!("Failed to read backup database: {}", e))?;
+
+    match (plaintext, database_key) {
+        (true, Some(key)) => {
+            let pool = db::open_database_file(extracted, None).await.map_err(|e| {
+                ClarityError::InvalidInput(format!("Invalid backup database: {}", e))
+            })?;
+            let result = db::export_encrypted_copy(&pool, &staging_path, &key).await;
+            pool.close().await;
+            if let Err(e) = result {
+                let _ = tokio::fs::remove_file(&staging_path).await;
+                return Err(ClarityError::Database(format!(
+                    "Failed to encrypt restored database: {}",
+                    e
+                )));
+            }
+            return Ok(());
+        }
+        (false, Some(key)) => {
+            let pool = db::open_database_file(extracted, Some(&key))
+                .await
+                .map_err(|_| {
+                    ClarityError::InvalidInput(
+                        "Backup database cannot be opened with the current database passphrase"
+                            .to_string(),
+                    )
+                })?;
+            pool.close().await;
+        }
+        (false, None) => {
+            return Err(ClarityError::InvalidInput(
+                "Backup database is encrypted, but the current database is not".to_string(),
+            ));
+        }
+        (true, None) => {}
+    }
+
+    if let Err(e) = tokio::fs::copy(extracted, &staging_path).await {
+        let _ = tokio::fs::remove_file(&staging_path).await;
+        return Err(format!("Failed to restore backup: {}", e).into());
+    }
+    Ok(())
+}
+
+// 创建备份 zip：包含数据库快照，include_recordings 为 true 时同时包含日期范围内的截图
+// dest 为目录时在其中生成文件名，返回备份文件路径
+#[tauri::command]
+pub async fn create_backup<R: Runtime>(
+    state: State<'_, AppState>,
+    app: AppHandle<R>,
+    dest: String,
+    include_recordings: bool,
+    start_date: Option<String>, // YYYY-MM-DD format
+    end_date: Option<String>,   // YYYY-MM-DD format
+) -> Result<String, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let start_date = start_date
+        .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid date format: {}", e)))?;
+    let end_date = end_date
+        .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid date format: {}", e)))?;
+
+    let suffix = Local::now().format("%Y%m%d_%H%M%S");
+    let mut zip_path = PathBuf::from(&dest);
+    if zip_path.is_dir() {
+        zip_path = zip_path.join(format!("clarity_backup_{}.zip", suffix));
+    }
+    if let Some(parent) = zip_path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    // VACUUM INTO 要求目标文件不存在
+    let db_snapshot = TempFile(std::env::temp_dir().join(format!("clarity_backup_{}.db", suffix)));
+    let _ = tokio::fs::remove_file(&db_snapshot.0).await;
+    db::backup_database(&state.db_pool, &db_snapshot.0)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let storage_path = state.storage_path.lock().await.clone();
+    let output = zip_path.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let recordings = if include_recordings {
+            collect_recordings(&storage_path, start_date, end_date)
+                .map_err(|e| format!("Failed to read recordings: {}", e))?
+        } else {
+            Vec::new()
+        };
+        log::info!(
+            "Creating backup {} with {} recording files",
+            output.display(),
+            recordings.len()
+        );
+        write_backup_zip(&output, &db_snapshot.0, &storage_path, &recordings, &app)
+    })
+    .await
+    .map_err(|e| format!("Backup task failed: {}", e))?;
+
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&zip_path).await;
+        return Err(e.into());
+    }
+
+    log::info!("Backup created: {}", zip_path.display());
+
+    Ok(zip_path.to_string_lossy().to_string())
+}
+
+// 从备份 zip 恢复：截图立即恢复，数据库在下次启动时替换（需要重启应用）
+// 当前数据库先保存一份副本到 clarity.db.pre-restore（只保留最近一次恢复前的数据库）
+#[tauri::command]
+pub async fn restore_backup(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    path: String,
+) -> Result<(), ClarityError> {
+    let zip_path = PathBuf::from(&path);
+    let storage_path = state.storage_path.lock().await.clone();
+
+    let suffix = Local::now().format("%Y%m%d_%H%M%S");
+    let extracted = TempFile(std::env::temp_dir().join(format!("clarity_restore_{}.db", suffix)));
+    let (archive, dest) = (zip_path.clone(), extracted.0.clone());
+    tokio::task::spawn_blocking(move || extract_backup_database(&archive, &dest))
+        .await
+        .map_err(|e| format!("Restore task failed: {}", e))??;
+
+    // VACUUM INTO 要求目标文件不存在
+    let pre_restore_path = db::pre_restore_path();
+    let _ = tokio::fs::remove_file(&pre_restore_path).await;
+    db::backup_database(&state.db_pool, &pre_restore_path)
+        .await
+        .map_err(ClarityError::from)?;
+    stage_restored_database(&extracted.0).await?;
+
+    let restored_files = tokio::task::spawn_blocking(move || {
+        extract_backup_recordings(&zip_path, &storage_path, &app)
+    })
+    .await
+    .map_err(|e| format!("Restore task failed: {}", e))??;
+
+    log::info!(
+        "Backup {} restored ({} files), database will be replaced on next start, previous database saved to {}",
+        path,
+        restored_files,
+        pre_restore_path.display()
+    );
+
+    Ok(())
+}
+
+// 获取数据库加密状态
+#[tauri::command]
+pub async fn get_database_encryption() -> Result<DatabaseEncryption, ClarityError> {
+    Ok(DatabaseEncryption {
+        available: cfg!(feature = "sqlcipher"),
+        enabled: keychain::load_database_key().await.is_some(),
+    })
+}
+
+// 把当前的明文数据库转换为 SQLCipher 加密数据库，密码保存在系统钥匙串中
+// 加密副本在下次启动时替换 clarity.db（需要重启应用）
+#[tauri::command]
+pub async fn encrypt_database(
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<(), ClarityError> {
+    if !cfg!(feature = "sqlcipher") {
+        return Err(ClarityError::Internal(
+            "This build does not support database encryption".to_string(),
+        ));
+    }
+    if passphrase.chars().count() < MIN_DATABASE_PASSPHRASE_CHARS {
+        return Err(ClarityError::InvalidInput(format!(
+            "Passphrase must be at least {} characters",
+            MIN_DATABASE_PASSPHRASE_CHARS
+        )));
+    }
+    if keychain::load_database_key().await.is_some() {
+        return Err(ClarityError::Conflict(
+            "Database is already encrypted".to_string(),
+        ));
+    }
+
+    // 待恢复的备份是按恢复时的密码状态准备的，加密后再替换会导致下次启动无法打开数据库
+    if tokio::fs::metadata(db::restore_staging_path())
+        .await
+        .is_ok()
+    {
+        return Err(ClarityError::Conflict(
+            "Restart the app to finish restoring the backup before encrypting the database"
+                .to_string(),
+        ));
+    }
+
+    let staging_path = db::encryption_staging_path();
+    let _ = tokio::fs::remove_file(&staging_path).await;
+    if let Err(e) = db::export_encrypted_copy(&state.db_pool, &staging_path, &passphrase).await {
+        let _ = tokio::fs::remove_file(&staging_path).await;
+        return Err(ClarityError::Database(format!(
+            "Failed to encrypt database: {}",
+            e
+        )));
+    }
+
+    // 密码保存失败时放弃加密副本，否则下次启动会无法打开数据库
+    if let Err(e) = keychain::save_database_key(&passphrase).await {
+        let _ = tokio::fs::remove_file(&staging_path).await;
+        return Err(e.into());
+    }
+
+    log::info!(
+        "Encrypted database copy created, it will replace the plaintext database on next start"
+    );
+
+    Ok(())
+}
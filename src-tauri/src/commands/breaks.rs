@@ -0,0 +1,268 @@
+use crate::commands::lock;
+use crate::db;
+use crate::error::ClarityError;
+use crate::locale;
+use crate::screenshot;
+use crate::settings;
+use crate::state::{AppState, RecordingState};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::time::Duration as StdDuration;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::watch;
+
+// 检查鼠标活动的间隔
+const BREAK_CHECK_SECONDS: u64 = 15;
+
+// 默认连续活动 50 分钟后提醒休息
+const DEFAULT_BREAK_REMINDER_MINUTES: u64 = 50;
+const MIN_BREAK_REMINDER_MINUTES: u64 = 10;
+const MAX_BREAK_REMINDER_MINUTES: u64 = 240;
+
+// 鼠标超过这么久没有移动视为开始休息，同时结束当前的连续活动
+const BREAK_IDLE_SECONDS: i64 = 5 * 60;
+
+// 提醒后多久内开始休息算作听从提醒
+const BREAK_COMPLIANCE_SECONDS: i64 = 10 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakReminderSettings {
+    pub enabled: bool,
+    pub minutes: u64, // 连续活动多少分钟后提醒
+}
+
+// 某一天的休息提醒执行情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakStats {
+    pub date: String, // YYYY-MM-DD
+    pub reminder_count: i64,
+    pub complied_count: i64,                // 提醒后及时休息的次数
+    pub average_delay_minutes: Option<f64>, // 从提醒到开始休息的平均时间
+}
+
+async fn load_break_reminder_settings(db_pool: &SqlitePool) -> BreakReminderSettings {
+    BreakReminderSettings {
+        enabled: settings::load_break_reminder_enabled_from_db(db_pool)
+            .await
+            .unwrap_or(false),
+        minutes: settings::load_break_reminder_minutes_from_db(db_pool)
+            .await
+            .unwrap_or(DEFAULT_BREAK_REMINDER_MINUTES),
+    }
+}
+
+// 根据鼠标活动跟踪连续活动时长，超过设置的时长后发送 "break-reminder" 事件（录制期间运行）
+pub async fn break_reminder_loop(
+    db_pool: SqlitePool,
+    mut recording: watch::Receiver<RecordingState>,
+    app_handle: Option<AppHandle>,
+) {
+    let mut interval = tokio::time::interval(StdDuration::from_secs(BREAK_CHECK_SECONDS));
+    let mut last_position: Option<(i32, i32)> = None;
+    let mut last_active = Local::now();
+    let mut session_start: Option<DateTime<Local>> = None;
+    let mut reminders_sent: u64 = 0;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            changed = recording.changed() => {
+                if changed.is_err()
+                    || *recording.borrow_and_update() == RecordingState::Stopped
+                {
+                    break;
+                }
+                continue;
+            }
+        }
+
+        if *recording.borrow() != RecordingState::Recording {
+            continue;
+        }
+
+        let reminder_settings = load_break_reminder_settings(&db_pool).await;
+        if !reminder_settings.enabled {
+            session_start = None;
+            continue;
+        }
+
+        // 获取不到鼠标位置时（例如部分 Wayland 环境）无法判断是否空闲，跳过
+        let Some(position) = tokio::task::spawn_blocking(screenshot::cursor_position)
+            .await
+            .ok()
+            .flatten()
+        else {
+            continue;
+        };
+
+        let now = Local::now();
+        if last_position != Some(position) {
+            last_position = Some(position);
+            last_active = now;
+            if session_start.is_none() {
+                session_start = Some(now);
+                reminders_sent = 0;
+            }
+        } else if let Some(start) = session_start {
+            // 空闲足够久，休息从最后一次活动时开始
+            if (now - last_active).num_seconds() >= BREAK_IDLE_SECONDS {
+                if let Err(e) = db::mark_break_taken(&db_pool, start, last_active).await {
+                    log::error!("Failed to record break: {}", e);
+                }
+                session_start = None;
+                continue;
+            }
+        }
+
+        let Some(start) = session_start else {
+            continue;
+        };
+        // 没有休息时每隔设置的时长再提醒一次
+        let active_minutes = (last_active - start).num_minutes().max(0) as u64;
+        if active_minutes < reminder_settings.minutes * (reminders_sent + 1) {
+            continue;
+        }
+
+        reminders_sent += 1;
+        log::info!(
+            "Continuous activity for {} minutes, sending break reminder",
+            active_minutes
+        );
+        if let Some(handle) = app_handle.as_ref() {
+            let _ = handle.emit("break-reminder", active_minutes);
+        }
+        if let Err(e) = db::insert_break_reminder(&db_pool, start, now).await {
+            log::error!("Failed to save break reminder: {}", e);
+        }
+    }
+}
+
+// 统计某一天的休息提醒执行情况
+pub async fn load_break_stats(
+    db_pool: &SqlitePool,
+    date: NaiveDate,
+) -> Result<BreakStats, ClarityError> {
+    let start_time = date
+        .and_hms_opt(0, 0, 0)
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid date".to_string()))?
+        .and_local_timezone(Local)
+        .single()
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid timezone conversion".to_string()))?;
+    let end_time = date
+        .and_hms_opt(23, 59, 59)
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid date".to_string()))?
+        .and_local_timezone(Local)
+        .single()
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid timezone conversion".to_string()))?;
+
+    let reminders = db::get_break_reminders(db_pool, start_time, end_time)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let delays: Vec<i64> = reminders
+        .iter()
+        .filter_map(|r| {
+            r.break_started_at
+                .map(|started| (started - r.reminded_at).num_seconds().max(0))
+        })
+        .collect();
+    let average_delay_minutes = if delays.is_empty() {
+        None
+    } else {
+        Some(delays.iter().sum::<i64>() as f64 / delays.len() as f64 / 60.0)
+    };
+
+    Ok(BreakStats {
+        date: date.format("%Y-%m-%d").to_string(),
+        reminder_count: reminders.len() as i64,
+        complied_count: delays
+            .iter()
+            .filter(|delay| **delay <= BREAK_COMPLIANCE_SECONDS)
+            .count() as i64,
+        average_delay_minutes,
+    })
+}
+
+// 把休息提醒执行情况整理为文字，附加到每日总结的提示词中
+pub fn format_break_stats(stats: &BreakStats, language: &str) -> String {
+    let delay = stats
+        .average_delay_minutes
+        .map(|minutes| format!("{:.0}", minutes));
+    if !locale::is_chinese(language) {
+        format!(
+            "- Break reminders: {}\n- Breaks taken within {} minutes of a reminder: {}\n- Average delay before taking a break: {} min",
+            stats.reminder_count,
+            BREAK_COMPLIANCE_SECONDS / 60,
+            stats.complied_count,
+            delay.unwrap_or_else(|| "-".to_string())
+        )
+    } else {
+        format!(
+            "- 休息提醒次数：{}\n- 提醒后 {} 分钟内休息的次数：{}\n- 从提醒到休息的平均时间：{} 分钟",
+            stats.reminder_count,
+            BREAK_COMPLIANCE_SECONDS / 60,
+            stats.complied_count,
+            delay.unwrap_or_else(|| "-".to_string())
+        )
+    }
+}
+
+// 获取休息提醒设置
+#[tauri::command]
+pub async fn get_break_reminder_settings(
+    state: State<'_, AppState>,
+) -> Result<BreakReminderSettings, ClarityError> {
+    Ok(load_break_reminder_settings(&state.db_pool).await)
+}
+
+// 检查休息提醒间隔（导入配置时也使用）
+pub(crate) fn validate_break_reminder_minutes(minutes: u64) -> Result<(), ClarityError> {
+    if !(MIN_BREAK_REMINDER_MINUTES..=MAX_BREAK_REMINDER_MINUTES).contains(&minutes) {
+        return Err(ClarityError::InvalidInput(format!(
+            "Break reminder interval must be between {} and {} minutes",
+            MIN_BREAK_REMINDER_MINUTES, MAX_BREAK_REMINDER_MINUTES
+        )));
+    }
+    Ok(())
+}
+
+// 设置休息提醒（正在录制时下一次检查生效）
+#[tauri::command]
+pub async fn set_break_reminder_settings(
+    state: State<'_, AppState>,
+    enabled: bool,
+    minutes: u64,
+) -> Result<(), ClarityError> {
+    validate_break_reminder_minutes(minutes)?;
+
+    settings::save_break_reminder_to_db(&state.db_pool, enabled, minutes)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "Break reminder settings updated: enabled={}, minutes={}",
+        enabled,
+        minutes
+    );
+
+    Ok(())
+}
+
+// 获取某一天的休息提醒执行情况
+#[tauri::command]
+pub async fn get_break_stats(
+    state: State<'_, AppState>,
+    date: Option<String>, // YYYY-MM-DD format, if None, use today
+) -> Result<BreakStats, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let date = match date {
+        Some(d) => NaiveDate::parse_from_str(&d, "%Y-%m-%d")
+            .map_err(|e| ClarityError::InvalidInput(format!("Invalid date format: {}", e)))?,
+        None => Local::now().date_naive(),
+    };
+
+    load_break_stats(&state.db_pool, date).await
+}
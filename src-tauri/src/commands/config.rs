@@ -0,0 +1,545 @@
+use crate::commands::api_server::{apply_api_server_settings, validate_api_server_port};
+use crate::commands::breaks::validate_break_reminder_minutes;
+use crate::commands::lock;
+use crate::commands::settings::{
+    load_settings, refresh_primary_gemini_key, validate_settings_update, Settings, SettingsUpdate,
+};
+use crate::error::ClarityError;
+use crate::keychain;
+use crate::redaction::{self, RedactionRule};
+use crate::settings;
+use crate::state::AppState;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tauri::{AppHandle, Emitter, State};
+
+// 配置文件格式版本，字段不兼容时增加
+const SETTINGS_FILE_VERSION: u32 = 1;
+
+// 加密 API key 的密码最短长度
+const MIN_EXPORT_PASSWORD_CHARS: usize = 8;
+
+// 不导出的设置：运行状态、只在本机有效的路径和令牌
+const NON_PORTABLE_SETTINGS: [&str; 15] = [
+    "recording_active",
+    "recording_session_started_at",
+    "clean_shutdown",
+    "auto_daily_summary_last_date",
+    "markdown_last_export_date",
+    "database_last_optimized_date",
+    "use_os_keychain",
+    "ffmpeg_path",
+    "markdown_export_dir",
+    "api_server_token",
+    "app_lock_pin_hash",
+    "incognito_mode",
+    "onboarding_step",
+    "screen_capture_consent",
+    "ai_upload_consent",
+];
+
+// API key 不放在明文的 settings 中，只在设置了密码时加密导出
+const SECRET_SETTINGS: [&str; 3] = ["gemini_api_key", "openai_api_key", "openrouter_api_key"];
+
+// 导出的配置文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsFile {
+    pub version: u32,
+    pub exported_at: DateTime<Local>,
+    pub settings: BTreeMap<String, String>, // settings 表中的 key / value（提示词、间隔、模型、定时任务等）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_keys: Option<EncryptedApiKeys>,
+}
+
+// 用密码加密的 API key（Argon2 派生密钥 + AES-256-GCM），字段都是 base64
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedApiKeys {
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiKeys {
+    gemini: Vec<ExportedGeminiKey>,
+    openai_api_key: String,
+    openrouter_api_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedGeminiKey {
+    api_key: String,
+    label: Option<String>,
+    priority: i64,
+}
+
+// 导入结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsImport {
+    pub settings: Settings,
+    pub imported_settings: usize,
+    pub imported_api_keys: usize,
+    pub skipped_api_keys: bool, // 文件中有 API key 但没有提供密码
+}
+
+fn is_portable(key: &str) -> bool {
+    !NON_PORTABLE_SETTINGS.contains(&key) && !SECRET_SETTINGS.contains(&key)
+}
+
+// 解析导入的设置值，没有此设置时返回 None
+fn parse_setting<T: FromStr>(
+    values: &BTreeMap<String, String>,
+    key: &str,
+) -> Result<Option<T>, ClarityError> {
+    values
+        .get(key)
+        .map(|value| {
+            value.trim().parse().map_err(|_| {
+                ClarityError::InvalidInput(format!("Invalid value for {}: {}", key, value))
+            })
+        })
+        .transpose()
+}
+
+// 导入的设置使用和修改设置时相同的校验，任何一项不合法时都不导入，返回规范化后的值
+async fn validate_imported_settings(
+    mut values: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, ClarityError> {
+    let update = validate_settings_update(SettingsUpdate {
+        language: values.get("language").cloned(),
+        summary_interval_seconds: parse_setting(&values, "summary_interval_seconds")?,
+        ai_provider: values.get("ai_provider").cloned(),
+        ai_model: values.get("ai_model").cloned(),
+        comparison_model: values.get("comparison_model").cloned(),
+        fallback_model: values.get("fallback_model").cloned(),
+        summary_input_mode: values.get("summary_input_mode").cloned(),
+        video_resolution: values.get("video_resolution").cloned(),
+        video_preset: values.get("video_preset").cloned(),
+        video_fps: parse_setting(&values, "video_fps")?,
+        video_codec: values.get("video_codec").cloned(),
+        capture_mode: values.get("capture_mode").cloned(),
+        capture_backend: values.get("capture_backend").cloned(),
+        cursor_overlay: parse_setting(&values, "cursor_overlay")?,
+        color_profile: values.get("color_profile").cloned(),
+        sensitive_screen_action: values.get("sensitive_screen_action").cloned(),
+        meeting_action: values.get("meeting_action").cloned(),
+        ffmpeg_path: None, // 只在本机有效，不导入
+        proxy_url: values.get("proxy_url").cloned(),
+        summarize_on_exit: parse_setting(&values, "summarize_on_exit")?,
+        resume_recording_on_launch: parse_setting(&values, "resume_recording_on_launch")?,
+        log_level: values.get("log_level").cloned(),
+    })
+    .await?;
+    let normalized = [
+        ("language", update.language),
+        ("ai_model", update.ai_model),
+        ("comparison_model", update.comparison_model),
+        ("fallback_model", update.fallback_model),
+        ("proxy_url", update.proxy_url),
+    ];
+    for (key, value) in normalized {
+        if let Some(value) = value {
+            values.insert(key.to_string(), value);
+        }
+    }
+
+    if let Some(minutes) = parse_setting(&values, "break_reminder_minutes")? {
+        validate_break_reminder_minutes(minutes)?;
+    }
+    parse_setting::<bool>(&values, "break_reminder_enabled")?;
+
+    parse_setting::<bool>(&values, "api_server_enabled")?;
+    if let Some(port) = parse_setting(&values, "api_server_port")? {
+        validate_api_server_port(port)?;
+    }
+
+    if let Some(value) = values.get("redaction_rules") {
+        let rules: Vec<RedactionRule> = serde_json::from_str(value).map_err(|e| {
+            ClarityError::InvalidInput(format!("Invalid value for redaction_rules: {}", e))
+        })?;
+        let rules = redaction::validate_rules(rules).map_err(ClarityError::InvalidInput)?;
+        let value = serde_json::to_string(&rules)
+            .map_err(|e| format!("Failed to serialize redaction rules: {}", e))?;
+        values.insert("redaction_rules".to_string(), value);
+    }
+
+    Ok(values)
+}
+
+// 参数不合法（例如文件中的 salt 太短）时 Argon2 失败，属于输入错误
+fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; 32], ClarityError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| {
+            ClarityError::InvalidInput(format!("Failed to derive encryption key: {}", e))
+        })?;
+    Ok(key)
+}
+
+fn encrypt_api_keys(api_keys: &ApiKeys, password: &str) -> Result<EncryptedApiKeys, ClarityError> {
+    let plaintext =
+        serde_json::to_vec(api_keys).map_err(|e| format!("Failed to serialize API keys: {}", e))?;
+
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(password, &salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_ref())
+        .map_err(|_| "Failed to encrypt API keys".to_string())?;
+
+    Ok(EncryptedApiKeys {
+        salt: general_purpose::STANDARD.encode(salt),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    })
+}
+
+fn decrypt_api_keys(encrypted: &EncryptedApiKeys, password: &str) -> Result<ApiKeys, ClarityError> {
+    let invalid = || ClarityError::InvalidInput("Invalid encrypted API keys".to_string());
+    let decode = |value: &str| {
+        general_purpose::STANDARD
+            .decode(value)
+            .map_err(|_| invalid())
+    };
+    let salt = decode(&encrypted.salt)?;
+    let nonce = decode(&encrypted.nonce)?;
+    let ciphertext = decode(&encrypted.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err(invalid());
+    }
+
+    let key = derive_key(password, &salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    // GCM 校验失败说明密码不对（或文件被修改）
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| ClarityError::InvalidInput("Incorrect password".to_string()))?;
+    serde_json::from_slice(&plaintext).map_err(|_| invalid())
+}
+
+async fn load_api_keys(state: &AppState) -> Result<ApiKeys, ClarityError> {
+    let gemini = keychain::load_gemini_api_keys(&state.db_pool)
+        .await?
+        .into_iter()
+        .map(|key| ExportedGeminiKey {
+            api_key: key.api_key,
+            label: key.label,
+            priority: key.priority,
+        })
+        .collect();
+    Ok(ApiKeys {
+        gemini,
+        openai_api_key: settings::load_openai_api_key_from_db(&state.db_pool)
+            .await
+            .unwrap_or_default(),
+        openrouter_api_key: settings::load_openrouter_api_key_from_db(&state.db_pool)
+            .await
+            .unwrap_or_default(),
+    })
+}
+
+// 保存导入的 API key：已经存在的 Gemini key 跳过，OpenAI / OpenRouter key 为空时保留本机的值
+async fn save_api_keys(state: &AppState, api_keys: ApiKeys) -> Result<usize, ClarityError> {
+    let existing = keychain::load_gemini_api_keys(&state.db_pool).await?;
+    let mut imported = 0;
+    for key in api_keys.gemini {
+        if existing.iter().any(|k| k.api_key == key.api_key) {
+            continue;
+        }
+        keychain::add_gemini_api_key(
+            &state.db_pool,
+            &key.api_key,
+            key.label.as_deref(),
+            key.priority,
+        )
+        .await?;
+        imported += 1;
+    }
+
+    let mut values = Vec::new();
+    if !api_keys.openai_api_key.is_empty() {
+        values.push(("openai_api_key".to_string(), api_keys.openai_api_key));
+    }
+    if !api_keys.openrouter_api_key.is_empty() {
+        values.push((
+            "openrouter_api_key".to_string(),
+            api_keys.openrouter_api_key,
+        ));
+    }
+    imported += values.len();
+    settings::save_settings_to_db(&state.db_pool, &values)
+        .await
+        .map_err(ClarityError::from)?;
+
+    Ok(imported)
+}
+
+// 导出配置到 JSON 文件，用于在其他电脑上使用相同的配置
+// 提供 password 时同时导出 API key（用密码加密），不提供时不包含任何 API key
+#[tauri::command]
+pub async fn export_settings(
+    state: State<'_, AppState>,
+    path: String,
+    password: Option<String>,
+) -> Result<String, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let password = password.filter(|p| !p.is_empty());
+    if let Some(password) = &password {
+        if password.chars().count() < MIN_EXPORT_PASSWORD_CHARS {
+            return Err(ClarityError::InvalidInput(format!(
+                "Password must be at least {} characters",
+                MIN_EXPORT_PASSWORD_CHARS
+            )));
+        }
+    }
+
+    let values = settings::load_all_settings_from_db(&state.db_pool)
+        .await
+        .map_err(ClarityError::from)?;
+    let api_keys = match &password {
+        Some(password) => Some(encrypt_api_keys(&load_api_keys(&state).await?, password)?),
+        None => None,
+    };
+    let file = SettingsFile {
+        version: SETTINGS_FILE_VERSION,
+        exported_at: Local::now(),
+        settings: values
+            .into_iter()
+            .filter(|(key, _)| is_portable(key))
+            .collect(),
+        api_keys,
+    };
+
+    let mut output = PathBuf::from(&path);
+    if output.is_dir() {
+        output = output.join(format!(
+            "clarity_settings_{}.json",
+            Local::now().format("%Y%m%d_%H%M%S")
+        ));
+    }
+    if let Some(parent) = output.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    tokio::fs::write(&output, content)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+
+    log::info!(
+        "Exported {} settings to {} (API keys: {})",
+        file.settings.len(),
+        output.display(),
+        file.api_keys.is_some()
+    );
+    Ok(output.to_string_lossy().to_string())
+}
+
+// 已经保存到数据库的导入结果
+struct SavedSettingsFile {
+    imported_settings: usize,
+    imported_api_keys: usize,
+    skipped_api_keys: bool,
+    api_server_changed: bool,
+}
+
+// 读取并保存配置文件中的设置和 API key，解密或校验失败时不修改任何设置
+async fn save_settings_file(
+    state: &AppState,
+    path: &str,
+    password: Option<String>,
+) -> Result<SavedSettingsFile, ClarityError> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let file: SettingsFile = serde_json::from_str(&content)
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid settings file: {}", e)))?;
+    if file.version > SETTINGS_FILE_VERSION {
+        return Err(ClarityError::InvalidInput(
+            "Settings file was created by a newer version of Clarity".to_string(),
+        ));
+    }
+
+    // 先解密，密码错误时不修改任何设置
+    let password = password.filter(|p| !p.is_empty());
+    let api_keys = match (&file.api_keys, &password) {
+        (Some(encrypted), Some(password)) => Some(decrypt_api_keys(encrypted, password)?),
+        _ => None,
+    };
+    let skipped_api_keys = file.api_keys.is_some() && api_keys.is_none();
+
+    let portable = file
+        .settings
+        .into_iter()
+        .filter(|(key, _)| is_portable(key))
+        .collect();
+    let values: Vec<(String, String)> = validate_imported_settings(portable)
+        .await?
+        .into_iter()
+        .collect();
+    let api_server_changed = values
+        .iter()
+        .any(|(key, _)| key == "api_server_enabled" || key == "api_server_port");
+    settings::save_settings_to_db(&state.db_pool, &values)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let imported_api_keys = match api_keys {
+        Some(api_keys) => save_api_keys(state, api_keys).await?,
+        None => 0,
+    };
+
+    Ok(SavedSettingsFile {
+        imported_settings: values.len(),
+        imported_api_keys,
+        skipped_api_keys,
+        api_server_changed,
+    })
+}
+
+// 从 JSON 文件导入配置，覆盖文件中包含的设置，其他设置保持不变
+// 文件中有加密的 API key 时需要提供导出时的密码，不提供时跳过 API key
+#[tauri::command]
+pub async fn import_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    password: Option<String>,
+) -> Result<SettingsImport, ClarityError> {
+    let saved = save_settings_file(&state, &path, password).await?;
+
+    state.reload_settings().await;
+    refresh_primary_gemini_key(&state).await?;
+    // 和修改本地 REST API 设置一样立即启动或停止服务
+    if saved.api_server_changed {
+        apply_api_server_settings(&app, &state).await?;
+    }
+
+    let current = load_settings(&state).await;
+    log::info!(
+        "Imported {} settings and {} API keys from {}",
+        saved.imported_settings,
+        saved.imported_api_keys,
+        path
+    );
+    if let Err(e) = app.emit("settings-changed", &current) {
+        log::warn!("Failed to emit settings-changed: {}", e);
+    }
+
+    Ok(SettingsImport {
+        settings: current,
+        imported_settings: saved.imported_settings,
+        imported_api_keys: saved.imported_api_keys,
+        skipped_api_keys: saved.skipped_api_keys,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn api_keys() -> ApiKeys {
+        ApiKeys {
+            gemini: vec![ExportedGeminiKey {
+                api_key: "gemini-key".to_string(),
+                label: Some("Work".to_string()),
+                priority: 1,
+            }],
+            openai_api_key: "openai-key".to_string(),
+            openrouter_api_key: String::new(),
+        }
+    }
+
+    #[test]
+    fn encrypted_api_keys_round_trip() {
+        let encrypted = encrypt_api_keys(&api_keys(), "correct horse").unwrap();
+        let decrypted = decrypt_api_keys(&encrypted, "correct horse").unwrap();
+        assert_eq!(
+            serde_json::to_value(decrypted).unwrap(),
+            serde_json::to_value(api_keys()).unwrap()
+        );
+    }
+
+    // 文件中的 salt 被改短时 Argon2 无法派生密钥，应该是输入错误
+    #[test]
+    fn short_salt_is_invalid_input() {
+        assert!(matches!(
+            derive_key("correct horse", &[0u8; 4]),
+            Err(ClarityError::InvalidInput(_))
+        ));
+        let mut encrypted = encrypt_api_keys(&api_keys(), "correct horse").unwrap();
+        encrypted.salt = general_purpose::STANDARD.encode([0u8; 4]);
+        assert!(matches!(
+            decrypt_api_keys(&encrypted, "correct horse"),
+            Err(ClarityError::InvalidInput(_))
+        ));
+    }
+
+    // GCM 校验失败说明密码不对，属于输入错误
+    #[test]
+    fn wrong_password_is_invalid_input() {
+        let encrypted = encrypt_api_keys(&api_keys(), "correct horse").unwrap();
+        assert!(matches!(
+            decrypt_api_keys(&encrypted, "wrong password"),
+            Err(ClarityError::InvalidInput(_))
+        ));
+    }
+
+    // 密码错误时返回 InvalidInput，文件中的设置也不导入
+    #[tokio::test]
+    async fn wrong_password_changes_no_settings() {
+        let state = AppState::ephemeral().await.unwrap();
+        let before = settings::load_all_settings_from_db(&state.db_pool)
+            .await
+            .unwrap();
+
+        let file = SettingsFile {
+            version: SETTINGS_FILE_VERSION,
+            exported_at: Local::now(),
+            settings: BTreeMap::from([("summary_interval_seconds".to_string(), "900".to_string())]),
+            api_keys: Some(encrypt_api_keys(&api_keys(), "correct horse").unwrap()),
+        };
+        let path =
+            std::env::temp_dir().join(format!("clarity_settings_test_{}.json", std::process::id()));
+        std::fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+
+        let result = save_settings_file(
+            &state,
+            &path.to_string_lossy(),
+            Some("wrong password".to_string()),
+        )
+        .await;
+        let _ = std::fs::remove_file(&path);
+
+        assert!(matches!(result, Err(ClarityError::InvalidInput(_))));
+        assert_eq!(
+            settings::load_all_settings_from_db(&state.db_pool)
+                .await
+                .unwrap(),
+            before
+        );
+        assert!(db::get_gemini_api_keys(&state.db_pool)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,17 +1,37 @@
+use crate::commands::{goals, lock, webhooks};
 use crate::db;
+use crate::error::ClarityError;
+use crate::events;
 use crate::state::AppState;
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 use std::path::PathBuf;
 use tauri::State;
 use base64::{Engine as _, engine::general_purpose};
 
+// 摘要详情：摘要本身（包括标签）以及相关的截图、视频、API 请求和分类
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryDetail {
+    pub summary: db::Summary,
+    pub traces: Vec<db::ScreenshotTrace>,
+    pub videos: Vec<db::Video>,
+    pub api_requests: Vec<db::ApiRequest>, // 摘要时间段结束到摘要保存之间的请求
+    pub categories: Vec<String>,
+}
+
+// 详情中最多返回的 API 请求数量
+const SUMMARY_DETAIL_API_REQUEST_LIMIT: i64 = 20;
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TodayStatistics {
     pub screenshot_count: i64,
     pub summary_count: i64,
     pub api_statistics: db::ApiStatistics,
+    pub category_totals: Vec<db::CategoryTotal>,
+    pub recording_gaps: Vec<db::RecordingGap>, // 录制期间程序异常退出造成的中断和会议时间段
 }
 
 // 查询截图记录
@@ -21,22 +41,144 @@ pub async fn get_traces(
     start_time: Option<String>,
     end_time: Option<String>,
     limit: Option<i64>,
-) -> Result<Vec<db::ScreenshotTrace>, String> {
+) -> Result<Vec<db::ScreenshotTrace>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
     let start_dt = start_time
         .map(|s| DateTime::parse_from_rfc3339(&s))
         .transpose()
-        .map_err(|e| format!("Invalid start_time format: {}", e))?
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
         .map(|dt| dt.with_timezone(&Local));
 
     let end_dt = end_time
         .map(|s| DateTime::parse_from_rfc3339(&s))
         .transpose()
-        .map_err(|e| format!("Invalid end_time format: {}", e))?
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
         .map(|dt| dt.with_timezone(&Local));
 
     db::get_screenshot_traces(&state.db_pool, start_dt, end_dt, limit)
         .await
-        .map_err(|e| format!("Database error: {}", e))
+        .map_err(ClarityError::from)
+}
+
+// 删除截图记录的结果（dry_run 时只统计，不删除）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteTracesResult {
+    pub dry_run: bool,
+    pub trace_count: u64,
+    pub file_count: u64,      // 截图和缩略图文件数量
+    pub reclaimed_bytes: u64, // 释放（或可释放）的磁盘空间
+    pub failed_files: Vec<String>,
+}
+
+// 删除截图记录以及对应的截图和缩略图文件
+// 先在事务中删除数据库记录，成功后再删除文件；文件删除失败时记录在 failed_files 中
+pub async fn remove_traces(
+    db_pool: &SqlitePool,
+    traces: &[db::ScreenshotTrace],
+    dry_run: bool,
+) -> Result<DeleteTracesResult, ClarityError> {
+    let mut paths: Vec<&str> = traces
+        .iter()
+        .flat_map(|t| std::iter::once(t.file_path.as_str()).chain(t.thumbnail_path.as_deref()))
+        .collect();
+    paths.sort_unstable();
+    paths.dedup();
+
+    // 按实际存在的文件计算大小
+    let mut existing = Vec::new();
+    let mut reclaimed_bytes = 0;
+    for path in paths {
+        if let Ok(metadata) = tokio::fs::metadata(path).await {
+            reclaimed_bytes += metadata.len();
+            existing.push(path);
+        }
+    }
+
+    let mut result = DeleteTracesResult {
+        dry_run,
+        trace_count: traces.len() as u64,
+        file_count: existing.len() as u64,
+        reclaimed_bytes,
+        failed_files: Vec::new(),
+    };
+    if dry_run || traces.is_empty() {
+        return Ok(result);
+    }
+
+    let ids: Vec<i64> = traces.iter().map(|t| t.id).collect();
+    result.trace_count = db::delete_screenshot_traces(db_pool, &ids)
+        .await
+        .map_err(ClarityError::from)?;
+
+    for path in existing {
+        match tokio::fs::remove_file(path).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                log::warn!("Failed to delete screenshot file {}: {}", path, e);
+                result.failed_files.push(path.to_string());
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+// 按 ID 或时间范围删除截图记录和文件（两者只能选一种），dry_run 为 true 时只返回可释放的空间
+#[tauri::command]
+pub async fn delete_traces(
+    state: State<'_, AppState>,
+    ids: Option<Vec<i64>>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+    dry_run: bool,
+) -> Result<DeleteTracesResult, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let start_dt = start_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    let end_dt = end_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    let has_range = start_dt.is_some() || end_dt.is_some();
+    let traces = match ids {
+        Some(_) if has_range => {
+            return Err(ClarityError::InvalidInput(
+                "Specify either ids or a time range, not both".to_string(),
+            ))
+        }
+        Some(ids) => db::get_screenshot_traces_by_ids(&state.db_pool, &ids).await,
+        // 防止误删全部截图，时间范围至少需要一端
+        None if has_range => {
+            db::get_screenshot_traces(&state.db_pool, start_dt, end_dt, None).await
+        }
+        None => {
+            return Err(ClarityError::InvalidInput(
+                "Either ids or a time range is required".to_string(),
+            ))
+        }
+    }
+    .map_err(ClarityError::from)?;
+
+    let result = remove_traces(&state.db_pool, &traces, dry_run).await?;
+
+    if !dry_run {
+        log::info!(
+            "Deleted {} screenshot traces, {} files, {} bytes reclaimed",
+            result.trace_count,
+            result.file_count - result.failed_files.len() as u64,
+            result.reclaimed_bytes
+        );
+    }
+
+    Ok(result)
 }
 
 // 查询摘要
@@ -45,23 +187,31 @@ pub async fn get_summaries(
     state: State<'_, AppState>,
     start_time: Option<String>,
     end_time: Option<String>,
+    tags: Option<Vec<String>>, // 只返回带有其中任一标签的摘要
     limit: Option<i64>,
-) -> Result<Vec<db::Summary>, String> {
+) -> Result<Vec<db::Summary>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
     let start_dt = start_time
         .map(|s| DateTime::parse_from_rfc3339(&s))
         .transpose()
-        .map_err(|e| format!("Invalid start_time format: {}", e))?
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
         .map(|dt| dt.with_timezone(&Local));
 
     let end_dt = end_time
         .map(|s| DateTime::parse_from_rfc3339(&s))
         .transpose()
-        .map_err(|e| format!("Invalid end_time format: {}", e))?
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
         .map(|dt| dt.with_timezone(&Local));
 
-    db::get_summaries(&state.db_pool, start_dt, end_dt, limit)
-        .await
-        .map_err(|e| format!("Database error: {}", e))
+    db::get_summaries_with_tags(
+        &state.db_pool,
+        start_dt,
+        end_dt,
+        &tags.unwrap_or_default(),
+        limit,
+    )
+    .await
+    .map_err(ClarityError::from)
 }
 
 // 添加摘要
@@ -72,26 +222,164 @@ pub async fn add_summary(
     end_time: String,
     content: String,
     screenshot_count: i32,
-) -> Result<i64, String> {
+) -> Result<i64, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
     let start_dt = DateTime::parse_from_rfc3339(&start_time)
-        .map_err(|e| format!("Invalid start_time format: {}", e))?
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
         .with_timezone(&Local);
 
     let end_dt = DateTime::parse_from_rfc3339(&end_time)
-        .map_err(|e| format!("Invalid end_time format: {}", e))?
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
         .with_timezone(&Local);
 
-    db::insert_summary(&state.db_pool, start_dt, end_dt, content, screenshot_count)
+    let id = db::insert_summary(
+        &state.db_pool,
+        start_dt,
+        end_dt,
+        content.clone(),
+        screenshot_count,
+    )
+    .await
+    .map_err(ClarityError::from)?;
+
+    state
+        .emit(
+            events::SUMMARY_CREATED,
+            events::SummaryEvent::new(id, start_dt, end_dt, &content),
+        )
+        .await;
+    webhooks::summary_created(&state.db_pool, id);
+    Ok(id)
+}
+
+// 一次获取摘要详情，详情页不需要分别查询截图、视频、API 请求和分类
+#[tauri::command]
+pub async fn get_summary_by_id(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<SummaryDetail, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let summary = db::get_summary(&state.db_pool, id)
+        .await
+        .map_err(ClarityError::from)?
+        .ok_or_else(|| ClarityError::NotFound(format!("Summary {} not found", id)))?;
+
+    let traces = db::get_screenshot_traces(
+        &state.db_pool,
+        Some(summary.start_time),
+        Some(summary.end_time),
+        None,
+    )
+    .await
+    .map_err(ClarityError::from)?;
+
+    let videos = db::get_summary_videos(&state.db_pool, Some(id), None)
+        .await
+        .map_err(ClarityError::from)?;
+
+    // API 请求没有关联摘要 ID，按生成摘要的时间段查找
+    let api_requests = db::get_api_requests(
+        &state.db_pool,
+        Some(summary.end_time),
+        Some(summary.created_at),
+        None,
+        None,
+        SUMMARY_DETAIL_API_REQUEST_LIMIT,
+        0,
+    )
+    .await
+    .map_err(ClarityError::from)?
+    .requests;
+
+    let categories = db::get_summary_categories(&state.db_pool, id)
         .await
-        .map_err(|e| format!("Database error: {}", e))
+        .map_err(ClarityError::from)?;
+
+    Ok(SummaryDetail {
+        summary,
+        traces,
+        videos,
+        api_requests,
+        categories,
+    })
+}
+
+// 修改摘要内容（例如纠正模型生成的错误内容），当天的每日总结会标记为需要重新生成
+#[tauri::command]
+pub async fn update_summary(
+    state: State<'_, AppState>,
+    id: i64,
+    content: String,
+) -> Result<(), ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let content = content.trim();
+    if content.is_empty() {
+        return Err(ClarityError::InvalidInput(
+            "Summary content cannot be empty".to_string(),
+        ));
+    }
+
+    let updated = db::edit_summary_content(&state.db_pool, id, content)
+        .await
+        .map_err(ClarityError::from)?;
+    if !updated {
+        return Err(ClarityError::NotFound(format!("Summary {} not found", id)));
+    }
+
+    log::info!("Summary {} updated", id);
+    if let Ok(Some(summary)) = db::get_summary(&state.db_pool, id).await {
+        state
+            .emit(
+                events::SUMMARY_UPDATED,
+                events::SummaryEvent::new(id, summary.start_time, summary.end_time, content),
+            )
+            .await;
+    }
+    Ok(())
+}
+
+// 删除摘要及其总结视频，当天的每日总结会标记为需要重新生成
+#[tauri::command]
+pub async fn delete_summary(state: State<'_, AppState>, id: i64) -> Result<(), ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let videos = db::get_summary_videos(&state.db_pool, Some(id), None)
+        .await
+        .map_err(ClarityError::from)?;
+    for video in videos {
+        match tokio::fs::remove_file(&video.file_path).await {
+            Ok(_) => {}
+            // 文件已经不存在，直接标记为已删除
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                log::warn!("Failed to delete video {}: {}", video.file_path, e);
+                continue;
+            }
+        }
+        db::mark_video_deleted(&state.db_pool, video.id)
+            .await
+            .map_err(ClarityError::from)?;
+    }
+
+    let deleted = db::delete_summary(&state.db_pool, id)
+        .await
+        .map_err(ClarityError::from)?;
+    if !deleted {
+        return Err(ClarityError::NotFound(format!("Summary {} not found", id)));
+    }
+
+    log::info!("Summary {} deleted", id);
+    state
+        .emit(events::SUMMARY_DELETED, events::SummaryDeleted { id })
+        .await;
+    Ok(())
 }
 
 // 获取今天的截图数量
 #[tauri::command]
-pub async fn get_today_count(state: State<'_, AppState>) -> Result<i64, String> {
+pub async fn get_today_count(state: State<'_, AppState>) -> Result<i64, ClarityError> {
     db::get_today_screenshot_count(&state.db_pool)
         .await
-        .map_err(|e| format!("Database error: {}", e))
+        .map_err(ClarityError::from)
 }
 
 // 获取 API 统计信息
@@ -100,11 +388,13 @@ pub async fn get_api_statistics(
     state: State<'_, AppState>,
     start_time: Option<String>,
     end_time: Option<String>,
-) -> Result<db::ApiStatistics, String> {
+) -> Result<db::ApiStatistics, ClarityError> {
     let start_dt = if let Some(st) = start_time {
         Some(
             DateTime::parse_from_rfc3339(&st)
-                .map_err(|e| format!("Invalid start_time format: {}", e))?
+                .map_err(|e| {
+                    ClarityError::InvalidInput(format!("Invalid start_time format: {}", e))
+                })?
                 .with_timezone(&Local),
         )
     } else {
@@ -114,7 +404,7 @@ pub async fn get_api_statistics(
     let end_dt = if let Some(et) = end_time {
         Some(
             DateTime::parse_from_rfc3339(&et)
-                .map_err(|e| format!("Invalid end_time format: {}", e))?
+                .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
                 .with_timezone(&Local),
         )
     } else {
@@ -123,12 +413,61 @@ pub async fn get_api_statistics(
 
     db::get_api_statistics(&state.db_pool, start_dt, end_dt)
         .await
-        .map_err(|e| format!("Database error: {}", e))
+        .map_err(ClarityError::from)
+}
+
+// 每页最多返回的 API 请求记录数量
+const MAX_API_REQUESTS_PAGE_SIZE: i64 = 500;
+
+// 分页查询 API 请求记录（包括错误信息），用于排查总结失败的原因
+#[tauri::command]
+pub async fn get_api_requests(
+    state: State<'_, AppState>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+    success: Option<bool>, // 只返回成功或失败的请求
+    model: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<db::ApiRequestPage, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let start_dt = start_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    let end_dt = end_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    let model = model
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty());
+    let limit = limit.unwrap_or(50).clamp(1, MAX_API_REQUESTS_PAGE_SIZE);
+    let offset = offset.unwrap_or(0).max(0);
+
+    db::get_api_requests(
+        &state.db_pool,
+        start_dt,
+        end_dt,
+        success,
+        model.as_deref(),
+        limit,
+        offset,
+    )
+    .await
+    .map_err(ClarityError::from)
 }
 
 // 获取今天的统计概览
 #[tauri::command]
-pub async fn get_today_statistics(state: State<'_, AppState>) -> Result<TodayStatistics, String> {
+pub async fn get_today_statistics(
+    state: State<'_, AppState>,
+) -> Result<TodayStatistics, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
     let today_start = Local::now().date_naive().and_hms_opt(0, 0, 0).unwrap();
     let today_start_dt = today_start.and_local_timezone(Local).unwrap();
     let today_end_dt = Local::now();
@@ -142,23 +481,30 @@ pub async fn get_today_statistics(state: State<'_, AppState>) -> Result<TodaySta
     // 获取截图数量
     let screenshot_count = db::get_today_screenshot_count(&state.db_pool)
         .await
-        .map_err(|e| format!("Database error: {}", e))?;
+        .map_err(ClarityError::from)?;
 
     // 获取总结数量
-    let summaries = db::get_summaries(
-        &state.db_pool,
-        Some(today_start_dt),
-        Some(today_end_dt),
-        None,
-    )
-    .await
-    .map_err(|e| format!("Database error: {}", e))?;
+    let summary_count =
+        db::get_summary_count(&state.db_pool, Some(today_start_dt), Some(today_end_dt))
+            .await
+            .map_err(ClarityError::from)?;
 
     // 获取 API 统计
     let api_stats =
         db::get_api_statistics(&state.db_pool, Some(today_start_dt), Some(today_end_dt))
             .await
-            .map_err(|e| format!("Database error: {}", e))?;
+            .map_err(ClarityError::from)?;
+
+    // 获取各分类的时长
+    let category_totals =
+        db::get_category_totals(&state.db_pool, Some(today_start_dt), Some(today_end_dt))
+            .await
+            .map_err(ClarityError::from)?;
+
+    // 获取录制中断
+    let recording_gaps = db::get_recording_gaps(&state.db_pool, today_start_dt, today_end_dt)
+        .await
+        .map_err(ClarityError::from)?;
 
     log::info!(
         "API statistics: total_requests={}, successful={}, failed={}, tokens={}",
@@ -170,21 +516,146 @@ pub async fn get_today_statistics(state: State<'_, AppState>) -> Result<TodaySta
 
     Ok(TodayStatistics {
         screenshot_count,
-        summary_count: summaries.len() as i64,
+        summary_count,
         api_statistics: api_stats,
+        category_totals,
+        recording_gaps,
     })
 }
 
+// 按分类统计时间范围内的摘要时长
+#[tauri::command]
+pub async fn get_category_totals(
+    state: State<'_, AppState>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+) -> Result<Vec<db::CategoryTotal>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let start_dt = start_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    let end_dt = end_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    db::get_category_totals(&state.db_pool, start_dt, end_dt)
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 按星期和小时统计截图数量和专注时长（用于活动热力图）
+#[tauri::command]
+pub async fn get_activity_heatmap(
+    state: State<'_, AppState>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+) -> Result<Vec<db::ActivityHeatmapCell>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let start_dt = start_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    let end_dt = end_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    db::get_activity_heatmap(
+        &state.db_pool,
+        start_dt,
+        end_dt,
+        goals::FOCUSED_SCORE_THRESHOLD,
+    )
+    .await
+    .map_err(ClarityError::from)
+}
+
+// 获取连续记录天数、记录时长和专注度的移动平均以及周环比
+#[tauri::command]
+pub async fn get_streaks_and_trends(
+    state: State<'_, AppState>,
+    days: Option<i64>, // 返回最近多少天的趋势数据，默认 30 天
+) -> Result<db::StreaksAndTrends, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let days = days.unwrap_or(30).clamp(1, 365);
+
+    db::get_streaks_and_trends(&state.db_pool, Local::now().date_naive(), days)
+        .await
+        .map_err(ClarityError::from)
+}
+
+fn validate_date(date: &Option<String>) -> Result<(), String> {
+    if let Some(d) = date {
+        NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date format: {}", e))?;
+    }
+    Ok(())
+}
+
+// 查询日期范围内每个应用每天的使用时长（根据截图时获得焦点的窗口统计）
+#[tauri::command]
+pub async fn get_app_usage(
+    state: State<'_, AppState>,
+    start_date: Option<String>, // YYYY-MM-DD format
+    end_date: Option<String>,   // YYYY-MM-DD format
+) -> Result<Vec<db::AppUsage>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    validate_date(&start_date).map_err(ClarityError::InvalidInput)?;
+    validate_date(&end_date).map_err(ClarityError::InvalidInput)?;
+
+    db::get_app_usage(&state.db_pool, start_date.as_deref(), end_date.as_deref())
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 查询日期范围内使用时长最多的应用
+#[tauri::command]
+pub async fn get_top_apps(
+    state: State<'_, AppState>,
+    start_date: Option<String>, // YYYY-MM-DD format
+    end_date: Option<String>,   // YYYY-MM-DD format
+    limit: Option<i64>,
+) -> Result<Vec<db::AppUsageTotal>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    validate_date(&start_date).map_err(ClarityError::InvalidInput)?;
+    validate_date(&end_date).map_err(ClarityError::InvalidInput)?;
+
+    db::get_top_apps(
+        &state.db_pool,
+        start_date.as_deref(),
+        end_date.as_deref(),
+        limit,
+    )
+    .await
+    .map_err(ClarityError::from)
+}
+
 // 读取截图文件并返回 base64
 #[tauri::command]
-pub async fn read_screenshot_file(file_path: String) -> Result<String, String> {
+pub async fn read_screenshot_file(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<String, ClarityError> {
     use tokio::fs;
 
+    lock::ensure_unlocked(&state).await?;
+
     let path = PathBuf::from(&file_path);
     
     // 检查文件是否存在
     if !path.exists() {
-        return Err(format!("File not found: {}", file_path));
+        return Err(ClarityError::NotFound(format!(
+            "File not found: {}",
+            file_path
+        )));
     }
 
     // 读取文件内容
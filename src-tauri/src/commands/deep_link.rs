@@ -0,0 +1,13 @@
+use crate::deep_link::DeepLinkTarget;
+use crate::error::ClarityError;
+use crate::state::AppState;
+use tauri::State;
+
+// 读取并清除还没有处理的 clarity:// 链接
+// 前端启动时调用一次（处理通过链接启动的情况），之后每次收到 deep-link-opened 事件时调用
+#[tauri::command]
+pub async fn take_deep_link(
+    state: State<'_, AppState>,
+) -> Result<Option<DeepLinkTarget>, ClarityError> {
+    Ok(state.pending_deep_link.lock().await.take())
+}
@@ -0,0 +1,993 @@
+use crate::commands::goals::day_range;
+use crate::commands::lock;
+use crate::commands::summary::structured_categories;
+use crate::db;
+use crate::deep_link;
+use crate::error::ClarityError;
+use crate::screenshot;
+use crate::settings;
+use crate::state::AppState;
+use crate::video_summary;
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+use std::time::Duration as StdDuration;
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+// 导出分辨率对应的视频尺寸
+fn export_dimensions(resolution: &str) -> Result<(u32, u32), String> {
+    match resolution {
+        "360p" => Ok((640, 360)),
+        "540p" => Ok((960, 540)),
+        "720p" => Ok((1280, 720)),
+        "1080p" => Ok((1920, 1080)),
+        _ => Err("Resolution must be '360p', '540p', '720p' or '1080p'".to_string()),
+    }
+}
+
+// 导出某一天的延时视频
+#[tauri::command]
+pub async fn export_timelapse(
+    state: State<'_, AppState>,
+    date: Option<String>,       // YYYY-MM-DD format, if None, use today
+    speed: Option<u32>,         // 每秒播放多少张截图（截图为 1fps，即加速倍数）
+    resolution: Option<String>, // "360p", "540p", "720p" or "1080p"
+) -> Result<String, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let target_date = if let Some(d) = date {
+        d
+    } else {
+        let today = Local::now().date_naive();
+        today.format("%Y-%m-%d").to_string()
+    };
+
+    let speed = speed.unwrap_or(30);
+    if speed == 0 || speed > 120 {
+        return Err(ClarityError::InvalidInput(
+            "Speed must be between 1 and 120".to_string(),
+        ));
+    }
+
+    let (width, height) = export_dimensions(resolution.as_deref().unwrap_or("720p"))
+        .map_err(ClarityError::InvalidInput)?;
+
+    // 解析日期
+    let date_naive = NaiveDate::parse_from_str(&target_date, "%Y-%m-%d")
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid date format: {}", e)))?;
+
+    // 计算当天的开始和结束时间
+    let start_time = date_naive
+        .and_hms_opt(0, 0, 0)
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid date".to_string()))?
+        .and_local_timezone(Local)
+        .single()
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid timezone conversion".to_string()))?;
+
+    let end_time = date_naive
+        .and_hms_opt(23, 59, 59)
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid date".to_string()))?
+        .and_local_timezone(Local)
+        .single()
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid timezone conversion".to_string()))?;
+
+    let mut traces =
+        db::get_screenshot_traces(&state.db_pool, Some(start_time), Some(end_time), None)
+            .await
+            .map_err(ClarityError::from)?;
+
+    if traces.is_empty() {
+        return Err(ClarityError::NotFound(format!(
+            "No screenshots recorded on {}",
+            target_date
+        )));
+    }
+
+    // traces 按时间倒序返回，延时视频需要按时间正序
+    traces.sort_by_key(|t| t.timestamp);
+
+    let image_paths: Vec<PathBuf> = traces.iter().map(|t| PathBuf::from(&t.file_path)).collect();
+
+    let storage_path = state.storage_path.lock().await.clone();
+    let output_path = storage_path.join("exports").join(format!(
+        "timelapse_{}_{}.mp4",
+        target_date,
+        Local::now().format("%H%M%S")
+    ));
+
+    if let Some(parent) = output_path.parent() {
+        screenshot::ensure_dir_exists(parent).await?;
+    }
+
+    let custom_ffmpeg = state.ffmpeg_path.lock().await.clone();
+    let ffmpeg_path = video_summary::find_ffmpeg(custom_ffmpeg.as_deref())
+        .await
+        .map_err(ClarityError::FfmpegMissing)?;
+
+    log::info!(
+        "Exporting timelapse for {} ({} frames, {}x speed, {}x{})",
+        target_date,
+        image_paths.len(),
+        speed,
+        width,
+        height
+    );
+
+    video_summary::encode_video(
+        &ffmpeg_path,
+        &image_paths,
+        &output_path,
+        speed,
+        width,
+        height,
+        "h264",
+    )
+    .await?;
+
+    log::info!("Timelapse exported to: {}", output_path.display());
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+// 导出片段最长时长（秒），避免误选整天生成超大文件
+const MAX_CLIP_SECONDS: i64 = 3600;
+
+// 导出指定时间段的 GIF 或短视频
+#[tauri::command]
+pub async fn export_clip(
+    state: State<'_, AppState>,
+    start_time: String,
+    end_time: String,
+    format: Option<String>, // "gif" or "mp4"
+) -> Result<String, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let format = format.unwrap_or_else(|| "gif".to_string());
+    if format != "gif" && format != "mp4" {
+        return Err(ClarityError::InvalidInput(
+            "Format must be 'gif' or 'mp4'".to_string(),
+        ));
+    }
+
+    let start_dt = DateTime::parse_from_rfc3339(&start_time)
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
+        .with_timezone(&Local);
+
+    let end_dt = DateTime::parse_from_rfc3339(&end_time)
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
+        .with_timezone(&Local);
+
+    if end_dt <= start_dt {
+        return Err(ClarityError::InvalidInput(
+            "end_time must be after start_time".to_string(),
+        ));
+    }
+
+    if (end_dt - start_dt).num_seconds() > MAX_CLIP_SECONDS {
+        return Err(ClarityError::InvalidInput(format!(
+            "Clip must be at most {} seconds long",
+            MAX_CLIP_SECONDS
+        )));
+    }
+
+    let mut traces = db::get_screenshot_traces(&state.db_pool, Some(start_dt), Some(end_dt), None)
+        .await
+        .map_err(ClarityError::from)?;
+
+    if traces.is_empty() {
+        return Err(ClarityError::NotFound(
+            "No screenshots in the selected time range".to_string(),
+        ));
+    }
+
+    // traces 按时间倒序返回，导出需要按时间正序
+    traces.sort_by_key(|t| t.timestamp);
+
+    let image_paths: Vec<PathBuf> = traces.iter().map(|t| PathBuf::from(&t.file_path)).collect();
+
+    let storage_path = state.storage_path.lock().await.clone();
+    let output_path = storage_path.join("exports").join(format!(
+        "clip_{}.{}",
+        start_dt.format("%Y%m%d_%H%M%S"),
+        format
+    ));
+
+    if let Some(parent) = output_path.parent() {
+        screenshot::ensure_dir_exists(parent).await?;
+    }
+
+    let custom_ffmpeg = state.ffmpeg_path.lock().await.clone();
+    let ffmpeg_path = video_summary::find_ffmpeg(custom_ffmpeg.as_deref())
+        .await
+        .map_err(ClarityError::FfmpegMissing)?;
+
+    log::info!(
+        "Exporting {} clip from {} to {} ({} frames)",
+        format,
+        start_dt.to_rfc3339(),
+        end_dt.to_rfc3339(),
+        image_paths.len()
+    );
+
+    // 片段以 10 倍速播放，便于快速分享
+    if format == "gif" {
+        video_summary::encode_gif(&ffmpeg_path, &image_paths, &output_path, 10, 640).await?;
+    } else {
+        video_summary::encode_video(
+            &ffmpeg_path,
+            &image_paths,
+            &output_path,
+            10,
+            1280,
+            720,
+            "h264",
+        )
+        .await?;
+    }
+
+    log::info!("Clip exported to: {}", output_path.display());
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+// 可导出的数据类型
+const EXPORT_KINDS: [&str; 4] = ["traces", "summaries", "daily_summaries", "api_stats"];
+
+// 每导出这么多条记录发送一次进度
+const EXPORT_PROGRESS_INTERVAL: usize = 500;
+
+// 数据导出进度（"export-progress" 事件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProgress {
+    pub kind: String,
+    pub exported: usize,
+    pub total: usize,
+}
+
+// CSV 字段：包含逗号、引号或换行时用引号包裹
+fn csv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+// 把记录写入 JSON 数组或 CSV 文件（CSV 表头取第一条记录的字段），过程中发送进度
+async fn write_records<R: Runtime>(
+    path: &PathBuf,
+    kind: &str,
+    format: &str,
+    records: Vec<Value>,
+    app_handle: &AppHandle<R>,
+) -> Result<(), String> {
+    let file = tokio::fs::File::create(path)
+        .await
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut writer = BufWriter::new(file);
+    let total = records.len();
+
+    let headers: Vec<String> = records
+        .first()
+        .and_then(|r| r.as_object())
+        .map(|obj| obj.keys().cloned().collect())
+        .unwrap_or_default();
+    let mut output = if format == "csv" {
+        format!("{}\n", headers.join(","))
+    } else {
+        "[\n".to_string()
+    };
+
+    for (i, record) in records.iter().enumerate() {
+        if format == "csv" {
+            let row: Vec<String> = headers
+                .iter()
+                .map(|h| csv_field(record.get(h).unwrap_or(&Value::Null)))
+                .collect();
+            output.push_str(&row.join(","));
+            output.push('\n');
+        } else {
+            let json = serde_json::to_string(record)
+                .map_err(|e| format!("Failed to serialize {}: {}", kind, e))?;
+            output.push_str("  ");
+            output.push_str(&json);
+            output.push_str(if i + 1 < total { ",\n" } else { "\n" });
+        }
+
+        if (i + 1) % EXPORT_PROGRESS_INTERVAL == 0 {
+            writer
+                .write_all(output.as_bytes())
+                .await
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            output.clear();
+            let _ = app_handle.emit(
+                "export-progress",
+                ExportProgress {
+                    kind: kind.to_string(),
+                    exported: i + 1,
+                    total,
+                },
+            );
+        }
+    }
+    if format == "json" {
+        output.push(']');
+        output.push('\n');
+    }
+
+    writer
+        .write_all(output.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    writer
+        .flush()
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    let _ = app_handle.emit(
+        "export-progress",
+        ExportProgress {
+            kind: kind.to_string(),
+            exported: total,
+            total,
+        },
+    );
+
+    Ok(())
+}
+
+fn to_records<T: Serialize>(items: &[T]) -> Result<Vec<Value>, String> {
+    items
+        .iter()
+        .map(|item| serde_json::to_value(item).map_err(|e| format!("Failed to serialize: {}", e)))
+        .collect()
+}
+
+// 导出时间范围内的数据为 JSON 或 CSV，每种数据一个文件，返回写入的文件路径
+// kinds: "traces"（截图元数据）、"summaries"、"daily_summaries"、"api_stats"
+#[tauri::command]
+pub async fn export_data<R: Runtime>(
+    state: State<'_, AppState>,
+    app: AppHandle<R>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+    kinds: Vec<String>,
+    format: String, // "json" or "csv"
+    dest: String,   // 导出目录
+) -> Result<Vec<String>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    if format != "json" && format != "csv" {
+        return Err(ClarityError::InvalidInput(
+            "Format must be 'json' or 'csv'".to_string(),
+        ));
+    }
+    if kinds.is_empty() {
+        return Err(ClarityError::InvalidInput(
+            "At least one kind of data is required".to_string(),
+        ));
+    }
+    if let Some(kind) = kinds.iter().find(|k| !EXPORT_KINDS.contains(&k.as_str())) {
+        return Err(ClarityError::InvalidInput(format!(
+            "Invalid export kind: {}",
+            kind
+        )));
+    }
+
+    let start_dt = start_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    let end_dt = end_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    let dest_dir = PathBuf::from(&dest);
+    screenshot::ensure_dir_exists(&dest_dir).await?;
+
+    let suffix = Local::now().format("%Y%m%d_%H%M%S");
+    let mut written = Vec::new();
+
+    for kind in &kinds {
+        let records = match kind.as_str() {
+            "traces" => {
+                let mut traces = db::get_screenshot_traces(&state.db_pool, start_dt, end_dt, None)
+                    .await
+                    .map_err(ClarityError::from)?;
+                traces.sort_by_key(|t| t.timestamp);
+                to_records(&traces)?
+            }
+            "summaries" => {
+                let mut summaries = db::get_summaries(&state.db_pool, start_dt, end_dt, None)
+                    .await
+                    .map_err(ClarityError::from)?;
+                summaries.sort_by_key(|s| s.start_time);
+                to_records(&summaries)?
+            }
+            "daily_summaries" => {
+                let start_date = start_dt.map(|dt| dt.format("%Y-%m-%d").to_string());
+                let end_date = end_dt.map(|dt| dt.format("%Y-%m-%d").to_string());
+                let mut daily = db::get_daily_summaries(
+                    &state.db_pool,
+                    start_date.as_deref(),
+                    end_date.as_deref(),
+                    None,
+                )
+                .await
+                .map_err(ClarityError::from)?;
+                daily.sort_by(|a, b| a.date.cmp(&b.date));
+                to_records(&daily)?
+            }
+            _ => {
+                let stats = db::get_api_statistics(&state.db_pool, start_dt, end_dt)
+                    .await
+                    .map_err(ClarityError::from)?;
+                to_records(std::slice::from_ref(&stats))?
+            }
+        };
+
+        let path = dest_dir.join(format!("clarity_{}_{}.{}", kind, suffix, format));
+        log::info!(
+            "Exporting {} {} records to {}",
+            records.len(),
+            kind,
+            path.display()
+        );
+        write_records(&path, kind, &format, records, &app).await?;
+        written.push(path.to_string_lossy().to_string());
+    }
+
+    log::info!("Data export finished: {} files", written.len());
+
+    Ok(written)
+}
+
+// 默认的 Markdown 模板，可用占位符：{{date}}、{{daily_summary}}、{{summaries}}
+const DEFAULT_MARKDOWN_TEMPLATE: &str =
+    "# {{date}}\n\n## Daily summary\n\n{{daily_summary}}\n\n## Timeline\n\n{{summaries}}\n";
+
+// 一次最多导出的天数
+const MAX_MARKDOWN_EXPORT_DAYS: i64 = 366;
+
+// 检查是否需要自动导出的间隔
+const MARKDOWN_EXPORT_INTERVAL_SECONDS: u64 = 60 * 60;
+
+// 程序没有运行时错过的自动导出，最多往前补多少天
+const MARKDOWN_EXPORT_BACKFILL_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkdownExportSettings {
+    pub dir: String,
+    pub template: String, // 空字符串表示使用默认模板
+    pub auto_export: bool,
+}
+
+async fn load_markdown_export_settings(db_pool: &SqlitePool) -> MarkdownExportSettings {
+    MarkdownExportSettings {
+        dir: settings::load_markdown_export_dir_from_db(db_pool)
+            .await
+            .unwrap_or_default(),
+        template: settings::load_markdown_export_template_from_db(db_pool)
+            .await
+            .unwrap_or_default(),
+        auto_export: settings::load_markdown_auto_export_from_db(db_pool)
+            .await
+            .unwrap_or(false),
+    }
+}
+
+// 生成某一天的 Markdown（YAML frontmatter 包含统计数据），当天没有任何摘要时返回 None
+async fn render_day_markdown(
+    db_pool: &SqlitePool,
+    date: NaiveDate,
+    template: &str,
+) -> Result<Option<String>, ClarityError> {
+    let (start_time, end_time) = day_range(date)?;
+    let date_str = date.format("%Y-%m-%d").to_string();
+
+    let mut summaries = db::get_summaries(db_pool, Some(start_time), Some(end_time), None)
+        .await
+        .map_err(ClarityError::from)?;
+    let daily_summary = db::get_daily_summary(db_pool, &date_str)
+        .await
+        .map_err(ClarityError::from)?;
+    if summaries.is_empty() && daily_summary.is_none() {
+        return Ok(None);
+    }
+    summaries.sort_by_key(|s| s.start_time);
+
+    let screenshot_count = db::get_screenshot_count(db_pool, Some(start_time), Some(end_time))
+        .await
+        .map_err(ClarityError::from)?;
+    let category_totals = db::get_category_totals(db_pool, Some(start_time), Some(end_time))
+        .await
+        .map_err(ClarityError::from)?;
+    let tracked_seconds: i64 = summaries
+        .iter()
+        .map(|s| (s.end_time - s.start_time).num_seconds().max(0))
+        .sum();
+    let focus_scores: Vec<i64> = summaries.iter().filter_map(|s| s.focus_score).collect();
+
+    // JSON 字符串也是合法的 YAML 字符串，用于转义分类名称
+    let mut frontmatter = format!(
+        "---\ndate: {}\nscreenshots: {}\nsummaries: {}\ntracked_minutes: {}\n",
+        date_str,
+        screenshot_count,
+        summaries.len(),
+        tracked_seconds / 60
+    );
+    if !focus_scores.is_empty() {
+        frontmatter.push_str(&format!(
+            "focus_score: {}\n",
+            focus_scores.iter().sum::<i64>() / focus_scores.len() as i64
+        ));
+    }
+    if !category_totals.is_empty() {
+        frontmatter.push_str("categories:\n");
+        for total in &category_totals {
+            frontmatter.push_str(&format!(
+                "  {}: {}\n",
+                Value::String(total.category.clone()),
+                total.duration_seconds / 60
+            ));
+        }
+    }
+    frontmatter.push_str(&format!(
+        "link: {}\ntags: [clarity]\n---\n\n",
+        deep_link::day_url(date)
+    ));
+
+    let timeline = summaries
+        .iter()
+        .map(|s| {
+            // 标题链接到应用中的这条摘要
+            format!(
+                "### [{} - {}]({})\n\n{}",
+                s.start_time.format("%H:%M"),
+                s.end_time.format("%H:%M"),
+                deep_link::summary_url(s.id),
+                s.content.trim()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let body = template
+        .replace("{{date}}", &date_str)
+        .replace(
+            "{{daily_summary}}",
+            daily_summary.as_ref().map_or("", |d| d.content.trim()),
+        )
+        .replace("{{summaries}}", &timeline);
+
+    Ok(Some(frontmatter + &body))
+}
+
+// 把日期范围内每一天导出为 {dir}/YYYY-MM-DD.md（已存在时覆盖），返回写入的文件路径
+async fn export_markdown_days(
+    db_pool: &SqlitePool,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    dir: &PathBuf,
+    template: &str,
+) -> Result<Vec<String>, ClarityError> {
+    screenshot::ensure_dir_exists(dir).await?;
+
+    let mut written = Vec::new();
+    let mut date = start_date;
+    while date <= end_date {
+        if let Some(markdown) = render_day_markdown(db_pool, date, template).await? {
+            let path = dir.join(format!("{}.md", date.format("%Y-%m-%d")));
+            tokio::fs::write(&path, markdown)
+                .await
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            written.push(path.to_string_lossy().to_string());
+        }
+        date += chrono::Duration::days(1);
+    }
+
+    Ok(written)
+}
+
+// 开启自动导出时，定期把已经结束的日期导出为 Markdown（包括程序未运行时错过的日期）
+pub async fn markdown_export_loop(db_pool: SqlitePool) {
+    let mut interval =
+        tokio::time::interval(StdDuration::from_secs(MARKDOWN_EXPORT_INTERVAL_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        let export_settings = load_markdown_export_settings(&db_pool).await;
+        if !export_settings.auto_export || export_settings.dir.is_empty() {
+            continue;
+        }
+
+        let yesterday = Local::now().date_naive() - chrono::Duration::days(1);
+        let earliest = yesterday - chrono::Duration::days(MARKDOWN_EXPORT_BACKFILL_DAYS - 1);
+        let start_date = settings::load_markdown_last_export_date_from_db(&db_pool)
+            .await
+            .ok()
+            .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+            .map_or(yesterday, |last| last + chrono::Duration::days(1))
+            .max(earliest);
+        if start_date > yesterday {
+            continue;
+        }
+
+        let template = if export_settings.template.is_empty() {
+            DEFAULT_MARKDOWN_TEMPLATE
+        } else {
+            export_settings.template.as_str()
+        };
+        match export_markdown_days(
+            &db_pool,
+            start_date,
+            yesterday,
+            &PathBuf::from(&export_settings.dir),
+            template,
+        )
+        .await
+        {
+            Ok(written) => {
+                log::info!(
+                    "Auto exported {} days to Markdown in {}",
+                    written.len(),
+                    export_settings.dir
+                );
+                let last = yesterday.format("%Y-%m-%d").to_string();
+                if let Err(e) =
+                    settings::save_markdown_last_export_date_to_db(&db_pool, &last).await
+                {
+                    log::error!("Failed to save last Markdown export date: {}", e);
+                }
+            }
+            Err(e) => log::error!("Failed to auto export Markdown: {}", e),
+        }
+    }
+}
+
+// 获取 Markdown 导出设置
+#[tauri::command]
+pub async fn get_markdown_export_settings(
+    state: State<'_, AppState>,
+) -> Result<MarkdownExportSettings, ClarityError> {
+    Ok(load_markdown_export_settings(&state.db_pool).await)
+}
+
+// 设置 Markdown 导出目录、模板以及是否在每天结束后自动导出
+#[tauri::command]
+pub async fn set_markdown_export_settings(
+    state: State<'_, AppState>,
+    dir: String,
+    template: String,
+    auto_export: bool,
+) -> Result<(), ClarityError> {
+    let dir = dir.trim().to_string();
+    if auto_export && dir.is_empty() {
+        return Err(ClarityError::InvalidInput(
+            "An export folder is required for automatic export".to_string(),
+        ));
+    }
+
+    settings::save_markdown_export_to_db(&state.db_pool, &dir, &template, auto_export)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "Markdown export settings updated: dir={}, auto_export={}",
+        dir,
+        auto_export
+    );
+
+    Ok(())
+}
+
+// 手动把日期范围内的摘要导出为 Markdown，dest 为空时使用设置中的目录
+#[tauri::command]
+pub async fn export_markdown(
+    state: State<'_, AppState>,
+    start_date: String,       // YYYY-MM-DD format
+    end_date: Option<String>, // YYYY-MM-DD format, if None, same as start_date
+    dest: Option<String>,
+) -> Result<Vec<String>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid date format: {}", e)))?;
+    let end = match end_date {
+        Some(d) => NaiveDate::parse_from_str(&d, "%Y-%m-%d")
+            .map_err(|e| ClarityError::InvalidInput(format!("Invalid date format: {}", e)))?,
+        None => start,
+    };
+    if end < start {
+        return Err(ClarityError::InvalidInput(
+            "end_date must not be before start_date".to_string(),
+        ));
+    }
+    if (end - start).num_days() >= MAX_MARKDOWN_EXPORT_DAYS {
+        return Err(ClarityError::InvalidInput(format!(
+            "At most {} days can be exported at once",
+            MAX_MARKDOWN_EXPORT_DAYS
+        )));
+    }
+
+    export_markdown_range(&state.db_pool, start, end, dest).await
+}
+
+// 导出日期范围内的 Markdown（手动导出和命令行共用），返回写入的文件路径
+pub async fn export_markdown_range(
+    db_pool: &SqlitePool,
+    start: NaiveDate,
+    end: NaiveDate,
+    dest: Option<String>,
+) -> Result<Vec<String>, ClarityError> {
+    let export_settings = load_markdown_export_settings(db_pool).await;
+    let dir = dest
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+        .unwrap_or(export_settings.dir);
+    if dir.is_empty() {
+        return Err(ClarityError::InvalidInput(
+            "No export folder configured".to_string(),
+        ));
+    }
+    let template = if export_settings.template.is_empty() {
+        DEFAULT_MARKDOWN_TEMPLATE
+    } else {
+        export_settings.template.as_str()
+    };
+
+    let written = export_markdown_days(db_pool, start, end, &PathBuf::from(&dir), template).await?;
+
+    log::info!("Exported {} days to Markdown in {}", written.len(), dir);
+
+    Ok(written)
+}
+
+// 导入数据的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub summaries_imported: u64,
+    pub summaries_skipped: u64, // 已存在相同开始时间的摘要
+    pub daily_summaries_imported: u64,
+    pub daily_summaries_skipped: u64, // 已存在同一天的每日总结
+    pub screenshots_imported: u64,
+    pub screenshots_skipped: u64, // 已存在或找不到截图文件
+}
+
+// 读取 export_data 导出的 JSON 文件
+async fn read_export_records<T: serde::de::DeserializeOwned>(
+    path: &PathBuf,
+) -> Result<Vec<T>, String> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid export file {}: {}", path.display(), e))
+}
+
+async fn import_summaries(
+    db_pool: &SqlitePool,
+    path: &PathBuf,
+    result: &mut ImportResult,
+) -> Result<(), ClarityError> {
+    for summary in read_export_records::<db::Summary>(path).await? {
+        if db::summary_exists_at(db_pool, summary.start_time)
+            .await
+            .map_err(ClarityError::from)?
+        {
+            result.summaries_skipped += 1;
+            continue;
+        }
+
+        let id = db::insert_summary(
+            db_pool,
+            summary.start_time,
+            summary.end_time,
+            summary.content,
+            summary.screenshot_count,
+        )
+        .await
+        .map_err(ClarityError::from)?;
+        if let Some(data) = &summary.structured_data {
+            db::update_summary_structured_data(db_pool, id, &data.to_string())
+                .await
+                .map_err(ClarityError::from)?;
+            db::set_summary_categories(db_pool, id, &structured_categories(data))
+                .await
+                .map_err(ClarityError::from)?;
+        }
+        if summary.private {
+            db::mark_summary_private(db_pool, id)
+                .await
+                .map_err(ClarityError::from)?;
+        }
+        if let Some(score) = summary.focus_score {
+            db::update_summary_focus_score(db_pool, id, score)
+                .await
+                .map_err(ClarityError::from)?;
+        }
+        for tag in &summary.tags {
+            let tag_id = db::upsert_tag(db_pool, tag, None)
+                .await
+                .map_err(ClarityError::from)?;
+            db::add_summary_tag(db_pool, id, tag_id, false)
+                .await
+                .map_err(ClarityError::from)?;
+        }
+        result.summaries_imported += 1;
+    }
+    Ok(())
+}
+
+async fn import_daily_summaries(
+    db_pool: &SqlitePool,
+    path: &PathBuf,
+    result: &mut ImportResult,
+) -> Result<(), ClarityError> {
+    for daily in read_export_records::<db::DailySummary>(path).await? {
+        if db::get_daily_summary(db_pool, &daily.date)
+            .await
+            .map_err(ClarityError::from)?
+            .is_some()
+        {
+            result.daily_summaries_skipped += 1;
+            continue;
+        }
+
+        db::upsert_daily_summary(
+            db_pool,
+            &daily.date,
+            &daily.content,
+            daily.screenshot_count,
+            daily.summary_count,
+            daily.total_duration_seconds,
+        )
+        .await
+        .map_err(ClarityError::from)?;
+        result.daily_summaries_imported += 1;
+    }
+    Ok(())
+}
+
+// 导入截图记录：截图文件在原路径时直接使用，否则在导入目录的 YYYY-MM-DD 子目录中查找并复制到存储目录
+async fn import_screenshots(
+    db_pool: &SqlitePool,
+    path: &PathBuf,
+    import_dir: &PathBuf,
+    storage_path: &PathBuf,
+    result: &mut ImportResult,
+) -> Result<(), ClarityError> {
+    for trace in read_export_records::<db::ScreenshotTrace>(path).await? {
+        if db::screenshot_trace_exists_at(db_pool, trace.timestamp)
+            .await
+            .map_err(ClarityError::from)?
+        {
+            result.screenshots_skipped += 1;
+            continue;
+        }
+
+        let original = PathBuf::from(&trace.file_path);
+        let file_path = if tokio::fs::metadata(&original).await.is_ok() {
+            original
+        } else {
+            let date_dir = trace.timestamp.format("%Y-%m-%d").to_string();
+            let Some(filename) = original.file_name() else {
+                result.screenshots_skipped += 1;
+                continue;
+            };
+            let source = import_dir.join(&date_dir).join(filename);
+            if tokio::fs::metadata(&source).await.is_err() {
+                result.screenshots_skipped += 1;
+                continue;
+            }
+            let target_dir = storage_path.join(&date_dir);
+            screenshot::ensure_dir_exists(&target_dir).await?;
+            let target = target_dir.join(filename);
+            tokio::fs::copy(&source, &target)
+                .await
+                .map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+            target
+        };
+
+        let new_trace = db::NewScreenshotTrace {
+            timestamp: trace.timestamp,
+            file_path: file_path.to_string_lossy().to_string(),
+            width: trace.width,
+            height: trace.height,
+            file_size: trace.file_size,
+            thumbnail_path: None,
+            cursor_x: trace.cursor_x,
+            cursor_y: trace.cursor_y,
+            private: trace.private,
+            app_name: trace.app_name,
+        };
+        db::insert_screenshot_trace(db_pool, new_trace)
+            .await
+            .map_err(ClarityError::from)?;
+        result.screenshots_imported += 1;
+    }
+    Ok(())
+}
+
+// 导入 export_data 导出的 JSON 文件（path 可以是单个文件或导出目录），按时间去重
+// include_screenshots 为 true 时同时导入截图记录
+#[tauri::command]
+pub async fn import_data(
+    state: State<'_, AppState>,
+    path: String,
+    include_screenshots: bool,
+) -> Result<ImportResult, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let path = PathBuf::from(&path);
+    let metadata = tokio::fs::metadata(&path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let (import_dir, files) = if metadata.is_dir() {
+        let mut files = Vec::new();
+        let mut entries = tokio::fs::read_dir(&path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        {
+            files.push(entry.path());
+        }
+        files.sort();
+        (path, files)
+    } else {
+        let dir = path.parent().map(PathBuf::from).unwrap_or_default();
+        (dir, vec![path])
+    };
+
+    let storage_path = state.storage_path.lock().await.clone();
+    let mut result = ImportResult::default();
+
+    for file in files {
+        let name = file
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !name.ends_with(".json") {
+            continue;
+        }
+
+        if name.starts_with("clarity_summaries_") {
+            import_summaries(&state.db_pool, &file, &mut result).await?;
+        } else if name.starts_with("clarity_daily_summaries_") {
+            import_daily_summaries(&state.db_pool, &file, &mut result).await?;
+        } else if name.starts_with("clarity_traces_") && include_screenshots {
+            import_screenshots(
+                &state.db_pool,
+                &file,
+                &import_dir,
+                &storage_path,
+                &mut result,
+            )
+            .await?;
+        }
+    }
+
+    log::info!(
+        "Imported {} summaries, {} daily summaries and {} screenshots ({} skipped)",
+        result.summaries_imported,
+        result.daily_summaries_imported,
+        result.screenshots_imported,
+        result.summaries_skipped + result.daily_summaries_skipped + result.screenshots_skipped
+    );
+
+    Ok(result)
+}
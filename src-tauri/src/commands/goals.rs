@@ -0,0 +1,350 @@
+use crate::commands::lock;
+use crate::db;
+use crate::error::ClarityError;
+use crate::locale;
+use crate::state::AppState;
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::time::Duration as StdDuration;
+use tauri::State;
+
+// 专注度达到此分数的摘要计入专注时长
+pub const FOCUSED_SCORE_THRESHOLD: i64 = 70;
+
+// 检查是否有需要评估的日期的间隔
+const GOAL_EVALUATION_INTERVAL_SECONDS: u64 = 60 * 60;
+
+// 程序没有运行时错过的评估，最多往前补多少天
+const GOAL_EVALUATION_BACKFILL_DAYS: i64 = 7;
+
+const GOAL_METRICS: [&str; 2] = ["category", "focus_time"];
+const GOAL_COMPARISONS: [&str; 2] = ["at_least", "at_most"];
+
+// 目标在某一天的进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalProgress {
+    pub goal: db::Goal,
+    pub date: String, // YYYY-MM-DD
+    pub actual_minutes: i64,
+    pub achieved: bool,  // 当天还没结束时表示目前是否达标
+    pub evaluated: bool, // 是否已在当天结束后评估并保存
+}
+
+// 计算某一天的开始和结束时间
+pub fn day_range(date: NaiveDate) -> Result<(DateTime<Local>, DateTime<Local>), String> {
+    let start_time = date
+        .and_hms_opt(0, 0, 0)
+        .ok_or_else(|| "Invalid date".to_string())?
+        .and_local_timezone(Local)
+        .single()
+        .ok_or_else(|| "Invalid timezone conversion".to_string())?;
+
+    let end_time = date
+        .and_hms_opt(23, 59, 59)
+        .ok_or_else(|| "Invalid date".to_string())?
+        .and_local_timezone(Local)
+        .single()
+        .ok_or_else(|| "Invalid timezone conversion".to_string())?;
+
+    Ok((start_time, end_time))
+}
+
+fn is_achieved(goal: &db::Goal, actual_minutes: i64) -> bool {
+    if goal.comparison == "at_most" {
+        actual_minutes <= goal.target_minutes
+    } else {
+        actual_minutes >= goal.target_minutes
+    }
+}
+
+// 当天适用的目标：已启用，并且在当天结束前创建
+fn goals_for_date(goals: Vec<db::Goal>, end_time: DateTime<Local>) -> Vec<db::Goal> {
+    goals
+        .into_iter()
+        .filter(|goal| goal.enabled && goal.created_at <= end_time)
+        .collect()
+}
+
+// 根据分类时长和专注度实时计算目标进度（不保存）
+async fn measure_goals(
+    db_pool: &SqlitePool,
+    date: NaiveDate,
+    goals: Vec<db::Goal>,
+) -> Result<Vec<GoalProgress>, ClarityError> {
+    let (start_time, end_time) = day_range(date)?;
+
+    let category_totals = db::get_category_totals(db_pool, Some(start_time), Some(end_time))
+        .await
+        .map_err(ClarityError::from)?;
+    let focused_seconds = db::get_summaries(db_pool, Some(start_time), Some(end_time), None)
+        .await
+        .map_err(ClarityError::from)?
+        .iter()
+        .filter(|s| {
+            s.focus_score
+                .is_some_and(|score| score >= FOCUSED_SCORE_THRESHOLD)
+        })
+        .map(|s| (s.end_time - s.start_time).num_seconds().max(0))
+        .sum::<i64>();
+
+    let date_str = date.format("%Y-%m-%d").to_string();
+    Ok(goals
+        .into_iter()
+        .map(|goal| {
+            let seconds = if goal.metric == "focus_time" {
+                focused_seconds
+            } else {
+                category_totals
+                    .iter()
+                    .find(|t| Some(&t.category) == goal.category.as_ref())
+                    .map_or(0, |t| t.duration_seconds)
+            };
+            let actual_minutes = seconds / 60;
+            GoalProgress {
+                achieved: is_achieved(&goal, actual_minutes),
+                goal,
+                date: date_str.clone(),
+                actual_minutes,
+                evaluated: false,
+            }
+        })
+        .collect())
+}
+
+// 评估并保存某一天所有目标的结果
+pub async fn evaluate_goals(
+    db_pool: &SqlitePool,
+    date: NaiveDate,
+) -> Result<Vec<GoalProgress>, ClarityError> {
+    let (_, end_time) = day_range(date)?;
+    let goals = db::get_goals(db_pool).await.map_err(ClarityError::from)?;
+
+    let mut progress = measure_goals(db_pool, date, goals_for_date(goals, end_time)).await?;
+    for item in &mut progress {
+        db::upsert_goal_result(
+            db_pool,
+            item.goal.id,
+            &item.date,
+            item.actual_minutes,
+            item.achieved,
+        )
+        .await
+        .map_err(ClarityError::from)?;
+        item.evaluated = true;
+    }
+
+    Ok(progress)
+}
+
+// 查询某一天的目标进度：已评估的使用保存的结果，其余实时计算
+pub async fn load_goal_progress(
+    db_pool: &SqlitePool,
+    date: NaiveDate,
+) -> Result<Vec<GoalProgress>, ClarityError> {
+    let (_, end_time) = day_range(date)?;
+    let date_str = date.format("%Y-%m-%d").to_string();
+
+    let goals = db::get_goals(db_pool).await.map_err(ClarityError::from)?;
+    let results = db::get_goal_results(db_pool, &date_str)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let (evaluated, pending): (Vec<db::Goal>, Vec<db::Goal>) = goals_for_date(goals, end_time)
+        .into_iter()
+        .partition(|goal| results.iter().any(|r| r.goal_id == goal.id));
+
+    let mut progress: Vec<GoalProgress> = evaluated
+        .into_iter()
+        .filter_map(|goal| {
+            let result = results.iter().find(|r| r.goal_id == goal.id)?;
+            Some(GoalProgress {
+                goal,
+                date: date_str.clone(),
+                actual_minutes: result.actual_minutes,
+                achieved: result.achieved,
+                evaluated: true,
+            })
+        })
+        .collect();
+    progress.extend(measure_goals(db_pool, date, pending).await?);
+    progress.sort_by_key(|p| p.goal.id);
+
+    Ok(progress)
+}
+
+// 把目标进度整理为文字，附加到每日总结的提示词中
+pub fn format_goal_progress(progress: &[GoalProgress], language: &str) -> String {
+    progress
+        .iter()
+        .map(|p| {
+            let status = match (locale::is_chinese(language), p.achieved) {
+                (true, true) => "已达成",
+                (true, false) => "未达成",
+                (false, true) => "met",
+                (false, false) => "not met",
+            };
+            format!(
+                "- {}: {} / {} min ({})",
+                p.goal.label, p.actual_minutes, p.goal.target_minutes, status
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// 定期评估已经结束的日期（包括程序未运行时错过的日期），结果保存后不再变化
+pub async fn goal_evaluation_loop(db_pool: SqlitePool) {
+    let mut interval =
+        tokio::time::interval(StdDuration::from_secs(GOAL_EVALUATION_INTERVAL_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        let today = Local::now().date_naive();
+        for days_ago in 1..=GOAL_EVALUATION_BACKFILL_DAYS {
+            let date = today - chrono::Duration::days(days_ago);
+            let has_pending = match load_goal_progress(&db_pool, date).await {
+                Ok(progress) => progress.iter().any(|p| !p.evaluated),
+                Err(e) => {
+                    log::error!("Failed to load goal progress for {}: {}", date, e);
+                    continue;
+                }
+            };
+            if !has_pending {
+                continue;
+            }
+
+            match evaluate_goals(&db_pool, date).await {
+                Ok(progress) => log::info!(
+                    "Evaluated {} goals for {} ({} achieved)",
+                    progress.len(),
+                    date,
+                    progress.iter().filter(|p| p.achieved).count()
+                ),
+                Err(e) => log::error!("Failed to evaluate goals for {}: {}", date, e),
+            }
+        }
+    }
+}
+
+// 获取所有目标
+#[tauri::command]
+pub async fn get_goals(state: State<'_, AppState>) -> Result<Vec<db::Goal>, ClarityError> {
+    db::get_goals(&state.db_pool)
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 添加目标，例如每天至少 240 分钟 coding，或每天最多 60 分钟 social
+#[tauri::command]
+pub async fn add_goal(
+    state: State<'_, AppState>,
+    label: Option<String>,
+    metric: String,
+    category: Option<String>,
+    comparison: String,
+    target_minutes: i64,
+) -> Result<db::Goal, ClarityError> {
+    if !GOAL_METRICS.contains(&metric.as_str()) {
+        return Err(ClarityError::InvalidInput(format!(
+            "Invalid goal metric: {}",
+            metric
+        )));
+    }
+    if !GOAL_COMPARISONS.contains(&comparison.as_str()) {
+        return Err(ClarityError::InvalidInput(format!(
+            "Invalid goal comparison: {}",
+            comparison
+        )));
+    }
+    if !(1..=24 * 60).contains(&target_minutes) {
+        return Err(ClarityError::InvalidInput(
+            "Target must be between 1 and 1440 minutes".to_string(),
+        ));
+    }
+
+    let category = category
+        .map(|c| c.trim().to_lowercase())
+        .filter(|c| !c.is_empty());
+    if metric == "category" && category.is_none() {
+        return Err(ClarityError::InvalidInput(
+            "Category is required for category goals".to_string(),
+        ));
+    }
+    let category = if metric == "category" { category } else { None };
+
+    // 没有填写名称时按目标内容生成
+    let label = label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| {
+            format!(
+                "{} {} min {}",
+                if comparison == "at_most" { "<=" } else { ">=" },
+                target_minutes,
+                category.as_deref().unwrap_or("focus")
+            )
+        });
+
+    let id = db::insert_goal(
+        &state.db_pool,
+        &label,
+        &metric,
+        category.as_deref(),
+        &comparison,
+        target_minutes,
+    )
+    .await
+    .map_err(ClarityError::from)?;
+
+    log::info!("Goal {} added: {}", id, label);
+
+    db::get_goals(&state.db_pool)
+        .await
+        .map_err(ClarityError::from)?
+        .into_iter()
+        .find(|goal| goal.id == id)
+        .ok_or_else(|| ClarityError::Internal("Failed to retrieve saved goal".to_string()))
+}
+
+// 启用或停用目标
+#[tauri::command]
+pub async fn set_goal_enabled(
+    state: State<'_, AppState>,
+    id: i64,
+    enabled: bool,
+) -> Result<(), ClarityError> {
+    db::update_goal_enabled(&state.db_pool, id, enabled)
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 删除目标
+#[tauri::command]
+pub async fn delete_goal(state: State<'_, AppState>, id: i64) -> Result<(), ClarityError> {
+    db::delete_goal(&state.db_pool, id)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!("Goal {} deleted", id);
+
+    Ok(())
+}
+
+// 获取某一天的目标进度
+#[tauri::command]
+pub async fn get_goal_progress(
+    state: State<'_, AppState>,
+    date: Option<String>, // YYYY-MM-DD format, if None, use today
+) -> Result<Vec<GoalProgress>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let date = match date {
+        Some(d) => NaiveDate::parse_from_str(&d, "%Y-%m-%d")
+            .map_err(|e| ClarityError::InvalidInput(format!("Invalid date format: {}", e)))?,
+        None => Local::now().date_naive(),
+    };
+
+    load_goal_progress(&state.db_pool, date).await
+}
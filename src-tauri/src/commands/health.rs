@@ -0,0 +1,46 @@
+use crate::error::ClarityError;
+use crate::health::TaskError;
+use crate::state::AppState;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use tauri::State;
+
+// 总结 worker 超过这个时间（秒）没有心跳就认为已经停止（正常每 5 秒一次）
+const SUMMARY_WORKER_TIMEOUT_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Health {
+    pub recording: bool, // 录制会话进行中（包括暂停），截图任务应该在运行
+    pub screenshot_loop_alive: bool,
+    pub summary_loop_alive: bool, // 总结定时任务，整个程序运行期间都应该在运行
+    pub summary_worker_alive: bool, // 处理总结队列的 worker，整个程序运行期间都应该在运行
+    pub last_capture_at: Option<DateTime<Local>>,
+    pub last_summary_at: Option<DateTime<Local>>,
+    pub last_error: Option<TaskError>,
+}
+
+// 获取后台任务的运行状态，用于排查截图或总结停止的问题
+#[tauri::command]
+pub async fn get_health(state: State<'_, AppState>) -> Result<Health, ClarityError> {
+    let screenshot_loop_alive = state
+        .handle
+        .lock()
+        .await
+        .as_ref()
+        .is_some_and(|handle| !handle.is_finished());
+    let summary_worker_alive = state
+        .health
+        .summary_worker_heartbeat_at()
+        .is_some_and(|at| (Local::now() - at).num_seconds() < SUMMARY_WORKER_TIMEOUT_SECONDS);
+
+    Ok(Health {
+        recording: state.recording.is_active(),
+        screenshot_loop_alive,
+        summary_loop_alive: state.health.summary_loop_running(),
+        summary_worker_alive,
+        last_capture_at: state.health.last_capture_at(),
+        last_summary_at: state.health.last_summary_at(),
+        last_error: state.health.last_error(),
+    })
+}
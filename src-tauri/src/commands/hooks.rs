@@ -0,0 +1,257 @@
+use crate::commands::lock;
+use crate::commands::webhooks::validate_events;
+use crate::db;
+use crate::error::ClarityError;
+use crate::state::AppState;
+use chrono::Local;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::State;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+
+// 脚本运行超时，超时后结束进程
+const HOOK_TIMEOUT_SECS: u64 = 60;
+// 记录到 last_error 的 stderr 最大长度
+const MAX_HOOK_ERROR_CHARS: usize = 1000;
+
+// 脚本运行结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryHookRun {
+    pub exit_code: Option<i32>, // 超时或被信号结束时为空
+    pub stdout: String,
+    pub stderr: String,
+}
+
+fn validate_command(command: &str) -> Result<String, ClarityError> {
+    let command = command.trim();
+    if command.is_empty() {
+        return Err(ClarityError::InvalidInput(
+            "Hook command cannot be empty".to_string(),
+        ));
+    }
+    Ok(command.to_string())
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_HOOK_ERROR_CHARS) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+// 运行一次脚本：payload 写入 stdin，环境变量 CLARITY_EVENT 为事件名
+async fn run_hook(
+    hook: &db::SummaryHook,
+    event: &str,
+    payload: &[u8],
+) -> Result<SummaryHookRun, String> {
+    let mut child = Command::new(&hook.command)
+        .args(&hook.args)
+        .env("CLARITY_EVENT", event)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", hook.command, e))?;
+
+    // 并发读取输出，避免缓冲区写满导致脚本阻塞
+    let mut stdout = child.stdout.take().ok_or("Failed to capture hook stdout")?;
+    let mut stderr = child.stderr.take().ok_or("Failed to capture hook stderr")?;
+    let stdout_task = tokio::spawn(async move {
+        let mut buffer = Vec::new();
+        let _ = stdout.read_to_end(&mut buffer).await;
+        buffer
+    });
+    let stderr_task = tokio::spawn(async move {
+        let mut buffer = Vec::new();
+        let _ = stderr.read_to_end(&mut buffer).await;
+        buffer
+    });
+
+    let mut stdin = child.stdin.take().ok_or("Failed to open hook stdin")?;
+    // 写入 stdin 也计入超时，脚本不读取 stdin 又不退出时不会一直等待
+    let run = async {
+        // 脚本不读取 stdin 时写入会失败，不影响运行结果
+        if let Err(e) = stdin.write_all(payload).await {
+            log::debug!("Hook {} did not read stdin: {}", hook.id, e);
+        }
+        // 关闭 stdin，通知脚本输入结束
+        drop(stdin);
+        child.wait().await
+    };
+
+    let status = match tokio::time::timeout(Duration::from_secs(HOOK_TIMEOUT_SECS), run).await {
+        Ok(status) => status.map_err(|e| format!("Failed to wait for {}: {}", hook.command, e))?,
+        Err(_) => {
+            let _ = child.kill().await;
+            return Err(format!(
+                "{} did not finish within {} seconds",
+                hook.command, HOOK_TIMEOUT_SECS
+            ));
+        }
+    };
+
+    Ok(SummaryHookRun {
+        exit_code: status.code(),
+        stdout: String::from_utf8_lossy(&stdout_task.await.unwrap_or_default()).to_string(),
+        stderr: String::from_utf8_lossy(&stderr_task.await.unwrap_or_default()).to_string(),
+    })
+}
+
+// 运行并记录结果，退出码不为 0 时返回错误
+async fn run_and_record(
+    db_pool: &SqlitePool,
+    hook: &db::SummaryHook,
+    event: &str,
+    payload: &[u8],
+) -> Result<SummaryHookRun, String> {
+    let run = run_hook(hook, event, payload).await;
+    let exit_code = run
+        .as_ref()
+        .ok()
+        .and_then(|run| run.exit_code)
+        .map(i64::from);
+    let result = run.and_then(|run| {
+        if run.exit_code == Some(0) {
+            Ok(run)
+        } else {
+            let code = run
+                .exit_code
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "none".to_string());
+            Err(format!(
+                "{} exited with code {}: {}",
+                hook.command,
+                code,
+                truncate(&run.stderr)
+            ))
+        }
+    });
+
+    if let Err(e) = db::record_summary_hook_run(
+        db_pool,
+        hook.id,
+        exit_code,
+        result.as_ref().err().map(String::as_str),
+    )
+    .await
+    {
+        log::error!("Failed to record hook run: {}", e);
+    }
+    result
+}
+
+// 在后台依次运行订阅了 event 的脚本，payload 和 webhook 推送的 JSON 相同
+pub fn dispatch(db_pool: &SqlitePool, event: &'static str, payload: Vec<u8>) {
+    let db_pool = db_pool.clone();
+    tokio::spawn(async move {
+        let hooks = match db::get_summary_hooks(&db_pool).await {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                log::error!("Failed to load summary hooks: {}", e);
+                return;
+            }
+        };
+        for hook in hooks
+            .iter()
+            .filter(|h| h.enabled && h.events.iter().any(|e| e == event))
+        {
+            match run_and_record(&db_pool, hook, event, &payload).await {
+                Ok(_) => log::info!("Hook {} ({}) finished for {}", hook.id, hook.command, event),
+                Err(e) => log::error!("Hook {} failed for {}: {}", hook.id, event, e),
+            }
+        }
+    });
+}
+
+// 获取所有后处理脚本
+#[tauri::command]
+pub async fn get_summary_hooks(
+    state: State<'_, AppState>,
+) -> Result<Vec<db::SummaryHook>, ClarityError> {
+    db::get_summary_hooks(&state.db_pool)
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 添加后处理脚本；events 为空时订阅所有事件
+#[tauri::command]
+pub async fn add_summary_hook(
+    state: State<'_, AppState>,
+    command: String,
+    args: Option<Vec<String>>,
+    events: Option<Vec<String>>,
+) -> Result<db::SummaryHook, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let command = validate_command(&command)?;
+    let args = args.unwrap_or_default();
+    let events = validate_events(events)?;
+    let id = db::insert_summary_hook(&state.db_pool, &command, &args, &events).await?;
+    log::info!("Added summary hook {}: {}", id, command);
+
+    db::get_summary_hooks(&state.db_pool)
+        .await?
+        .into_iter()
+        .find(|h| h.id == id)
+        .ok_or_else(|| ClarityError::NotFound(format!("Hook {} not found", id)))
+}
+
+// 修改后处理脚本
+#[tauri::command]
+pub async fn update_summary_hook(
+    state: State<'_, AppState>,
+    id: i64,
+    command: String,
+    args: Option<Vec<String>>,
+    events: Option<Vec<String>>,
+    enabled: bool,
+) -> Result<(), ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let command = validate_command(&command)?;
+    let args = args.unwrap_or_default();
+    let events = validate_events(events)?;
+    if !db::update_summary_hook(&state.db_pool, id, &command, &args, &events, enabled).await? {
+        return Err(ClarityError::NotFound(format!("Hook {} not found", id)));
+    }
+    Ok(())
+}
+
+// 删除后处理脚本
+#[tauri::command]
+pub async fn delete_summary_hook(state: State<'_, AppState>, id: i64) -> Result<(), ClarityError> {
+    if !db::delete_summary_hook(&state.db_pool, id).await? {
+        return Err(ClarityError::NotFound(format!("Hook {} not found", id)));
+    }
+    Ok(())
+}
+
+// 立即用 ping 事件运行一次脚本（不检查是否启用），返回输出，便于调试
+#[tauri::command]
+pub async fn test_summary_hook(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<SummaryHookRun, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let hook = db::get_summary_hooks(&state.db_pool)
+        .await?
+        .into_iter()
+        .find(|h| h.id == id)
+        .ok_or_else(|| ClarityError::NotFound(format!("Hook {} not found", id)))?;
+
+    let payload = serde_json::to_vec(&serde_json::json!({
+        "event": "ping",
+        "timestamp": Local::now().timestamp(),
+        "data": { "hookId": hook.id },
+    }))
+    .map_err(|e| format!("Failed to serialize hook payload: {}", e))?;
+
+    run_and_record(&state.db_pool, &hook, "ping", &payload)
+        .await
+        .map_err(ClarityError::Internal)
+}
@@ -0,0 +1,74 @@
+use crate::error::ClarityError;
+use crate::events;
+use crate::settings;
+use crate::state::AppState;
+use serde::Serialize;
+use tauri::State;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncognitoStatus {
+    pub enabled: bool,
+}
+
+// 开启或关闭隐身模式：保存设置，新的截图立即按新状态标记，并通知前端
+pub async fn set_incognito(state: &AppState, enabled: bool) -> Result<(), ClarityError> {
+    settings::save_incognito_mode_to_db(&state.db_pool, enabled)
+        .await
+        .map_err(ClarityError::from)?;
+    *state.incognito.lock().await = enabled;
+    log::info!(
+        "Incognito mode {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+
+    let app_handle = state.app_handle.lock().await.clone();
+    events::emit(
+        app_handle.as_ref(),
+        events::INCOGNITO_CHANGED,
+        IncognitoStatus { enabled },
+    );
+    Ok(())
+}
+
+// 切换隐身模式（快捷键和托盘菜单使用），返回切换后的状态
+pub async fn toggle_incognito(state: &AppState) -> Result<bool, ClarityError> {
+    let enabled = !*state.incognito.lock().await;
+    set_incognito(state, enabled).await?;
+    Ok(enabled)
+}
+
+// 会把数据发送给 AI 的命令先调用，隐身模式下或用户没有同意上传时拒绝
+pub async fn ensure_uploads_allowed(state: &AppState) -> Result<(), ClarityError> {
+    if *state.incognito.lock().await {
+        return Err(ClarityError::Conflict(
+            "Incognito mode is on, nothing is sent to AI providers".to_string(),
+        ));
+    }
+    if !*state.ai_upload_consent.lock().await {
+        return Err(ClarityError::ConsentRequired(
+            "Consent to upload to AI providers has not been given".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// 获取隐身模式状态
+#[tauri::command]
+pub async fn get_incognito_mode(
+    state: State<'_, AppState>,
+) -> Result<IncognitoStatus, ClarityError> {
+    Ok(IncognitoStatus {
+        enabled: *state.incognito.lock().await,
+    })
+}
+
+// 开启或关闭隐身模式
+#[tauri::command]
+pub async fn set_incognito_mode(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<IncognitoStatus, ClarityError> {
+    set_incognito(&state, enabled).await?;
+    Ok(IncognitoStatus { enabled })
+}
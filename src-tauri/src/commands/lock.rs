@@ -0,0 +1,424 @@
+use crate::error::ClarityError;
+use crate::events;
+use crate::settings;
+use crate::state::AppState;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::State;
+
+// PIN 最短长度
+const MIN_PIN_LENGTH: usize = 4;
+// PIN 错误后等待一段时间再返回，减慢逐个尝试
+const UNLOCK_FAILURE_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppLockStatus {
+    pub enabled: bool, // 是否设置了 PIN
+    pub locked: bool,
+}
+
+// PIN 只保存 Argon2 哈希（PHC 字符串，包含盐和参数）
+fn hash_pin(pin: &str) -> Result<String, String> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let salt =
+        SaltString::encode_b64(&salt).map_err(|e| format!("Failed to generate salt: {}", e))?;
+    Argon2::default()
+        .hash_password(pin.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Failed to hash PIN: {}", e))
+}
+
+fn verify_pin(pin: &str, pin_hash: &str) -> bool {
+    match PasswordHash::new(pin_hash) {
+        Ok(hash) => Argon2::default()
+            .verify_password(pin.as_bytes(), &hash)
+            .is_ok(),
+        Err(e) => {
+            log::error!("Stored app lock PIN hash is invalid: {}", e);
+            false
+        }
+    }
+}
+
+fn validate_pin(pin: &str) -> Result<(), ClarityError> {
+    if pin.chars().count() < MIN_PIN_LENGTH {
+        return Err(ClarityError::InvalidInput(format!(
+            "PIN must be at least {} characters",
+            MIN_PIN_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+async fn load_pin_hash(state: &AppState) -> Result<Option<String>, ClarityError> {
+    settings::load_app_lock_pin_hash_from_db(&state.db_pool)
+        .await
+        .map_err(ClarityError::from)
+}
+
+async fn current_status(state: &AppState) -> Result<AppLockStatus, ClarityError> {
+    Ok(AppLockStatus {
+        enabled: load_pin_hash(state).await?.is_some(),
+        locked: *state.locked.lock().await,
+    })
+}
+
+async fn set_locked(state: &AppState, locked: bool) -> Result<AppLockStatus, ClarityError> {
+    *state.locked.lock().await = locked;
+    let status = current_status(state).await?;
+    let app_handle = state.app_handle.lock().await.clone();
+    events::emit(
+        app_handle.as_ref(),
+        events::APP_LOCK_CHANGED,
+        status.clone(),
+    );
+    Ok(status)
+}
+
+// 返回历史记录（时间线、摘要、截图）的命令先调用，锁定时拒绝
+pub async fn ensure_unlocked(state: &AppState) -> Result<(), ClarityError> {
+    if *state.locked.lock().await {
+        return Err(ClarityError::Locked(
+            "Clarity is locked, enter the PIN to unlock".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// 获取应用锁状态
+#[tauri::command]
+pub async fn get_app_lock_status(
+    state: State<'_, AppState>,
+) -> Result<AppLockStatus, ClarityError> {
+    current_status(&state).await
+}
+
+// 设置、修改或删除 PIN（pin 为空时删除）；已经设置过 PIN 时需要提供当前 PIN
+#[tauri::command]
+pub async fn set_app_lock_pin(
+    state: State<'_, AppState>,
+    current_pin: Option<String>,
+    pin: Option<String>,
+) -> Result<AppLockStatus, ClarityError> {
+    if let Some(pin_hash) = load_pin_hash(&state).await? {
+        let current_pin = current_pin.unwrap_or_default();
+        if !verify_pin(&current_pin, &pin_hash) {
+            tokio::time::sleep(UNLOCK_FAILURE_DELAY).await;
+            return Err(ClarityError::InvalidInput("Incorrect PIN".to_string()));
+        }
+    }
+
+    match pin {
+        Some(pin) => {
+            validate_pin(&pin)?;
+            settings::save_app_lock_pin_hash_to_db(&state.db_pool, &hash_pin(&pin)?).await?;
+            log::info!("App lock PIN updated");
+        }
+        None => {
+            settings::delete_app_lock_pin_hash_from_db(&state.db_pool).await?;
+            log::info!("App lock disabled");
+        }
+    }
+    // 修改 PIN 时已经验证过当前 PIN，保持解锁
+    set_locked(&state, false).await
+}
+
+// 立即锁定应用，需要先设置 PIN
+#[tauri::command]
+pub async fn lock_app(state: State<'_, AppState>) -> Result<AppLockStatus, ClarityError> {
+    if load_pin_hash(&state).await?.is_none() {
+        return Err(ClarityError::Conflict(
+            "Set a PIN before locking Clarity".to_string(),
+        ));
+    }
+    log::info!("App locked");
+    set_locked(&state, true).await
+}
+
+// 输入 PIN 解锁
+#[tauri::command]
+pub async fn unlock_app(
+    state: State<'_, AppState>,
+    pin: String,
+) -> Result<AppLockStatus, ClarityError> {
+    let Some(pin_hash) = load_pin_hash(&state).await? else {
+        // 没有设置 PIN 时不会锁定
+        return set_locked(&state, false).await;
+    };
+    if !verify_pin(&pin, &pin_hash) {
+        log::warn!("Failed attempt to unlock the app");
+        tokio::time::sleep(UNLOCK_FAILURE_DELAY).await;
+        return Err(ClarityError::InvalidInput("Incorrect PIN".to_string()));
+    }
+    log::info!("App unlocked");
+    set_locked(&state, false).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::*;
+    use crate::error::ClarityError;
+    use crate::state::AppState;
+    use tauri::Manager;
+
+    // 锁定时返回或导出历史记录的命令都应该拒绝
+    #[tokio::test]
+    async fn locked_state_rejects_history_commands() {
+        let app = tauri::test::mock_app();
+        app.manage(AppState::ephemeral().await.unwrap());
+        let handle = app.handle().clone();
+        let state = || app.state::<AppState>();
+        *state().locked.lock().await = true;
+        let dest = std::env::temp_dir().to_string_lossy().to_string();
+
+        let results: Vec<(&str, Result<(), ClarityError>)> = vec![
+            (
+                "get_traces",
+                get_traces(state(), None, None, None).await.map(drop),
+            ),
+            (
+                "delete_traces",
+                delete_traces(state(), Some(vec![1]), None, None, true)
+                    .await
+                    .map(drop),
+            ),
+            (
+                "get_summaries",
+                get_summaries(state(), None, None, None, None)
+                    .await
+                    .map(drop),
+            ),
+            (
+                "add_summary",
+                add_summary(
+                    state(),
+                    "2024-05-01T09:00:00+00:00".to_string(),
+                    "2024-05-01T09:01:00+00:00".to_string(),
+                    "content".to_string(),
+                    0,
+                )
+                .await
+                .map(drop),
+            ),
+            (
+                "get_summary_by_id",
+                get_summary_by_id(state(), 1).await.map(drop),
+            ),
+            (
+                "update_summary",
+                update_summary(state(), 1, "content".to_string())
+                    .await
+                    .map(drop),
+            ),
+            ("delete_summary", delete_summary(state(), 1).await.map(drop)),
+            (
+                "get_api_requests",
+                get_api_requests(state(), None, None, None, None, None, None)
+                    .await
+                    .map(drop),
+            ),
+            (
+                "get_today_statistics",
+                get_today_statistics(state()).await.map(drop),
+            ),
+            (
+                "get_category_totals",
+                get_category_totals(state(), None, None).await.map(drop),
+            ),
+            (
+                "get_activity_heatmap",
+                get_activity_heatmap(state(), None, None).await.map(drop),
+            ),
+            (
+                "get_streaks_and_trends",
+                get_streaks_and_trends(state(), None).await.map(drop),
+            ),
+            (
+                "get_app_usage",
+                get_app_usage(state(), None, None).await.map(drop),
+            ),
+            (
+                "get_top_apps",
+                get_top_apps(state(), None, None, None).await.map(drop),
+            ),
+            (
+                "read_screenshot_file",
+                read_screenshot_file(state(), "trace.jpg".to_string())
+                    .await
+                    .map(drop),
+            ),
+            (
+                "get_summary_videos",
+                get_summary_videos(state(), None, None).await.map(drop),
+            ),
+            (
+                "get_summary_jobs",
+                get_summary_jobs(state(), None, None).await.map(drop),
+            ),
+            (
+                "regenerate_summary",
+                regenerate_summary(state(), 1, None).await.map(drop),
+            ),
+            (
+                "generate_daily_summary",
+                generate_daily_summary(state(), None).await.map(drop),
+            ),
+            (
+                "get_daily_summary",
+                get_daily_summary(state(), None).await.map(drop),
+            ),
+            (
+                "generate_monthly_summary",
+                generate_monthly_summary(state(), None).await.map(drop),
+            ),
+            (
+                "get_monthly_summary",
+                get_monthly_summary(state(), None).await.map(drop),
+            ),
+            (
+                "get_monthly_summaries",
+                get_monthly_summaries(state(), None).await.map(drop),
+            ),
+            (
+                "get_historical_stats",
+                get_historical_stats(state(), 7).await.map(drop),
+            ),
+            (
+                "get_focus_scores",
+                get_focus_scores(state(), None, None).await.map(drop),
+            ),
+            (
+                "get_model_comparisons",
+                get_model_comparisons(state(), None).await.map(drop),
+            ),
+            (
+                "export_timelapse",
+                export_timelapse(state(), None, None, None).await.map(drop),
+            ),
+            (
+                "export_clip",
+                export_clip(
+                    state(),
+                    "2024-05-01T09:00:00+00:00".to_string(),
+                    "2024-05-01T09:01:00+00:00".to_string(),
+                    None,
+                )
+                .await
+                .map(drop),
+            ),
+            (
+                "export_data",
+                export_data(
+                    state(),
+                    handle.clone(),
+                    None,
+                    None,
+                    vec!["summaries".to_string()],
+                    "json".to_string(),
+                    dest.clone(),
+                )
+                .await
+                .map(drop),
+            ),
+            (
+                "export_markdown",
+                export_markdown(state(), "2024-05-01".to_string(), None, Some(dest.clone()))
+                    .await
+                    .map(drop),
+            ),
+            (
+                "create_backup",
+                create_backup(state(), handle.clone(), dest.clone(), false, None, None)
+                    .await
+                    .map(drop),
+            ),
+            (
+                "generate_weekly_report",
+                generate_weekly_report(state(), None, None).await.map(drop),
+            ),
+            (
+                "summarize_range",
+                summarize_range(
+                    state(),
+                    "2024-05-01T09:00:00+00:00".to_string(),
+                    "2024-05-01T09:01:00+00:00".to_string(),
+                )
+                .await
+                .map(drop),
+            ),
+            (
+                "get_goal_progress",
+                get_goal_progress(state(), None).await.map(drop),
+            ),
+            (
+                "get_break_stats",
+                get_break_stats(state(), None).await.map(drop),
+            ),
+            (
+                "import_data",
+                import_data(state(), dest.clone(), false).await.map(drop),
+            ),
+            (
+                "export_settings",
+                export_settings(state(), dest.clone(), None).await.map(drop),
+            ),
+            (
+                "get_gemini_api_key",
+                get_gemini_api_key(state()).await.map(drop),
+            ),
+            (
+                "add_webhook",
+                add_webhook(state(), "https://example.com/hook".to_string(), None)
+                    .await
+                    .map(drop),
+            ),
+            (
+                "update_webhook",
+                update_webhook(
+                    state(),
+                    1,
+                    "https://example.com/hook".to_string(),
+                    None,
+                    true,
+                )
+                .await
+                .map(drop),
+            ),
+            ("test_webhook", test_webhook(state(), 1).await.map(drop)),
+            (
+                "get_webhook_secret",
+                get_webhook_secret(state(), 1).await.map(drop),
+            ),
+            (
+                "add_summary_hook",
+                add_summary_hook(state(), "/bin/true".to_string(), None, None)
+                    .await
+                    .map(drop),
+            ),
+            (
+                "update_summary_hook",
+                update_summary_hook(state(), 1, "/bin/true".to_string(), None, None, true)
+                    .await
+                    .map(drop),
+            ),
+            (
+                "test_summary_hook",
+                test_summary_hook(state(), 1).await.map(drop),
+            ),
+        ];
+
+        for (command, result) in results {
+            assert!(
+                matches!(result, Err(ClarityError::Locked(_))),
+                "{} is not rejected while locked",
+                command
+            );
+        }
+    }
+}
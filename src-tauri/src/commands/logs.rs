@@ -0,0 +1,32 @@
+use crate::error::ClarityError;
+use crate::logging::{self, LogEntry};
+use log::Level;
+use std::str::FromStr;
+
+// 默认返回最近 200 条日志
+const DEFAULT_RECENT_LOG_LINES: usize = 200;
+
+// 获取最近的日志（从新到旧），用于设置页的诊断视图，level 为最低级别，例如 "warn" 只返回警告和错误
+#[tauri::command]
+pub async fn get_recent_logs(
+    lines: Option<usize>,
+    level: Option<String>,
+) -> Result<Vec<LogEntry>, ClarityError> {
+    let lines = lines
+        .unwrap_or(DEFAULT_RECENT_LOG_LINES)
+        .clamp(1, logging::MAX_RECENT_LOG_LINES);
+    let min_level = match level.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+        Some(level) => Some(Level::from_str(level).map_err(|_| {
+            ClarityError::InvalidInput(format!(
+                "Log level must be one of: {}",
+                logging::LOG_LEVELS.join(", ")
+            ))
+        })?),
+        None => None,
+    };
+
+    tokio::task::spawn_blocking(move || logging::read_recent_logs(lines, min_level))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(ClarityError::from)
+}
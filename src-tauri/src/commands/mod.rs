@@ -1,9 +1,53 @@
+pub mod api_server;
+pub mod backup;
+pub mod breaks;
+pub mod config;
 pub mod data;
+pub mod deep_link;
+pub mod export;
+pub mod goals;
+pub mod health;
+pub mod hooks;
+pub mod incognito;
+pub mod lock;
+pub mod logs;
+pub mod notifications;
+pub mod onboarding;
+pub mod profiles;
 pub mod recording;
+pub mod redaction;
+pub mod report;
+pub mod retention;
 pub mod settings;
+pub mod storage;
 pub mod summary;
+pub mod tags;
+pub mod webhooks;
+pub mod wipe;
 
+pub use api_server::*;
+pub use backup::*;
+pub use breaks::*;
+pub use config::*;
 pub use data::*;
+pub use deep_link::*;
+pub use export::*;
+pub use goals::*;
+pub use health::*;
+pub use hooks::*;
+pub use incognito::*;
+pub use lock::*;
+pub use logs::*;
+pub use notifications::*;
+pub use onboarding::*;
+pub use profiles::*;
 pub use recording::*;
+pub use redaction::*;
+pub use report::*;
+pub use retention::*;
 pub use settings::*;
+pub use storage::*;
 pub use summary::*;
+pub use tags::*;
+pub use webhooks::*;
+pub use wipe::*;
@@ -0,0 +1,172 @@
+use crate::error::ClarityError;
+use crate::locale;
+use crate::settings;
+use crate::state::AppState;
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, State};
+use tauri_plugin_notification::NotificationExt;
+
+// 默认免打扰时段（本地时间）
+const DEFAULT_QUIET_HOURS_START: &str = "22:00";
+const DEFAULT_QUIET_HOURS_END: &str = "08:00";
+
+// 通知内容最多显示的字符数
+const NOTIFICATION_BODY_CHARS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationSettings {
+    pub summary: bool,       // 每段摘要生成后通知
+    pub daily_summary: bool, // 自动生成每日总结后通知
+    pub quiet_hours_enabled: bool,
+    pub quiet_hours_start: String, // 本地时间 HH:MM，可以跨过午夜
+    pub quiet_hours_end: String,
+}
+
+// 摘要类型，决定使用哪个开关和通知标题
+#[derive(Debug, Clone, Copy)]
+pub enum SummaryNotification {
+    Summary,
+    DailySummary,
+}
+
+async fn load_notification_settings(db_pool: &SqlitePool) -> NotificationSettings {
+    NotificationSettings {
+        summary: settings::load_notify_summary_from_db(db_pool)
+            .await
+            .unwrap_or(false),
+        daily_summary: settings::load_notify_daily_summary_from_db(db_pool)
+            .await
+            .unwrap_or(false),
+        quiet_hours_enabled: settings::load_quiet_hours_enabled_from_db(db_pool)
+            .await
+            .unwrap_or(false),
+        quiet_hours_start: settings::load_quiet_hours_start_from_db(db_pool)
+            .await
+            .unwrap_or_else(|_| DEFAULT_QUIET_HOURS_START.to_string()),
+        quiet_hours_end: settings::load_quiet_hours_end_from_db(db_pool)
+            .await
+            .unwrap_or_else(|_| DEFAULT_QUIET_HOURS_END.to_string()),
+    }
+}
+
+// 当前时间是否在免打扰时段内（开始时间晚于结束时间时表示跨过午夜）
+fn in_quiet_hours(settings: &NotificationSettings, now: NaiveTime) -> bool {
+    if !settings.quiet_hours_enabled {
+        return false;
+    }
+    let (Ok(start), Ok(end)) = (
+        NaiveTime::parse_from_str(&settings.quiet_hours_start, "%H:%M"),
+        NaiveTime::parse_from_str(&settings.quiet_hours_end, "%H:%M"),
+    ) else {
+        return false;
+    };
+
+    if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+// 摘要的第一行非空文字（去掉 Markdown 标题和列表符号）
+fn first_line(content: &str) -> String {
+    let line = content
+        .lines()
+        .map(|line| line.trim_start_matches(['#', '-', '*', '>', ' ']).trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    match line.char_indices().nth(NOTIFICATION_BODY_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+// 摘要生成后按设置发送系统通知，通知失败只记录日志
+pub async fn notify_summary_ready(
+    app_handle: Option<&AppHandle>,
+    db_pool: &SqlitePool,
+    kind: SummaryNotification,
+    content: &str,
+) {
+    let Some(handle) = app_handle else {
+        return;
+    };
+
+    let notification_settings = load_notification_settings(db_pool).await;
+    let enabled = match kind {
+        SummaryNotification::Summary => notification_settings.summary,
+        SummaryNotification::DailySummary => notification_settings.daily_summary,
+    };
+    if !enabled || in_quiet_hours(&notification_settings, Local::now().time()) {
+        return;
+    }
+
+    let language = settings::load_language_from_db(db_pool)
+        .await
+        .unwrap_or_else(|_| locale::DEFAULT_LANGUAGE.to_string());
+    let title = match (kind, locale::is_chinese(&language)) {
+        (SummaryNotification::Summary, true) => "摘要已生成",
+        (SummaryNotification::Summary, false) => "Summary ready",
+        (SummaryNotification::DailySummary, true) => "每日总结已生成",
+        (SummaryNotification::DailySummary, false) => "Daily summary ready",
+    };
+
+    if let Err(e) = handle
+        .notification()
+        .builder()
+        .title(title)
+        .body(first_line(content))
+        .show()
+    {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
+
+// 获取通知设置
+#[tauri::command]
+pub async fn get_notification_settings(
+    state: State<'_, AppState>,
+) -> Result<NotificationSettings, ClarityError> {
+    Ok(load_notification_settings(&state.db_pool).await)
+}
+
+// 设置摘要生成后是否发送系统通知，以及免打扰时段（本地时间 HH:MM）
+#[tauri::command]
+pub async fn set_notification_settings(
+    state: State<'_, AppState>,
+    notifications: NotificationSettings,
+) -> Result<(), ClarityError> {
+    let mut times = Vec::new();
+    for time in [
+        &notifications.quiet_hours_start,
+        &notifications.quiet_hours_end,
+    ] {
+        let time = NaiveTime::parse_from_str(time.trim(), "%H:%M")
+            .map_err(|e| ClarityError::InvalidInput(format!("Invalid time format: {}", e)))?;
+        times.push(time.format("%H:%M").to_string());
+    }
+
+    settings::save_notification_settings_to_db(
+        &state.db_pool,
+        notifications.summary,
+        notifications.daily_summary,
+        notifications.quiet_hours_enabled,
+        &times[0],
+        &times[1],
+    )
+    .await
+    .map_err(ClarityError::from)?;
+
+    log::info!(
+        "Notification settings updated: summary={}, daily summary={}, quiet hours={} ({}-{})",
+        notifications.summary,
+        notifications.daily_summary,
+        notifications.quiet_hours_enabled,
+        times[0],
+        times[1]
+    );
+    Ok(())
+}
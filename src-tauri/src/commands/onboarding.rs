@@ -0,0 +1,90 @@
+use crate::commands::recording::stop_recording_session;
+use crate::error::ClarityError;
+use crate::events;
+use crate::onboarding::{self, OnboardingState};
+use crate::settings;
+use crate::state::AppState;
+use tauri::State;
+
+// 保存用户的选择并更新内存中的状态；撤回截图同意时停止正在进行的录制
+pub async fn set_consent(
+    state: &AppState,
+    consent: &str,
+    granted: bool,
+) -> Result<(), ClarityError> {
+    onboarding::record_consent(&state.db_pool, consent, granted)
+        .await
+        .map_err(ClarityError::from)?;
+    if consent == onboarding::CONSENT_AI_UPLOAD {
+        *state.ai_upload_consent.lock().await = granted;
+    } else if !granted && stop_recording_session(state).await {
+        log::info!("Recording stopped because screen capture consent was withdrawn");
+    }
+    Ok(())
+}
+
+async fn emit_onboarding_changed(state: &AppState) -> OnboardingState {
+    let onboarding_state = onboarding::load_state(&state.db_pool).await;
+    state
+        .emit(events::ONBOARDING_CHANGED, onboarding_state.clone())
+        .await;
+    onboarding_state
+}
+
+// 获取引导流程的当前步骤和同意记录
+#[tauri::command]
+pub async fn get_onboarding_state(
+    state: State<'_, AppState>,
+) -> Result<OnboardingState, ClarityError> {
+    Ok(onboarding::load_state(&state.db_pool).await)
+}
+
+// 完成当前步骤并进入下一步：step 必须是当前步骤（防止重复提交），
+// screen_capture 和 ai_upload 步骤需要传入 granted（同意或拒绝都会记录，拒绝不影响进入下一步）
+#[tauri::command]
+pub async fn advance_onboarding(
+    state: State<'_, AppState>,
+    step: String,
+    granted: Option<bool>,
+) -> Result<OnboardingState, ClarityError> {
+    let current = onboarding::load_state(&state.db_pool).await;
+    if current.completed {
+        return Err(ClarityError::Conflict(
+            "Onboarding is already completed".to_string(),
+        ));
+    }
+    if step != current.step {
+        return Err(ClarityError::Conflict(format!(
+            "Onboarding is at step '{}', not '{}'",
+            current.step, step
+        )));
+    }
+
+    if onboarding::is_valid_consent(&step) {
+        let granted = granted.ok_or_else(|| {
+            ClarityError::InvalidInput(format!("Step '{}' requires granted", step))
+        })?;
+        set_consent(&state, &step, granted).await?;
+    }
+    let next = onboarding::next_step(&step);
+    settings::save_onboarding_step_to_db(&state.db_pool, next).await?;
+    log::info!("Onboarding advanced to {}", next);
+
+    Ok(emit_onboarding_changed(&state).await)
+}
+
+// 引导完成后修改同意（设置页使用），consent 为 "screen_capture" 或 "ai_upload"
+#[tauri::command]
+pub async fn set_onboarding_consent(
+    state: State<'_, AppState>,
+    consent: String,
+    granted: bool,
+) -> Result<OnboardingState, ClarityError> {
+    if !onboarding::is_valid_consent(&consent) {
+        return Err(ClarityError::InvalidInput(
+            "Consent must be 'screen_capture' or 'ai_upload'".to_string(),
+        ));
+    }
+    set_consent(&state, &consent, granted).await?;
+    Ok(emit_onboarding_changed(&state).await)
+}
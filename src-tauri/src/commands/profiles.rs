@@ -0,0 +1,44 @@
+use crate::commands::recording::shutdown;
+use crate::error::ClarityError;
+use crate::profiles::{self, Profile};
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+// 列出所有配置
+#[tauri::command]
+pub async fn list_profiles() -> Result<Vec<Profile>, ClarityError> {
+    Ok(profiles::list_profiles())
+}
+
+// 创建配置（有独立的数据库、截图目录、提示词和定时设置）
+#[tauri::command]
+pub async fn create_profile(name: String) -> Result<Profile, ClarityError> {
+    let profile = profiles::create_profile(&name)?;
+    log::info!("Profile created: {}", profile.name);
+    Ok(profile)
+}
+
+// 切换配置：数据库连接和后台任务都在启动时创建，切换后重启应用
+#[tauri::command]
+pub async fn switch_profile(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    name: String,
+) -> Result<(), ClarityError> {
+    let name = profiles::validate_profile_name(&name).map_err(ClarityError::InvalidInput)?;
+    if name == profiles::active_profile() {
+        return Ok(());
+    }
+    if state.recording.is_active() {
+        return Err(ClarityError::Conflict(
+            "Stop recording before switching profiles".to_string(),
+        ));
+    }
+
+    profiles::set_next_profile(&name)?;
+    log::info!("Switching to profile '{}', restarting", name);
+
+    // 和正常退出一样收尾（保存正常退出标记、关闭连接池），重启时退出回调不再重复执行
+    shutdown(&state).await;
+    app.restart()
+}
@@ -1,135 +1,306 @@
-use crate::commands::summary::video_summary_loop;
+use crate::commands::breaks::break_reminder_loop;
+use crate::commands::summary::stop_summary_loop;
+use crate::db;
+use crate::error::ClarityError;
+use crate::onboarding;
 use crate::screenshot;
-use crate::state::AppState;
+use crate::settings;
+use crate::state::{AppState, RecordingState};
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 use tauri::State;
 
+// 退出时最后一段不足这个时长（秒）的录制不再总结
+const MIN_FINAL_SUMMARY_SECONDS: i64 = 10;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScreenshotStatus {
-    pub is_recording: bool,
-    pub screenshots_count: u64,
+    pub is_recording: bool, // 录制会话进行中（包括暂停）
+    pub is_paused: bool,
+    pub screenshots_count: u64, // 本次录制会话（没有录制时为最近一次）的截图数，重启后从数据库恢复
+    pub total_screenshots_count: u64, // 数据库中的截图总数（不包括保留策略已经删除的）
     pub storage_path: String,
 }
 
 #[tauri::command]
-pub async fn start_recording(state: State<'_, AppState>) -> Result<ScreenshotStatus, String> {
-    log::info!("Starting recording");
-    let mut is_recording = state.is_recording.lock().await;
+pub async fn start_recording(state: State<'_, AppState>) -> Result<ScreenshotStatus, ClarityError> {
+    begin_recording(&state).await
+}
 
-    if *is_recording {
+// 开始新的录制会话（桌面应用和命令行模式共用）
+pub async fn begin_recording(state: &AppState) -> Result<ScreenshotStatus, ClarityError> {
+    begin_recording_session(state, Local::now()).await
+}
+
+// 开始录制：启动截图和休息提醒任务，session_started_at 为录制会话的开始时间（恢复录制时沿用上次的时间）
+// 总结定时任务在程序启动时由 start_summary_loop 启动，根据录制状态自动开始计时
+async fn begin_recording_session(
+    state: &AppState,
+    session_started_at: DateTime<Local>,
+) -> Result<ScreenshotStatus, ClarityError> {
+    log::info!("Starting recording");
+    if !onboarding::is_granted(&state.db_pool, onboarding::CONSENT_SCREEN_CAPTURE).await {
+        return Err(ClarityError::ConsentRequired(
+            "Consent to capture the screen has not been given".to_string(),
+        ));
+    }
+    if !state.recording.start() {
         log::warn!("Recording is already in progress");
-        return Err("Recording is already in progress".to_string());
+        return Err(ClarityError::Conflict(
+            "Recording is already in progress".to_string(),
+        ));
     }
-
-    *is_recording = true;
     log::info!("Recording started");
-
-    let storage_path = state.storage_path.lock().await.clone();
-    let is_recording_clone = state.is_recording.clone();
-    let screenshots_count_clone = state.screenshots_count.clone();
-    let db_pool = state.db_pool.clone();
-
-    // 克隆 storage_path 用于两个任务
-    let storage_path_screenshot = storage_path.clone();
-    let storage_path_summary = storage_path.clone();
+    remember_recording_active(state, true).await;
+    restore_session_count(state, session_started_at).await;
 
     // 启动截图任务
-    let app_handle_screenshot = state.app_handle.lock().await.clone();
-    let handle = tokio::spawn(async move {
-        screenshot::screenshot_loop(
-            storage_path_screenshot,
-            is_recording_clone.clone(),
-            screenshots_count_clone,
-            db_pool.clone(),
-            app_handle_screenshot,
-        )
-        .await;
-    });
-
-    // 启动视频总结任务
-    let db_pool_summary = state.db_pool.clone();
-    let is_recording_summary = state.is_recording.clone();
-    let api_key_summary = state.gemini_api_key.clone();
-    let summary_interval_summary = state.summary_interval_seconds.clone();
-    let app_handle_summary = state.app_handle.lock().await.clone();
-    let ai_model_summary = state.ai_model.clone();
-    // 注意：ai_prompt 不再需要传递，因为 video_summary_loop 会根据语言从数据库加载
-    let _ai_prompt_summary = state._ai_prompt.clone(); // 保留以兼容函数签名，但实际不再使用
-    let video_resolution_summary = state.video_resolution.clone();
-    let summary_handle = tokio::spawn(async move {
-        log::info!("Starting video summary background task");
-        video_summary_loop(
-            storage_path_summary,
-            db_pool_summary,
-            is_recording_summary,
-            api_key_summary,
-            summary_interval_summary,
-            app_handle_summary,
-            ai_model_summary,
-            _ai_prompt_summary,
-            video_resolution_summary,
-        )
-        .await;
-        log::warn!("Video summary loop exited unexpectedly");
-    });
-
-    // 监控总结任务（如果出错会记录日志）
+    let capture_context = screenshot::CaptureContext::new(state).await;
+    let recording_screenshot = state.recording.subscribe();
+    let handle = tokio::spawn(screenshot::screenshot_loop(
+        capture_context,
+        recording_screenshot,
+    ));
+
+    // 启动休息提醒（录制停止后自动退出）
+    let db_pool_break = state.db_pool.clone();
+    let recording_break = state.recording.subscribe();
+    let app_handle_break = state.app_handle.lock().await.clone();
     tokio::spawn(async move {
-        if let Err(e) = summary_handle.await {
-            log::error!("Video summary task panicked: {:?}", e);
-        }
+        break_reminder_loop(db_pool_break, recording_break, app_handle_break).await;
     });
 
     *state.handle.lock().await = Some(handle);
 
-    let storage_path_str = state
-        .storage_path
-        .lock()
+    Ok(current_status(state).await)
+}
+
+// 保存录制会话的开始时间，并从数据库统计这次会话已有的截图数（新会话为 0）
+async fn restore_session_count(state: &AppState, session_started_at: DateTime<Local>) {
+    if let Err(e) =
+        settings::save_recording_session_started_at_to_db(&state.db_pool, session_started_at).await
+    {
+        log::error!("Failed to save recording session start: {}", e);
+    }
+    let count = db::get_screenshot_count(&state.db_pool, Some(session_started_at), None)
         .await
-        .to_string_lossy()
-        .to_string();
+        .unwrap_or_else(|e| {
+            log::error!("Failed to count session screenshots: {}", e);
+            0
+        });
+    *state.screenshots_count.lock().await = count.max(0) as u64;
+}
 
-    Ok(ScreenshotStatus {
-        is_recording: true,
-        screenshots_count: 0,
-        storage_path: storage_path_str,
-    })
+#[tauri::command]
+pub async fn stop_recording(state: State<'_, AppState>) -> Result<ScreenshotStatus, ClarityError> {
+    if !stop_recording_session(&state).await {
+        return Err(ClarityError::Conflict(
+            "Recording is not in progress".to_string(),
+        ));
+    }
+
+    Ok(current_status(&state).await)
+}
+
+// 停止录制并等待截图写完，没有在录制时返回 false
+pub async fn stop_recording_session(state: &AppState) -> bool {
+    if !state.recording.stop() {
+        return false;
+    }
+    remember_recording_active(state, false).await;
+    wait_for_screenshot_task(state).await;
+    true
 }
 
 #[tauri::command]
-pub async fn stop_recording(state: State<'_, AppState>) -> Result<ScreenshotStatus, String> {
-    let mut is_recording = state.is_recording.lock().await;
+pub async fn get_status(state: State<'_, AppState>) -> Result<ScreenshotStatus, ClarityError> {
+    Ok(current_status(&state).await)
+}
 
-    if !*is_recording {
-        return Err("Recording is not in progress".to_string());
+// 暂停录制：截图和总结任务保持运行但跳过本次工作，恢复后继续
+#[tauri::command]
+pub async fn pause_recording(state: State<'_, AppState>) -> Result<ScreenshotStatus, ClarityError> {
+    if !state.recording.pause() {
+        return Err(ClarityError::Conflict(
+            "Recording is not in progress".to_string(),
+        ));
     }
+    log::info!("Recording paused");
+    Ok(current_status(&state).await)
+}
 
-    *is_recording = false;
+#[tauri::command]
+pub async fn resume_recording(
+    state: State<'_, AppState>,
+) -> Result<ScreenshotStatus, ClarityError> {
+    if !state.recording.resume() {
+        return Err(ClarityError::Conflict(
+            "Recording is not paused".to_string(),
+        ));
+    }
+    log::info!("Recording resumed");
+    Ok(current_status(&state).await)
+}
 
-    // 等待任务完成
-    if let Some(handle) = state.handle.lock().await.take() {
-        handle.abort();
+// 记录录制是否在进行中，用于下次启动时恢复录制（退出时停止录制不清除）
+async fn remember_recording_active(state: &AppState, active: bool) {
+    if let Err(e) = settings::save_recording_active_to_db(&state.db_pool, active).await {
+        log::error!("Failed to save recording state: {}", e);
     }
+}
 
-    let screenshots_count = *state.screenshots_count.lock().await;
-    let storage_path_str = state
-        .storage_path
-        .lock()
+// 启动时恢复上次退出时仍在进行的录制：
+// 上次异常退出（崩溃、断电等）时记录空白时间段并总是恢复，正常退出时按“启动时恢复录制”设置决定
+pub async fn resume_recording_after_launch(state: &AppState) {
+    // 没有记录时按正常退出处理（首次运行）
+    let clean_shutdown = settings::load_clean_shutdown_from_db(&state.db_pool)
         .await
-        .to_string_lossy()
-        .to_string();
+        .unwrap_or(true);
+    // 本次运行先标记为未正常退出，正常退出时在 shutdown 中改回
+    if let Err(e) = settings::save_clean_shutdown_to_db(&state.db_pool, false).await {
+        log::error!("Failed to save shutdown state: {}", e);
+    }
 
-    Ok(ScreenshotStatus {
-        is_recording: false,
-        screenshots_count,
-        storage_path: storage_path_str,
-    })
+    let was_recording = settings::load_recording_active_from_db(&state.db_pool)
+        .await
+        .unwrap_or(false);
+    if !was_recording {
+        return;
+    }
+
+    if clean_shutdown {
+        let enabled = settings::load_resume_recording_on_launch_from_db(&state.db_pool)
+            .await
+            .unwrap_or(false);
+        if !enabled {
+            return;
+        }
+        log::info!("Resuming recording from the previous session");
+    } else {
+        log::warn!("Previous session ended unexpectedly while recording, resuming");
+        record_crash_gap(&state.db_pool).await;
+    }
+
+    // 恢复的录制属于同一个会话，截图数接着上次统计
+    let session_started_at = settings::load_recording_session_started_at_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| Local::now());
+    if let Err(e) = begin_recording_session(state, session_started_at).await {
+        log::error!("Failed to resume recording: {}", e);
+    }
 }
 
-#[tauri::command]
-pub async fn get_status(state: State<'_, AppState>) -> Result<ScreenshotStatus, String> {
-    let is_recording = *state.is_recording.lock().await;
+// 从最后一张截图到现在记为一段录制空白，统计中会标出这段中断
+async fn record_crash_gap(db_pool: &SqlitePool) {
+    let last_screenshot = match db::get_screenshot_traces(db_pool, None, None, Some(1)).await {
+        Ok(traces) => traces.into_iter().next().map(|t| t.timestamp),
+        Err(e) => {
+            log::error!("Failed to load the last screenshot: {}", e);
+            return;
+        }
+    };
+    let Some(start_time) = last_screenshot else {
+        return;
+    };
+
+    let end_time = Local::now();
+    if end_time <= start_time {
+        return;
+    }
+    match db::insert_recording_gap(db_pool, start_time, end_time, "crash").await {
+        Ok(_) => log::info!(
+            "Recorded a recording gap of {} seconds",
+            (end_time - start_time).num_seconds()
+        ),
+        Err(e) => log::error!("Failed to record recording gap: {}", e),
+    }
+}
+
+// 停止录制通过录制状态通知截图任务，不会中途取消任务：
+// 截图任务会把已经截取的图片全部编码、写入文件和数据库后再退出，等待它结束
+pub async fn wait_for_screenshot_task(state: &AppState) {
+    let handle = state.handle.lock().await.take();
+    if let Some(handle) = handle {
+        if let Err(e) = handle.await {
+            log::error!("Screenshot task panicked: {:?}", e);
+        }
+    }
+}
+
+// 程序退出前的收尾：停止录制并等待截图写完（同时写入未保存的应用使用时长），
+// 按设置把最后不足一个间隔的时间段加入总结队列（下次启动时处理），最后关闭数据库
+pub async fn shutdown(state: &AppState) {
+    log::info!("Shutting down");
+    // 先停止总结定时任务：退出时是否总结最后一段由“退出时总结”设置决定，而不是停止录制时的自动入队
+    stop_summary_loop(state).await;
+    let was_recording = state.recording.state() == RecordingState::Recording;
+    if state.recording.stop() {
+        wait_for_screenshot_task(state).await;
+    }
+
+    let summarize_on_exit = settings::load_summarize_on_exit_from_db(&state.db_pool)
+        .await
+        .unwrap_or(false);
+    if was_recording && summarize_on_exit {
+        queue_final_summary(state).await;
+    }
+
+    if let Err(e) = settings::save_clean_shutdown_to_db(&state.db_pool, true).await {
+        log::error!("Failed to save shutdown state: {}", e);
+    }
+
+    // 关闭连接池，把 WAL 合并到数据库文件
+    state.db_pool.close().await;
+
+    // 临时模式：删除这次运行的截图和总结视频
+    if state.ephemeral {
+        let storage_path = state.storage_path.lock().await.clone();
+        if let Err(e) = tokio::fs::remove_dir_all(&storage_path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::error!("Failed to delete {}: {}", storage_path.display(), e);
+            }
+        }
+    }
+    log::info!("Shutdown complete");
+}
+
+// 从上一个总结任务结束（最多往前一个总结间隔）到现在的时间段加入总结队列
+async fn queue_final_summary(state: &AppState) {
+    let interval = *state.summary_interval_seconds.lock().await as i64;
+    let end_time = Local::now();
+    let earliest = end_time - chrono::Duration::seconds(interval);
+    let start_time = match db::get_summary_jobs(&state.db_pool, None, Some(1)).await {
+        Ok(jobs) => jobs
+            .first()
+            .map(|job| job.end_time)
+            .filter(|t| *t > earliest)
+            .unwrap_or(earliest),
+        Err(e) => {
+            log::error!("Failed to load summary jobs: {}", e);
+            return;
+        }
+    };
+
+    if (end_time - start_time).num_seconds() < MIN_FINAL_SUMMARY_SECONDS {
+        return;
+    }
+    match db::insert_summary_job(&state.db_pool, start_time, end_time).await {
+        Ok(id) => log::info!("Queued final summary job {} before exit", id),
+        Err(e) => log::error!("Failed to queue final summary job: {}", e),
+    }
+}
+
+async fn current_status(state: &AppState) -> ScreenshotStatus {
+    let recording = state.recording.state();
     let screenshots_count = *state.screenshots_count.lock().await;
+    let total_screenshots_count = db::get_screenshot_count(&state.db_pool, None, None)
+        .await
+        .unwrap_or_else(|e| {
+            log::error!("Failed to count screenshots: {}", e);
+            0
+        })
+        .max(0) as u64;
     let storage_path_str = state
         .storage_path
         .lock()
@@ -137,15 +308,17 @@ pub async fn get_status(state: State<'_, AppState>) -> Result<ScreenshotStatus,
         .to_string_lossy()
         .to_string();
 
-    Ok(ScreenshotStatus {
-        is_recording,
+    ScreenshotStatus {
+        is_recording: recording != RecordingState::Stopped,
+        is_paused: recording == RecordingState::Paused,
         screenshots_count,
+        total_screenshots_count,
         storage_path: storage_path_str,
-    })
+    }
 }
 
 #[tauri::command]
-pub async fn get_storage_path(state: State<'_, AppState>) -> Result<String, String> {
+pub async fn get_storage_path(state: State<'_, AppState>) -> Result<String, ClarityError> {
     let storage_path_str = state
         .storage_path
         .lock()
@@ -156,13 +329,13 @@ pub async fn get_storage_path(state: State<'_, AppState>) -> Result<String, Stri
 }
 
 #[tauri::command]
-pub async fn test_screenshot() -> Result<String, String> {
+pub async fn test_screenshot() -> Result<String, ClarityError> {
     // 测试截图功能，返回截图信息
     let result = tokio::task::spawn_blocking(|| {
         let monitors = xcap::Monitor::all().map_err(|e| format!("Failed to get monitors: {}", e))?;
 
         if monitors.is_empty() {
-            return Err("No monitors found".to_string());
+            return Err(ClarityError::NotFound("No monitors found".to_string()));
         }
 
         let monitor = monitors.into_iter().next().unwrap();
@@ -176,10 +349,10 @@ pub async fn test_screenshot() -> Result<String, String> {
 
         // 尝试截图
         let image = monitor.capture_image().map_err(|e| {
-            format!(
+            ClarityError::Permission(format!(
                 "Capture failed: {}. On macOS, ensure Screen Recording permission is granted in System Settings > Privacy & Security > Screen Recording",
                 e
-            )
+            ))
         })?;
 
         let width = image.width();
@@ -0,0 +1,32 @@
+use crate::error::ClarityError;
+use crate::redaction::{self, RedactionRule};
+use crate::settings;
+use crate::state::AppState;
+use tauri::State;
+
+// 获取按应用设置的隐私规则
+#[tauri::command]
+pub async fn get_redaction_rules(
+    state: State<'_, AppState>,
+) -> Result<Vec<RedactionRule>, ClarityError> {
+    Ok(state.redaction_rules.lock().await.clone())
+}
+
+// 替换全部隐私规则：截图规则从下一张截图开始生效，
+// local_only 规则同时对还没有总结的截图生效
+#[tauri::command]
+pub async fn set_redaction_rules(
+    state: State<'_, AppState>,
+    rules: Vec<RedactionRule>,
+) -> Result<Vec<RedactionRule>, ClarityError> {
+    let rules = redaction::validate_rules(rules).map_err(ClarityError::InvalidInput)?;
+
+    settings::save_redaction_rules_to_db(&state.db_pool, &rules)
+        .await
+        .map_err(ClarityError::from)?;
+    *state.redaction_rules.lock().await = rules.clone();
+
+    log::info!("Redaction rules updated ({} rules)", rules.len());
+
+    Ok(rules)
+}
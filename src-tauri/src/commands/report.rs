@@ -0,0 +1,312 @@
+use crate::commands::goals::day_range;
+use crate::commands::lock;
+use crate::db;
+use crate::error::ClarityError;
+use crate::screenshot;
+use crate::state::AppState;
+use chrono::{Datelike, Local, NaiveDate, Weekday};
+use tauri::State;
+
+// 周报的格式
+pub const REPORT_FORMATS: [&str; 2] = ["markdown", "html"];
+
+// 某一天的统计
+struct ReportDay {
+    date: NaiveDate,
+    tracked_seconds: i64,
+    summary_count: usize,
+    screenshot_count: i64,
+    focus_score: Option<f64>, // 按摘要时长加权的平均专注度，没有专注度时为 None
+    daily_summary: Option<String>,
+}
+
+// 一周的数据，Markdown 和 HTML 使用同样的内容
+struct WeeklyReport {
+    week: String, // YYYY-Www
+    start: NaiveDate,
+    end: NaiveDate,
+    days: Vec<ReportDay>,
+    categories: Vec<db::CategoryTotal>,
+    focus_score: Option<f64>,
+    api_stats: db::ApiStatistics,
+}
+
+impl WeeklyReport {
+    fn tracked_seconds(&self) -> i64 {
+        self.days.iter().map(|d| d.tracked_seconds).sum()
+    }
+
+    fn summary_count(&self) -> usize {
+        self.days.iter().map(|d| d.summary_count).sum()
+    }
+
+    fn screenshot_count(&self) -> i64 {
+        self.days.iter().map(|d| d.screenshot_count).sum()
+    }
+
+    // 分类时长占所有分类时长的百分比
+    fn category_share(&self, total: &db::CategoryTotal) -> i64 {
+        let all: i64 = self.categories.iter().map(|c| c.duration_seconds).sum();
+        if all == 0 {
+            0
+        } else {
+            (total.duration_seconds as f64 * 100.0 / all as f64).round() as i64
+        }
+    }
+}
+
+// 解析 ISO 周（例如 2024-W18），返回这一周的周一
+fn parse_week(week: &str) -> Result<NaiveDate, String> {
+    let invalid = || format!("Invalid week format: {} (expected YYYY-Www)", week);
+    let (year, number) = week.split_once("-W").ok_or_else(invalid)?;
+    let year: i32 = year.parse().map_err(|_| invalid())?;
+    let number: u32 = number.parse().map_err(|_| invalid())?;
+    NaiveDate::from_isoywd_opt(year, number, Weekday::Mon).ok_or_else(invalid)
+}
+
+fn format_duration(seconds: i64) -> String {
+    format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60)
+}
+
+fn format_focus(score: Option<f64>) -> String {
+    score.map_or_else(|| "-".to_string(), |s| format!("{:.0}", s))
+}
+
+// 按时长加权的平均专注度（时长为 0 的摘要也至少算 1 秒）
+fn weighted_focus(scores: &[(i64, i64)]) -> Option<f64> {
+    if scores.is_empty() {
+        return None;
+    }
+    let (sum, weight) = scores
+        .iter()
+        .fold((0.0, 0.0), |(sum, weight), (score, seconds)| {
+            let seconds = (*seconds).max(1) as f64;
+            (sum + *score as f64 * seconds, weight + seconds)
+        });
+    Some(sum / weight)
+}
+
+async fn load_report(state: &AppState, monday: NaiveDate) -> Result<WeeklyReport, ClarityError> {
+    let pool = &state.db_pool;
+    let sunday = monday + chrono::Duration::days(6);
+    let (week_start, _) = day_range(monday)?;
+    let (_, week_end) = day_range(sunday)?;
+
+    let mut days = Vec::with_capacity(7);
+    let mut week_scores = Vec::new();
+    for date in monday.iter_days().take(7) {
+        let (start_time, end_time) = day_range(date)?;
+        let summaries = db::get_summaries(pool, Some(start_time), Some(end_time), None)
+            .await
+            .map_err(ClarityError::from)?;
+        let screenshot_count = db::get_screenshot_count(pool, Some(start_time), Some(end_time))
+            .await
+            .map_err(ClarityError::from)?;
+        let daily_summary = db::get_daily_summary(pool, &date.format("%Y-%m-%d").to_string())
+            .await
+            .map_err(ClarityError::from)?;
+
+        let scores: Vec<(i64, i64)> = summaries
+            .iter()
+            .filter_map(|s| {
+                let seconds = (s.end_time - s.start_time).num_seconds().max(0);
+                s.focus_score.map(|score| (score, seconds))
+            })
+            .collect();
+        week_scores.extend_from_slice(&scores);
+
+        days.push(ReportDay {
+            date,
+            tracked_seconds: summaries
+                .iter()
+                .map(|s| (s.end_time - s.start_time).num_seconds().max(0))
+                .sum(),
+            summary_count: summaries.len(),
+            screenshot_count,
+            focus_score: weighted_focus(&scores),
+            daily_summary: daily_summary.map(|d| d.content.trim().to_string()),
+        });
+    }
+
+    let categories = db::get_category_totals(pool, Some(week_start), Some(week_end))
+        .await
+        .map_err(ClarityError::from)?;
+    let api_stats = db::get_api_statistics(pool, Some(week_start), Some(week_end))
+        .await
+        .map_err(ClarityError::from)?;
+
+    Ok(WeeklyReport {
+        week: format!(
+            "{}-W{:02}",
+            monday.iso_week().year(),
+            monday.iso_week().week()
+        ),
+        start: monday,
+        end: sunday,
+        days,
+        categories,
+        focus_score: weighted_focus(&week_scores),
+        api_stats,
+    })
+}
+
+fn render_markdown(report: &WeeklyReport) -> String {
+    let mut out = format!(
+        "# Weekly report {}\n\n{} to {}\n\n## Overview\n\n",
+        report.week, report.start, report.end
+    );
+    out.push_str(&format!(
+        "- Tracked time: {}\n- Summaries: {}\n- Screenshots: {}\n- Focus score: {}\n- AI cost: ${:.4} ({} requests, {} tokens)\n\n",
+        format_duration(report.tracked_seconds()),
+        report.summary_count(),
+        report.screenshot_count(),
+        format_focus(report.focus_score),
+        report.api_stats.total_cost_usd,
+        report.api_stats.total_requests,
+        report.api_stats.total_tokens
+    ));
+
+    out.push_str("## Days\n\n| Day | Tracked | Summaries | Focus |\n|---|---|---|---|\n");
+    for day in &report.days {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            day.date.format("%a %Y-%m-%d"),
+            format_duration(day.tracked_seconds),
+            day.summary_count,
+            format_focus(day.focus_score)
+        ));
+    }
+
+    if !report.categories.is_empty() {
+        out.push_str("\n## Categories\n\n| Category | Time | Share |\n|---|---|---|\n");
+        for total in &report.categories {
+            out.push_str(&format!(
+                "| {} | {} | {}% |\n",
+                total.category.replace('|', "\\|"),
+                format_duration(total.duration_seconds),
+                report.category_share(total)
+            ));
+        }
+    }
+
+    out.push_str("\n## Daily summaries\n");
+    for day in &report.days {
+        out.push_str(&format!(
+            "\n### {}\n\n{}\n",
+            day.date.format("%A, %Y-%m-%d"),
+            day.daily_summary.as_deref().unwrap_or("_No daily summary_")
+        ));
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 单个 HTML 文件，样式内联，方便直接发送给别人；每日总结是 Markdown 原文，保留换行显示
+fn render_html(report: &WeeklyReport) -> String {
+    let title = format!("Weekly report {}", report.week);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\nbody {{ font-family: -apple-system, BlinkMacSystemFont, \"Segoe UI\", sans-serif; max-width: 800px; margin: 2em auto; padding: 0 1em; color: #111827; }}\ntable {{ border-collapse: collapse; width: 100%; }}\nth, td {{ border-bottom: 1px solid #e5e7eb; padding: 6px 8px; text-align: left; }}\n.summary {{ white-space: pre-wrap; color: #374151; }}\n.muted {{ color: #6b7280; }}\n</style>\n</head>\n<body>\n<h1>{}</h1>\n<p class=\"muted\">{} to {}</p>\n",
+        title, title, report.start, report.end
+    );
+
+    out.push_str(&format!(
+        "<h2>Overview</h2>\n<ul>\n<li>Tracked time: {}</li>\n<li>Summaries: {}</li>\n<li>Screenshots: {}</li>\n<li>Focus score: {}</li>\n<li>AI cost: ${:.4} ({} requests, {} tokens)</li>\n</ul>\n",
+        format_duration(report.tracked_seconds()),
+        report.summary_count(),
+        report.screenshot_count(),
+        format_focus(report.focus_score),
+        report.api_stats.total_cost_usd,
+        report.api_stats.total_requests,
+        report.api_stats.total_tokens
+    ));
+
+    out.push_str("<h2>Days</h2>\n<table>\n<tr><th>Day</th><th>Tracked</th><th>Summaries</th><th>Focus</th></tr>\n");
+    for day in &report.days {
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            day.date.format("%a %Y-%m-%d"),
+            format_duration(day.tracked_seconds),
+            day.summary_count,
+            format_focus(day.focus_score)
+        ));
+    }
+    out.push_str("</table>\n");
+
+    if !report.categories.is_empty() {
+        out.push_str(
+            "<h2>Categories</h2>\n<table>\n<tr><th>Category</th><th>Time</th><th>Share</th></tr>\n",
+        );
+        for total in &report.categories {
+            out.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}%</td></tr>\n",
+                escape_html(&total.category),
+                format_duration(total.duration_seconds),
+                report.category_share(total)
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Daily summaries</h2>\n");
+    for day in &report.days {
+        out.push_str(&format!("<h3>{}</h3>\n", day.date.format("%A, %Y-%m-%d")));
+        match &day.daily_summary {
+            Some(content) => out.push_str(&format!(
+                "<div class=\"summary\">{}</div>\n",
+                escape_html(content)
+            )),
+            None => out.push_str("<p class=\"muted\">No daily summary</p>\n"),
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// 生成一周的报告（每日总结、专注度、分类时长和 AI 费用），保存到存储目录的 exports 中，返回文件路径
+#[tauri::command]
+pub async fn generate_weekly_report(
+    state: State<'_, AppState>,
+    week: Option<String>, // ISO week such as 2024-W18, if None, use the current week
+    format: Option<String>, // "markdown" (default) or "html"
+) -> Result<String, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+
+    let format = format.unwrap_or_else(|| "markdown".to_string());
+    if !REPORT_FORMATS.contains(&format.as_str()) {
+        return Err(ClarityError::InvalidInput(
+            "Format must be 'markdown' or 'html'".to_string(),
+        ));
+    }
+    let monday = match week {
+        Some(week) => parse_week(week.trim()).map_err(ClarityError::InvalidInput)?,
+        None => {
+            let today = Local::now().date_naive();
+            today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64)
+        }
+    };
+
+    let report = load_report(&state, monday).await?;
+    let (content, extension) = if format == "html" {
+        (render_html(&report), "html")
+    } else {
+        (render_markdown(&report), "md")
+    };
+
+    let storage_path = state.storage_path.lock().await.clone();
+    let dir = storage_path.join("exports");
+    screenshot::ensure_dir_exists(&dir).await?;
+    let path = dir.join(format!("weekly_report_{}.{}", report.week, extension));
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    log::info!("Weekly report {} saved to {}", report.week, path.display());
+
+    Ok(path.to_string_lossy().to_string())
+}
@@ -0,0 +1,159 @@
+use crate::commands::data::remove_traces;
+use crate::commands::summary::cleanup_summary_videos;
+use crate::db;
+use crate::error::ClarityError;
+use crate::settings;
+use crate::state::AppState;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::time::Duration as StdDuration;
+use tauri::State;
+
+// 执行保留策略的间隔
+const RETENTION_INTERVAL_SECONDS: u64 = 60 * 60;
+
+// 每批删除的截图数量，避免一次加载过多记录
+const RETENTION_BATCH_SIZE: i64 = 1000;
+
+// 截图和摘要保留天数（0 表示永久保留），总结视频使用单独的视频保留策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionSettings {
+    pub screenshot_days: u64,
+    pub summary_days: u64,
+}
+
+// 一次执行保留策略删除的内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReport {
+    pub screenshots_deleted: u64,
+    pub screenshot_bytes_reclaimed: u64,
+    pub summaries_deleted: u64,
+    pub videos_deleted: u64,
+}
+
+async fn load_retention_settings(db_pool: &SqlitePool) -> RetentionSettings {
+    RetentionSettings {
+        screenshot_days: settings::load_keep_screenshots_days_from_db(db_pool)
+            .await
+            .unwrap_or(0),
+        summary_days: settings::load_keep_summaries_days_from_db(db_pool)
+            .await
+            .unwrap_or(0),
+    }
+}
+
+// 截图删除的截止时间：早于保留天数，同时不能删除还在排队或正在总结的时间段
+async fn screenshot_cutoff(
+    db_pool: &SqlitePool,
+    days: u64,
+) -> Result<DateTime<Local>, ClarityError> {
+    let mut cutoff = Local::now() - chrono::Duration::days(days as i64);
+
+    for status in ["pending", "running"] {
+        let jobs = db::get_summary_jobs(db_pool, Some(status), None)
+            .await
+            .map_err(ClarityError::from)?;
+        if let Some(earliest) = jobs.iter().map(|job| job.start_time).min() {
+            cutoff = cutoff.min(earliest);
+        }
+    }
+
+    Ok(cutoff)
+}
+
+// 按保留策略删除过期的截图、摘要和总结视频
+pub async fn apply_retention(db_pool: &SqlitePool) -> Result<RetentionReport, ClarityError> {
+    let retention = load_retention_settings(db_pool).await;
+    let mut report = RetentionReport::default();
+
+    if retention.screenshot_days > 0 {
+        let cutoff = screenshot_cutoff(db_pool, retention.screenshot_days).await?;
+        loop {
+            let traces =
+                db::get_screenshot_traces(db_pool, None, Some(cutoff), Some(RETENTION_BATCH_SIZE))
+                    .await
+                    .map_err(ClarityError::from)?;
+            if traces.is_empty() {
+                break;
+            }
+
+            let result = remove_traces(db_pool, &traces, false).await?;
+            report.screenshots_deleted += result.trace_count;
+            report.screenshot_bytes_reclaimed += result.reclaimed_bytes;
+            if result.trace_count == 0 {
+                break;
+            }
+        }
+    }
+
+    if retention.summary_days > 0 {
+        let cutoff = Local::now() - chrono::Duration::days(retention.summary_days as i64);
+        report.summaries_deleted = db::delete_summaries_before(db_pool, cutoff)
+            .await
+            .map_err(ClarityError::from)?;
+    }
+
+    report.videos_deleted = cleanup_summary_videos(db_pool).await?;
+
+    if report.screenshots_deleted > 0 || report.summaries_deleted > 0 {
+        log::info!(
+            "Retention removed {} screenshots ({} bytes), {} summaries and {} videos",
+            report.screenshots_deleted,
+            report.screenshot_bytes_reclaimed,
+            report.summaries_deleted,
+            report.videos_deleted
+        );
+    }
+
+    Ok(report)
+}
+
+// 定期执行保留策略（程序运行期间一直运行）
+pub async fn retention_loop(db_pool: SqlitePool) {
+    let mut interval = tokio::time::interval(StdDuration::from_secs(RETENTION_INTERVAL_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = apply_retention(&db_pool).await {
+            log::error!("Failed to apply retention policy: {}", e);
+        }
+    }
+}
+
+// 获取截图和摘要的保留天数
+#[tauri::command]
+pub async fn get_retention_settings(
+    state: State<'_, AppState>,
+) -> Result<RetentionSettings, ClarityError> {
+    Ok(load_retention_settings(&state.db_pool).await)
+}
+
+// 设置截图和摘要的保留天数（0 表示永久保留），下一次执行保留策略时生效
+#[tauri::command]
+pub async fn set_retention_settings(
+    state: State<'_, AppState>,
+    screenshot_days: u64,
+    summary_days: u64,
+) -> Result<(), ClarityError> {
+    settings::save_retention_to_db(&state.db_pool, screenshot_days, summary_days)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "Retention updated: screenshots {} days, summaries {} days",
+        screenshot_days,
+        summary_days
+    );
+
+    Ok(())
+}
+
+// 立即执行一次保留策略
+#[tauri::command]
+pub async fn run_retention(state: State<'_, AppState>) -> Result<RetentionReport, ClarityError> {
+    apply_retention(&state.db_pool).await
+}
@@ -1,68 +1,673 @@
+use crate::color::ColorProfile;
+use crate::commands::lock;
+use crate::commands::summary::{
+    load_activity_categories, load_gemini_generation_settings, DEFAULT_SUMMARY_CONCURRENCY,
+    DEFAULT_SUMMARY_REQUESTS_PER_MINUTE, MAX_SUMMARY_CONCURRENCY,
+};
 use crate::db;
+use crate::error::ClarityError;
+use crate::keychain;
+use crate::locale::{self, Template};
+use crate::logging;
+use crate::meeting;
+use crate::ocr;
+use crate::ollama;
+use crate::openai;
+use crate::openrouter;
+use crate::proxy;
+use crate::screenshot;
+use crate::sensitive;
 use crate::settings;
-use crate::state::AppState;
-use tauri::State;
+use crate::state::{AppState, RecordingState};
+use crate::video_summary;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_autostart::ManagerExt;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenAiSettings {
+    pub api_key: String,
+    pub base_url: String,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenRouterSettings {
+    pub api_key: String,
+    pub base_url: String,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaSettings {
+    pub base_url: String,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiApiKeyInfo {
+    pub id: i64,
+    pub label: Option<String>,
+    pub masked_key: String, // 只返回首尾几位，不把完整 key 发给前端
+    pub priority: i64,
+    pub last_quota_error_at: Option<String>,
+    pub total_requests: i64,
+    pub failed_requests: i64,
+    pub total_tokens: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryQueueSettings {
+    pub concurrency: usize,
+    pub requests_per_minute: u32, // 0 表示不限制
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollingContextSettings {
+    pub enabled: bool,               // 把上一条摘要作为上下文
+    pub include_daily_summary: bool, // 同时传入当天的每日总结
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyBudget {
+    pub token_budget: i64,    // 0 表示不限制
+    pub cost_budget_usd: f64, // 0 表示不限制
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoRetention {
+    pub keep_videos: String, // "never", "for_n_days" or "forever"
+    pub days: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrSettings {
+    pub enabled: bool,
+    pub languages: String, // tesseract 语言，例如 "eng+chi_sim"
+    pub available: bool,   // 是否找到 tesseract
+}
+
+// 常用的单项设置，get_settings 一次返回全部，update_settings 只修改传入的字段
+// 分组设置（OpenAI、OCR、预算等）仍使用各自的命令
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    pub language: String, // BCP-47 语言标签，例如 "en"、"zh"、"ja"、"de-AT"
+    pub summary_interval_seconds: u64,
+    pub ai_provider: String, // "gemini", "openai", "openrouter" or "ollama"
+    pub ai_model: String,
+    // 对比模型：每段时间由当前模型和对比模型各总结一次（费用加倍），空字符串表示关闭
+    pub comparison_model: String,
+    // 备用模型：当前模型连续失败或超时后改用此模型，空字符串表示不使用
+    pub fallback_model: String,
+    pub summary_input_mode: String, // "video" or "frames"
+    pub video_resolution: String,   // "low" or "default"
+    pub video_preset: String,       // "tiny", "540p" or "720p"
+    pub video_fps: u32,
+    pub video_codec: String,     // "h264", "vp9" or "av1"
+    pub capture_mode: String,    // "primary" or "composite"
+    pub capture_backend: String, // "auto", "xcap" or "portal"
+    pub cursor_overlay: bool,
+    pub color_profile: String,            // "srgb", "display_p3" or "hdr"
+    pub sensitive_screen_action: String,  // "blur", "skip" or "off"
+    pub meeting_action: String,           // "pause", "local_only" or "off"
+    pub ffmpeg_path: String,              // 空字符串表示自动查找
+    pub proxy_url: String,                // 空字符串表示使用环境变量
+    pub summarize_on_exit: bool,          // 退出时为最后不足一个间隔的时间段生成总结
+    pub resume_recording_on_launch: bool, // 启动时恢复上次退出时仍在进行的录制
+    pub log_level: String,                // "error", "warn", "info", "debug" or "trace"
+}
+
+// update_settings 的参数，未传的字段保持不变
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsUpdate {
+    pub language: Option<String>,
+    pub summary_interval_seconds: Option<u64>,
+    pub ai_provider: Option<String>,
+    pub ai_model: Option<String>,
+    pub comparison_model: Option<String>,
+    pub fallback_model: Option<String>,
+    pub summary_input_mode: Option<String>,
+    pub video_resolution: Option<String>,
+    pub video_preset: Option<String>,
+    pub video_fps: Option<u32>,
+    pub video_codec: Option<String>,
+    pub capture_mode: Option<String>,
+    pub capture_backend: Option<String>,
+    pub cursor_overlay: Option<bool>,
+    pub color_profile: Option<String>,
+    pub sensitive_screen_action: Option<String>,
+    pub meeting_action: Option<String>,
+    pub ffmpeg_path: Option<String>,
+    pub proxy_url: Option<String>,
+    pub summarize_on_exit: Option<bool>,
+    pub resume_recording_on_launch: Option<bool>,
+    pub log_level: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoPresetEstimate {
+    pub preset: String,
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    pub estimated_tokens_per_summary: u64,
+}
+
+// 读取常用设置，内存中已有的值直接使用，其他从数据库读取
+pub async fn load_settings(state: &AppState) -> Settings {
+    let pool = &state.db_pool;
+    Settings {
+        language: state.language.lock().await.clone(),
+        summary_interval_seconds: *state.summary_interval_seconds.lock().await,
+        ai_provider: settings::load_ai_provider_from_db(pool)
+            .await
+            .unwrap_or_else(|_| "gemini".to_string()),
+        ai_model: state.ai_model.lock().await.clone(),
+        comparison_model: settings::load_comparison_model_from_db(pool)
+            .await
+            .unwrap_or_default(),
+        fallback_model: settings::load_fallback_model_from_db(pool)
+            .await
+            .unwrap_or_default(),
+        summary_input_mode: settings::load_summary_input_mode_from_db(pool)
+            .await
+            .unwrap_or_else(|_| "video".to_string()),
+        video_resolution: state.video_resolution.lock().await.clone(),
+        video_preset: state.video_preset.lock().await.clone(),
+        video_fps: *state.video_fps.lock().await,
+        video_codec: state.video_codec.lock().await.clone(),
+        capture_mode: state.capture_mode.lock().await.clone(),
+        capture_backend: state.capture_backend.lock().await.clone(),
+        cursor_overlay: *state.cursor_overlay.lock().await,
+        color_profile: state.color_profile.lock().await.clone(),
+        sensitive_screen_action: state.sensitive_screen_action.lock().await.clone(),
+        meeting_action: state.meeting_action.lock().await.clone(),
+        ffmpeg_path: state.ffmpeg_path.lock().await.clone().unwrap_or_default(),
+        proxy_url: settings::load_proxy_url_from_db(pool)
+            .await
+            .unwrap_or_default(),
+        summarize_on_exit: settings::load_summarize_on_exit_from_db(pool)
+            .await
+            .unwrap_or(false),
+        resume_recording_on_launch: settings::load_resume_recording_on_launch_from_db(pool)
+            .await
+            .unwrap_or(false),
+        log_level: settings::load_log_level_from_db(pool)
+            .await
+            .unwrap_or_else(|_| logging::DEFAULT_LOG_LEVEL.to_string()),
+    }
+}
+
+// 获取全部常用设置
+#[tauri::command]
+pub async fn get_settings(state: State<'_, AppState>) -> Result<Settings, ClarityError> {
+    Ok(load_settings(&state).await)
+}
+
+// 检查并规范化要修改的设置，任何一项不合法时整个更新都不生效（导入配置时也使用）
+pub(crate) async fn validate_settings_update(
+    mut update: SettingsUpdate,
+) -> Result<SettingsUpdate, ClarityError> {
+    if let Some(language) = &update.language {
+        update.language = Some(locale::normalize(language).map_err(ClarityError::InvalidInput)?);
+    }
+
+    if let Some(interval_seconds) = update.summary_interval_seconds {
+        if interval_seconds < 10 {
+            return Err(ClarityError::InvalidInput(
+                "Summary interval must be at least 10 seconds".to_string(),
+            ));
+        }
+        if interval_seconds > 3600 {
+            return Err(ClarityError::InvalidInput(
+                "Summary interval must be at most 3600 seconds (1 hour)".to_string(),
+            ));
+        }
+    }
+
+    // gemini: 上传视频，openai / openrouter: 发送抽样截图，ollama: 本地模型
+    if let Some(provider) = &update.ai_provider {
+        if !matches!(
+            provider.as_str(),
+            "gemini" | "openai" | "openrouter" | "ollama"
+        ) {
+            return Err(ClarityError::InvalidInput(
+                "AI provider must be 'gemini', 'openai', 'openrouter' or 'ollama'".to_string(),
+            ));
+        }
+    }
+
+    update.ai_model = update.ai_model.map(|model| model.trim().to_string());
+    if update.ai_model.as_deref() == Some("") {
+        return Err(ClarityError::InvalidInput(
+            "Model cannot be empty".to_string(),
+        ));
+    }
+    update.comparison_model = update
+        .comparison_model
+        .map(|model| model.trim().to_string());
+    update.fallback_model = update.fallback_model.map(|model| model.trim().to_string());
+
+    // 只有 Gemini 支持视频，其他服务始终使用截图
+    if let Some(mode) = &update.summary_input_mode {
+        if mode != "video" && mode != "frames" {
+            return Err(ClarityError::InvalidInput(
+                "Summary input mode must be 'video' or 'frames'".to_string(),
+            ));
+        }
+    }
+
+    if let Some(resolution) = &update.video_resolution {
+        if resolution != "low" && resolution != "default" {
+            return Err(ClarityError::InvalidInput(
+                "Resolution must be 'low' or 'default'".to_string(),
+            ));
+        }
+    }
+
+    if let Some(preset) = &update.video_preset {
+        if video_summary::preset_dimensions(preset).is_none() {
+            return Err(ClarityError::InvalidInput(
+                "Video preset must be 'tiny', '540p' or '720p'".to_string(),
+            ));
+        }
+    }
+
+    if let Some(fps) = update.video_fps {
+        if !(video_summary::MIN_VIDEO_FPS..=video_summary::MAX_VIDEO_FPS).contains(&fps) {
+            return Err(ClarityError::InvalidInput(format!(
+                "Video fps must be between {} and {}",
+                video_summary::MIN_VIDEO_FPS,
+                video_summary::MAX_VIDEO_FPS
+            )));
+        }
+    }
+
+    if let Some(codec) = &update.video_codec {
+        if !video_summary::VIDEO_CODECS.contains(&codec.as_str()) {
+            return Err(ClarityError::InvalidInput(
+                "Video codec must be 'h264', 'vp9' or 'av1'".to_string(),
+            ));
+        }
+    }
+
+    // primary: 仅主屏幕，composite: 拼接所有显示器
+    if let Some(mode) = &update.capture_mode {
+        if mode != "primary" && mode != "composite" {
+            return Err(ClarityError::InvalidInput(
+                "Capture mode must be 'primary' or 'composite'".to_string(),
+            ));
+        }
+    }
+
+    // auto: 自动选择，xcap: 原生截图，portal: XDG 桌面门户，仅 Linux
+    if let Some(backend) = &update.capture_backend {
+        if backend != "auto" && backend != "xcap" && backend != "portal" {
+            return Err(ClarityError::InvalidInput(
+                "Capture backend must be 'auto', 'xcap' or 'portal'".to_string(),
+            ));
+        }
+        if backend == "portal" && !cfg!(target_os = "linux") {
+            return Err(ClarityError::InvalidInput(
+                "The portal capture backend is only available on Linux".to_string(),
+            ));
+        }
+    }
+
+    if let Some(profile) = &update.color_profile {
+        if !ColorProfile::is_valid_setting(profile) {
+            return Err(ClarityError::InvalidInput(
+                "Color profile must be 'srgb', 'display_p3' or 'hdr'".to_string(),
+            ));
+        }
+    }
+
+    if let Some(action) = &update.sensitive_screen_action {
+        if !sensitive::is_valid_action(action) {
+            return Err(ClarityError::InvalidInput(
+                "Sensitive screen action must be 'blur', 'skip' or 'off'".to_string(),
+            ));
+        }
+    }
+
+    if let Some(action) = &update.meeting_action {
+        if !meeting::is_valid_action(action) {
+            return Err(ClarityError::InvalidInput(
+                "Meeting action must be 'pause', 'local_only' or 'off'".to_string(),
+            ));
+        }
+    }
+
+    // 空字符串恢复自动查找 ffmpeg
+    update.ffmpeg_path = update.ffmpeg_path.map(|path| path.trim().to_string());
+    if let Some(path) = update
+        .ffmpeg_path
+        .as_deref()
+        .filter(|path| !path.is_empty())
+    {
+        let check = tokio::process::Command::new(path)
+            .arg("-version")
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run ffmpeg at {}: {}", path, e))?;
+        if !check.status.success() {
+            return Err(ClarityError::InvalidInput(format!(
+                "{} is not a working ffmpeg binary",
+                path
+            )));
+        }
+    }
+
+    // 空字符串恢复使用 HTTP_PROXY / HTTPS_PROXY / ALL_PROXY 环境变量
+    update.proxy_url = update.proxy_url.map(|url| url.trim().to_string());
+    if let Some(url) = update.proxy_url.as_deref().filter(|url| !url.is_empty()) {
+        proxy::validate_proxy_url(url).map_err(ClarityError::InvalidInput)?;
+    }
+
+    if let Some(level) = &update.log_level {
+        if logging::parse_level(level).is_none() {
+            return Err(ClarityError::InvalidInput(format!(
+                "Log level must be one of: {}",
+                logging::LOG_LEVELS.join(", ")
+            )));
+        }
+    }
+
+    Ok(update)
+}
+
+// 修改设置：只修改传入的字段，先全部校验再保存，
+// 保存后更新内存中的值并发送 settings-changed 事件，返回修改后的全部设置
+#[tauri::command]
+pub async fn update_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    update: SettingsUpdate,
+) -> Result<Settings, ClarityError> {
+    let update = validate_settings_update(update).await?;
+    let pool = &state.db_pool;
+
+    if let Some(language) = &update.language {
+        settings::save_language_to_db(pool, language).await?;
+        *state.language.lock().await = language.clone();
+    }
+    if let Some(interval_seconds) = update.summary_interval_seconds {
+        settings::save_summary_interval_to_db(pool, interval_seconds).await?;
+        *state.summary_interval_seconds.lock().await = interval_seconds;
+    }
+    if let Some(provider) = &update.ai_provider {
+        settings::save_ai_provider_to_db(pool, provider).await?;
+    }
+    if let Some(model) = &update.ai_model {
+        settings::save_ai_model_to_db(pool, model).await?;
+        *state.ai_model.lock().await = model.clone();
+    }
+    if let Some(model) = &update.comparison_model {
+        settings::save_comparison_model_to_db(pool, model).await?;
+    }
+    if let Some(model) = &update.fallback_model {
+        settings::save_fallback_model_to_db(pool, model).await?;
+    }
+    if let Some(mode) = &update.summary_input_mode {
+        settings::save_summary_input_mode_to_db(pool, mode).await?;
+    }
+    if let Some(resolution) = &update.video_resolution {
+        settings::save_video_resolution_to_db(pool, resolution).await?;
+        *state.video_resolution.lock().await = resolution.clone();
+    }
+    // 预设和帧率保存在一起，只传其中一个时另一个保持当前值
+    if update.video_preset.is_some() || update.video_fps.is_some() {
+        let preset = match &update.video_preset {
+            Some(preset) => preset.clone(),
+            None => state.video_preset.lock().await.clone(),
+        };
+        let fps = match update.video_fps {
+            Some(fps) => fps,
+            None => *state.video_fps.lock().await,
+        };
+        settings::save_video_preset_to_db(pool, &preset, fps).await?;
+        *state.video_preset.lock().await = preset;
+        *state.video_fps.lock().await = fps;
+    }
+    if let Some(codec) = &update.video_codec {
+        settings::save_video_codec_to_db(pool, codec).await?;
+        *state.video_codec.lock().await = codec.clone();
+    }
+    if let Some(mode) = &update.capture_mode {
+        settings::save_capture_mode_to_db(pool, mode).await?;
+        *state.capture_mode.lock().await = mode.clone();
+    }
+    if let Some(backend) = &update.capture_backend {
+        settings::save_capture_backend_to_db(pool, backend).await?;
+        *state.capture_backend.lock().await = backend.clone();
+        log::info!(
+            "Capture backend resolved to: {}",
+            screenshot::resolve_capture_backend(backend)
+        );
+    }
+    if let Some(enabled) = update.cursor_overlay {
+        settings::save_cursor_overlay_to_db(pool, enabled).await?;
+        *state.cursor_overlay.lock().await = enabled;
+    }
+    if let Some(profile) = &update.color_profile {
+        settings::save_color_profile_to_db(pool, profile).await?;
+        *state.color_profile.lock().await = profile.clone();
+    }
+    if let Some(action) = &update.sensitive_screen_action {
+        settings::save_sensitive_screen_action_to_db(pool, action).await?;
+        *state.sensitive_screen_action.lock().await = action.clone();
+    }
+    if let Some(action) = &update.meeting_action {
+        settings::save_meeting_action_to_db(pool, action).await?;
+        *state.meeting_action.lock().await = action.clone();
+    }
+    if let Some(path) = &update.ffmpeg_path {
+        settings::save_ffmpeg_path_to_db(pool, path).await?;
+        *state.ffmpeg_path.lock().await = Some(path.clone()).filter(|path| !path.is_empty());
+    }
+    if let Some(url) = &update.proxy_url {
+        settings::save_proxy_url_to_db(pool, url).await?;
+        // 之后创建的 HTTP 客户端立即使用新代理
+        proxy::set_proxy_url(url);
+    }
+    if let Some(enabled) = update.summarize_on_exit {
+        settings::save_summarize_on_exit_to_db(pool, enabled).await?;
+    }
+    if let Some(enabled) = update.resume_recording_on_launch {
+        settings::save_resume_recording_on_launch_to_db(pool, enabled).await?;
+    }
+    if let Some(level) = &update.log_level {
+        settings::save_log_level_to_db(pool, level).await?;
+        logging::set_level(level);
+    }
+
+    let settings = load_settings(&state).await;
+    // 代理地址可能包含账号密码，不记录具体的值
+    log::info!("Settings updated");
+    if let Err(e) = app.emit("settings-changed", &settings) {
+        log::warn!("Failed to emit settings-changed: {}", e);
+    }
+
+    Ok(settings)
+}
 
 // 获取 Google Gemini API Key
 #[tauri::command]
-pub async fn get_gemini_api_key(state: State<'_, AppState>) -> Result<String, String> {
+pub async fn get_gemini_api_key(state: State<'_, AppState>) -> Result<String, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
     let api_key = state.gemini_api_key.lock().await.clone();
     Ok(api_key.unwrap_or_default())
 }
 
-// 设置 Google Gemini API Key
+// 设置 Google Gemini API Key（设为优先级最高的 key，空字符串表示删除当前 key）
 #[tauri::command]
-pub async fn set_gemini_api_key(state: State<'_, AppState>, api_key: String) -> Result<(), String> {
-    // 保存到数据库
-    settings::save_api_key_to_db(&state.db_pool, &api_key)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
+pub async fn set_gemini_api_key(
+    state: State<'_, AppState>,
+    api_key: String,
+) -> Result<(), ClarityError> {
+    let api_key = api_key.trim();
+    let keys = keychain::load_gemini_api_keys(&state.db_pool).await?;
+
+    if api_key.is_empty() {
+        if let Some(primary) = keys.first() {
+            keychain::delete_gemini_api_key(&state.db_pool, primary.id).await?;
+        }
+    } else {
+        let top_priority = keys.first().map(|k| k.priority - 1).unwrap_or(0);
+        match keys.iter().find(|k| k.api_key == api_key) {
+            Some(existing) => {
+                db::update_gemini_api_key_priority(&state.db_pool, existing.id, top_priority)
+                    .await
+                    .map_err(ClarityError::from)?
+            }
+            None => {
+                keychain::add_gemini_api_key(&state.db_pool, api_key, None, top_priority).await?;
+            }
+        }
+    }
 
-    // 更新内存中的值
-    *state.gemini_api_key.lock().await = Some(api_key);
+    refresh_primary_gemini_key(&state).await
+}
 
+// 用优先级最高的 key 更新内存中的值（每日总结等单次调用使用）
+pub async fn refresh_primary_gemini_key(state: &AppState) -> Result<(), ClarityError> {
+    let keys = keychain::load_gemini_api_keys(&state.db_pool).await?;
+    *state.gemini_api_key.lock().await = keys.into_iter().next().map(|k| k.api_key);
     Ok(())
 }
 
-// 获取总结间隔（秒）
+// 隐藏 key 的中间部分
+fn mask_api_key(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", head, tail)
+}
+
+// 获取所有 Gemini API key 及各自的用量
 #[tauri::command]
-pub async fn get_summary_interval(state: State<'_, AppState>) -> Result<u64, String> {
-    let interval = *state.summary_interval_seconds.lock().await;
-    log::info!("Getting summary interval: {} seconds", interval);
-    Ok(interval)
+pub async fn get_gemini_api_keys(
+    state: State<'_, AppState>,
+) -> Result<Vec<GeminiApiKeyInfo>, ClarityError> {
+    let keys = keychain::load_gemini_api_keys(&state.db_pool).await?;
+    let usage = db::get_api_key_usage(&state.db_pool, None)
+        .await
+        .map_err(ClarityError::from)?;
+
+    Ok(keys
+        .into_iter()
+        .map(|key| {
+            let key_usage = usage.iter().find(|u| u.api_key_id == key.id);
+            GeminiApiKeyInfo {
+                id: key.id,
+                label: key.label,
+                masked_key: mask_api_key(&key.api_key),
+                priority: key.priority,
+                last_quota_error_at: key.last_quota_error_at.map(|t| t.to_rfc3339()),
+                total_requests: key_usage.map_or(0, |u| u.total_requests),
+                failed_requests: key_usage.map_or(0, |u| u.failed_requests),
+                total_tokens: key_usage.map_or(0, |u| u.total_tokens),
+            }
+        })
+        .collect())
 }
 
-// 设置总结间隔（秒）
+// 添加 Gemini API key（排在已有 key 之后）
 #[tauri::command]
-pub async fn set_summary_interval(
+pub async fn add_gemini_api_key(
     state: State<'_, AppState>,
-    interval_seconds: u64,
-) -> Result<(), String> {
-    log::info!("Setting summary interval to {} seconds", interval_seconds);
+    api_key: String,
+    label: Option<String>,
+) -> Result<i64, ClarityError> {
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Err(ClarityError::InvalidInput(
+            "API key cannot be empty".to_string(),
+        ));
+    }
 
-    if interval_seconds < 10 {
-        return Err("Summary interval must be at least 10 seconds".to_string());
+    let keys = keychain::load_gemini_api_keys(&state.db_pool).await?;
+    if keys.iter().any(|k| k.api_key == api_key) {
+        return Err(ClarityError::Conflict("API key already exists".to_string()));
     }
 
-    if interval_seconds > 3600 {
-        return Err("Summary interval must be at most 3600 seconds (1 hour)".to_string());
+    let priority = keys.last().map(|k| k.priority + 1).unwrap_or(0);
+    let label = label
+        .as_deref()
+        .map(str::trim)
+        .filter(|label| !label.is_empty());
+    let id = keychain::add_gemini_api_key(&state.db_pool, api_key, label, priority).await?;
+
+    refresh_primary_gemini_key(&state).await?;
+    Ok(id)
+}
+
+// 删除 Gemini API key
+#[tauri::command]
+pub async fn remove_gemini_api_key(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<(), ClarityError> {
+    keychain::delete_gemini_api_key(&state.db_pool, id).await?;
+
+    refresh_primary_gemini_key(&state).await
+}
+
+// 调整 Gemini API key 的使用顺序（ids 按优先级从高到低排列）
+#[tauri::command]
+pub async fn reorder_gemini_api_keys(
+    state: State<'_, AppState>,
+    ids: Vec<i64>,
+) -> Result<(), ClarityError> {
+    for (index, id) in ids.iter().enumerate() {
+        db::update_gemini_api_key_priority(&state.db_pool, *id, index as i64)
+            .await
+            .map_err(ClarityError::from)?;
     }
 
-    // 保存到数据库
-    settings::save_summary_interval_to_db(&state.db_pool, interval_seconds)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
+    refresh_primary_gemini_key(&state).await
+}
 
-    // 更新内存中的值
-    *state.summary_interval_seconds.lock().await = interval_seconds;
-    log::info!("Summary interval updated successfully");
+// 获取是否使用系统钥匙串保存 API key
+#[tauri::command]
+pub async fn get_use_os_keychain(state: State<'_, AppState>) -> Result<bool, ClarityError> {
+    Ok(keychain::is_enabled(&state.db_pool).await)
+}
 
-    Ok(())
+// 设置是否使用系统钥匙串保存 API key（无桌面环境的 Linux 可以关闭，key 会移回数据库）
+#[tauri::command]
+pub async fn set_use_os_keychain(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), ClarityError> {
+    // 先迁移已有的 key，迁移失败时不修改设置
+    keychain::migrate_gemini_api_keys(&state.db_pool, enabled).await?;
+
+    settings::save_use_os_keychain_to_db(&state.db_pool, enabled)
+        .await
+        .map_err(ClarityError::from)?;
+
+    refresh_primary_gemini_key(&state).await
 }
 
 // 测试视频总结功能（诊断用）
 #[tauri::command]
-pub async fn test_video_summary(state: State<'_, AppState>) -> Result<String, String> {
+pub async fn test_video_summary(state: State<'_, AppState>) -> Result<String, ClarityError> {
     log::info!("Testing video summary functionality");
 
     let mut diagnostics = Vec::new();
@@ -75,42 +680,40 @@ pub async fn test_video_summary(state: State<'_, AppState>) -> Result<String, St
         diagnostics.push("✅ Google Gemini API key is set".to_string());
     }
 
-    // 检查 ffmpeg
-    let ffmpeg_paths = if cfg!(target_os = "macos") {
-        vec![
-            "ffmpeg",
-            "/usr/local/bin/ffmpeg",
-            "/opt/homebrew/bin/ffmpeg",
-        ]
-    } else {
-        vec!["ffmpeg"]
-    };
-
-    let mut ffmpeg_found = false;
-    let mut ffmpeg_path = String::new();
-    for path in &ffmpeg_paths {
-        let check = tokio::process::Command::new(path)
-            .arg("-version")
-            .output()
-            .await;
-
-        if check.is_ok() {
-            ffmpeg_found = true;
-            ffmpeg_path = path.to_string();
-            break;
-        }
+    // 检查 ffmpeg（优先使用配置的路径）
+    let custom_ffmpeg = state.ffmpeg_path.lock().await.clone();
+    if let Some(path) = custom_ffmpeg.as_deref() {
+        diagnostics.push(format!("⚙️ Configured ffmpeg path: {}", path));
+    }
+    match video_summary::find_ffmpeg(custom_ffmpeg.as_deref()).await {
+        Ok(path) => diagnostics.push(format!("✅ ffmpeg found at: {}", path)),
+        Err(e) => diagnostics.push(format!("❌ {}", e)),
     }
 
-    if ffmpeg_found {
-        diagnostics.push(format!("✅ ffmpeg found at: {}", ffmpeg_path));
-    } else {
-        diagnostics.push(format!("❌ ffmpeg not found. Tried: {:?}", ffmpeg_paths));
+    // 检查 AI 服务提供方
+    let provider = settings::load_ai_provider_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| "gemini".to_string());
+    diagnostics.push(format!("🤖 AI provider: {}", provider));
+    let input_mode = settings::load_summary_input_mode_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| "video".to_string());
+    diagnostics.push(format!("🖼️ Summary input mode: {}", input_mode));
+    if provider == "ollama" {
+        let base_url = settings::load_ollama_base_url_from_db(&state.db_pool)
+            .await
+            .unwrap_or_else(|_| ollama::DEFAULT_OLLAMA_BASE_URL.to_string());
+        if ollama::is_server_available(&base_url).await {
+            diagnostics.push(format!("✅ Ollama server reachable at: {}", base_url));
+        } else {
+            diagnostics.push(format!("❌ Ollama server not reachable at: {}", base_url));
+        }
     }
 
     // 检查截图数量
     let count = db::get_today_screenshot_count(&state.db_pool)
         .await
-        .map_err(|e| format!("Database error: {}", e))?;
+        .map_err(ClarityError::from)?;
     diagnostics.push(format!("📸 Today's screenshots: {}", count));
 
     // 检查总结间隔
@@ -118,12 +721,33 @@ pub async fn test_video_summary(state: State<'_, AppState>) -> Result<String, St
     diagnostics.push(format!("⏱️ Summary interval: {} seconds", interval));
 
     // 检查是否在录制
-    let recording = *state.is_recording.lock().await;
     diagnostics.push(format!(
         "🎬 Recording: {}",
-        if recording { "Yes" } else { "No" }
+        match state.recording.state() {
+            RecordingState::Recording => "Yes",
+            RecordingState::Paused => "Paused",
+            RecordingState::Stopped => "No",
+        }
+    ));
+
+    // 检查截图后端
+    let backend = state.capture_backend.lock().await.clone();
+    diagnostics.push(format!(
+        "🖥️ Capture backend: {} (resolved: {})",
+        backend,
+        screenshot::resolve_capture_backend(&backend)
     ));
 
+    // 检查代理
+    let proxy_url = settings::load_proxy_url_from_db(&state.db_pool)
+        .await
+        .unwrap_or_default();
+    if !proxy_url.is_empty() {
+        diagnostics.push(format!("🌐 Proxy: {}", proxy_url));
+    } else if let Some(env_proxy) = proxy::env_proxy_url() {
+        diagnostics.push(format!("🌐 Proxy (from environment): {}", env_proxy));
+    }
+
     // 检查存储路径
     let storage_path = state.storage_path.lock().await.clone();
     diagnostics.push(format!("📁 Storage path: {}", storage_path.display()));
@@ -133,51 +757,495 @@ pub async fn test_video_summary(state: State<'_, AppState>) -> Result<String, St
     Ok(result)
 }
 
-// 获取 AI 模型
+// 获取 OCR 设置
 #[tauri::command]
-pub async fn get_ai_model(state: State<'_, AppState>) -> Result<String, String> {
-    Ok(state.ai_model.lock().await.clone())
+pub async fn get_ocr_settings(state: State<'_, AppState>) -> Result<OcrSettings, ClarityError> {
+    Ok(OcrSettings {
+        enabled: settings::load_ocr_enabled_from_db(&state.db_pool)
+            .await
+            .unwrap_or(false),
+        languages: settings::load_ocr_languages_from_db(&state.db_pool)
+            .await
+            .unwrap_or_else(|_| ocr::DEFAULT_OCR_LANGUAGES.to_string()),
+        available: ocr::tesseract_available().await,
+    })
 }
 
-// 设置 AI 模型
+// 设置 OCR：开启后每个时间段抽样识别几张截图的文字，附加到总结提示词中（需要安装 tesseract）
 #[tauri::command]
-pub async fn set_ai_model(state: State<'_, AppState>, model: String) -> Result<(), String> {
+pub async fn set_ocr_settings(
+    state: State<'_, AppState>,
+    enabled: bool,
+    languages: String,
+) -> Result<(), ClarityError> {
+    let languages = languages.trim().to_string();
+    let languages = if languages.is_empty() {
+        ocr::DEFAULT_OCR_LANGUAGES.to_string()
+    } else {
+        languages
+    };
+    ocr::validate_languages(&languages).map_err(ClarityError::InvalidInput)?;
+
+    if enabled && !ocr::tesseract_available().await {
+        return Err(ClarityError::NotFound(
+            "tesseract was not found, please install it first".to_string(),
+        ));
+    }
+
+    settings::save_ocr_settings_to_db(&state.db_pool, enabled, &languages)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "OCR settings updated: enabled={}, languages={}",
+        enabled,
+        languages
+    );
+
+    Ok(())
+}
+
+// 获取 OpenAI 设置
+#[tauri::command]
+pub async fn get_openai_settings(
+    state: State<'_, AppState>,
+) -> Result<OpenAiSettings, ClarityError> {
+    let api_key = settings::load_openai_api_key_from_db(&state.db_pool)
+        .await
+        .unwrap_or_default();
+    let base_url = settings::load_openai_base_url_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| openai::DEFAULT_OPENAI_BASE_URL.to_string());
+    let model = settings::load_openai_model_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| openai::OPENAI_MODELS[0].to_string());
+
+    Ok(OpenAiSettings {
+        api_key,
+        base_url,
+        model,
+    })
+}
+
+// 设置 OpenAI API key、接口地址（兼容 OpenAI 协议的服务）和模型
+#[tauri::command]
+pub async fn set_openai_settings(
+    state: State<'_, AppState>,
+    api_key: String,
+    base_url: Option<String>,
+    model: String,
+) -> Result<(), ClarityError> {
+    if model.is_empty() {
+        return Err(ClarityError::InvalidInput(
+            "Model cannot be empty".to_string(),
+        ));
+    }
+
+    let base_url = base_url
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| openai::DEFAULT_OPENAI_BASE_URL.to_string());
+    if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+        return Err(ClarityError::InvalidInput(
+            "Base URL must start with http:// or https://".to_string(),
+        ));
+    }
+
+    // 保存到数据库
+    settings::save_openai_settings_to_db(&state.db_pool, api_key.trim(), &base_url, &model)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "OpenAI settings updated (base URL: {}, model: {})",
+        base_url,
+        model
+    );
+
+    Ok(())
+}
+
+// 获取可选的 OpenAI 模型列表
+#[tauri::command]
+pub async fn get_openai_models() -> Result<Vec<String>, ClarityError> {
+    Ok(openai::OPENAI_MODELS
+        .iter()
+        .map(|m| m.to_string())
+        .collect())
+}
+
+// 获取 OpenRouter 设置
+#[tauri::command]
+pub async fn get_openrouter_settings(
+    state: State<'_, AppState>,
+) -> Result<OpenRouterSettings, ClarityError> {
+    let api_key = settings::load_openrouter_api_key_from_db(&state.db_pool)
+        .await
+        .unwrap_or_default();
+    let base_url = settings::load_openrouter_base_url_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| openrouter::DEFAULT_OPENROUTER_BASE_URL.to_string());
+    let model = settings::load_openrouter_model_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| openrouter::DEFAULT_OPENROUTER_MODEL.to_string());
+
+    Ok(OpenRouterSettings {
+        api_key,
+        base_url,
+        model,
+    })
+}
+
+// 设置 OpenRouter API key、接口地址和模型
+#[tauri::command]
+pub async fn set_openrouter_settings(
+    state: State<'_, AppState>,
+    api_key: String,
+    base_url: Option<String>,
+    model: String,
+) -> Result<(), ClarityError> {
+    if model.is_empty() {
+        return Err(ClarityError::InvalidInput(
+            "Model cannot be empty".to_string(),
+        ));
+    }
+
+    let base_url = base_url
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| openrouter::DEFAULT_OPENROUTER_BASE_URL.to_string());
+    if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+        return Err(ClarityError::InvalidInput(
+            "Base URL must start with http:// or https://".to_string(),
+        ));
+    }
+
+    // 保存到数据库
+    settings::save_openrouter_settings_to_db(&state.db_pool, api_key.trim(), &base_url, &model)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "OpenRouter settings updated (base URL: {}, model: {})",
+        base_url,
+        model
+    );
+
+    Ok(())
+}
+
+// 获取 OpenRouter 上支持图片输入的模型列表
+#[tauri::command]
+pub async fn get_openrouter_models(
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, ClarityError> {
+    let base_url = settings::load_openrouter_base_url_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| openrouter::DEFAULT_OPENROUTER_BASE_URL.to_string());
+
+    openrouter::list_vision_models(&base_url).await
+}
+
+// 获取 Ollama 设置
+#[tauri::command]
+pub async fn get_ollama_settings(
+    state: State<'_, AppState>,
+) -> Result<OllamaSettings, ClarityError> {
+    let base_url = settings::load_ollama_base_url_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| ollama::DEFAULT_OLLAMA_BASE_URL.to_string());
+    let model = settings::load_ollama_model_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| ollama::DEFAULT_OLLAMA_MODEL.to_string());
+
+    Ok(OllamaSettings { base_url, model })
+}
+
+// 设置 Ollama 服务地址和模型
+#[tauri::command]
+pub async fn set_ollama_settings(
+    state: State<'_, AppState>,
+    base_url: Option<String>,
+    model: String,
+) -> Result<(), ClarityError> {
     if model.is_empty() {
-        return Err("Model cannot be empty".to_string());
+        return Err(ClarityError::InvalidInput(
+            "Model cannot be empty".to_string(),
+        ));
+    }
+
+    let base_url = base_url
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| ollama::DEFAULT_OLLAMA_BASE_URL.to_string());
+    if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+        return Err(ClarityError::InvalidInput(
+            "Base URL must start with http:// or https://".to_string(),
+        ));
+    }
+
+    // 保存到数据库
+    settings::save_ollama_settings_to_db(&state.db_pool, &base_url, &model)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "Ollama settings updated (base URL: {}, model: {})",
+        base_url,
+        model
+    );
+
+    Ok(())
+}
+
+// 获取本地 Ollama 已下载的模型列表
+#[tauri::command]
+pub async fn get_ollama_models(state: State<'_, AppState>) -> Result<Vec<String>, ClarityError> {
+    let base_url = settings::load_ollama_base_url_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| ollama::DEFAULT_OLLAMA_BASE_URL.to_string());
+
+    ollama::list_models(&base_url).await
+}
+
+// 获取总结任务队列设置
+#[tauri::command]
+pub async fn get_summary_queue_settings(
+    state: State<'_, AppState>,
+) -> Result<SummaryQueueSettings, ClarityError> {
+    let concurrency = settings::load_summary_concurrency_from_db(&state.db_pool)
+        .await
+        .unwrap_or(DEFAULT_SUMMARY_CONCURRENCY);
+    let requests_per_minute = settings::load_summary_requests_per_minute_from_db(&state.db_pool)
+        .await
+        .unwrap_or(DEFAULT_SUMMARY_REQUESTS_PER_MINUTE);
+
+    Ok(SummaryQueueSettings {
+        concurrency,
+        requests_per_minute,
+    })
+}
+
+// 设置总结任务的并发数和每分钟请求上限（worker 每次检查时读取，立即生效）
+#[tauri::command]
+pub async fn set_summary_queue_settings(
+    state: State<'_, AppState>,
+    concurrency: usize,
+    requests_per_minute: u32,
+) -> Result<(), ClarityError> {
+    if !(1..=MAX_SUMMARY_CONCURRENCY).contains(&concurrency) {
+        return Err(ClarityError::InvalidInput(format!(
+            "Concurrency must be between 1 and {}",
+            MAX_SUMMARY_CONCURRENCY
+        )));
+    }
+
+    // 保存到数据库
+    settings::save_summary_queue_settings_to_db(&state.db_pool, concurrency, requests_per_minute)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "Summary queue settings updated (concurrency: {}, requests per minute: {})",
+        concurrency,
+        requests_per_minute
+    );
+
+    Ok(())
+}
+
+// 获取每日 AI 预算
+#[tauri::command]
+pub async fn get_daily_budget(state: State<'_, AppState>) -> Result<DailyBudget, ClarityError> {
+    let token_budget = settings::load_daily_token_budget_from_db(&state.db_pool)
+        .await
+        .unwrap_or(0);
+    let cost_budget_usd = settings::load_daily_cost_budget_from_db(&state.db_pool)
+        .await
+        .unwrap_or(0.0);
+
+    Ok(DailyBudget {
+        token_budget,
+        cost_budget_usd,
+    })
+}
+
+// 设置每日 AI 预算，超出后当天不再生成总结（截图照常保存）
+#[tauri::command]
+pub async fn set_daily_budget(
+    state: State<'_, AppState>,
+    token_budget: i64,
+    cost_budget_usd: f64,
+) -> Result<(), ClarityError> {
+    if token_budget < 0 {
+        return Err(ClarityError::InvalidInput(
+            "Token budget cannot be negative".to_string(),
+        ));
+    }
+    if !cost_budget_usd.is_finite() || cost_budget_usd < 0.0 {
+        return Err(ClarityError::InvalidInput(
+            "Cost budget must be a non-negative number".to_string(),
+        ));
     }
 
     // 保存到数据库
-    settings::save_ai_model_to_db(&state.db_pool, &model)
+    settings::save_daily_budget_to_db(&state.db_pool, token_budget, cost_budget_usd)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "Daily AI budget updated (tokens: {}, cost: ${})",
+        token_budget,
+        cost_budget_usd
+    );
+
+    Ok(())
+}
+
+// 获取 Gemini 生成参数和安全设置
+#[tauri::command]
+pub async fn get_gemini_generation_settings(
+    state: State<'_, AppState>,
+) -> Result<video_summary::GeminiGenerationSettings, ClarityError> {
+    Ok(load_gemini_generation_settings(&state.db_pool).await)
+}
+
+// 设置 Gemini 生成参数和安全设置（下一次请求生效）
+#[tauri::command]
+pub async fn set_gemini_generation_settings(
+    state: State<'_, AppState>,
+    generation: video_summary::GeminiGenerationSettings,
+) -> Result<(), ClarityError> {
+    if !video_summary::GEMINI_SAFETY_THRESHOLDS.contains(&generation.safety_threshold.as_str()) {
+        return Err(ClarityError::InvalidInput(format!(
+            "Invalid safety threshold: {}",
+            generation.safety_threshold
+        )));
+    }
+    if let Some(temperature) = generation.temperature {
+        if !(0.0..=2.0).contains(&temperature) {
+            return Err(ClarityError::InvalidInput(
+                "Temperature must be between 0 and 2".to_string(),
+            ));
+        }
+    }
+    if generation.max_output_tokens == Some(0) {
+        return Err(ClarityError::InvalidInput(
+            "Max output tokens must be greater than 0".to_string(),
+        ));
+    }
+    if let Some(thinking_budget) = generation.thinking_budget {
+        if thinking_budget < -1 {
+            return Err(ClarityError::InvalidInput(
+                "Thinking budget must be -1 (dynamic) or at least 0".to_string(),
+            ));
+        }
+    }
+
+    settings::save_gemini_generation_settings_to_db(
+        &state.db_pool,
+        &generation.safety_threshold,
+        generation.temperature,
+        generation.max_output_tokens,
+        generation.thinking_budget,
+    )
+    .await
+    .map_err(ClarityError::from)?;
+
+    log::info!("Gemini generation settings updated: {:?}", generation);
+
+    Ok(())
+}
+
+// 获取活动分类
+#[tauri::command]
+pub async fn get_activity_categories(
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, ClarityError> {
+    Ok(load_activity_categories(&state.db_pool).await)
+}
+
+// 设置活动分类（之后生成的摘要生效，已有摘要的分类不变）
+#[tauri::command]
+pub async fn set_activity_categories(
+    state: State<'_, AppState>,
+    categories: Vec<String>,
+) -> Result<(), ClarityError> {
+    // 统一为小写并去重，始终保留 other 用于无法归类的活动
+    let mut normalized: Vec<String> = Vec::new();
+    for category in categories {
+        let category = category.trim().to_lowercase();
+        if !category.is_empty() && !normalized.contains(&category) {
+            normalized.push(category);
+        }
+    }
+    if !normalized.iter().any(|c| c == "other") {
+        normalized.push("other".to_string());
+    }
+
+    settings::save_activity_categories_to_db(&state.db_pool, &normalized)
         .await
-        .map_err(|e| format!("Database error: {}", e))?;
+        .map_err(ClarityError::from)?;
 
-    // 更新内存中的值
-    *state.ai_model.lock().await = model;
-    log::info!("AI model updated successfully");
+    log::info!("Activity categories updated: {:?}", normalized);
 
     Ok(())
 }
 
+// 获取摘要上下文设置
+#[tauri::command]
+pub async fn get_rolling_context(
+    state: State<'_, AppState>,
+) -> Result<RollingContextSettings, ClarityError> {
+    let enabled = settings::load_rolling_context_from_db(&state.db_pool)
+        .await
+        .unwrap_or(false);
+    let include_daily_summary = settings::load_rolling_context_daily_from_db(&state.db_pool)
+        .await
+        .unwrap_or(false);
+
+    Ok(RollingContextSettings {
+        enabled,
+        include_daily_summary,
+    })
+}
+
+// 设置是否把上一条摘要（和当天总结）作为上下文传给模型
+#[tauri::command]
+pub async fn set_rolling_context(
+    state: State<'_, AppState>,
+    enabled: bool,
+    include_daily_summary: bool,
+) -> Result<(), ClarityError> {
+    settings::save_rolling_context_to_db(&state.db_pool, enabled, include_daily_summary)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "Rolling context updated (enabled: {}, include daily summary: {})",
+        enabled,
+        include_daily_summary
+    );
+
+    Ok(())
+}
+
+// 提示词命令的语言参数，不传时使用默认语言
+fn prompt_language(language: Option<String>) -> Result<String, ClarityError> {
+    locale::normalize(language.as_deref().unwrap_or(locale::DEFAULT_LANGUAGE))
+        .map_err(ClarityError::InvalidInput)
+}
+
 // 获取 AI 提示词（按语言）
 #[tauri::command]
 pub async fn get_ai_prompt(
     state: State<'_, AppState>,
     language: Option<String>,
-) -> Result<String, String> {
-    let lang = language.as_deref().unwrap_or("zh");
-
-    // 从数据库加载指定语言的提示词
-    match settings::load_ai_prompt_from_db(&state.db_pool, Some(lang)).await {
-        Ok(prompt) => Ok(prompt),
-        Err(_) => {
-            // 如果没有找到，返回默认提示词
-            if lang == "en" {
-                Ok("Analyze this screen activity video and provide a concise activity summary. Focus on: 1) Main apps/websites used; 2) Activity type (work/entertainment/learning, etc.); 3) Any distractions or inefficient behaviors. Respond in English, keep it under 100 words.".to_string())
-            } else {
-                Ok("分析这段屏幕活动视频，提供简洁的活动摘要。重点关注：1) 主要使用的应用/网站；2) 活动类型（工作/娱乐/学习等）；3) 是否有分心或低效行为。用中文回答，控制在100字以内。".to_string())
-            }
-        }
-    }
+) -> Result<String, ClarityError> {
+    let lang = prompt_language(language)?;
+
+    // 指定语言没有保存过提示词时，按回退链返回（最终是内置的默认提示词）
+    Ok(locale::summary_prompt(&state.db_pool, &lang).await)
 }
 
 // 设置 AI 提示词（按语言）
@@ -186,17 +1254,19 @@ pub async fn set_ai_prompt(
     state: State<'_, AppState>,
     prompt: String,
     language: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), ClarityError> {
     if prompt.is_empty() {
-        return Err("Prompt cannot be empty".to_string());
+        return Err(ClarityError::InvalidInput(
+            "Prompt cannot be empty".to_string(),
+        ));
     }
 
-    let lang = language.as_deref().unwrap_or("zh");
+    let lang = prompt_language(language)?;
 
     // 保存到数据库（按语言）
-    settings::save_ai_prompt_to_db(&state.db_pool, &prompt, Some(lang))
+    settings::save_ai_prompt_to_db(&state.db_pool, &prompt, Some(&lang))
         .await
-        .map_err(|e| format!("Database error: {}", e))?;
+        .map_err(ClarityError::from)?;
 
     // 如果当前语言匹配，更新内存中的值
     // 注意：这里我们不再更新内存，因为内存中只存储一个值
@@ -211,74 +1281,117 @@ pub async fn set_ai_prompt(
 pub async fn reset_ai_prompt(
     state: State<'_, AppState>,
     language: Option<String>,
-) -> Result<String, String> {
-    let lang = language.as_deref().unwrap_or("zh");
+) -> Result<String, ClarityError> {
+    let lang = prompt_language(language)?;
 
-    let default_prompt = if lang == "en" {
-        "Analyze this screen activity video and provide a concise activity summary. Focus on: 1) Main apps/websites used; 2) Activity type (work/entertainment/learning, etc.); 3) Any distractions or inefficient behaviors. Respond in English, keep it under 100 words.".to_string()
-    } else {
-        "分析这段屏幕活动视频，提供简洁的活动摘要。重点关注：1) 主要使用的应用/网站；2) 活动类型（工作/娱乐/学习等）；3) 是否有分心或低效行为。用中文回答，控制在100字以内。".to_string()
-    };
+    let default_prompt = locale::render(&lang, Template::SummaryPrompt, &[]);
 
     // 保存到数据库（按语言）
-    settings::save_ai_prompt_to_db(&state.db_pool, &default_prompt, Some(lang))
+    settings::save_ai_prompt_to_db(&state.db_pool, &default_prompt, Some(&lang))
         .await
-        .map_err(|e| format!("Database error: {}", e))?;
+        .map_err(ClarityError::from)?;
 
     log::info!("AI prompt reset to default for language: {}", lang);
 
     Ok(default_prompt)
 }
 
-// 获取视频分辨率设置
+// 获取总结视频保留策略
 #[tauri::command]
-pub async fn get_video_resolution(state: State<'_, AppState>) -> Result<String, String> {
-    Ok(state.video_resolution.lock().await.clone())
+pub async fn get_video_retention(
+    state: State<'_, AppState>,
+) -> Result<VideoRetention, ClarityError> {
+    let keep_videos = settings::load_keep_videos_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| "forever".to_string());
+    let days = settings::load_keep_videos_days_from_db(&state.db_pool)
+        .await
+        .unwrap_or(7);
+
+    Ok(VideoRetention { keep_videos, days })
 }
 
-// 设置视频分辨率
+// 设置总结视频保留策略（never: 总结后立即删除，for_n_days: 保留 N 天，forever: 永久保留）
 #[tauri::command]
-pub async fn set_video_resolution(
+pub async fn set_video_retention(
     state: State<'_, AppState>,
-    resolution: String,
-) -> Result<(), String> {
-    if resolution != "low" && resolution != "default" {
-        return Err("Resolution must be 'low' or 'default'".to_string());
+    keep_videos: String,
+    days: Option<u64>,
+) -> Result<(), ClarityError> {
+    if keep_videos != "never" && keep_videos != "for_n_days" && keep_videos != "forever" {
+        return Err(ClarityError::InvalidInput(
+            "keep_videos must be 'never', 'for_n_days' or 'forever'".to_string(),
+        ));
+    }
+
+    let days = days.unwrap_or(7);
+    if keep_videos == "for_n_days" && days == 0 {
+        return Err(ClarityError::InvalidInput(
+            "Retention days must be at least 1".to_string(),
+        ));
     }
 
     // 保存到数据库
-    settings::save_video_resolution_to_db(&state.db_pool, &resolution)
+    settings::save_keep_videos_to_db(&state.db_pool, &keep_videos, days)
         .await
-        .map_err(|e| format!("Database error: {}", e))?;
+        .map_err(ClarityError::from)?;
 
-    // 更新内存中的值
-    *state.video_resolution.lock().await = resolution.clone();
-    log::info!("Video resolution updated to: {}", resolution);
+    log::info!(
+        "Video retention updated to: {} ({} days)",
+        keep_videos,
+        days
+    );
 
     Ok(())
 }
 
-// 获取语言设置
+// 获取各预设和帧率组合的 token 估算（基于当前总结间隔和视频分辨率）
 #[tauri::command]
-pub async fn get_language(state: State<'_, AppState>) -> Result<String, String> {
-    Ok(state.language.lock().await.clone())
-}
+pub async fn get_video_preset_estimates(
+    state: State<'_, AppState>,
+) -> Result<Vec<VideoPresetEstimate>, ClarityError> {
+    let interval = *state.summary_interval_seconds.lock().await;
+    let media_resolution = state.video_resolution.lock().await.clone();
 
-// 设置语言
-#[tauri::command]
-pub async fn set_language(state: State<'_, AppState>, language: String) -> Result<(), String> {
-    if language != "en" && language != "zh" {
-        return Err("Language must be 'en' or 'zh'".to_string());
+    let mut estimates = Vec::new();
+    for (preset, width, height) in video_summary::VIDEO_PRESETS {
+        for fps in video_summary::MIN_VIDEO_FPS..=video_summary::MAX_VIDEO_FPS {
+            estimates.push(VideoPresetEstimate {
+                preset: preset.to_string(),
+                width,
+                height,
+                fps,
+                estimated_tokens_per_summary: video_summary::estimate_video_tokens(
+                    interval,
+                    fps,
+                    &media_resolution,
+                ),
+            });
+        }
     }
 
-    // 保存到数据库
-    settings::save_language_to_db(&state.db_pool, &language)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(estimates)
+}
 
-    // 更新内存中的值
-    *state.language.lock().await = language.clone();
-    log::info!("Language updated to: {}", language);
+// 获取是否开机自动启动
+#[tauri::command]
+pub async fn get_autostart(app: AppHandle) -> Result<bool, ClarityError> {
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| ClarityError::Internal(format!("Autostart error: {}", e)))
+}
+
+// 设置是否开机自动启动
+#[tauri::command]
+pub async fn set_autostart(app: AppHandle, enabled: bool) -> Result<(), ClarityError> {
+    let autolaunch = app.autolaunch();
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|e| ClarityError::Internal(format!("Autostart error: {}", e)))?;
+    log::info!("Autostart updated to: {}", enabled);
 
     Ok(())
 }
@@ -0,0 +1,395 @@
+use crate::commands::backup::collect_recordings;
+use crate::commands::data::remove_traces;
+use crate::db;
+use crate::error::ClarityError;
+use crate::settings;
+use crate::state::{AppState, RecordingState};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration as StdDuration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::{watch, Mutex};
+
+// 后台检查的间隔
+const RECONCILE_INTERVAL_SECONDS: u64 = 24 * 60 * 60;
+
+// 最近修改的文件可能还没写入数据库，不算作孤立文件
+const RECONCILE_MIN_FILE_AGE_SECONDS: u64 = 60;
+
+// 报告中最多列出的路径数量
+const RECONCILE_SAMPLE_LIMIT: usize = 50;
+
+// 检查是否需要自动优化数据库的间隔
+const OPTIMIZE_CHECK_INTERVAL_SECONDS: u64 = 6 * 60 * 60;
+
+// 自动优化数据库的周期（天）
+const OPTIMIZE_INTERVAL_DAYS: i64 = 30;
+
+// 磁盘和数据库的对账结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileReport {
+    pub orphan_file_count: u64,      // 没有数据库记录的截图
+    pub orphan_thumbnail_count: u64, // 没有对应记录的缩略图
+    pub missing_file_count: u64,     // 截图文件已不存在的记录
+    pub orphan_file_samples: Vec<String>,
+    pub missing_file_samples: Vec<String>,
+    pub repaired: bool,
+    pub restored_rows: u64, // 为孤立截图补回的记录
+    pub removed_rows: u64,  // 删除的文件缺失记录
+    pub removed_thumbnails: u64,
+}
+
+fn is_recently_modified(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age.as_secs() < RECONCILE_MIN_FILE_AGE_SECONDS)
+}
+
+// 为孤立截图补回数据库记录（时间从文件名 YYYY-MM-DD_HH-MM-SS_index.jpg 中解析）
+async fn restore_trace_row(db_pool: &SqlitePool, path: &Path) -> Result<bool, ClarityError> {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return Ok(false);
+    };
+    let Some(timestamp) = name
+        .get(..19)
+        .and_then(|s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d_%H-%M-%S").ok())
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+    else {
+        return Ok(false);
+    };
+    if db::screenshot_trace_exists_at(db_pool, timestamp)
+        .await
+        .map_err(ClarityError::from)?
+    {
+        return Ok(false);
+    }
+
+    let Ok((width, height)) = image::image_dimensions(path) else {
+        return Ok(false);
+    };
+    let file_size = tokio::fs::metadata(path)
+        .await
+        .map(|m| m.len() as i64)
+        .unwrap_or(0);
+    let thumbnail_path = path
+        .parent()
+        .map(|dir| dir.join("thumbs").join(&name))
+        .filter(|thumb| thumb.exists())
+        .map(|thumb| thumb.to_string_lossy().to_string());
+
+    let trace = db::NewScreenshotTrace {
+        timestamp,
+        file_path: path.to_string_lossy().to_string(),
+        width: width as i32,
+        height: height as i32,
+        file_size,
+        thumbnail_path,
+        cursor_x: None,
+        cursor_y: None,
+        private: false,
+        app_name: None,
+    };
+    db::insert_screenshot_trace(db_pool, trace)
+        .await
+        .map_err(ClarityError::from)?;
+    Ok(true)
+}
+
+// 对比存储目录中的截图文件和数据库记录；repair 为 true 时为孤立截图补回记录、
+// 删除孤立缩略图，并删除文件已不存在的记录
+pub async fn reconcile(
+    db_pool: &SqlitePool,
+    storage_path: &Path,
+    repair: bool,
+) -> Result<ReconcileReport, ClarityError> {
+    let traces = db::get_screenshot_traces(db_pool, None, None, None)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let root = storage_path.to_path_buf();
+    let files = tokio::task::spawn_blocking(move || collect_recordings(&root, None, None))
+        .await
+        .map_err(|e| format!("Storage scan failed: {}", e))?
+        .map_err(|e| format!("Failed to read recordings: {}", e))?;
+
+    let referenced: HashSet<PathBuf> = traces
+        .iter()
+        .flat_map(|t| {
+            std::iter::once(PathBuf::from(&t.file_path))
+                .chain(t.thumbnail_path.as_ref().map(PathBuf::from))
+        })
+        .collect();
+
+    let mut report = ReconcileReport {
+        repaired: repair,
+        ..Default::default()
+    };
+    let mut orphan_files = Vec::new();
+    let mut orphan_thumbnails = Vec::new();
+    for relative in files {
+        let path = storage_path.join(&relative);
+        // 只处理截图（.jpg），日期目录中的其他文件保持不变
+        let is_screenshot = path.extension().is_some_and(|ext| ext == "jpg");
+        if !is_screenshot || referenced.contains(&path) || is_recently_modified(&path) {
+            continue;
+        }
+        let is_thumbnail = path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .is_some_and(|dir| dir == "thumbs");
+        if is_thumbnail {
+            orphan_thumbnails.push(path);
+        } else {
+            orphan_files.push(path);
+        }
+    }
+
+    let mut missing = Vec::new();
+    for trace in traces {
+        if tokio::fs::metadata(&trace.file_path).await.is_err() {
+            missing.push(trace);
+        }
+    }
+
+    report.orphan_file_count = orphan_files.len() as u64;
+    report.orphan_thumbnail_count = orphan_thumbnails.len() as u64;
+    report.missing_file_count = missing.len() as u64;
+    report.orphan_file_samples = orphan_files
+        .iter()
+        .take(RECONCILE_SAMPLE_LIMIT)
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    report.missing_file_samples = missing
+        .iter()
+        .take(RECONCILE_SAMPLE_LIMIT)
+        .map(|t| t.file_path.clone())
+        .collect();
+
+    if !repair {
+        return Ok(report);
+    }
+
+    // 先删除文件缺失的记录，补回记录时同一时间的旧记录不会阻止去重判断
+    if !missing.is_empty() {
+        report.removed_rows = remove_traces(db_pool, &missing, false).await?.trace_count;
+    }
+    for path in &orphan_files {
+        if restore_trace_row(db_pool, path).await? {
+            report.restored_rows += 1;
+        }
+    }
+    for path in &orphan_thumbnails {
+        // 补回记录时可能重新引用了缩略图
+        let name = path.file_name().unwrap_or_default();
+        let screenshot = path
+            .parent()
+            .and_then(|dir| dir.parent())
+            .map(|dir| dir.join(name));
+        if screenshot.is_some_and(|s| orphan_files.contains(&s) && s.exists()) {
+            continue;
+        }
+        match tokio::fs::remove_file(path).await {
+            Ok(_) => report.removed_thumbnails += 1,
+            Err(e) => log::warn!("Failed to delete thumbnail {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(report)
+}
+
+// 定期检查磁盘和数据库是否一致（只记录日志，不自动修复）
+pub async fn reconcile_loop(db_pool: SqlitePool, storage_path: Arc<Mutex<PathBuf>>) {
+    let mut interval = tokio::time::interval(StdDuration::from_secs(RECONCILE_INTERVAL_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        let storage = storage_path.lock().await.clone();
+        match reconcile(&db_pool, &storage, false).await {
+            Ok(report) => {
+                if report.orphan_file_count > 0
+                    || report.orphan_thumbnail_count > 0
+                    || report.missing_file_count > 0
+                {
+                    log::warn!(
+                        "Storage out of sync: {} screenshots without records, {} orphan thumbnails, {} records without files",
+                        report.orphan_file_count,
+                        report.orphan_thumbnail_count,
+                        report.missing_file_count
+                    );
+                }
+            }
+            Err(e) => log::error!("Failed to reconcile storage: {}", e),
+        }
+    }
+}
+
+// 检查磁盘和数据库是否一致，repair 为 true 时同时修复
+#[tauri::command]
+pub async fn reconcile_storage(
+    state: State<'_, AppState>,
+    repair: bool,
+) -> Result<ReconcileReport, ClarityError> {
+    let storage_path = state.storage_path.lock().await.clone();
+    let report = reconcile(&state.db_pool, &storage_path, repair).await?;
+
+    if repair {
+        log::info!(
+            "Storage reconciled: {} records restored, {} records removed, {} thumbnails removed",
+            report.restored_rows,
+            report.removed_rows,
+            report.removed_thumbnails
+        );
+    }
+
+    Ok(report)
+}
+
+// 数据库优化进度（"optimize-progress" 事件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeProgress {
+    pub stage: String, // "checkpoint", "vacuum", "analyze" or "completed"
+    pub current: usize,
+    pub total: usize,
+}
+
+// 一次数据库优化的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeReport {
+    pub size_before: u64,
+    pub size_after: u64,
+    pub reclaimed_bytes: u64,
+    pub duration_ms: u64,
+}
+
+// 合并 WAL、重建数据库文件（保留策略删除数据后文件不会自动变小）并更新统计信息
+pub async fn optimize(
+    db_pool: &SqlitePool,
+    app_handle: Option<&AppHandle>,
+) -> Result<OptimizeReport, ClarityError> {
+    let started = Instant::now();
+    let size_before = db::database_file_size().await;
+
+    let stages = ["checkpoint", "vacuum", "analyze"];
+    for (index, stage) in stages.iter().enumerate() {
+        if let Some(handle) = app_handle {
+            let _ = handle.emit(
+                "optimize-progress",
+                OptimizeProgress {
+                    stage: stage.to_string(),
+                    current: index,
+                    total: stages.len(),
+                },
+            );
+        }
+        let result = match *stage {
+            "checkpoint" => db::checkpoint_wal(db_pool).await,
+            "vacuum" => db::vacuum_database(db_pool).await,
+            _ => db::analyze_database(db_pool).await,
+        };
+        result.map_err(ClarityError::from)?;
+    }
+
+    // VACUUM 的内容先写入 WAL，再合并一次才能让主文件变小
+    db::checkpoint_wal(db_pool)
+        .await
+        .map_err(ClarityError::from)?;
+
+    if let Some(handle) = app_handle {
+        let _ = handle.emit(
+            "optimize-progress",
+            OptimizeProgress {
+                stage: "completed".to_string(),
+                current: stages.len(),
+                total: stages.len(),
+            },
+        );
+    }
+
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    if let Err(e) = settings::save_database_last_optimized_date_to_db(db_pool, &today).await {
+        log::error!("Failed to save last database optimization date: {}", e);
+    }
+
+    let size_after = db::database_file_size().await;
+    let report = OptimizeReport {
+        size_before,
+        size_after,
+        reclaimed_bytes: size_before.saturating_sub(size_after),
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    log::info!(
+        "Database optimized in {} ms: {} -> {} bytes",
+        report.duration_ms,
+        report.size_before,
+        report.size_after
+    );
+    Ok(report)
+}
+
+// 每月自动优化一次数据库（录制时跳过，VACUUM 期间无法写入截图记录）
+pub async fn database_maintenance_loop(
+    db_pool: SqlitePool,
+    recording: watch::Receiver<RecordingState>,
+) {
+    let mut interval =
+        tokio::time::interval(StdDuration::from_secs(OPTIMIZE_CHECK_INTERVAL_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        let today = Local::now().date_naive();
+        let due = settings::load_database_last_optimized_date_from_db(&db_pool)
+            .await
+            .ok()
+            .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+            .is_none_or(|last| (today - last).num_days() >= OPTIMIZE_INTERVAL_DAYS);
+        if !due || *recording.borrow() != RecordingState::Stopped {
+            continue;
+        }
+
+        if let Err(e) = optimize(&db_pool, None).await {
+            log::error!("Failed to optimize database: {}", e);
+        }
+    }
+}
+
+// 立即优化数据库，过程中发送 "optimize-progress" 事件
+#[tauri::command]
+pub async fn optimize_database(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<OptimizeReport, ClarityError> {
+    optimize(&state.db_pool, Some(&app)).await
+}
+
+// 检查数据库完整性，供诊断页面显示
+#[tauri::command]
+pub async fn check_database_integrity(
+    state: State<'_, AppState>,
+) -> Result<db::IntegrityReport, ClarityError> {
+    let report = db::check_integrity(&state.db_pool)
+        .await
+        .map_err(ClarityError::from)?;
+
+    if !report.ok {
+        log::warn!(
+            "Database integrity check found problems: {} integrity errors, {} missing tables, {} missing indexes, {} invalid timestamps",
+            report.integrity_errors.len(),
+            report.missing_tables.len(),
+            report.missing_indexes.len(),
+            report.invalid_timestamps.len()
+        );
+    }
+
+    Ok(report)
+}
@@ -1,17 +1,301 @@
+use crate::commands::notifications::{self, SummaryNotification};
+use crate::commands::tags::auto_tag_summary;
+use crate::commands::{breaks, goals, incognito, lock, webhooks};
 use crate::db;
+use crate::error::ClarityError;
+use crate::events;
+use crate::health::{self, HealthMonitor};
+use crate::keychain;
+use crate::locale::{self, Template};
+use crate::meeting::{ActiveMeeting, MEETING_CATEGORY};
+use crate::ocr;
+use crate::ollama;
+use crate::openai;
+use crate::openrouter;
+use crate::proxy;
+use crate::redaction;
 use crate::screenshot;
 use crate::settings;
-use crate::state::AppState;
-use crate::video_summary;
-use chrono::{DateTime, Local, NaiveDate};
+use crate::state::{AppState, RecordingState};
+use crate::video_summary::{self, SummaryStage};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
+use std::collections::VecDeque;
+use std::future::Future;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
-use std::time::Duration as StdDuration;
+use std::time::{Duration as StdDuration, Instant};
 use tauri::{AppHandle, Emitter, State};
-use tokio::sync::Mutex;
-use tokio::time::interval;
+use tokio::sync::{oneshot, watch, Mutex};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::{interval, interval_at, Interval};
+
+// 长时间间隔按此时长（秒）分段总结
+const SUMMARY_CHUNK_SECONDS: i64 = 10 * 60;
+
+// 总结失败的视频最多重新上传的次数
+const MAX_VIDEO_RETRIES: i32 = 3;
+
+// 总结任务最多尝试的次数
+const MAX_JOB_ATTEMPTS: i32 = 3;
+
+// 停止或暂停录制时，最后一段不足这个时长（秒）的录制不再总结
+const MIN_FLUSH_SUMMARY_SECONDS: i64 = 10;
+
+// 手动总结的时间段最长时长（秒），更长的时间段请使用每日总结
+const MAX_SUMMARIZE_RANGE_SECONDS: i64 = 24 * 60 * 60;
+
+// 同时处理的总结任务数（默认值和上限）
+pub const DEFAULT_SUMMARY_CONCURRENCY: usize = 1;
+pub const MAX_SUMMARY_CONCURRENCY: usize = 4;
+
+// 每分钟最多发送的 AI 请求数默认值（0 表示不限制）
+pub const DEFAULT_SUMMARY_REQUESTS_PER_MINUTE: u32 = 10;
+
+// 请求频率限制的统计窗口
+const RATE_LIMIT_WINDOW: StdDuration = StdDuration::from_secs(60);
+
+// worker 检查新任务的间隔（秒）
+const WORKER_POLL_SECONDS: u64 = 5;
+
+// 重新上传失败视频的检查间隔（秒）
+const VIDEO_RETRY_INTERVAL_SECONDS: u64 = 60;
+
+// Gemini key 遇到配额错误后，在此时间内排到其他 key 之后
+const GEMINI_KEY_COOLDOWN_SECONDS: i64 = 60;
+
+// 网络断开或 AI 服务不可用时，每隔多久检查一次是否恢复
+const CONNECTIVITY_CHECK_SECONDS: u64 = 30;
+
+// 上一条摘要在此时间内结束时才作为上下文（间隔太久的活动不算连续）
+const ROLLING_CONTEXT_MAX_GAP_SECONDS: i64 = 30 * 60;
+
+// 主模型连续失败（包括超时）达到此次数后，失败的请求改用备用模型重试
+const FALLBACK_AFTER_FAILURES: u32 = 2;
+
+// 一段截图的总结结果
+struct ChunkSummary {
+    content: String,
+    structured_data: Option<serde_json::Value>, // 服务支持结构化输出时才有
+}
+
+impl From<video_summary::ApiRequestResult> for ChunkSummary {
+    fn from(result: video_summary::ApiRequestResult) -> Self {
+        ChunkSummary {
+            content: result.content,
+            structured_data: result
+                .structured_data
+                .and_then(|json| serde_json::from_str(&json).ok()),
+        }
+    }
+}
+
+// 本次总结使用的 AI 服务
+#[derive(Clone)]
+enum SummaryProvider {
+    // 上传视频到 Gemini File API（按顺序使用多个 key，遇到配额错误时换下一个）
+    Gemini {
+        api_keys: Vec<db::GeminiApiKey>,
+        model: String,
+        fallback_from: Option<String>, // 改用备用模型时为失败的主模型
+    },
+    // 抽样截图发送到 OpenAI 兼容接口（OpenRouter 也走这里）
+    OpenAi {
+        base_url: String,
+        api_key: String,
+        model: String,
+        fallback_from: Option<String>,
+    },
+    // 抽样截图发送到本地 Ollama，不需要 API key
+    Ollama {
+        base_url: String,
+        model: String,
+        fallback_from: Option<String>,
+    },
+}
+
+impl SummaryProvider {
+    fn model(&self) -> &str {
+        match self {
+            SummaryProvider::Gemini { model, .. }
+            | SummaryProvider::OpenAi { model, .. }
+            | SummaryProvider::Ollama { model, .. } => model,
+        }
+    }
+
+    // 使用同一个服务的另一个模型（对比模式使用）
+    fn with_model(&self, model: &str) -> SummaryProvider {
+        let mut provider = self.clone();
+        match &mut provider {
+            SummaryProvider::Gemini { model: m, .. }
+            | SummaryProvider::OpenAi { model: m, .. }
+            | SummaryProvider::Ollama { model: m, .. } => *m = model.to_string(),
+        }
+        provider
+    }
+
+    // 主模型失败后改用的备用模型，记录原来的主模型
+    fn fallback(&self, model: &str) -> SummaryProvider {
+        let mut provider = self.with_model(model);
+        match &mut provider {
+            SummaryProvider::Gemini { fallback_from, .. }
+            | SummaryProvider::OpenAi { fallback_from, .. }
+            | SummaryProvider::Ollama { fallback_from, .. } => {
+                *fallback_from = Some(self.model().to_string())
+            }
+        }
+        provider
+    }
+
+    fn fallback_from(&self) -> Option<&str> {
+        match self {
+            SummaryProvider::Gemini { fallback_from, .. }
+            | SummaryProvider::OpenAi { fallback_from, .. }
+            | SummaryProvider::Ollama { fallback_from, .. } => fallback_from.as_deref(),
+        }
+    }
+
+    // 是否支持上传视频（不支持的服务只能使用截图模式）
+    fn supports_video(&self) -> bool {
+        matches!(self, SummaryProvider::Gemini { .. })
+    }
+
+    // 记录到 api_requests 的接口地址
+    fn endpoint(&self) -> String {
+        match self {
+            SummaryProvider::Gemini { .. } => {
+                "https://generativelanguage.googleapis.com/v1beta/models".to_string()
+            }
+            SummaryProvider::OpenAi { base_url, .. } => openai::chat_completions_url(base_url),
+            SummaryProvider::Ollama { base_url, .. } => ollama::generate_url(base_url),
+        }
+    }
+}
+
+// 根据设置加载当前的 AI 服务，未设置 API key 时返回 None
+async fn load_summary_provider(
+    db_pool: &SqlitePool,
+    ai_model: &Arc<Mutex<String>>,
+) -> Option<SummaryProvider> {
+    let provider = settings::load_ai_provider_from_db(db_pool)
+        .await
+        .unwrap_or_else(|_| "gemini".to_string());
+
+    if provider == "openai" {
+        let api_key = settings::load_openai_api_key_from_db(db_pool)
+            .await
+            .ok()
+            .filter(|key| !key.is_empty())?;
+        let base_url = settings::load_openai_base_url_from_db(db_pool)
+            .await
+            .unwrap_or_else(|_| openai::DEFAULT_OPENAI_BASE_URL.to_string());
+        let model = settings::load_openai_model_from_db(db_pool)
+            .await
+            .unwrap_or_else(|_| openai::OPENAI_MODELS[0].to_string());
+        return Some(SummaryProvider::OpenAi {
+            base_url,
+            api_key,
+            model,
+            fallback_from: None,
+        });
+    }
+
+    // OpenRouter 兼容 OpenAI 协议，只是使用独立的 key、地址和模型设置
+    if provider == "openrouter" {
+        let api_key = settings::load_openrouter_api_key_from_db(db_pool)
+            .await
+            .ok()
+            .filter(|key| !key.is_empty())?;
+        let base_url = settings::load_openrouter_base_url_from_db(db_pool)
+            .await
+            .unwrap_or_else(|_| openrouter::DEFAULT_OPENROUTER_BASE_URL.to_string());
+        let model = settings::load_openrouter_model_from_db(db_pool)
+            .await
+            .unwrap_or_else(|_| openrouter::DEFAULT_OPENROUTER_MODEL.to_string());
+        return Some(SummaryProvider::OpenAi {
+            base_url,
+            api_key,
+            model,
+            fallback_from: None,
+        });
+    }
+
+    if provider == "ollama" {
+        let base_url = settings::load_ollama_base_url_from_db(db_pool)
+            .await
+            .unwrap_or_else(|_| ollama::DEFAULT_OLLAMA_BASE_URL.to_string());
+        let model = settings::load_ollama_model_from_db(db_pool)
+            .await
+            .unwrap_or_else(|_| ollama::DEFAULT_OLLAMA_MODEL.to_string());
+        return Some(SummaryProvider::Ollama {
+            base_url,
+            model,
+            fallback_from: None,
+        });
+    }
+
+    let mut api_keys = match keychain::load_gemini_api_keys(db_pool).await {
+        Ok(keys) => keys,
+        Err(e) => {
+            log::error!("Failed to load Gemini API keys: {}", e);
+            return None;
+        }
+    };
+    if api_keys.is_empty() {
+        return None;
+    }
+    // 刚遇到配额错误的 key 排到最后，冷却后恢复原来的顺序
+    api_keys.sort_by_key(is_gemini_key_cooling_down);
+
+    let model = ai_model.lock().await.clone();
+    Some(SummaryProvider::Gemini {
+        api_keys,
+        model,
+        fallback_from: None,
+    })
+}
+
+// Gemini key 是否还在配额错误后的冷却期内
+fn is_gemini_key_cooling_down(key: &db::GeminiApiKey) -> bool {
+    key.last_quota_error_at
+        .is_some_and(|t| (Local::now() - t).num_seconds() < GEMINI_KEY_COOLDOWN_SECONDS)
+}
+
+// 是否为 429 / 配额用尽错误（可以换下一个 key 重试）
+fn is_quota_error(error: &ClarityError) -> bool {
+    matches!(error, ClarityError::Ai(message)
+        if message.contains("error: 429") || message.contains("RESOURCE_EXHAUSTED"))
+}
+
+// 记录一次 Gemini 调用的结果，返回是否应该换下一个 key 重试
+async fn should_try_next_gemini_key(
+    db_pool: &SqlitePool,
+    provider: &SummaryProvider,
+    key: &db::GeminiApiKey,
+    has_next_key: bool,
+    result: &Result<video_summary::ApiRequestResult, ClarityError>,
+    app_handle: Option<&AppHandle>,
+) -> bool {
+    record_api_result(db_pool, provider, Some(key.id), result, app_handle).await;
+
+    match result {
+        Err(e) if is_quota_error(e) => {
+            if let Err(e) = db::mark_gemini_api_key_quota_error(db_pool, key.id).await {
+                log::error!("Failed to record Gemini key quota error: {}", e);
+            }
+            if has_next_key {
+                log::warn!(
+                    "Gemini key {} hit rate limit or quota, switching to next key",
+                    key.id
+                );
+            }
+            has_next_key
+        }
+        _ => false,
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -20,35 +304,293 @@ pub struct HistoricalStats {
     pub screenshot_count: i64,
     pub summary_count: i64,
     pub total_duration_seconds: i64,
+    pub gap_seconds: i64, // 录制期间程序异常退出造成的中断时长
+}
+
+// 每月总结中计算得出的趋势
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyTrends {
+    pub total_tracked_hours: f64,
+    pub active_days: i64,
+    pub average_focus_score: Option<f64>,
+    pub top_categories: Vec<CategoryDuration>, // 按时长倒序
+    pub busiest_days: Vec<DayDuration>,        // 按时长倒序
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryDuration {
+    pub category: String,
+    pub duration_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayDuration {
+    pub date: String, // YYYY-MM-DD
+    pub duration_seconds: i64,
+}
+
+// 单条摘要的专注度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusScorePoint {
+    pub summary_id: i64,
+    pub start_time: DateTime<Local>,
+    pub end_time: DateTime<Local>,
+    pub focus_score: i64,
+}
+
+// 按天或按周汇总的专注度（按时长加权平均）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusScoreAggregate {
+    pub period: String, // 按天为 YYYY-MM-DD，按周为 ISO 周 YYYY-Www
+    pub average_score: f64,
+    pub summary_count: i64,
+    pub duration_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusScores {
+    pub intervals: Vec<FocusScorePoint>, // 按时间升序
+    pub daily: Vec<FocusScoreAggregate>,
+    pub weekly: Vec<FocusScoreAggregate>,
+}
+
+// 今天的 AI 用量和每日预算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub token_budget: i64,    // 0 表示不限制
+    pub cost_budget_usd: f64, // 0 表示不限制
+    pub tokens_used: i64,
+    pub cost_used_usd: f64,
+    pub exceeded: bool,
+}
+
+// 从数据库加载 Gemini 生成参数和安全设置（未设置的项使用模型默认值）
+pub async fn load_gemini_generation_settings(
+    db_pool: &SqlitePool,
+) -> video_summary::GeminiGenerationSettings {
+    video_summary::GeminiGenerationSettings {
+        safety_threshold: settings::load_gemini_safety_threshold_from_db(db_pool)
+            .await
+            .unwrap_or_else(|_| "default".to_string()),
+        temperature: settings::load_gemini_temperature_from_db(db_pool)
+            .await
+            .ok(),
+        max_output_tokens: settings::load_gemini_max_output_tokens_from_db(db_pool)
+            .await
+            .ok(),
+        thinking_budget: settings::load_gemini_thinking_budget_from_db(db_pool)
+            .await
+            .ok(),
+    }
+}
+
+// 加载活动分类，没有设置时使用默认分类
+pub async fn load_activity_categories(db_pool: &SqlitePool) -> Vec<String> {
+    settings::load_activity_categories_from_db(db_pool)
+        .await
+        .ok()
+        .filter(|categories| !categories.is_empty())
+        .unwrap_or_else(|| {
+            video_summary::DEFAULT_ACTIVITY_CATEGORIES
+                .iter()
+                .map(|c| c.to_string())
+                .collect()
+        })
+}
+
+// 统计今天的 AI 用量并和每日预算比较
+pub async fn load_budget_status(db_pool: &SqlitePool) -> Result<BudgetStatus, ClarityError> {
+    let token_budget = settings::load_daily_token_budget_from_db(db_pool)
+        .await
+        .unwrap_or(0);
+    let cost_budget_usd = settings::load_daily_cost_budget_from_db(db_pool)
+        .await
+        .unwrap_or(0.0);
+
+    let today_start = Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .ok_or_else(|| ClarityError::Internal("Invalid timezone conversion".to_string()))?;
+    let stats = db::get_api_statistics(db_pool, Some(today_start), None)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let exceeded = (token_budget > 0 && stats.total_tokens >= token_budget)
+        || (cost_budget_usd > 0.0 && stats.total_cost_usd >= cost_budget_usd);
+
+    Ok(BudgetStatus {
+        token_budget,
+        cost_budget_usd,
+        tokens_used: stats.total_tokens,
+        cost_used_usd: stats.total_cost_usd,
+        exceeded,
+    })
 }
 
-// 视频总结任务
-pub async fn video_summary_loop(
-    storage_path: PathBuf,
+// 总结定时任务的句柄，保存在 AppState 中，整个程序运行期间只有一个
+pub struct SummaryLoopHandle {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+// 启动总结定时任务（已经在运行时不做任何事），任务一直运行到 stop_summary_loop，按录制状态决定是否入队
+pub async fn start_summary_loop(state: &AppState) {
+    let mut summary_loop = state.summary_loop.lock().await;
+    if summary_loop
+        .as_ref()
+        .is_some_and(|handle| !handle.task.is_finished())
+    {
+        return;
+    }
+
+    let (stop, stop_rx) = oneshot::channel();
+    let db_pool = state.db_pool.clone();
+    let recording = state.recording.subscribe();
+    let summary_interval_seconds = state.summary_interval_seconds.clone();
+    let ai_model = state.ai_model.clone();
+    let health = state.health.clone();
+    health.summary_loop_started();
+    let task = tokio::spawn(async move {
+        let result = tokio::spawn(video_summary_loop(
+            db_pool,
+            recording,
+            stop_rx,
+            summary_interval_seconds,
+            ai_model,
+        ))
+        .await;
+        // 出错时记录日志，结束后更新健康状态
+        if let Err(e) = result {
+            log::error!("Video summary task panicked: {:?}", e);
+            health.record_error(health::TASK_SUMMARY_LOOP, format!("panicked: {}", e));
+        }
+        health.summary_loop_stopped();
+    });
+
+    *summary_loop = Some(SummaryLoopHandle { stop, task });
+}
+
+// 停止总结定时任务并等待它退出
+pub async fn stop_summary_loop(state: &AppState) {
+    let Some(handle) = state.summary_loop.lock().await.take() else {
+        return;
+    };
+    // 任务已经退出时接收端不存在，忽略错误
+    let _ = handle.stop.send(());
+    if let Err(e) = handle.task.await {
+        log::error!("Video summary task panicked: {:?}", e);
+    }
+}
+
+// 从现在开始每隔 seconds 秒触发一次（不立即触发，等待完整的间隔时间）
+fn summary_timer(seconds: u64) -> Interval {
+    let period = StdDuration::from_secs(seconds);
+    interval_at(tokio::time::Instant::now() + period, period)
+}
+
+// 检查是否可以总结（已设置 API key，没有超出每日预算），不能时记录原因
+async fn can_queue_summary(db_pool: &SqlitePool, ai_model: &Arc<Mutex<String>>) -> bool {
+    // 未设置 API key 时不入队
+    if load_summary_provider(db_pool, ai_model).await.is_none() {
+        log::warn!("AI provider API key not set, skipping video summary");
+        return false;
+    }
+
+    // 超出每日预算时跳过本次总结，截图照常保存
+    match load_budget_status(db_pool).await {
+        Ok(status) if status.exceeded => {
+            log::warn!("Daily AI budget exceeded, skipping video summary");
+            false
+        }
+        Ok(_) => true,
+        Err(e) => {
+            log::error!("Failed to check daily AI budget: {}", e);
+            true
+        }
+    }
+}
+
+async fn queue_summary_job(
+    db_pool: &SqlitePool,
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+) {
+    match db::insert_summary_job(db_pool, start_time, end_time).await {
+        Ok(id) => log::info!(
+            "Queued summary job {} for {} seconds",
+            id,
+            (end_time - start_time).num_seconds()
+        ),
+        Err(e) => log::error!("Failed to queue summary job: {}", e),
+    }
+}
+
+// 视频总结定时任务：录制时每个间隔把最近一段时间加入总结任务队列，由 summary_worker_loop 处理
+// 停止和暂停录制时不退出，把上次入队之后不足一个间隔的时间段也加入队列，之后不再入队；
+// 每次开始录制时重新计时
+async fn video_summary_loop(
     db_pool: SqlitePool,
-    is_recording: Arc<Mutex<bool>>,
-    gemini_api_key: Arc<Mutex<Option<String>>>,
+    mut recording: watch::Receiver<RecordingState>,
+    mut stop: oneshot::Receiver<()>,
     summary_interval_seconds: Arc<Mutex<u64>>,
-    app_handle: Option<AppHandle>,
     ai_model: Arc<Mutex<String>>,
-    _ai_prompt: Arc<Mutex<String>>,
-    video_resolution: Arc<Mutex<String>>,
 ) {
     log::info!("Video summary loop started");
     let mut current_interval = *summary_interval_seconds.lock().await;
-    let mut interval_timer = interval(StdDuration::from_secs(current_interval));
-    // 跳过第一次立即触发，等待完整的间隔时间
-    interval_timer.tick().await;
+    let mut interval_timer = summary_timer(current_interval);
+    let mut recording_state = *recording.borrow_and_update();
+    // 还没有加入队列的录制时间段的开始时间（上次入队的结束时间，或开始 / 恢复录制的时间）
+    let mut pending_since = Local::now();
     log::info!("Video summary interval set to {} seconds", current_interval);
 
     loop {
-        interval_timer.tick().await;
+        tokio::select! {
+            _ = &mut stop => break,
+            _ = interval_timer.tick() => {}
+            changed = recording.changed() => {
+                // AppState 已经释放
+                if changed.is_err() {
+                    break;
+                }
+                let new_state = *recording.borrow_and_update();
+                match (recording_state, new_state) {
+                    // 开始新的录制时重新计时，第一段总结包含完整的间隔
+                    (RecordingState::Stopped, RecordingState::Recording) => {
+                        interval_timer.reset();
+                        pending_since = Local::now();
+                    }
+                    // 恢复录制：暂停期间不计入下一段
+                    (RecordingState::Paused, RecordingState::Recording) => {
+                        pending_since = Local::now();
+                    }
+                    // 停止或暂停：最后不足一个间隔的部分也加入队列，避免丢失
+                    (RecordingState::Recording, _) => {
+                        let end_time = Local::now();
+                        if (end_time - pending_since).num_seconds() >= MIN_FLUSH_SUMMARY_SECONDS
+                            && can_queue_summary(&db_pool, &ai_model).await
+                        {
+                            queue_summary_job(&db_pool, pending_since, end_time).await;
+                        }
+                        pending_since = end_time;
+                    }
+                    _ => {}
+                }
+                recording_state = new_state;
+                continue;
+            }
+        }
         log::debug!("Video summary tick");
 
-        // 检查是否还在录制
-        let recording = *is_recording.lock().await;
-        if !recording {
-            log::debug!("Recording is not active, skipping video summary");
+        // 停止和暂停期间不入队
+        if recording_state != RecordingState::Recording {
             continue;
         }
 
@@ -61,194 +603,2062 @@ pub async fn video_summary_loop(
                 new_interval
             );
             current_interval = new_interval;
-            interval_timer = interval(StdDuration::from_secs(current_interval));
+            interval_timer = summary_timer(current_interval);
             continue; // 跳过本次，等待新的间隔
         }
 
-        // 检查 API key
-        let api_key = gemini_api_key.lock().await.clone();
-        if api_key.is_none() {
-            log::warn!("Google Gemini API key not set, skipping video summary");
+        if !can_queue_summary(&db_pool, &ai_model).await {
             continue;
         }
-        let api_key = api_key.unwrap();
-        log::info!(
-            "Starting video summary for last {} seconds",
-            current_interval
-        );
 
-        // 获取最近 N 秒的截图（N = summary_interval_seconds）
-        let seconds_ago = Local::now() - chrono::Duration::seconds(current_interval as i64);
-        match db::get_screenshot_traces(&db_pool, Some(seconds_ago), None, None).await {
-            Ok(traces) => {
-                if traces.is_empty() {
-                    log::warn!("No screenshots in the last {} seconds", current_interval);
-                    continue;
+        // 最多总结一个间隔；恢复录制后的第一段只包含恢复之后的时间
+        let end_time = Local::now();
+        let start_time =
+            pending_since.max(end_time - chrono::Duration::seconds(current_interval as i64));
+        queue_summary_job(&db_pool, start_time, end_time).await;
+        pending_since = end_time;
+    }
+    log::info!("Video summary loop stopped");
+}
+
+// 按分钟限制 AI 请求次数（滑动窗口），所有总结任务共用
+pub struct RateLimiter {
+    db_pool: SqlitePool,
+    requests: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(db_pool: SqlitePool) -> Self {
+        RateLimiter {
+            db_pool,
+            requests: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    // 等待直到可以发送下一个请求（每次读取最新的限制设置）
+    async fn acquire(&self) {
+        loop {
+            let limit = settings::load_summary_requests_per_minute_from_db(&self.db_pool)
+                .await
+                .unwrap_or(DEFAULT_SUMMARY_REQUESTS_PER_MINUTE);
+
+            let wait = {
+                let mut requests = self.requests.lock().await;
+                let now = Instant::now();
+                while requests
+                    .front()
+                    .is_some_and(|t| now.duration_since(*t) >= RATE_LIMIT_WINDOW)
+                {
+                    requests.pop_front();
+                }
+
+                if limit == 0 || requests.len() < limit as usize {
+                    requests.push_back(now);
+                    return;
+                }
+
+                RATE_LIMIT_WINDOW - now.duration_since(requests[0])
+            };
+
+            log::info!(
+                "Summary rate limit reached ({} requests/minute), waiting {}s",
+                limit,
+                wait.as_secs()
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+// 总结任务 worker 使用的共享状态
+#[derive(Clone)]
+pub struct SummaryWorker {
+    storage_path: Arc<Mutex<PathBuf>>,
+    db_pool: SqlitePool,
+    ai_model: Arc<Mutex<String>>,
+    video_resolution: Arc<Mutex<String>>,
+    video_preset: Arc<Mutex<String>>,
+    video_fps: Arc<Mutex<u32>>,
+    video_codec: Arc<Mutex<String>>,
+    ffmpeg_path: Arc<Mutex<Option<String>>>,
+    app_handle: Option<AppHandle>,
+    rate_limiter: Arc<RateLimiter>,
+    primary_model_failures: Arc<AtomicU32>,
+    offline: Arc<Mutex<bool>>, // 网络断开或 AI 服务不可用，暂停处理直到恢复
+    incognito: Arc<Mutex<bool>>, // 隐身模式下不上传任何内容，暂停处理
+    ai_upload_consent: Arc<Mutex<bool>>, // 用户没有同意上传给 AI 时暂停处理
+    meeting: Arc<Mutex<Option<ActiveMeeting>>>, // 正在进行的会议，期间的本地截图标记为会议时间
+    health: Arc<HealthMonitor>,
+}
+
+impl SummaryWorker {
+    // 命令行模式没有窗口，app_handle 为 None 时不发送事件和通知
+    pub fn new(state: &AppState, app_handle: Option<AppHandle>) -> Self {
+        SummaryWorker {
+            storage_path: state.storage_path.clone(),
+            db_pool: state.db_pool.clone(),
+            ai_model: state.ai_model.clone(),
+            video_resolution: state.video_resolution.clone(),
+            video_preset: state.video_preset.clone(),
+            video_fps: state.video_fps.clone(),
+            video_codec: state.video_codec.clone(),
+            ffmpeg_path: state.ffmpeg_path.clone(),
+            app_handle,
+            rate_limiter: state.summary_rate_limiter.clone(),
+            primary_model_failures: state.primary_model_failures.clone(),
+            offline: Arc::new(Mutex::new(false)),
+            incognito: state.incognito.clone(),
+            ai_upload_consent: state.ai_upload_consent.clone(),
+            meeting: state.meeting.clone(),
+            health: state.health.clone(),
+        }
+    }
+
+    // 获取当前的视频编码和分析设置
+    async fn video_settings(&self) -> video_summary::VideoSettings {
+        video_summary::VideoSettings {
+            preset: self.video_preset.lock().await.clone(),
+            fps: *self.video_fps.lock().await,
+            codec: self.video_codec.lock().await.clone(),
+            media_resolution: self.video_resolution.lock().await.clone(),
+            ffmpeg_path: self.ffmpeg_path.lock().await.clone(),
+            input_mode: settings::load_summary_input_mode_from_db(&self.db_pool)
+                .await
+                .unwrap_or_else(|_| "video".to_string()),
+            generation: load_gemini_generation_settings(&self.db_pool).await,
+            categories: load_activity_categories(&self.db_pool).await,
+        }
+    }
+
+    // 加载当前语言和对应的提示词
+    async fn language_and_prompt(&self) -> (String, String) {
+        // 尝试从数据库加载语言设置，如果没有则默认中文
+        let current_language = settings::load_language_from_db(&self.db_pool)
+            .await
+            .unwrap_or_else(|_| locale::DEFAULT_LANGUAGE.to_string());
+
+        // 当前语言的提示词（没有保存过时按回退链使用内置模板）
+        let prompt = locale::summary_prompt(&self.db_pool, &current_language).await;
+
+        (current_language, prompt)
+    }
+
+    // 在提示词后附加上一条摘要（以及可选的当天总结），让模型描述活动的延续
+    async fn prompt_with_context(
+        &self,
+        prompt: String,
+        language: &str,
+        start_time: DateTime<Local>,
+    ) -> String {
+        if !settings::load_rolling_context_from_db(&self.db_pool)
+            .await
+            .unwrap_or(false)
+        {
+            return prompt;
+        }
+
+        let mut context = Vec::new();
+
+        let since = start_time - chrono::Duration::seconds(ROLLING_CONTEXT_MAX_GAP_SECONDS);
+        match db::get_summaries(&self.db_pool, Some(since), Some(start_time), Some(1)).await {
+            Ok(summaries) => {
+                if let Some(previous) = summaries.first() {
+                    let range = format!(
+                        "{}-{}",
+                        previous.start_time.format("%H:%M"),
+                        previous.end_time.format("%H:%M")
+                    );
+                    context.push(locale::render(
+                        language,
+                        Template::PreviousSummaryContext,
+                        &[("range", &range), ("content", &previous.content)],
+                    ));
                 }
+            }
+            Err(e) => log::warn!("Failed to load previous summary for context: {}", e),
+        }
+
+        if settings::load_rolling_context_daily_from_db(&self.db_pool)
+            .await
+            .unwrap_or(false)
+        {
+            let date = start_time.format("%Y-%m-%d").to_string();
+            match db::get_daily_summary(&self.db_pool, &date).await {
+                Ok(Some(daily)) => context.push(locale::render(
+                    language,
+                    Template::DailySummaryContext,
+                    &[("content", &daily.content)],
+                )),
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to load daily summary for context: {}", e),
+            }
+        }
+
+        if context.is_empty() {
+            return prompt;
+        }
+
+        let instruction = locale::render(language, Template::ContextInstruction, &[]);
+        format!("{}\n\n{}\n\n{}", prompt, instruction, context.join("\n\n"))
+    }
+
+    // 加载当前的 AI 服务，服务不可用时返回 None（任务保留在队列中，稍后再处理）
+    async fn available_provider(&self) -> Option<SummaryProvider> {
+        let provider = load_summary_provider(&self.db_pool, &self.ai_model).await;
+        let Some(provider) = provider else {
+            log::warn!("AI provider API key not set, summary jobs stay queued");
+            return None;
+        };
+
+        // 本地 Ollama 服务未启动时暂不处理，截图保留，不记录失败请求
+        if let SummaryProvider::Ollama { base_url, .. } = &provider {
+            if !ollama::is_server_available(base_url).await {
+                log::warn!(
+                    "Ollama server not reachable at {}, summary jobs stay queued",
+                    base_url
+                );
+                return None;
+            }
+        }
+
+        Some(provider)
+    }
+
+    // 标记为离线，暂停处理队列（截图和任务入队不受影响，恢复后按顺序补做）
+    async fn mark_offline(&self, error: &str) {
+        let mut offline = self.offline.lock().await;
+        if *offline {
+            return;
+        }
+        *offline = true;
+        log::warn!(
+            "AI service unreachable, pausing summaries until it comes back: {}",
+            error
+        );
+        if let Some(handle) = self.app_handle.as_ref() {
+            let _ = handle.emit("summary-offline", error);
+        }
+    }
+}
+
+// 是否为网络断开或服务暂时不可用的错误（不计入重试次数，恢复后再处理）
+fn is_connectivity_error(error: &ClarityError) -> bool {
+    matches!(error, ClarityError::Network(_))
+}
+
+// 检查 AI 服务是否可以访问（不消耗 token），收到非 5xx 的响应即视为已恢复
+async fn is_provider_reachable(provider: &SummaryProvider) -> bool {
+    let url = match provider {
+        SummaryProvider::Gemini { .. } => {
+            "https://generativelanguage.googleapis.com/v1beta/models".to_string()
+        }
+        SummaryProvider::OpenAi { base_url, .. } => {
+            format!("{}/models", base_url.trim_end_matches('/'))
+        }
+        SummaryProvider::Ollama { base_url, .. } => {
+            return ollama::is_server_available(base_url).await;
+        }
+    };
+
+    match proxy::client()
+        .get(&url)
+        .timeout(StdDuration::from_secs(10))
+        .send()
+        .await
+    {
+        Ok(response) => !response.status().is_server_error(),
+        Err(_) => false,
+    }
+}
+
+// 总结任务 worker 的句柄，保存在 AppState 中，整个程序运行期间只有一个
+pub struct SummaryWorkerHandle {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+// 启动总结任务 worker（已经在运行时不做任何事），录制停止后仍会处理剩余任务
+pub async fn start_summary_worker(state: &AppState) {
+    let mut summary_worker = state.summary_worker.lock().await;
+    if summary_worker
+        .as_ref()
+        .is_some_and(|handle| !handle.task.is_finished())
+    {
+        return;
+    }
+
+    let (stop, stop_rx) = oneshot::channel();
+    let app_handle = state.app_handle.lock().await.clone();
+    let worker = SummaryWorker::new(state, app_handle);
+    let task = tokio::spawn(summary_worker_loop(worker, stop_rx));
+    *summary_worker = Some(SummaryWorkerHandle { stop, task });
+}
+
+// 停止总结任务 worker 并等待它退出，正在处理的任务被取消（保持 running 状态，下次启动 worker 时重新排队）
+pub async fn stop_summary_worker(state: &AppState) {
+    let Some(handle) = state.summary_worker.lock().await.take() else {
+        return;
+    };
+    // 任务已经退出时接收端不存在，忽略错误
+    let _ = handle.stop.send(());
+    if let Err(e) = handle.task.await {
+        log::error!("Summary worker panicked: {:?}", e);
+    }
+}
 
-                log::info!("Found {} screenshots to process", traces.len());
+// 总结任务 worker：按设置的并发数处理队列中的任务，所有 AI 请求共用每分钟限额，收到 stop 后退出
+async fn summary_worker_loop(worker: SummaryWorker, mut stop: oneshot::Receiver<()>) {
+    log::info!("Summary worker started");
+
+    // 上次退出时正在处理的任务重新排队
+    match db::requeue_running_summary_jobs(&worker.db_pool).await {
+        Ok(0) => {}
+        Ok(count) => log::info!("Requeued {} interrupted summary jobs", count),
+        Err(e) => log::error!("Failed to requeue interrupted summary jobs: {}", e),
+    }
+
+    let mut running = JoinSet::new();
+    let mut poll_timer = interval(StdDuration::from_secs(WORKER_POLL_SECONDS));
+    let mut last_video_retry: Option<Instant> = None;
+    let mut last_connectivity_check: Option<Instant> = None;
+    let mut budget_exceeded_on: Option<NaiveDate> = None;
+
+    loop {
+        tokio::select! {
+            _ = &mut stop => break,
+            _ = poll_timer.tick() => {}
+        }
+        worker.health.summary_worker_heartbeat();
+
+        // 回收已经结束的任务
+        while let Some(result) = running.try_join_next() {
+            if let Err(e) = result {
+                log::error!("Summary job task panicked: {:?}", e);
+            }
+        }
+
+        // 超出每日预算时暂停处理，已入队的任务等调高预算或第二天再处理
+        match load_budget_status(&worker.db_pool).await {
+            Ok(status) if status.exceeded => {
+                // 每天只通知一次
+                let today = Local::now().date_naive();
+                if budget_exceeded_on != Some(today) {
+                    budget_exceeded_on = Some(today);
+                    log::warn!(
+                        "Daily AI budget exceeded (tokens: {}/{}, cost: ${:.4}/${:.4}), pausing summaries",
+                        status.tokens_used,
+                        status.token_budget,
+                        status.cost_used_usd,
+                        status.cost_budget_usd
+                    );
+                    if let Some(handle) = worker.app_handle.as_ref() {
+                        let _ = handle.emit("budget-exceeded", &status);
+                    }
+                }
+                continue;
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to check daily AI budget: {}", e),
+        }
+
+        // 隐身模式下或用户没有同意上传时不领取任务也不重新上传视频，之后按时间顺序继续处理
+        if *worker.incognito.lock().await || !*worker.ai_upload_consent.lock().await {
+            continue;
+        }
+
+        // 离线时不再领取任务，定期检查服务是否恢复，恢复后按时间顺序补做积压的任务
+        if *worker.offline.lock().await {
+            let check_due = last_connectivity_check
+                .is_none_or(|t| t.elapsed() >= StdDuration::from_secs(CONNECTIVITY_CHECK_SECONDS));
+            if !running.is_empty() || !check_due {
+                continue;
+            }
+            last_connectivity_check = Some(Instant::now());
+
+            let reachable = match worker.available_provider().await {
+                Some(provider) => is_provider_reachable(&provider).await,
+                None => false,
+            };
+            if !reachable {
+                continue;
+            }
+
+            *worker.offline.lock().await = false;
+            last_video_retry = None; // 立即重新上传离线期间保存的视频
+            log::info!("AI service reachable again, backfilling queued summaries");
+            if let Some(handle) = worker.app_handle.as_ref() {
+                let _ = handle.emit("summary-online", ());
+            }
+        }
+
+        let concurrency = settings::load_summary_concurrency_from_db(&worker.db_pool)
+            .await
+            .unwrap_or(DEFAULT_SUMMARY_CONCURRENCY)
+            .clamp(1, MAX_SUMMARY_CONCURRENCY);
+
+        // 定期重新上传之前总结失败的视频，无需再次编码
+        // 只在没有任务运行时查询，避免把正在总结的新视频当作失败视频
+        let retry_due = last_video_retry
+            .is_none_or(|t| t.elapsed() >= StdDuration::from_secs(VIDEO_RETRY_INTERVAL_SECONDS));
+        if running.is_empty() && retry_due {
+            last_video_retry = Some(Instant::now());
+            match db::get_pending_videos(&worker.db_pool, MAX_VIDEO_RETRIES).await {
+                Ok(videos) if !videos.is_empty() => {
+                    let worker = worker.clone();
+                    running.spawn(async move { retry_pending_videos(&worker, videos).await });
+                }
+                Ok(_) => {}
+                Err(e) => log::error!("Failed to get pending videos from database: {}", e),
+            }
+        }
+
+        while running.len() < concurrency {
+            let job = match db::claim_next_summary_job(&worker.db_pool).await {
+                Ok(Some(job)) => job,
+                Ok(None) => break,
+                Err(e) => {
+                    log::error!("Failed to claim summary job: {}", e);
+                    break;
+                }
+            };
+
+            // AI 服务不可用时放回队列，不计入失败次数，下次检查时再处理
+            let Some(provider) = worker.available_provider().await else {
+                if let Err(e) = db::release_summary_job(&worker.db_pool, job.id, None).await {
+                    log::error!("Failed to release summary job: {}", e);
+                }
+                break;
+            };
+
+            let worker = worker.clone();
+            running.spawn(async move { process_summary_job(&worker, provider, job).await });
+        }
+    }
+
+    running.shutdown().await;
+    log::info!("Summary worker stopped");
+}
+
+// 处理一个总结任务，失败时按重试次数重新排队
+async fn process_summary_job(
+    worker: &SummaryWorker,
+    provider: SummaryProvider,
+    job: db::SummaryJob,
+) {
+    log::info!(
+        "Processing summary job {} ({} - {})",
+        job.id,
+        job.start_time.format("%H:%M:%S"),
+        job.end_time.format("%H:%M:%S")
+    );
+
+    let result = summarize_time_range(worker, &provider, &job).await;
+
+    let saved = match &result {
+        Ok(summary_id) => db::complete_summary_job(&worker.db_pool, job.id, *summary_id).await,
+        Err(e) if is_connectivity_error(e) => {
+            // 网络断开时放回队列，不计入失败次数
+            log::warn!("Summary job {} deferred until online: {}", job.id, e);
+            let error = e.to_string();
+            worker.mark_offline(&error).await;
+            db::release_summary_job(&worker.db_pool, job.id, Some(&error)).await
+        }
+        Err(e) => {
+            log::error!("Summary job {} failed: {}", job.id, e);
+            worker.health.record_error(health::TASK_SUMMARY_WORKER, e);
+            db::fail_summary_job(&worker.db_pool, job.id, &e.to_string(), MAX_JOB_ATTEMPTS).await
+        }
+    };
+    if let Err(e) = saved {
+        log::error!("Failed to update summary job: {}", e);
+    }
+
+    if let Ok(Some(_)) = result {
+        worker.health.record_summary();
+    }
+
+    // 对比模式：在清理视频之前用对比模型再总结一次
+    if let Ok(Some(summary_id)) = result {
+        if let Err(e) = run_model_comparison(worker, &provider, summary_id).await {
+            log::warn!("Model comparison for summary {} failed: {}", summary_id, e);
+        }
+    }
+
+    // 按保留策略清理旧的总结视频
+    if let Err(e) = cleanup_summary_videos(&worker.db_pool).await {
+        log::error!("Failed to clean up summary videos: {}", e);
+    }
+}
+
+// 用对比模型重新总结同一时间段，保存两个模型的结果（没有设置对比模型时跳过）
+async fn run_model_comparison(
+    worker: &SummaryWorker,
+    provider: &SummaryProvider,
+    summary_id: i64,
+) -> Result<(), ClarityError> {
+    let db_pool = &worker.db_pool;
+    let comparison_model = settings::load_comparison_model_from_db(db_pool)
+        .await
+        .unwrap_or_default();
+    if comparison_model.is_empty() || comparison_model == provider.model() {
+        return Ok(());
+    }
+    let Some(summary) = db::get_summary(db_pool, summary_id)
+        .await
+        .map_err(ClarityError::from)?
+    else {
+        return Ok(());
+    };
+
+    log::info!(
+        "Comparing summary {} from {} with {}",
+        summary_id,
+        provider.model(),
+        comparison_model
+    );
+    let comparison_provider = provider.with_model(&comparison_model);
+    let video_settings = worker.video_settings().await;
+    let (current_language, prompt) = worker.language_and_prompt().await;
+    let prompt = worker
+        .prompt_with_context(prompt, &current_language, summary.start_time)
+        .await;
+
+    // 优先使用主模型刚上传过的视频，保证两个模型看到的内容一致
+    let mut videos = db::get_summary_videos(db_pool, Some(summary_id), None)
+        .await
+        .map_err(ClarityError::from)?;
+    videos.sort_by_key(|v| v.start_time);
+
+    let mut chunk_summaries = Vec::new();
+    let mut chunk_structured_data = Vec::new();
+    if comparison_provider.supports_video() && !videos.is_empty() {
+        for video in &videos {
+            worker.rate_limiter.acquire().await;
+            let chunk = summarize_video_file(
+                db_pool,
+                &comparison_provider,
+                &prompt,
+                &video_settings,
+                &PathBuf::from(&video.file_path),
+                None,
+            )
+            .await?;
+            chunk_summaries.push(chunk.content);
+            chunk_structured_data.extend(chunk.structured_data);
+        }
+    } else {
+        let traces = db::get_screenshot_traces(
+            db_pool,
+            Some(summary.start_time),
+            Some(summary.end_time),
+            None,
+        )
+        .await
+        .map_err(ClarityError::from)?;
+        // 只保存在本地的截图不上传
+        let rules = redaction::load_rules(db_pool).await;
+        let traces: Vec<_> = traces
+            .into_iter()
+            .filter(|t| !redaction::is_local_only(&rules, t))
+            .collect();
+        for chunk in split_traces_into_chunks(&traces, SUMMARY_CHUNK_SECONDS) {
+            worker.rate_limiter.acquire().await;
+            let chunk = summarize_frames(
+                db_pool,
+                &comparison_provider,
+                &prompt,
+                &video_settings,
+                &chunk,
+                None,
+            )
+            .await?;
+            chunk_summaries.push(chunk.content);
+            chunk_structured_data.extend(chunk.structured_data);
+        }
+    }
+
+    if chunk_summaries.is_empty() {
+        return Err(ClarityError::Ai(
+            "No comparison summary generated".to_string(),
+        ));
+    }
+    let content = if chunk_summaries.len() == 1 {
+        chunk_summaries.remove(0)
+    } else {
+        worker.rate_limiter.acquire().await;
+        merge_chunk_summaries(
+            db_pool,
+            &comparison_provider,
+            &current_language,
+            &chunk_summaries,
+        )
+        .await
+    };
+    let structured_data = merge_structured_data(&content, &chunk_structured_data);
+
+    let primary_structured_data = summary.structured_data.as_ref().map(|d| d.to_string());
+    let comparison_structured_data = structured_data.as_ref().map(|d| d.to_string());
+    for (model, role, content, structured_data) in [
+        (
+            provider.model(),
+            "primary",
+            summary.content.as_str(),
+            primary_structured_data.as_deref(),
+        ),
+        (
+            comparison_model.as_str(),
+            "comparison",
+            content.as_str(),
+            comparison_structured_data.as_deref(),
+        ),
+    ] {
+        db::insert_model_comparison(db_pool, summary_id, model, role, content, structured_data)
+            .await
+            .map_err(ClarityError::from)?;
+    }
+
+    log::info!("Model comparison saved for summary {}", summary_id);
+    Ok(())
+}
+
+// 为任务覆盖的时间段生成并保存摘要，返回摘要 ID（时间段内没有截图时为 None）
+async fn summarize_time_range(
+    worker: &SummaryWorker,
+    provider: &SummaryProvider,
+    job: &db::SummaryJob,
+) -> Result<Option<i64>, ClarityError> {
+    let db_pool = &worker.db_pool;
+    let app_handle = worker.app_handle.as_ref();
+    let video_settings = worker.video_settings().await;
+    let (current_language, prompt) = worker.language_and_prompt().await;
+    let prompt = worker
+        .prompt_with_context(prompt, &current_language, job.start_time)
+        .await;
+
+    // 获取任务时间段内的截图
+    let traces = db::get_screenshot_traces(db_pool, Some(job.start_time), Some(job.end_time), None)
+        .await
+        .map_err(ClarityError::from)?;
+
+    if traces.is_empty() {
+        log::warn!("No screenshots for summary job {}", job.id);
+        return Ok(None);
+    }
+
+    // 隐身模式下的截图和按应用规则只保存在本地的截图不上传，单独保存为一条“私人时间”摘要，其余截图照常总结
+    let rules = redaction::load_rules(db_pool).await;
+    let (private_traces, traces): (Vec<_>, Vec<_>) = traces
+        .into_iter()
+        .partition(|t| redaction::is_local_only(&rules, t));
+    let private_summary_id =
+        save_private_summary(worker, &current_language, &private_traces).await?;
+    if traces.is_empty() {
+        return Ok(private_summary_id);
+    }
+
+    log::info!("Found {} screenshots to process", traces.len());
+    video_summary::emit_summary_progress(
+        app_handle,
+        SummaryStage::Collecting,
+        5,
+        Some(format!("{} screenshots", traces.len())),
+    );
+
+    // 时间跨度较长时按时间分段，每段单独生成视频和摘要，避免单个视频过大导致上传失败
+    let chunks = split_traces_into_chunks(&traces, SUMMARY_CHUNK_SECONDS);
+    if chunks.len() > 1 {
+        log::info!(
+            "Splitting {} screenshots into {} chunks",
+            traces.len(),
+            chunks.len()
+        );
+    }
+
+    let storage_path = worker.storage_path.lock().await.clone();
+    let mut chunk_summaries = Vec::new();
+    let mut chunk_structured_data = Vec::new();
+    let mut video_ids = Vec::new();
+    let mut summarized_traces = Vec::new();
+    let mut last_error = None;
+    let extension = video_summary::codec_extension(&video_settings.codec);
+    for (index, chunk) in chunks.iter().enumerate() {
+        // 按任务开始时间命名，并发处理多个任务时不会重名
+        let video_name = if chunks.len() > 1 {
+            format!(
+                "summary_{}_{:02}.{}",
+                job.start_time.format("%Y%m%d_%H%M%S"),
+                index + 1,
+                extension
+            )
+        } else {
+            format!(
+                "summary_{}.{}",
+                job.start_time.format("%Y%m%d_%H%M%S"),
+                extension
+            )
+        };
+        let video_path = storage_path.join("videos").join(video_name);
+
+        worker.rate_limiter.acquire().await;
+        match summarize_chunk(
+            worker,
+            provider,
+            &prompt,
+            &video_settings,
+            chunk,
+            &video_path,
+            app_handle,
+        )
+        .await
+        {
+            Ok((summary, video_id)) => {
+                chunk_summaries.push(summary.content);
+                chunk_structured_data.extend(summary.structured_data);
+                video_ids.extend(video_id);
+                summarized_traces.extend(chunk.iter());
+            }
+            Err(e) => {
+                log::error!(
+                    "Failed to summarize chunk {}/{}: {}",
+                    index + 1,
+                    chunks.len(),
+                    e
+                );
+                last_error = Some(e);
+            }
+        }
+    }
+
+    if chunk_summaries.is_empty() {
+        if let Some(e) = last_error.as_ref().filter(|e| is_connectivity_error(e)) {
+            worker.mark_offline(&e.to_string()).await;
+        }
+
+        // 已保存视频的分段会单独重新上传，不需要重新处理整个任务
+        let has_pending_video = db::get_pending_videos(db_pool, MAX_VIDEO_RETRIES)
+            .await
+            .map(|videos| {
+                videos.iter().any(|v| {
+                    v.start_time.is_some_and(|t| t >= job.start_time)
+                        && v.end_time.is_some_and(|t| t <= job.end_time)
+                })
+            })
+            .unwrap_or(false);
+        if has_pending_video {
+            log::info!(
+                "Summary job {} failed, its videos will be retried separately",
+                job.id
+            );
+            return Ok(None);
+        }
+        return Err(
+            last_error.unwrap_or_else(|| ClarityError::Ai("No summary generated".to_string()))
+        );
+    }
+
+    // 多段时再调用一次文本接口，把各段摘要合并为一条
+    let content = if chunk_summaries.len() == 1 {
+        chunk_summaries.remove(0)
+    } else {
+        worker.rate_limiter.acquire().await;
+        merge_chunk_summaries(
+            &worker.db_pool,
+            provider,
+            &current_language,
+            &chunk_summaries,
+        )
+        .await
+    };
+    let structured_data = merge_structured_data(&content, &chunk_structured_data);
+
+    // 保存摘要到数据库
+    video_summary::emit_summary_progress(app_handle, SummaryStage::Saving, 90, None);
+    // 确保时间顺序正确：start_time 应该是最早的，end_time 应该是最晚的
+    // 只统计总结成功的分段，失败分段的视频会在之后单独重试
+    let mut timestamps: Vec<DateTime<Local>> =
+        summarized_traces.iter().map(|t| t.timestamp).collect();
+    timestamps.sort(); // 按时间升序排序
+    let start_time = timestamps.first().unwrap().clone(); // 最早的时间
+    let end_time = timestamps.last().unwrap().clone(); // 最晚的时间
+    let screenshot_count = summarized_traces.len() as i32;
+
+    match db::insert_summary(
+        db_pool,
+        start_time,
+        end_time,
+        content.clone(),
+        screenshot_count,
+    )
+    .await
+    {
+        Ok(id) => {
+            log::info!("Summary saved to database with id: {}", id);
+            save_structured_data(
+                db_pool,
+                id,
+                structured_data.as_ref(),
+                input_activity_ratio(summarized_traces.iter().copied()),
+            )
+            .await;
+            video_summary::emit_summary_progress(app_handle, SummaryStage::Completed, 100, None);
+            for video_id in &video_ids {
+                if let Err(e) = db::link_video_to_summary(db_pool, *video_id, id).await {
+                    log::error!("Failed to link video to summary: {}", e);
+                }
+            }
+            // 总结保存成功，通知前端
+            events::emit(
+                app_handle,
+                events::SUMMARY_CREATED,
+                events::SummaryEvent::new(id, start_time, end_time, &content),
+            );
+            webhooks::summary_created(db_pool, id);
+            notifications::notify_summary_ready(
+                app_handle,
+                db_pool,
+                SummaryNotification::Summary,
+                &content,
+            )
+            .await;
+            Ok(Some(id))
+        }
+        Err(e) => {
+            log::error!("Failed to save summary to database: {}", e);
+            video_summary::emit_summary_progress(
+                app_handle,
+                SummaryStage::Failed,
+                100,
+                Some(e.to_string()),
+            );
+            Err(ClarityError::from(e))
+        }
+    }
+}
+
+// 保存只保存在本地的截图的摘要：时间段与会议重叠时内容为“会议”，否则为“私人时间”
+async fn save_private_summary(
+    worker: &SummaryWorker,
+    language: &str,
+    traces: &[db::ScreenshotTrace],
+) -> Result<Option<i64>, ClarityError> {
+    let (Some(start_time), Some(end_time)) = (
+        traces.iter().map(|t| t.timestamp).min(),
+        traces.iter().map(|t| t.timestamp).max(),
+    ) else {
+        return Ok(None);
+    };
+
+    // 正在进行的会议还没有写入 recording_gaps，从共享状态中读取
+    let in_meeting = worker
+        .meeting
+        .lock()
+        .await
+        .as_ref()
+        .is_some_and(|meeting| meeting.started_at <= end_time)
+        || db::get_recording_gaps(&worker.db_pool, start_time, end_time)
+            .await
+            .map_err(ClarityError::from)?
+            .iter()
+            .any(|gap| gap.reason == "meeting");
+
+    save_placeholder_summary(
+        &worker.db_pool,
+        worker.app_handle.as_ref(),
+        language,
+        start_time,
+        end_time,
+        traces.len() as i32,
+        in_meeting,
+    )
+    .await
+}
+
+// 保存不调用 AI 的占位摘要（隐身模式的“私人时间”，或 meeting 为 true 时的“会议”），不推送 webhook
+// 任务失败重试时已经保存过的摘要（开始时间相同）不重复保存
+pub async fn save_placeholder_summary(
+    db_pool: &SqlitePool,
+    app_handle: Option<&AppHandle>,
+    language: &str,
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+    screenshot_count: i32,
+    meeting: bool,
+) -> Result<Option<i64>, ClarityError> {
+    if db::summary_exists_at(db_pool, start_time)
+        .await
+        .map_err(ClarityError::from)?
+    {
+        return Ok(None);
+    }
+
+    let template = if meeting {
+        Template::MeetingTime
+    } else {
+        Template::PrivateTime
+    };
+    let content = locale::render(language, template, &[]);
+    let id = db::insert_summary(
+        db_pool,
+        start_time,
+        end_time,
+        content.clone(),
+        screenshot_count,
+    )
+    .await
+    .map_err(ClarityError::from)?;
+    db::mark_summary_private(db_pool, id)
+        .await
+        .map_err(ClarityError::from)?;
+    // 会议时间计入 meetings 分类，前端据此显示为会议
+    if meeting {
+        let data = serde_json::json!({ "category": MEETING_CATEGORY });
+        if let Err(e) = db::update_summary_structured_data(db_pool, id, &data.to_string()).await {
+            log::error!("Failed to save meeting summary data: {}", e);
+        }
+        if let Err(e) =
+            db::set_summary_categories(db_pool, id, &[MEETING_CATEGORY.to_string()]).await
+        {
+            log::error!("Failed to save meeting summary category: {}", e);
+        }
+    }
+    log::info!(
+        "Saved {} summary {} ({} screenshots)",
+        if meeting { "meeting" } else { "private" },
+        id,
+        screenshot_count
+    );
+
+    events::emit(
+        app_handle,
+        events::SUMMARY_CREATED,
+        events::SummaryEvent::new(id, start_time, end_time, &content),
+    );
+    Ok(Some(id))
+}
+
+// 把截图按时间切分为不超过 chunk_seconds 的分段（每段按时间升序）
+fn split_traces_into_chunks(
+    traces: &[db::ScreenshotTrace],
+    chunk_seconds: i64,
+) -> Vec<Vec<db::ScreenshotTrace>> {
+    let mut sorted = traces.to_vec();
+    sorted.sort_by_key(|t| t.timestamp);
+
+    let mut chunks: Vec<Vec<db::ScreenshotTrace>> = Vec::new();
+    for trace in sorted {
+        match chunks.last_mut() {
+            Some(chunk) if (trace.timestamp - chunk[0].timestamp).num_seconds() < chunk_seconds => {
+                chunk.push(trace);
+            }
+            _ => chunks.push(vec![trace]),
+        }
+    }
+
+    chunks
+}
+
+// 合并各分段的结构化数据：应用和分心行为取并集，专注度取平均，分类取出现最多的
+fn merge_structured_data(content: &str, items: &[serde_json::Value]) -> Option<serde_json::Value> {
+    if items.len() <= 1 {
+        return items.first().cloned();
+    }
+
+    let collect_strings = |key: &str| {
+        let mut values: Vec<String> = Vec::new();
+        for item in items {
+            for value in item[key].as_array().into_iter().flatten() {
+                if let Some(value) = value.as_str() {
+                    if !values.iter().any(|v| v == value) {
+                        values.push(value.to_string());
+                    }
+                }
+            }
+        }
+        values
+    };
+
+    let scores: Vec<i64> = items
+        .iter()
+        .filter_map(|item| item["focusScore"].as_i64())
+        .collect();
+    let focus_score = if scores.is_empty() {
+        None
+    } else {
+        Some((scores.iter().sum::<i64>() as f64 / scores.len() as f64).round() as i64)
+    };
+
+    let categories: Vec<&str> = items
+        .iter()
+        .filter_map(|item| item["category"].as_str())
+        .collect();
+    let category = categories
+        .iter()
+        .copied()
+        .max_by_key(|c| categories.iter().filter(|other| *other == c).count())
+        .unwrap_or("other");
+
+    Some(serde_json::json!({
+        "summary": content,
+        "apps": collect_strings("apps"),
+        "category": category,
+        "categories": collect_strings("categories"),
+        "focusScore": focus_score,
+        "distractions": collect_strings("distractions"),
+    }))
+}
+
+// 保存摘要的结构化数据、分类和专注度（没有结构化数据时跳过）
+async fn save_structured_data(
+    db_pool: &SqlitePool,
+    summary_id: i64,
+    structured_data: Option<&serde_json::Value>,
+    input_activity: Option<f64>,
+) {
+    if let Some(data) = structured_data {
+        if let Err(e) =
+            db::update_summary_structured_data(db_pool, summary_id, &data.to_string()).await
+        {
+            log::error!("Failed to save structured summary data: {}", e);
+        }
+
+        let categories = structured_categories(data);
+        if let Err(e) = db::set_summary_categories(db_pool, summary_id, &categories).await {
+            log::error!("Failed to save summary categories: {}", e);
+        }
+        if let Err(e) = auto_tag_summary(db_pool, summary_id, data).await {
+            log::error!("Failed to auto-tag summary: {}", e);
+        }
+
+        // 模型没有给出专注度时根据分类和输入活动估算
+        let focus_score = data["focusScore"]
+            .as_i64()
+            .map(|score| score.clamp(0, 100))
+            .or_else(|| estimate_focus_score(&categories, input_activity));
+        if let Some(focus_score) = focus_score {
+            if let Err(e) = db::update_summary_focus_score(db_pool, summary_id, focus_score).await {
+                log::error!("Failed to save focus score: {}", e);
+            }
+        }
+    }
+}
+
+// 各分类的基础专注度，未列出的分类按 50 计算
+const CATEGORY_FOCUS_SCORES: [(&str, i64); 9] = [
+    ("coding", 90),
+    ("writing", 85),
+    ("learning", 80),
+    ("meetings", 70),
+    ("email", 60),
+    ("browsing", 45),
+    ("social", 25),
+    ("entertainment", 15),
+    ("other", 50),
+];
+
+// 输入活动占比：相邻两张截图之间鼠标位置发生变化的比例（没有鼠标位置时为 None）
+fn input_activity_ratio<'a>(
+    traces: impl IntoIterator<Item = &'a db::ScreenshotTrace>,
+) -> Option<f64> {
+    let positions: Vec<(i32, i32)> = traces
+        .into_iter()
+        .filter_map(|t| Some((t.cursor_x?, t.cursor_y?)))
+        .collect();
+    if positions.len() < 2 {
+        return None;
+    }
+
+    let moved = positions.windows(2).filter(|w| w[0] != w[1]).count();
+    Some(moved as f64 / (positions.len() - 1) as f64)
+}
+
+// 本地估算专注度：以分类的基础分为主，输入活动占 20%
+fn estimate_focus_score(categories: &[String], input_activity: Option<f64>) -> Option<i64> {
+    if categories.is_empty() {
+        return None;
+    }
+
+    let category_score = categories
+        .iter()
+        .map(|category| {
+            CATEGORY_FOCUS_SCORES
+                .iter()
+                .find(|(name, _)| name == category)
+                .map_or(50, |(_, score)| *score)
+        })
+        .sum::<i64>() as f64
+        / categories.len() as f64;
+
+    let score = match input_activity {
+        Some(ratio) => category_score * 0.8 + ratio * 100.0 * 0.2,
+        None => category_score,
+    };
+    Some((score.round() as i64).clamp(0, 100))
+}
+
+// 从结构化数据中取出分类（旧数据只有主分类 category）
+pub fn structured_categories(data: &serde_json::Value) -> Vec<String> {
+    let mut categories: Vec<String> = Vec::new();
+    let values = data["category"].as_str().into_iter().chain(
+        data["categories"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|c| c.as_str()),
+    );
+    for category in values {
+        if !categories.iter().any(|c| c == category) {
+            categories.push(category.to_string());
+        }
+    }
+    categories
+}
+
+// 为一段截图生成摘要，返回摘要结果和视频记录 ID（截图模式下没有视频）
+async fn summarize_chunk(
+    worker: &SummaryWorker,
+    provider: &SummaryProvider,
+    prompt: &str,
+    video_settings: &video_summary::VideoSettings,
+    traces: &[db::ScreenshotTrace],
+    video_path: &PathBuf,
+    app_handle: Option<&AppHandle>,
+) -> Result<(ChunkSummary, Option<i64>), ClarityError> {
+    let db_pool = &worker.db_pool;
+    let prompt = with_ocr_context(db_pool, prompt, traces).await;
+    let prompt = prompt.as_str();
+
+    // 服务不支持视频或选择了截图模式时，直接发送抽样截图，不需要 ffmpeg
+    if !provider.supports_video() || video_settings.input_mode == "frames" {
+        return summarize_with_fallback(worker, provider, move |provider| async move {
+            summarize_frames(
+                db_pool,
+                &provider,
+                prompt,
+                video_settings,
+                traces,
+                app_handle,
+            )
+            .await
+        })
+        .await
+        .map(|summary| (summary, None));
+    }
+    // 确保视频目录存在
+    if let Some(parent) = video_path.parent() {
+        screenshot::ensure_dir_exists(parent)
+            .await
+            .map_err(|e| format!("Failed to create video directory: {}", e))?;
+    }
+
+    let image_paths: Vec<PathBuf> = traces.iter().map(|t| PathBuf::from(&t.file_path)).collect();
+
+    log::info!("Creating video from {} images", image_paths.len());
+    video_summary::emit_summary_progress(app_handle, SummaryStage::Encoding, 15, None);
+    if let Err(e) =
+        video_summary::create_video_from_images(&image_paths, video_path, video_settings).await
+    {
+        log::error!("Failed to create video from images: {}", e);
+        video_summary::emit_summary_progress(
+            app_handle,
+            SummaryStage::Failed,
+            100,
+            Some(e.clone()),
+        );
+        return Err(ClarityError::from(e));
+    }
+    log::info!("Video created successfully: {}", video_path.display());
+
+    // 记录视频文件和覆盖的时间段，便于按保留策略清理以及失败后重试
+    let video_size = tokio::fs::metadata(video_path)
+        .await
+        .map(|m| m.len() as i64)
+        .unwrap_or(0);
+    // 视频时长 = 帧数 / 每秒帧数
+    let fps = video_settings
+        .fps
+        .clamp(video_summary::MIN_VIDEO_FPS, video_summary::MAX_VIDEO_FPS);
+    let duration_seconds = traces.len() as f64 / fps as f64;
+    let video_id = match db::insert_video(
+        db_pool,
+        &video_path.to_string_lossy(),
+        video_size,
+        traces
+            .first()
+            .map(|t| t.timestamp)
+            .unwrap_or_else(Local::now),
+        traces
+            .last()
+            .map(|t| t.timestamp)
+            .unwrap_or_else(Local::now),
+        traces.len() as i32,
+        duration_seconds,
+    )
+    .await
+    {
+        Ok(id) => Some(id),
+        Err(e) => {
+            log::error!("Failed to save video record to database: {}", e);
+            None
+        }
+    };
+
+    let summary = summarize_with_fallback(worker, provider, move |provider| async move {
+        summarize_video_file(
+            db_pool,
+            &provider,
+            prompt,
+            video_settings,
+            video_path,
+            app_handle,
+        )
+        .await
+    })
+    .await?;
+
+    Ok((summary, video_id))
+}
+
+// 开启 OCR 时识别几张抽样截图中的文字，附加到提示词末尾（识别不到文字时保持原样）
+async fn with_ocr_context(
+    db_pool: &SqlitePool,
+    prompt: &str,
+    traces: &[db::ScreenshotTrace],
+) -> String {
+    if !settings::load_ocr_enabled_from_db(db_pool)
+        .await
+        .unwrap_or(false)
+    {
+        return prompt.to_string();
+    }
+    let languages = settings::load_ocr_languages_from_db(db_pool)
+        .await
+        .unwrap_or_else(|_| ocr::DEFAULT_OCR_LANGUAGES.to_string());
+
+    let image_paths: Vec<PathBuf> = traces.iter().map(|t| PathBuf::from(&t.file_path)).collect();
+    let text =
+        ocr::extract_text_from_frames(&image_paths, &languages, ocr::OCR_FRAMES_PER_CHUNK).await;
+    if text.is_empty() {
+        return prompt.to_string();
+    }
+
+    log::info!("Adding {} characters of OCR text to prompt", text.len());
+    format!(
+        "{}\n\nText recognized on screen by OCR (may contain errors, use it to identify documents, code and terminal output):\n{}",
+        prompt, text
+    )
+}
+
+// 调用 summarize 生成摘要，主模型连续失败时用备用模型重试一次（网络错误不计入失败次数）
+async fn summarize_with_fallback<F, Fut>(
+    worker: &SummaryWorker,
+    provider: &SummaryProvider,
+    summarize: F,
+) -> Result<ChunkSummary, ClarityError>
+where
+    F: Fn(SummaryProvider) -> Fut,
+    Fut: Future<Output = Result<ChunkSummary, ClarityError>>,
+{
+    let result = summarize(provider.clone()).await;
+    let error = match &result {
+        Ok(_) => {
+            worker.primary_model_failures.store(0, Ordering::Relaxed);
+            return result;
+        }
+        Err(e) if is_connectivity_error(e) => return result,
+        Err(e) => e,
+    };
+
+    let failures = worker
+        .primary_model_failures
+        .fetch_add(1, Ordering::Relaxed)
+        + 1;
+    let fallback_model = settings::load_fallback_model_from_db(&worker.db_pool)
+        .await
+        .unwrap_or_default();
+    if failures < FALLBACK_AFTER_FAILURES
+        || fallback_model.is_empty()
+        || fallback_model == provider.model()
+    {
+        return result;
+    }
+
+    log::warn!(
+        "{} failed {} times in a row ({}), falling back to {}",
+        provider.model(),
+        failures,
+        error,
+        fallback_model
+    );
+    summarize(provider.fallback(&fallback_model)).await
+}
+
+// 抽样截图直接发送给当前 AI 服务生成摘要，同时记录 API 请求
+async fn summarize_frames(
+    db_pool: &SqlitePool,
+    provider: &SummaryProvider,
+    prompt: &str,
+    video_settings: &video_summary::VideoSettings,
+    traces: &[db::ScreenshotTrace],
+    app_handle: Option<&AppHandle>,
+) -> Result<ChunkSummary, ClarityError> {
+    let image_paths: Vec<PathBuf> = traces.iter().map(|t| PathBuf::from(&t.file_path)).collect();
+
+    let result = match provider {
+        SummaryProvider::Gemini {
+            api_keys, model, ..
+        } => {
+            let mut result = Err(ClarityError::InvalidInput(
+                "Google Gemini API key not set".to_string(),
+            ));
+            for (index, key) in api_keys.iter().enumerate() {
+                result = video_summary::summarize_frames_with_gemini(
+                    &key.api_key,
+                    model,
+                    prompt,
+                    &image_paths,
+                    &video_settings.media_resolution,
+                    &video_settings.generation,
+                    &video_settings.categories,
+                    app_handle,
+                )
+                .await;
+                let has_next_key = index + 1 < api_keys.len();
+                if !should_try_next_gemini_key(
+                    db_pool,
+                    provider,
+                    key,
+                    has_next_key,
+                    &result,
+                    app_handle,
+                )
+                .await
+                {
+                    break;
+                }
+            }
+            // 每个 key 的结果已经分别记录
+            return result.map(ChunkSummary::from);
+        }
+        SummaryProvider::OpenAi {
+            base_url,
+            api_key,
+            model,
+            ..
+        } => {
+            openai::summarize_frames_with_openai(
+                base_url,
+                api_key,
+                model,
+                prompt,
+                &image_paths,
+                app_handle,
+            )
+            .await
+        }
+        SummaryProvider::Ollama {
+            base_url, model, ..
+        } => {
+            ollama::summarize_frames_with_ollama(base_url, model, prompt, &image_paths, app_handle)
+                .await
+        }
+    };
+
+    record_api_result(db_pool, provider, None, &result, app_handle).await;
+    result.map(ChunkSummary::from)
+}
+
+// 上传已编码的视频并调用 Gemini 总结，同时记录 API 请求
+async fn summarize_video_file(
+    db_pool: &SqlitePool,
+    provider: &SummaryProvider,
+    prompt: &str,
+    video_settings: &video_summary::VideoSettings,
+    video_path: &PathBuf,
+    app_handle: Option<&AppHandle>,
+) -> Result<ChunkSummary, ClarityError> {
+    let SummaryProvider::Gemini {
+        api_keys, model, ..
+    } = provider
+    else {
+        return Err(ClarityError::InvalidInput(
+            "AI provider does not support video input".to_string(),
+        ));
+    };
+
+    // 调用 Google Gemini API（使用 File API），上传的文件只属于当前 key，换 key 时需要重新上传
+    log::info!("Calling Google Gemini API for video summary");
+    let mut result = Err(ClarityError::InvalidInput(
+        "Google Gemini API key not set".to_string(),
+    ));
+    for (index, key) in api_keys.iter().enumerate() {
+        result = video_summary::summarize_video_with_gemini(
+            &key.api_key,
+            video_path,
+            model,
+            prompt,
+            video_settings,
+            app_handle,
+        )
+        .await;
+        let has_next_key = index + 1 < api_keys.len();
+        if !should_try_next_gemini_key(db_pool, provider, key, has_next_key, &result, app_handle)
+            .await
+        {
+            break;
+        }
+    }
+
+    result.map(ChunkSummary::from)
+}
+
+// 记录一次总结请求的结果（成功时包含 token 使用情况）
+async fn record_api_result(
+    db_pool: &SqlitePool,
+    provider: &SummaryProvider,
+    api_key_id: Option<i64>,
+    result: &Result<video_summary::ApiRequestResult, ClarityError>,
+    app_handle: Option<&AppHandle>,
+) {
+    let model = provider.model();
+    let endpoint = provider.endpoint();
+    let fallback_from = provider.fallback_from();
+
+    let saved = match result {
+        Ok(result) => {
+            log::info!(
+                "Summary generated successfully, length: {} chars",
+                result.content.len()
+            );
+            log::info!(
+                "Token usage: prompt={:?}, completion={:?}, total={:?}, cost={:?}",
+                result.prompt_tokens,
+                result.completion_tokens,
+                result.total_tokens,
+                result.cost_usd
+            );
+
+            // 记录 API 请求到数据库
+            db::insert_api_request(
+                db_pool,
+                model,
+                &endpoint,
+                result.prompt_tokens,
+                result.completion_tokens,
+                result.total_tokens,
+                result.cost_usd,
+                result.status_code,
+                true,
+                None,
+                result.duration_ms,
+                api_key_id,
+                fallback_from,
+            )
+            .await
+        }
+        Err(e) => {
+            log::error!("Failed to generate summary with {}: {}", model, e);
+            let error = e.to_string();
+            video_summary::emit_summary_progress(
+                app_handle,
+                SummaryStage::Failed,
+                100,
+                Some(error.clone()),
+            );
+
+            // 记录失败的 API 请求
+            db::insert_api_request(
+                db_pool,
+                model,
+                &endpoint,
+                None,
+                None,
+                None,
+                None,
+                0,
+                false,
+                Some(error.as_str()),
+                0,
+                api_key_id,
+                fallback_from,
+            )
+            .await
+        }
+    };
+
+    match saved {
+        Ok(id) => {
+            // API 请求保存成功，把本次请求发给前端累加统计
+            let recorded = match result {
+                Ok(result) => events::ApiRequestRecorded {
+                    id,
+                    model: model.to_string(),
+                    success: true,
+                    prompt_tokens: result.prompt_tokens,
+                    completion_tokens: result.completion_tokens,
+                    total_tokens: result.total_tokens,
+                    cost_usd: result.cost_usd,
+                    duration_ms: result.duration_ms,
+                },
+                Err(_) => events::ApiRequestRecorded {
+                    id,
+                    model: model.to_string(),
+                    success: false,
+                    prompt_tokens: None,
+                    completion_tokens: None,
+                    total_tokens: None,
+                    cost_usd: None,
+                    duration_ms: 0,
+                },
+            };
+            events::emit(app_handle, events::API_REQUEST_RECORDED, recorded);
+        }
+        Err(e) => log::error!("Failed to save API request to database: {}", e),
+    }
+}
+
+// 重新上传总结失败的视频，成功后按视频覆盖的时间段保存摘要
+// 只有 Gemini 使用视频，其他服务没有可重试的视频
+async fn retry_pending_videos(worker: &SummaryWorker, videos: Vec<db::Video>) {
+    let Some(provider) = worker.available_provider().await else {
+        return;
+    };
+    if !matches!(provider, SummaryProvider::Gemini { .. }) {
+        return;
+    }
+
+    let db_pool = &worker.db_pool;
+    let app_handle = worker.app_handle.as_ref();
+    let video_settings = worker.video_settings().await;
+    let (current_language, prompt) = worker.language_and_prompt().await;
+
+    for video in videos {
+        let (start_time, end_time) = match (video.start_time, video.end_time) {
+            (Some(start), Some(end)) => (start, end),
+            _ => continue,
+        };
+
+        let video_path = PathBuf::from(&video.file_path);
+        if tokio::fs::metadata(&video_path).await.is_err() {
+            // 视频文件已经不存在，无法重试
+            log::warn!("Pending video missing, skipping retry: {}", video.file_path);
+            if let Err(e) = db::mark_video_deleted(db_pool, video.id).await {
+                log::error!("Failed to mark video as deleted: {}", e);
+            }
+            continue;
+        }
+
+        log::info!(
+            "Retrying summary for video {} (attempt {}/{})",
+            video.file_path,
+            video.retry_count + 1,
+            MAX_VIDEO_RETRIES
+        );
+
+        let video_prompt = worker
+            .prompt_with_context(prompt.clone(), &current_language, start_time)
+            .await;
+
+        worker.rate_limiter.acquire().await;
+        let (video_prompt, video_settings, video_path) =
+            (&video_prompt, &video_settings, &video_path);
+        let summary = match summarize_with_fallback(worker, &provider, move |provider| async move {
+            summarize_video_file(
+                db_pool,
+                &provider,
+                video_prompt,
+                video_settings,
+                video_path,
+                app_handle,
+            )
+            .await
+        })
+        .await
+        {
+            Ok(summary) => summary,
+            Err(e) if is_connectivity_error(&e) => {
+                // 网络断开时不计入重试次数，恢复后再上传剩余的视频
+                log::warn!("Retry deferred for video {}: {}", video.file_path, e);
+                worker.mark_offline(&e.to_string()).await;
+                break;
+            }
+            Err(e) => {
+                log::error!("Retry failed for video {}: {}", video.file_path, e);
+                worker.health.record_error(health::TASK_SUMMARY_WORKER, &e);
+                if let Err(e) = db::increment_video_retry_count(db_pool, video.id).await {
+                    log::error!("Failed to update video retry count: {}", e);
+                }
+                continue;
+            }
+        };
+
+        video_summary::emit_summary_progress(app_handle, SummaryStage::Saving, 90, None);
+        match db::insert_summary(
+            db_pool,
+            start_time,
+            end_time,
+            summary.content.clone(),
+            video.screenshot_count,
+        )
+        .await
+        {
+            Ok(id) => {
+                log::info!("Summary saved to database with id: {} (retried)", id);
+                worker.health.record_summary();
+                let traces =
+                    db::get_screenshot_traces(db_pool, Some(start_time), Some(end_time), None)
+                        .await
+                        .unwrap_or_default();
+                save_structured_data(
+                    db_pool,
+                    id,
+                    summary.structured_data.as_ref(),
+                    input_activity_ratio(&traces),
+                )
+                .await;
+                video_summary::emit_summary_progress(
+                    app_handle,
+                    SummaryStage::Completed,
+                    100,
+                    None,
+                );
+                if let Err(e) = db::link_video_to_summary(db_pool, video.id, id).await {
+                    log::error!("Failed to link video to summary: {}", e);
+                }
+                events::emit(
+                    app_handle,
+                    events::SUMMARY_CREATED,
+                    events::SummaryEvent::new(id, start_time, end_time, &summary.content),
+                );
+                webhooks::summary_created(db_pool, id);
+                notifications::notify_summary_ready(
+                    app_handle,
+                    db_pool,
+                    SummaryNotification::Summary,
+                    &summary.content,
+                )
+                .await;
+            }
+            Err(e) => {
+                log::error!("Failed to save summary to database: {}", e);
+            }
+        }
+    }
+}
+
+// 把多个分段摘要合并为一条摘要，AI 调用失败时直接拼接
+async fn merge_chunk_summaries(
+    db_pool: &SqlitePool,
+    provider: &SummaryProvider,
+    language: &str,
+    chunk_summaries: &[String],
+) -> String {
+    let combined_content = chunk_summaries.join("\n\n");
+
+    let merge_prompt = locale::render(
+        language,
+        Template::MergePrompt,
+        &[("summaries", &combined_content)],
+    );
+
+    let result = match provider {
+        SummaryProvider::Gemini {
+            api_keys, model, ..
+        } => {
+            let generation = load_gemini_generation_settings(db_pool).await;
+            let mut result = Err(ClarityError::InvalidInput(
+                "Google Gemini API key not set".to_string(),
+            ));
+            for key in api_keys {
+                result = video_summary::generate_text_summary_with_gemini(
+                    &key.api_key,
+                    model,
+                    &merge_prompt,
+                    &generation,
+                )
+                .await;
+                match &result {
+                    Err(e) if is_quota_error(e) => {
+                        let _ = db::mark_gemini_api_key_quota_error(db_pool, key.id).await;
+                    }
+                    _ => break,
+                }
+            }
+            result
+        }
+        SummaryProvider::OpenAi {
+            base_url,
+            api_key,
+            model,
+            ..
+        } => openai::generate_text_with_openai(base_url, api_key, model, &merge_prompt).await,
+        SummaryProvider::Ollama {
+            base_url, model, ..
+        } => ollama::generate_text_with_ollama(base_url, model, &merge_prompt).await,
+    };
+
+    match result {
+        Ok(content) => content,
+        Err(e) => {
+            log::warn!(
+                "Failed to merge chunk summaries with AI: {}. Using combined summaries.",
+                e
+            );
+            combined_content
+        }
+    }
+}
+
+// 按保留策略清理总结视频，返回删除的文件数量
+pub async fn cleanup_summary_videos(db_pool: &SqlitePool) -> Result<u64, ClarityError> {
+    let keep_videos = settings::load_keep_videos_from_db(db_pool)
+        .await
+        .unwrap_or_else(|_| "forever".to_string());
+
+    let cutoff = match keep_videos.as_str() {
+        "never" => Local::now(),
+        "for_n_days" => {
+            let days = settings::load_keep_videos_days_from_db(db_pool)
+                .await
+                .unwrap_or(7);
+            Local::now() - chrono::Duration::days(days as i64)
+        }
+        _ => return Ok(0),
+    };
+
+    let videos = db::get_videos(db_pool, Some(cutoff), true, None)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let mut deleted = 0;
+    for video in videos {
+        // 等待重试的视频先保留，重试次数用完后再清理
+        if video.summary_id.is_none()
+            && video.start_time.is_some()
+            && video.retry_count < MAX_VIDEO_RETRIES
+        {
+            continue;
+        }
+
+        match tokio::fs::remove_file(&video.file_path).await {
+            Ok(_) => {}
+            // 文件已经不存在，直接标记为已删除
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                log::warn!("Failed to delete video {}: {}", video.file_path, e);
+                continue;
+            }
+        }
+
+        db::mark_video_deleted(db_pool, video.id)
+            .await
+            .map_err(ClarityError::from)?;
+        deleted += 1;
+    }
+
+    if deleted > 0 {
+        log::info!(
+            "Cleaned up {} summary videos (policy: {})",
+            deleted,
+            keep_videos
+        );
+    }
+
+    Ok(deleted)
+}
+
+// 手动触发总结视频清理
+#[tauri::command]
+pub async fn cleanup_videos(state: State<'_, AppState>) -> Result<u64, ClarityError> {
+    cleanup_summary_videos(&state.db_pool).await
+}
+
+// 获取总结视频列表，供前端回放每条摘要对应的原始视频
+#[tauri::command]
+pub async fn get_summary_videos(
+    state: State<'_, AppState>,
+    summary_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<db::Video>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    db::get_summary_videos(&state.db_pool, summary_id, limit)
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 获取总结任务队列，可按状态过滤（pending / running / done / failed）
+#[tauri::command]
+pub async fn get_summary_jobs(
+    state: State<'_, AppState>,
+    status: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<db::SummaryJob>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    db::get_summary_jobs(&state.db_pool, status.as_deref(), limit)
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 手动把一段时间加入总结队列（不要求正在录制，例如补上停止录制前没有总结的部分），返回任务 ID
+#[tauri::command]
+pub async fn summarize_range(
+    state: State<'_, AppState>,
+    start_time: String,
+    end_time: String,
+) -> Result<i64, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let start_dt = DateTime::parse_from_rfc3339(&start_time)
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
+        .with_timezone(&Local);
+    let end_dt = DateTime::parse_from_rfc3339(&end_time)
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
+        .with_timezone(&Local);
+    if end_dt <= start_dt {
+        return Err(ClarityError::InvalidInput(
+            "end_time must be after start_time".to_string(),
+        ));
+    }
+    if (end_dt - start_dt).num_seconds() > MAX_SUMMARIZE_RANGE_SECONDS {
+        return Err(ClarityError::InvalidInput(
+            "Time range cannot be longer than 24 hours".to_string(),
+        ));
+    }
+
+    if load_summary_provider(&state.db_pool, &state.ai_model)
+        .await
+        .is_none()
+    {
+        return Err(ClarityError::InvalidInput(
+            "AI provider API key not set".to_string(),
+        ));
+    }
+    if db::get_screenshot_count(&state.db_pool, Some(start_dt), Some(end_dt)).await? == 0 {
+        return Err(ClarityError::NotFound(
+            "No screenshots in this time range".to_string(),
+        ));
+    }
+
+    let id = db::insert_summary_job(&state.db_pool, start_dt, end_dt).await?;
+    log::info!(
+        "Queued summary job {} for {} - {} on request",
+        id,
+        start_dt.format("%Y-%m-%d %H:%M:%S"),
+        end_dt.format("%H:%M:%S")
+    );
+    Ok(id)
+}
+
+// 获取今天的 AI 用量和预算状态
+#[tauri::command]
+pub async fn get_budget_status(state: State<'_, AppState>) -> Result<BudgetStatus, ClarityError> {
+    load_budget_status(&state.db_pool).await
+}
+
+// 重新生成已有的摘要（修改提示词或更换模型后使用）
+// 已保存的视频仍在时直接重新上传，否则按摘要的时间段重新生成视频
+#[tauri::command]
+pub async fn regenerate_summary(
+    state: State<'_, AppState>,
+    id: i64,
+    prompt_override: Option<String>,
+) -> Result<db::Summary, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let db_pool = state.db_pool.clone();
+    let summary = db::get_summary(&db_pool, id)
+        .await
+        .map_err(ClarityError::from)?
+        .ok_or_else(|| ClarityError::NotFound("Summary not found".to_string()))?;
+    if summary.private {
+        return Err(ClarityError::Conflict(
+            "Private summaries cannot be regenerated".to_string(),
+        ));
+    }
+    incognito::ensure_uploads_allowed(&state).await?;
+
+    let app_handle = state.app_handle.lock().await.clone();
+    let worker = SummaryWorker::new(&state, app_handle.clone());
+    let provider = worker
+        .available_provider()
+        .await
+        .ok_or_else(|| "AI provider not available".to_string())?;
+    let video_settings = worker.video_settings().await;
+    let (current_language, prompt) = worker.language_and_prompt().await;
+    let prompt = match prompt_override
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+    {
+        Some(prompt) => prompt,
+        None => {
+            worker
+                .prompt_with_context(prompt, &current_language, summary.start_time)
+                .await
+        }
+    };
+
+    log::info!(
+        "Regenerating summary {} ({} - {})",
+        id,
+        summary.start_time.format("%H:%M:%S"),
+        summary.end_time.format("%H:%M:%S")
+    );
+
+    let traces = db::get_screenshot_traces(
+        &db_pool,
+        Some(summary.start_time),
+        Some(summary.end_time),
+        None,
+    )
+    .await
+    .map_err(ClarityError::from)?;
+    // 隐身模式下的截图和只保存在本地的截图不上传
+    let rules = redaction::load_rules(&db_pool).await;
+    let trace_count = traces.len();
+    let traces: Vec<_> = traces
+        .into_iter()
+        .filter(|t| !redaction::is_local_only(&rules, t))
+        .collect();
+
+    // 只有全部视频文件都还在时才复用，避免只覆盖部分时间段
+    // 按当前规则有截图不能上传时不复用（视频是按生成时的规则编码的），重新编码剩下的截图
+    let linked_videos = db::get_summary_videos(&db_pool, Some(id), None)
+        .await
+        .map_err(ClarityError::from)?;
+    let reuse_videos = provider.supports_video()
+        && video_settings.input_mode != "frames"
+        && traces.len() == trace_count
+        && !linked_videos.is_empty()
+        && linked_videos
+            .iter()
+            .all(|v| PathBuf::from(&v.file_path).exists());
+
+    let mut chunk_summaries = Vec::new();
+    let mut chunk_structured_data = Vec::new();
+    if reuse_videos {
+        let mut videos = linked_videos;
+        videos.sort_by_key(|v| v.start_time);
+        for video in &videos {
+            worker.rate_limiter.acquire().await;
+            let chunk = summarize_video_file(
+                &db_pool,
+                &provider,
+                &prompt,
+                &video_settings,
+                &PathBuf::from(&video.file_path),
+                app_handle.as_ref(),
+            )
+            .await?;
+            chunk_summaries.push(chunk.content);
+            chunk_structured_data.extend(chunk.structured_data);
+        }
+    } else {
+        if traces.is_empty() {
+            return Err(ClarityError::NotFound(
+                "No screenshots left for this summary's time range".to_string(),
+            ));
+        }
+        // 需要编码视频时先确认 ffmpeg 可用
+        if provider.supports_video() && video_settings.input_mode != "frames" {
+            video_summary::find_ffmpeg(video_settings.ffmpeg_path.as_deref())
+                .await
+                .map_err(ClarityError::FfmpegMissing)?;
+        }
 
-                // 创建视频
-                let video_path = storage_path.join("videos").join(format!(
-                    "summary_{}.mp4",
-                    Local::now().format("%Y%m%d_%H%M%S")
-                ));
+        let chunks = split_traces_into_chunks(&traces, SUMMARY_CHUNK_SECONDS);
+        let storage_path = worker.storage_path.lock().await.clone();
+        let extension = video_summary::codec_extension(&video_settings.codec);
+        // 文件名带上重新生成的时间，不会覆盖之前的视频
+        let prefix = format!("regenerate_{}_{}", id, Local::now().format("%Y%m%d_%H%M%S"));
+        let mut video_paths = Vec::new();
+        let mut video_ids = Vec::new();
+        let mut error = None;
+        for (index, chunk) in chunks.iter().enumerate() {
+            let video_name = if chunks.len() > 1 {
+                format!("{}_{:02}.{}", prefix, index + 1, extension)
+            } else {
+                format!("{}.{}", prefix, extension)
+            };
+            let video_path = storage_path.join("videos").join(video_name);
+            video_paths.push(video_path.to_string_lossy().to_string());
 
-                // 确保视频目录存在
-                if let Some(parent) = video_path.parent() {
-                    if let Err(e) = screenshot::ensure_dir_exists(parent).await {
-                        log::error!("Failed to create video directory: {}", e);
-                        continue;
-                    }
+            worker.rate_limiter.acquire().await;
+            match summarize_chunk(
+                &worker,
+                &provider,
+                &prompt,
+                &video_settings,
+                chunk,
+                &video_path,
+                app_handle.as_ref(),
+            )
+            .await
+            {
+                Ok((chunk, video_id)) => {
+                    chunk_summaries.push(chunk.content);
+                    chunk_structured_data.extend(chunk.structured_data);
+                    video_ids.extend(video_id);
                 }
-
-                let image_paths: Vec<PathBuf> =
-                    traces.iter().map(|t| PathBuf::from(&t.file_path)).collect();
-
-                log::info!("Creating video from {} images", image_paths.len());
-                match video_summary::create_video_from_images(&image_paths, &video_path, 1).await {
-                    Ok(_) => {
-                        log::info!("Video created successfully: {}", video_path.display());
-
-                        // 调用 Google Gemini API（使用 File API）
-                        log::info!("Calling Google Gemini API for video summary");
-                        let model = ai_model.lock().await.clone();
-
-                        // 根据当前语言从数据库加载提示词
-                        let current_language = {
-                            // 尝试从数据库加载语言设置，如果没有则默认中文
-                            let lang_result = settings::load_language_from_db(&db_pool)
-                                .await
-                                .unwrap_or_else(|_| "zh".to_string());
-                            lang_result
-                        };
-
-                        // 从数据库加载当前语言的提示词
-                        let prompt = settings::load_ai_prompt_from_db(&db_pool, Some(&current_language)).await
-                            .unwrap_or_else(|_| {
-                                if current_language == "en" {
-                                    "Analyze this screen activity video and provide a concise activity summary. Focus on: 1) Main apps/websites used; 2) Activity type (work/entertainment/learning, etc.); 3) Any distractions or inefficient behaviors. Respond in English, keep it under 100 words.".to_string()
-                                } else {
-                                    "分析这段屏幕活动视频，提供简洁的活动摘要。重点关注：1) 主要使用的应用/网站；2) 活动类型（工作/娱乐/学习等）；3) 是否有分心或低效行为。用中文回答，控制在100字以内。".to_string()
-                                }
-                            });
-
-                        // 获取视频分辨率设置
-                        let resolution = video_resolution.lock().await.clone();
-
-                        match video_summary::summarize_video_with_gemini(
-                            &api_key,
-                            &video_path,
-                            &model,
-                            &prompt,
-                            &resolution,
-                        )
-                        .await
-                        {
-                            Ok(result) => {
-                                log::info!(
-                                    "Summary generated successfully, length: {} chars",
-                                    result.content.len()
-                                );
-                                log::info!(
-                                    "Token usage: prompt={:?}, completion={:?}, total={:?}",
-                                    result.prompt_tokens,
-                                    result.completion_tokens,
-                                    result.total_tokens
-                                );
-
-                                // 记录 API 请求到数据库
-                                if let Err(e) = db::insert_api_request(
-                                    &db_pool,
-                                    &model,
-                                    "https://generativelanguage.googleapis.com/v1beta/models",
-                                    result.prompt_tokens,
-                                    result.completion_tokens,
-                                    result.total_tokens,
-                                    result.status_code,
-                                    true,
-                                    None,
-                                    result.duration_ms,
-                                )
-                                .await
-                                {
-                                    log::error!("Failed to save API request to database: {}", e);
-                                } else {
-                                    // API 请求保存成功，发送统计更新事件
-                                    if let Some(handle) = app_handle.as_ref() {
-                                        let _ = handle.emit("statistics-updated", ());
-                                    }
-                                }
-
-                                // 保存摘要到数据库
-                                // 确保时间顺序正确：start_time 应该是最早的，end_time 应该是最晚的
-                                // traces 是按 timestamp DESC 排序的，所以需要找到最小和最大时间
-                                let mut timestamps: Vec<DateTime<Local>> =
-                                    traces.iter().map(|t| t.timestamp).collect();
-                                timestamps.sort(); // 按时间升序排序
-                                let start_time = timestamps.first().unwrap().clone(); // 最早的时间
-                                let end_time = timestamps.last().unwrap().clone(); // 最晚的时间
-                                let screenshot_count = traces.len() as i32;
-
-                                match db::insert_summary(
-                                    &db_pool,
-                                    start_time,
-                                    end_time,
-                                    result.content,
-                                    screenshot_count,
-                                )
-                                .await
-                                {
-                                    Ok(id) => {
-                                        log::info!("Summary saved to database with id: {}", id);
-                                        // 总结保存成功，发送统计更新事件
-                                        if let Some(handle) = app_handle.as_ref() {
-                                            let _ = handle.emit("statistics-updated", ());
-                                        }
-                                    }
-                                    Err(e) => {
-                                        log::error!("Failed to save summary to database: {}", e);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                log::error!("Failed to summarize video with Google Gemini: {}", e);
-
-                                // 记录失败的 API 请求
-                                let error_msg = e.clone();
-                                if db::insert_api_request(
-                                    &db_pool,
-                                    &model,
-                                    "https://generativelanguage.googleapis.com/v1beta/models",
-                                    None,
-                                    None,
-                                    None,
-                                    0,
-                                    false,
-                                    Some(&error_msg),
-                                    0,
-                                )
-                                .await
-                                .is_ok()
-                                {
-                                    // API 请求记录保存成功，发送统计更新事件
-                                    if let Some(handle) = app_handle.as_ref() {
-                                        let _ = handle.emit("statistics-updated", ());
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Failed to create video from images: {}", e);
-                    }
+                Err(e) => {
+                    error = Some(e);
+                    break;
                 }
             }
-            Err(e) => {
-                log::error!("Failed to get screenshot traces from database: {}", e);
+        }
+
+        // 失败时新生成的视频也关联到这条摘要，避免后台重试时再生成一条重复的摘要
+        if error.is_some() {
+            if let Ok(pending) = db::get_pending_videos(&db_pool, MAX_VIDEO_RETRIES).await {
+                video_ids.extend(
+                    pending
+                        .iter()
+                        .filter(|v| video_paths.contains(&v.file_path))
+                        .map(|v| v.id),
+                );
+            }
+        }
+        for video_id in &video_ids {
+            if let Err(e) = db::link_video_to_summary(&db_pool, *video_id, id).await {
+                log::error!("Failed to link video to summary: {}", e);
             }
         }
+        if let Some(e) = error {
+            log::error!("Failed to regenerate summary {}: {}", id, e);
+            return Err(e);
+        }
     }
+
+    if chunk_summaries.is_empty() {
+        return Err(ClarityError::Internal("No summary generated".to_string()));
+    }
+
+    let content = if chunk_summaries.len() == 1 {
+        chunk_summaries.remove(0)
+    } else {
+        worker.rate_limiter.acquire().await;
+        merge_chunk_summaries(&db_pool, &provider, &current_language, &chunk_summaries).await
+    };
+    let structured_data = merge_structured_data(&content, &chunk_structured_data);
+
+    db::update_summary_content(&db_pool, id, &content)
+        .await
+        .map_err(ClarityError::from)?;
+    save_structured_data(
+        &db_pool,
+        id,
+        structured_data.as_ref(),
+        input_activity_ratio(&traces),
+    )
+    .await;
+    log::info!("Summary {} regenerated", id);
+
+    let summary = db::get_summary(&db_pool, id)
+        .await
+        .map_err(ClarityError::from)?
+        .ok_or_else(|| ClarityError::NotFound("Summary not found".to_string()))?;
+    events::emit(
+        app_handle.as_ref(),
+        events::SUMMARY_UPDATED,
+        events::SummaryEvent::new(id, summary.start_time, summary.end_time, &summary.content),
+    );
+    Ok(summary)
 }
 
 // 生成每日总结
@@ -256,7 +2666,9 @@ pub async fn video_summary_loop(
 pub async fn generate_daily_summary(
     state: State<'_, AppState>,
     date: Option<String>, // YYYY-MM-DD format, if None, use today
-) -> Result<db::DailySummary, String> {
+) -> Result<db::DailySummary, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    incognito::ensure_uploads_allowed(&state).await?;
     let target_date = if let Some(d) = date {
         d
     } else {
@@ -264,36 +2676,46 @@ pub async fn generate_daily_summary(
         today.format("%Y-%m-%d").to_string()
     };
 
+    let api_key = state.gemini_api_key.lock().await.clone();
+    let model = state.ai_model.lock().await.clone();
+    build_daily_summary(&state.db_pool, api_key, &model, &target_date).await
+}
+
+// 根据当天的摘要生成并保存每日总结（手动生成、自动生成和命令行共用）
+pub async fn build_daily_summary(
+    db_pool: &SqlitePool,
+    api_key: Option<String>,
+    model: &str,
+    target_date: &str,
+) -> Result<db::DailySummary, ClarityError> {
     // 解析日期
-    let date_naive = NaiveDate::parse_from_str(&target_date, "%Y-%m-%d")
-        .map_err(|e| format!("Invalid date format: {}", e))?;
+    let date_naive = NaiveDate::parse_from_str(target_date, "%Y-%m-%d")
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid date format: {}", e)))?;
 
     // 计算当天的开始和结束时间
     let start_time = date_naive
         .and_hms_opt(0, 0, 0)
-        .ok_or_else(|| "Invalid date".to_string())?
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid date".to_string()))?
         .and_local_timezone(Local)
         .single()
-        .ok_or_else(|| "Invalid timezone conversion".to_string())?;
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid timezone conversion".to_string()))?;
 
     let end_time = date_naive
         .and_hms_opt(23, 59, 59)
-        .ok_or_else(|| "Invalid date".to_string())?
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid date".to_string()))?
         .and_local_timezone(Local)
         .single()
-        .ok_or_else(|| "Invalid timezone conversion".to_string())?;
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid timezone conversion".to_string()))?;
 
     // 获取当天的所有摘要
-    let summaries = db::get_summaries(&state.db_pool, Some(start_time), Some(end_time), None)
+    let summaries = db::get_summaries(db_pool, Some(start_time), Some(end_time), None)
         .await
-        .map_err(|e| format!("Database error: {}", e))?;
+        .map_err(ClarityError::from)?;
 
     // 获取当天的截图数量
-    let screenshot_count =
-        db::get_screenshot_traces(&state.db_pool, Some(start_time), Some(end_time), None)
-            .await
-            .map_err(|e| format!("Database error: {}", e))?
-            .len() as i32;
+    let screenshot_count = db::get_screenshot_count(db_pool, Some(start_time), Some(end_time))
+        .await
+        .map_err(ClarityError::from)? as i32;
 
     // 计算总时长（秒）
     let total_duration_seconds = summaries
@@ -302,30 +2724,13 @@ pub async fn generate_daily_summary(
         .sum::<i64>();
 
     // 获取当前语言设置
-    let current_language = {
-        let lang_result = settings::load_language_from_db(&state.db_pool)
-            .await
-            .unwrap_or_else(|_| "zh".to_string());
-        lang_result
-    };
-
-    // 获取对应语言的提示词
-    let _prompt = settings::load_ai_prompt_from_db(&state.db_pool, Some(&current_language)).await
-        .unwrap_or_else(|_| {
-            if current_language == "en" {
-                "Analyze this screen activity video and provide a concise activity summary. Focus on: 1) Main apps/websites used; 2) Activity type (work/entertainment/learning, etc.); 3) Any distractions or inefficient behaviors. Respond in English, keep it under 100 words.".to_string()
-            } else {
-                "分析这段屏幕活动视频，提供简洁的活动摘要。重点关注：1) 主要使用的应用/网站；2) 活动类型（工作/娱乐/学习等）；3) 是否有分心或低效行为。用中文回答，控制在100字以内。".to_string()
-            }
-        });
+    let current_language = settings::load_language_from_db(db_pool)
+        .await
+        .unwrap_or_else(|_| locale::DEFAULT_LANGUAGE.to_string());
 
     // 如果有摘要，合并所有摘要内容并生成每日总结
     let content = if summaries.is_empty() {
-        if current_language == "en" {
-            "No activity recorded for this day.".to_string()
-        } else {
-            "今天没有记录任何活动。".to_string()
-        }
+        locale::render(&current_language, Template::NoActivityDay, &[])
     } else {
         // 合并所有摘要内容
         let combined_content = summaries
@@ -335,20 +2740,51 @@ pub async fn generate_daily_summary(
             .join("\n\n");
 
         // 使用 Gemini API 生成每日总结
-        let api_key = state.gemini_api_key.lock().await.clone();
         if let Some(key) = api_key {
-            let model = state.ai_model.lock().await.clone();
-
             // 构建提示词，要求生成每日总结
-            let daily_prompt = if current_language == "en" {
-                format!("Based on the following activity summaries from today, provide a comprehensive daily summary. Include: 1) Overall productivity assessment; 2) Main activities and time distribution; 3) Key insights and recommendations for improvement.\n\nToday's summaries:\n{}", combined_content)
-            } else {
-                format!("基于以下今天的所有活动摘要，生成一份综合的每日总结。包括：1) 整体效率评估；2) 主要活动和时间分布；3) 关键洞察和改进建议。\n\n今天的摘要：\n{}", combined_content)
-            };
+            let mut daily_prompt = locale::render(
+                &current_language,
+                Template::DailyPrompt,
+                &[("summaries", &combined_content)],
+            );
+
+            // 设置了目标时附上目标进度，让总结评价目标完成情况
+            match goals::load_goal_progress(db_pool, date_naive).await {
+                Ok(progress) if !progress.is_empty() => {
+                    let progress_text = goals::format_goal_progress(&progress, &current_language);
+                    daily_prompt.push_str(&locale::render(
+                        &current_language,
+                        Template::DailyGoals,
+                        &[("progress", &progress_text)],
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to load goal progress for daily summary: {}", e),
+            }
+
+            // 有休息提醒时附上执行情况
+            match breaks::load_break_stats(db_pool, date_naive).await {
+                Ok(stats) if stats.reminder_count > 0 => {
+                    let stats_text = breaks::format_break_stats(&stats, &current_language);
+                    daily_prompt.push_str(&locale::render(
+                        &current_language,
+                        Template::DailyBreaks,
+                        &[("stats", &stats_text)],
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to load break stats for daily summary: {}", e),
+            }
 
             // 调用 Gemini API（使用文本输入，不需要视频）
-            match video_summary::generate_text_summary_with_gemini(&key, &model, &daily_prompt)
-                .await
+            let generation = load_gemini_generation_settings(db_pool).await;
+            match video_summary::generate_text_summary_with_gemini(
+                &key,
+                model,
+                &daily_prompt,
+                &generation,
+            )
+            .await
             {
                 Ok(summary_content) => summary_content,
                 Err(e) => {
@@ -368,21 +2804,22 @@ pub async fn generate_daily_summary(
 
     // 保存或更新每日总结
     let _id = db::upsert_daily_summary(
-        &state.db_pool,
-        &target_date,
+        db_pool,
+        target_date,
         &content,
         screenshot_count,
         summaries.len() as i32,
         total_duration_seconds,
     )
     .await
-    .map_err(|e| format!("Database error: {}", e))?;
+    .map_err(ClarityError::from)?;
 
     // 获取保存的每日总结
-    let daily_summary = db::get_daily_summary(&state.db_pool, &target_date)
+    let daily_summary = db::get_daily_summary(db_pool, target_date)
         .await
-        .map_err(|e| format!("Database error: {}", e))?
+        .map_err(ClarityError::from)?
         .ok_or_else(|| "Failed to retrieve saved daily summary".to_string())?;
+    webhooks::daily_summary_created(db_pool, &daily_summary);
 
     Ok(daily_summary)
 }
@@ -392,7 +2829,8 @@ pub async fn generate_daily_summary(
 pub async fn get_daily_summary(
     state: State<'_, AppState>,
     date: Option<String>, // YYYY-MM-DD format, if None, use today
-) -> Result<Option<db::DailySummary>, String> {
+) -> Result<Option<db::DailySummary>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
     let target_date = if let Some(d) = date {
         d
     } else {
@@ -402,7 +2840,399 @@ pub async fn get_daily_summary(
 
     db::get_daily_summary(&state.db_pool, &target_date)
         .await
-        .map_err(|e| format!("Database error: {}", e))
+        .map_err(ClarityError::from)
+}
+
+// 默认自动生成每日总结的时间（本地时间）
+pub const DEFAULT_AUTO_DAILY_SUMMARY_TIME: &str = "23:30";
+
+// 检查是否需要自动生成每日总结的间隔
+const AUTO_DAILY_SUMMARY_CHECK_SECONDS: u64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoDailySummarySettings {
+    pub enabled: bool,
+    pub time: String, // 本地时间 HH:MM
+}
+
+async fn load_auto_daily_summary_settings(db_pool: &SqlitePool) -> AutoDailySummarySettings {
+    AutoDailySummarySettings {
+        enabled: settings::load_auto_daily_summary_from_db(db_pool)
+            .await
+            .unwrap_or(false),
+        time: settings::load_auto_daily_summary_time_from_db(db_pool)
+            .await
+            .unwrap_or_else(|_| DEFAULT_AUTO_DAILY_SUMMARY_TIME.to_string()),
+    }
+}
+
+// 需要自动生成每日总结的日期：到了设置的时间生成当天的总结，
+// 还没到时间时补生成前一天的（例如前一天在设置的时间之前关闭了程序）
+// 返回已到期的最后一个日期和需要生成的日期，已经处理过时返回 None
+async fn auto_daily_summary_dates(
+    db_pool: &SqlitePool,
+    time: NaiveTime,
+) -> Result<Option<(NaiveDate, Vec<NaiveDate>)>, String> {
+    let now = Local::now();
+    let today = now.date_naive();
+    let yesterday = today - chrono::Duration::days(1);
+    let due = if now.time() >= time { today } else { yesterday };
+
+    let last = settings::load_auto_daily_summary_last_date_from_db(db_pool)
+        .await
+        .ok()
+        .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+    if last.is_some_and(|last| last >= due) {
+        return Ok(None);
+    }
+
+    let mut dates = Vec::new();
+    if due == today && last.is_none_or(|last| last < yesterday) {
+        dates.push(yesterday);
+    }
+    dates.push(due);
+
+    // 没有摘要的日期和已有最新总结的往日不需要生成
+    let mut pending = Vec::new();
+    for date in dates {
+        let (start_time, end_time) = goals::day_range(date)?;
+        let has_summaries = db::get_summary_count(db_pool, Some(start_time), Some(end_time))
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            > 0;
+        let existing = db::get_daily_summary(db_pool, &date.format("%Y-%m-%d").to_string())
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        let up_to_date = date < today && existing.is_some_and(|daily| !daily.stale);
+        if has_summaries && !up_to_date {
+            pending.push(date);
+        }
+    }
+    Ok(Some((due, pending)))
+}
+
+// 在设置的时间自动生成每日总结
+pub async fn auto_daily_summary_loop(
+    db_pool: SqlitePool,
+    gemini_api_key: Arc<Mutex<Option<String>>>,
+    ai_model: Arc<Mutex<String>>,
+    incognito: Arc<Mutex<bool>>,
+    ai_upload_consent: Arc<Mutex<bool>>,
+    app_handle: AppHandle,
+) {
+    let mut interval = interval(StdDuration::from_secs(AUTO_DAILY_SUMMARY_CHECK_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        // 隐身模式下或用户没有同意上传时不生成（不上传任何内容）
+        let auto_settings = load_auto_daily_summary_settings(&db_pool).await;
+        if !auto_settings.enabled || *incognito.lock().await || !*ai_upload_consent.lock().await {
+            continue;
+        }
+        let time = NaiveTime::parse_from_str(&auto_settings.time, "%H:%M").unwrap_or_else(|_| {
+            NaiveTime::parse_from_str(DEFAULT_AUTO_DAILY_SUMMARY_TIME, "%H:%M").unwrap()
+        });
+
+        let (due, dates) = match auto_daily_summary_dates(&db_pool, time).await {
+            Ok(Some(result)) => result,
+            Ok(None) => continue,
+            Err(e) => {
+                log::error!("Failed to check automatic daily summary: {}", e);
+                continue;
+            }
+        };
+
+        let mut failed = false;
+        for date in &dates {
+            let date = date.format("%Y-%m-%d").to_string();
+            let api_key = gemini_api_key.lock().await.clone();
+            let model = ai_model.lock().await.clone();
+            match build_daily_summary(&db_pool, api_key, &model, &date).await {
+                Ok(daily_summary) => {
+                    log::info!("Daily summary generated automatically for {}", date);
+                    notifications::notify_summary_ready(
+                        Some(&app_handle),
+                        &db_pool,
+                        SummaryNotification::DailySummary,
+                        &daily_summary.content,
+                    )
+                    .await;
+                }
+                Err(e) => {
+                    log::error!("Failed to generate daily summary for {}: {}", date, e);
+                    failed = true;
+                    break;
+                }
+            }
+        }
+        if failed {
+            continue;
+        }
+
+        // 记录已处理到的日期，没有需要生成的日期时也记录，避免重复检查
+        let due = due.format("%Y-%m-%d").to_string();
+        if let Err(e) = settings::save_auto_daily_summary_last_date_to_db(&db_pool, &due).await {
+            log::error!("Failed to save last automatic daily summary date: {}", e);
+        }
+    }
+}
+
+// 获取自动生成每日总结的设置
+#[tauri::command]
+pub async fn get_auto_daily_summary_settings(
+    state: State<'_, AppState>,
+) -> Result<AutoDailySummarySettings, ClarityError> {
+    Ok(load_auto_daily_summary_settings(&state.db_pool).await)
+}
+
+// 设置是否在每天的指定时间（本地时间 HH:MM）自动生成每日总结
+#[tauri::command]
+pub async fn set_auto_daily_summary_settings(
+    state: State<'_, AppState>,
+    enabled: bool,
+    time: String,
+) -> Result<(), ClarityError> {
+    let time = NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid time format: {}", e)))?
+        .format("%H:%M")
+        .to_string();
+
+    settings::save_auto_daily_summary_to_db(&state.db_pool, enabled, &time)
+        .await
+        .map_err(ClarityError::from)?;
+
+    log::info!(
+        "Automatic daily summary settings updated: enabled={}, time={}",
+        enabled,
+        time
+    );
+    Ok(())
+}
+
+// 每月总结中列出的最忙日期数量
+const MONTHLY_BUSIEST_DAYS: usize = 5;
+
+// 根据当月的摘要计算趋势：总时长、分类占比、最忙的日期和平均专注度
+fn compute_monthly_trends(summaries: &[db::Summary]) -> MonthlyTrends {
+    let mut category_seconds: std::collections::HashMap<String, i64> =
+        std::collections::HashMap::new();
+    let mut day_seconds: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
+    let mut focus_scores = Vec::new();
+    let mut total_seconds = 0;
+
+    for summary in summaries {
+        let seconds = (summary.end_time - summary.start_time).num_seconds();
+        total_seconds += seconds;
+        *day_seconds
+            .entry(summary.start_time.format("%Y-%m-%d").to_string())
+            .or_insert(0) += seconds;
+
+        let structured = summary.structured_data.as_ref();
+        let category = structured
+            .and_then(|data| data["category"].as_str())
+            .unwrap_or("other");
+        *category_seconds.entry(category.to_string()).or_insert(0) += seconds;
+        if let Some(score) = summary.focus_score {
+            focus_scores.push(score);
+        }
+    }
+
+    let mut top_categories: Vec<CategoryDuration> = category_seconds
+        .into_iter()
+        .map(|(category, duration_seconds)| CategoryDuration {
+            category,
+            duration_seconds,
+        })
+        .collect();
+    top_categories.sort_by(|a, b| b.duration_seconds.cmp(&a.duration_seconds));
+
+    let active_days = day_seconds.len() as i64;
+    let mut busiest_days: Vec<DayDuration> = day_seconds
+        .into_iter()
+        .map(|(date, duration_seconds)| DayDuration {
+            date,
+            duration_seconds,
+        })
+        .collect();
+    busiest_days.sort_by(|a, b| b.duration_seconds.cmp(&a.duration_seconds));
+    busiest_days.truncate(MONTHLY_BUSIEST_DAYS);
+
+    let average_focus_score = if focus_scores.is_empty() {
+        None
+    } else {
+        Some(focus_scores.iter().sum::<i64>() as f64 / focus_scores.len() as f64)
+    };
+
+    MonthlyTrends {
+        total_tracked_hours: total_seconds as f64 / 3600.0,
+        active_days,
+        average_focus_score,
+        top_categories,
+        busiest_days,
+    }
+}
+
+// 生成每月总结（基于当月的每日总结和计算出的趋势）
+#[tauri::command]
+pub async fn generate_monthly_summary(
+    state: State<'_, AppState>,
+    month: Option<String>, // YYYY-MM format, if None, use current month
+) -> Result<db::MonthlySummary, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    incognito::ensure_uploads_allowed(&state).await?;
+    let target_month = month.unwrap_or_else(|| Local::now().format("%Y-%m").to_string());
+
+    // 计算当月的第一天和下个月的第一天
+    let first_day = NaiveDate::parse_from_str(&format!("{}-01", target_month), "%Y-%m-%d")
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid month format: {}", e)))?;
+    let next_month = first_day
+        .checked_add_months(chrono::Months::new(1))
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid month".to_string()))?;
+    let last_day = next_month
+        .pred_opt()
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid month".to_string()))?;
+
+    let start_time = first_day
+        .and_hms_opt(0, 0, 0)
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid date".to_string()))?
+        .and_local_timezone(Local)
+        .single()
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid timezone conversion".to_string()))?;
+    let end_time = last_day
+        .and_hms_opt(23, 59, 59)
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid date".to_string()))?
+        .and_local_timezone(Local)
+        .single()
+        .ok_or_else(|| ClarityError::InvalidInput("Invalid timezone conversion".to_string()))?;
+
+    // 获取当月的所有摘要，用于计算趋势
+    let summaries = db::get_summaries(&state.db_pool, Some(start_time), Some(end_time), None)
+        .await
+        .map_err(ClarityError::from)?;
+    let trends = compute_monthly_trends(&summaries);
+    let total_duration_seconds = summaries
+        .iter()
+        .map(|s| (s.end_time - s.start_time).num_seconds())
+        .sum::<i64>();
+
+    // 当月的每日总结作为生成月度叙述的素材（逐条摘要太多，不直接发送）
+    let first_day_str = first_day.format("%Y-%m-%d").to_string();
+    let last_day_str = last_day.format("%Y-%m-%d").to_string();
+    let mut daily_summaries = db::get_daily_summaries(
+        &state.db_pool,
+        Some(&first_day_str),
+        Some(&last_day_str),
+        None,
+    )
+    .await
+    .map_err(ClarityError::from)?;
+    daily_summaries.reverse();
+
+    let current_language = settings::load_language_from_db(&state.db_pool)
+        .await
+        .unwrap_or_else(|_| locale::DEFAULT_LANGUAGE.to_string());
+
+    let trends_text = serde_json::to_string_pretty(&trends).unwrap_or_default();
+    let combined_content = daily_summaries
+        .iter()
+        .map(|d| format!("{}:\n{}", d.date, d.content))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let content = if summaries.is_empty() {
+        locale::render(&current_language, Template::NoActivityMonth, &[])
+    } else {
+        // 使用 Gemini API 生成每月总结，失败时保留每日总结拼接的内容
+        let fallback = if combined_content.is_empty() {
+            trends_text.clone()
+        } else {
+            combined_content.clone()
+        };
+
+        let api_key = state.gemini_api_key.lock().await.clone();
+        if let Some(key) = api_key {
+            let model = state.ai_model.lock().await.clone();
+
+            let monthly_prompt = locale::render(
+                &current_language,
+                Template::MonthlyPrompt,
+                &[
+                    ("month", &target_month),
+                    ("statistics", &trends_text),
+                    ("summaries", &combined_content),
+                ],
+            );
+
+            let generation = load_gemini_generation_settings(&state.db_pool).await;
+            match video_summary::generate_text_summary_with_gemini(
+                &key,
+                &model,
+                &monthly_prompt,
+                &generation,
+            )
+            .await
+            {
+                Ok(summary_content) => summary_content,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to generate monthly summary with AI: {}. Using daily summaries.",
+                        e
+                    );
+                    fallback
+                }
+            }
+        } else {
+            fallback
+        }
+    };
+
+    let trends_json =
+        serde_json::to_string(&trends).map_err(|e| format!("Serialization error: {}", e))?;
+    db::upsert_monthly_summary(
+        &state.db_pool,
+        &target_month,
+        &content,
+        summaries.len() as i32,
+        total_duration_seconds,
+        &trends_json,
+    )
+    .await
+    .map_err(ClarityError::from)?;
+
+    db::get_monthly_summary(&state.db_pool, &target_month)
+        .await
+        .map_err(ClarityError::from)?
+        .ok_or_else(|| {
+            ClarityError::Internal("Failed to retrieve saved monthly summary".to_string())
+        })
+}
+
+// 获取每月总结
+#[tauri::command]
+pub async fn get_monthly_summary(
+    state: State<'_, AppState>,
+    month: Option<String>, // YYYY-MM format, if None, use current month
+) -> Result<Option<db::MonthlySummary>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let target_month = month.unwrap_or_else(|| Local::now().format("%Y-%m").to_string());
+
+    db::get_monthly_summary(&state.db_pool, &target_month)
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 获取最近的每月总结
+#[tauri::command]
+pub async fn get_monthly_summaries(
+    state: State<'_, AppState>,
+    limit: Option<i64>,
+) -> Result<Vec<db::MonthlySummary>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    db::get_monthly_summaries(&state.db_pool, limit)
+        .await
+        .map_err(ClarityError::from)
 }
 
 // 获取历史统计数据（用于图表）
@@ -410,7 +3240,8 @@ pub async fn get_daily_summary(
 pub async fn get_historical_stats(
     state: State<'_, AppState>,
     days: i64, // 获取最近多少天的数据
-) -> Result<Vec<HistoricalStats>, String> {
+) -> Result<Vec<HistoricalStats>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
     let end_date = Local::now().date_naive();
     let start_date = end_date - chrono::Duration::days(days - 1);
 
@@ -425,7 +3256,7 @@ pub async fn get_historical_stats(
         None,
     )
     .await
-    .map_err(|e| format!("Database error: {}", e))?;
+    .map_err(ClarityError::from)?;
 
     // 创建一个日期到统计数据的映射
     let mut stats_map: std::collections::HashMap<String, HistoricalStats> =
@@ -440,55 +3271,53 @@ pub async fn get_historical_stats(
                 screenshot_count: summary.screenshot_count as i64,
                 summary_count: summary.summary_count as i64,
                 total_duration_seconds: summary.total_duration_seconds,
+                gap_seconds: 0,
             },
         );
     }
 
-    // 填充缺失的日期（如果没有每日总结，从原始数据计算）
+    // 没有每日总结的日期从原始数据统计（按日期聚合，不需要读取每条记录）
+    let (range_start, _) = goals::day_range(start_date)?;
+    let (_, range_end) = goals::day_range(end_date)?;
+    let screenshot_counts: std::collections::HashMap<String, i64> =
+        db::get_daily_screenshot_counts(&state.db_pool, range_start, range_end)
+            .await
+            .map_err(ClarityError::from)?
+            .into_iter()
+            .collect();
+    let summary_totals: std::collections::HashMap<String, (i64, i64)> =
+        db::get_daily_summary_totals(&state.db_pool, range_start, range_end)
+            .await
+            .map_err(ClarityError::from)?
+            .into_iter()
+            .map(|(date, count, duration)| (date, (count, duration)))
+            .collect();
+    let gap_seconds: std::collections::HashMap<String, i64> =
+        db::get_daily_gap_seconds(&state.db_pool, range_start, range_end, "crash")
+            .await
+            .map_err(ClarityError::from)?
+            .into_iter()
+            .collect();
+
     let mut current_date = start_date;
     let mut result: Vec<HistoricalStats> = Vec::new();
 
     while current_date <= end_date {
         let date_str = current_date.format("%Y-%m-%d").to_string();
 
-        if let Some(stats) = stats_map.get(&date_str) {
-            result.push(stats.clone());
+        let gaps = gap_seconds.get(&date_str).copied().unwrap_or(0);
+        if let Some(mut stats) = stats_map.remove(&date_str) {
+            stats.gap_seconds = gaps;
+            result.push(stats);
         } else {
-            // 如果没有每日总结，从原始数据计算
-            let day_start = current_date
-                .and_hms_opt(0, 0, 0)
-                .ok_or_else(|| "Invalid date".to_string())?
-                .and_local_timezone(Local)
-                .single()
-                .ok_or_else(|| "Invalid timezone conversion".to_string())?;
-
-            let day_end = current_date
-                .and_hms_opt(23, 59, 59)
-                .ok_or_else(|| "Invalid date".to_string())?
-                .and_local_timezone(Local)
-                .single()
-                .ok_or_else(|| "Invalid timezone conversion".to_string())?;
-
-            // 从数据库获取当天的截图和摘要
-            let screenshots =
-                db::get_screenshot_traces(&state.db_pool, Some(day_start), Some(day_end), None)
-                    .await
-                    .map_err(|e| format!("Database error: {}", e))?;
-
-            let summaries = db::get_summaries(&state.db_pool, Some(day_start), Some(day_end), None)
-                .await
-                .map_err(|e| format!("Database error: {}", e))?;
-
-            let total_duration = summaries
-                .iter()
-                .map(|s| (s.end_time - s.start_time).num_seconds())
-                .sum::<i64>();
-
+            let (summary_count, total_duration) =
+                summary_totals.get(&date_str).copied().unwrap_or((0, 0));
             result.push(HistoricalStats {
-                date: date_str,
-                screenshot_count: screenshots.len() as i64,
-                summary_count: summaries.len() as i64,
+                screenshot_count: screenshot_counts.get(&date_str).copied().unwrap_or(0),
+                summary_count,
                 total_duration_seconds: total_duration,
+                gap_seconds: gaps,
+                date: date_str,
             });
         }
 
@@ -500,3 +3329,140 @@ pub async fn get_historical_stats(
 
     Ok(result)
 }
+
+// 按时间段汇总专注度，period_key 决定分组方式（按天或按周）
+fn aggregate_focus_scores(
+    points: &[FocusScorePoint],
+    period_key: impl Fn(&DateTime<Local>) -> String,
+) -> Vec<FocusScoreAggregate> {
+    let mut aggregates: Vec<FocusScoreAggregate> = Vec::new();
+    let mut weighted_sums: Vec<f64> = Vec::new();
+    let mut weights: Vec<f64> = Vec::new();
+
+    for point in points {
+        let period = period_key(&point.start_time);
+        let seconds = (point.end_time - point.start_time).num_seconds().max(0);
+        // 时长为 0 的摘要也至少算 1 秒，避免被完全忽略
+        let weight = seconds.max(1) as f64;
+
+        let index = match aggregates.iter().position(|a| a.period == period) {
+            Some(index) => index,
+            None => {
+                aggregates.push(FocusScoreAggregate {
+                    period,
+                    average_score: 0.0,
+                    summary_count: 0,
+                    duration_seconds: 0,
+                });
+                weighted_sums.push(0.0);
+                weights.push(0.0);
+                aggregates.len() - 1
+            }
+        };
+        aggregates[index].summary_count += 1;
+        aggregates[index].duration_seconds += seconds;
+        weighted_sums[index] += point.focus_score as f64 * weight;
+        weights[index] += weight;
+    }
+
+    for (index, aggregate) in aggregates.iter_mut().enumerate() {
+        aggregate.average_score = (weighted_sums[index] / weights[index] * 10.0).round() / 10.0;
+    }
+    aggregates.sort_by(|a, b| a.period.cmp(&b.period));
+    aggregates
+}
+
+// 获取时间范围内每条摘要的专注度，以及按天和按周的汇总（用于图表）
+#[tauri::command]
+pub async fn get_focus_scores(
+    state: State<'_, AppState>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+) -> Result<FocusScores, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let start_dt = start_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid start_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    let end_dt = end_time
+        .map(|s| DateTime::parse_from_rfc3339(&s))
+        .transpose()
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid end_time format: {}", e)))?
+        .map(|dt| dt.with_timezone(&Local));
+
+    let summaries = db::get_summaries(&state.db_pool, start_dt, end_dt, None)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let mut intervals: Vec<FocusScorePoint> = summaries
+        .iter()
+        .filter_map(|s| {
+            Some(FocusScorePoint {
+                summary_id: s.id,
+                start_time: s.start_time,
+                end_time: s.end_time,
+                focus_score: s.focus_score?,
+            })
+        })
+        .collect();
+    intervals.sort_by_key(|p| p.start_time);
+
+    let daily = aggregate_focus_scores(&intervals, |t| t.format("%Y-%m-%d").to_string());
+    let weekly = aggregate_focus_scores(&intervals, |t| t.format("%G-W%V").to_string());
+
+    Ok(FocusScores {
+        intervals,
+        daily,
+        weekly,
+    })
+}
+
+// 同一条摘要的多个模型结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelComparison {
+    pub summary_id: i64,
+    pub start_time: Option<DateTime<Local>>, // 摘要已不存在时为 None
+    pub end_time: Option<DateTime<Local>>,
+    pub results: Vec<db::ModelComparisonResult>, // 主模型在前
+}
+
+// 获取最近的模型对比结果
+#[tauri::command]
+pub async fn get_model_comparisons(
+    state: State<'_, AppState>,
+    limit: Option<i64>,
+) -> Result<Vec<ModelComparison>, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let results = db::get_model_comparisons(&state.db_pool, limit)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let mut comparisons: Vec<ModelComparison> = Vec::new();
+    for result in results {
+        match comparisons.last_mut() {
+            Some(comparison) if comparison.summary_id == result.summary_id => {
+                comparison.results.push(result);
+            }
+            _ => {
+                let summary = db::get_summary(&state.db_pool, result.summary_id)
+                    .await
+                    .map_err(ClarityError::from)?;
+                comparisons.push(ModelComparison {
+                    summary_id: result.summary_id,
+                    start_time: summary.as_ref().map(|s| s.start_time),
+                    end_time: summary.as_ref().map(|s| s.end_time),
+                    results: vec![result],
+                });
+            }
+        }
+    }
+
+    for comparison in &mut comparisons {
+        comparison.results.sort_by_key(|r| r.role != "primary");
+    }
+
+    Ok(comparisons)
+}
@@ -0,0 +1,135 @@
+use crate::commands::summary::structured_categories;
+use crate::db;
+use crate::error::ClarityError;
+use crate::state::AppState;
+use sqlx::SqlitePool;
+use tauri::State;
+
+// 标签名称的最大长度
+const MAX_TAG_NAME_CHARS: usize = 50;
+
+fn normalize_tag_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_TAG_NAME_CHARS {
+        return Err(format!(
+            "Tag name cannot be longer than {} characters",
+            MAX_TAG_NAME_CHARS
+        ));
+    }
+    Ok(name.to_string())
+}
+
+// 找出与结构化数据匹配的标签：标签名称与某个应用或分类相同，
+// 或者某个关键词出现在应用或分类中（都不区分大小写）
+fn matching_tag_ids(tags: &[db::Tag], data: &serde_json::Value) -> Vec<i64> {
+    let values: Vec<String> = data["apps"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|app| app.as_str())
+        .map(str::to_string)
+        .chain(structured_categories(data))
+        .map(|value| value.to_lowercase())
+        .collect();
+
+    tags.iter()
+        .filter(|tag| {
+            let name = tag.name.to_lowercase();
+            values.iter().any(|value| {
+                *value == name
+                    || tag.keywords.iter().any(|keyword| {
+                        let keyword = keyword.trim().to_lowercase();
+                        !keyword.is_empty() && value.contains(&keyword)
+                    })
+            })
+        })
+        .map(|tag| tag.id)
+        .collect()
+}
+
+// 根据摘要的结构化数据自动添加标签（重新生成摘要时替换原有的自动标签）
+pub async fn auto_tag_summary(
+    db_pool: &SqlitePool,
+    summary_id: i64,
+    data: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    let tags = db::get_tags(db_pool).await?;
+    if tags.is_empty() {
+        return Ok(());
+    }
+    db::set_auto_summary_tags(db_pool, summary_id, &matching_tag_ids(&tags, data)).await
+}
+
+// 获取所有标签
+#[tauri::command]
+pub async fn get_tags(state: State<'_, AppState>) -> Result<Vec<db::Tag>, ClarityError> {
+    db::get_tags(&state.db_pool)
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 创建标签（同名标签已存在时更新关键词），返回标签 ID
+#[tauri::command]
+pub async fn create_tag(
+    state: State<'_, AppState>,
+    name: String,
+    keywords: Option<Vec<String>>,
+) -> Result<i64, ClarityError> {
+    let name = normalize_tag_name(&name).map_err(ClarityError::InvalidInput)?;
+    let keywords: Vec<String> = keywords
+        .unwrap_or_default()
+        .into_iter()
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect();
+
+    db::upsert_tag(&state.db_pool, &name, Some(&keywords))
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 删除标签（同时从所有摘要中移除）
+#[tauri::command]
+pub async fn delete_tag(state: State<'_, AppState>, tag_id: i64) -> Result<(), ClarityError> {
+    db::delete_tag(&state.db_pool, tag_id)
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 给摘要添加标签，标签不存在时自动创建，返回标签 ID
+#[tauri::command]
+pub async fn add_summary_tag(
+    state: State<'_, AppState>,
+    summary_id: i64,
+    tag: String,
+) -> Result<i64, ClarityError> {
+    let name = normalize_tag_name(&tag).map_err(ClarityError::InvalidInput)?;
+    db::get_summary(&state.db_pool, summary_id)
+        .await
+        .map_err(ClarityError::from)?
+        .ok_or_else(|| ClarityError::NotFound(format!("Summary {} not found", summary_id)))?;
+
+    let tag_id = db::upsert_tag(&state.db_pool, &name, None)
+        .await
+        .map_err(ClarityError::from)?;
+    db::add_summary_tag(&state.db_pool, summary_id, tag_id, false)
+        .await
+        .map_err(ClarityError::from)?;
+
+    Ok(tag_id)
+}
+
+// 移除摘要的标签
+#[tauri::command]
+pub async fn remove_summary_tag(
+    state: State<'_, AppState>,
+    summary_id: i64,
+    tag_id: i64,
+) -> Result<(), ClarityError> {
+    db::remove_summary_tag(&state.db_pool, summary_id, tag_id)
+        .await
+        .map_err(ClarityError::from)
+}
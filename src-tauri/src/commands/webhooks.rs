@@ -0,0 +1,391 @@
+use crate::commands::api_server::generate_token;
+use crate::commands::{hooks, lock};
+use crate::db;
+use crate::error::ClarityError;
+use crate::proxy;
+use crate::state::AppState;
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tauri::State;
+
+// 摘要保存后推送
+pub const WEBHOOK_SUMMARY_CREATED: &str = "summary.created";
+// 每日总结保存后推送（重新生成时也会推送）
+pub const WEBHOOK_DAILY_SUMMARY_CREATED: &str = "daily_summary.created";
+// 只由 test_webhook 发送，不需要订阅
+const WEBHOOK_PING: &str = "ping";
+
+const WEBHOOK_EVENTS: [&str; 2] = [WEBHOOK_SUMMARY_CREATED, WEBHOOK_DAILY_SUMMARY_CREATED];
+
+// 单次请求超时
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+// 连接失败或返回 5xx 时重试，4xx 说明接收方拒绝，不重试
+const WEBHOOK_MAX_ATTEMPTS: u32 = 3;
+
+// 推送的 JSON：{ event, timestamp, data }，data 和前端命令返回的结构一致
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a, T: Serialize> {
+    event: &'a str,
+    timestamp: i64,
+    data: T,
+}
+
+// 添加 webhook 时返回，只有这里和 get_webhook_secret 包含签名密钥
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewWebhook {
+    #[serde(flatten)]
+    pub webhook: db::Webhook,
+    pub secret: String,
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+// 签名：HMAC-SHA256(secret, "{timestamp}.{body}")，十六进制
+// 接收方用同样的方式计算并比较 X-Clarity-Signature，同时检查时间戳防止重放
+fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn validate_url(url: &str) -> Result<String, ClarityError> {
+    let url = url.trim();
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| ClarityError::InvalidInput(format!("Invalid webhook URL: {}", e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(ClarityError::InvalidInput(
+            "Webhook URL must start with http:// or https://".to_string(),
+        ));
+    }
+    Ok(url.to_string())
+}
+
+pub(crate) fn validate_events(events: Option<Vec<String>>) -> Result<Vec<String>, ClarityError> {
+    // 不指定时订阅所有事件
+    let Some(events) = events else {
+        return Ok(WEBHOOK_EVENTS.iter().map(|e| e.to_string()).collect());
+    };
+    let mut result: Vec<String> = Vec::new();
+    for event in events {
+        let event = event.trim().to_string();
+        if !WEBHOOK_EVENTS.contains(&event.as_str()) {
+            return Err(ClarityError::InvalidInput(format!(
+                "Unknown webhook event: {} (expected one of: {})",
+                event,
+                WEBHOOK_EVENTS.join(", ")
+            )));
+        }
+        if !result.contains(&event) {
+            result.push(event);
+        }
+    }
+    if result.is_empty() {
+        return Err(ClarityError::InvalidInput(
+            "Webhook must subscribe to at least one event".to_string(),
+        ));
+    }
+    Ok(result)
+}
+
+async fn find_webhook(db_pool: &SqlitePool, id: i64) -> Result<db::Webhook, ClarityError> {
+    db::get_webhooks(db_pool)
+        .await?
+        .into_iter()
+        .find(|w| w.id == id)
+        .ok_or_else(|| ClarityError::NotFound(format!("Webhook {} not found", id)))
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    proxy::client_builder()
+        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+// 发送一次推送（失败时重试），返回接收方的状态码；非 2xx 也算失败
+async fn deliver(
+    client: &reqwest::Client,
+    webhook: &db::Webhook,
+    event: &str,
+    body: &[u8],
+) -> (Option<u16>, Result<(), String>) {
+    let mut last = (None, Err("Webhook was not sent".to_string()));
+    for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+        if attempt > 1 {
+            tokio::time::sleep(Duration::from_secs(2u64.pow(attempt - 1))).await;
+        }
+        // 每次重试重新签名，时间戳和发送时间一致
+        let timestamp = Local::now().timestamp();
+        let result = client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-Clarity-Event", event)
+            .header("X-Clarity-Timestamp", timestamp.to_string())
+            .header(
+                "X-Clarity-Signature",
+                format!("sha256={}", sign(&webhook.secret, timestamp, body)),
+            )
+            .body(body.to_vec())
+            .send()
+            .await;
+
+        let (status, error, retry) = match result {
+            Ok(response) => {
+                let status = response.status();
+                if status.is_success() {
+                    return (Some(status.as_u16()), Ok(()));
+                }
+                (
+                    Some(status.as_u16()),
+                    format!("Webhook returned HTTP {}", status.as_u16()),
+                    !status.is_client_error(),
+                )
+            }
+            Err(e) => (None, format!("Failed to send request: {}", e), true),
+        };
+        log::warn!(
+            "Webhook {} delivery of {} failed (attempt {}/{}): {}",
+            webhook.id,
+            event,
+            attempt,
+            WEBHOOK_MAX_ATTEMPTS,
+            error
+        );
+        last = (status, Err(error));
+        if !retry {
+            break;
+        }
+    }
+    last
+}
+
+// 发送并记录结果
+async fn deliver_and_record(
+    db_pool: &SqlitePool,
+    client: &reqwest::Client,
+    webhook: &db::Webhook,
+    event: &str,
+    body: &[u8],
+) -> (Option<u16>, Result<(), String>) {
+    let (status, result) = deliver(client, webhook, event, body).await;
+    if let Err(e) = db::record_webhook_delivery(
+        db_pool,
+        webhook.id,
+        status.map(i64::from),
+        result.as_ref().err().map(String::as_str),
+    )
+    .await
+    {
+        log::error!("Failed to record webhook delivery: {}", e);
+    }
+    (status, result)
+}
+
+// 在后台推送给订阅了 event 的所有 webhook 并运行后处理脚本，不阻塞摘要生成
+fn dispatch<T: Serialize>(db_pool: &SqlitePool, event: &'static str, data: T) {
+    let body = match serde_json::to_vec(&WebhookPayload {
+        event,
+        timestamp: Local::now().timestamp(),
+        data,
+    }) {
+        Ok(body) => body,
+        Err(e) => {
+            log::error!("Failed to serialize webhook payload: {}", e);
+            return;
+        }
+    };
+    hooks::dispatch(db_pool, event, body.clone());
+    let db_pool = db_pool.clone();
+    tokio::spawn(async move {
+        let webhooks = match db::get_webhooks(&db_pool).await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                log::error!("Failed to load webhooks: {}", e);
+                return;
+            }
+        };
+        let webhooks: Vec<_> = webhooks
+            .into_iter()
+            .filter(|w| w.enabled && w.events.iter().any(|e| e == event))
+            .collect();
+        if webhooks.is_empty() {
+            return;
+        }
+        let client = match client() {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!("{}", e);
+                return;
+            }
+        };
+        for webhook in &webhooks {
+            if let (_, Err(e)) = deliver_and_record(&db_pool, &client, webhook, event, &body).await
+            {
+                log::error!("Webhook {} delivery of {} failed: {}", webhook.id, event, e);
+            }
+        }
+    });
+}
+
+// 摘要保存后调用（推送 webhook、运行后处理脚本）
+pub fn summary_created(db_pool: &SqlitePool, summary_id: i64) {
+    let db_pool = db_pool.clone();
+    tokio::spawn(async move {
+        match db::get_summary(&db_pool, summary_id).await {
+            Ok(Some(summary)) => dispatch(&db_pool, WEBHOOK_SUMMARY_CREATED, summary),
+            Ok(None) => {}
+            Err(e) => log::error!("Failed to load summary {} for webhooks: {}", summary_id, e),
+        }
+    });
+}
+
+// 每日总结保存后调用
+pub fn daily_summary_created(db_pool: &SqlitePool, daily_summary: &db::DailySummary) {
+    dispatch(
+        db_pool,
+        WEBHOOK_DAILY_SUMMARY_CREATED,
+        daily_summary.clone(),
+    );
+}
+
+// 获取所有 webhook（不包含签名密钥）
+#[tauri::command]
+pub async fn get_webhooks(state: State<'_, AppState>) -> Result<Vec<db::Webhook>, ClarityError> {
+    db::get_webhooks(&state.db_pool)
+        .await
+        .map_err(ClarityError::from)
+}
+
+// 添加 webhook，自动生成签名密钥；events 为空时订阅所有事件
+#[tauri::command]
+pub async fn add_webhook(
+    state: State<'_, AppState>,
+    url: String,
+    events: Option<Vec<String>>,
+) -> Result<NewWebhook, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let url = validate_url(&url)?;
+    let events = validate_events(events)?;
+    let id = db::insert_webhook(&state.db_pool, &url, &generate_token(), &events).await?;
+    log::info!("Added webhook {} for {}", id, events.join(", "));
+
+    let webhook = find_webhook(&state.db_pool, id).await?;
+    Ok(NewWebhook {
+        secret: webhook.secret.clone(),
+        webhook,
+    })
+}
+
+// 查看 webhook 的签名密钥，用于重新配置接收方
+#[tauri::command]
+pub async fn get_webhook_secret(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<String, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    Ok(find_webhook(&state.db_pool, id).await?.secret)
+}
+
+// 修改 webhook 的地址、订阅的事件和是否启用
+#[tauri::command]
+pub async fn update_webhook(
+    state: State<'_, AppState>,
+    id: i64,
+    url: String,
+    events: Option<Vec<String>>,
+    enabled: bool,
+) -> Result<(), ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let url = validate_url(&url)?;
+    let events = validate_events(events)?;
+    if !db::update_webhook(&state.db_pool, id, &url, &events, enabled).await? {
+        return Err(ClarityError::NotFound(format!("Webhook {} not found", id)));
+    }
+    Ok(())
+}
+
+// 删除 webhook
+#[tauri::command]
+pub async fn delete_webhook(state: State<'_, AppState>, id: i64) -> Result<(), ClarityError> {
+    if !db::delete_webhook(&state.db_pool, id).await? {
+        return Err(ClarityError::NotFound(format!("Webhook {} not found", id)));
+    }
+    Ok(())
+}
+
+// 立即发送一条 ping 测试 webhook（不检查是否启用），返回接收方的状态码
+#[tauri::command]
+pub async fn test_webhook(state: State<'_, AppState>, id: i64) -> Result<u16, ClarityError> {
+    lock::ensure_unlocked(&state).await?;
+    let webhook = find_webhook(&state.db_pool, id).await?;
+
+    let body = serde_json::to_vec(&WebhookPayload {
+        event: WEBHOOK_PING,
+        timestamp: Local::now().timestamp(),
+        data: serde_json::json!({ "webhookId": webhook.id }),
+    })
+    .map_err(|e| format!("Failed to serialize webhook payload: {}", e))?;
+
+    let client = client()?;
+    match deliver_and_record(&state.db_pool, &client, &webhook, WEBHOOK_PING, &body).await {
+        (Some(status), Ok(())) => Ok(status),
+        (Some(_), Err(e)) => Err(ClarityError::Network(e)),
+        (None, result) => Err(ClarityError::Network(
+            result
+                .err()
+                .unwrap_or_else(|| "Webhook was not sent".to_string()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 接收方按文档中的方式计算的签名
+    #[test]
+    fn sign_matches_hmac_of_timestamp_and_body() {
+        assert_eq!(
+            sign("secret", 1700000000, br#"{"event":"ping"}"#),
+            "4d39bd2442f073b6bc62e95d0297ce25475582a17389ab860abdc778fe1d9f77"
+        );
+    }
+
+    // get_webhooks 不返回签名密钥，添加时才返回
+    #[test]
+    fn secret_is_only_serialized_for_new_webhooks() {
+        let webhook = db::Webhook {
+            id: 1,
+            url: "https://example.com/hook".to_string(),
+            secret: "secret".to_string(),
+            events: vec![WEBHOOK_SUMMARY_CREATED.to_string()],
+            enabled: true,
+            created_at: Local::now(),
+            last_delivery_at: None,
+            last_status: None,
+            last_error: None,
+        };
+        let listed = serde_json::to_value(&webhook).unwrap();
+        assert!(listed.get("secret").is_none());
+
+        let added = serde_json::to_value(NewWebhook {
+            secret: webhook.secret.clone(),
+            webhook,
+        })
+        .unwrap();
+        assert_eq!(added["secret"], "secret");
+        assert_eq!(added["url"], "https://example.com/hook");
+    }
+}
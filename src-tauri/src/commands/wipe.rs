@@ -0,0 +1,215 @@
+use crate::commands::recording::wait_for_screenshot_task;
+use crate::commands::summary::{
+    start_summary_loop, start_summary_worker, stop_summary_loop, stop_summary_worker,
+};
+use crate::db;
+use crate::error::ClarityError;
+use crate::events;
+use crate::keychain;
+use crate::logging;
+use crate::profiles;
+use crate::state::AppState;
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use tauri::State;
+
+// 清除全部数据前必须原样传入的确认文本，防止误调用
+pub const WIPE_CONFIRM_TOKEN: &str = "WIPE ALL DATA";
+
+// 覆盖文件内容时每次写入的大小
+const SHRED_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WipeReport {
+    pub deleted_files: u64,    // 截图、缩略图、总结视频、日志和其他配置的文件
+    pub failed_files: u64,     // 无法删除的文件（详情见日志）
+    pub deleted_rows: u64,     // 数据库中删除的行数
+    pub deleted_profiles: u64, // 删除的其他配置
+    pub deleted_secrets: u64,  // 钥匙串中删除的 API key
+    pub failed_secrets: u64,
+}
+
+// 先用 0 覆盖文件内容并写入磁盘，再删除文件
+// （SSD 和写时复制文件系统上不能保证覆盖到原来的数据块，只能尽量减少可恢复的内容）
+fn shred_file(path: &Path) -> std::io::Result<()> {
+    let len = std::fs::metadata(path)?.len();
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    let zeros = [0u8; SHRED_CHUNK_SIZE];
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk = remaining.min(SHRED_CHUNK_SIZE as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    std::fs::remove_file(path)
+}
+
+// 删除目录下的所有文件和子目录（保留目录本身），失败的文件记录到日志后继续
+fn shred_dir(dir: &Path, report: &mut WipeReport) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => {
+            log::warn!("Failed to read {}: {}", dir.display(), e);
+            report.failed_files += 1;
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let result = match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => {
+                shred_dir(&path, report);
+                std::fs::remove_dir(&path).map(|_| false)
+            }
+            // 符号链接只删除链接本身，不覆盖它指向的文件
+            Ok(file_type) if file_type.is_symlink() => std::fs::remove_file(&path).map(|_| false),
+            _ => shred_file(&path).map(|_| true),
+        };
+        match result {
+            Ok(true) => report.deleted_files += 1,
+            Ok(false) => {}
+            Err(e) => {
+                log::warn!("Failed to delete {}: {}", path.display(), e);
+                report.failed_files += 1;
+            }
+        }
+    }
+}
+
+// 删除文件，或目录和其中的所有内容，不存在时跳过
+fn shred_path(path: &Path, report: &mut WipeReport) {
+    let result = match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => {
+            shred_dir(path, report);
+            std::fs::remove_dir(path)
+        }
+        Ok(_) => shred_file(path).map(|_| report.deleted_files += 1),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        log::warn!("Failed to delete {}: {}", path.display(), e);
+        report.failed_files += 1;
+    }
+}
+
+// 清除全部数据：删除截图、缩略图、总结视频、数据库中的所有记录和设置、钥匙串中的 API key、日志文件，
+// 以及其他配置的数据库和截图，完成后应用回到首次运行的状态
+// （当前数据库的密码保留在钥匙串中，否则清空后的数据库无法打开；
+// 其他配置保存在钥匙串中的 API key 需要它们的数据库才能找到，不会删除）
+#[tauri::command]
+pub async fn wipe_all_data(
+    state: State<'_, AppState>,
+    confirm_token: String,
+) -> Result<WipeReport, ClarityError> {
+    if confirm_token != WIPE_CONFIRM_TOKEN {
+        return Err(ClarityError::InvalidInput(format!(
+            "Type \"{}\" to confirm",
+            WIPE_CONFIRM_TOKEN
+        )));
+    }
+    log::warn!("Wiping all data");
+
+    // 先停止所有会写入数据的后台任务：录制（等待已经截取的图片写完）、总结定时任务、
+    // 总结任务 worker（等待它退出，正在进行的总结不会再写入结果）和本地 REST API
+    stop_summary_loop(&state).await;
+    stop_summary_worker(&state).await;
+    if state.recording.stop() {
+        wait_for_screenshot_task(&state).await;
+    }
+    if let Some(server) = state.api_server.lock().await.take() {
+        server.stop().await;
+    }
+
+    // 钥匙串条目需要数据库中的 key ID，在删除数据库记录之前处理
+    let mut report = WipeReport::default();
+    let (deleted_secrets, failed_secrets) =
+        keychain::delete_all_gemini_secrets(&state.db_pool).await?;
+    report.deleted_secrets = deleted_secrets;
+    report.failed_secrets = failed_secrets;
+
+    // 截图目录（包括缩略图和总结视频）、还没有替换数据库的备份恢复文件和加密副本、恢复备份前的数据库副本、
+    // 日志文件和其他配置的数据
+    let storage_path = state.storage_path.lock().await.clone();
+    let encryption_pending = tokio::fs::metadata(db::encryption_staging_path())
+        .await
+        .is_ok();
+    let staging_paths = [
+        db::restore_staging_path(),
+        db::encryption_staging_path(),
+        db::pre_restore_path(),
+    ];
+    let other_profiles = profiles::other_profiles();
+    report.deleted_profiles = other_profiles.len() as u64;
+    let other_profile_paths: Vec<_> = other_profiles
+        .iter()
+        .flat_map(|name| profiles::profile_data_paths(name))
+        .collect();
+    let mut report = tokio::task::spawn_blocking(move || {
+        shred_dir(&storage_path, &mut report);
+        for path in &staging_paths {
+            shred_path(path, &mut report);
+        }
+        for path in &other_profile_paths {
+            shred_path(path, &mut report);
+        }
+        // 当前的日志文件关闭后才能在所有平台上删除，删除后的日志写入新的文件
+        logging::close_log_file();
+        shred_dir(&logging::log_dir(), &mut report);
+        logging::close_log_file();
+        report
+    })
+    .await
+    .map_err(|e| format!("Wipe task failed: {}", e))?;
+
+    report.deleted_rows = db::wipe_all_rows(&state.db_pool)
+        .await
+        .map_err(ClarityError::from)?;
+
+    // 加密副本包含清除前的数据，已经删除；当前数据库仍然没有加密，要同时删除密码，否则下次启动无法打开
+    if encryption_pending {
+        if let Err(e) = keychain::delete_profile_database_key(profiles::active_profile()).await {
+            log::warn!("Failed to delete database key: {}", e);
+            report.failed_secrets += 1;
+        }
+    }
+
+    // 其他配置的数据库已经删除，它们的数据库密码也不再需要
+    profiles::remove_other_profiles()?;
+    for name in &other_profiles {
+        if let Err(e) = keychain::delete_profile_database_key(name).await {
+            log::warn!("Failed to delete database key of profile '{}': {}", name, e);
+            report.failed_secrets += 1;
+        }
+    }
+
+    // 内存中的状态恢复为首次运行时的默认值
+    state.reload_settings().await;
+    *state.gemini_api_key.lock().await = None;
+    *state.screenshots_count.lock().await = 0;
+    *state.locked.lock().await = false;
+    *state.incognito.lock().await = false;
+    start_summary_loop(&state).await;
+    start_summary_worker(&state).await;
+
+    log::warn!(
+        "All data wiped: {} files, {} database rows, {} other profiles, {} keychain entries ({} files and {} keychain entries failed)",
+        report.deleted_files,
+        report.deleted_rows,
+        report.deleted_profiles,
+        report.deleted_secrets,
+        report.failed_files,
+        report.failed_secrets
+    );
+
+    let app_handle = state.app_handle.lock().await.clone();
+    events::emit(app_handle.as_ref(), events::DATA_WIPED, report.clone());
+
+    Ok(report)
+}
@@ -1,9 +1,14 @@
-use chrono::{DateTime, Local, NaiveDateTime};
+use crate::profiles;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
-use sqlx::Row;
-use std::path::PathBuf;
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow,
+    SqliteSynchronous,
+};
+use sqlx::{QueryBuilder, Row, Sqlite};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -14,6 +19,28 @@ pub struct ScreenshotTrace {
     pub width: i32,
     pub height: i32,
     pub file_size: i64,
+    pub thumbnail_path: Option<String>,
+    pub cursor_x: Option<i32>,
+    pub cursor_y: Option<i32>,
+    #[serde(default)]
+    pub private: bool, // 隐身模式下的截图，不会上传给 AI
+    #[serde(default)]
+    pub app_name: Option<String>, // 截图时的焦点应用
+}
+
+// 要插入的截图记录（没有 id）
+#[derive(Debug, Clone)]
+pub struct NewScreenshotTrace {
+    pub timestamp: DateTime<Local>,
+    pub file_path: String,
+    pub width: i32,
+    pub height: i32,
+    pub file_size: i64,
+    pub thumbnail_path: Option<String>,
+    pub cursor_x: Option<i32>,
+    pub cursor_y: Option<i32>,
+    pub private: bool,
+    pub app_name: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +52,12 @@ pub struct Summary {
     pub content: String,
     pub screenshot_count: i32,
     pub created_at: DateTime<Local>,
+    pub structured_data: Option<serde_json::Value>, // 模型返回的结构化字段（应用、分类、专注度等）
+    pub focus_score: Option<i64>,                   // 0-100，模型给出或根据分类和输入活动估算
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub private: bool, // 隐身模式的时间段，内容是“私人时间”而不是 AI 生成的摘要
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,10 +71,30 @@ pub struct DailySummary {
     pub total_duration_seconds: i64,
     pub created_at: DateTime<Local>,
     pub updated_at: DateTime<Local>,
+    #[serde(default)]
+    pub stale: bool, // 生成后当天的摘要被修改或删除过，需要重新生成
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlySummary {
+    pub id: i64,
+    pub month: String, // YYYY-MM format
+    pub content: String,
+    pub summary_count: i32,
+    pub total_duration_seconds: i64,
+    pub trends: Option<serde_json::Value>, // 计算得出的趋势（分类、最忙的日期等）
+    pub created_at: DateTime<Local>,
+    pub updated_at: DateTime<Local>,
 }
 
-// 获取数据库路径
+// 获取当前配置的数据库路径
 fn get_db_path() -> PathBuf {
+    profiles::profile_path(default_db_path())
+}
+
+// 获取默认配置的数据库路径
+pub fn default_db_path() -> PathBuf {
     let app_name = "clarity";
 
     #[cfg(target_os = "windows")]
@@ -98,8 +151,321 @@ fn get_db_path() -> PathBuf {
     }
 }
 
+// 从备份恢复的数据库先写到这里，下次启动时替换当前数据库
+pub fn restore_staging_path() -> PathBuf {
+    get_db_path().with_extension("db.restore")
+}
+
+// 加密后的数据库副本先写到这里，下次启动时替换当前数据库
+pub fn encryption_staging_path() -> PathBuf {
+    get_db_path().with_extension("db.encrypt")
+}
+
+// 恢复备份前当前数据库的副本（只保留最近一次）
+pub fn pre_restore_path() -> PathBuf {
+    get_db_path().with_extension("db.pre-restore")
+}
+
+// 用 staging_path 替换数据库文件，没有待替换的文件时返回 false
+async fn replace_database_file(db_path: &Path, staging_path: &Path) -> Result<bool, sqlx::Error> {
+    if tokio::fs::metadata(staging_path).await.is_err() {
+        return Ok(false);
+    }
+
+    // 旧数据库的 WAL 文件不能留给替换后的数据库
+    for suffix in ["-wal", "-shm"] {
+        let mut path = db_path.as_os_str().to_owned();
+        path.push(suffix);
+        let _ = tokio::fs::remove_file(PathBuf::from(path)).await;
+    }
+    tokio::fs::rename(staging_path, db_path).await?;
+    Ok(true)
+}
+
+// 如果有待加密或待恢复的数据库，替换当前数据库文件（连接数据库之前调用）
+// 加密后又恢复备份时，恢复的数据库已经使用新的密码加密，因此最后替换
+async fn apply_pending_restore(db_path: &Path) -> Result<(), sqlx::Error> {
+    if replace_database_file(db_path, &encryption_staging_path()).await? {
+        log::info!(
+            "Replaced database with encrypted copy: {}",
+            db_path.display()
+        );
+    }
+    if replace_database_file(db_path, &restore_staging_path()).await? {
+        log::info!("Restored database from backup: {}", db_path.display());
+    }
+    Ok(())
+}
+
+// 文件是否为未加密的 SQLite 数据库（SQLCipher 加密的数据库没有明文文件头）
+pub fn is_plaintext_database(path: &Path) -> std::io::Result<bool> {
+    const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+    let mut header = [0u8; 16];
+    let mut file = std::fs::File::open(path)?;
+    match std::io::Read::read_exact(&mut file, &mut header) {
+        Ok(()) => Ok(&header == SQLITE_HEADER),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// 打开不是当前数据库的数据库文件（检查和转换备份中的数据库），只使用一个连接
+// key 不正确时读取 sqlite_master 会失败
+pub async fn open_database_file(path: &Path, key: Option<&str>) -> Result<SqlitePool, sqlx::Error> {
+    let mut connect_options =
+        SqliteConnectOptions::from_str(&format!("sqlite://{}", path.display()))?
+            .create_if_missing(false);
+    if let Some(key) = key {
+        connect_options = connect_options.pragma("key", sql_string_literal(key));
+    }
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(connect_options)
+        .await?;
+    if let Err(e) = sqlx::query("SELECT COUNT(*) FROM sqlite_master")
+        .execute(&pool)
+        .await
+    {
+        pool.close().await;
+        return Err(e);
+    }
+    Ok(pool)
+}
+
+// 把数据库的一致性快照写入 dest（先把 WAL 合并到主文件）
+pub async fn backup_database(pool: &SqlitePool, dest: &Path) -> Result<(), sqlx::Error> {
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(pool)
+        .await?;
+    sqlx::query("VACUUM INTO ?")
+        .bind(dest.to_string_lossy().to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 数据库文件（包括 WAL 文件）占用的磁盘空间
+pub async fn database_file_size() -> u64 {
+    let db_path = get_db_path();
+    let mut size = 0;
+    for suffix in ["", "-wal"] {
+        let mut path = db_path.as_os_str().to_owned();
+        path.push(suffix);
+        if let Ok(metadata) = tokio::fs::metadata(PathBuf::from(path)).await {
+            size += metadata.len();
+        }
+    }
+    size
+}
+
+// 把 WAL 合并到主文件并截断 WAL 文件
+pub async fn checkpoint_wal(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 重建数据库文件，释放删除数据后留下的空闲页
+pub async fn vacuum_database(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query("VACUUM").execute(pool).await?;
+    Ok(())
+}
+
+// 删除所有表中的数据（保留表结构和迁移记录），然后重建数据库文件并截断 WAL，
+// 删除的内容不会留在空闲页或 WAL 文件中，返回删除的行数
+pub async fn wipe_all_rows(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let tables: Vec<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name != 'schema_version' \
+         AND (name NOT LIKE 'sqlite_%' OR name = 'sqlite_sequence')",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tx = pool.begin().await?;
+    // 外键在提交时才检查，删除顺序不受表之间引用关系的影响
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+    let mut deleted = 0;
+    for (table,) in &tables {
+        // 表名来自 sqlite_master，不是用户输入
+        deleted += sqlx::query(&format!("DELETE FROM \"{}\"", table))
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+    tx.commit().await?;
+
+    sqlx::query("VACUUM").execute(pool).await?;
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(pool)
+        .await?;
+    Ok(deleted)
+}
+
+// 更新查询优化器使用的统计信息
+pub async fn analyze_database(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query("ANALYZE").execute(pool).await?;
+    Ok(())
+}
+
+// 完整性检查时抽样解析的时间列
+const TIMESTAMP_COLUMNS: [(&str, &str); 8] = [
+    ("screenshot_traces", "timestamp"),
+    ("summaries", "start_time"),
+    ("summaries", "end_time"),
+    ("summaries", "created_at"),
+    ("api_requests", "timestamp"),
+    ("videos", "created_at"),
+    ("summary_jobs", "created_at"),
+    ("recording_gaps", "start_time"),
+];
+
+// 每个时间列抽样检查的行数
+const TIMESTAMP_SAMPLE_SIZE: i64 = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidTimestamp {
+    pub table: String,
+    pub column: String,
+    pub row_id: i64,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub ok: bool,
+    pub schema_version: i64,
+    pub integrity_errors: Vec<String>, // PRAGMA integrity_check 的结果（正常时为空）
+    pub missing_tables: Vec<String>,
+    pub missing_indexes: Vec<String>,
+    pub checked_timestamps: u64,
+    pub invalid_timestamps: Vec<InvalidTimestamp>,
+}
+
+// 迁移中创建的表和索引名称
+fn expected_schema_objects(keyword: &str) -> Vec<String> {
+    let prefix = format!("CREATE {} IF NOT EXISTS ", keyword);
+    MIGRATIONS
+        .iter()
+        .flat_map(|m| m.statements.iter())
+        .filter_map(|statement| {
+            let rest = &statement[statement.find(&prefix)? + prefix.len()..];
+            rest.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .next()
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+// 检查数据库文件完整性、迁移创建的表和索引是否存在，并抽样检查时间能否解析
+pub async fn check_integrity(pool: &SqlitePool) -> Result<IntegrityReport, sqlx::Error> {
+    let integrity: Vec<(String,)> = sqlx::query_as("PRAGMA integrity_check(100)")
+        .fetch_all(pool)
+        .await?;
+    let integrity_errors: Vec<String> = integrity
+        .into_iter()
+        .map(|(message,)| message)
+        .filter(|message| message != "ok")
+        .collect();
+
+    let existing: Vec<(String, String)> =
+        sqlx::query_as("SELECT type, name FROM sqlite_master WHERE type IN ('table', 'index')")
+            .fetch_all(pool)
+            .await?;
+    let exists = |kind: &str, name: &str| existing.iter().any(|(t, n)| t == kind && n == name);
+    let missing_tables: Vec<String> = expected_schema_objects("TABLE")
+        .into_iter()
+        .chain(std::iter::once("schema_version".to_string()))
+        .filter(|name| !exists("table", name))
+        .collect();
+    let missing_indexes: Vec<String> = expected_schema_objects("INDEX")
+        .into_iter()
+        .filter(|name| !exists("index", name))
+        .collect();
+
+    let (schema_version,): (i64,) =
+        sqlx::query_as("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+            .fetch_one(pool)
+            .await
+            .unwrap_or((0,));
+
+    let mut checked_timestamps = 0;
+    let mut invalid_timestamps = Vec::new();
+    for (table, column) in TIMESTAMP_COLUMNS {
+        if !exists("table", table) {
+            continue;
+        }
+        // 表名和列名来自上面的常量，不是用户输入
+        let rows: Vec<(i64, String)> = sqlx::query_as(&format!(
+            "SELECT rowid, {column} FROM {table} WHERE {column} IS NOT NULL ORDER BY RANDOM() LIMIT ?"
+        ))
+        .bind(TIMESTAMP_SAMPLE_SIZE)
+        .fetch_all(pool)
+        .await?;
+        for (row_id, value) in rows {
+            checked_timestamps += 1;
+            if parse_timestamp(&value).is_err() {
+                invalid_timestamps.push(InvalidTimestamp {
+                    table: table.to_string(),
+                    column: column.to_string(),
+                    row_id,
+                    value,
+                });
+            }
+        }
+    }
+
+    Ok(IntegrityReport {
+        ok: integrity_errors.is_empty()
+            && missing_tables.is_empty()
+            && missing_indexes.is_empty()
+            && invalid_timestamps.is_empty(),
+        schema_version,
+        integrity_errors,
+        missing_tables,
+        missing_indexes,
+        checked_timestamps,
+        invalid_timestamps,
+    })
+}
+
+// 把字符串转换为 SQL 字符串字面量（用于不能绑定参数的 PRAGMA）
+fn sql_string_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+// 使用 SQLCipher 把当前数据库导出为加密副本（需要以 sqlcipher feature 构建）
+pub async fn export_encrypted_copy(
+    pool: &SqlitePool,
+    dest: &Path,
+    key: &str,
+) -> Result<(), sqlx::Error> {
+    // ATTACH 和导出必须在同一个连接上执行
+    let mut conn = pool.acquire().await?;
+    sqlx::query("ATTACH DATABASE ? AS encrypted KEY ?")
+        .bind(dest.to_string_lossy().to_string())
+        .bind(key)
+        .execute(&mut *conn)
+        .await?;
+    let result = sqlx::query("SELECT sqlcipher_export('encrypted')")
+        .execute(&mut *conn)
+        .await;
+    sqlx::query("DETACH DATABASE encrypted")
+        .execute(&mut *conn)
+        .await?;
+    result.map(|_| ())
+}
+
+// 等待数据库锁的最长时间
+const DB_BUSY_TIMEOUT_SECONDS: u64 = 10;
+
 // 初始化数据库连接
-pub async fn init_db() -> Result<SqlitePool, sqlx::Error> {
+// database_key: 使用 SQLCipher 加密的数据库的密码，未加密时为 None
+pub async fn init_db(database_key: Option<&str>) -> Result<SqlitePool, sqlx::Error> {
     let db_path = get_db_path();
 
     // 确保目录存在
@@ -107,10 +473,22 @@ pub async fn init_db() -> Result<SqlitePool, sqlx::Error> {
         tokio::fs::create_dir_all(parent).await?;
     }
 
+    // 上次恢复备份后需要替换数据库文件
+    apply_pending_restore(&db_path).await?;
+
     // 构建连接选项
-    let connect_options =
+    // 录制时每秒写入截图，同时还有总结写入和界面读取，使用 WAL 避免读写互相阻塞
+    // 遇到锁时最多等待 DB_BUSY_TIMEOUT_SECONDS 秒，而不是立即返回 "database is locked"
+    let mut connect_options =
         SqliteConnectOptions::from_str(&format!("sqlite://{}", db_path.display()))?
-            .create_if_missing(true);
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(Duration::from_secs(DB_BUSY_TIMEOUT_SECONDS));
+    // sqlx 会把 key 作为第一条 PRAGMA 执行，满足 SQLCipher 的要求
+    if let Some(key) = database_key {
+        connect_options = connect_options.pragma("key", sql_string_literal(key));
+    }
 
     // 创建连接池
     let pool = SqlitePoolOptions::new()
@@ -118,118 +496,524 @@ pub async fn init_db() -> Result<SqlitePool, sqlx::Error> {
         .connect_with(connect_options)
         .await?;
 
-    // 创建表
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS screenshot_traces (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp TEXT NOT NULL,
-            file_path TEXT NOT NULL,
-            width INTEGER NOT NULL,
-            height INTEGER NOT NULL,
-            file_size INTEGER NOT NULL,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )
-        "#,
-    )
-    .execute(&pool)
-    .await?;
+    // 创建或升级表结构
+    run_migrations(&pool).await?;
 
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS summaries (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            start_time TEXT NOT NULL,
-            end_time TEXT NOT NULL,
-            content TEXT NOT NULL,
-            screenshot_count INTEGER NOT NULL DEFAULT 0,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )
-        "#,
-    )
-    .execute(&pool)
-    .await?;
+    Ok(pool)
+}
 
-    // 创建索引以提高查询性能
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_traces_timestamp ON screenshot_traces(timestamp)")
-        .execute(&pool)
-        .await?;
+// 初始化只保存在内存中的数据库（临时模式和测试使用），连接池关闭后数据全部丢失
+// 每个内存数据库连接都是一个独立的数据库，因此只保留一个永不过期的连接
+pub async fn init_memory_db() -> Result<SqlitePool, sqlx::Error> {
+    let connect_options = SqliteConnectOptions::from_str("sqlite::memory:")?
+        .busy_timeout(Duration::from_secs(DB_BUSY_TIMEOUT_SECONDS));
 
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_summaries_start_time ON summaries(start_time)")
-        .execute(&pool)
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(connect_options)
         .await?;
 
-    // 创建 API 请求记录表
+    run_migrations(&pool).await?;
+
+    Ok(pool)
+}
+
+// 数据库结构迁移：按版本号顺序执行，已执行的版本记录在 schema_version 表中
+// 修改表结构时在 MIGRATIONS 末尾追加新版本，不要修改已发布的迁移
+struct Migration {
+    version: i64,
+    description: &'static str,
+    statements: &'static [&'static str],
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS screenshot_traces (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                width INTEGER NOT NULL,
+                height INTEGER NOT NULL,
+                file_size INTEGER NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                thumbnail_path TEXT,
+                cursor_x INTEGER,
+                cursor_y INTEGER
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS summaries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                start_time TEXT NOT NULL,
+                end_time TEXT NOT NULL,
+                content TEXT NOT NULL,
+                screenshot_count INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                structured_data TEXT,
+                focus_score INTEGER
+            )
+            "#,
+            // 旧版本只在结构化数据中保存了专注度，补充到 focus_score 列
+            "UPDATE summaries SET focus_score = json_extract(structured_data, '$.focusScore') WHERE focus_score IS NULL AND json_valid(structured_data)",
+            "CREATE INDEX IF NOT EXISTS idx_traces_timestamp ON screenshot_traces(timestamp)",
+            "CREATE INDEX IF NOT EXISTS idx_summaries_start_time ON summaries(start_time)",
+            // 摘要分类表（一条摘要可以属于多个分类）
+            r#"
+            CREATE TABLE IF NOT EXISTS summary_categories (
+                summary_id INTEGER NOT NULL,
+                category TEXT NOT NULL,
+                PRIMARY KEY (summary_id, category)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_summary_categories_category ON summary_categories(category)",
+            // API 请求记录表，api_key_id 记录使用的 Gemini key，fallback_from_model 记录改用备用模型前的主模型
+            r#"
+            CREATE TABLE IF NOT EXISTS api_requests (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                model TEXT NOT NULL,
+                endpoint TEXT NOT NULL,
+                prompt_tokens INTEGER,
+                completion_tokens INTEGER,
+                total_tokens INTEGER,
+                cost_usd REAL,
+                status_code INTEGER,
+                success INTEGER NOT NULL DEFAULT 1,
+                error_message TEXT,
+                request_duration_ms INTEGER,
+                api_key_id INTEGER,
+                fallback_from_model TEXT
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_api_requests_timestamp ON api_requests(timestamp)",
+            r#"
+            CREATE TABLE IF NOT EXISTS daily_summaries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                date TEXT NOT NULL UNIQUE,
+                content TEXT NOT NULL,
+                screenshot_count INTEGER NOT NULL DEFAULT 0,
+                summary_count INTEGER NOT NULL DEFAULT 0,
+                total_duration_seconds INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_daily_summaries_date ON daily_summaries(date)",
+            r#"
+            CREATE TABLE IF NOT EXISTS monthly_summaries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                month TEXT NOT NULL UNIQUE,
+                content TEXT NOT NULL,
+                summary_count INTEGER NOT NULL DEFAULT 0,
+                total_duration_seconds INTEGER NOT NULL DEFAULT 0,
+                trends TEXT,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            // 总结视频表（记录视频文件与摘要的关联，便于审计清理）
+            r#"
+            CREATE TABLE IF NOT EXISTS videos (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                file_path TEXT NOT NULL,
+                summary_id INTEGER,
+                file_size INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                deleted_at TEXT,
+                start_time TEXT,
+                end_time TEXT,
+                screenshot_count INTEGER NOT NULL DEFAULT 0,
+                retry_count INTEGER NOT NULL DEFAULT 0,
+                duration_seconds REAL NOT NULL DEFAULT 0
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_videos_summary_id ON videos(summary_id)",
+            "CREATE INDEX IF NOT EXISTS idx_videos_created_at ON videos(created_at)",
+            // Gemini API key 列表（按 priority 升序使用，遇到配额错误时换下一个）
+            r#"
+            CREATE TABLE IF NOT EXISTS gemini_api_keys (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                api_key TEXT NOT NULL UNIQUE,
+                label TEXT,
+                priority INTEGER NOT NULL DEFAULT 0,
+                last_quota_error_at TEXT,
+                created_at TEXT NOT NULL
+            )
+            "#,
+            // 总结任务队列表（定时器只负责入队，由后台 worker 按并发和频率限制处理）
+            r#"
+            CREATE TABLE IF NOT EXISTS summary_jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                start_time TEXT NOT NULL,
+                end_time TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                error_message TEXT,
+                summary_id INTEGER,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_summary_jobs_status ON summary_jobs(status)",
+            // 目标表和每日评估结果表
+            r#"
+            CREATE TABLE IF NOT EXISTS goals (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                label TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                category TEXT,
+                comparison TEXT NOT NULL,
+                target_minutes INTEGER NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS goal_results (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                goal_id INTEGER NOT NULL,
+                date TEXT NOT NULL,
+                actual_minutes INTEGER NOT NULL,
+                achieved INTEGER NOT NULL,
+                evaluated_at TEXT NOT NULL,
+                UNIQUE(goal_id, date)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_goal_results_date ON goal_results(date)",
+            // 模型对比结果表（对比模式下同一条摘要由两个模型各生成一次）
+            r#"
+            CREATE TABLE IF NOT EXISTS model_comparisons (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                summary_id INTEGER NOT NULL,
+                model TEXT NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                structured_data TEXT,
+                created_at TEXT NOT NULL
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_model_comparisons_summary_id ON model_comparisons(summary_id)",
+            // 休息提醒记录表（记录提醒后是否休息，用于统计执行情况）
+            r#"
+            CREATE TABLE IF NOT EXISTS break_reminders (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_start TEXT NOT NULL,
+                reminded_at TEXT NOT NULL,
+                break_started_at TEXT
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_break_reminders_reminded_at ON break_reminders(reminded_at)",
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "store timestamps in utc",
+        // 旧版本按本地时区保存 RFC3339 时间，夏令时切换或更换时区后按字符串比较会出错
+        // SQLite 的 strftime 会按时间中的时区偏移转换为 UTC
+        statements: &[
+            "UPDATE screenshot_traces SET timestamp = strftime('%Y-%m-%dT%H:%M:%fZ', timestamp) WHERE timestamp LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', timestamp) IS NOT NULL",
+            "UPDATE summaries SET start_time = strftime('%Y-%m-%dT%H:%M:%fZ', start_time) WHERE start_time LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', start_time) IS NOT NULL",
+            "UPDATE summaries SET end_time = strftime('%Y-%m-%dT%H:%M:%fZ', end_time) WHERE end_time LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', end_time) IS NOT NULL",
+            "UPDATE api_requests SET timestamp = strftime('%Y-%m-%dT%H:%M:%fZ', timestamp) WHERE timestamp LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', timestamp) IS NOT NULL",
+            "UPDATE videos SET created_at = strftime('%Y-%m-%dT%H:%M:%fZ', created_at) WHERE created_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', created_at) IS NOT NULL",
+            "UPDATE videos SET deleted_at = strftime('%Y-%m-%dT%H:%M:%fZ', deleted_at) WHERE deleted_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', deleted_at) IS NOT NULL",
+            "UPDATE videos SET start_time = strftime('%Y-%m-%dT%H:%M:%fZ', start_time) WHERE start_time LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', start_time) IS NOT NULL",
+            "UPDATE videos SET end_time = strftime('%Y-%m-%dT%H:%M:%fZ', end_time) WHERE end_time LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', end_time) IS NOT NULL",
+            "UPDATE gemini_api_keys SET last_quota_error_at = strftime('%Y-%m-%dT%H:%M:%fZ', last_quota_error_at) WHERE last_quota_error_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', last_quota_error_at) IS NOT NULL",
+            "UPDATE gemini_api_keys SET created_at = strftime('%Y-%m-%dT%H:%M:%fZ', created_at) WHERE created_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', created_at) IS NOT NULL",
+            "UPDATE summary_jobs SET start_time = strftime('%Y-%m-%dT%H:%M:%fZ', start_time) WHERE start_time LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', start_time) IS NOT NULL",
+            "UPDATE summary_jobs SET end_time = strftime('%Y-%m-%dT%H:%M:%fZ', end_time) WHERE end_time LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', end_time) IS NOT NULL",
+            "UPDATE summary_jobs SET created_at = strftime('%Y-%m-%dT%H:%M:%fZ', created_at) WHERE created_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', created_at) IS NOT NULL",
+            "UPDATE summary_jobs SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', updated_at) WHERE updated_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', updated_at) IS NOT NULL",
+            "UPDATE goals SET created_at = strftime('%Y-%m-%dT%H:%M:%fZ', created_at) WHERE created_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', created_at) IS NOT NULL",
+            "UPDATE goal_results SET evaluated_at = strftime('%Y-%m-%dT%H:%M:%fZ', evaluated_at) WHERE evaluated_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', evaluated_at) IS NOT NULL",
+            "UPDATE model_comparisons SET created_at = strftime('%Y-%m-%dT%H:%M:%fZ', created_at) WHERE created_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', created_at) IS NOT NULL",
+            "UPDATE break_reminders SET session_start = strftime('%Y-%m-%dT%H:%M:%fZ', session_start) WHERE session_start LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', session_start) IS NOT NULL",
+            "UPDATE break_reminders SET reminded_at = strftime('%Y-%m-%dT%H:%M:%fZ', reminded_at) WHERE reminded_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', reminded_at) IS NOT NULL",
+            "UPDATE break_reminders SET break_started_at = strftime('%Y-%m-%dT%H:%M:%fZ', break_started_at) WHERE break_started_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', break_started_at) IS NOT NULL",
+            "UPDATE schema_version SET applied_at = strftime('%Y-%m-%dT%H:%M:%fZ', applied_at) WHERE applied_at LIKE '____-__-__T%' AND strftime('%Y-%m-%dT%H:%M:%fZ', applied_at) IS NOT NULL",
+        ],
+    },
+    Migration {
+        version: 3,
+        description: "summary tags",
+        statements: &[
+            // 标签表，keywords 为 JSON 数组，与摘要结构化数据中的应用或分类匹配时自动添加标签
+            r#"
+            CREATE TABLE IF NOT EXISTS tags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                keywords TEXT NOT NULL DEFAULT '[]',
+                created_at TEXT NOT NULL
+            )
+            "#,
+            // 摘要和标签的多对多关系，auto 表示是否为自动添加
+            r#"
+            CREATE TABLE IF NOT EXISTS summary_tags (
+                summary_id INTEGER NOT NULL,
+                tag_id INTEGER NOT NULL,
+                auto INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (summary_id, tag_id)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_summary_tags_tag_id ON summary_tags(tag_id)",
+        ],
+    },
+    Migration {
+        version: 4,
+        description: "daily summary stale flag",
+        statements: &[
+            "ALTER TABLE daily_summaries ADD COLUMN stale INTEGER NOT NULL DEFAULT 0",
+        ],
+    },
+    Migration {
+        version: 5,
+        description: "app usage",
+        statements: &[
+            // 每个应用每天（本地日期 YYYY-MM-DD）获得焦点的秒数，由截图循环更新
+            r#"
+            CREATE TABLE IF NOT EXISTS app_usage (
+                app TEXT NOT NULL,
+                date TEXT NOT NULL,
+                seconds INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (app, date)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_app_usage_date ON app_usage(date)",
+        ],
+    },
+    Migration {
+        version: 6,
+        description: "recording gaps",
+        statements: &[
+            // 录制期间程序异常退出（崩溃、断电等）造成的空白时间段，启动恢复录制时写入
+            r#"
+            CREATE TABLE IF NOT EXISTS recording_gaps (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                start_time TEXT NOT NULL,
+                end_time TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_recording_gaps_start_time ON recording_gaps(start_time)",
+        ],
+    },
+    Migration {
+        version: 7,
+        description: "webhooks",
+        statements: &[
+            // 摘要或每日总结保存后推送到的地址，events 为订阅的事件（JSON 数组）
+            r#"
+            CREATE TABLE IF NOT EXISTS webhooks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                url TEXT NOT NULL,
+                secret TEXT NOT NULL,
+                events TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL,
+                last_delivery_at TEXT,
+                last_status INTEGER,
+                last_error TEXT
+            )
+            "#,
+        ],
+    },
+    Migration {
+        version: 8,
+        description: "summary hooks",
+        statements: &[
+            // 摘要或每日总结保存后运行的外部命令，args / events 为 JSON 数组
+            r#"
+            CREATE TABLE IF NOT EXISTS summary_hooks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                command TEXT NOT NULL,
+                args TEXT NOT NULL,
+                events TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL,
+                last_run_at TEXT,
+                last_exit_code INTEGER,
+                last_error TEXT
+            )
+            "#,
+        ],
+    },
+    Migration {
+        version: 9,
+        description: "incognito mode",
+        statements: &[
+            // 隐身模式下截取的截图只保存在本地，总结时不会上传
+            "ALTER TABLE screenshot_traces ADD COLUMN private INTEGER NOT NULL DEFAULT 0",
+            // 只包含隐身模式截图的摘要，不调用 AI
+            "ALTER TABLE summaries ADD COLUMN private INTEGER NOT NULL DEFAULT 0",
+        ],
+    },
+    Migration {
+        version: 10,
+        description: "screenshot app name",
+        statements: &[
+            // 截图时的焦点应用，生成总结时按应用的隐私规则决定是否上传
+            "ALTER TABLE screenshot_traces ADD COLUMN app_name TEXT",
+        ],
+    },
+    Migration {
+        version: 11,
+        description: "onboarding consent",
+        statements: &[
+            // 已经在使用的安装（有截图）之前一直在截图，记为已同意截图，引导流程从上传给 AI 的步骤开始；
+            // 上传需要用户自己同意，不在迁移中记录。新安装没有截图，需要完成全部引导
+            r#"
+            INSERT OR IGNORE INTO settings (key, value)
+            SELECT 'screen_capture_consent', json_object('granted', json('true'), 'recordedAt', strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+            WHERE EXISTS (SELECT 1 FROM screenshot_traces)
+            "#,
+            r#"
+            INSERT OR IGNORE INTO settings (key, value)
+            SELECT 'onboarding_step', 'ai_upload'
+            WHERE EXISTS (SELECT 1 FROM screenshot_traces)
+            "#,
+        ],
+    },
+];
+
+// 执行尚未执行的迁移，每个版本在一个事务中完成
+async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS api_requests (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp TEXT NOT NULL,
-            model TEXT NOT NULL,
-            endpoint TEXT NOT NULL,
-            prompt_tokens INTEGER,
-            completion_tokens INTEGER,
-            total_tokens INTEGER,
-            cost_usd REAL,
-            status_code INTEGER,
-            success INTEGER NOT NULL DEFAULT 1,
-            error_message TEXT,
-            request_duration_ms INTEGER
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL
         )
         "#,
     )
-    .execute(&pool)
+    .execute(pool)
     .await?;
 
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_api_requests_timestamp ON api_requests(timestamp)")
-        .execute(&pool)
+    let (current,): (i64,) = sqlx::query_as("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+        .fetch_one(pool)
         .await?;
 
-    // 创建每日总结表
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS daily_summaries (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            date TEXT NOT NULL UNIQUE,
-            content TEXT NOT NULL,
-            screenshot_count INTEGER NOT NULL DEFAULT 0,
-            summary_count INTEGER NOT NULL DEFAULT 0,
-            total_duration_seconds INTEGER NOT NULL DEFAULT 0,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    // 引入迁移之前的数据库结构不固定，先补齐缺少的列，再执行初始迁移
+    if current == 0 {
+        upgrade_legacy_schema(pool).await?;
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let mut tx = pool.begin().await?;
+        for statement in migration.statements {
+            sqlx::query(statement).execute(&mut *tx).await?;
+        }
+        sqlx::query(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?, ?, ?)",
         )
-        "#,
+        .bind(migration.version)
+        .bind(migration.description)
+        .bind(to_db_time(&Local::now()))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        log::info!(
+            "Applied database migration {}: {}",
+            migration.version,
+            migration.description
+        );
+    }
+
+    Ok(())
+}
+
+// 旧版本数据库的表可能缺少后来添加的列，需要补充（表不存在时由初始迁移创建）
+async fn upgrade_legacy_schema(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    ensure_column(pool, "screenshot_traces", "thumbnail_path", "TEXT").await?;
+    ensure_column(pool, "screenshot_traces", "cursor_x", "INTEGER").await?;
+    ensure_column(pool, "screenshot_traces", "cursor_y", "INTEGER").await?;
+    ensure_column(pool, "summaries", "structured_data", "TEXT").await?;
+    ensure_column(pool, "summaries", "focus_score", "INTEGER").await?;
+    ensure_column(pool, "api_requests", "api_key_id", "INTEGER").await?;
+    ensure_column(pool, "api_requests", "fallback_from_model", "TEXT").await?;
+    ensure_column(pool, "videos", "start_time", "TEXT").await?;
+    ensure_column(pool, "videos", "end_time", "TEXT").await?;
+    ensure_column(
+        pool,
+        "videos",
+        "screenshot_count",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+    .await?;
+    ensure_column(pool, "videos", "retry_count", "INTEGER NOT NULL DEFAULT 0").await?;
+    ensure_column(
+        pool,
+        "videos",
+        "duration_seconds",
+        "REAL NOT NULL DEFAULT 0",
     )
-    .execute(&pool)
     .await?;
+    Ok(())
+}
 
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_daily_summaries_date ON daily_summaries(date)")
-        .execute(&pool)
+// 如果表中缺少某列则添加（用于兼容旧版本数据库，表不存在时跳过）
+async fn ensure_column(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    let rows = sqlx::query(&format!("PRAGMA table_info({})", table))
+        .fetch_all(pool)
         .await?;
 
-    Ok(pool)
+    let exists = rows.iter().any(|row| row.get::<String, _>(1) == column);
+
+    if !rows.is_empty() && !exists {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
 }
 
 // 插入截图记录
 pub async fn insert_screenshot_trace(
     pool: &SqlitePool,
-    timestamp: DateTime<Local>,
-    file_path: String,
-    width: i32,
-    height: i32,
-    file_size: i64,
+    trace: NewScreenshotTrace,
 ) -> Result<i64, sqlx::Error> {
     let id = sqlx::query(
         r#"
-        INSERT INTO screenshot_traces (timestamp, file_path, width, height, file_size)
-        VALUES (?, ?, ?, ?, ?)
+        INSERT INTO screenshot_traces (timestamp, file_path, width, height, file_size, thumbnail_path, cursor_x, cursor_y, private, app_name)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
-    .bind(timestamp.to_rfc3339())
-    .bind(file_path)
-    .bind(width)
-    .bind(height)
-    .bind(file_size)
+    .bind(to_db_time(&trace.timestamp))
+    .bind(trace.file_path)
+    .bind(trace.width)
+    .bind(trace.height)
+    .bind(trace.file_size)
+    .bind(trace.thumbnail_path)
+    .bind(trace.cursor_x)
+    .bind(trace.cursor_y)
+    .bind(trace.private)
+    .bind(trace.app_name)
     .execute(pool)
     .await?
     .last_insert_rowid();
@@ -237,6 +1021,29 @@ pub async fn insert_screenshot_trace(
     Ok(id)
 }
 
+const TRACE_COLUMNS: &str = "id, timestamp, file_path, width, height, file_size, thumbnail_path, cursor_x, cursor_y, private, app_name";
+
+fn trace_from_row(row: &SqliteRow) -> Result<ScreenshotTrace, sqlx::Error> {
+    let timestamp_str: String = row.get(1);
+    let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
+        .map_err(|_| sqlx::Error::Decode("Invalid timestamp format".into()))?
+        .with_timezone(&Local);
+
+    Ok(ScreenshotTrace {
+        id: row.get(0),
+        timestamp,
+        file_path: row.get(2),
+        width: row.get(3),
+        height: row.get(4),
+        file_size: row.get(5),
+        thumbnail_path: row.get(6),
+        cursor_x: row.get(7),
+        cursor_y: row.get(8),
+        private: row.get::<i64, _>(9) != 0,
+        app_name: row.get(10),
+    })
+}
+
 // 查询截图记录（按时间范围）
 pub async fn get_screenshot_traces(
     pool: &SqlitePool,
@@ -244,47 +1051,143 @@ pub async fn get_screenshot_traces(
     end_time: Option<DateTime<Local>>,
     limit: Option<i64>,
 ) -> Result<Vec<ScreenshotTrace>, sqlx::Error> {
-    let mut query = String::from("SELECT id, timestamp, file_path, width, height, file_size FROM screenshot_traces WHERE 1=1");
-    let mut conditions = Vec::new();
+    let mut query = QueryBuilder::<Sqlite>::new(format!(
+        "SELECT {} FROM screenshot_traces WHERE 1=1",
+        TRACE_COLUMNS
+    ));
 
     if let Some(start) = start_time {
-        conditions.push(format!("timestamp >= '{}'", start.to_rfc3339()));
+        query
+            .push(" AND timestamp >= ")
+            .push_bind(to_db_time(&start));
     }
     if let Some(end) = end_time {
-        conditions.push(format!("timestamp <= '{}'", end.to_rfc3339()));
+        query.push(" AND timestamp <= ").push_bind(to_db_time(&end));
     }
 
-    if !conditions.is_empty() {
-        query.push_str(" AND ");
-        query.push_str(&conditions.join(" AND "));
+    query.push(" ORDER BY timestamp DESC");
+
+    if let Some(limit_val) = limit {
+        query.push(" LIMIT ").push_bind(limit_val);
     }
 
-    query.push_str(" ORDER BY timestamp DESC");
+    let rows = query.build().fetch_all(pool).await?;
 
-    if let Some(limit_val) = limit {
-        query.push_str(&format!(" LIMIT {}", limit_val));
+    rows.iter().map(trace_from_row).collect()
+}
+
+// 统计时间范围内的截图数量（只需要数量时不读取整行）
+pub async fn get_screenshot_count(
+    pool: &SqlitePool,
+    start_time: Option<DateTime<Local>>,
+    end_time: Option<DateTime<Local>>,
+) -> Result<i64, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM screenshot_traces WHERE 1=1");
+
+    if let Some(start) = start_time {
+        query
+            .push(" AND timestamp >= ")
+            .push_bind(to_db_time(&start));
+    }
+    if let Some(end) = end_time {
+        query.push(" AND timestamp <= ").push_bind(to_db_time(&end));
     }
 
-    let rows = sqlx::query(&query).fetch_all(pool).await?;
+    let (count,): (i64,) = query.build_query_as().fetch_one(pool).await?;
+    Ok(count)
+}
 
-    let mut traces = Vec::new();
-    for row in rows {
-        let timestamp_str: String = row.get(1);
-        let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
-            .map_err(|_| sqlx::Error::Decode("Invalid timestamp format".into()))?
-            .with_timezone(&Local);
+// 是否已有相同时间的截图记录（导入时去重）
+pub async fn screenshot_trace_exists_at(
+    pool: &SqlitePool,
+    timestamp: DateTime<Local>,
+) -> Result<bool, sqlx::Error> {
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM screenshot_traces WHERE timestamp = ?")
+            .bind(to_db_time(&timestamp))
+            .fetch_one(pool)
+            .await?;
+    Ok(count > 0)
+}
 
-        traces.push(ScreenshotTrace {
-            id: row.get(0),
-            timestamp,
-            file_path: row.get(2),
-            width: row.get(3),
-            height: row.get(4),
-            file_size: row.get(5),
-        });
+// 按 ID 查询截图记录
+pub async fn get_screenshot_traces_by_ids(
+    pool: &SqlitePool,
+    ids: &[i64],
+) -> Result<Vec<ScreenshotTrace>, sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query = QueryBuilder::<Sqlite>::new(format!(
+        "SELECT {} FROM screenshot_traces WHERE id IN (",
+        TRACE_COLUMNS
+    ));
+    let mut separated = query.separated(", ");
+    for id in ids {
+        separated.push_bind(*id);
     }
+    query.push(") ORDER BY timestamp DESC");
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    rows.iter().map(trace_from_row).collect()
+}
+
+// 删除在此时间之前结束的摘要（包括分类、标签和模型对比结果），返回删除的摘要数量
+pub async fn delete_summaries_before(
+    pool: &SqlitePool,
+    before: DateTime<Local>,
+) -> Result<u64, sqlx::Error> {
+    let before = to_db_time(&before);
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "DELETE FROM summary_categories WHERE summary_id IN (SELECT id FROM summaries WHERE end_time < ?)",
+    )
+    .bind(&before)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "DELETE FROM summary_tags WHERE summary_id IN (SELECT id FROM summaries WHERE end_time < ?)",
+    )
+    .bind(&before)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "DELETE FROM model_comparisons WHERE summary_id IN (SELECT id FROM summaries WHERE end_time < ?)",
+    )
+    .bind(&before)
+    .execute(&mut *tx)
+    .await?;
+
+    let result = sqlx::query("DELETE FROM summaries WHERE end_time < ?")
+        .bind(&before)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(result.rows_affected())
+}
 
-    Ok(traces)
+// 在一个事务中删除截图记录，返回删除的行数
+pub async fn delete_screenshot_traces(pool: &SqlitePool, ids: &[i64]) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut deleted = 0;
+    // 分批删除，避免超过 SQLite 的参数数量限制
+    for chunk in ids.chunks(500) {
+        let mut query = QueryBuilder::<Sqlite>::new("DELETE FROM screenshot_traces WHERE id IN (");
+        let mut separated = query.separated(", ");
+        for id in chunk {
+            separated.push_bind(*id);
+        }
+        query.push(")");
+        deleted += query.build().execute(&mut *tx).await?.rows_affected();
+    }
+    tx.commit().await?;
+    Ok(deleted)
 }
 
 // 插入摘要
@@ -301,8 +1204,8 @@ pub async fn insert_summary(
         VALUES (?, ?, ?, ?)
         "#,
     )
-    .bind(start_time.to_rfc3339())
-    .bind(end_time.to_rfc3339())
+    .bind(to_db_time(&start_time))
+    .bind(to_db_time(&end_time))
     .bind(content)
     .bind(screenshot_count)
     .execute(pool)
@@ -312,81 +1215,747 @@ pub async fn insert_summary(
     Ok(id)
 }
 
-// 查询摘要（按时间范围）
-pub async fn get_summaries(
+// 保存摘要的结构化数据（JSON 字符串）
+pub async fn update_summary_structured_data(
     pool: &SqlitePool,
-    start_time: Option<DateTime<Local>>,
-    end_time: Option<DateTime<Local>>,
-    limit: Option<i64>,
-) -> Result<Vec<Summary>, sqlx::Error> {
-    let mut query = String::from("SELECT id, start_time, end_time, content, screenshot_count, created_at FROM summaries WHERE 1=1");
-    let mut conditions = Vec::new();
-
-    if let Some(start) = start_time {
-        conditions.push(format!("start_time >= '{}'", start.to_rfc3339()));
-    }
-    if let Some(end) = end_time {
-        conditions.push(format!("end_time <= '{}'", end.to_rfc3339()));
-    }
+    summary_id: i64,
+    structured_data: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE summaries SET structured_data = ? WHERE id = ?")
+        .bind(structured_data)
+        .bind(summary_id)
+        .execute(pool)
+        .await?;
 
-    if !conditions.is_empty() {
-        query.push_str(" AND ");
-        query.push_str(&conditions.join(" AND "));
-    }
+    Ok(())
+}
 
-    query.push_str(" ORDER BY start_time DESC");
+// 标记为隐身模式的摘要
+pub async fn mark_summary_private(pool: &SqlitePool, summary_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE summaries SET private = 1 WHERE id = ?")
+        .bind(summary_id)
+        .execute(pool)
+        .await?;
 
-    if let Some(limit_val) = limit {
-        query.push_str(&format!(" LIMIT {}", limit_val));
-    }
+    Ok(())
+}
 
-    let rows = sqlx::query(&query).fetch_all(pool).await?;
+// 更新摘要的专注度评分
+pub async fn update_summary_focus_score(
+    pool: &SqlitePool,
+    summary_id: i64,
+    focus_score: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE summaries SET focus_score = ? WHERE id = ?")
+        .bind(focus_score)
+        .bind(summary_id)
+        .execute(pool)
+        .await?;
 
-    let mut summaries = Vec::new();
-    for row in rows {
-        let start_time_str: String = row.get(1);
-        let end_time_str: String = row.get(2);
-        let created_at_str: String = row.get(5);
+    Ok(())
+}
 
-        // 尝试解析 RFC3339 格式，如果失败则尝试 SQLite 格式
-        let start_time = parse_timestamp(&start_time_str)
-            .map_err(|e| sqlx::Error::Decode(format!("Invalid start_time format: {}", e).into()))?;
+// 设置摘要的分类（替换原有分类）
+pub async fn set_summary_categories(
+    pool: &SqlitePool,
+    summary_id: i64,
+    categories: &[String],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM summary_categories WHERE summary_id = ?")
+        .bind(summary_id)
+        .execute(&mut *tx)
+        .await?;
 
-        let end_time = parse_timestamp(&end_time_str)
-            .map_err(|e| sqlx::Error::Decode(format!("Invalid end_time format: {}", e).into()))?;
+    for category in categories {
+        sqlx::query(
+            "INSERT OR IGNORE INTO summary_categories (summary_id, category) VALUES (?, ?)",
+        )
+        .bind(summary_id)
+        .bind(category)
+        .execute(&mut *tx)
+        .await?;
+    }
 
-        let created_at = parse_timestamp(&created_at_str)
-            .map_err(|e| sqlx::Error::Decode(format!("Invalid created_at format: {}", e).into()))?;
+    tx.commit().await?;
+    Ok(())
+}
 
-        summaries.push(Summary {
-            id: row.get(0),
-            start_time,
-            end_time,
-            content: row.get(3),
-            screenshot_count: row.get(4),
-            created_at,
-        });
-    }
+// 查询摘要的分类
+pub async fn get_summary_categories(
+    pool: &SqlitePool,
+    summary_id: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT category FROM summary_categories WHERE summary_id = ? ORDER BY category ASC",
+    )
+    .bind(summary_id)
+    .fetch_all(pool)
+    .await?;
 
-    Ok(summaries)
+    Ok(rows.into_iter().map(|(category,)| category).collect())
 }
 
-// API 请求记录结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ApiRequest {
-    pub id: i64,
-    pub timestamp: DateTime<Local>,
-    pub model: String,
-    pub endpoint: String,
-    pub prompt_tokens: Option<i64>,
-    pub completion_tokens: Option<i64>,
-    pub total_tokens: Option<i64>,
-    pub cost_usd: Option<f64>,
-    pub status_code: Option<i32>,
-    pub success: bool,
-    pub error_message: Option<String>,
+pub struct CategoryTotal {
+    pub category: String,
+    pub summary_count: i64,
+    pub duration_seconds: i64, // 属于多个分类的摘要按分类数平分时长
+}
+
+// 按分类统计摘要数量和时长（按时长倒序）
+pub async fn get_category_totals(
+    pool: &SqlitePool,
+    start_time: Option<DateTime<Local>>,
+    end_time: Option<DateTime<Local>>,
+) -> Result<Vec<CategoryTotal>, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new(
+        "SELECT c.category, s.start_time, s.end_time, (SELECT COUNT(*) FROM summary_categories WHERE summary_id = s.id) FROM summary_categories c JOIN summaries s ON s.id = c.summary_id WHERE 1=1",
+    );
+
+    if let Some(start) = start_time {
+        query
+            .push(" AND s.start_time >= ")
+            .push_bind(to_db_time(&start));
+    }
+    if let Some(end) = end_time {
+        query
+            .push(" AND s.end_time <= ")
+            .push_bind(to_db_time(&end));
+    }
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    let mut totals: Vec<CategoryTotal> = Vec::new();
+    for row in rows {
+        let category: String = row.get(0);
+        let start_time = parse_timestamp(&row.get::<String, _>(1))
+            .map_err(|e| sqlx::Error::Decode(format!("Invalid start_time format: {}", e).into()))?;
+        let end_time = parse_timestamp(&row.get::<String, _>(2))
+            .map_err(|e| sqlx::Error::Decode(format!("Invalid end_time format: {}", e).into()))?;
+        let category_count: i64 = row.get(3);
+        let seconds = (end_time - start_time).num_seconds().max(0) / category_count.max(1);
+
+        match totals.iter_mut().find(|t| t.category == category) {
+            Some(total) => {
+                total.summary_count += 1;
+                total.duration_seconds += seconds;
+            }
+            None => totals.push(CategoryTotal {
+                category,
+                summary_count: 1,
+                duration_seconds: seconds,
+            }),
+        }
+    }
+
+    totals.sort_by(|a, b| b.duration_seconds.cmp(&a.duration_seconds));
+    Ok(totals)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub keywords: Vec<String>, // 与摘要的应用或分类匹配时自动添加此标签
+    pub summary_count: i64,
+    pub created_at: DateTime<Local>,
+}
+
+// 查询所有标签（按名称排序）
+pub async fn get_tags(pool: &SqlitePool) -> Result<Vec<Tag>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT t.id, t.name, t.keywords, t.created_at, (SELECT COUNT(*) FROM summary_tags WHERE tag_id = t.id) FROM tags t ORDER BY t.name COLLATE NOCASE ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let created_at = parse_timestamp(&row.get::<String, _>(3)).map_err(|e| {
+                sqlx::Error::Decode(format!("Invalid created_at format: {}", e).into())
+            })?;
+            Ok(Tag {
+                id: row.get(0),
+                name: row.get(1),
+                keywords: serde_json::from_str(&row.get::<String, _>(2)).unwrap_or_default(),
+                summary_count: row.get(4),
+                created_at,
+            })
+        })
+        .collect()
+}
+
+// 添加标签，同名标签（不区分大小写）已存在时更新关键词，返回标签 ID
+pub async fn upsert_tag(
+    pool: &SqlitePool,
+    name: &str,
+    keywords: Option<&[String]>,
+) -> Result<i64, sqlx::Error> {
+    let keywords_json = keywords.map(|k| serde_json::to_string(k).unwrap_or_else(|_| "[]".into()));
+    sqlx::query("INSERT OR IGNORE INTO tags (name, keywords, created_at) VALUES (?, ?, ?)")
+        .bind(name)
+        .bind(keywords_json.as_deref().unwrap_or("[]"))
+        .bind(to_db_time(&Local::now()))
+        .execute(pool)
+        .await?;
+
+    if let Some(keywords_json) = &keywords_json {
+        sqlx::query("UPDATE tags SET keywords = ? WHERE name = ?")
+            .bind(keywords_json)
+            .bind(name)
+            .execute(pool)
+            .await?;
+    }
+
+    let (id,): (i64,) = sqlx::query_as("SELECT id FROM tags WHERE name = ?")
+        .bind(name)
+        .fetch_one(pool)
+        .await?;
+    Ok(id)
+}
+
+// 删除标签及其与摘要的关联
+pub async fn delete_tag(pool: &SqlitePool, tag_id: i64) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM summary_tags WHERE tag_id = ?")
+        .bind(tag_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(tag_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+// 给摘要添加标签（已添加时忽略），auto 表示是否为自动添加
+pub async fn add_summary_tag(
+    pool: &SqlitePool,
+    summary_id: i64,
+    tag_id: i64,
+    auto: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR IGNORE INTO summary_tags (summary_id, tag_id, auto) VALUES (?, ?, ?)")
+        .bind(summary_id)
+        .bind(tag_id)
+        .bind(auto)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 移除摘要的标签
+pub async fn remove_summary_tag(
+    pool: &SqlitePool,
+    summary_id: i64,
+    tag_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM summary_tags WHERE summary_id = ? AND tag_id = ?")
+        .bind(summary_id)
+        .bind(tag_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 设置摘要的自动标签（替换原有的自动标签，手动添加的标签不变）
+pub async fn set_auto_summary_tags(
+    pool: &SqlitePool,
+    summary_id: i64,
+    tag_ids: &[i64],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM summary_tags WHERE summary_id = ? AND auto = 1")
+        .bind(summary_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for tag_id in tag_ids {
+        sqlx::query(
+            "INSERT OR IGNORE INTO summary_tags (summary_id, tag_id, auto) VALUES (?, ?, 1)",
+        )
+        .bind(summary_id)
+        .bind(tag_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityHeatmapCell {
+    pub weekday: u32, // 0 = 周日，6 = 周六
+    pub hour: u32,    // 本地时间 0-23
+    pub screenshot_count: i64,
+    pub focus_seconds: i64, // 专注度达到阈值的摘要时长，按摘要开始的小时统计
+}
+
+// 按星期和小时（本地时间）统计截图数量和专注时长，只返回有数据的格子
+pub async fn get_activity_heatmap(
+    pool: &SqlitePool,
+    start_time: Option<DateTime<Local>>,
+    end_time: Option<DateTime<Local>>,
+    focus_threshold: i64,
+) -> Result<Vec<ActivityHeatmapCell>, sqlx::Error> {
+    let mut cells: Vec<ActivityHeatmapCell> = Vec::new();
+
+    let mut query = QueryBuilder::<Sqlite>::new(
+        "SELECT CAST(strftime('%w', timestamp, 'localtime') AS INTEGER) AS weekday, CAST(strftime('%H', timestamp, 'localtime') AS INTEGER) AS hour, COUNT(*) FROM screenshot_traces WHERE 1=1",
+    );
+    if let Some(start) = start_time {
+        query
+            .push(" AND timestamp >= ")
+            .push_bind(to_db_time(&start));
+    }
+    if let Some(end) = end_time {
+        query.push(" AND timestamp <= ").push_bind(to_db_time(&end));
+    }
+    query.push(" GROUP BY weekday, hour");
+
+    for row in query.build().fetch_all(pool).await? {
+        cells.push(ActivityHeatmapCell {
+            weekday: row.get::<i64, _>(0) as u32,
+            hour: row.get::<i64, _>(1) as u32,
+            screenshot_count: row.get(2),
+            focus_seconds: 0,
+        });
+    }
+
+    let mut query = QueryBuilder::<Sqlite>::new(
+        "SELECT CAST(strftime('%w', start_time, 'localtime') AS INTEGER) AS weekday, CAST(strftime('%H', start_time, 'localtime') AS INTEGER) AS hour, SUM(CAST(ROUND((julianday(end_time) - julianday(start_time)) * 86400) AS INTEGER)) FROM summaries WHERE focus_score >= ",
+    );
+    query.push_bind(focus_threshold);
+    if let Some(start) = start_time {
+        query
+            .push(" AND start_time >= ")
+            .push_bind(to_db_time(&start));
+    }
+    if let Some(end) = end_time {
+        query.push(" AND end_time <= ").push_bind(to_db_time(&end));
+    }
+    query.push(" GROUP BY weekday, hour");
+
+    for row in query.build().fetch_all(pool).await? {
+        let weekday = row.get::<i64, _>(0) as u32;
+        let hour = row.get::<i64, _>(1) as u32;
+        let seconds: i64 = row.get(2);
+        match cells
+            .iter_mut()
+            .find(|c| c.weekday == weekday && c.hour == hour)
+        {
+            Some(cell) => cell.focus_seconds = seconds,
+            None => cells.push(ActivityHeatmapCell {
+                weekday,
+                hour,
+                screenshot_count: 0,
+                focus_seconds: seconds,
+            }),
+        }
+    }
+
+    cells.sort_by_key(|c| (c.weekday, c.hour));
+    Ok(cells)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendPoint {
+    pub date: String, // YYYY-MM-DD format
+    pub tracked_seconds: i64,
+    pub focus_score: Option<f64>, // 按摘要时长加权的平均专注度
+    pub tracked_seconds_avg_7d: f64,
+    pub tracked_seconds_avg_30d: f64,
+    pub focus_score_avg_7d: Option<f64>,
+    pub focus_score_avg_30d: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeekOverWeek {
+    pub tracked_seconds_this_week: i64, // 最近 7 天（包括今天）
+    pub tracked_seconds_last_week: i64, // 再往前 7 天
+    pub tracked_seconds_change_percent: Option<f64>, // 上周没有数据时为 None
+    pub focus_score_this_week: Option<f64>,
+    pub focus_score_last_week: Option<f64>,
+    pub focus_score_change: Option<f64>, // 分数差值
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreaksAndTrends {
+    pub current_streak_days: i64, // 今天还没有记录时从昨天开始算
+    pub longest_streak_days: i64,
+    pub points: Vec<TrendPoint>, // 按日期升序
+    pub week_over_week: WeekOverWeek,
+}
+
+// 一段日期内的记录时长和加权专注度，days 为 (记录秒数, 专注度×秒数, 有专注度的秒数)
+fn window_totals(
+    days: &std::collections::HashMap<NaiveDate, (i64, f64, i64)>,
+    end: NaiveDate,
+    len: i64,
+) -> (i64, Option<f64>) {
+    let (mut tracked, mut weighted, mut scored) = (0, 0.0, 0);
+    for offset in 0..len {
+        if let Some((t, w, s)) = days.get(&(end - chrono::Duration::days(offset))) {
+            tracked += t;
+            weighted += w;
+            scored += s;
+        }
+    }
+    let focus = (scored > 0).then(|| weighted / scored as f64);
+    (tracked, focus)
+}
+
+// 计算连续记录天数、最近 days 天每天的记录时长和专注度（包括 7 天和 30 天移动平均）以及周环比
+pub async fn get_streaks_and_trends(
+    pool: &SqlitePool,
+    today: NaiveDate,
+    days: i64,
+) -> Result<StreaksAndTrends, sqlx::Error> {
+    // 有截图或摘要的日期都算作有记录
+    let active_days: Vec<(String,)> = sqlx::query_as(
+        "SELECT day FROM (SELECT date(timestamp, 'localtime') AS day FROM screenshot_traces UNION SELECT date(start_time, 'localtime') FROM summaries) WHERE day IS NOT NULL ORDER BY day ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+    let active_days: Vec<NaiveDate> = active_days
+        .iter()
+        .filter_map(|(day,)| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+        .collect();
+
+    let mut longest_streak_days = 0;
+    let mut streak = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in &active_days {
+        streak = match previous {
+            Some(prev) if *day - prev == chrono::Duration::days(1) => streak + 1,
+            _ => 1,
+        };
+        longest_streak_days = longest_streak_days.max(streak);
+        previous = Some(*day);
+    }
+    let current_streak_days = match previous {
+        Some(last) if last >= today - chrono::Duration::days(1) => streak,
+        _ => 0,
+    };
+
+    // 多取 30 天用于计算第一天的移动平均，至少覆盖两周用于周环比
+    let history_days = days.max(14) + 29;
+    let range_start = today - chrono::Duration::days(history_days);
+    let range_start = range_start
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .ok_or_else(|| sqlx::Error::Decode("Invalid timezone conversion".into()))?;
+    let rows = sqlx::query(
+        "SELECT date(start_time, 'localtime') AS day, CAST(ROUND((julianday(end_time) - julianday(start_time)) * 86400) AS INTEGER), focus_score FROM summaries WHERE start_time >= ?",
+    )
+    .bind(to_db_time(&range_start))
+    .fetch_all(pool)
+    .await?;
+
+    let mut totals: std::collections::HashMap<NaiveDate, (i64, f64, i64)> =
+        std::collections::HashMap::new();
+    for row in rows {
+        let Ok(day) = NaiveDate::parse_from_str(&row.get::<String, _>(0), "%Y-%m-%d") else {
+            continue;
+        };
+        let seconds = row.get::<i64, _>(1).max(0);
+        let entry = totals.entry(day).or_insert((0, 0.0, 0));
+        entry.0 += seconds;
+        if let Some(score) = row.get::<Option<i64>, _>(2) {
+            entry.1 += score as f64 * seconds as f64;
+            entry.2 += seconds;
+        }
+    }
+
+    let points = (0..days)
+        .rev()
+        .map(|offset| {
+            let date = today - chrono::Duration::days(offset);
+            let (tracked_seconds, focus_score) = window_totals(&totals, date, 1);
+            let (tracked_7d, focus_score_avg_7d) = window_totals(&totals, date, 7);
+            let (tracked_30d, focus_score_avg_30d) = window_totals(&totals, date, 30);
+            TrendPoint {
+                date: date.format("%Y-%m-%d").to_string(),
+                tracked_seconds,
+                focus_score,
+                tracked_seconds_avg_7d: tracked_7d as f64 / 7.0,
+                tracked_seconds_avg_30d: tracked_30d as f64 / 30.0,
+                focus_score_avg_7d,
+                focus_score_avg_30d,
+            }
+        })
+        .collect();
+
+    let (this_week, focus_this_week) = window_totals(&totals, today, 7);
+    let (last_week, focus_last_week) = window_totals(&totals, today - chrono::Duration::days(7), 7);
+    let week_over_week = WeekOverWeek {
+        tracked_seconds_this_week: this_week,
+        tracked_seconds_last_week: last_week,
+        tracked_seconds_change_percent: (last_week > 0)
+            .then(|| (this_week - last_week) as f64 * 100.0 / last_week as f64),
+        focus_score_this_week: focus_this_week,
+        focus_score_last_week: focus_last_week,
+        focus_score_change: focus_this_week
+            .zip(focus_last_week)
+            .map(|(this, last)| this - last),
+    };
+
+    Ok(StreaksAndTrends {
+        current_streak_days,
+        longest_streak_days,
+        points,
+        week_over_week,
+    })
+}
+
+// 更新摘要内容（重新生成摘要时使用）
+pub async fn update_summary_content(
+    pool: &SqlitePool,
+    summary_id: i64,
+    content: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE summaries SET content = ? WHERE id = ?")
+        .bind(content)
+        .bind(summary_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 把摘要所在日期（本地时间）的每日总结标记为需要重新生成，参数为摘要 ID
+const MARK_SUMMARY_DAYS_STALE: &str = "UPDATE daily_summaries SET stale = 1 WHERE date BETWEEN (SELECT date(start_time, 'localtime') FROM summaries WHERE id = ?1) AND (SELECT date(end_time, 'localtime') FROM summaries WHERE id = ?1)";
+
+// 修改摘要内容，并把对应的每日总结标记为需要重新生成，摘要不存在时返回 false
+pub async fn edit_summary_content(
+    pool: &SqlitePool,
+    summary_id: i64,
+    content: &str,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query("UPDATE summaries SET content = ? WHERE id = ?")
+        .bind(content)
+        .bind(summary_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    sqlx::query(MARK_SUMMARY_DAYS_STALE)
+        .bind(summary_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(updated > 0)
+}
+
+// 删除一条摘要（包括分类、标签和模型对比结果），并把对应的每日总结标记为需要重新生成，
+// 摘要不存在时返回 false
+pub async fn delete_summary(pool: &SqlitePool, summary_id: i64) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(MARK_SUMMARY_DAYS_STALE)
+        .bind(summary_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for statement in [
+        "DELETE FROM summary_categories WHERE summary_id = ?",
+        "DELETE FROM summary_tags WHERE summary_id = ?",
+        "DELETE FROM model_comparisons WHERE summary_id = ?",
+    ] {
+        sqlx::query(statement)
+            .bind(summary_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let deleted = sqlx::query("DELETE FROM summaries WHERE id = ?")
+        .bind(summary_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+    Ok(deleted > 0)
+}
+
+// 倒数第二列是摘要的标签名称（JSON 数组）
+const SUMMARY_COLUMNS: &str = "id, start_time, end_time, content, screenshot_count, created_at, structured_data, focus_score, (SELECT json_group_array(t.name) FROM summary_tags st JOIN tags t ON t.id = st.tag_id WHERE st.summary_id = summaries.id), private";
+
+fn summary_from_row(row: &SqliteRow) -> Result<Summary, sqlx::Error> {
+    let start_time_str: String = row.get(1);
+    let end_time_str: String = row.get(2);
+    let created_at_str: String = row.get(5);
+
+    // 尝试解析 RFC3339 格式，如果失败则尝试 SQLite 格式
+    let start_time = parse_timestamp(&start_time_str)
+        .map_err(|e| sqlx::Error::Decode(format!("Invalid start_time format: {}", e).into()))?;
+
+    let end_time = parse_timestamp(&end_time_str)
+        .map_err(|e| sqlx::Error::Decode(format!("Invalid end_time format: {}", e).into()))?;
+
+    let created_at = parse_timestamp(&created_at_str)
+        .map_err(|e| sqlx::Error::Decode(format!("Invalid created_at format: {}", e).into()))?;
+
+    // 结构化数据损坏时忽略，不影响摘要本身
+    let structured_data = row
+        .get::<Option<String>, _>(6)
+        .and_then(|json| serde_json::from_str(&json).ok());
+
+    Ok(Summary {
+        id: row.get(0),
+        start_time,
+        end_time,
+        content: row.get(3),
+        screenshot_count: row.get(4),
+        created_at,
+        structured_data,
+        focus_score: row.get(7),
+        tags: row
+            .get::<Option<String>, _>(8)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        private: row.get::<i64, _>(9) != 0,
+    })
+}
+
+// 是否已有相同开始时间的摘要（导入时去重）
+pub async fn summary_exists_at(
+    pool: &SqlitePool,
+    start_time: DateTime<Local>,
+) -> Result<bool, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM summaries WHERE start_time = ?")
+        .bind(to_db_time(&start_time))
+        .fetch_one(pool)
+        .await?;
+    Ok(count > 0)
+}
+
+// 按 ID 查询摘要
+pub async fn get_summary(
+    pool: &SqlitePool,
+    summary_id: i64,
+) -> Result<Option<Summary>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM summaries WHERE id = ?",
+        SUMMARY_COLUMNS
+    ))
+    .bind(summary_id)
+    .fetch_optional(pool)
+    .await?;
+
+    row.as_ref().map(summary_from_row).transpose()
+}
+
+// 查询摘要（按时间范围）
+pub async fn get_summaries(
+    pool: &SqlitePool,
+    start_time: Option<DateTime<Local>>,
+    end_time: Option<DateTime<Local>>,
+    limit: Option<i64>,
+) -> Result<Vec<Summary>, sqlx::Error> {
+    get_summaries_with_tags(pool, start_time, end_time, &[], limit).await
+}
+
+// 查询摘要（按时间范围），tags 不为空时只返回带有其中任一标签的摘要
+pub async fn get_summaries_with_tags(
+    pool: &SqlitePool,
+    start_time: Option<DateTime<Local>>,
+    end_time: Option<DateTime<Local>>,
+    tags: &[String],
+    limit: Option<i64>,
+) -> Result<Vec<Summary>, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new(format!(
+        "SELECT {} FROM summaries WHERE 1=1",
+        SUMMARY_COLUMNS
+    ));
+
+    if let Some(start) = start_time {
+        query
+            .push(" AND start_time >= ")
+            .push_bind(to_db_time(&start));
+    }
+    if let Some(end) = end_time {
+        query.push(" AND end_time <= ").push_bind(to_db_time(&end));
+    }
+    if !tags.is_empty() {
+        query.push(
+            " AND id IN (SELECT st.summary_id FROM summary_tags st JOIN tags t ON t.id = st.tag_id WHERE t.name IN (",
+        );
+        let mut separated = query.separated(", ");
+        for tag in tags {
+            separated.push_bind(tag);
+        }
+        query.push("))");
+    }
+
+    query.push(" ORDER BY start_time DESC");
+
+    if let Some(limit_val) = limit {
+        query.push(" LIMIT ").push_bind(limit_val);
+    }
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    rows.iter().map(summary_from_row).collect()
+}
+
+// 统计时间范围内的摘要数量（只需要数量时不读取整行）
+pub async fn get_summary_count(
+    pool: &SqlitePool,
+    start_time: Option<DateTime<Local>>,
+    end_time: Option<DateTime<Local>>,
+) -> Result<i64, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM summaries WHERE 1=1");
+
+    if let Some(start) = start_time {
+        query
+            .push(" AND start_time >= ")
+            .push_bind(to_db_time(&start));
+    }
+    if let Some(end) = end_time {
+        query.push(" AND end_time <= ").push_bind(to_db_time(&end));
+    }
+
+    let (count,): (i64,) = query.build_query_as().fetch_one(pool).await?;
+    Ok(count)
+}
+
+// API 请求记录结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiRequest {
+    pub id: i64,
+    pub timestamp: DateTime<Local>,
+    pub model: String,
+    pub endpoint: String,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub total_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
+    pub status_code: Option<i32>,
+    pub success: bool,
+    pub error_message: Option<String>,
     pub request_duration_ms: Option<i64>,
+    pub fallback_from_model: Option<String>, // 主模型失败后改用备用模型时，记录原来的主模型
 }
 
 // 插入 API 请求记录
@@ -397,22 +1966,26 @@ pub async fn insert_api_request(
     prompt_tokens: Option<i64>,
     completion_tokens: Option<i64>,
     total_tokens: Option<i64>,
+    cost_usd: Option<f64>,
     status_code: u16,
     success: bool,
     error_message: Option<&str>,
     duration_ms: u64,
+    api_key_id: Option<i64>,
+    fallback_from_model: Option<&str>,
 ) -> Result<i64, sqlx::Error> {
     use chrono::Local;
 
-    let timestamp = Local::now().to_rfc3339();
+    let timestamp = to_db_time(&Local::now());
 
     let id = sqlx::query(
         r#"
         INSERT INTO api_requests (
             timestamp, model, endpoint, prompt_tokens, completion_tokens, total_tokens,
-            status_code, success, error_message, request_duration_ms
+            cost_usd, status_code, success, error_message, request_duration_ms, api_key_id,
+            fallback_from_model
         )
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(timestamp)
@@ -421,10 +1994,13 @@ pub async fn insert_api_request(
     .bind(prompt_tokens)
     .bind(completion_tokens)
     .bind(total_tokens)
+    .bind(cost_usd)
     .bind(status_code as i32)
     .bind(if success { 1 } else { 0 })
     .bind(error_message)
     .bind(duration_ms as i64)
+    .bind(api_key_id)
+    .bind(fallback_from_model)
     .execute(pool)
     .await?
     .last_insert_rowid();
@@ -432,13 +2008,98 @@ pub async fn insert_api_request(
     Ok(id)
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiRequestPage {
+    pub requests: Vec<ApiRequest>,
+    pub total: i64, // 符合条件的记录总数（不受分页影响）
+}
+
+// 在查询中追加 API 请求记录的筛选条件
+fn push_api_request_filters(
+    query: &mut QueryBuilder<'_, Sqlite>,
+    start_time: Option<DateTime<Local>>,
+    end_time: Option<DateTime<Local>>,
+    success: Option<bool>,
+    model: Option<&str>,
+) {
+    if let Some(start) = start_time {
+        query
+            .push(" AND timestamp >= ")
+            .push_bind(to_db_time(&start));
+    }
+    if let Some(end) = end_time {
+        query.push(" AND timestamp <= ").push_bind(to_db_time(&end));
+    }
+    if let Some(success) = success {
+        query.push(" AND success = ").push_bind(success);
+    }
+    if let Some(model) = model {
+        query.push(" AND model = ").push_bind(model.to_string());
+    }
+}
+
+// 分页查询 API 请求记录（按时间倒序），可按成功与否和模型筛选
+pub async fn get_api_requests(
+    pool: &SqlitePool,
+    start_time: Option<DateTime<Local>>,
+    end_time: Option<DateTime<Local>>,
+    success: Option<bool>,
+    model: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<ApiRequestPage, sqlx::Error> {
+    let mut count_query =
+        QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM api_requests WHERE 1=1");
+    push_api_request_filters(&mut count_query, start_time, end_time, success, model);
+    let total: i64 = count_query.build().fetch_one(pool).await?.get(0);
+
+    let mut query = QueryBuilder::<Sqlite>::new(
+        "SELECT id, timestamp, model, endpoint, prompt_tokens, completion_tokens, total_tokens, cost_usd, status_code, success, error_message, request_duration_ms, fallback_from_model FROM api_requests WHERE 1=1",
+    );
+    push_api_request_filters(&mut query, start_time, end_time, success, model);
+    query
+        .push(" ORDER BY timestamp DESC, id DESC LIMIT ")
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    let requests = rows
+        .iter()
+        .map(|row| {
+            let timestamp = parse_timestamp(&row.get::<String, _>(1)).map_err(|e| {
+                sqlx::Error::Decode(format!("Invalid timestamp format: {}", e).into())
+            })?;
+            Ok(ApiRequest {
+                id: row.get(0),
+                timestamp,
+                model: row.get(2),
+                endpoint: row.get(3),
+                prompt_tokens: row.get(4),
+                completion_tokens: row.get(5),
+                total_tokens: row.get(6),
+                cost_usd: row.get(7),
+                status_code: row.get(8),
+                success: row.get(9),
+                error_message: row.get(10),
+                request_duration_ms: row.get(11),
+                fallback_from_model: row.get(12),
+            })
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+    Ok(ApiRequestPage { requests, total })
+}
+
 // 获取 API 请求统计
 pub async fn get_api_statistics(
     pool: &SqlitePool,
     start_time: Option<DateTime<Local>>,
     end_time: Option<DateTime<Local>>,
 ) -> Result<ApiStatistics, sqlx::Error> {
-    let mut query = String::from(
+    let mut query = QueryBuilder::<Sqlite>::new(
         "SELECT 
             COALESCE(COUNT(*), 0) as total_requests,
             COALESCE(SUM(CASE WHEN success = 1 THEN 1 ELSE 0 END), 0) as successful_requests,
@@ -446,18 +2107,21 @@ pub async fn get_api_statistics(
             COALESCE(SUM(prompt_tokens), 0) as total_prompt_tokens,
             COALESCE(SUM(completion_tokens), 0) as total_completion_tokens,
             COALESCE(SUM(total_tokens), 0) as total_tokens,
-            AVG(request_duration_ms) as avg_duration_ms
+            AVG(request_duration_ms) as avg_duration_ms,
+            COALESCE(SUM(cost_usd), 0) as total_cost_usd
         FROM api_requests WHERE 1=1",
     );
 
     if let Some(start) = start_time {
-        query.push_str(&format!(" AND timestamp >= '{}'", start.to_rfc3339()));
+        query
+            .push(" AND timestamp >= ")
+            .push_bind(to_db_time(&start));
     }
     if let Some(end) = end_time {
-        query.push_str(&format!(" AND timestamp <= '{}'", end.to_rfc3339()));
+        query.push(" AND timestamp <= ").push_bind(to_db_time(&end));
     }
 
-    let row = sqlx::query(&query).fetch_one(pool).await?;
+    let row = query.build().fetch_one(pool).await?;
 
     Ok(ApiStatistics {
         total_requests: row.get::<i64, _>(0),
@@ -467,6 +2131,7 @@ pub async fn get_api_statistics(
         total_completion_tokens: row.get::<i64, _>(4),
         total_tokens: row.get::<i64, _>(5),
         avg_duration_ms: row.get::<Option<f64>, _>(6),
+        total_cost_usd: row.get::<f64, _>(7),
     })
 }
 
@@ -480,6 +2145,13 @@ pub struct ApiStatistics {
     pub total_completion_tokens: i64,
     pub total_tokens: i64,
     pub avg_duration_ms: Option<f64>,
+    pub total_cost_usd: f64, // 只统计服务商返回了费用的请求
+}
+
+// 数据库中的时间统一保存为 UTC，使用固定格式以便按字符串比较，读取时再转换为本地时间
+pub fn to_db_time(dt: &DateTime<Local>) -> String {
+    dt.with_timezone(&Utc)
+        .to_rfc3339_opts(SecondsFormat::Millis, true)
 }
 
 // 解析时间戳，支持多种格式
@@ -489,20 +2161,14 @@ fn parse_timestamp(timestamp_str: &str) -> Result<DateTime<Local>, String> {
         return Ok(dt.with_timezone(&Local));
     }
 
-    // 尝试 SQLite 的 datetime 格式: "YYYY-MM-DD HH:MM:SS"
+    // 尝试 SQLite 的 datetime 格式: "YYYY-MM-DD HH:MM:SS"（CURRENT_TIMESTAMP，为 UTC 时间）
     if let Ok(dt) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S") {
-        return Ok(dt
-            .and_local_timezone(Local)
-            .single()
-            .ok_or_else(|| "Invalid timezone conversion".to_string())?);
+        return Ok(dt.and_utc().with_timezone(&Local));
     }
 
     // 尝试带毫秒的格式: "YYYY-MM-DD HH:MM:SS.fff"
     if let Ok(dt) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%.f") {
-        return Ok(dt
-            .and_local_timezone(Local)
-            .single()
-            .ok_or_else(|| "Invalid timezone conversion".to_string())?);
+        return Ok(dt.and_utc().with_timezone(&Local));
     }
 
     Err(format!("Unable to parse timestamp: {}", timestamp_str))
@@ -510,34 +2176,176 @@ fn parse_timestamp(timestamp_str: &str) -> Result<DateTime<Local>, String> {
 
 // 获取今天的截图数量
 pub async fn get_today_screenshot_count(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
-    let count: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM screenshot_traces WHERE date(timestamp) = date('now')",
-    )
-    .fetch_one(pool)
-    .await?;
+    // 按本地时间的今天计算，而不是 SQLite 的 UTC 日期
+    let today_start = Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .ok_or_else(|| sqlx::Error::Decode("Invalid timezone conversion".into()))?;
+    let count: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM screenshot_traces WHERE timestamp >= ?")
+            .bind(to_db_time(&today_start))
+            .fetch_one(pool)
+            .await?;
 
     Ok(count.0)
 }
 
-// 插入或更新每日总结
-pub async fn upsert_daily_summary(
+// 按本地日期统计截图数量，返回 (YYYY-MM-DD, 数量)
+pub async fn get_daily_screenshot_counts(
     pool: &SqlitePool,
-    date: &str, // YYYY-MM-DD format
-    content: &str,
-    screenshot_count: i32,
-    summary_count: i32,
-    total_duration_seconds: i64,
-) -> Result<i64, sqlx::Error> {
-    sqlx::query(
-        r#"
-        INSERT INTO daily_summaries (date, content, screenshot_count, summary_count, total_duration_seconds, updated_at)
-        VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
-        ON CONFLICT(date) DO UPDATE SET
-            content = excluded.content,
-            screenshot_count = excluded.screenshot_count,
-            summary_count = excluded.summary_count,
-            total_duration_seconds = excluded.total_duration_seconds,
-            updated_at = CURRENT_TIMESTAMP
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT date(timestamp, 'localtime') AS day, COUNT(*) FROM screenshot_traces WHERE timestamp >= ? AND timestamp <= ? GROUP BY day",
+    )
+    .bind(to_db_time(&start_time))
+    .bind(to_db_time(&end_time))
+    .fetch_all(pool)
+    .await
+}
+
+// 按开始时间的本地日期统计摘要数量和总时长（秒），返回 (YYYY-MM-DD, 摘要数量, 总时长)
+pub async fn get_daily_summary_totals(
+    pool: &SqlitePool,
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+) -> Result<Vec<(String, i64, i64)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT date(start_time, 'localtime') AS day, COUNT(*), COALESCE(SUM(CAST(ROUND((julianday(end_time) - julianday(start_time)) * 86400) AS INTEGER)), 0) FROM summaries WHERE start_time >= ? AND end_time <= ? GROUP BY day",
+    )
+    .bind(to_db_time(&start_time))
+    .bind(to_db_time(&end_time))
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppUsage {
+    pub app: String,
+    pub date: String, // YYYY-MM-DD format
+    pub seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppUsageTotal {
+    pub app: String,
+    pub seconds: i64,
+}
+
+// 累加应用使用时长，entries 为 (应用, YYYY-MM-DD, 秒数)
+pub async fn add_app_usage(
+    pool: &SqlitePool,
+    entries: &[(String, String, i64)],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    for (app, date, seconds) in entries {
+        sqlx::query(
+            r#"
+            INSERT INTO app_usage (app, date, seconds)
+            VALUES (?, ?, ?)
+            ON CONFLICT(app, date) DO UPDATE SET seconds = seconds + excluded.seconds
+            "#,
+        )
+        .bind(app)
+        .bind(date)
+        .bind(seconds)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+// 查询日期范围内每个应用每天的使用时长
+pub async fn get_app_usage(
+    pool: &SqlitePool,
+    start_date: Option<&str>, // YYYY-MM-DD format
+    end_date: Option<&str>,   // YYYY-MM-DD format
+) -> Result<Vec<AppUsage>, sqlx::Error> {
+    let mut query =
+        QueryBuilder::<Sqlite>::new("SELECT app, date, seconds FROM app_usage WHERE 1=1");
+
+    if let Some(start) = start_date {
+        query.push(" AND date >= ").push_bind(start);
+    }
+    if let Some(end) = end_date {
+        query.push(" AND date <= ").push_bind(end);
+    }
+
+    query.push(" ORDER BY date DESC, seconds DESC");
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| AppUsage {
+            app: row.get(0),
+            date: row.get(1),
+            seconds: row.get(2),
+        })
+        .collect())
+}
+
+// 查询日期范围内使用时长最多的应用（按总时长倒序）
+pub async fn get_top_apps(
+    pool: &SqlitePool,
+    start_date: Option<&str>, // YYYY-MM-DD format
+    end_date: Option<&str>,   // YYYY-MM-DD format
+    limit: Option<i64>,
+) -> Result<Vec<AppUsageTotal>, sqlx::Error> {
+    let mut query =
+        QueryBuilder::<Sqlite>::new("SELECT app, SUM(seconds) AS total FROM app_usage WHERE 1=1");
+
+    if let Some(start) = start_date {
+        query.push(" AND date >= ").push_bind(start);
+    }
+    if let Some(end) = end_date {
+        query.push(" AND date <= ").push_bind(end);
+    }
+
+    query.push(" GROUP BY app ORDER BY total DESC");
+
+    if let Some(limit_val) = limit {
+        query.push(" LIMIT ").push_bind(limit_val);
+    }
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| AppUsageTotal {
+            app: row.get(0),
+            seconds: row.get(1),
+        })
+        .collect())
+}
+
+// 插入或更新每日总结
+pub async fn upsert_daily_summary(
+    pool: &SqlitePool,
+    date: &str, // YYYY-MM-DD format
+    content: &str,
+    screenshot_count: i32,
+    summary_count: i32,
+    total_duration_seconds: i64,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO daily_summaries (date, content, screenshot_count, summary_count, total_duration_seconds, updated_at)
+        VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT(date) DO UPDATE SET
+            content = excluded.content,
+            screenshot_count = excluded.screenshot_count,
+            summary_count = excluded.summary_count,
+            total_duration_seconds = excluded.total_duration_seconds,
+            updated_at = CURRENT_TIMESTAMP,
+            stale = 0
         "#,
     )
     .bind(date)
@@ -562,8 +2370,8 @@ pub async fn get_daily_summary(
     pool: &SqlitePool,
     date: &str, // YYYY-MM-DD format
 ) -> Result<Option<DailySummary>, sqlx::Error> {
-    let result: Option<(i64, String, String, i32, i32, i64, String, String)> = sqlx::query_as(
-        "SELECT id, date, content, screenshot_count, summary_count, total_duration_seconds, created_at, updated_at FROM daily_summaries WHERE date = ?"
+    let result: Option<(i64, String, String, i32, i32, i64, String, String, bool)> = sqlx::query_as(
+        "SELECT id, date, content, screenshot_count, summary_count, total_duration_seconds, created_at, updated_at, stale FROM daily_summaries WHERE date = ?"
     )
     .bind(date)
     .fetch_optional(pool)
@@ -584,6 +2392,7 @@ pub async fn get_daily_summary(
             total_duration_seconds: row.5,
             created_at,
             updated_at,
+            stale: row.8,
         }))
     } else {
         Ok(None)
@@ -597,28 +2406,22 @@ pub async fn get_daily_summaries(
     end_date: Option<&str>,   // YYYY-MM-DD format
     limit: Option<i64>,
 ) -> Result<Vec<DailySummary>, sqlx::Error> {
-    let mut query = String::from("SELECT id, date, content, screenshot_count, summary_count, total_duration_seconds, created_at, updated_at FROM daily_summaries WHERE 1=1");
-    let mut conditions = Vec::new();
+    let mut query = QueryBuilder::<Sqlite>::new("SELECT id, date, content, screenshot_count, summary_count, total_duration_seconds, created_at, updated_at, stale FROM daily_summaries WHERE 1=1");
 
     if let Some(start) = start_date {
-        conditions.push(format!("date >= '{}'", start));
+        query.push(" AND date >= ").push_bind(start);
     }
     if let Some(end) = end_date {
-        conditions.push(format!("date <= '{}'", end));
-    }
-
-    if !conditions.is_empty() {
-        query.push_str(" AND ");
-        query.push_str(&conditions.join(" AND "));
+        query.push(" AND date <= ").push_bind(end);
     }
 
-    query.push_str(" ORDER BY date DESC");
+    query.push(" ORDER BY date DESC");
 
     if let Some(limit_val) = limit {
-        query.push_str(&format!(" LIMIT {}", limit_val));
+        query.push(" LIMIT ").push_bind(limit_val);
     }
 
-    let rows = sqlx::query(&query).fetch_all(pool).await?;
+    let rows = query.build().fetch_all(pool).await?;
 
     let mut summaries = Vec::new();
     for row in rows {
@@ -639,8 +2442,1477 @@ pub async fn get_daily_summaries(
             total_duration_seconds: row.get(5),
             created_at,
             updated_at,
+            stale: row.get(8),
         });
     }
 
     Ok(summaries)
 }
+
+// 插入或更新每月总结
+pub async fn upsert_monthly_summary(
+    pool: &SqlitePool,
+    month: &str, // YYYY-MM format
+    content: &str,
+    summary_count: i32,
+    total_duration_seconds: i64,
+    trends: &str, // JSON
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO monthly_summaries (month, content, summary_count, total_duration_seconds, trends, updated_at)
+        VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT(month) DO UPDATE SET
+            content = excluded.content,
+            summary_count = excluded.summary_count,
+            total_duration_seconds = excluded.total_duration_seconds,
+            trends = excluded.trends,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(month)
+    .bind(content)
+    .bind(summary_count)
+    .bind(total_duration_seconds)
+    .bind(trends)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+const MONTHLY_SUMMARY_COLUMNS: &str =
+    "id, month, content, summary_count, total_duration_seconds, trends, created_at, updated_at";
+
+fn monthly_summary_from_row(row: &SqliteRow) -> Result<MonthlySummary, sqlx::Error> {
+    let created_at_str: String = row.get(6);
+    let updated_at_str: String = row.get(7);
+
+    let created_at = parse_timestamp(&created_at_str)
+        .map_err(|e| sqlx::Error::Decode(format!("Invalid created_at format: {}", e).into()))?;
+    let updated_at = parse_timestamp(&updated_at_str)
+        .map_err(|e| sqlx::Error::Decode(format!("Invalid updated_at format: {}", e).into()))?;
+
+    Ok(MonthlySummary {
+        id: row.get(0),
+        month: row.get(1),
+        content: row.get(2),
+        summary_count: row.get(3),
+        total_duration_seconds: row.get(4),
+        trends: row
+            .get::<Option<String>, _>(5)
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        created_at,
+        updated_at,
+    })
+}
+
+// 获取每月总结
+pub async fn get_monthly_summary(
+    pool: &SqlitePool,
+    month: &str, // YYYY-MM format
+) -> Result<Option<MonthlySummary>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM monthly_summaries WHERE month = ?",
+        MONTHLY_SUMMARY_COLUMNS
+    ))
+    .bind(month)
+    .fetch_optional(pool)
+    .await?;
+
+    row.as_ref().map(monthly_summary_from_row).transpose()
+}
+
+// 获取最近的每月总结（按月份倒序）
+pub async fn get_monthly_summaries(
+    pool: &SqlitePool,
+    limit: Option<i64>,
+) -> Result<Vec<MonthlySummary>, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new(format!(
+        "SELECT {} FROM monthly_summaries ORDER BY month DESC",
+        MONTHLY_SUMMARY_COLUMNS
+    ));
+    if let Some(limit_val) = limit {
+        query.push(" LIMIT ").push_bind(limit_val);
+    }
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    rows.iter().map(monthly_summary_from_row).collect()
+}
+
+// 总结视频记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Video {
+    pub id: i64,
+    pub file_path: String,
+    pub summary_id: Option<i64>,
+    pub file_size: i64,
+    pub created_at: DateTime<Local>,
+    pub deleted_at: Option<DateTime<Local>>,
+    pub start_time: Option<DateTime<Local>>, // 视频覆盖的第一张截图时间
+    pub end_time: Option<DateTime<Local>>,   // 视频覆盖的最后一张截图时间
+    pub screenshot_count: i32,               // 视频帧数（每帧一张截图）
+    pub retry_count: i32,                    // 总结失败后重新上传的次数
+    pub duration_seconds: f64,               // 视频播放时长
+}
+
+// 插入视频记录
+pub async fn insert_video(
+    pool: &SqlitePool,
+    file_path: &str,
+    file_size: i64,
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+    screenshot_count: i32,
+    duration_seconds: f64,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query(
+        r#"
+        INSERT INTO videos (file_path, file_size, created_at, start_time, end_time, screenshot_count, duration_seconds)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(file_path)
+    .bind(file_size)
+    .bind(to_db_time(&Local::now()))
+    .bind(to_db_time(&start_time))
+    .bind(to_db_time(&end_time))
+    .bind(screenshot_count)
+    .bind(duration_seconds)
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+// 关联视频和摘要
+pub async fn link_video_to_summary(
+    pool: &SqlitePool,
+    video_id: i64,
+    summary_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE videos SET summary_id = ? WHERE id = ?")
+        .bind(summary_id)
+        .bind(video_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 标记视频文件已删除（保留记录用于审计）
+pub async fn mark_video_deleted(pool: &SqlitePool, video_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE videos SET deleted_at = ? WHERE id = ?")
+        .bind(to_db_time(&Local::now()))
+        .bind(video_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 增加视频的重试次数
+pub async fn increment_video_retry_count(
+    pool: &SqlitePool,
+    video_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE videos SET retry_count = retry_count + 1 WHERE id = ?")
+        .bind(video_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+const VIDEO_COLUMNS: &str = "id, file_path, summary_id, file_size, created_at, deleted_at, start_time, end_time, screenshot_count, retry_count, duration_seconds";
+
+// 把查询结果行转换为视频记录
+fn video_from_row(row: &SqliteRow) -> Result<Video, sqlx::Error> {
+    let created_at_str: String = row.get(4);
+    let created_at = parse_timestamp(&created_at_str)
+        .map_err(|e| sqlx::Error::Decode(format!("Invalid created_at format: {}", e).into()))?;
+
+    let parse_optional =
+        |index: usize, column: &str| -> Result<Option<DateTime<Local>>, sqlx::Error> {
+            let value: Option<String> = row.get(index);
+            value.map(|s| parse_timestamp(&s)).transpose().map_err(|e| {
+                sqlx::Error::Decode(format!("Invalid {} format: {}", column, e).into())
+            })
+        };
+
+    Ok(Video {
+        id: row.get(0),
+        file_path: row.get(1),
+        summary_id: row.get(2),
+        file_size: row.get(3),
+        created_at,
+        deleted_at: parse_optional(5, "deleted_at")?,
+        start_time: parse_optional(6, "start_time")?,
+        end_time: parse_optional(7, "end_time")?,
+        screenshot_count: row.get(8),
+        retry_count: row.get(9),
+        duration_seconds: row.get(10),
+    })
+}
+
+// 查询等待重试的视频：已编码但总结失败、文件未删除、重试次数未超过上限
+pub async fn get_pending_videos(
+    pool: &SqlitePool,
+    max_retries: i32,
+) -> Result<Vec<Video>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM videos WHERE summary_id IS NULL AND deleted_at IS NULL AND start_time IS NOT NULL AND retry_count < ? ORDER BY start_time ASC",
+        VIDEO_COLUMNS
+    ))
+    .bind(max_retries)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(video_from_row).collect()
+}
+
+// 查询视频记录
+// before: 只返回此时间之前创建的视频；only_existing: 只返回未删除的视频
+pub async fn get_videos(
+    pool: &SqlitePool,
+    before: Option<DateTime<Local>>,
+    only_existing: bool,
+    limit: Option<i64>,
+) -> Result<Vec<Video>, sqlx::Error> {
+    let mut query =
+        QueryBuilder::<Sqlite>::new(format!("SELECT {} FROM videos WHERE 1=1", VIDEO_COLUMNS));
+
+    if let Some(before) = before {
+        query
+            .push(" AND created_at < ")
+            .push_bind(to_db_time(&before));
+    }
+    if only_existing {
+        query.push(" AND deleted_at IS NULL");
+    }
+
+    query.push(" ORDER BY created_at DESC");
+
+    if let Some(limit_val) = limit {
+        query.push(" LIMIT ").push_bind(limit_val);
+    }
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    rows.iter().map(video_from_row).collect()
+}
+
+// 查询已关联摘要且文件仍存在的总结视频，可按摘要 ID 过滤
+pub async fn get_summary_videos(
+    pool: &SqlitePool,
+    summary_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<Video>, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new(format!(
+        "SELECT {} FROM videos WHERE summary_id IS NOT NULL AND deleted_at IS NULL",
+        VIDEO_COLUMNS
+    ));
+
+    if let Some(summary_id) = summary_id {
+        query.push(" AND summary_id = ").push_bind(summary_id);
+    }
+
+    query.push(" ORDER BY start_time DESC");
+
+    if let Some(limit_val) = limit {
+        query.push(" LIMIT ").push_bind(limit_val);
+    }
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    rows.iter().map(video_from_row).collect()
+}
+
+// 总结任务（一个时间段的截图等待生成摘要）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryJob {
+    pub id: i64,
+    pub start_time: DateTime<Local>,
+    pub end_time: DateTime<Local>,
+    pub status: String, // "pending", "running", "done" or "failed"
+    pub attempts: i32,  // 已失败的次数
+    pub error_message: Option<String>,
+    pub summary_id: Option<i64>,
+    pub created_at: DateTime<Local>,
+    pub updated_at: DateTime<Local>,
+}
+
+const SUMMARY_JOB_COLUMNS: &str =
+    "id, start_time, end_time, status, attempts, error_message, summary_id, created_at, updated_at";
+
+// 把查询结果行转换为总结任务
+fn summary_job_from_row(row: &SqliteRow) -> Result<SummaryJob, sqlx::Error> {
+    let parse = |index: usize, column: &str| -> Result<DateTime<Local>, sqlx::Error> {
+        let value: String = row.get(index);
+        parse_timestamp(&value)
+            .map_err(|e| sqlx::Error::Decode(format!("Invalid {} format: {}", column, e).into()))
+    };
+
+    Ok(SummaryJob {
+        id: row.get(0),
+        start_time: parse(1, "start_time")?,
+        end_time: parse(2, "end_time")?,
+        status: row.get(3),
+        attempts: row.get(4),
+        error_message: row.get(5),
+        summary_id: row.get(6),
+        created_at: parse(7, "created_at")?,
+        updated_at: parse(8, "updated_at")?,
+    })
+}
+
+// 添加总结任务
+pub async fn insert_summary_job(
+    pool: &SqlitePool,
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+) -> Result<i64, sqlx::Error> {
+    let now = to_db_time(&Local::now());
+    let id = sqlx::query(
+        r#"
+        INSERT INTO summary_jobs (start_time, end_time, created_at, updated_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(to_db_time(&start_time))
+    .bind(to_db_time(&end_time))
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+// 领取最早的待处理任务并标记为处理中，没有任务时返回 None
+pub async fn claim_next_summary_job(pool: &SqlitePool) -> Result<Option<SummaryJob>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        UPDATE summary_jobs SET status = 'running', updated_at = ?
+        WHERE id = (SELECT id FROM summary_jobs WHERE status = 'pending' ORDER BY start_time ASC LIMIT 1)
+        RETURNING {}
+        "#,
+        SUMMARY_JOB_COLUMNS
+    ))
+    .bind(to_db_time(&Local::now()))
+    .fetch_optional(pool)
+    .await?;
+
+    row.as_ref().map(summary_job_from_row).transpose()
+}
+
+// 标记任务完成（时间段内没有截图时 summary_id 为空）
+pub async fn complete_summary_job(
+    pool: &SqlitePool,
+    job_id: i64,
+    summary_id: Option<i64>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE summary_jobs SET status = 'done', summary_id = ?, error_message = NULL, updated_at = ? WHERE id = ?",
+    )
+    .bind(summary_id)
+    .bind(to_db_time(&Local::now()))
+    .bind(job_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 记录任务失败：失败次数未达到上限时重新排队，否则标记为 failed
+pub async fn fail_summary_job(
+    pool: &SqlitePool,
+    job_id: i64,
+    error_message: &str,
+    max_attempts: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE summary_jobs
+        SET attempts = attempts + 1,
+            status = CASE WHEN attempts + 1 >= ? THEN 'failed' ELSE 'pending' END,
+            error_message = ?,
+            updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(max_attempts)
+    .bind(error_message)
+    .bind(to_db_time(&Local::now()))
+    .bind(job_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 把任务放回队列，不计入失败次数（如 AI 服务暂时不可用或网络断开）
+pub async fn release_summary_job(
+    pool: &SqlitePool,
+    job_id: i64,
+    error_message: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE summary_jobs
+        SET status = 'pending', error_message = COALESCE(?, error_message), updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(error_message)
+    .bind(to_db_time(&Local::now()))
+    .bind(job_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 程序退出时正在处理的任务重新排队，返回恢复的任务数量
+pub async fn requeue_running_summary_jobs(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE summary_jobs SET status = 'pending', updated_at = ? WHERE status = 'running'",
+    )
+    .bind(to_db_time(&Local::now()))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+// 查询总结任务，可按状态过滤
+pub async fn get_summary_jobs(
+    pool: &SqlitePool,
+    status: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Vec<SummaryJob>, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new(format!(
+        "SELECT {} FROM summary_jobs WHERE 1=1",
+        SUMMARY_JOB_COLUMNS
+    ));
+
+    if let Some(status) = status {
+        query.push(" AND status = ").push_bind(status);
+    }
+
+    query.push(" ORDER BY start_time DESC");
+
+    if let Some(limit_val) = limit {
+        query.push(" LIMIT ").push_bind(limit_val);
+    }
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    rows.iter().map(summary_job_from_row).collect()
+}
+
+// Gemini API key
+#[derive(Debug, Clone)]
+pub struct GeminiApiKey {
+    pub id: i64,
+    pub api_key: String,
+    pub label: Option<String>,
+    pub priority: i64,
+    pub last_quota_error_at: Option<DateTime<Local>>, // 最近一次 429 / 配额错误的时间
+}
+
+// 查询所有 Gemini API key（按优先级排序）
+pub async fn get_gemini_api_keys(pool: &SqlitePool) -> Result<Vec<GeminiApiKey>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, api_key, label, priority, last_quota_error_at FROM gemini_api_keys ORDER BY priority ASC, id ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut keys = Vec::new();
+    for row in rows {
+        let last_quota_error_at: Option<String> = row.get(4);
+        keys.push(GeminiApiKey {
+            id: row.get(0),
+            api_key: row.get(1),
+            label: row.get(2),
+            priority: row.get(3),
+            last_quota_error_at: last_quota_error_at.and_then(|s| parse_timestamp(&s).ok()),
+        });
+    }
+
+    Ok(keys)
+}
+
+// 添加 Gemini API key
+pub async fn insert_gemini_api_key(
+    pool: &SqlitePool,
+    api_key: &str,
+    label: Option<&str>,
+    priority: i64,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query(
+        r#"
+        INSERT INTO gemini_api_keys (api_key, label, priority, created_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(api_key)
+    .bind(label)
+    .bind(priority)
+    .bind(to_db_time(&Local::now()))
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+// 删除 Gemini API key
+pub async fn delete_gemini_api_key(pool: &SqlitePool, key_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM gemini_api_keys WHERE id = ?")
+        .bind(key_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 更新 Gemini API key 的优先级（数值越小越先使用）
+pub async fn update_gemini_api_key_priority(
+    pool: &SqlitePool,
+    key_id: i64,
+    priority: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE gemini_api_keys SET priority = ? WHERE id = ?")
+        .bind(priority)
+        .bind(key_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 更新 Gemini API key 在数据库中保存的值（迁移到钥匙串时替换为占位符）
+pub async fn update_gemini_api_key_value(
+    pool: &SqlitePool,
+    key_id: i64,
+    api_key: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE gemini_api_keys SET api_key = ? WHERE id = ?")
+        .bind(api_key)
+        .bind(key_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 记录 Gemini API key 遇到 429 / 配额错误的时间
+pub async fn mark_gemini_api_key_quota_error(
+    pool: &SqlitePool,
+    key_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE gemini_api_keys SET last_quota_error_at = ? WHERE id = ?")
+        .bind(to_db_time(&Local::now()))
+        .bind(key_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 单个 API key 的用量统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyUsage {
+    pub api_key_id: i64,
+    pub total_requests: i64,
+    pub failed_requests: i64,
+    pub total_tokens: i64,
+}
+
+// 按 API key 统计请求数和 token 用量
+pub async fn get_api_key_usage(
+    pool: &SqlitePool,
+    start_time: Option<DateTime<Local>>,
+) -> Result<Vec<ApiKeyUsage>, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new(
+        "SELECT
+            api_key_id,
+            COUNT(*) as total_requests,
+            COALESCE(SUM(CASE WHEN success = 0 THEN 1 ELSE 0 END), 0) as failed_requests,
+            COALESCE(SUM(total_tokens), 0) as total_tokens
+        FROM api_requests WHERE api_key_id IS NOT NULL",
+    );
+
+    if let Some(start) = start_time {
+        query
+            .push(" AND timestamp >= ")
+            .push_bind(to_db_time(&start));
+    }
+
+    query.push(" GROUP BY api_key_id");
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| ApiKeyUsage {
+            api_key_id: row.get(0),
+            total_requests: row.get(1),
+            failed_requests: row.get(2),
+            total_tokens: row.get(3),
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Goal {
+    pub id: i64,
+    pub label: String,
+    pub metric: String, // "category" 某个分类的时长，"focus_time" 专注时长
+    pub category: Option<String>, // metric 为 "category" 时使用
+    pub comparison: String, // "at_least" 或 "at_most"
+    pub target_minutes: i64, // 每天的目标分钟数
+    pub enabled: bool,
+    pub created_at: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalResult {
+    pub goal_id: i64,
+    pub date: String, // YYYY-MM-DD
+    pub actual_minutes: i64,
+    pub achieved: bool,
+    pub evaluated_at: DateTime<Local>,
+}
+
+// 查询所有目标
+pub async fn get_goals(pool: &SqlitePool) -> Result<Vec<Goal>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, label, metric, category, comparison, target_minutes, enabled, created_at FROM goals ORDER BY id ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut goals = Vec::new();
+    for row in rows {
+        let created_at_str: String = row.get(7);
+        let created_at = parse_timestamp(&created_at_str)
+            .map_err(|e| sqlx::Error::Decode(format!("Invalid created_at format: {}", e).into()))?;
+        goals.push(Goal {
+            id: row.get(0),
+            label: row.get(1),
+            metric: row.get(2),
+            category: row.get(3),
+            comparison: row.get(4),
+            target_minutes: row.get(5),
+            enabled: row.get::<i64, _>(6) != 0,
+            created_at,
+        });
+    }
+
+    Ok(goals)
+}
+
+// 添加目标
+pub async fn insert_goal(
+    pool: &SqlitePool,
+    label: &str,
+    metric: &str,
+    category: Option<&str>,
+    comparison: &str,
+    target_minutes: i64,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query(
+        r#"
+        INSERT INTO goals (label, metric, category, comparison, target_minutes, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(label)
+    .bind(metric)
+    .bind(category)
+    .bind(comparison)
+    .bind(target_minutes)
+    .bind(to_db_time(&Local::now()))
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+// 启用或停用目标
+pub async fn update_goal_enabled(
+    pool: &SqlitePool,
+    goal_id: i64,
+    enabled: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE goals SET enabled = ? WHERE id = ?")
+        .bind(enabled as i64)
+        .bind(goal_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 删除目标（同时删除评估结果）
+pub async fn delete_goal(pool: &SqlitePool, goal_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM goal_results WHERE goal_id = ?")
+        .bind(goal_id)
+        .execute(pool)
+        .await?;
+
+    sqlx::query("DELETE FROM goals WHERE id = ?")
+        .bind(goal_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 保存目标在某一天的评估结果（重新评估时覆盖）
+pub async fn upsert_goal_result(
+    pool: &SqlitePool,
+    goal_id: i64,
+    date: &str, // YYYY-MM-DD format
+    actual_minutes: i64,
+    achieved: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO goal_results (goal_id, date, actual_minutes, achieved, evaluated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(goal_id, date) DO UPDATE SET
+            actual_minutes = excluded.actual_minutes,
+            achieved = excluded.achieved,
+            evaluated_at = excluded.evaluated_at
+        "#,
+    )
+    .bind(goal_id)
+    .bind(date)
+    .bind(actual_minutes)
+    .bind(achieved as i64)
+    .bind(to_db_time(&Local::now()))
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 查询某一天所有目标的评估结果
+pub async fn get_goal_results(
+    pool: &SqlitePool,
+    date: &str,
+) -> Result<Vec<GoalResult>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT goal_id, date, actual_minutes, achieved, evaluated_at FROM goal_results WHERE date = ?",
+    )
+    .bind(date)
+    .fetch_all(pool)
+    .await?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        let evaluated_at_str: String = row.get(4);
+        let evaluated_at = parse_timestamp(&evaluated_at_str).map_err(|e| {
+            sqlx::Error::Decode(format!("Invalid evaluated_at format: {}", e).into())
+        })?;
+        results.push(GoalResult {
+            goal_id: row.get(0),
+            date: row.get(1),
+            actual_minutes: row.get(2),
+            achieved: row.get::<i64, _>(3) != 0,
+            evaluated_at,
+        });
+    }
+
+    Ok(results)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakReminder {
+    pub id: i64,
+    pub session_start: DateTime<Local>, // 这段连续活动的开始时间
+    pub reminded_at: DateTime<Local>,
+    pub break_started_at: Option<DateTime<Local>>, // 之后开始休息的时间，没有休息时为 None
+}
+
+// 记录一次休息提醒
+pub async fn insert_break_reminder(
+    pool: &SqlitePool,
+    session_start: DateTime<Local>,
+    reminded_at: DateTime<Local>,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query("INSERT INTO break_reminders (session_start, reminded_at) VALUES (?, ?)")
+        .bind(to_db_time(&session_start))
+        .bind(to_db_time(&reminded_at))
+        .execute(pool)
+        .await?
+        .last_insert_rowid();
+
+    Ok(id)
+}
+
+// 一段连续活动结束时，记录这段活动中的提醒之后开始休息的时间
+pub async fn mark_break_taken(
+    pool: &SqlitePool,
+    session_start: DateTime<Local>,
+    break_started_at: DateTime<Local>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE break_reminders SET break_started_at = ? WHERE session_start = ? AND break_started_at IS NULL",
+    )
+    .bind(to_db_time(&break_started_at))
+    .bind(to_db_time(&session_start))
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 查询时间范围内的休息提醒
+pub async fn get_break_reminders(
+    pool: &SqlitePool,
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+) -> Result<Vec<BreakReminder>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, session_start, reminded_at, break_started_at FROM break_reminders WHERE reminded_at >= ? AND reminded_at <= ? ORDER BY reminded_at ASC",
+    )
+    .bind(to_db_time(&start_time))
+    .bind(to_db_time(&end_time))
+    .fetch_all(pool)
+    .await?;
+
+    let mut reminders = Vec::new();
+    for row in rows {
+        let session_start_str: String = row.get(1);
+        let reminded_at_str: String = row.get(2);
+        let break_started_at: Option<String> = row.get(3);
+        reminders.push(BreakReminder {
+            id: row.get(0),
+            session_start: parse_timestamp(&session_start_str).map_err(|e| {
+                sqlx::Error::Decode(format!("Invalid session_start format: {}", e).into())
+            })?,
+            reminded_at: parse_timestamp(&reminded_at_str).map_err(|e| {
+                sqlx::Error::Decode(format!("Invalid reminded_at format: {}", e).into())
+            })?,
+            break_started_at: break_started_at.and_then(|s| parse_timestamp(&s).ok()),
+        });
+    }
+
+    Ok(reminders)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelComparisonResult {
+    pub id: i64,
+    pub summary_id: i64,
+    pub model: String,
+    pub role: String, // "primary" 当前使用的模型，"comparison" 对比模型
+    pub content: String,
+    pub structured_data: Option<serde_json::Value>,
+    pub created_at: DateTime<Local>,
+}
+
+// 保存一个模型的对比结果
+pub async fn insert_model_comparison(
+    pool: &SqlitePool,
+    summary_id: i64,
+    model: &str,
+    role: &str,
+    content: &str,
+    structured_data: Option<&str>,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query(
+        r#"
+        INSERT INTO model_comparisons (summary_id, model, role, content, structured_data, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(summary_id)
+    .bind(model)
+    .bind(role)
+    .bind(content)
+    .bind(structured_data)
+    .bind(to_db_time(&Local::now()))
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+// 查询最近的模型对比结果（按摘要 ID 倒序，limit 限制摘要数量）
+pub async fn get_model_comparisons(
+    pool: &SqlitePool,
+    limit: Option<i64>,
+) -> Result<Vec<ModelComparisonResult>, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new(
+        "SELECT id, summary_id, model, role, content, structured_data, created_at FROM model_comparisons",
+    );
+    if let Some(limit_val) = limit {
+        query
+            .push(" WHERE summary_id IN (SELECT DISTINCT summary_id FROM model_comparisons ORDER BY summary_id DESC LIMIT ")
+            .push_bind(limit_val)
+            .push(")");
+    }
+    query.push(" ORDER BY summary_id DESC, id ASC");
+
+    let rows = query.build().fetch_all(pool).await?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        let created_at_str: String = row.get(6);
+        let created_at = parse_timestamp(&created_at_str)
+            .map_err(|e| sqlx::Error::Decode(format!("Invalid created_at format: {}", e).into()))?;
+        results.push(ModelComparisonResult {
+            id: row.get(0),
+            summary_id: row.get(1),
+            model: row.get(2),
+            role: row.get(3),
+            content: row.get(4),
+            structured_data: row
+                .get::<Option<String>, _>(5)
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            created_at,
+        });
+    }
+
+    Ok(results)
+}
+
+// 录制空白时间段（程序在录制期间异常退出，或检测到会议时自动暂停 / 只保存在本地）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingGap {
+    pub id: i64,
+    pub start_time: DateTime<Local>,
+    pub end_time: DateTime<Local>,
+    pub reason: String, // "crash" or "meeting"
+}
+
+// 添加录制空白时间段
+pub async fn insert_recording_gap(
+    pool: &SqlitePool,
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+    reason: &str,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query(
+        r#"
+        INSERT INTO recording_gaps (start_time, end_time, reason, created_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(to_db_time(&start_time))
+    .bind(to_db_time(&end_time))
+    .bind(reason)
+    .bind(to_db_time(&Local::now()))
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+// 查询与时间范围有重叠的录制空白时间段
+pub async fn get_recording_gaps(
+    pool: &SqlitePool,
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+) -> Result<Vec<RecordingGap>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, start_time, end_time, reason FROM recording_gaps WHERE end_time >= ? AND start_time <= ? ORDER BY start_time",
+    )
+    .bind(to_db_time(&start_time))
+    .bind(to_db_time(&end_time))
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let parse = |index: usize, column: &str| -> Result<DateTime<Local>, sqlx::Error> {
+                let value: String = row.get(index);
+                parse_timestamp(&value).map_err(|e| {
+                    sqlx::Error::Decode(format!("Invalid {} format: {}", column, e).into())
+                })
+            };
+            Ok(RecordingGap {
+                id: row.get(0),
+                start_time: parse(1, "start_time")?,
+                end_time: parse(2, "end_time")?,
+                reason: row.get(3),
+            })
+        })
+        .collect()
+}
+
+// 按开始时间的本地日期统计某种录制空白的总时长（秒），返回 (YYYY-MM-DD, 秒数)
+pub async fn get_daily_gap_seconds(
+    pool: &SqlitePool,
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+    reason: &str,
+) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT date(start_time, 'localtime') AS day, COALESCE(SUM(CAST(ROUND((julianday(end_time) - julianday(start_time)) * 86400) AS INTEGER)), 0) FROM recording_gaps WHERE start_time >= ? AND start_time <= ? AND reason = ? GROUP BY day",
+    )
+    .bind(to_db_time(&start_time))
+    .bind(to_db_time(&end_time))
+    .bind(reason)
+    .fetch_all(pool)
+    .await
+}
+
+// Webhook：摘要或每日总结保存后推送 JSON 到 url，用 secret 签名
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    #[serde(skip_serializing)] // 只在添加时或调用 get_webhook_secret 时返回给前端
+    pub secret: String,
+    pub events: Vec<String>, // "summary.created" / "daily_summary.created"
+    pub enabled: bool,
+    pub created_at: DateTime<Local>,
+    pub last_delivery_at: Option<DateTime<Local>>,
+    pub last_status: Option<i64>, // 最近一次推送的 HTTP 状态码，连接失败时为空
+    pub last_error: Option<String>,
+}
+
+// 查询所有 webhook（按添加顺序）
+pub async fn get_webhooks(pool: &SqlitePool) -> Result<Vec<Webhook>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, url, secret, events, enabled, created_at, last_delivery_at, last_status, last_error FROM webhooks ORDER BY id ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let parse = |value: String, column: &str| -> Result<DateTime<Local>, sqlx::Error> {
+                parse_timestamp(&value).map_err(|e| {
+                    sqlx::Error::Decode(format!("Invalid {} format: {}", column, e).into())
+                })
+            };
+            Ok(Webhook {
+                id: row.get(0),
+                url: row.get(1),
+                secret: row.get(2),
+                events: serde_json::from_str(&row.get::<String, _>(3)).unwrap_or_default(),
+                enabled: row.get::<i64, _>(4) != 0,
+                created_at: parse(row.get(5), "created_at")?,
+                last_delivery_at: row
+                    .get::<Option<String>, _>(6)
+                    .map(|value| parse(value, "last_delivery_at"))
+                    .transpose()?,
+                last_status: row.get(7),
+                last_error: row.get(8),
+            })
+        })
+        .collect()
+}
+
+// 添加 webhook，返回 ID
+pub async fn insert_webhook(
+    pool: &SqlitePool,
+    url: &str,
+    secret: &str,
+    events: &[String],
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query(
+        "INSERT INTO webhooks (url, secret, events, enabled, created_at) VALUES (?, ?, ?, 1, ?)",
+    )
+    .bind(url)
+    .bind(secret)
+    .bind(serde_json::to_string(events).unwrap_or_else(|_| "[]".into()))
+    .bind(to_db_time(&Local::now()))
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+// 修改 webhook 的地址、订阅的事件和是否启用
+pub async fn update_webhook(
+    pool: &SqlitePool,
+    webhook_id: i64,
+    url: &str,
+    events: &[String],
+    enabled: bool,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE webhooks SET url = ?, events = ?, enabled = ? WHERE id = ?")
+        .bind(url)
+        .bind(serde_json::to_string(events).unwrap_or_else(|_| "[]".into()))
+        .bind(enabled as i64)
+        .bind(webhook_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// 删除 webhook
+pub async fn delete_webhook(pool: &SqlitePool, webhook_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = ?")
+        .bind(webhook_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// 记录最近一次推送的结果
+pub async fn record_webhook_delivery(
+    pool: &SqlitePool,
+    webhook_id: i64,
+    status: Option<i64>,
+    error: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE webhooks SET last_delivery_at = ?, last_status = ?, last_error = ? WHERE id = ?",
+    )
+    .bind(to_db_time(&Local::now()))
+    .bind(status)
+    .bind(error)
+    .bind(webhook_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 后处理脚本：摘要或每日总结保存后运行 command，通过 stdin 传入 JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryHook {
+    pub id: i64,
+    pub command: String,
+    pub args: Vec<String>,
+    pub events: Vec<String>, // 和 webhook 相同的事件名
+    pub enabled: bool,
+    pub created_at: DateTime<Local>,
+    pub last_run_at: Option<DateTime<Local>>,
+    pub last_exit_code: Option<i64>, // 超时或无法启动时为空
+    pub last_error: Option<String>,
+}
+
+// 查询所有后处理脚本（按添加顺序，也是运行顺序）
+pub async fn get_summary_hooks(pool: &SqlitePool) -> Result<Vec<SummaryHook>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, command, args, events, enabled, created_at, last_run_at, last_exit_code, last_error FROM summary_hooks ORDER BY id ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let parse = |value: String, column: &str| -> Result<DateTime<Local>, sqlx::Error> {
+                parse_timestamp(&value).map_err(|e| {
+                    sqlx::Error::Decode(format!("Invalid {} format: {}", column, e).into())
+                })
+            };
+            Ok(SummaryHook {
+                id: row.get(0),
+                command: row.get(1),
+                args: serde_json::from_str(&row.get::<String, _>(2)).unwrap_or_default(),
+                events: serde_json::from_str(&row.get::<String, _>(3)).unwrap_or_default(),
+                enabled: row.get::<i64, _>(4) != 0,
+                created_at: parse(row.get(5), "created_at")?,
+                last_run_at: row
+                    .get::<Option<String>, _>(6)
+                    .map(|value| parse(value, "last_run_at"))
+                    .transpose()?,
+                last_exit_code: row.get(7),
+                last_error: row.get(8),
+            })
+        })
+        .collect()
+}
+
+// 添加后处理脚本，返回 ID
+pub async fn insert_summary_hook(
+    pool: &SqlitePool,
+    command: &str,
+    args: &[String],
+    events: &[String],
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query(
+        "INSERT INTO summary_hooks (command, args, events, enabled, created_at) VALUES (?, ?, ?, 1, ?)",
+    )
+    .bind(command)
+    .bind(serde_json::to_string(args).unwrap_or_else(|_| "[]".into()))
+    .bind(serde_json::to_string(events).unwrap_or_else(|_| "[]".into()))
+    .bind(to_db_time(&Local::now()))
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+// 修改后处理脚本的命令、参数、订阅的事件和是否启用
+pub async fn update_summary_hook(
+    pool: &SqlitePool,
+    hook_id: i64,
+    command: &str,
+    args: &[String],
+    events: &[String],
+    enabled: bool,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE summary_hooks SET command = ?, args = ?, events = ?, enabled = ? WHERE id = ?",
+    )
+    .bind(command)
+    .bind(serde_json::to_string(args).unwrap_or_else(|_| "[]".into()))
+    .bind(serde_json::to_string(events).unwrap_or_else(|_| "[]".into()))
+    .bind(enabled as i64)
+    .bind(hook_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// 删除后处理脚本
+pub async fn delete_summary_hook(pool: &SqlitePool, hook_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM summary_hooks WHERE id = ?")
+        .bind(hook_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// 记录最近一次运行的结果
+pub async fn record_summary_hook_run(
+    pool: &SqlitePool,
+    hook_id: i64,
+    exit_code: Option<i64>,
+    error: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE summary_hooks SET last_run_at = ?, last_exit_code = ?, last_error = ? WHERE id = ?",
+    )
+    .bind(to_db_time(&Local::now()))
+    .bind(exit_code)
+    .bind(error)
+    .bind(hook_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 不执行迁移的内存数据库，用来模拟旧版本留下的数据库
+    async fn bare_memory_db() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(SqliteConnectOptions::from_str("sqlite::memory:").unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn columns(pool: &SqlitePool, table: &str) -> Vec<String> {
+        sqlx::query(&format!("PRAGMA table_info({})", table))
+            .fetch_all(pool)
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get::<String, _>(1))
+            .collect()
+    }
+
+    async fn schema_versions(pool: &SqlitePool) -> Vec<(i64, String)> {
+        sqlx::query_as("SELECT version, applied_at FROM schema_version ORDER BY version")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    // 已经是最新版本时再次执行迁移不应该修改任何内容
+    #[tokio::test]
+    async fn rerunning_migrations_is_a_no_op() {
+        let pool = bare_memory_db().await;
+        run_migrations(&pool).await.unwrap();
+        let timestamp = "2024-03-10T01:30:00.000Z";
+        sqlx::query(
+            "INSERT INTO screenshot_traces (timestamp, file_path, width, height, file_size) VALUES (?, 'a.png', 10, 10, 100)",
+        )
+        .bind(timestamp)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let versions = schema_versions(&pool).await;
+        let schema: Vec<(String, Option<String>)> =
+            sqlx::query_as("SELECT name, sql FROM sqlite_master ORDER BY name")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(versions.len(), MIGRATIONS.len());
+
+        run_migrations(&pool).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        assert_eq!(schema_versions(&pool).await, versions);
+        let schema_after: Vec<(String, Option<String>)> =
+            sqlx::query_as("SELECT name, sql FROM sqlite_master ORDER BY name")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(schema_after, schema);
+        let (stored,): (String,) = sqlx::query_as("SELECT timestamp FROM screenshot_traces")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, timestamp);
+    }
+
+    // 引入迁移之前的数据库只添加过部分列，迁移时补齐缺少的列并保留原有数据
+    #[tokio::test]
+    async fn legacy_schema_gets_missing_columns() {
+        let pool = bare_memory_db().await;
+        for statement in [
+            // 缺少缩略图和鼠标位置
+            r#"
+            CREATE TABLE screenshot_traces (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                width INTEGER NOT NULL,
+                height INTEGER NOT NULL,
+                file_size INTEGER NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            // 已经有结构化数据，但缺少专注度
+            r#"
+            CREATE TABLE summaries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                start_time TEXT NOT NULL,
+                end_time TEXT NOT NULL,
+                content TEXT NOT NULL,
+                screenshot_count INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                structured_data TEXT
+            )
+            "#,
+            "INSERT INTO screenshot_traces (timestamp, file_path, width, height, file_size) VALUES ('2024-03-10T09:30:00+08:00', 'a.png', 10, 10, 100)",
+            r#"
+            INSERT INTO summaries (start_time, end_time, content, structured_data)
+            VALUES ('2024-03-10T09:30:00+08:00', '2024-03-10T09:45:00+08:00', 'Coding', '{"focusScore": 80}')
+            "#,
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        run_migrations(&pool).await.unwrap();
+
+        let trace_columns = columns(&pool, "screenshot_traces").await;
+        for column in ["thumbnail_path", "cursor_x", "cursor_y"] {
+            assert!(trace_columns.iter().any(|c| c == column), "{}", column);
+        }
+        let summary_columns = columns(&pool, "summaries").await;
+        assert!(summary_columns.iter().any(|c| c == "focus_score"));
+
+        let (content, focus_score): (String, Option<i64>) =
+            sqlx::query_as("SELECT content, focus_score FROM summaries")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(content, "Coding");
+        assert_eq!(focus_score, Some(80));
+        assert_eq!(
+            get_screenshot_traces(&pool, None, None, None)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(schema_versions(&pool).await.len(), MIGRATIONS.len());
+    }
+
+    // 旧版本按本地时区保存的 RFC3339 时间，迁移后应该和 to_db_time 的格式一致
+    #[tokio::test]
+    async fn local_offset_timestamps_migrate_to_utc() {
+        let pool = bare_memory_db().await;
+        // 只执行初始迁移，得到保存 UTC 时间之前的数据库
+        sqlx::query(
+            "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, description TEXT NOT NULL, applied_at TEXT NOT NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for statement in MIGRATIONS[0].statements {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (1, 'initial schema', '2024-03-10T09:30:00+08:00')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let local_times = ["2024-03-10T09:30:00+08:00", "2024-11-03T01:30:00.250-05:00"];
+        for timestamp in local_times {
+            sqlx::query(
+                "INSERT INTO screenshot_traces (timestamp, file_path, width, height, file_size) VALUES (?, 'a.png', 10, 10, 100)",
+            )
+            .bind(timestamp)
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO summaries (start_time, end_time, content) VALUES (?, ?, 'Coding')",
+            )
+            .bind(timestamp)
+            .bind(timestamp)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        run_migrations(&pool).await.unwrap();
+
+        let expected: Vec<String> = local_times
+            .iter()
+            .map(|t| {
+                to_db_time(
+                    &DateTime::parse_from_rfc3339(t)
+                        .unwrap()
+                        .with_timezone(&Local),
+                )
+            })
+            .collect();
+        let traces: Vec<(String,)> =
+            sqlx::query_as("SELECT timestamp FROM screenshot_traces ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            traces.into_iter().map(|(t,)| t).collect::<Vec<_>>(),
+            expected
+        );
+        let summaries: Vec<(String, String)> =
+            sqlx::query_as("SELECT start_time, end_time FROM summaries ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        for ((start_time, end_time), expected) in summaries.iter().zip(&expected) {
+            assert_eq!(start_time, expected);
+            assert_eq!(end_time, expected);
+        }
+        assert_eq!(schema_versions(&pool).await[0].1, expected[0]);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::db;
+use crate::events;
+use crate::state::AppState;
+use crate::tray;
+use chrono::NaiveDate;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+// 自定义 URL 协议：clarity://summary/123 打开摘要，clarity://day/2024-06-01 打开某一天
+pub const SCHEME: &str = "clarity";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DeepLink {
+    Summary(i64),
+    Day(NaiveDate),
+}
+
+// 前端要打开的位置：某一天的时间线，打开摘要时同时展开这条摘要
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepLinkTarget {
+    pub date: String, // YYYY-MM-DD
+    pub summary_id: Option<i64>,
+}
+
+pub fn summary_url(summary_id: i64) -> String {
+    format!("{}://summary/{}", SCHEME, summary_id)
+}
+
+pub fn day_url(date: NaiveDate) -> String {
+    format!("{}://day/{}", SCHEME, date.format("%Y-%m-%d"))
+}
+
+fn parse(url: &Url) -> Result<DeepLink, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Unsupported link: {}", url));
+    }
+    // clarity://summary/123 中 summary 是 host，123 是 path
+    let value = url.path().trim_matches('/');
+    match url.host_str().unwrap_or_default() {
+        "summary" => value
+            .parse()
+            .map(DeepLink::Summary)
+            .map_err(|_| format!("Invalid summary ID in link: {}", url)),
+        "day" => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(DeepLink::Day)
+            .map_err(|_| format!("Invalid date in link: {}", url)),
+        _ => Err(format!("Unsupported link: {}", url)),
+    }
+}
+
+async fn resolve(state: &AppState, link: DeepLink) -> Result<DeepLinkTarget, String> {
+    match link {
+        DeepLink::Summary(summary_id) => {
+            let summary = db::get_summary(&state.db_pool, summary_id)
+                .await
+                .map_err(|e| format!("Database error: {}", e))?
+                .ok_or_else(|| format!("Summary {} not found", summary_id))?;
+            Ok(DeepLinkTarget {
+                date: summary.start_time.format("%Y-%m-%d").to_string(),
+                summary_id: Some(summary_id),
+            })
+        }
+        DeepLink::Day(date) => Ok(DeepLinkTarget {
+            date: date.format("%Y-%m-%d").to_string(),
+            summary_id: None,
+        }),
+    }
+}
+
+// 打开链接：保存要打开的位置，显示窗口并通知前端（前端通过 take_deep_link 读取）
+// 同时收到多个链接时只打开最后一个
+fn open(app: &AppHandle, urls: Vec<Url>) {
+    let Some(url) = urls.into_iter().last() else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let target = match parse(&url) {
+            Ok(link) => resolve(&state, link).await,
+            Err(e) => Err(e),
+        };
+        let target = match target {
+            Ok(target) => target,
+            Err(e) => {
+                log::warn!("Ignoring deep link {}: {}", url, e);
+                return;
+            }
+        };
+
+        log::info!("Opening deep link {}", url);
+        *state.pending_deep_link.lock().await = Some(target.clone());
+        tray::show_main_window(&app);
+        if let Err(e) = app.emit(events::DEEP_LINK_OPENED, &target) {
+            log::warn!("Failed to emit deep link event: {}", e);
+        }
+    });
+}
+
+// 注册链接处理，需要在 AppState 注册之后调用
+pub fn setup(app: &AppHandle) {
+    // 开发模式和没有安装的 AppImage 不会注册协议，启动时注册一次（macOS 由 Info.plist 注册）
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("Failed to register {}:// links: {}", SCHEME, e);
+    }
+
+    let handle = app.clone();
+    app.deep_link()
+        .on_open_url(move |event| open(&handle, event.urls()));
+
+    // 通过链接启动程序时，链接在启动参数中
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => open(app, urls),
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to read startup deep link: {}", e),
+    }
+}
@@ -0,0 +1,104 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+// 命令返回给前端的错误
+// 序列化为 { kind, message, retryable }，前端按 kind 区分处理，不需要匹配错误字符串
+#[derive(Debug, thiserror::Error)]
+pub enum ClarityError {
+    #[error("{0}")]
+    Database(String),
+    // 网络断开或 AI 服务暂时不可用（5xx），恢复后重试
+    #[error("{0}")]
+    Network(String),
+    // AI 服务返回的错误（限流、配额、请求被拒绝、没有生成内容等）
+    #[error("{0}")]
+    Ai(String),
+    // 缺少屏幕录制等系统权限
+    #[error("{0}")]
+    Permission(String),
+    #[error("{0}")]
+    FfmpegMissing(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    InvalidInput(String),
+    // 当前状态不允许该操作，例如录制中切换配置
+    #[error("{0}")]
+    Conflict(String),
+    // 应用已锁定，需要先输入 PIN 解锁
+    #[error("{0}")]
+    Locked(String),
+    // 用户还没有同意截图或上传给 AI，需要先完成引导流程
+    #[error("{0}")]
+    ConsentRequired(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl ClarityError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClarityError::Database(_) => "database",
+            ClarityError::Network(_) => "network",
+            ClarityError::Ai(_) => "ai",
+            ClarityError::Permission(_) => "permission",
+            ClarityError::FfmpegMissing(_) => "ffmpegMissing",
+            ClarityError::NotFound(_) => "notFound",
+            ClarityError::InvalidInput(_) => "invalidInput",
+            ClarityError::Conflict(_) => "conflict",
+            ClarityError::Locked(_) => "locked",
+            ClarityError::ConsentRequired(_) => "consentRequired",
+            ClarityError::Internal(_) => "internal",
+        }
+    }
+
+    // 网络和 AI 服务的错误通常是暂时的，稍后重试可能成功
+    pub fn retryable(&self) -> bool {
+        matches!(self, ClarityError::Network(_) | ClarityError::Ai(_))
+    }
+
+    // AI 服务返回非 2xx 状态码时的错误
+    pub fn from_api_status(status: reqwest::StatusCode, message: String) -> Self {
+        if status.is_server_error() {
+            ClarityError::Network(message)
+        } else {
+            ClarityError::Ai(message)
+        }
+    }
+}
+
+impl Serialize for ClarityError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ClarityError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("retryable", &self.retryable())?;
+        state.end()
+    }
+}
+
+// 内部函数返回的字符串错误都是内部错误，其他类型的错误在产生的地方直接构造
+impl From<String> for ClarityError {
+    fn from(message: String) -> Self {
+        ClarityError::Internal(message)
+    }
+}
+
+impl From<&str> for ClarityError {
+    fn from(message: &str) -> Self {
+        ClarityError::from(message.to_string())
+    }
+}
+
+impl From<sqlx::Error> for ClarityError {
+    fn from(e: sqlx::Error) -> Self {
+        ClarityError::Database(format!("Database error: {}", e))
+    }
+}
+
+// 命令行和后台任务仍然使用字符串错误
+impl From<ClarityError> for String {
+    fn from(e: ClarityError) -> Self {
+        e.to_string()
+    }
+}
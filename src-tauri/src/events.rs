@@ -0,0 +1,101 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+// 发送给前端的数据变化事件，前端按事件内容局部更新，不需要每次重新查询全部统计
+pub const SCREENSHOT_CAPTURED: &str = "screenshot-captured";
+pub const SUMMARY_CREATED: &str = "summary-created";
+pub const SUMMARY_UPDATED: &str = "summary-updated";
+pub const SUMMARY_DELETED: &str = "summary-deleted";
+pub const API_REQUEST_RECORDED: &str = "api-request-recorded";
+// 通过 clarity:// 链接打开了某条记录，内容为 deep_link::DeepLinkTarget
+pub const DEEP_LINK_OPENED: &str = "deep-link-opened";
+// 应用锁定或解锁，内容为 commands::lock::AppLockStatus
+pub const APP_LOCK_CHANGED: &str = "app-lock-changed";
+// 隐身模式开启或关闭，内容为 commands::incognito::IncognitoStatus
+pub const INCOGNITO_CHANGED: &str = "incognito-changed";
+// 所有数据已清除，应用回到首次运行的状态，内容为 commands::wipe::WipeReport
+pub const DATA_WIPED: &str = "data-wiped";
+// 检测到会议或屏幕共享，或会议结束，内容为 meeting::MeetingStatus
+pub const MEETING_CHANGED: &str = "meeting-changed";
+// 引导流程前进或同意记录变化，内容为 onboarding::OnboardingState
+pub const ONBOARDING_CHANGED: &str = "onboarding-changed";
+
+// 摘要事件中附带的内容长度（字符数）
+const SUMMARY_EXCERPT_CHARS: usize = 120;
+
+// 新截图
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenshotCaptured {
+    pub id: Option<i64>, // 写入数据库失败时为空（文件已保存）
+    pub timestamp: DateTime<Local>,
+    pub file_path: String,
+    pub thumbnail_path: Option<String>,
+    pub width: i32,
+    pub height: i32,
+    pub file_size: i64,
+}
+
+// 新生成或重新生成的摘要
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryEvent {
+    pub id: i64,
+    pub start_time: DateTime<Local>,
+    pub end_time: DateTime<Local>,
+    pub excerpt: String, // 摘要开头的一段文字
+}
+
+impl SummaryEvent {
+    pub fn new(
+        id: i64,
+        start_time: DateTime<Local>,
+        end_time: DateTime<Local>,
+        content: &str,
+    ) -> Self {
+        Self {
+            id,
+            start_time,
+            end_time,
+            excerpt: excerpt(content),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryDeleted {
+    pub id: i64,
+}
+
+// 新记录的 AI 请求，前端直接累加到今天的 API 统计
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiRequestRecorded {
+    pub id: i64,
+    pub model: String,
+    pub success: bool,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub total_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
+    pub duration_ms: u64,
+}
+
+fn excerpt(content: &str) -> String {
+    let content = content.trim();
+    match content.char_indices().nth(SUMMARY_EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &content[..end]),
+        None => content.to_string(),
+    }
+}
+
+// 发送事件，没有窗口句柄（例如启动过程中）时忽略
+pub fn emit<S: Serialize + Clone>(app_handle: Option<&AppHandle>, event: &str, payload: S) {
+    if let Some(handle) = app_handle {
+        if let Err(e) = handle.emit(event, payload) {
+            log::warn!("Failed to emit {}: {}", event, e);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::sync::Mutex;
+
+// 后台任务名称，用于记录最近一次错误来自哪里
+pub const TASK_SCREENSHOT: &str = "screenshot";
+pub const TASK_SUMMARY_LOOP: &str = "summary_loop";
+pub const TASK_SUMMARY_WORKER: &str = "summary_worker";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskError {
+    pub task: String,
+    pub message: String,
+    pub at: DateTime<Local>,
+}
+
+#[derive(Debug, Default)]
+struct HealthRecord {
+    last_capture_at: Option<DateTime<Local>>,
+    last_summary_at: Option<DateTime<Local>>,
+    last_error: Option<TaskError>,
+    summary_loops: usize, // 正在运行的总结定时任务数（正常只有一个）
+    summary_worker_heartbeat: Option<DateTime<Local>>,
+}
+
+// 后台任务的运行情况，由各个任务自己更新，get_health 读取
+// 只在内存中保存，重启后清空
+#[derive(Debug, Default)]
+pub struct HealthMonitor {
+    record: Mutex<HealthRecord>,
+}
+
+impl HealthMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut HealthRecord)) {
+        if let Ok(mut record) = self.record.lock() {
+            f(&mut record);
+        }
+    }
+
+    pub fn record_capture(&self) {
+        self.update(|record| record.last_capture_at = Some(Local::now()));
+    }
+
+    pub fn record_summary(&self) {
+        self.update(|record| record.last_summary_at = Some(Local::now()));
+    }
+
+    pub fn record_error(&self, task: &str, message: impl ToString) {
+        self.update(|record| {
+            record.last_error = Some(TaskError {
+                task: task.to_string(),
+                message: message.to_string(),
+                at: Local::now(),
+            })
+        });
+    }
+
+    pub fn summary_loop_started(&self) {
+        self.update(|record| record.summary_loops += 1);
+    }
+
+    pub fn summary_loop_stopped(&self) {
+        self.update(|record| record.summary_loops = record.summary_loops.saturating_sub(1));
+    }
+
+    // 总结 worker 每轮轮询时调用，长时间没有更新说明 worker 已经退出或卡住
+    pub fn summary_worker_heartbeat(&self) {
+        self.update(|record| record.summary_worker_heartbeat = Some(Local::now()));
+    }
+
+    pub fn last_capture_at(&self) -> Option<DateTime<Local>> {
+        self.record.lock().ok()?.last_capture_at
+    }
+
+    pub fn last_summary_at(&self) -> Option<DateTime<Local>> {
+        self.record.lock().ok()?.last_summary_at
+    }
+
+    pub fn last_error(&self) -> Option<TaskError> {
+        self.record.lock().ok()?.last_error.clone()
+    }
+
+    pub fn summary_loop_running(&self) -> bool {
+        self.record
+            .lock()
+            .map(|record| record.summary_loops > 0)
+            .unwrap_or(false)
+    }
+
+    pub fn summary_worker_heartbeat_at(&self) -> Option<DateTime<Local>> {
+        self.record.lock().ok()?.summary_worker_heartbeat
+    }
+}
@@ -0,0 +1,246 @@
+use crate::db::{self, GeminiApiKey};
+use crate::error::ClarityError;
+use crate::profiles;
+use crate::settings;
+use sqlx::SqlitePool;
+
+// 钥匙串条目的服务名（与应用 identifier 一致）
+const KEYCHAIN_SERVICE: &str = "com.monsterstep.clarity";
+
+// 保存在钥匙串中的 key，数据库里只留下此前缀开头的占位符
+const KEYCHAIN_PLACEHOLDER_PREFIX: &str = "keychain:";
+
+// Gemini key 在钥匙串中的账户名
+fn gemini_account(key_id: i64) -> String {
+    profiles::keychain_account(&format!("gemini_api_key_{}", key_id))
+}
+
+fn is_placeholder(value: &str) -> bool {
+    value.starts_with(KEYCHAIN_PLACEHOLDER_PREFIX)
+}
+
+// 钥匙串操作是阻塞调用（Linux 上通过 D-Bus 访问 Secret Service），放到单独线程执行
+async fn run_blocking<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, keyring::Error> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Keychain task failed: {}", e))?
+        .map_err(|e| format!("Keychain error: {}", e))
+}
+
+async fn read_secret(account: String) -> Result<String, String> {
+    run_blocking(move || keyring::Entry::new(KEYCHAIN_SERVICE, &account)?.get_password()).await
+}
+
+async fn write_secret(account: String, secret: String) -> Result<(), String> {
+    run_blocking(move || keyring::Entry::new(KEYCHAIN_SERVICE, &account)?.set_password(&secret))
+        .await
+}
+
+async fn delete_secret(account: String) -> Result<(), String> {
+    run_blocking(move || {
+        match keyring::Entry::new(KEYCHAIN_SERVICE, &account)?.delete_credential() {
+            // 条目本来就不存在时视为删除成功
+            Err(keyring::Error::NoEntry) => Ok(()),
+            result => result,
+        }
+    })
+    .await
+}
+
+// 加密数据库的密码在钥匙串中的账户名
+const DATABASE_KEY_ACCOUNT: &str = "database_key";
+
+// 读取加密数据库的密码（数据库未加密时没有此条目，返回 None）
+pub async fn load_database_key() -> Option<String> {
+    let result = tokio::task::spawn_blocking(|| {
+        keyring::Entry::new(
+            KEYCHAIN_SERVICE,
+            &profiles::keychain_account(DATABASE_KEY_ACCOUNT),
+        )?
+        .get_password()
+    })
+    .await;
+
+    match result {
+        Ok(Ok(key)) => Some(key),
+        Ok(Err(keyring::Error::NoEntry)) => None,
+        Ok(Err(e)) => {
+            log::warn!("Failed to read database key from keychain: {}", e);
+            None
+        }
+        Err(e) => {
+            log::warn!("Keychain task failed: {}", e);
+            None
+        }
+    }
+}
+
+// 保存加密数据库的密码
+pub async fn save_database_key(key: &str) -> Result<(), String> {
+    write_secret(
+        profiles::keychain_account(DATABASE_KEY_ACCOUNT),
+        key.to_string(),
+    )
+    .await
+}
+
+// 删除指定配置的数据库密码（清除全部数据时删除其他配置的数据库后调用）
+pub async fn delete_profile_database_key(profile: &str) -> Result<(), String> {
+    delete_secret(profiles::profile_keychain_account(
+        profile,
+        DATABASE_KEY_ACCOUNT,
+    ))
+    .await
+}
+
+// 是否使用系统钥匙串保存 API key（默认开启）
+pub async fn is_enabled(pool: &SqlitePool) -> bool {
+    settings::load_use_os_keychain_from_db(pool)
+        .await
+        .unwrap_or(true)
+}
+
+// 查询所有 Gemini API key，保存在钥匙串中的 key 会替换为真实值
+pub async fn load_gemini_api_keys(pool: &SqlitePool) -> Result<Vec<GeminiApiKey>, ClarityError> {
+    let keys = db::get_gemini_api_keys(pool)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let mut resolved = Vec::with_capacity(keys.len());
+    for mut key in keys {
+        if is_placeholder(&key.api_key) {
+            match read_secret(gemini_account(key.id)).await {
+                Ok(secret) => key.api_key = secret,
+                Err(e) => {
+                    // 钥匙串不可用时跳过该 key，其他 key 仍可使用
+                    log::warn!("Failed to read Gemini key {} from keychain: {}", key.id, e);
+                    continue;
+                }
+            }
+        }
+        resolved.push(key);
+    }
+
+    Ok(resolved)
+}
+
+// 把数据库中明文保存的 key 移到钥匙串
+async fn move_to_keychain(
+    pool: &SqlitePool,
+    key_id: i64,
+    api_key: &str,
+) -> Result<(), ClarityError> {
+    write_secret(gemini_account(key_id), api_key.to_string()).await?;
+    db::update_gemini_api_key_value(
+        pool,
+        key_id,
+        &format!("{}{}", KEYCHAIN_PLACEHOLDER_PREFIX, key_id),
+    )
+    .await
+    .map_err(ClarityError::from)
+}
+
+// 把钥匙串中的 key 移回数据库
+async fn move_to_database(pool: &SqlitePool, key_id: i64) -> Result<(), ClarityError> {
+    let account = gemini_account(key_id);
+    let secret = read_secret(account.clone()).await?;
+    db::update_gemini_api_key_value(pool, key_id, &secret)
+        .await
+        .map_err(ClarityError::from)?;
+    delete_secret(account).await?;
+    Ok(())
+}
+
+// 添加 Gemini API key，开启钥匙串时只在数据库中保存占位符
+pub async fn add_gemini_api_key(
+    pool: &SqlitePool,
+    api_key: &str,
+    label: Option<&str>,
+    priority: i64,
+) -> Result<i64, ClarityError> {
+    let id = db::insert_gemini_api_key(pool, api_key, label, priority)
+        .await
+        .map_err(ClarityError::from)?;
+
+    if is_enabled(pool).await {
+        if let Err(e) = move_to_keychain(pool, id, api_key).await {
+            // 钥匙串不可用时保留在数据库中，下次启动会再次尝试迁移
+            log::warn!(
+                "Failed to store Gemini key in keychain, keeping it in database: {}",
+                e
+            );
+        }
+    }
+
+    Ok(id)
+}
+
+// 删除 Gemini API key（同时删除钥匙串中的条目）
+pub async fn delete_gemini_api_key(pool: &SqlitePool, key_id: i64) -> Result<(), ClarityError> {
+    db::delete_gemini_api_key(pool, key_id)
+        .await
+        .map_err(ClarityError::from)?;
+
+    if let Err(e) = delete_secret(gemini_account(key_id)).await {
+        log::warn!(
+            "Failed to delete Gemini key {} from keychain: {}",
+            key_id,
+            e
+        );
+    }
+
+    Ok(())
+}
+
+// 删除钥匙串中保存的所有 Gemini API key（清除全部数据时使用，数据库中的记录由调用方删除）
+// 返回 (删除的条目数, 删除失败的条目数)
+pub async fn delete_all_gemini_secrets(pool: &SqlitePool) -> Result<(u64, u64), ClarityError> {
+    let keys = db::get_gemini_api_keys(pool)
+        .await
+        .map_err(ClarityError::from)?;
+
+    let mut deleted = 0;
+    let mut failed = 0;
+    for key in keys.iter().filter(|key| is_placeholder(&key.api_key)) {
+        match delete_secret(gemini_account(key.id)).await {
+            Ok(()) => deleted += 1,
+            Err(e) => {
+                log::warn!(
+                    "Failed to delete Gemini key {} from keychain: {}",
+                    key.id,
+                    e
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    Ok((deleted, failed))
+}
+
+// 按设置在数据库和钥匙串之间迁移所有 Gemini API key
+pub async fn migrate_gemini_api_keys(
+    pool: &SqlitePool,
+    use_keychain: bool,
+) -> Result<(), ClarityError> {
+    let keys = db::get_gemini_api_keys(pool)
+        .await
+        .map_err(ClarityError::from)?;
+
+    for key in keys {
+        let in_keychain = is_placeholder(&key.api_key);
+        if use_keychain && !in_keychain {
+            move_to_keychain(pool, key.id, &key.api_key).await?;
+            log::info!("Moved Gemini key {} to OS keychain", key.id);
+        } else if !use_keychain && in_keychain {
+            move_to_database(pool, key.id).await?;
+            log::info!("Moved Gemini key {} from OS keychain to database", key.id);
+        }
+    }
+
+    Ok(())
+}
@@ -1,40 +1,144 @@
+mod api_server;
+mod app_usage;
+mod cli;
+mod color;
 mod commands;
 mod db;
+mod deep_link;
+mod error;
+mod events;
+mod health;
+mod keychain;
+mod locale;
+mod logging;
+mod meeting;
+mod ocr;
+mod ollama;
+mod onboarding;
+mod openai;
+mod openrouter;
+mod profiles;
+mod proxy;
+mod redaction;
 mod screenshot;
+mod sensitive;
 mod settings;
+mod shortcuts;
 mod state;
+mod tray;
 mod video_summary;
 
 use state::AppState;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::Manager;
 
+// 退出收尾是否已经开始 / 已经完成
+static SHUTDOWN_STARTED: AtomicBool = AtomicBool::new(false);
+static SHUTDOWN_FINISHED: AtomicBool = AtomicBool::new(false);
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // 初始化日志
-    env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Info)
-        .init();
+    // 带子命令启动时在命令行模式运行（例如 clarity --headless record），不创建窗口
+    if let Some(code) = cli::run(std::env::args().collect()) {
+        std::process::exit(code);
+    }
+
+    // 初始化日志：写入应用数据目录下的 logs，每天一个文件
+    logging::init();
 
     log::info!("Clarity application starting");
+    let ephemeral = cli::ephemeral_requested(&std::env::args().collect::<Vec<_>>());
 
     tauri::Builder::default()
+        // 必须最先注册：第二个实例启动时把参数（包括 clarity:// 链接）交给当前实例后退出
+        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            tray::show_main_window(app);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
-        .setup(|app| {
+        .plugin(tauri_plugin_autostart::init(
+            tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+            None,
+        ))
+        .plugin(tauri_plugin_notification::init())
+        .setup(move |app| {
             tauri::async_runtime::block_on(async {
                 log::info!("Initializing application state");
-                let app_state = AppState::new().await.map_err(|e| {
+                let app_state = cli::init_state(ephemeral).await.map_err(|e| {
                     Box::<dyn std::error::Error>::from(format!(
                         "Failed to initialize database: {}",
                         e
                     ))
                 })?;
 
+                // 应用保存的日志级别
+                let log_level = settings::load_log_level_from_db(&app_state.db_pool)
+                    .await
+                    .unwrap_or_else(|_| logging::DEFAULT_LOG_LEVEL.to_string());
+                logging::set_level(&log_level);
+
                 // 保存 app handle 用于发送事件
                 *app_state.app_handle.lock().await = Some(app.handle().clone());
 
+                // 启动总结任务 worker（整个程序运行期间只有一个，录制停止后仍会处理剩余任务）
+                commands::start_summary_worker(&app_state).await;
+
+                // 启动目标评估（每天结束后保存目标是否达成）
+                tauri::async_runtime::spawn(commands::goal_evaluation_loop(
+                    app_state.db_pool.clone(),
+                ));
+
+                // 启动保留策略（定期删除过期的截图、摘要和总结视频）
+                tauri::async_runtime::spawn(commands::retention_loop(app_state.db_pool.clone()));
+
+                // 启动存储对账（定期检查截图文件和数据库记录是否一致）
+                tauri::async_runtime::spawn(commands::reconcile_loop(
+                    app_state.db_pool.clone(),
+                    app_state.storage_path.clone(),
+                ));
+
+                // 启动每日总结自动生成（开启后在设置的时间生成当天的总结）
+                tauri::async_runtime::spawn(commands::auto_daily_summary_loop(
+                    app_state.db_pool.clone(),
+                    app_state.gemini_api_key.clone(),
+                    app_state.ai_model.clone(),
+                    app_state.incognito.clone(),
+                    app_state.ai_upload_consent.clone(),
+                    app.handle().clone(),
+                ));
+
+                // 启动数据库维护（每月自动整理一次数据库文件）
+                tauri::async_runtime::spawn(commands::database_maintenance_loop(
+                    app_state.db_pool.clone(),
+                    app_state.recording.subscribe(),
+                ));
+
+                // 启动 Markdown 自动导出（开启后每天结束时导出到设置的目录）
+                tauri::async_runtime::spawn(commands::markdown_export_loop(
+                    app_state.db_pool.clone(),
+                ));
+
+                // 启动总结定时任务（整个程序运行期间只有一个，录制时按间隔把时间段加入总结队列）
+                commands::start_summary_loop(&app_state).await;
+
                 log::info!("Application state initialized successfully");
                 app.manage(app_state);
+
+                // 按设置启动本地 REST API（默认关闭）
+                commands::start_api_server_if_enabled(app.handle()).await;
+
+                // 托盘图标：显示录制状态和今天的截图数量，提供录制控制
+                tray::setup_tray(app.handle())?;
+
+                // 处理 clarity:// 链接（包括通过链接启动时的链接）
+                deep_link::setup(app.handle());
+
+                // 全局快捷键：切换隐身模式
+                shortcuts::setup(app.handle())?;
+
+                // 按设置恢复上次退出时仍在进行的录制
+                commands::resume_recording_after_launch(&app.state::<AppState>()).await;
                 Ok(())
             })
         })
@@ -42,33 +146,174 @@ pub fn run() {
             commands::start_recording,
             commands::stop_recording,
             commands::get_status,
+            commands::pause_recording,
+            commands::resume_recording,
             commands::get_storage_path,
             commands::test_screenshot,
             commands::get_traces,
+            commands::delete_traces,
             commands::get_summaries,
             commands::add_summary,
             commands::get_today_count,
+            commands::get_settings,
+            commands::update_settings,
+            commands::get_recent_logs,
+            commands::get_health,
+            commands::export_settings,
+            commands::import_settings,
+            commands::get_api_server_settings,
+            commands::set_api_server_settings,
+            commands::regenerate_api_server_token,
+            commands::take_deep_link,
+            commands::get_app_lock_status,
+            commands::set_app_lock_pin,
+            commands::lock_app,
+            commands::unlock_app,
+            commands::get_incognito_mode,
+            commands::set_incognito_mode,
+            commands::wipe_all_data,
+            commands::get_redaction_rules,
+            commands::set_redaction_rules,
+            commands::get_onboarding_state,
+            commands::advance_onboarding,
+            commands::set_onboarding_consent,
             commands::get_gemini_api_key,
             commands::set_gemini_api_key,
-            commands::get_summary_interval,
-            commands::set_summary_interval,
+            commands::get_gemini_api_keys,
+            commands::add_gemini_api_key,
+            commands::remove_gemini_api_key,
+            commands::reorder_gemini_api_keys,
+            commands::get_use_os_keychain,
+            commands::set_use_os_keychain,
             commands::test_video_summary,
             commands::get_api_statistics,
+            commands::get_api_requests,
             commands::get_today_statistics,
-            commands::get_ai_model,
-            commands::set_ai_model,
+            commands::get_category_totals,
+            commands::get_activity_heatmap,
+            commands::get_streaks_and_trends,
+            commands::get_app_usage,
+            commands::get_top_apps,
+            commands::get_focus_scores,
+            commands::get_goals,
+            commands::add_goal,
+            commands::set_goal_enabled,
+            commands::delete_goal,
+            commands::get_goal_progress,
+            commands::get_break_reminder_settings,
+            commands::set_break_reminder_settings,
+            commands::get_break_stats,
+            commands::get_model_comparisons,
+            commands::get_ocr_settings,
+            commands::set_ocr_settings,
             commands::get_ai_prompt,
             commands::set_ai_prompt,
             commands::reset_ai_prompt,
-            commands::get_language,
-            commands::set_language,
             commands::generate_daily_summary,
             commands::get_daily_summary,
+            commands::get_auto_daily_summary_settings,
+            commands::set_auto_daily_summary_settings,
+            commands::get_notification_settings,
+            commands::set_notification_settings,
+            commands::generate_monthly_summary,
+            commands::get_monthly_summary,
+            commands::get_monthly_summaries,
             commands::get_historical_stats,
-            commands::get_video_resolution,
-            commands::set_video_resolution,
             commands::read_screenshot_file,
+            commands::get_autostart,
+            commands::set_autostart,
+            commands::get_video_retention,
+            commands::set_video_retention,
+            commands::cleanup_videos,
+            commands::get_retention_settings,
+            commands::set_retention_settings,
+            commands::run_retention,
+            commands::reconcile_storage,
+            commands::optimize_database,
+            commands::check_database_integrity,
+            commands::list_profiles,
+            commands::create_profile,
+            commands::switch_profile,
+            commands::get_summary_by_id,
+            commands::update_summary,
+            commands::delete_summary,
+            commands::get_tags,
+            commands::create_tag,
+            commands::delete_tag,
+            commands::add_summary_tag,
+            commands::remove_summary_tag,
+            commands::get_webhooks,
+            commands::get_webhook_secret,
+            commands::add_webhook,
+            commands::update_webhook,
+            commands::delete_webhook,
+            commands::test_webhook,
+            commands::get_summary_hooks,
+            commands::add_summary_hook,
+            commands::update_summary_hook,
+            commands::delete_summary_hook,
+            commands::test_summary_hook,
+            commands::get_summary_videos,
+            commands::export_timelapse,
+            commands::export_clip,
+            commands::export_data,
+            commands::import_data,
+            commands::create_backup,
+            commands::restore_backup,
+            commands::get_database_encryption,
+            commands::encrypt_database,
+            commands::export_markdown,
+            commands::generate_weekly_report,
+            commands::get_markdown_export_settings,
+            commands::set_markdown_export_settings,
+            commands::get_video_preset_estimates,
+            commands::get_openai_settings,
+            commands::set_openai_settings,
+            commands::get_openai_models,
+            commands::get_openrouter_settings,
+            commands::set_openrouter_settings,
+            commands::get_openrouter_models,
+            commands::get_ollama_settings,
+            commands::set_ollama_settings,
+            commands::get_ollama_models,
+            commands::get_summary_queue_settings,
+            commands::set_summary_queue_settings,
+            commands::get_summary_jobs,
+            commands::summarize_range,
+            commands::get_daily_budget,
+            commands::set_daily_budget,
+            commands::get_rolling_context,
+            commands::set_rolling_context,
+            commands::get_gemini_generation_settings,
+            commands::set_gemini_generation_settings,
+            commands::get_activity_categories,
+            commands::set_activity_categories,
+            commands::get_budget_status,
+            commands::regenerate_summary,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // 退出前先停止录制、写完数据，收尾完成后再真正退出（重启切换配置时已在切换前收尾）
+            if let tauri::RunEvent::ExitRequested { code, api, .. } = event {
+                if code == Some(tauri::RESTART_EXIT_CODE)
+                    || SHUTDOWN_FINISHED.load(Ordering::SeqCst)
+                {
+                    return;
+                }
+                api.prevent_exit();
+                if SHUTDOWN_STARTED.swap(true, Ordering::SeqCst) {
+                    return;
+                }
+
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(state) = app.try_state::<AppState>() {
+                        commands::shutdown(&state).await;
+                    }
+                    SHUTDOWN_FINISHED.store(true, Ordering::SeqCst);
+                    app.exit(0);
+                });
+            }
+        });
 }
@@ -0,0 +1,254 @@
+use crate::settings;
+use sqlx::SqlitePool;
+
+// 界面和提示词的语言：任意 BCP-47 标签，例如 "ja"、"de-AT"、"zh-Hant-TW"
+// 查找提示词时按 zh-Hant-TW → zh-Hant → zh → en 依次回退
+
+// 没有设置语言时使用
+pub const DEFAULT_LANGUAGE: &str = "zh";
+
+// 回退链的最后一项，英文模板中的 {language} 会替换为实际语言，其他语言也能得到对应语言的回答
+const FALLBACK_LANGUAGE: &str = "en";
+
+// 提示词和生成内容中用到的文字
+#[derive(Debug, Clone, Copy)]
+pub enum Template {
+    SummaryPrompt,          // 默认的摘要提示词
+    MergePrompt,            // {summaries}
+    DailyPrompt,            // {summaries}
+    DailyGoals,             // {progress}
+    DailyBreaks,            // {stats}
+    MonthlyPrompt,          // {month} {statistics} {summaries}
+    NoActivityDay,          //
+    NoActivityMonth,        //
+    PreviousSummaryContext, // {range} {content}
+    DailySummaryContext,    // {content}
+    ContextInstruction,     //
+    PrivateTime,            // 隐身模式时间段的摘要内容
+    MeetingTime,            // 会议或共享屏幕时间段的摘要内容
+}
+
+// 检查并规范化语言标签：语言小写、文字首字母大写、地区大写，"_" 换成 "-"（zh_cn → zh-CN）
+pub fn normalize(tag: &str) -> Result<String, String> {
+    let invalid = || format!("Invalid language tag: {}", tag);
+    let subtags: Vec<&str> = tag.trim().split(['-', '_']).collect();
+    let language = subtags[0];
+    if !(2..=8).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(invalid());
+    }
+
+    let mut result = vec![language.to_ascii_lowercase()];
+    for (index, subtag) in subtags.iter().enumerate().skip(1) {
+        if !(1..=8).contains(&subtag.len()) || !subtag.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(invalid());
+        }
+        let normalized = match subtag.len() {
+            // 文字，例如 Hant
+            4 if index == 1 && subtag.chars().all(|c| c.is_ascii_alphabetic()) => {
+                let lower = subtag.to_ascii_lowercase();
+                lower[..1].to_ascii_uppercase() + &lower[1..]
+            }
+            // 地区，例如 CN、419
+            2 if subtag.chars().all(|c| c.is_ascii_alphabetic()) => subtag.to_ascii_uppercase(),
+            3 if subtag.chars().all(|c| c.is_ascii_digit()) => subtag.to_string(),
+            _ => subtag.to_ascii_lowercase(),
+        };
+        result.push(normalized);
+    }
+    Ok(result.join("-"))
+}
+
+// 语言部分，例如 zh-Hant-TW → zh
+pub fn base_language(tag: &str) -> String {
+    tag.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+// 托盘、通知等只有中英文的文字：中文使用中文，其他语言使用英文
+pub fn is_chinese(tag: &str) -> bool {
+    base_language(tag) == "zh"
+}
+
+// 回退链：依次去掉最后一个子标签，最后是英文
+pub fn fallback_chain(tag: &str) -> Vec<String> {
+    let tag = normalize(tag).unwrap_or_else(|_| DEFAULT_LANGUAGE.to_string());
+    let subtags: Vec<&str> = tag.split('-').collect();
+    let mut chain: Vec<String> = (1..=subtags.len())
+        .rev()
+        .map(|n| subtags[..n].join("-"))
+        .collect();
+    if !chain.iter().any(|t| t == FALLBACK_LANGUAGE) {
+        chain.push(FALLBACK_LANGUAGE.to_string());
+    }
+    chain
+}
+
+// 语言的英文名称，用于英文模板中的 {language}
+pub fn language_name(tag: &str) -> String {
+    let name = match base_language(tag).as_str() {
+        "en" => "English",
+        "zh" => "Chinese",
+        "ja" => "Japanese",
+        "de" => "German",
+        "es" => "Spanish",
+        "fr" => "French",
+        "it" => "Italian",
+        "pt" => "Portuguese",
+        "ko" => "Korean",
+        "ru" => "Russian",
+        "nl" => "Dutch",
+        "pl" => "Polish",
+        "tr" => "Turkish",
+        "uk" => "Ukrainian",
+        "sv" => "Swedish",
+        "ar" => "Arabic",
+        "hi" => "Hindi",
+        "id" => "Indonesian",
+        "vi" => "Vietnamese",
+        "th" => "Thai",
+        _ => return format!("the language with the BCP-47 tag \"{}\"", tag),
+    };
+    name.to_string()
+}
+
+// 内置模板，按回退链中的标签查找，可以为某个地区单独添加（例如 "zh-Hant"）
+fn builtin(tag: &str, template: Template) -> Option<&'static str> {
+    use Template::*;
+    let text = match tag {
+        "en" => match template {
+            SummaryPrompt => "Analyze this screen activity video and provide a concise activity summary. Focus on: 1) Main apps/websites used; 2) Activity type (work/entertainment/learning, etc.); 3) Any distractions or inefficient behaviors. Respond in {language}, keep it under 100 words.",
+            MergePrompt => "The following are consecutive activity summaries covering one period of screen activity, in chronological order. Merge them into a single concise activity summary with the same focus: main apps/websites used, activity type, and any distractions or inefficient behaviors. Respond in {language}, keep it under 150 words.\n\nSummaries:\n{summaries}",
+            DailyPrompt => "Based on the following activity summaries from today, provide a comprehensive daily summary. Include: 1) Overall productivity assessment; 2) Main activities and time distribution; 3) Key insights and recommendations for improvement. Respond in {language}.\n\nToday's summaries:\n{summaries}",
+            DailyGoals => "\n\nGoal progress (comment on which goals were met or missed):\n{progress}",
+            DailyBreaks => "\n\nBreak habits (comment briefly on whether enough breaks were taken):\n{stats}",
+            MonthlyPrompt => "Based on the following daily summaries and computed statistics for {month}, write a higher-level monthly review. Include: 1) Overall productivity trend across the month; 2) How time was distributed between categories and which days were busiest; 3) Recurring patterns, habits or distractions; 4) Concrete recommendations for next month. Respond in {language}.\n\nStatistics (JSON):\n{statistics}\n\nDaily summaries:\n{summaries}",
+            NoActivityDay => "No activity recorded for this day.",
+            NoActivityMonth => "No activity recorded for this month.",
+            PreviousSummaryContext => "Summary of the preceding period ({range}):\n{content}",
+            DailySummaryContext => "Summary of the day so far:\n{content}",
+            ContextInstruction => "Context for continuity (do not summarize it again). If the current activity continues it, describe it as a continuation (e.g. \"continued working on the same task\").",
+            PrivateTime => "Private time (incognito mode, not summarized).",
+            MeetingTime => "Meeting or screen sharing (not summarized).",
+        },
+        "zh" => match template {
+            SummaryPrompt => "分析这段屏幕活动视频，提供简洁的活动摘要。重点关注：1) 主要使用的应用/网站；2) 活动类型（工作/娱乐/学习等）；3) 是否有分心或低效行为。用中文回答，控制在100字以内。",
+            MergePrompt => "以下是同一时间段内按时间顺序排列的多段屏幕活动摘要。请将它们合并为一条简洁的活动摘要，重点保持不变：主要使用的应用/网站、活动类型、是否有分心或低效行为。用中文回答，控制在150字以内。\n\n摘要：\n{summaries}",
+            DailyPrompt => "基于以下今天的所有活动摘要，生成一份综合的每日总结。包括：1) 整体效率评估；2) 主要活动和时间分布；3) 关键洞察和改进建议。\n\n今天的摘要：\n{summaries}",
+            DailyGoals => "\n\n目标进度（请评价哪些目标达成或未达成）：\n{progress}",
+            DailyBreaks => "\n\n休息情况（请简要评价休息是否充分）：\n{stats}",
+            MonthlyPrompt => "基于以下 {month} 的每日总结和统计数据，撰写一份更高层次的月度回顾。包括：1) 本月整体效率趋势；2) 各类活动的时间分布和最忙的日期；3) 反复出现的模式、习惯或分心行为；4) 下个月的具体改进建议。\n\n统计数据（JSON）：\n{statistics}\n\n每日总结：\n{summaries}",
+            NoActivityDay => "今天没有记录任何活动。",
+            NoActivityMonth => "本月没有记录任何活动。",
+            PreviousSummaryContext => "上一时间段（{range}）的摘要：\n{content}",
+            DailySummaryContext => "今天到目前为止的总结：\n{content}",
+            ContextInstruction => "以下内容仅作为上下文，不需要再次总结。如果当前活动是其延续，请描述为延续（例如“继续处理同一个任务”）。",
+            PrivateTime => "私人时间（隐身模式，未生成摘要）。",
+            MeetingTime => "会议或屏幕共享（未生成摘要）。",
+        },
+        "ja" => match template {
+            SummaryPrompt => "この画面アクティビティの動画を分析し、簡潔なアクティビティの要約を作成してください。重点：1) 主に使用したアプリ/ウェブサイト；2) アクティビティの種類（仕事/娯楽/学習など）；3) 気が散った行動や非効率な行動の有無。日本語で200字以内で回答してください。",
+            MergePrompt => "以下は同じ期間の画面アクティビティを時系列順に並べた複数の要約です。重点（主に使用したアプリ/ウェブサイト、アクティビティの種類、気が散った行動や非効率な行動の有無）はそのままに、1つの簡潔な要約にまとめてください。日本語で300字以内で回答してください。\n\n要約：\n{summaries}",
+            DailyPrompt => "以下の今日のアクティビティ要約をもとに、総合的な1日のまとめを作成してください。含める内容：1) 全体的な生産性の評価；2) 主なアクティビティと時間配分；3) 重要な気づきと改善のための提案。日本語で回答してください。\n\n今日の要約：\n{summaries}",
+            DailyGoals => "\n\n目標の進捗（達成した目標と未達成の目標について評価してください）：\n{progress}",
+            DailyBreaks => "\n\n休憩の状況（十分に休憩を取れたか簡単に評価してください）：\n{stats}",
+            MonthlyPrompt => "以下の{month}の日ごとのまとめと統計データをもとに、より高い視点から月間の振り返りを作成してください。含める内容：1) 今月全体の生産性の傾向；2) カテゴリごとの時間配分と最も忙しかった日；3) 繰り返し見られるパターン、習慣、気が散る行動；4) 来月に向けた具体的な改善提案。日本語で回答してください。\n\n統計データ（JSON）：\n{statistics}\n\n日ごとのまとめ：\n{summaries}",
+            NoActivityDay => "この日のアクティビティは記録されていません。",
+            NoActivityMonth => "今月のアクティビティは記録されていません。",
+            PreviousSummaryContext => "直前の時間帯（{range}）の要約：\n{content}",
+            DailySummaryContext => "今日これまでのまとめ：\n{content}",
+            ContextInstruction => "以下は文脈としてのみ提供されます（再度要約しないでください）。現在のアクティビティがその続きであれば、続きとして記述してください（例：「同じタスクの作業を続けた」）。",
+            PrivateTime => "プライベートな時間（シークレットモードのため要約していません）。",
+            MeetingTime => "会議または画面共有（要約していません）。",
+        },
+        "de" => match template {
+            SummaryPrompt => "Analysiere dieses Video der Bildschirmaktivität und erstelle eine kurze Zusammenfassung der Aktivitäten. Konzentriere dich auf: 1) hauptsächlich genutzte Apps/Websites; 2) Art der Aktivität (Arbeit/Unterhaltung/Lernen usw.); 3) Ablenkungen oder ineffizientes Verhalten. Antworte auf Deutsch, höchstens 100 Wörter.",
+            MergePrompt => "Die folgenden Zusammenfassungen beschreiben nacheinander denselben Zeitraum der Bildschirmaktivität in chronologischer Reihenfolge. Fasse sie zu einer einzigen kurzen Zusammenfassung mit demselben Fokus zusammen: hauptsächlich genutzte Apps/Websites, Art der Aktivität sowie Ablenkungen oder ineffizientes Verhalten. Antworte auf Deutsch, höchstens 150 Wörter.\n\nZusammenfassungen:\n{summaries}",
+            DailyPrompt => "Erstelle auf Grundlage der folgenden Aktivitätszusammenfassungen von heute eine umfassende Tageszusammenfassung. Enthalten sein sollen: 1) eine Gesamteinschätzung der Produktivität; 2) die wichtigsten Aktivitäten und die Zeitverteilung; 3) zentrale Erkenntnisse und Verbesserungsvorschläge. Antworte auf Deutsch.\n\nZusammenfassungen von heute:\n{summaries}",
+            DailyGoals => "\n\nZielfortschritt (bewerte, welche Ziele erreicht oder verfehlt wurden):\n{progress}",
+            DailyBreaks => "\n\nPausenverhalten (bewerte kurz, ob genügend Pausen gemacht wurden):\n{stats}",
+            MonthlyPrompt => "Schreibe auf Grundlage der folgenden Tageszusammenfassungen und berechneten Statistiken für {month} einen Monatsrückblick auf höherer Ebene. Enthalten sein sollen: 1) der allgemeine Produktivitätstrend im Monat; 2) die Zeitverteilung auf die Kategorien und die arbeitsreichsten Tage; 3) wiederkehrende Muster, Gewohnheiten oder Ablenkungen; 4) konkrete Empfehlungen für den nächsten Monat. Antworte auf Deutsch.\n\nStatistiken (JSON):\n{statistics}\n\nTageszusammenfassungen:\n{summaries}",
+            NoActivityDay => "Für diesen Tag wurde keine Aktivität aufgezeichnet.",
+            NoActivityMonth => "Für diesen Monat wurde keine Aktivität aufgezeichnet.",
+            PreviousSummaryContext => "Zusammenfassung des vorherigen Zeitraums ({range}):\n{content}",
+            DailySummaryContext => "Zusammenfassung des bisherigen Tages:\n{content}",
+            ContextInstruction => "Kontext für die Kontinuität (nicht erneut zusammenfassen). Wenn die aktuelle Aktivität daran anknüpft, beschreibe sie als Fortsetzung (z. B. „arbeitete weiter an derselben Aufgabe“).",
+            PrivateTime => "Private Zeit (Inkognito-Modus, nicht zusammengefasst).",
+            MeetingTime => "Meeting oder Bildschirmfreigabe (nicht zusammengefasst).",
+        },
+        "es" => match template {
+            SummaryPrompt => "Analiza este vídeo de la actividad en pantalla y ofrece un resumen breve de la actividad. Céntrate en: 1) las principales aplicaciones/sitios web utilizados; 2) el tipo de actividad (trabajo/entretenimiento/aprendizaje, etc.); 3) cualquier distracción o comportamiento poco eficiente. Responde en español, en menos de 100 palabras.",
+            MergePrompt => "Los siguientes son resúmenes consecutivos que cubren un mismo periodo de actividad en pantalla, en orden cronológico. Combínalos en un único resumen breve con el mismo enfoque: principales aplicaciones/sitios web utilizados, tipo de actividad y cualquier distracción o comportamiento poco eficiente. Responde en español, en menos de 150 palabras.\n\nResúmenes:\n{summaries}",
+            DailyPrompt => "A partir de los siguientes resúmenes de actividad de hoy, elabora un resumen diario completo. Incluye: 1) una valoración general de la productividad; 2) las actividades principales y la distribución del tiempo; 3) conclusiones clave y recomendaciones de mejora. Responde en español.\n\nResúmenes de hoy:\n{summaries}",
+            DailyGoals => "\n\nProgreso de los objetivos (comenta qué objetivos se cumplieron y cuáles no):\n{progress}",
+            DailyBreaks => "\n\nHábitos de descanso (comenta brevemente si se tomaron suficientes descansos):\n{stats}",
+            MonthlyPrompt => "A partir de los siguientes resúmenes diarios y estadísticas calculadas de {month}, redacta una revisión mensual de alto nivel. Incluye: 1) la tendencia general de productividad del mes; 2) cómo se distribuyó el tiempo entre categorías y qué días fueron los más intensos; 3) patrones, hábitos o distracciones recurrentes; 4) recomendaciones concretas para el próximo mes. Responde en español.\n\nEstadísticas (JSON):\n{statistics}\n\nResúmenes diarios:\n{summaries}",
+            NoActivityDay => "No se registró actividad este día.",
+            NoActivityMonth => "No se registró actividad este mes.",
+            PreviousSummaryContext => "Resumen del periodo anterior ({range}):\n{content}",
+            DailySummaryContext => "Resumen del día hasta ahora:\n{content}",
+            ContextInstruction => "Contexto para dar continuidad (no lo resumas de nuevo). Si la actividad actual es continuación de él, descríbela como tal (p. ej., «siguió trabajando en la misma tarea»).",
+            PrivateTime => "Tiempo privado (modo incógnito, sin resumir).",
+            MeetingTime => "Reunión o pantalla compartida (sin resumir).",
+        },
+        _ => return None,
+    };
+    Some(text)
+}
+
+// 按回退链找到模板，替换 {name} 占位符（{language} 总是可用）
+// 只扫描一遍模板，替换进去的内容中的花括号不会被再次替换
+pub fn render(tag: &str, template: Template, args: &[(&str, &str)]) -> String {
+    let text = fallback_chain(tag)
+        .iter()
+        .find_map(|t| builtin(t, template))
+        .unwrap_or_default();
+    let language = language_name(tag);
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            let value = if name == "language" {
+                Some(language.as_str())
+            } else {
+                args.iter().find(|(key, _)| *key == name).map(|(_, v)| *v)
+            };
+            value.map(|v| (v, end))
+        });
+        match value {
+            Some((value, end)) => {
+                result.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+// 当前语言使用的摘要提示词：按回退链查找用户保存的提示词，都没有时使用内置模板
+// 英文的自定义提示词只用于英文，避免其他语言回退后得到要求用英文回答的提示词
+pub async fn summary_prompt(pool: &SqlitePool, tag: &str) -> String {
+    let chain = fallback_chain(tag);
+    let english = base_language(tag) == FALLBACK_LANGUAGE;
+    for t in chain
+        .iter()
+        .filter(|t| english || t.as_str() != FALLBACK_LANGUAGE)
+    {
+        if let Ok(prompt) = settings::load_ai_prompt_from_db(pool, Some(t)).await {
+            if !prompt.trim().is_empty() {
+                return prompt;
+            }
+        }
+    }
+    render(tag, Template::SummaryPrompt, &[])
+}
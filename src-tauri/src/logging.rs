@@ -0,0 +1,238 @@
+use chrono::{Local, NaiveDate};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+// 默认日志级别，可以在设置中修改
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+pub const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+// 日志文件按天保存，保留最近 7 天
+const LOG_RETENTION_DAYS: i64 = 7;
+const LOG_FILE_PREFIX: &str = "clarity-";
+const LOG_FILE_EXTENSION: &str = "log";
+
+// get_recent_logs 最多返回的条数
+pub const MAX_RECENT_LOG_LINES: usize = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub timestamp: String, // 本地时间 YYYY-MM-DD HH:MM:SS.mmm
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+// 当前写入的日志文件和对应的日期，日期变化时切换到新文件
+static CURRENT_FILE: Mutex<Option<(NaiveDate, File)>> = Mutex::new(None);
+
+struct FileLogger {
+    dir: PathBuf,
+}
+
+impl FileLogger {
+    fn open(&self, date: NaiveDate) -> Option<File> {
+        if let Err(e) = std::fs::create_dir_all(&self.dir) {
+            eprintln!(
+                "Failed to create log directory {}: {}",
+                self.dir.display(),
+                e
+            );
+            return None;
+        }
+        remove_expired_logs(&self.dir, date);
+        match OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_file_path(&self.dir, date))
+        {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!("Failed to open log file: {}", e);
+                None
+            }
+        }
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // 依赖库的日志只记录警告和错误，避免 debug 级别时被网络库等刷屏
+        metadata.level() <= log::max_level()
+            && (metadata.target().starts_with("clarity") || metadata.level() <= Level::Warn)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let now = Local::now();
+        let line = format!(
+            "{} {:<5} [{}] {}\n",
+            now.format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        // 开发时仍然在终端输出
+        eprint!("{}", line);
+
+        let Ok(mut current) = CURRENT_FILE.lock() else {
+            return;
+        };
+        let today = now.date_naive();
+        if current.as_ref().is_none_or(|(date, _)| *date != today) {
+            *current = self.open(today).map(|file| (today, file));
+        }
+        if let Some((_, file)) = current.as_mut() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut current) = CURRENT_FILE.lock() {
+            if let Some((_, file)) = current.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+// 日志目录：应用数据目录下的 logs（所有配置共用）
+pub fn log_dir() -> PathBuf {
+    crate::db::default_db_path()
+        .parent()
+        .map(|dir| dir.join("logs"))
+        .unwrap_or_else(|| PathBuf::from("logs"))
+}
+
+fn log_file_path(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!(
+        "{}{}.{}",
+        LOG_FILE_PREFIX,
+        date.format("%Y-%m-%d"),
+        LOG_FILE_EXTENSION
+    ))
+}
+
+// 从文件名中解析日志日期，不是日志文件时返回 None
+fn log_file_date(path: &Path) -> Option<NaiveDate> {
+    if path.extension()?.to_str()? != LOG_FILE_EXTENSION {
+        return None;
+    }
+    let date = path.file_stem()?.to_str()?.strip_prefix(LOG_FILE_PREFIX)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+// 按日期从新到旧列出日志文件
+fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<(NaiveDate, PathBuf)> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter_map(|path| log_file_date(&path).map(|date| (date, path)))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+fn remove_expired_logs(dir: &Path, today: NaiveDate) {
+    let oldest = today - chrono::Duration::days(LOG_RETENTION_DAYS - 1);
+    for path in log_files(dir) {
+        if log_file_date(&path).is_some_and(|date| date < oldest) {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+}
+
+pub fn parse_level(level: &str) -> Option<LevelFilter> {
+    if LOG_LEVELS.contains(&level) {
+        LevelFilter::from_str(level).ok()
+    } else {
+        None
+    }
+}
+
+// 初始化文件日志，程序启动时调用一次；保存的日志级别在数据库打开后再应用
+pub fn init() {
+    let logger = FileLogger { dir: log_dir() };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
+
+// 关闭当前的日志文件（删除日志文件前后调用），之后的日志写入新建的文件
+pub fn close_log_file() {
+    if let Ok(mut current) = CURRENT_FILE.lock() {
+        *current = None;
+    }
+}
+
+// 修改日志级别，立即生效
+pub fn set_level(level: &str) {
+    if let Some(filter) = parse_level(level) {
+        log::set_max_level(filter);
+    }
+}
+
+// 解析一行日志，格式见 FileLogger::log
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let (date, rest) = line.split_once(' ')?;
+    let (time, rest) = rest.split_once(' ')?;
+    let (level, rest) = rest.trim_start().split_once(' ')?;
+    Level::from_str(level).ok()?;
+    let rest = rest.trim_start().strip_prefix('[')?;
+    let (target, message) = rest.split_once("] ")?;
+    Some(LogEntry {
+        timestamp: format!("{} {}", date, time),
+        level: level.to_string(),
+        target: target.to_string(),
+        message: message.to_string(),
+    })
+}
+
+// 读取最近的日志（从新到旧），只返回不低于 min_level 的条目
+// 多行的日志内容会合并到同一条中
+pub fn read_recent_logs(lines: usize, min_level: Option<Level>) -> Result<Vec<LogEntry>, String> {
+    let mut entries = Vec::new();
+    for path in log_files(&log_dir()) {
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        let mut file_entries: Vec<LogEntry> = Vec::new();
+        for line in content.lines() {
+            match parse_line(line) {
+                Some(entry) => file_entries.push(entry),
+                None => {
+                    if let Some(last) = file_entries.last_mut() {
+                        last.message.push('\n');
+                        last.message.push_str(line);
+                    }
+                }
+            }
+        }
+
+        for entry in file_entries.into_iter().rev() {
+            let matches = match (min_level, Level::from_str(&entry.level)) {
+                (Some(min_level), Ok(level)) => level <= min_level,
+                _ => true,
+            };
+            if matches {
+                entries.push(entry);
+                if entries.len() >= lines {
+                    return Ok(entries);
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
@@ -0,0 +1,219 @@
+use crate::commands::summary::save_placeholder_summary;
+use crate::db;
+use crate::events;
+use crate::locale;
+use crate::settings;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+// 检测到会议或屏幕共享时的处理方式：pause 不截图，local_only 截图只保存在本地，off 不检测
+pub const MEETING_ACTIONS: [&str; 3] = ["pause", "local_only", "off"];
+pub const DEFAULT_MEETING_ACTION: &str = "off";
+
+// 会议时间段的摘要计入的活动分类
+pub const MEETING_CATEGORY: &str = "meetings";
+
+// 检查会议窗口的间隔（需要枚举所有窗口，不必每次截图都检查）
+const MEETING_CHECK_INTERVAL: StdDuration = StdDuration::from_secs(5);
+
+// 连续这么久没有检测到会议窗口才算会议结束，避免会议窗口短暂关闭或切换时反复开始和结束
+const MEETING_END_GRACE: StdDuration = StdDuration::from_secs(20);
+
+// 会议软件和屏幕共享提示的窗口：(应用名称包含, 窗口标题包含)，不区分大小写，空字符串匹配任何应用
+const MEETING_WINDOWS: [(&str, &str); 12] = [
+    ("zoom", "zoom meeting"),
+    ("zoom", "zoom webinar"),
+    ("zoom", "screen sharing"),
+    ("teams", "meeting"),
+    ("teams", "call with"),
+    ("webex", "meeting"),
+    ("slack", "huddle"),
+    ("", "google meet"),
+    ("", "meet - "), // 浏览器中的 Google Meet 标签页，例如 "Meet - abc-defg-hij"
+    ("", "is sharing your screen"), // Chrome / Edge 共享屏幕时的提示条
+    ("", "is sharing a window"),
+    ("firefox", "sharing indicator"),
+];
+
+// 正在进行的会议
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveMeeting {
+    pub app: String,
+    pub started_at: DateTime<Local>,
+    pub paused: bool, // 会议期间是否暂停过截图
+}
+
+// 会议开始或结束时发送给前端
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingStatus {
+    pub active: bool,
+    pub meeting: Option<ActiveMeeting>,
+}
+
+pub fn is_valid_action(action: &str) -> bool {
+    MEETING_ACTIONS.contains(&action)
+}
+
+fn is_meeting_window(app: &str, title: &str) -> bool {
+    let app = app.to_lowercase();
+    let title = title.to_lowercase();
+    MEETING_WINDOWS
+        .iter()
+        .any(|(app_part, title_part)| app.contains(app_part) && title.contains(title_part))
+}
+
+// 查找会议或屏幕共享的窗口（阻塞调用），返回所属应用的名称
+// 最小化的会议窗口也算，会议通常在后台继续进行
+fn detect_meeting() -> Option<String> {
+    let windows = xcap::Window::all().ok()?;
+    windows.iter().find_map(|window| {
+        let app = window.app_name().unwrap_or_default();
+        let title = window.title().unwrap_or_default();
+        is_meeting_window(&app, &title).then(|| app.trim().to_string())
+    })
+}
+
+fn emit_meeting_changed(app_handle: Option<&AppHandle>, meeting: Option<ActiveMeeting>) {
+    events::emit(
+        app_handle,
+        events::MEETING_CHANGED,
+        MeetingStatus {
+            active: meeting.is_some(),
+            meeting,
+        },
+    );
+}
+
+// 由截图循环使用，跟踪会议的开始和结束，当前会议保存在共享状态中（总结任务也会读取）
+pub struct MeetingTracker {
+    meeting: Arc<Mutex<Option<ActiveMeeting>>>,
+    last_check: Option<Instant>,
+    last_seen: Option<(Instant, DateTime<Local>)>, // 最后一次检测到会议窗口的时间
+}
+
+impl MeetingTracker {
+    pub fn new(meeting: Arc<Mutex<Option<ActiveMeeting>>>) -> Self {
+        Self {
+            meeting,
+            last_check: None,
+            last_seen: None,
+        }
+    }
+
+    // 每次截图前调用：按间隔检查会议窗口并更新会议状态，返回当前是否在会议中
+    pub async fn update(
+        &mut self,
+        db_pool: &SqlitePool,
+        app_handle: Option<&AppHandle>,
+        action: &str,
+    ) -> bool {
+        if action == "off" {
+            self.finish(db_pool, app_handle).await;
+            return false;
+        }
+
+        if self
+            .last_check
+            .is_some_and(|checked| checked.elapsed() < MEETING_CHECK_INTERVAL)
+        {
+            let mut meeting = self.meeting.lock().await;
+            if let Some(meeting) = meeting.as_mut() {
+                meeting.paused |= action == "pause";
+            }
+            return meeting.is_some();
+        }
+        self.last_check = Some(Instant::now());
+
+        let detected = tokio::task::spawn_blocking(detect_meeting)
+            .await
+            .unwrap_or(None);
+        if let Some(app) = detected {
+            self.last_seen = Some((Instant::now(), Local::now()));
+            let mut meeting = self.meeting.lock().await;
+            match meeting.as_mut() {
+                Some(meeting) => meeting.paused |= action == "pause",
+                None => {
+                    log::info!(
+                        "Meeting detected ({}), screenshots will be {}",
+                        app,
+                        if action == "pause" {
+                            "paused"
+                        } else {
+                            "kept local"
+                        }
+                    );
+                    let started = ActiveMeeting {
+                        app,
+                        started_at: Local::now(),
+                        paused: action == "pause",
+                    };
+                    *meeting = Some(started.clone());
+                    emit_meeting_changed(app_handle, Some(started));
+                }
+            }
+            return true;
+        }
+
+        if self
+            .last_seen
+            .is_some_and(|(seen, _)| seen.elapsed() < MEETING_END_GRACE)
+        {
+            return self.meeting.lock().await.is_some();
+        }
+        self.finish(db_pool, app_handle).await;
+        false
+    }
+
+    // 结束当前会议（会议窗口关闭、关闭检测或录制停止时调用）：
+    // 时间段记录为 meeting，暂停过截图的会议在时间线中添加一条“会议”摘要
+    // （只保存在本地的截图由总结任务生成同样的摘要）
+    pub async fn finish(&mut self, db_pool: &SqlitePool, app_handle: Option<&AppHandle>) {
+        let last_seen = self.last_seen.take();
+        let Some(meeting) = self.meeting.lock().await.take() else {
+            return;
+        };
+        let end_time = last_seen
+            .map(|(_, seen_at)| seen_at)
+            .unwrap_or_else(Local::now)
+            .max(meeting.started_at);
+        log::info!(
+            "Meeting ended ({}, {} minutes)",
+            meeting.app,
+            (end_time - meeting.started_at).num_minutes()
+        );
+
+        if let Err(e) =
+            db::insert_recording_gap(db_pool, meeting.started_at, end_time, "meeting").await
+        {
+            log::error!("Failed to record meeting: {}", e);
+        }
+        if meeting.paused {
+            let language = settings::load_language_from_db(db_pool)
+                .await
+                .unwrap_or_else(|_| locale::DEFAULT_LANGUAGE.to_string());
+            if let Err(e) = save_placeholder_summary(
+                db_pool,
+                app_handle,
+                &language,
+                meeting.started_at,
+                end_time,
+                0,
+                true,
+            )
+            .await
+            {
+                log::error!("Failed to save meeting summary: {}", e);
+            }
+        }
+
+        emit_meeting_changed(app_handle, None);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::video_summary;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tokio::process::Command;
+
+// 默认识别的语言（tesseract 语言包名称，多个用 + 连接，例如 eng+chi_sim）
+pub const DEFAULT_OCR_LANGUAGES: &str = "eng";
+
+// 每个时间段抽样识别的截图数量
+pub const OCR_FRAMES_PER_CHUNK: usize = 3;
+
+// 附加到提示词中的识别文字最大字符数，避免占用过多 token
+const MAX_OCR_TEXT_CHARS: usize = 4000;
+
+// 少于这么多字符的行通常是图标或噪点，忽略
+const MIN_OCR_LINE_CHARS: usize = 3;
+
+// 检查 tesseract 是否可以执行
+pub async fn tesseract_available() -> bool {
+    Command::new("tesseract")
+        .arg("--version")
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success())
+}
+
+// 检查语言参数格式，例如 eng 或 eng+chi_sim
+pub fn validate_languages(languages: &str) -> Result<(), String> {
+    let valid = !languages.is_empty()
+        && languages.split('+').all(|lang| {
+            !lang.is_empty() && lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid OCR languages: {}", languages))
+    }
+}
+
+// 识别单张截图中的文字
+pub async fn extract_text(image_path: &PathBuf, languages: &str) -> Result<String, String> {
+    let output = Command::new("tesseract")
+        .arg(image_path)
+        .arg("stdout")
+        .arg("-l")
+        .arg(languages)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tesseract: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "tesseract failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// 抽样识别多张截图的文字，去掉重复的行（相邻截图通常内容相同），识别失败的截图跳过
+pub async fn extract_text_from_frames(
+    image_paths: &[PathBuf],
+    languages: &str,
+    max_frames: usize,
+) -> String {
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+    let mut total_chars = 0;
+
+    for path in video_summary::sample_frames(image_paths, max_frames) {
+        let text = match extract_text(&path, languages).await {
+            Ok(text) => text,
+            Err(e) => {
+                log::warn!("OCR failed for {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        for line in text.lines() {
+            let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+            if line.chars().count() < MIN_OCR_LINE_CHARS || !seen.insert(line.clone()) {
+                continue;
+            }
+            total_chars += line.chars().count() + 1;
+            if total_chars > MAX_OCR_TEXT_CHARS {
+                return lines.join("\n");
+            }
+            lines.push(line);
+        }
+    }
+
+    lines.join("\n")
+}
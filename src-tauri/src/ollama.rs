@@ -0,0 +1,216 @@
+use crate::error::ClarityError;
+use crate::video_summary::{self, ApiRequestResult, SummaryStage};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::AppHandle;
+
+// 本地 Ollama 服务的默认地址
+pub const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+// 默认使用的本地视觉模型
+pub const DEFAULT_OLLAMA_MODEL: &str = "llava";
+
+// 每次总结最多发送的截图数量（本地模型上下文较小，推理也慢）
+pub const MAX_OLLAMA_FRAMES: usize = 8;
+
+// 发送前把截图缩小到此宽度
+const FRAME_WIDTH: u32 = 672;
+
+// 本地推理可能很慢，给生成请求留足时间
+const GENERATE_TIMEOUT_SECS: u64 = 300;
+
+// Ollama /api/generate 响应结构
+#[derive(Debug, Deserialize)]
+struct GenerateResponse {
+    response: String,
+    prompt_eval_count: Option<i64>,
+    eval_count: Option<i64>,
+}
+
+// Ollama /api/tags 响应结构
+#[derive(Debug, Deserialize)]
+struct TagsResponse {
+    models: Vec<TagModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagModel {
+    name: String,
+}
+
+// 拼接 generate 接口地址
+pub fn generate_url(base_url: &str) -> String {
+    format!("{}/api/generate", base_url.trim_end_matches('/'))
+}
+
+// 获取本地已下载的模型列表，服务未启动时返回错误
+pub async fn list_models(base_url: &str) -> Result<Vec<String>, ClarityError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(3))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let response = client
+        .get(format!("{}/api/tags", base_url.trim_end_matches('/')))
+        .send()
+        .await
+        .map_err(|e| {
+            ClarityError::Network(format!(
+                "Ollama server not reachable at {}: {}",
+                base_url, e
+            ))
+        })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ClarityError::from_api_status(
+            status,
+            format!("Ollama API error: {} - {}", status, error_text),
+        ));
+    }
+
+    let tags: TagsResponse = response
+        .json()
+        .await
+        .map_err(|e| ClarityError::Ai(format!("Failed to parse response: {}", e)))?;
+
+    Ok(tags.models.into_iter().map(|m| m.name).collect())
+}
+
+// 检查 Ollama 服务是否可用
+pub async fn is_server_available(base_url: &str) -> bool {
+    list_models(base_url).await.is_ok()
+}
+
+// 调用 generate 接口（非流式），images 为 base64 编码的图片
+async fn generate(
+    base_url: &str,
+    model: &str,
+    prompt: &str,
+    images: Vec<String>,
+) -> Result<ApiRequestResult, ClarityError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(GENERATE_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let start_time = std::time::Instant::now();
+
+    let mut request_body = serde_json::json!({
+        "model": model,
+        "prompt": prompt,
+        "stream": false
+    });
+    if !images.is_empty() {
+        request_body["images"] = serde_json::json!(images);
+    }
+
+    let url = generate_url(base_url);
+    log::info!("Calling Ollama API: {} (model: {})", url, model);
+
+    let response = client
+        .post(&url)
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(|e| {
+            ClarityError::Network(format!(
+                "Ollama server not reachable at {}: {}",
+                base_url, e
+            ))
+        })?;
+
+    let duration_ms = start_time.elapsed().as_millis() as u64;
+    let status = response.status();
+
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ClarityError::from_api_status(
+            status,
+            format!("Ollama API error: {} - {}", status, error_text),
+        ));
+    }
+
+    let api_response: GenerateResponse = response
+        .json()
+        .await
+        .map_err(|e| ClarityError::Ai(format!("Failed to parse response: {}", e)))?;
+
+    if api_response.response.trim().is_empty() {
+        return Err(ClarityError::Ai("No response from Ollama".to_string()));
+    }
+
+    // Ollama 的 token 统计：prompt_eval_count 为输入，eval_count 为输出
+    let total_tokens = match (api_response.prompt_eval_count, api_response.eval_count) {
+        (Some(prompt), Some(completion)) => Some(prompt + completion),
+        _ => None,
+    };
+
+    Ok(ApiRequestResult {
+        content: api_response.response,
+        prompt_tokens: api_response.prompt_eval_count,
+        completion_tokens: api_response.eval_count,
+        total_tokens,
+        cost_usd: None,
+        structured_data: None,
+        status_code: status.as_u16(),
+        duration_ms,
+    })
+}
+
+// 抽取部分截图发送给本地视觉模型生成摘要，数据不会离开本机
+pub async fn summarize_frames_with_ollama(
+    base_url: &str,
+    model: &str,
+    prompt: &str,
+    image_paths: &[PathBuf],
+    app_handle: Option<&AppHandle>,
+) -> Result<ApiRequestResult, ClarityError> {
+    let frames = video_summary::sample_frames(image_paths, MAX_OLLAMA_FRAMES);
+    log::info!(
+        "Sending {} of {} screenshots to Ollama (model: {})",
+        frames.len(),
+        image_paths.len(),
+        model
+    );
+
+    video_summary::emit_summary_progress(app_handle, SummaryStage::Uploading, 30, None);
+    let mut images = Vec::new();
+    for path in &frames {
+        match video_summary::encode_frame_base64(path, FRAME_WIDTH).await {
+            Ok(data) => images.push(data),
+            Err(e) => {
+                // 截图文件丢失或损坏时跳过该帧
+                log::warn!("Skipping frame {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    if images.is_empty() {
+        return Err(ClarityError::Internal(
+            "No readable screenshots to send".to_string(),
+        ));
+    }
+
+    // 截图按时间顺序排列，提示模型把它们当作连续的屏幕活动
+    let prompt = format!(
+        "The following {} images are screenshots sampled in chronological order from one period of screen activity.\n\n{}",
+        images.len(),
+        prompt
+    );
+
+    video_summary::emit_summary_progress(app_handle, SummaryStage::Generating, 70, None);
+    generate(base_url, model, &prompt, images).await
+}
+
+// 生成文本摘要（用于合并分段摘要等纯文本场景）
+pub async fn generate_text_with_ollama(
+    base_url: &str,
+    model: &str,
+    prompt: &str,
+) -> Result<String, ClarityError> {
+    generate(base_url, model, prompt, Vec::new())
+        .await
+        .map(|result| result.content)
+}
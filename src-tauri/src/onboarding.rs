@@ -0,0 +1,92 @@
+use crate::settings;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+// 首次运行的引导流程，按顺序进行：欢迎 → 同意截图 → 同意上传给 AI → 完成
+pub const ONBOARDING_STEPS: [&str; 4] = ["welcome", "screen_capture", "ai_upload", "completed"];
+pub const ONBOARDING_COMPLETED: &str = "completed";
+
+// 需要用户同意的两件事，步骤名称与同意记录使用相同的名称
+pub const CONSENT_SCREEN_CAPTURE: &str = "screen_capture";
+pub const CONSENT_AI_UPLOAD: &str = "ai_upload";
+
+// 用户同意或拒绝的记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsentRecord {
+    pub granted: bool,
+    pub recorded_at: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingState {
+    pub step: String,
+    pub completed: bool,
+    pub screen_capture: Option<ConsentRecord>, // 还没有选择时为 None
+    pub ai_upload: Option<ConsentRecord>,
+}
+
+pub fn is_valid_consent(consent: &str) -> bool {
+    consent == CONSENT_SCREEN_CAPTURE || consent == CONSENT_AI_UPLOAD
+}
+
+// 下一个步骤（已完成时仍为完成）
+pub fn next_step(step: &str) -> &'static str {
+    let index = ONBOARDING_STEPS
+        .iter()
+        .position(|s| *s == step)
+        .unwrap_or(0);
+    ONBOARDING_STEPS[(index + 1).min(ONBOARDING_STEPS.len() - 1)]
+}
+
+// 加载当前的引导状态，没有记录时从欢迎页开始，无法解析的同意记录按没有同意处理
+pub async fn load_state(pool: &SqlitePool) -> OnboardingState {
+    let step = settings::load_onboarding_step_from_db(pool)
+        .await
+        .ok()
+        .filter(|step| ONBOARDING_STEPS.contains(&step.as_str()))
+        .unwrap_or_else(|| ONBOARDING_STEPS[0].to_string());
+    OnboardingState {
+        completed: step == ONBOARDING_COMPLETED,
+        step,
+        screen_capture: load_consent(pool, CONSENT_SCREEN_CAPTURE).await,
+        ai_upload: load_consent(pool, CONSENT_AI_UPLOAD).await,
+    }
+}
+
+async fn load_consent(pool: &SqlitePool, consent: &str) -> Option<ConsentRecord> {
+    settings::load_consent_from_db(pool, consent)
+        .await
+        .unwrap_or_else(|e| {
+            log::error!("Failed to load {} consent: {}", consent, e);
+            None
+        })
+}
+
+// 是否已经同意（没有记录或拒绝时为 false）
+pub async fn is_granted(pool: &SqlitePool, consent: &str) -> bool {
+    load_consent(pool, consent)
+        .await
+        .is_some_and(|record| record.granted)
+}
+
+// 保存用户的选择
+pub async fn record_consent(
+    pool: &SqlitePool,
+    consent: &str,
+    granted: bool,
+) -> Result<ConsentRecord, sqlx::Error> {
+    let record = ConsentRecord {
+        granted,
+        recorded_at: Local::now(),
+    };
+    settings::save_consent_to_db(pool, consent, &record).await?;
+    log::info!(
+        "{} consent {}",
+        consent,
+        if granted { "granted" } else { "declined" }
+    );
+    Ok(record)
+}
@@ -0,0 +1,193 @@
+use crate::error::ClarityError;
+use crate::proxy;
+use crate::video_summary::{self, ApiRequestResult, SummaryStage};
+use serde::Deserialize;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+// OpenAI 兼容接口的默认地址
+pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+// 可选的 OpenAI 视觉模型
+pub const OPENAI_MODELS: [&str; 2] = ["gpt-4o-mini", "gpt-4o"];
+
+// 每次总结最多发送的截图数量
+pub const MAX_OPENAI_FRAMES: usize = 16;
+
+// 发送前把截图缩小到此宽度（low detail 模式下模型只按 512px 处理，更大没有意义）
+const FRAME_WIDTH: u32 = 768;
+
+// OpenAI chat completions 响应结构
+#[derive(Debug, Deserialize)]
+struct ChatCompletionResponse {
+    choices: Vec<ChatChoice>,
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    prompt_tokens: Option<i64>,
+    completion_tokens: Option<i64>,
+    total_tokens: Option<i64>,
+    // OpenRouter 开启 usage accounting 后返回的费用（美元）
+    cost: Option<f64>,
+}
+
+// 是否为 OpenRouter 接口（需要额外的请求参数）
+fn is_openrouter(base_url: &str) -> bool {
+    base_url.contains("openrouter.ai")
+}
+
+// 拼接 chat completions 接口地址
+pub fn chat_completions_url(base_url: &str) -> String {
+    format!("{}/chat/completions", base_url.trim_end_matches('/'))
+}
+
+// 调用 chat completions 接口，content 可以是字符串或多段内容数组
+async fn send_chat_completion(
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+    content: serde_json::Value,
+) -> Result<ApiRequestResult, ClarityError> {
+    let client = proxy::client();
+    let start_time = std::time::Instant::now();
+
+    let mut request_body = serde_json::json!({
+        "model": model,
+        "messages": [{
+            "role": "user",
+            "content": content
+        }]
+    });
+
+    let url = chat_completions_url(base_url);
+    log::info!("Calling OpenAI-compatible API: {} (model: {})", url, model);
+
+    let mut request = client.post(&url).bearer_auth(api_key);
+    if is_openrouter(base_url) {
+        // 让 OpenRouter 在 usage 中返回实际费用，并标明请求来源
+        request_body["usage"] = serde_json::json!({ "include": true });
+        request = request
+            .header("HTTP-Referer", "https://github.com/crapthings/clarity")
+            .header("X-Title", "Clarity");
+    }
+
+    let response = request
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(|e| ClarityError::Network(format!("Failed to send request: {}", e)))?;
+
+    let duration_ms = start_time.elapsed().as_millis() as u64;
+    let status = response.status();
+
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ClarityError::from_api_status(
+            status,
+            format!("OpenAI API error: {} - {}", status, error_text),
+        ));
+    }
+
+    let api_response: ChatCompletionResponse = response
+        .json()
+        .await
+        .map_err(|e| ClarityError::Ai(format!("Failed to parse response: {}", e)))?;
+
+    let text = api_response
+        .choices
+        .first()
+        .and_then(|choice| choice.message.content.clone())
+        .ok_or_else(|| ClarityError::Ai("No response from OpenAI API".to_string()))?;
+
+    Ok(ApiRequestResult {
+        content: text,
+        prompt_tokens: api_response.usage.as_ref().and_then(|u| u.prompt_tokens),
+        completion_tokens: api_response
+            .usage
+            .as_ref()
+            .and_then(|u| u.completion_tokens),
+        total_tokens: api_response.usage.as_ref().and_then(|u| u.total_tokens),
+        cost_usd: api_response.usage.as_ref().and_then(|u| u.cost),
+        structured_data: None,
+        status_code: status.as_u16(),
+        duration_ms,
+    })
+}
+
+// 抽取部分截图直接发送给视觉模型生成摘要（不需要 ffmpeg）
+pub async fn summarize_frames_with_openai(
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+    prompt: &str,
+    image_paths: &[PathBuf],
+    app_handle: Option<&AppHandle>,
+) -> Result<ApiRequestResult, ClarityError> {
+    let frames = video_summary::sample_frames(image_paths, MAX_OPENAI_FRAMES);
+    log::info!(
+        "Sending {} of {} screenshots to OpenAI (model: {})",
+        frames.len(),
+        image_paths.len(),
+        model
+    );
+
+    // 截图按时间顺序排列，提示模型把它们当作连续的屏幕活动
+    let mut content = vec![serde_json::json!({
+        "type": "text",
+        "text": format!(
+            "The following {} images are screenshots sampled in chronological order from one period of screen activity.\n\n{}",
+            frames.len(),
+            prompt
+        )
+    })];
+
+    video_summary::emit_summary_progress(app_handle, SummaryStage::Uploading, 30, None);
+    for path in &frames {
+        match video_summary::encode_frame_base64(path, FRAME_WIDTH).await {
+            Ok(data) => content.push(serde_json::json!({
+                "type": "image_url",
+                "image_url": {
+                    "url": format!("data:image/jpeg;base64,{}", data),
+                    "detail": "low"
+                }
+            })),
+            Err(e) => {
+                // 截图文件丢失或损坏时跳过该帧
+                log::warn!("Skipping frame {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    if content.len() == 1 {
+        return Err(ClarityError::Internal(
+            "No readable screenshots to send".to_string(),
+        ));
+    }
+
+    video_summary::emit_summary_progress(app_handle, SummaryStage::Generating, 70, None);
+    send_chat_completion(base_url, api_key, model, serde_json::Value::Array(content)).await
+}
+
+// 生成文本摘要（用于合并分段摘要等纯文本场景）
+pub async fn generate_text_with_openai(
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+    prompt: &str,
+) -> Result<String, ClarityError> {
+    send_chat_completion(base_url, api_key, model, serde_json::json!(prompt))
+        .await
+        .map(|result| result.content)
+}
@@ -0,0 +1,69 @@
+use crate::error::ClarityError;
+use crate::proxy;
+use serde::Deserialize;
+
+// OpenRouter 接口地址（兼容 OpenAI 协议，总结请求复用 openai 模块）
+pub const DEFAULT_OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
+
+// 默认使用的模型
+pub const DEFAULT_OPENROUTER_MODEL: &str = "openai/gpt-4o-mini";
+
+// OpenRouter /models 响应结构
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    data: Vec<ModelInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelInfo {
+    id: String,
+    #[serde(default)]
+    architecture: Option<ModelArchitecture>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelArchitecture {
+    #[serde(default)]
+    input_modalities: Vec<String>,
+}
+
+// 获取支持图片输入的模型列表（总结需要发送截图）
+pub async fn list_vision_models(base_url: &str) -> Result<Vec<String>, ClarityError> {
+    let client = proxy::client();
+
+    let response = client
+        .get(format!("{}/models", base_url.trim_end_matches('/')))
+        .send()
+        .await
+        .map_err(|e| ClarityError::Network(format!("Failed to fetch OpenRouter models: {}", e)))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ClarityError::from_api_status(
+            status,
+            format!("OpenRouter API error: {} - {}", status, error_text),
+        ));
+    }
+
+    let models: ModelsResponse = response
+        .json()
+        .await
+        .map_err(|e| ClarityError::Ai(format!("Failed to parse response: {}", e)))?;
+
+    let mut ids: Vec<String> = models
+        .data
+        .into_iter()
+        .filter(|model| {
+            model
+                .architecture
+                .as_ref()
+                .map(|a| a.input_modalities.iter().any(|m| m == "image"))
+                .unwrap_or(false)
+        })
+        .map(|model| model.id)
+        .collect();
+    ids.sort();
+
+    Ok(ids)
+}
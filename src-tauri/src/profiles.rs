@@ -0,0 +1,220 @@
+use crate::error::ClarityError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// 默认配置使用原来的数据目录，升级前的数据不需要迁移
+pub const DEFAULT_PROFILE: &str = "default";
+
+const MAX_PROFILE_NAME_CHARS: usize = 32;
+
+// profiles.json：配置列表和下次启动使用的配置（不包括默认配置）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfilesFile {
+    active: String,
+    profiles: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub name: String,
+    pub active: bool,
+    pub data_dir: String,
+}
+
+// 当前进程使用的配置，启动后不再变化（切换配置需要重启）
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+
+// 所有配置共用的应用数据目录（默认配置的数据库所在目录）
+fn base_dir() -> PathBuf {
+    crate::db::default_db_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+fn profiles_file() -> PathBuf {
+    base_dir().join("profiles.json")
+}
+
+fn read_profiles() -> ProfilesFile {
+    std::fs::read_to_string(profiles_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_profiles(file: &ProfilesFile) -> Result<(), String> {
+    let path = profiles_file();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(file)
+        .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// 配置名称只允许小写字母、数字、- 和 _，直接用作目录名
+pub fn validate_profile_name(name: &str) -> Result<String, String> {
+    let name = name.trim().to_lowercase();
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_PROFILE_NAME_CHARS
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(name)
+    } else {
+        Err(format!(
+            "Profile name must be 1-{} characters of a-z, 0-9, - or _",
+            MAX_PROFILE_NAME_CHARS
+        ))
+    }
+}
+
+// 当前进程使用的配置名称
+pub fn active_profile() -> &'static str {
+    ACTIVE_PROFILE.get_or_init(|| {
+        let file = read_profiles();
+        if file.profiles.contains(&file.active) {
+            file.active
+        } else {
+            DEFAULT_PROFILE.to_string()
+        }
+    })
+}
+
+// 配置的数据目录（数据库和截图都在此目录下）
+pub fn profile_dir(name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        base_dir()
+    } else {
+        base_dir().join("profiles").join(name)
+    }
+}
+
+// 把默认配置下的数据路径转换为当前配置下的路径，例如 clarity/clarity.db -> clarity/profiles/work/clarity.db
+pub fn profile_path(default_path: PathBuf) -> PathBuf {
+    let profile = active_profile();
+    if profile == DEFAULT_PROFILE {
+        return default_path;
+    }
+    match default_path.file_name() {
+        Some(file_name) => profile_dir(profile).join(file_name),
+        None => default_path,
+    }
+}
+
+// 钥匙串账户名按配置区分（不同配置的数据库中 Gemini key 的 ID 会重复）
+pub fn keychain_account(account: &str) -> String {
+    profile_keychain_account(active_profile(), account)
+}
+
+// 指定配置的钥匙串账户名
+pub fn profile_keychain_account(profile: &str, account: &str) -> String {
+    if profile == DEFAULT_PROFILE {
+        account.to_string()
+    } else {
+        format!("{}:{}", profile, account)
+    }
+}
+
+// 列出所有配置（默认配置在最前面）
+pub fn list_profiles() -> Vec<Profile> {
+    let active = active_profile();
+    std::iter::once(DEFAULT_PROFILE.to_string())
+        .chain(read_profiles().profiles)
+        .map(|name| Profile {
+            active: name == active,
+            data_dir: profile_dir(&name).to_string_lossy().to_string(),
+            name,
+        })
+        .collect()
+}
+
+// 创建配置和它的数据目录（数据库在第一次使用此配置启动时创建）
+pub fn create_profile(name: &str) -> Result<Profile, ClarityError> {
+    let name = validate_profile_name(name).map_err(ClarityError::InvalidInput)?;
+    let mut file = read_profiles();
+    if name == DEFAULT_PROFILE || file.profiles.contains(&name) {
+        return Err(ClarityError::Conflict(format!(
+            "Profile '{}' already exists",
+            name
+        )));
+    }
+
+    let dir = profile_dir(&name);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    file.profiles.push(name.clone());
+    write_profiles(&file)?;
+
+    Ok(Profile {
+        active: false,
+        data_dir: dir.to_string_lossy().to_string(),
+        name,
+    })
+}
+
+// 当前配置以外的所有配置（包括默认配置）
+pub fn other_profiles() -> Vec<String> {
+    let active = active_profile();
+    std::iter::once(DEFAULT_PROFILE.to_string())
+        .chain(read_profiles().profiles)
+        .filter(|name| name != active)
+        .collect()
+}
+
+// 配置的数据（数据库和截图），清除全部数据时删除
+// 默认配置的目录是所有配置共用的应用数据目录，只包括它的数据库文件（含 WAL 等同名前缀的文件）和截图目录
+pub fn profile_data_paths(name: &str) -> Vec<PathBuf> {
+    if name != DEFAULT_PROFILE {
+        return vec![profile_dir(name)];
+    }
+    let db_name = crate::db::default_db_path()
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(base_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    !db_name.is_empty()
+                        && path
+                            .file_name()
+                            .is_some_and(|name| name.to_string_lossy().starts_with(&db_name))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.push(crate::screenshot::default_recordings_dir());
+    paths
+}
+
+// 只保留当前配置（清除全部数据时删除其他配置后调用）
+pub fn remove_other_profiles() -> Result<(), String> {
+    let active = active_profile();
+    let mut file = read_profiles();
+    file.profiles.retain(|name| name == active);
+    file.active = active.to_string();
+    write_profiles(&file)
+}
+
+// 设置下次启动使用的配置
+pub fn set_next_profile(name: &str) -> Result<(), ClarityError> {
+    let mut file = read_profiles();
+    if name != DEFAULT_PROFILE && !file.profiles.iter().any(|p| p == name) {
+        return Err(ClarityError::NotFound(format!(
+            "Profile '{}' does not exist",
+            name
+        )));
+    }
+    file.active = name.to_string();
+    write_profiles(&file)?;
+    Ok(())
+}
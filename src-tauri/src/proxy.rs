@@ -0,0 +1,66 @@
+use std::sync::RwLock;
+
+// 设置中的代理地址（空字符串表示未设置，由 reqwest 读取 HTTP_PROXY / HTTPS_PROXY / ALL_PROXY 环境变量）
+static PROXY_URL: RwLock<String> = RwLock::new(String::new());
+
+// 支持的代理协议
+const PROXY_SCHEMES: [&str; 4] = ["http://", "https://", "socks5://", "socks5h://"];
+
+// 检查代理地址格式，例如 http://127.0.0.1:7890 或 socks5://127.0.0.1:1080
+pub fn validate_proxy_url(url: &str) -> Result<(), String> {
+    if !PROXY_SCHEMES.iter().any(|scheme| url.starts_with(scheme)) {
+        return Err(format!(
+            "Proxy URL must start with one of: {}",
+            PROXY_SCHEMES.join(", ")
+        ));
+    }
+    reqwest::Proxy::all(url)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid proxy URL: {}", e))
+}
+
+// 更新当前使用的代理地址，之后创建的客户端生效
+pub fn set_proxy_url(url: &str) {
+    if let Ok(mut proxy_url) = PROXY_URL.write() {
+        *proxy_url = url.to_string();
+    }
+}
+
+// 环境变量中配置的代理（仅用于诊断显示）
+pub fn env_proxy_url() -> Option<String> {
+    [
+        "HTTPS_PROXY",
+        "https_proxy",
+        "ALL_PROXY",
+        "all_proxy",
+        "HTTP_PROXY",
+        "http_proxy",
+    ]
+    .iter()
+    .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+}
+
+// 创建访问外部 AI 服务的 HTTP 客户端构建器（设置了代理时使用设置中的代理，否则使用环境变量）
+pub fn client_builder() -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+    let proxy_url = PROXY_URL.read().map(|url| url.clone()).unwrap_or_default();
+    if proxy_url.is_empty() {
+        return builder;
+    }
+
+    match reqwest::Proxy::all(&proxy_url) {
+        Ok(proxy) => builder.proxy(proxy),
+        Err(e) => {
+            log::warn!("Ignoring invalid proxy URL {}: {}", proxy_url, e);
+            builder
+        }
+    }
+}
+
+// 创建访问外部 AI 服务的 HTTP 客户端
+pub fn client() -> reqwest::Client {
+    client_builder().build().unwrap_or_else(|e| {
+        log::warn!("Failed to create HTTP client with proxy: {}", e);
+        reqwest::Client::new()
+    })
+}
@@ -0,0 +1,136 @@
+use crate::app_usage::FocusedWindow;
+use crate::db::ScreenshotTrace;
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+// 规则的处理方式：blur 模糊（指定 region 时只模糊窗口中的这部分），
+// local_only 截图只保存在本地、不会上传给 AI，skip 不截图
+pub const REDACTION_ACTIONS: [&str; 3] = ["blur", "local_only", "skip"];
+
+const MAX_REDACTION_RULES: usize = 50;
+
+// 窗口中要模糊的区域，按窗口宽高的比例（0-1），例如聊天软件右侧的消息区域
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionRegion {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+// 按应用设置的隐私规则：该应用是焦点窗口时生效
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionRule {
+    pub app: String,    // 应用名称，不区分大小写，可以带 .app 后缀（例如 Mail.app）
+    pub action: String, // "blur", "local_only" or "skip"
+    #[serde(default)]
+    pub region: Option<RedactionRegion>, // 只用于 blur，为空时模糊整张截图
+}
+
+impl RedactionRule {
+    pub fn matches(&self, app: &str) -> bool {
+        normalize_app_name(&self.app) == normalize_app_name(app)
+    }
+}
+
+// 一次截图需要做的处理（多条规则同时匹配时合并）
+#[derive(Debug, Clone, Default)]
+pub struct Redaction {
+    pub skip: bool,
+    pub local_only: bool,
+    pub blur: bool,                              // 模糊整张截图
+    pub blur_regions: Vec<(i32, i32, u32, u32)>, // 要模糊的区域（桌面坐标 x, y, 宽, 高）
+}
+
+fn normalize_app_name(app: &str) -> String {
+    let app = app.trim().to_lowercase();
+    app.strip_suffix(".app").map(str::to_string).unwrap_or(app)
+}
+
+fn is_fraction(value: f64) -> bool {
+    (0.0..=1.0).contains(&value)
+}
+
+// 检查并整理规则（去掉应用名称两端的空格），有无效规则时返回错误
+pub fn validate_rules(rules: Vec<RedactionRule>) -> Result<Vec<RedactionRule>, String> {
+    if rules.len() > MAX_REDACTION_RULES {
+        return Err(format!(
+            "At most {} redaction rules are allowed",
+            MAX_REDACTION_RULES
+        ));
+    }
+
+    let mut validated = Vec::with_capacity(rules.len());
+    for mut rule in rules {
+        rule.app = rule.app.trim().to_string();
+        if rule.app.is_empty() {
+            return Err("Redaction rule app name is required".to_string());
+        }
+        if !REDACTION_ACTIONS.contains(&rule.action.as_str()) {
+            return Err(format!(
+                "Redaction rule action must be 'blur', 'local_only' or 'skip', got '{}'",
+                rule.action
+            ));
+        }
+        if let Some(region) = &rule.region {
+            if rule.action != "blur" {
+                return Err("Only blur rules can have a region".to_string());
+            }
+            let valid = [region.x, region.y, region.width, region.height]
+                .into_iter()
+                .all(is_fraction)
+                && region.width > 0.0
+                && region.height > 0.0
+                && region.x + region.width <= 1.0
+                && region.y + region.height <= 1.0;
+            if !valid {
+                return Err(format!(
+                    "Region of the {} rule must be inside the window (fractions from 0 to 1)",
+                    rule.app
+                ));
+            }
+        }
+        validated.push(rule);
+    }
+    Ok(validated)
+}
+
+// 加载规则，没有设置或无法解析时为空
+pub async fn load_rules(pool: &SqlitePool) -> Vec<RedactionRule> {
+    settings::load_redaction_rules_from_db(pool)
+        .await
+        .unwrap_or_default()
+}
+
+// 截图阶段：按焦点窗口计算本次截图需要的处理
+pub fn evaluate(rules: &[RedactionRule], window: &FocusedWindow) -> Redaction {
+    let mut redaction = Redaction::default();
+    for rule in rules.iter().filter(|rule| rule.matches(&window.app)) {
+        match (rule.action.as_str(), &rule.region) {
+            ("skip", _) => redaction.skip = true,
+            ("local_only", _) => redaction.local_only = true,
+            ("blur", Some(region)) => redaction.blur_regions.push((
+                window.x + (region.x * window.width as f64).round() as i32,
+                window.y + (region.y * window.height as f64).round() as i32,
+                (region.width * window.width as f64).round() as u32,
+                (region.height * window.height as f64).round() as u32,
+            )),
+            _ => redaction.blur = true,
+        }
+    }
+    redaction
+}
+
+// 生成总结阶段：截图不能上传给 AI（隐身模式下截取的，或截图时的焦点应用有 local_only 规则），
+// 规则修改后对还没有总结的截图同样生效
+pub fn is_local_only(rules: &[RedactionRule], trace: &ScreenshotTrace) -> bool {
+    trace.private
+        || trace.app_name.as_deref().is_some_and(|app| {
+            rules
+                .iter()
+                .any(|rule| rule.action == "local_only" && rule.matches(app))
+        })
+}
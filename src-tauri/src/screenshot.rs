@@ -1,18 +1,45 @@
-use chrono::Local;
+use chrono::{DateTime, Local};
 use image::{ImageBuffer, Rgb, Rgba};
 use sqlx::SqlitePool;
+use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
-use tokio::sync::Mutex;
-use tokio::time::interval;
+use tokio::sync::{mpsc, watch, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Instant, MissedTickBehavior};
 use xcap::Monitor;
 
+use crate::app_usage::{self, AppUsageTracker};
+use crate::color::{ColorConverter, ColorProfile};
 use crate::db;
+use crate::events::{self, ScreenshotCaptured};
+use crate::health::{self, HealthMonitor};
+use crate::meeting::{ActiveMeeting, MeetingTracker};
+use crate::profiles;
+use crate::redaction::{self, Redaction};
+use crate::sensitive::{self, SensitiveWindow};
+use crate::state::{AppState, RecordingState};
 use std::sync::Arc;
 use std::time::Duration as StdDuration;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 
-// 获取跨平台的应用数据目录
+// 缩略图宽度（像素），高度按比例缩放
+const THUMBNAIL_WIDTH: u32 = 320;
+// 截图间隔（1fps）
+const CAPTURE_INTERVAL: StdDuration = StdDuration::from_secs(1);
+// 等待编码的原始截图数（每张是未压缩的全尺寸图片，保持很小）
+const RAW_FRAME_QUEUE_SIZE: usize = 2;
+// 同时编码的截图数（同样占用全尺寸图片的内存）
+const MAX_CONCURRENT_ENCODES: usize = 2;
+// 等待写入的已压缩截图数
+const ENCODED_FRAME_QUEUE_SIZE: usize = 8;
+
+// 获取当前配置的截图目录
 pub fn get_app_data_dir() -> PathBuf {
+    profiles::profile_path(default_recordings_dir())
+}
+
+// 获取跨平台的应用数据目录（默认配置的截图目录）
+pub fn default_recordings_dir() -> PathBuf {
     let app_name = "clarity";
 
     #[cfg(target_os = "windows")]
@@ -79,15 +106,156 @@ pub async fn ensure_dir_exists(path: &Path) -> Result<(), String> {
     Ok(())
 }
 
-// 截图并压缩保存
-pub async fn capture_and_save_screenshot(
-    storage_path: &Path,
-    index: u64,
-    db_pool: &SqlitePool,
-) -> Result<(), String> {
-    // 获取主屏幕并截图（在 tokio 的 blocking thread 中执行，因为 xcap 是同步的）
+// 截取单个显示器
+fn capture_monitor(monitor: &Monitor) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, String> {
+    #[cfg(target_os = "macos")]
+    {
+        log::debug!(
+            "Capturing monitor: {} ({}x{})",
+            monitor.name().unwrap_or_default(),
+            monitor.width().unwrap_or(0),
+            monitor.height().unwrap_or(0)
+        );
+    }
+
+    // 截图 - 这会捕获整个屏幕，包括所有前景应用
+    // xcap 使用更现代的 macOS API，应该能捕获所有窗口
+    let image = monitor.capture_image().map_err(|e| {
+        format!(
+            "Failed to capture screen: {}. On macOS, ensure Screen Recording permission is granted in System Settings > Privacy & Security > Screen Recording",
+            e
+        )
+    })?;
+
+    #[cfg(target_os = "macos")]
+    {
+        log::debug!(
+            "Captured image: {}x{} pixels",
+            image.width(),
+            image.height()
+        );
+    }
+
+    // xcap 直接返回 RgbaImage (ImageBuffer<Rgba<u8>, Vec<u8>>)
+    Ok(image)
+}
+
+// 一帧截图及其在桌面坐标系中的位置，用于换算鼠标坐标
+struct CapturedFrame {
+    image: ImageBuffer<Rgba<u8>, Vec<u8>>,
+    origin_x: i32,
+    origin_y: i32,
+    scale: f64,                 // 图片像素 / 桌面坐标
+    cursor: Option<(i32, i32)>, // 截图时的鼠标位置（图片像素坐标），获取失败或不在图片内时为空
+}
+
+impl CapturedFrame {
+    // 把桌面坐标换算为图片内的像素坐标，不在图片范围内时返回 None
+    fn to_image_coords(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let image_x = ((x - self.origin_x) as f64 * self.scale).round() as i32;
+        let image_y = ((y - self.origin_y) as f64 * self.scale).round() as i32;
+
+        if image_x < 0
+            || image_y < 0
+            || image_x >= self.image.width() as i32
+            || image_y >= self.image.height() as i32
+        {
+            return None;
+        }
+
+        Some((image_x, image_y))
+    }
+
+    // 把桌面坐标中的矩形 (x, y, 宽, 高) 换算为图片内的像素矩形（裁剪到图片范围内），完全不在图片内时返回 None
+    fn to_image_rect(&self, rect: (i32, i32, u32, u32)) -> Option<(u32, u32, u32, u32)> {
+        let (x, y, width, height) = rect;
+        let to_pixel = |value: i32, origin: i32, max: u32| {
+            (((value - origin) as f64 * self.scale).round() as i64).clamp(0, max as i64) as u32
+        };
+        let left = to_pixel(x, self.origin_x, self.image.width());
+        let top = to_pixel(y, self.origin_y, self.image.height());
+        let right = to_pixel(x + width as i32, self.origin_x, self.image.width());
+        let bottom = to_pixel(y + height as i32, self.origin_y, self.image.height());
+
+        (right > left && bottom > top).then(|| (left, top, right - left, bottom - top))
+    }
+
+    // 读取当前鼠标位置（在截图所在的 blocking 线程中调用，不需要再切换一次线程）
+    fn locate_cursor(mut self) -> Self {
+        self.cursor = cursor_position().and_then(|(x, y)| self.to_image_coords(x, y));
+        self
+    }
+}
+
+// 按显示器的实际布局把所有屏幕拼接成一张图
+fn capture_composite(monitors: &[Monitor]) -> Result<CapturedFrame, String> {
+    let mut frames = Vec::new();
+
+    for monitor in monitors {
+        let image = match capture_monitor(monitor) {
+            Ok(image) => image,
+            Err(e) => {
+                // 单个显示器失败时跳过，避免整帧丢失
+                log::warn!("Skipping monitor in composite capture: {}", e);
+                continue;
+            }
+        };
+
+        // HiDPI 屏幕返回的是物理像素，缩放到逻辑尺寸，保证和布局坐标一致
+        let logical_width = monitor.width().unwrap_or(image.width()).max(1);
+        let logical_height = monitor.height().unwrap_or(image.height()).max(1);
+        let image = if image.width() != logical_width || image.height() != logical_height {
+            image::imageops::resize(
+                &image,
+                logical_width,
+                logical_height,
+                image::imageops::FilterType::Triangle,
+            )
+        } else {
+            image
+        };
+
+        frames.push((monitor.x().unwrap_or(0), monitor.y().unwrap_or(0), image));
+    }
+
+    if frames.is_empty() {
+        return Err("Failed to capture any monitor".to_string());
+    }
+
+    // 计算所有显示器的包围盒
+    let min_x = frames.iter().map(|(x, _, _)| *x).min().unwrap_or(0);
+    let min_y = frames.iter().map(|(_, y, _)| *y).min().unwrap_or(0);
+    let max_x = frames
+        .iter()
+        .map(|(x, _, image)| *x + image.width() as i32)
+        .max()
+        .unwrap_or(0);
+    let max_y = frames
+        .iter()
+        .map(|(_, y, image)| *y + image.height() as i32)
+        .max()
+        .unwrap_or(0);
+
+    let mut canvas: ImageBuffer<Rgba<u8>, Vec<u8>> =
+        ImageBuffer::new((max_x - min_x) as u32, (max_y - min_y) as u32);
+
+    for (x, y, image) in &frames {
+        image::imageops::overlay(&mut canvas, image, (*x - min_x) as i64, (*y - min_y) as i64);
+    }
+
+    Ok(CapturedFrame {
+        image: canvas,
+        origin_x: min_x,
+        origin_y: min_y,
+        scale: 1.0,
+        cursor: None,
+    })
+}
+
+// 使用 xcap 截图（在 tokio 的 blocking thread 中执行，因为 xcap 是同步的）
+async fn capture_with_xcap(composite: bool) -> Result<CapturedFrame, String> {
     // 将获取 monitors 和截图都放在同一个 spawn_blocking 中，避免生命周期问题
-    let img_buffer = tokio::task::spawn_blocking(|| {
+    tokio::task::spawn_blocking(move || {
         let monitors = Monitor::all().map_err(|e| {
             format!(
                 "Failed to get monitors: {}. Make sure Screen Recording permission is granted in System Settings > Privacy & Security > Screen Recording",
@@ -99,147 +267,716 @@ pub async fn capture_and_save_screenshot(
             return Err("No monitors found".to_string());
         }
 
+        // 拼接模式：把所有显示器合成一帧
+        if composite && monitors.len() > 1 {
+            return capture_composite(&monitors).map(CapturedFrame::locate_cursor);
+        }
+
         // 使用主屏幕（第一个显示器）
         let monitor = monitors.into_iter().next().unwrap();
+        let image = capture_monitor(&monitor)?;
+        let logical_width = monitor.width().unwrap_or(image.width()).max(1);
 
-        #[cfg(target_os = "macos")]
-        {
-            eprintln!(
-                "Capturing monitor: {} ({}x{})",
-                monitor.name().unwrap_or_default(),
-                monitor.width().unwrap_or(0),
-                monitor.height().unwrap_or(0)
-            );
+        Ok(CapturedFrame {
+            origin_x: monitor.x().unwrap_or(0),
+            origin_y: monitor.y().unwrap_or(0),
+            scale: image.width() as f64 / logical_width as f64,
+            image,
+            cursor: None,
         }
+        .locate_cursor())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
 
-        // 截图 - 这会捕获整个屏幕，包括所有前景应用
-        // xcap 使用更现代的 macOS API，应该能捕获所有窗口
-        let image = monitor.capture_image().map_err(|e| {
+// 通过 XDG 桌面门户截图（Wayland 下 xcap 往往无法正常工作）
+// 门户本身返回的就是整个桌面，因此不区分主屏和拼接模式
+#[cfg(target_os = "linux")]
+async fn capture_with_portal() -> Result<CapturedFrame, String> {
+    use ashpd::desktop::screenshot::Screenshot;
+
+    let response = Screenshot::request()
+        .interactive(false)
+        .modal(false)
+        .send()
+        .await
+        .and_then(|request| request.response())
+        .map_err(|e| {
             format!(
-                "Failed to capture screen: {}. On macOS, ensure Screen Recording permission is granted in System Settings > Privacy & Security > Screen Recording",
+                "Failed to capture screen via desktop portal: {}. Make sure xdg-desktop-portal is running and screenshots are allowed",
                 e
             )
         })?;
 
-        #[cfg(target_os = "macos")]
-        {
-            eprintln!("Captured image: {}x{} pixels", image.width(), image.height());
-        }
+    let path = response
+        .uri()
+        .to_file_path()
+        .map_err(|_| format!("Unsupported screenshot URI: {}", response.uri()))?;
 
-        // xcap 直接返回 RgbaImage (ImageBuffer<Rgba<u8>, Vec<u8>>)
-        Ok::<ImageBuffer<Rgba<u8>, Vec<u8>>, String>(image)
+    // 门户会把截图写到用户目录，读取后立即删除
+    let frame = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || {
+            let image = image::open(&path)
+                .map(|img| img.to_rgba8())
+                .map_err(|e| format!("Failed to read portal screenshot: {}", e))?;
+            Ok::<CapturedFrame, String>(
+                CapturedFrame {
+                    image,
+                    origin_x: 0,
+                    origin_y: 0,
+                    scale: 1.0,
+                    cursor: None,
+                }
+                .locate_cursor(),
+            )
+        }
     })
     .await
-    .map_err(|e| format!("Task join error: {}", e))??;
+    .map_err(|e| format!("Task join error: {}", e))?;
 
-    let width = img_buffer.width();
-    let height = img_buffer.height();
+    let _ = tokio::fs::remove_file(&path).await;
 
-    // 生成文件名（使用时间戳和索引）
-    let now = Local::now();
-    let date_str = now.format("%Y-%m-%d").to_string();
-    let time_str = now.format("%H-%M-%S").to_string();
-    let filename = format!("{}_{}_{:06}.jpg", date_str, time_str, index);
+    frame
+}
 
-    // 创建日期目录
-    let date_dir = storage_path.join(&date_str);
-    ensure_dir_exists(&date_dir).await?;
+// 获取当前鼠标在桌面坐标系中的位置
+pub fn cursor_position() -> Option<(i32, i32)> {
+    use enigo::{Enigo, Mouse, Settings};
 
-    let file_path = date_dir.join(&filename);
+    let enigo = Enigo::new(&Settings::default()).ok()?;
+    enigo.location().ok()
+}
+
+// 鼠标指针图案：X 为黑色描边，. 为白色填充
+const CURSOR_BITMAP: [&str; 16] = [
+    "X          ",
+    "XX         ",
+    "X.X        ",
+    "X..X       ",
+    "X...X      ",
+    "X....X     ",
+    "X.....X    ",
+    "X......X   ",
+    "X.......X  ",
+    "X........X ",
+    "X.....XXXXX",
+    "X..X..X    ",
+    "X.X X..X   ",
+    "XX  X..X   ",
+    "X    X..X  ",
+    "      XX   ",
+];
+
+// 在截图上绘制鼠标指针（xcap 截图不包含指针）
+fn draw_cursor(image: &mut ImageBuffer<Rgba<u8>, Vec<u8>>, x: i32, y: i32, scale: f64) {
+    // 高分屏上按比例放大指针
+    let size = scale.round().max(1.0) as i32;
+
+    for (row, line) in CURSOR_BITMAP.iter().enumerate() {
+        for (col, ch) in line.chars().enumerate() {
+            let color = match ch {
+                'X' => Rgba([0, 0, 0, 255]),
+                '.' => Rgba([255, 255, 255, 255]),
+                _ => continue,
+            };
+
+            for dy in 0..size {
+                for dx in 0..size {
+                    let px = x + col as i32 * size + dx;
+                    let py = y + row as i32 * size + dy;
+                    if px >= 0
+                        && py >= 0
+                        && (px as u32) < image.width()
+                        && (py as u32) < image.height()
+                    {
+                        image.put_pixel(px as u32, py as u32, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// 根据设置和当前会话选择实际使用的截图后端
+pub fn resolve_capture_backend(backend: &str) -> &'static str {
+    match backend {
+        "xcap" => "xcap",
+        "portal" if cfg!(target_os = "linux") => "portal",
+        _ => {
+            // auto：Linux 的 Wayland 会话使用门户，其余情况使用 xcap
+            let is_wayland = std::env::var("XDG_SESSION_TYPE")
+                .map(|t| t.eq_ignore_ascii_case("wayland"))
+                .unwrap_or(false)
+                || std::env::var_os("WAYLAND_DISPLAY").is_some();
+
+            if cfg!(target_os = "linux") && is_wayland {
+                "portal"
+            } else {
+                "xcap"
+            }
+        }
+    }
+}
+
+// 单次截图使用的配置
+#[derive(Debug, Clone)]
+pub struct CaptureOptions {
+    pub mode: String,    // "primary" or "composite"
+    pub backend: String, // "auto", "xcap" or "portal"
+    pub cursor_overlay: bool,
+    pub color_profile: String, // "srgb", "display_p3" or "hdr"
+    pub private: bool,         // 隐身模式：截图只保存在本地，不会上传给 AI
+    pub redact: bool,          // 保存前模糊整张截图（敏感画面或应用的隐私规则）
+    pub blur_regions: Vec<(i32, i32, u32, u32)>, // 保存前模糊的区域（桌面坐标 x, y, 宽, 高）
+    pub app_name: Option<String>, // 截图时的焦点应用
+}
+
+// 截图阶段的输出：原始图片（已绘制鼠标指针）和截图时间
+struct RawFrame {
+    timestamp: DateTime<Local>,
+    image: ImageBuffer<Rgba<u8>, Vec<u8>>,
+    cursor: Option<(i32, i32)>, // 图片像素坐标
+    color_profile: ColorProfile,
+    private: bool,
+    redact: bool,
+    blur_regions: Vec<(u32, u32, u32, u32)>, // 图片像素坐标
+    app_name: Option<String>,
+}
+
+// 编码阶段的输出：压缩后的原图和缩略图
+struct EncodedFrame {
+    timestamp: DateTime<Local>,
+    width: u32,
+    height: u32,
+    cursor: Option<(i32, i32)>,
+    private: bool,
+    app_name: Option<String>,
+    image: Vec<u8>,
+    thumbnail: Vec<u8>,
+}
+
+// 截图（不做任何磁盘或数据库操作，保证截图节奏不受写入速度影响）
+async fn capture_frame(options: &CaptureOptions) -> Result<RawFrame, String> {
+    let timestamp = Local::now();
+    let composite = options.mode == "composite";
+    let frame = match resolve_capture_backend(&options.backend) {
+        #[cfg(target_os = "linux")]
+        "portal" => capture_with_portal().await?,
+        _ => capture_with_xcap(composite).await?,
+    };
+
+    let blur_regions = options
+        .blur_regions
+        .iter()
+        .filter_map(|rect| frame.to_image_rect(*rect))
+        .collect();
+
+    // 鼠标位置在截图时一起读取（换算为图片像素坐标），获取失败时不影响截图
+    let cursor = frame.cursor;
+    let mut image = frame.image;
+    if options.cursor_overlay {
+        if let Some((x, y)) = cursor {
+            draw_cursor(&mut image, x, y, frame.scale);
+        }
+    }
+
+    Ok(RawFrame {
+        timestamp,
+        image,
+        cursor,
+        color_profile: ColorProfile::from_setting(&options.color_profile),
+        private: options.private,
+        redact: options.redact,
+        blur_regions,
+        app_name: options.app_name.clone(),
+    })
+}
+
+// 压缩截图并生成缩略图
+// converter 在帧之间复用（查找表只在色彩配置变化时重新计算），
+// capacity 是上一帧原图和缩略图 JPEG 的大小，用来预先分配输出缓冲区
+async fn encode_frame(
+    frame: RawFrame,
+    converter: Arc<ColorConverter>,
+    capacity: (usize, usize),
+) -> Result<EncodedFrame, String> {
+    let RawFrame {
+        timestamp,
+        image: img_buffer,
+        cursor,
+        private,
+        redact,
+        blur_regions,
+        app_name,
+        ..
+    } = frame;
+    let width = img_buffer.width();
+    let height = img_buffer.height();
 
-    // 压缩并保存（JPEG 质量 85，平衡质量和文件大小）
-    // JPEG 不支持 RGBA，需要转换为 RGB
-    // 在 blocking thread 中执行图片编码
-    let output = tokio::task::spawn_blocking(move || {
-        // 将 RGBA 转换为 RGB（去掉 alpha 通道）
+    // 颜色转换、压缩和缩略图都在同一个 blocking thread 中完成
+    let (output, thumbnail_output) = tokio::task::spawn_blocking(move || {
+        // 敏感画面和隐私规则指定的区域先模糊，原图和缩略图都不会包含可辨认的内容
+        let img_buffer = if redact {
+            sensitive::blur(img_buffer)
+        } else {
+            let mut img_buffer = img_buffer;
+            for (x, y, width, height) in blur_regions {
+                sensitive::blur_region(&mut img_buffer, x, y, width, height);
+            }
+            img_buffer
+        };
+
+        // JPEG 不支持 RGBA：在原缓冲区内转换为 RGB（去掉 alpha 通道），同时把显示器色彩空间转换为 sRGB
+        // 第 i 个像素写入 [3i, 3i + 3)，不会覆盖还没读取的像素 [4j, 4j + 4)（j > i），不需要第二个全尺寸缓冲区
+        let mut pixels = img_buffer.into_raw();
+        let pixel_count = width as usize * height as usize;
+        for i in 0..pixel_count {
+            let src = i * 4;
+            let rgb = converter.convert(pixels[src], pixels[src + 1], pixels[src + 2]);
+            pixels[i * 3..i * 3 + 3].copy_from_slice(&rgb);
+        }
+        pixels.truncate(pixel_count * 3);
         let rgb_buffer: ImageBuffer<Rgb<u8>, Vec<u8>> =
-            ImageBuffer::from_fn(width, height, |x, y| {
-                let pixel = img_buffer.get_pixel(x, y);
-                Rgb([pixel[0], pixel[1], pixel[2]])
-            });
+            ImageBuffer::from_raw(width, height, pixels)
+                .ok_or_else(|| "Invalid screenshot buffer size".to_string())?;
 
-        let mut output = Vec::new();
+        // 压缩（JPEG 质量 85，平衡质量和文件大小）
+        let mut output = Vec::with_capacity(capacity.0);
         {
             let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut output, 85);
             encoder
                 .encode(&rgb_buffer, width, height, image::ExtendedColorType::Rgb8)
                 .map_err(|e| format!("Failed to encode image: {}", e))?;
         }
-        Ok::<Vec<u8>, String>(output)
+
+        // 生成缩略图，供前端时间线直接加载
+        let thumb_width = THUMBNAIL_WIDTH.min(width);
+        let thumb_height =
+            ((height as u64 * thumb_width as u64) / width.max(1) as u64).max(1) as u32;
+        let thumbnail = image::imageops::thumbnail(&rgb_buffer, thumb_width, thumb_height);
+
+        let mut thumbnail_output = Vec::with_capacity(capacity.1);
+        {
+            let mut encoder =
+                image::codecs::jpeg::JpegEncoder::new_with_quality(&mut thumbnail_output, 75);
+            encoder
+                .encode(
+                    &thumbnail,
+                    thumb_width,
+                    thumb_height,
+                    image::ExtendedColorType::Rgb8,
+                )
+                .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+        }
+
+        Ok::<(Vec<u8>, Vec<u8>), String>((output, thumbnail_output))
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))??;
 
-    tokio::fs::write(&file_path, output)
+    Ok(EncodedFrame {
+        timestamp,
+        width,
+        height,
+        cursor,
+        private,
+        app_name,
+        image: output,
+        thumbnail: thumbnail_output,
+    })
+}
+
+// 先写入临时文件再重命名为 path：停止录制时会等待写入完成，
+// 这样即使进程在写入中途被结束（崩溃、强制退出），也不会留下不完整的 JPEG
+async fn write_atomically(path: &Path, contents: Vec<u8>) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("jpg.tmp");
+    if let Err(e) = tokio::fs::write(&tmp_path, contents).await {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        return Err(e);
+    }
+    tokio::fs::rename(&tmp_path, path).await
+}
+
+// 写入文件并保存到数据库
+async fn save_frame(
+    storage_path: &Path,
+    index: u64,
+    db_pool: &SqlitePool,
+    frame: EncodedFrame,
+) -> Result<ScreenshotCaptured, String> {
+    // 生成文件名（使用截图时间和索引）
+    let date_str = frame.timestamp.format("%Y-%m-%d").to_string();
+    let time_str = frame.timestamp.format("%H-%M-%S").to_string();
+    let filename = format!("{}_{}_{:06}.jpg", date_str, time_str, index);
+
+    // 创建日期目录
+    let date_dir = storage_path.join(&date_str);
+    ensure_dir_exists(&date_dir).await?;
+
+    let file_path = date_dir.join(&filename);
+
+    // 缩略图保存在日期目录下的 thumbs 子目录
+    let thumb_dir = date_dir.join("thumbs");
+    ensure_dir_exists(&thumb_dir).await?;
+    let thumbnail_path = thumb_dir.join(&filename);
+
+    write_atomically(&file_path, frame.image)
         .await
         .map_err(|e| format!("Failed to write file: {}", e))?;
 
+    // 缩略图写入失败不影响原图保存
+    let thumbnail_path_str = match write_atomically(&thumbnail_path, frame.thumbnail).await {
+        Ok(_) => Some(thumbnail_path.to_string_lossy().to_string()),
+        Err(e) => {
+            log::warn!("Failed to write thumbnail: {}", e);
+            None
+        }
+    };
+
     // 获取文件大小
     let file_size = tokio::fs::metadata(&file_path)
         .await
         .map_err(|e| format!("Failed to get file metadata: {}", e))?
         .len() as i64;
 
-    // 保存到数据库
-    let timestamp = Local::now();
+    // 保存到数据库（时间为截图时间，而不是写入完成的时间）
     let file_path_str = file_path.to_string_lossy().to_string();
 
-    if let Err(e) = db::insert_screenshot_trace(
-        db_pool,
-        timestamp,
-        file_path_str,
-        width as i32,
-        height as i32,
+    let trace = db::NewScreenshotTrace {
+        timestamp: frame.timestamp,
+        file_path: file_path_str.clone(),
+        width: frame.width as i32,
+        height: frame.height as i32,
         file_size,
-    )
-    .await
-    {
-        eprintln!("Failed to insert screenshot trace to database: {}", e);
-        // 不返回错误，因为文件已经保存成功
-    }
+        thumbnail_path: thumbnail_path_str.clone(),
+        cursor_x: frame.cursor.map(|(x, _)| x),
+        cursor_y: frame.cursor.map(|(_, y)| y),
+        private: frame.private,
+        app_name: frame.app_name,
+    };
+    let id = match db::insert_screenshot_trace(db_pool, trace).await {
+        Ok(id) => Some(id),
+        Err(e) => {
+            log::error!("Failed to insert screenshot trace to database: {}", e);
+            // 不返回错误，因为文件已经保存成功
+            None
+        }
+    };
 
-    Ok(())
+    Ok(ScreenshotCaptured {
+        id,
+        timestamp: frame.timestamp,
+        file_path: file_path_str,
+        thumbnail_path: thumbnail_path_str,
+        width: frame.width as i32,
+        height: frame.height as i32,
+        file_size,
+    })
 }
 
-// 截图循环任务
-pub async fn screenshot_loop(
+// 等待最早开始编码的一帧完成（没有正在编码的截图时一直等待），保证写入顺序和截图顺序一致
+async fn next_encoded(
+    in_flight: &mut VecDeque<JoinHandle<Result<EncodedFrame, String>>>,
+) -> Result<EncodedFrame, String> {
+    let Some(handle) = in_flight.front_mut() else {
+        return std::future::pending().await;
+    };
+    let result = handle
+        .await
+        .map_err(|e| format!("Task join error: {}", e))
+        .and_then(|result| result);
+    in_flight.pop_front();
+    result
+}
+
+// 编码阶段：最多同时压缩 MAX_CONCURRENT_ENCODES 张截图，按截图顺序交给写入阶段
+// 高分辨率下单张编码可能超过截图间隔，并行编码让截图节奏不受影响
+async fn encode_stage(
+    mut frames: mpsc::Receiver<RawFrame>,
+    encoded: mpsc::Sender<EncodedFrame>,
+    health: Arc<HealthMonitor>,
+) {
+    let mut converter: Option<Arc<ColorConverter>> = None;
+    let mut capacity = (0, 0);
+    let mut in_flight = VecDeque::new();
+    let mut receiving = true;
+
+    while receiving || !in_flight.is_empty() {
+        tokio::select! {
+            frame = frames.recv(), if receiving && in_flight.len() < MAX_CONCURRENT_ENCODES => {
+                let Some(frame) = frame else {
+                    // 截图阶段已经结束，处理完剩下的截图后退出
+                    receiving = false;
+                    continue;
+                };
+                // 色彩配置不变时复用转换器
+                let frame_converter = match converter.take() {
+                    Some(c) if c.profile() == frame.color_profile => c,
+                    _ => Arc::new(ColorConverter::new(frame.color_profile)),
+                };
+                converter = Some(frame_converter.clone());
+                in_flight.push_back(tokio::spawn(encode_frame(frame, frame_converter, capacity)));
+            }
+            result = next_encoded(&mut in_flight) => match result {
+                Ok(frame) => {
+                    capacity = (frame.image.len(), frame.thumbnail.len());
+                    // 写入阶段满了时在这里等待，背压一直传到截图阶段
+                    if encoded.send(frame).await.is_err() {
+                        log::error!("Screenshot writer stopped unexpectedly");
+                        break;
+                    }
+                }
+                Err(e) => {
+                    log::error!("Screenshot encode error: {}", e);
+                    health.record_error(health::TASK_SCREENSHOT, &e);
+                }
+            },
+        }
+    }
+}
+
+// 写入阶段：保存文件和数据库记录，通知前端
+async fn persist_stage(
     storage_path: PathBuf,
-    is_recording: Arc<Mutex<bool>>,
+    mut frames: mpsc::Receiver<EncodedFrame>,
     screenshots_count: Arc<Mutex<u64>>,
     db_pool: SqlitePool,
     app_handle: Option<AppHandle>,
+    health: Arc<HealthMonitor>,
 ) {
-    let mut interval = interval(StdDuration::from_secs(1)); // 1秒 = 1fps
     let mut index = 0u64;
+    while let Some(frame) = frames.recv().await {
+        match save_frame(&storage_path, index, &db_pool, frame).await {
+            Ok(captured) => {
+                index += 1;
+                *screenshots_count.lock().await += 1;
+                health.record_capture();
+                events::emit(app_handle.as_ref(), events::SCREENSHOT_CAPTURED, captured);
+            }
+            Err(e) => {
+                log::error!("Screenshot error: {}", e);
+                health.record_error(health::TASK_SCREENSHOT, &e);
+            }
+        }
+    }
+}
+
+// 截图循环任务使用的共享状态
+pub struct CaptureContext {
+    storage_path: PathBuf, // 录制开始时的截图目录
+    screenshots_count: Arc<Mutex<u64>>,
+    db_pool: SqlitePool,
+    app_handle: Option<AppHandle>,
+    capture_mode: Arc<Mutex<String>>,
+    capture_backend: Arc<Mutex<String>>,
+    cursor_overlay: Arc<Mutex<bool>>,
+    color_profile: Arc<Mutex<String>>,
+    incognito: Arc<Mutex<bool>>,
+    ai_upload_consent: Arc<Mutex<bool>>,
+    sensitive_screen_action: Arc<Mutex<String>>,
+    redaction_rules: Arc<Mutex<Vec<redaction::RedactionRule>>>,
+    meeting_action: Arc<Mutex<String>>,
+    meeting: Arc<Mutex<Option<ActiveMeeting>>>,
+    health: Arc<HealthMonitor>,
+}
+
+impl CaptureContext {
+    pub async fn new(state: &AppState) -> Self {
+        CaptureContext {
+            storage_path: state.storage_path.lock().await.clone(),
+            screenshots_count: state.screenshots_count.clone(),
+            db_pool: state.db_pool.clone(),
+            app_handle: state.app_handle.lock().await.clone(),
+            capture_mode: state.capture_mode.clone(),
+            capture_backend: state.capture_backend.clone(),
+            cursor_overlay: state.cursor_overlay.clone(),
+            color_profile: state.color_profile.clone(),
+            incognito: state.incognito.clone(),
+            ai_upload_consent: state.ai_upload_consent.clone(),
+            sensitive_screen_action: state.sensitive_screen_action.clone(),
+            redaction_rules: state.redaction_rules.clone(),
+            meeting_action: state.meeting_action.clone(),
+            meeting: state.meeting.clone(),
+            health: state.health.clone(),
+        }
+    }
+}
+
+// 截图循环任务：截图 → 编码 → 写入三个阶段通过有界队列连接，
+// 磁盘或数据库变慢时队列写满，截图阶段等待并跳过错过的截图（记录到日志），而不是让截图时间漂移
+pub async fn screenshot_loop(
+    context: CaptureContext,
+    mut recording: watch::Receiver<RecordingState>,
+) {
+    let CaptureContext {
+        storage_path,
+        screenshots_count,
+        db_pool,
+        app_handle,
+        capture_mode,
+        capture_backend,
+        cursor_overlay,
+        color_profile,
+        incognito,
+        ai_upload_consent,
+        sensitive_screen_action,
+        redaction_rules,
+        meeting_action,
+        meeting,
+        health,
+    } = context;
+    let mut interval = interval(CAPTURE_INTERVAL);
+    // 等待下游时错过的截图直接跳过，之后的截图仍然对齐到原来的节奏，不会连续补拍
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut app_usage = AppUsageTracker::new();
+    let mut last_sensitive: Option<SensitiveWindow> = None;
+    let mut meeting_tracker = MeetingTracker::new(meeting);
 
     // 确保目录存在
     if let Err(e) = ensure_dir_exists(&storage_path).await {
-        eprintln!("Failed to create storage directory: {}", e);
+        log::error!("Failed to create storage directory: {}", e);
         return;
     }
 
+    let (raw_tx, raw_rx) = mpsc::channel(RAW_FRAME_QUEUE_SIZE);
+    let (encoded_tx, encoded_rx) = mpsc::channel(ENCODED_FRAME_QUEUE_SIZE);
+    let encoder = tokio::spawn(encode_stage(raw_rx, encoded_tx, health.clone()));
+    let writer = tokio::spawn(persist_stage(
+        storage_path,
+        encoded_rx,
+        screenshots_count,
+        db_pool.clone(),
+        app_handle.clone(),
+        health.clone(),
+    ));
+
     loop {
-        interval.tick().await;
+        // 录制状态变化时立即响应；已经截取的图片总会完整写入后才退出
+        tokio::select! {
+            _ = interval.tick() => {}
+            changed = recording.changed() => {
+                if changed.is_err() {
+                    app_usage.flush(&db_pool).await;
+                    break;
+                }
+            }
+        }
 
-        // 检查是否还在录制
-        let recording = *is_recording.lock().await;
-        if !recording {
-            break;
+        match *recording.borrow_and_update() {
+            RecordingState::Stopped => {
+                app_usage.flush(&db_pool).await;
+                break;
+            }
+            RecordingState::Paused => continue,
+            RecordingState::Recording => {}
         }
 
-        // 执行截图
-        match capture_and_save_screenshot(&storage_path, index, &db_pool).await {
-            Ok(_) => {
-                index += 1;
-                *screenshots_count.lock().await = index;
-                // 发送统计更新事件
-                if let Some(handle) = app_handle.as_ref() {
-                    let _ = handle.emit("statistics-updated", ());
+        // 读取焦点窗口，检查是否是敏感画面（密码管理器、隐私浏览窗口、输入密码的窗口）
+        let focused_window = tokio::task::spawn_blocking(app_usage::focused_window)
+            .await
+            .unwrap_or(None);
+        let action = sensitive_screen_action.lock().await.clone();
+        let sensitive_window = if action == "off" {
+            None
+        } else {
+            focused_window.as_ref().and_then(sensitive::detect)
+        };
+        if sensitive_window != last_sensitive {
+            match &sensitive_window {
+                Some(window) => log::info!(
+                    "Sensitive window detected ({}: {}), screenshots will be {}",
+                    window.reason,
+                    window.app,
+                    if action == "skip" {
+                        "skipped"
+                    } else {
+                        "blurred"
+                    }
+                ),
+                None => log::info!("Sensitive window no longer focused"),
+            }
+            last_sensitive = sensitive_window.clone();
+        }
+
+        // 按焦点应用的隐私规则决定跳过、只保存在本地或模糊
+        let redaction = match &focused_window {
+            Some(window) => redaction::evaluate(&redaction_rules.lock().await, window),
+            None => Redaction::default(),
+        };
+        let skip = redaction.skip || (sensitive_window.is_some() && action == "skip");
+
+        // 会议或屏幕共享期间按设置暂停截图或只保存在本地
+        let meeting_mode = meeting_action.lock().await.clone();
+        let in_meeting = meeting_tracker
+            .update(&db_pool, app_handle.as_ref(), &meeting_mode)
+            .await;
+        let skip = skip || (in_meeting && meeting_mode == "pause");
+
+        // 执行截图（每次读取最新的截图设置，修改后立即生效）
+        let options = CaptureOptions {
+            mode: capture_mode.lock().await.clone(),
+            backend: capture_backend.lock().await.clone(),
+            cursor_overlay: *cursor_overlay.lock().await,
+            color_profile: color_profile.lock().await.clone(),
+            // 用户没有同意上传时的截图之后同意了也不会上传
+            private: *incognito.lock().await
+                || !*ai_upload_consent.lock().await
+                || redaction.local_only
+                || (in_meeting && meeting_mode == "local_only"),
+            redact: sensitive_window.is_some() || redaction.blur,
+            blur_regions: redaction.blur_regions,
+            app_name: focused_window
+                .map(|window| window.app)
+                .filter(|app| !app.is_empty()),
+        };
+        let captured = if skip {
+            // 跳过这次截图，不写入任何文件
+            None
+        } else {
+            Some(capture_frame(&options).await)
+        };
+        match captured {
+            None => {}
+            Some(Ok(frame)) => {
+                // 队列满时等待编码和写入赶上来（背压）
+                let queued_at = Instant::now();
+                if raw_tx.send(frame).await.is_err() {
+                    log::error!("Screenshot encoder stopped unexpectedly");
+                    health.record_error(health::TASK_SCREENSHOT, "Screenshot encoder stopped");
+                    app_usage.flush(&db_pool).await;
+                    break;
+                }
+                let skipped = queued_at.elapsed().as_millis() / CAPTURE_INTERVAL.as_millis();
+                if skipped > 0 {
+                    let message = format!(
+                        "Encoding or writing screenshots is too slow, skipped {} capture(s)",
+                        skipped
+                    );
+                    log::warn!("{}", message);
+                    health.record_error(health::TASK_SCREENSHOT, message);
                 }
             }
-            Err(e) => {
-                eprintln!("Screenshot error: {}", e);
+            Some(Err(e)) => {
+                log::error!("Screenshot error: {}", e);
+                health.record_error(health::TASK_SCREENSHOT, &e);
             }
         }
+
+        // 记录当前使用的应用（每次循环 1 秒），放在截图之后，数据库写入不会推迟截图时间
+        app_usage.record(&db_pool, 1).await;
+    }
+
+    // 录制停止时结束正在进行的会议，记录到停止为止
+    meeting_tracker.finish(&db_pool, app_handle.as_ref()).await;
+
+    // 关闭队列，等待已经截取的图片全部编码并写入
+    drop(raw_tx);
+    if let Err(e) = encoder.await {
+        log::error!("Screenshot encode task panicked: {:?}", e);
+    }
+    if let Err(e) = writer.await {
+        log::error!("Screenshot write task panicked: {:?}", e);
     }
 }
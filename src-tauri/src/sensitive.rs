@@ -0,0 +1,106 @@
+use crate::app_usage::FocusedWindow;
+use image::{imageops, ImageBuffer, Rgba};
+
+// 检测到敏感画面时的处理方式：blur 模糊整张截图，skip 不截图，off 不检测
+pub const SENSITIVE_SCREEN_ACTIONS: [&str; 3] = ["blur", "skip", "off"];
+pub const DEFAULT_SENSITIVE_SCREEN_ACTION: &str = "blur";
+
+// 模糊时先缩小到原来的 1/BLUR_FACTOR 再放大，文字无法辨认，计算量比高斯模糊小得多
+const BLUR_FACTOR: u32 = 32;
+
+// 密码管理器（按应用名称匹配，不区分大小写）
+const PASSWORD_MANAGER_APPS: [&str; 10] = [
+    "1password",
+    "bitwarden",
+    "keepass", // 也匹配 KeePassXC
+    "keeper",
+    "lastpass",
+    "dashlane",
+    "enpass",
+    "proton pass",
+    "keychain access",
+    "seahorse",
+];
+
+// 浏览器隐私窗口的标题（Firefox、Chrome、Edge、Safari 等在标题中标明）
+const PRIVATE_BROWSING_TITLES: [&str; 6] = [
+    "private browsing",
+    "inprivate",
+    "incognito",
+    "隐私浏览",
+    "无痕",
+    "隐身",
+];
+
+// 输入密码的窗口（系统认证对话框、登录页等）
+const PASSWORD_PROMPT_TITLES: [&str; 7] = [
+    "password",
+    "passwort",
+    "contraseña",
+    "パスワード",
+    "密码",
+    "authentication required",
+    "authenticate",
+];
+
+// 检测到的敏感窗口
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensitiveWindow {
+    pub app: String,
+    pub reason: &'static str, // "password manager", "private browsing" or "password prompt"
+}
+
+pub fn is_valid_action(action: &str) -> bool {
+    SENSITIVE_SCREEN_ACTIONS.contains(&action)
+}
+
+fn classify(app: &str, title: &str) -> Option<&'static str> {
+    let app = app.to_lowercase();
+    let title = title.to_lowercase();
+    if PASSWORD_MANAGER_APPS.iter().any(|name| app.contains(name)) {
+        Some("password manager")
+    } else if PRIVATE_BROWSING_TITLES.iter().any(|t| title.contains(t)) {
+        Some("private browsing")
+    } else if PASSWORD_PROMPT_TITLES.iter().any(|t| title.contains(t)) {
+        Some("password prompt")
+    } else {
+        None
+    }
+}
+
+// 根据焦点窗口的应用名称和标题判断是否是敏感画面
+pub fn detect(window: &FocusedWindow) -> Option<SensitiveWindow> {
+    classify(&window.app, &window.title).map(|reason| SensitiveWindow {
+        app: window.app.clone(),
+        reason,
+    })
+}
+
+// 模糊整张截图（敏感窗口的位置换算到截图坐标并不可靠，所以不只处理窗口区域）
+pub fn blur(image: ImageBuffer<Rgba<u8>, Vec<u8>>) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let (width, height) = image.dimensions();
+    let small = imageops::resize(
+        &image,
+        (width / BLUR_FACTOR).max(1),
+        (height / BLUR_FACTOR).max(1),
+        imageops::FilterType::Triangle,
+    );
+    imageops::resize(&small, width, height, imageops::FilterType::Triangle)
+}
+
+// 模糊截图中的一个区域（图片像素坐标，超出图片的部分忽略）
+pub fn blur_region(
+    image: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) {
+    let width = width.min(image.width().saturating_sub(x));
+    let height = height.min(image.height().saturating_sub(y));
+    if width == 0 || height == 0 {
+        return;
+    }
+    let region = imageops::crop_imm(image, x, y, width, height).to_image();
+    imageops::replace(image, &blur(region), x as i64, y as i64);
+}
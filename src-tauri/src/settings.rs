@@ -1,6 +1,10 @@
+use crate::locale;
+use crate::onboarding::ConsentRecord;
+use crate::redaction::RedactionRule;
+use chrono::{DateTime, Local};
 use sqlx::SqlitePool;
 
-// 从数据库加载 API key
+// 从数据库加载旧版本保存的 API key
 pub async fn load_api_key_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
     let result: Option<(String,)> =
         sqlx::query_as("SELECT value FROM settings WHERE key = 'gemini_api_key' LIMIT 1")
@@ -10,33 +14,74 @@ pub async fn load_api_key_from_db(pool: &SqlitePool) -> Result<String, sqlx::Err
     result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
 }
 
-// 保存 API key 到数据库
-pub async fn save_api_key_to_db(pool: &SqlitePool, api_key: &str) -> Result<(), sqlx::Error> {
-    // 确保 settings 表存在
+// 删除旧版本保存在 settings 表中的 API key（已迁移到 gemini_api_keys 表）
+pub async fn delete_api_key_from_db(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM settings WHERE key = 'gemini_api_key'")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 从数据库加载是否把 API key 保存到系统钥匙串（默认开启，无桌面环境的 Linux 可以关闭）
+pub async fn load_use_os_keychain_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'use_os_keychain' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid use_os_keychain format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存是否使用系统钥匙串到数据库
+pub async fn save_use_os_keychain_to_db(
+    pool: &SqlitePool,
+    enabled: bool,
+) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )
+        INSERT INTO settings (key, value)
+        VALUES ('use_os_keychain', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
+    .bind(enabled.to_string())
     .execute(pool)
     .await?;
+    Ok(())
+}
 
-    // 插入或更新
+// 从数据库加载自定义 ffmpeg 路径（空字符串表示未设置）
+pub async fn load_ffmpeg_path_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'ffmpeg_path' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result
+        .map(|r| r.0)
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存自定义 ffmpeg 路径到数据库
+pub async fn save_ffmpeg_path_to_db(pool: &SqlitePool, path: &str) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
         INSERT INTO settings (key, value)
-        VALUES ('gemini_api_key', ?)
+        VALUES ('ffmpeg_path', ?)
         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
-    .bind(api_key)
+    .bind(path)
     .execute(pool)
     .await?;
-
     Ok(())
 }
 
@@ -55,221 +100,1923 @@ pub async fn save_video_resolution_to_db(
     pool: &SqlitePool,
     resolution: &str,
 ) -> Result<(), sqlx::Error> {
-    // 确保 settings 表存在
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )
+        INSERT INTO settings (key, value)
+        VALUES ('video_resolution', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
+    .bind(resolution)
     .execute(pool)
     .await?;
+    Ok(())
+}
+
+// 从数据库加载截图模式
+pub async fn load_capture_mode_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'capture_mode' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
 
+// 保存截图模式到数据库
+pub async fn save_capture_mode_to_db(pool: &SqlitePool, mode: &str) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
         INSERT INTO settings (key, value)
-        VALUES ('video_resolution', ?)
+        VALUES ('capture_mode', ?)
         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
-    .bind(resolution)
+    .bind(mode)
     .execute(pool)
     .await?;
     Ok(())
 }
 
-// 从数据库加载 AI 模型
-pub async fn load_ai_model_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+// 从数据库加载截图后端
+pub async fn load_capture_backend_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
     let result: Option<(String,)> =
-        sqlx::query_as("SELECT value FROM settings WHERE key = 'ai_model' LIMIT 1")
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'capture_backend' LIMIT 1")
             .fetch_optional(pool)
             .await?;
 
     result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
 }
 
-// 保存 AI 模型到数据库
-pub async fn save_ai_model_to_db(pool: &SqlitePool, model: &str) -> Result<(), sqlx::Error> {
-    // 确保 settings 表存在
+// 保存截图后端到数据库
+pub async fn save_capture_backend_to_db(
+    pool: &SqlitePool,
+    backend: &str,
+) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )
+        INSERT INTO settings (key, value)
+        VALUES ('capture_backend', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(backend)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载检测到敏感画面时的处理方式
+pub async fn load_sensitive_screen_action_from_db(
+    pool: &SqlitePool,
+) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'sensitive_screen_action' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存检测到敏感画面时的处理方式到数据库
+pub async fn save_sensitive_screen_action_to_db(
+    pool: &SqlitePool,
+    action: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('sensitive_screen_action', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
+    .bind(action)
     .execute(pool)
     .await?;
+    Ok(())
+}
+
+// 从数据库加载检测到会议或屏幕共享时的处理方式
+pub async fn load_meeting_action_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'meeting_action' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
 
+// 保存检测到会议或屏幕共享时的处理方式到数据库
+pub async fn save_meeting_action_to_db(pool: &SqlitePool, action: &str) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
         INSERT INTO settings (key, value)
-        VALUES ('ai_model', ?)
+        VALUES ('meeting_action', ?)
         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
-    .bind(model)
+    .bind(action)
     .execute(pool)
     .await?;
     Ok(())
 }
 
-// 从数据库加载语言设置
-pub async fn load_language_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+// 从数据库加载是否在截图中绘制鼠标指针
+pub async fn load_cursor_overlay_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
     let result: Option<(String,)> =
-        sqlx::query_as("SELECT value FROM settings WHERE key = 'language' LIMIT 1")
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'cursor_overlay' LIMIT 1")
             .fetch_optional(pool)
             .await?;
 
-    if let Some((lang,)) = result {
-        // 验证语言值是否有效
-        if lang == "en" || lang == "zh" {
-            Ok(lang)
-        } else {
-            Err(sqlx::Error::RowNotFound)
-        }
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid cursor overlay format".into()))
     } else {
         Err(sqlx::Error::RowNotFound)
     }
 }
 
-// 保存语言设置到数据库
-pub async fn save_language_to_db(pool: &SqlitePool, language: &str) -> Result<(), sqlx::Error> {
-    // 确保 settings 表存在
+// 保存是否绘制鼠标指针到数据库
+pub async fn save_cursor_overlay_to_db(
+    pool: &SqlitePool,
+    enabled: bool,
+) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )
+        INSERT INTO settings (key, value)
+        VALUES ('cursor_overlay', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
+    .bind(enabled.to_string())
     .execute(pool)
     .await?;
+    Ok(())
+}
+
+// 从数据库加载截图色彩配置
+pub async fn load_color_profile_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'color_profile' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
 
+// 保存截图色彩配置到数据库
+pub async fn save_color_profile_to_db(pool: &SqlitePool, profile: &str) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
         INSERT INTO settings (key, value)
-        VALUES ('language', ?)
+        VALUES ('color_profile', ?)
         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
-    .bind(language)
+    .bind(profile)
     .execute(pool)
     .await?;
     Ok(())
 }
 
-// 保存 AI 提示词到数据库（按语言）
-pub async fn save_ai_prompt_to_db(
+// 从数据库加载总结视频预设
+pub async fn load_video_preset_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'video_preset' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 从数据库加载总结视频帧率
+pub async fn load_video_fps_from_db(pool: &SqlitePool) -> Result<u32, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'video_fps' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<u32>()
+            .map_err(|_| sqlx::Error::Decode("Invalid video fps format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存总结视频预设和帧率到数据库
+pub async fn save_video_preset_to_db(
     pool: &SqlitePool,
-    prompt: &str,
-    language: Option<&str>,
+    preset: &str,
+    fps: u32,
 ) -> Result<(), sqlx::Error> {
-    // 确保 settings 表存在
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )
+        INSERT INTO settings (key, value)
+        VALUES ('video_preset', ?1), ('video_fps', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
+    .bind(preset)
+    .bind(fps.to_string())
     .execute(pool)
     .await?;
+    Ok(())
+}
+
+// 从数据库加载总结视频编码格式
+pub async fn load_video_codec_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'video_codec' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
 
-    let key = match language {
-        Some("zh") => "ai_prompt_zh",
-        Some("en") => "ai_prompt_en",
-        _ => "ai_prompt", // 默认兼容旧版本
-    };
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
 
+// 保存总结视频编码格式到数据库
+pub async fn save_video_codec_to_db(pool: &SqlitePool, codec: &str) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
         INSERT INTO settings (key, value)
-        VALUES (?1, ?2)
+        VALUES ('video_codec', ?)
         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
-    .bind(key)
-    .bind(prompt)
+    .bind(codec)
     .execute(pool)
     .await?;
     Ok(())
 }
 
-// 从数据库加载 AI 提示词（按语言）
-pub async fn load_ai_prompt_from_db(
-    pool: &SqlitePool,
-    language: Option<&str>,
-) -> Result<String, sqlx::Error> {
-    let key = match language {
-        Some("zh") => "ai_prompt_zh",
-        Some("en") => "ai_prompt_en",
-        _ => "ai_prompt", // 默认兼容旧版本
-    };
+// 从数据库加载AI 服务提供方
+pub async fn load_ai_provider_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'ai_provider' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存 AI 服务提供方到数据库
+pub async fn save_ai_provider_to_db(pool: &SqlitePool, provider: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('ai_provider', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(provider)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
 
+// 从数据库加载OpenAI API key
+pub async fn load_openai_api_key_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
     let result: Option<(String,)> =
-        sqlx::query_as("SELECT value FROM settings WHERE key = ?1 LIMIT 1")
-            .bind(key)
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'openai_api_key' LIMIT 1")
             .fetch_optional(pool)
             .await?;
 
     result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
 }
 
-// 从数据库加载总结间隔
-pub async fn load_summary_interval_from_db(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+// 从数据库加载OpenAI 接口地址
+pub async fn load_openai_base_url_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
     let result: Option<(String,)> =
-        sqlx::query_as("SELECT value FROM settings WHERE key = 'summary_interval_seconds' LIMIT 1")
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'openai_base_url' LIMIT 1")
             .fetch_optional(pool)
             .await?;
 
-    if let Some((value,)) = result {
-        value
-            .parse::<u64>()
-            .map_err(|_| sqlx::Error::Decode("Invalid summary interval format".into()))
-    } else {
-        Err(sqlx::Error::RowNotFound)
-    }
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
 }
 
-// 保存总结间隔到数据库
-pub async fn save_summary_interval_to_db(
+// 从数据库加载OpenAI 模型
+pub async fn load_openai_model_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'openai_model' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存 OpenAI 设置到数据库
+pub async fn save_openai_settings_to_db(
     pool: &SqlitePool,
-    interval_seconds: u64,
+    api_key: &str,
+    base_url: &str,
+    model: &str,
 ) -> Result<(), sqlx::Error> {
-    // 确保 settings 表存在
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )
+        INSERT INTO settings (key, value)
+        VALUES ('openai_api_key', ?1), ('openai_base_url', ?2), ('openai_model', ?3)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
+    .bind(api_key)
+    .bind(base_url)
+    .bind(model)
     .execute(pool)
     .await?;
+    Ok(())
+}
 
-    // 插入或更新
+// 从数据库加载Ollama 服务地址
+pub async fn load_ollama_base_url_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'ollama_base_url' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 从数据库加载Ollama 模型
+pub async fn load_ollama_model_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'ollama_model' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存 Ollama 设置到数据库
+pub async fn save_ollama_settings_to_db(
+    pool: &SqlitePool,
+    base_url: &str,
+    model: &str,
+) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
         INSERT INTO settings (key, value)
-        VALUES ('summary_interval_seconds', ?)
+        VALUES ('ollama_base_url', ?1), ('ollama_model', ?2)
         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
         "#,
     )
-    .bind(interval_seconds.to_string())
+    .bind(base_url)
+    .bind(model)
     .execute(pool)
     .await?;
+    Ok(())
+}
+
+// 从数据库加载OpenRouter API key
+pub async fn load_openrouter_api_key_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'openrouter_api_key' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 从数据库加载OpenRouter 接口地址
+pub async fn load_openrouter_base_url_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'openrouter_base_url' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 从数据库加载OpenRouter 模型
+pub async fn load_openrouter_model_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'openrouter_model' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
 
+// 保存 OpenRouter 设置到数据库
+pub async fn save_openrouter_settings_to_db(
+    pool: &SqlitePool,
+    api_key: &str,
+    base_url: &str,
+    model: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('openrouter_api_key', ?1), ('openrouter_base_url', ?2), ('openrouter_model', ?3)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(api_key)
+    .bind(base_url)
+    .bind(model)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载总结输入方式
+pub async fn load_summary_input_mode_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'summary_input_mode' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存总结输入方式到数据库
+pub async fn save_summary_input_mode_to_db(
+    pool: &SqlitePool,
+    mode: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('summary_input_mode', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(mode)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载 AI 模型
+pub async fn load_ai_model_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'ai_model' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存 AI 模型到数据库
+pub async fn save_ai_model_to_db(pool: &SqlitePool, model: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('ai_model', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(model)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载语言设置
+pub async fn load_language_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'language' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((lang,)) = result {
+        // 验证语言值是否有效
+        locale::normalize(&lang).map_err(|_| sqlx::Error::RowNotFound)
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存语言设置到数据库
+pub async fn save_language_to_db(pool: &SqlitePool, language: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('language', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(language)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 提示词的 key：ai_prompt_<语言标签>，例如 ai_prompt_zh、ai_prompt_ja、ai_prompt_de-AT
+fn ai_prompt_key(language: Option<&str>) -> String {
+    match language {
+        Some(language) => format!("ai_prompt_{}", language),
+        None => "ai_prompt".to_string(), // 默认兼容旧版本
+    }
+}
+
+// 保存 AI 提示词到数据库（按语言）
+pub async fn save_ai_prompt_to_db(
+    pool: &SqlitePool,
+    prompt: &str,
+    language: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let key = ai_prompt_key(language);
+
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES (?1, ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(key)
+    .bind(prompt)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载 AI 提示词（按语言）
+pub async fn load_ai_prompt_from_db(
+    pool: &SqlitePool,
+    language: Option<&str>,
+) -> Result<String, sqlx::Error> {
+    let key = ai_prompt_key(language);
+
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = ?1 LIMIT 1")
+            .bind(key)
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 从数据库加载总结间隔
+pub async fn load_summary_interval_from_db(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'summary_interval_seconds' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<u64>()
+            .map_err(|_| sqlx::Error::Decode("Invalid summary interval format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存总结间隔到数据库
+pub async fn save_summary_interval_to_db(
+    pool: &SqlitePool,
+    interval_seconds: u64,
+) -> Result<(), sqlx::Error> {
+    // 插入或更新
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('summary_interval_seconds', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(interval_seconds.to_string())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 从数据库加载视频保留策略（never / for_n_days / forever）
+pub async fn load_keep_videos_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'keep_videos' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 从数据库加载视频保留天数
+pub async fn load_keep_videos_days_from_db(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'keep_videos_days' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<u64>()
+            .map_err(|_| sqlx::Error::Decode("Invalid keep videos days format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存视频保留策略到数据库
+pub async fn save_keep_videos_to_db(
+    pool: &SqlitePool,
+    keep_videos: &str,
+    days: u64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('keep_videos', ?1), ('keep_videos_days', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(keep_videos)
+    .bind(days.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载总结任务的并发数
+pub async fn load_summary_concurrency_from_db(pool: &SqlitePool) -> Result<usize, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'summary_concurrency' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<usize>()
+            .map_err(|_| sqlx::Error::Decode("Invalid summary concurrency format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载每分钟最多发送的 AI 请求数（0 表示不限制）
+pub async fn load_summary_requests_per_minute_from_db(
+    pool: &SqlitePool,
+) -> Result<u32, sqlx::Error> {
+    let result: Option<(String,)> = sqlx::query_as(
+        "SELECT value FROM settings WHERE key = 'summary_requests_per_minute' LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<u32>()
+            .map_err(|_| sqlx::Error::Decode("Invalid summary requests per minute format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存总结任务队列设置到数据库
+pub async fn save_summary_queue_settings_to_db(
+    pool: &SqlitePool,
+    concurrency: usize,
+    requests_per_minute: u32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('summary_concurrency', ?1), ('summary_requests_per_minute', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(concurrency.to_string())
+    .bind(requests_per_minute.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载每日 token 预算（0 表示不限制）
+pub async fn load_daily_token_budget_from_db(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'daily_token_budget' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<i64>()
+            .map_err(|_| sqlx::Error::Decode("Invalid daily token budget format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载每日费用预算（美元，0 表示不限制）
+pub async fn load_daily_cost_budget_from_db(pool: &SqlitePool) -> Result<f64, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'daily_cost_budget_usd' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<f64>()
+            .map_err(|_| sqlx::Error::Decode("Invalid daily cost budget format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存每日 token 和费用预算到数据库
+pub async fn save_daily_budget_to_db(
+    pool: &SqlitePool,
+    token_budget: i64,
+    cost_budget_usd: f64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('daily_token_budget', ?1), ('daily_cost_budget_usd', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(token_budget.to_string())
+    .bind(cost_budget_usd.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载是否把上一条摘要作为上下文传给模型
+pub async fn load_rolling_context_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'rolling_context' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid rolling context format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载是否同时传入当天的每日总结
+pub async fn load_rolling_context_daily_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'rolling_context_daily' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid rolling context daily format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存摘要上下文设置到数据库
+pub async fn save_rolling_context_to_db(
+    pool: &SqlitePool,
+    enabled: bool,
+    include_daily_summary: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('rolling_context', ?1), ('rolling_context_daily', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(enabled.to_string())
+    .bind(include_daily_summary.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载 Gemini 安全设置的拦截级别
+pub async fn load_gemini_safety_threshold_from_db(
+    pool: &SqlitePool,
+) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'gemini_safety_threshold' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 从数据库加载Gemini temperature（空字符串表示使用模型默认值）
+pub async fn load_gemini_temperature_from_db(pool: &SqlitePool) -> Result<f32, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'gemini_temperature' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<f32>()
+            .map_err(|_| sqlx::Error::Decode("Invalid Gemini temperature format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载Gemini 最大输出 token 数
+pub async fn load_gemini_max_output_tokens_from_db(pool: &SqlitePool) -> Result<u32, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'gemini_max_output_tokens' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<u32>()
+            .map_err(|_| sqlx::Error::Decode("Invalid Gemini max output tokens format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载Gemini 思考预算
+pub async fn load_gemini_thinking_budget_from_db(pool: &SqlitePool) -> Result<i32, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'gemini_thinking_budget' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<i32>()
+            .map_err(|_| sqlx::Error::Decode("Invalid Gemini thinking budget format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存 Gemini 生成参数和安全设置到数据库（None 保存为空字符串，表示使用模型默认值）
+pub async fn save_gemini_generation_settings_to_db(
+    pool: &SqlitePool,
+    safety_threshold: &str,
+    temperature: Option<f32>,
+    max_output_tokens: Option<u32>,
+    thinking_budget: Option<i32>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('gemini_safety_threshold', ?1), ('gemini_temperature', ?2),
+               ('gemini_max_output_tokens', ?3), ('gemini_thinking_budget', ?4)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(safety_threshold)
+    .bind(temperature.map(|v| v.to_string()).unwrap_or_default())
+    .bind(max_output_tokens.map(|v| v.to_string()).unwrap_or_default())
+    .bind(thinking_budget.map(|v| v.to_string()).unwrap_or_default())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载访问 AI 服务使用的代理地址（空字符串表示使用环境变量）
+pub async fn load_proxy_url_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'proxy_url' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存代理地址到数据库
+pub async fn save_proxy_url_to_db(pool: &SqlitePool, url: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('proxy_url', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(url)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载活动分类（JSON 数组）
+pub async fn load_activity_categories_from_db(
+    pool: &SqlitePool,
+) -> Result<Vec<String>, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'activity_categories' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    let value = result
+        .map(|r| r.0)
+        .ok_or_else(|| sqlx::Error::RowNotFound)?;
+    serde_json::from_str(&value).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+// 保存活动分类到数据库
+pub async fn save_activity_categories_to_db(
+    pool: &SqlitePool,
+    categories: &[String],
+) -> Result<(), sqlx::Error> {
+    let value = serde_json::to_string(categories).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('activity_categories', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(value)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载是否开启休息提醒
+pub async fn load_break_reminder_enabled_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'break_reminder_enabled' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid break reminder enabled format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载连续活动多少分钟后提醒休息
+pub async fn load_break_reminder_minutes_from_db(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'break_reminder_minutes' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<u64>()
+            .map_err(|_| sqlx::Error::Decode("Invalid break reminder minutes format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存休息提醒设置到数据库
+pub async fn save_break_reminder_to_db(
+    pool: &SqlitePool,
+    enabled: bool,
+    minutes: u64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('break_reminder_enabled', ?1), ('break_reminder_minutes', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(enabled.to_string())
+    .bind(minutes.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载对比模式使用的模型（空字符串表示关闭对比模式）
+pub async fn load_comparison_model_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'comparison_model' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存对比模式使用的模型到数据库
+pub async fn save_comparison_model_to_db(
+    pool: &SqlitePool,
+    model: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('comparison_model', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(model)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载主模型连续失败后使用的备用模型（空字符串表示不使用）
+pub async fn load_fallback_model_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'fallback_model' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存备用模型到数据库
+pub async fn save_fallback_model_to_db(pool: &SqlitePool, model: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('fallback_model', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(model)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载是否识别截图文字作为总结的补充上下文
+pub async fn load_ocr_enabled_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'ocr_enabled' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid OCR enabled format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载 OCR 识别的语言
+pub async fn load_ocr_languages_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'ocr_languages' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result
+        .map(|r| r.0)
+        .filter(|languages| !languages.is_empty())
+        .ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存 OCR 设置到数据库
+pub async fn save_ocr_settings_to_db(
+    pool: &SqlitePool,
+    enabled: bool,
+    languages: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('ocr_enabled', ?1), ('ocr_languages', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(enabled.to_string())
+    .bind(languages)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载截图保留天数（0 表示永久保留）
+pub async fn load_keep_screenshots_days_from_db(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'keep_screenshots_days' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<u64>()
+            .map_err(|_| sqlx::Error::Decode("Invalid keep screenshots days format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载摘要保留天数（0 表示永久保留）
+pub async fn load_keep_summaries_days_from_db(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'keep_summaries_days' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<u64>()
+            .map_err(|_| sqlx::Error::Decode("Invalid keep summaries days format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存截图和摘要的保留天数到数据库
+pub async fn save_retention_to_db(
+    pool: &SqlitePool,
+    screenshot_days: u64,
+    summary_days: u64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('keep_screenshots_days', ?1), ('keep_summaries_days', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(screenshot_days.to_string())
+    .bind(summary_days.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载 Markdown 导出目录（例如 Obsidian vault 中的文件夹）
+pub async fn load_markdown_export_dir_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'markdown_export_dir' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result
+        .map(|r| r.0)
+        .filter(|dir| !dir.is_empty())
+        .ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 从数据库加载 Markdown 导出模板（空字符串表示使用默认模板）
+pub async fn load_markdown_export_template_from_db(
+    pool: &SqlitePool,
+) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'markdown_export_template' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result
+        .map(|r| r.0)
+        .filter(|template| !template.is_empty())
+        .ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 从数据库加载是否在每天结束后自动导出 Markdown
+pub async fn load_markdown_auto_export_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'markdown_auto_export' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid markdown auto export format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存 Markdown 导出设置到数据库
+pub async fn save_markdown_export_to_db(
+    pool: &SqlitePool,
+    dir: &str,
+    template: &str,
+    auto_export: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('markdown_export_dir', ?1), ('markdown_export_template', ?2), ('markdown_auto_export', ?3)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(dir)
+    .bind(template)
+    .bind(auto_export.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载最后一次自动导出 Markdown 的日期
+pub async fn load_markdown_last_export_date_from_db(
+    pool: &SqlitePool,
+) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> = sqlx::query_as(
+        "SELECT value FROM settings WHERE key = 'markdown_last_export_date' LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存最后一次自动导出 Markdown 的日期到数据库
+pub async fn save_markdown_last_export_date_to_db(
+    pool: &SqlitePool,
+    date: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('markdown_last_export_date', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(date)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载是否自动生成每日总结
+pub async fn load_auto_daily_summary_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'auto_daily_summary' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid auto daily summary format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载自动生成每日总结的时间（本地时间 HH:MM）
+pub async fn load_auto_daily_summary_time_from_db(
+    pool: &SqlitePool,
+) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'auto_daily_summary_time' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存自动生成每日总结的设置到数据库
+pub async fn save_auto_daily_summary_to_db(
+    pool: &SqlitePool,
+    enabled: bool,
+    time: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('auto_daily_summary', ?1), ('auto_daily_summary_time', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(enabled.to_string())
+    .bind(time)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载最后一次自动生成每日总结的日期
+pub async fn load_auto_daily_summary_last_date_from_db(
+    pool: &SqlitePool,
+) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> = sqlx::query_as(
+        "SELECT value FROM settings WHERE key = 'auto_daily_summary_last_date' LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存最后一次自动生成每日总结的日期到数据库
+pub async fn save_auto_daily_summary_last_date_to_db(
+    pool: &SqlitePool,
+    date: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('auto_daily_summary_last_date', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(date)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载最后一次优化数据库的日期
+pub async fn load_database_last_optimized_date_from_db(
+    pool: &SqlitePool,
+) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> = sqlx::query_as(
+        "SELECT value FROM settings WHERE key = 'database_last_optimized_date' LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存最后一次优化数据库的日期到数据库
+pub async fn save_database_last_optimized_date_to_db(
+    pool: &SqlitePool,
+    date: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('database_last_optimized_date', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(date)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载退出时是否为最后不足一个间隔的时间段生成总结
+pub async fn load_summarize_on_exit_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'summarize_on_exit' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid summarize on exit format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存退出时是否生成最后一段总结到数据库
+pub async fn save_summarize_on_exit_to_db(
+    pool: &SqlitePool,
+    enabled: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('summarize_on_exit', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(enabled.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载录制是否在进行中（用户停止录制时清除，程序退出时保留）
+pub async fn load_recording_active_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'recording_active' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid recording active format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存录制是否在进行中到数据库
+pub async fn save_recording_active_to_db(
+    pool: &SqlitePool,
+    active: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('recording_active', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(active.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载最近一次录制会话的开始时间（统计本次录制的截图数量）
+pub async fn load_recording_session_started_at_from_db(
+    pool: &SqlitePool,
+) -> Result<DateTime<Local>, sqlx::Error> {
+    let result: Option<(String,)> = sqlx::query_as(
+        "SELECT value FROM settings WHERE key = 'recording_session_started_at' LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some((value,)) = result {
+        DateTime::parse_from_rfc3339(&value)
+            .map(|t| t.with_timezone(&Local))
+            .map_err(|_| sqlx::Error::Decode("Invalid recording session start format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存录制会话的开始时间到数据库
+pub async fn save_recording_session_started_at_to_db(
+    pool: &SqlitePool,
+    started_at: DateTime<Local>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('recording_session_started_at', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(started_at.to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载启动时是否恢复上次退出时仍在进行的录制
+pub async fn load_resume_recording_on_launch_from_db(
+    pool: &SqlitePool,
+) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> = sqlx::query_as(
+        "SELECT value FROM settings WHERE key = 'resume_recording_on_launch' LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid resume recording format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存启动时是否恢复录制到数据库
+pub async fn save_resume_recording_on_launch_to_db(
+    pool: &SqlitePool,
+    enabled: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('resume_recording_on_launch', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(enabled.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载上次运行是否正常退出
+pub async fn load_clean_shutdown_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'clean_shutdown' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid clean shutdown format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存本次运行是否正常退出到数据库（启动时设为 false，正常退出时设为 true）
+pub async fn save_clean_shutdown_to_db(pool: &SqlitePool, clean: bool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('clean_shutdown', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(clean.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载是否在每段摘要生成后发送系统通知
+pub async fn load_notify_summary_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'notify_summary' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid notify summary format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载是否在每日总结生成后发送系统通知
+pub async fn load_notify_daily_summary_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'notify_daily_summary' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid notify daily summary format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载是否启用免打扰时段
+pub async fn load_quiet_hours_enabled_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'quiet_hours_enabled' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid quiet hours format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载免打扰开始时间（本地时间 HH:MM）
+pub async fn load_quiet_hours_start_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'quiet_hours_start' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 从数据库加载免打扰结束时间（本地时间 HH:MM）
+pub async fn load_quiet_hours_end_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'quiet_hours_end' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存通知设置到数据库
+pub async fn save_notification_settings_to_db(
+    pool: &SqlitePool,
+    summary: bool,
+    daily_summary: bool,
+    quiet_hours_enabled: bool,
+    quiet_hours_start: &str,
+    quiet_hours_end: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('notify_summary', ?1), ('notify_daily_summary', ?2), ('quiet_hours_enabled', ?3),
+            ('quiet_hours_start', ?4), ('quiet_hours_end', ?5)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(summary.to_string())
+    .bind(daily_summary.to_string())
+    .bind(quiet_hours_enabled.to_string())
+    .bind(quiet_hours_start)
+    .bind(quiet_hours_end)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载日志级别
+pub async fn load_log_level_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'log_level' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存日志级别到数据库
+pub async fn save_log_level_to_db(pool: &SqlitePool, level: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('log_level', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(level)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载是否开启本地 REST API
+pub async fn load_api_server_enabled_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'api_server_enabled' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid api server enabled format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 从数据库加载本地 REST API 端口
+pub async fn load_api_server_port_from_db(pool: &SqlitePool) -> Result<u16, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'api_server_port' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<u16>()
+            .map_err(|_| sqlx::Error::Decode("Invalid api server port format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存本地 REST API 设置到数据库
+pub async fn save_api_server_settings_to_db(
+    pool: &SqlitePool,
+    enabled: bool,
+    port: u16,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('api_server_enabled', ?1), ('api_server_port', ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(enabled.to_string())
+    .bind(port.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载本地 REST API 的访问令牌
+pub async fn load_api_server_token_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'api_server_token' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存本地 REST API 的访问令牌到数据库
+pub async fn save_api_server_token_to_db(
+    pool: &SqlitePool,
+    token: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('api_server_token', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(token)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载是否开启隐身模式（截图只保存在本地，不上传给 AI）
+pub async fn load_incognito_mode_from_db(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'incognito_mode' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some((value,)) = result {
+        value
+            .parse::<bool>()
+            .map_err(|_| sqlx::Error::Decode("Invalid incognito mode format".into()))
+    } else {
+        Err(sqlx::Error::RowNotFound)
+    }
+}
+
+// 保存是否开启隐身模式到数据库
+pub async fn save_incognito_mode_to_db(
+    pool: &SqlitePool,
+    enabled: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('incognito_mode', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(enabled.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载应用锁 PIN 的哈希（Argon2 PHC 字符串），没有设置时返回 None
+pub async fn load_app_lock_pin_hash_from_db(
+    pool: &SqlitePool,
+) -> Result<Option<String>, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'app_lock_pin_hash' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(result.map(|r| r.0))
+}
+
+// 保存应用锁 PIN 的哈希到数据库
+pub async fn save_app_lock_pin_hash_to_db(
+    pool: &SqlitePool,
+    pin_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('app_lock_pin_hash', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(pin_hash)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 删除应用锁 PIN（关闭应用锁）
+pub async fn delete_app_lock_pin_hash_from_db(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM settings WHERE key = 'app_lock_pin_hash'")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 从数据库加载按应用设置的隐私规则（JSON 数组）
+pub async fn load_redaction_rules_from_db(
+    pool: &SqlitePool,
+) -> Result<Vec<RedactionRule>, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'redaction_rules' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    let value = result
+        .map(|r| r.0)
+        .ok_or_else(|| sqlx::Error::RowNotFound)?;
+    serde_json::from_str(&value).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+// 保存按应用设置的隐私规则到数据库
+pub async fn save_redaction_rules_to_db(
+    pool: &SqlitePool,
+    rules: &[RedactionRule],
+) -> Result<(), sqlx::Error> {
+    let value = serde_json::to_string(rules).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('redaction_rules', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(value)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载引导流程当前的步骤
+pub async fn load_onboarding_step_from_db(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = 'onboarding_step' LIMIT 1")
+            .fetch_optional(pool)
+            .await?;
+
+    result.map(|r| r.0).ok_or_else(|| sqlx::Error::RowNotFound)
+}
+
+// 保存引导流程当前的步骤到数据库
+pub async fn save_onboarding_step_to_db(pool: &SqlitePool, step: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES ('onboarding_step', ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(step)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 从数据库加载用户的同意记录（JSON），consent 为 "screen_capture" 或 "ai_upload"，没有记录时为 None
+pub async fn load_consent_from_db(
+    pool: &SqlitePool,
+    consent: &str,
+) -> Result<Option<ConsentRecord>, sqlx::Error> {
+    let result: Option<(String,)> =
+        sqlx::query_as("SELECT value FROM settings WHERE key = ? LIMIT 1")
+            .bind(format!("{}_consent", consent))
+            .fetch_optional(pool)
+            .await?;
+
+    result
+        .map(|r| serde_json::from_str(&r.0).map_err(|e| sqlx::Error::Decode(Box::new(e))))
+        .transpose()
+}
+
+// 保存用户的同意记录到数据库
+pub async fn save_consent_to_db(
+    pool: &SqlitePool,
+    consent: &str,
+    record: &ConsentRecord,
+) -> Result<(), sqlx::Error> {
+    let value = serde_json::to_string(record).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value)
+        VALUES (?, ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(format!("{}_consent", consent))
+    .bind(value)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 读取 settings 表中的全部设置（按 key 排序），用于导出配置
+pub async fn load_all_settings_from_db(
+    pool: &SqlitePool,
+) -> Result<Vec<(String, String)>, sqlx::Error> {
+    sqlx::query_as("SELECT key, value FROM settings ORDER BY key")
+        .fetch_all(pool)
+        .await
+}
+
+// 在一个事务中保存多项设置，用于导入配置（任何一项失败时全部不生效）
+pub async fn save_settings_to_db(
+    pool: &SqlitePool,
+    values: &[(String, String)],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    for (key, value) in values {
+        sqlx::query(
+            r#"
+            INSERT INTO settings (key, value)
+            VALUES (?1, ?2)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(key)
+        .bind(value)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
     Ok(())
 }
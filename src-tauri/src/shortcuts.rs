@@ -0,0 +1,33 @@
+use crate::commands;
+use crate::state::AppState;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+// 全局快捷键：切换隐身模式（Ctrl+Shift+I 是开发者工具，这里用 Alt）
+pub const INCOGNITO_SHORTCUT: &str = "CommandOrControl+Alt+I";
+
+// 注册全局快捷键，需要在 AppState 注册之后调用
+// 快捷键被其他程序占用时只记录日志，仍然可以在界面和托盘中切换
+pub fn setup(app: &AppHandle) -> tauri::Result<()> {
+    app.plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(|app, _shortcut, event| {
+                if event.state() != ShortcutState::Pressed {
+                    return;
+                }
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app.state::<AppState>();
+                    if let Err(e) = commands::toggle_incognito(&state).await {
+                        log::error!("Failed to toggle incognito mode: {}", e);
+                    }
+                });
+            })
+            .build(),
+    )?;
+
+    if let Err(e) = app.global_shortcut().register(INCOGNITO_SHORTCUT) {
+        log::warn!("Failed to register shortcut {}: {}", INCOGNITO_SHORTCUT, e);
+    }
+    Ok(())
+}
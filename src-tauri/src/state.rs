@@ -1,15 +1,103 @@
+use crate::api_server::ApiServerHandle;
+use crate::commands::{RateLimiter, SummaryLoopHandle, SummaryWorkerHandle};
 use crate::db;
+use crate::deep_link::DeepLinkTarget;
+use crate::events;
+use crate::health::HealthMonitor;
+use crate::keychain;
+use crate::locale;
+use crate::logging;
+use crate::meeting::{self, ActiveMeeting};
+use crate::onboarding;
+use crate::proxy;
+use crate::redaction::{self, RedactionRule};
 use crate::screenshot;
+use crate::sensitive;
 use crate::settings;
+use serde::Serialize;
 use sqlx::SqlitePool;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
-use tokio::sync::Mutex;
+use tauri::AppHandle;
+use tokio::sync::{watch, Mutex};
+
+// 录制状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingState {
+    Stopped,
+    Recording,
+    Paused,
+}
+
+// 录制控制器：录制状态保存在 watch channel 中，
+// 截图、总结等后台任务订阅后在状态变化时立即响应，而不是每次循环轮询
+pub struct RecordingController {
+    sender: watch::Sender<RecordingState>,
+}
+
+impl RecordingController {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(RecordingState::Stopped);
+        Self { sender }
+    }
+
+    pub fn state(&self) -> RecordingState {
+        *self.sender.borrow()
+    }
+
+    // 录制会话是否在进行中（暂停也算）
+    pub fn is_active(&self) -> bool {
+        self.state() != RecordingState::Stopped
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<RecordingState> {
+        self.sender.subscribe()
+    }
+
+    // 以下状态切换都是原子的，返回 false 表示当前状态不允许该操作
+    pub fn start(&self) -> bool {
+        self.transition(RecordingState::Stopped, RecordingState::Recording)
+    }
+
+    pub fn pause(&self) -> bool {
+        self.transition(RecordingState::Recording, RecordingState::Paused)
+    }
+
+    pub fn resume(&self) -> bool {
+        self.transition(RecordingState::Paused, RecordingState::Recording)
+    }
+
+    pub fn stop(&self) -> bool {
+        self.sender.send_if_modified(|state| {
+            if *state == RecordingState::Stopped {
+                return false;
+            }
+            *state = RecordingState::Stopped;
+            true
+        })
+    }
+
+    fn transition(&self, from: RecordingState, to: RecordingState) -> bool {
+        self.sender.send_if_modified(|state| {
+            if *state != from {
+                return false;
+            }
+            *state = to;
+            true
+        })
+    }
+}
+
+impl Default for RecordingController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 // 全局状态管理
 pub struct AppState {
-    pub is_recording: Arc<Mutex<bool>>,
+    pub recording: RecordingController,
     pub screenshots_count: Arc<Mutex<u64>>,
     pub storage_path: Arc<Mutex<PathBuf>>,
     pub handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
@@ -21,14 +109,94 @@ pub struct AppState {
     pub _ai_prompt: Arc<Mutex<String>>,
     pub language: Arc<Mutex<String>>,
     pub video_resolution: Arc<Mutex<String>>, // "low" or "default"
+    pub video_preset: Arc<Mutex<String>>,     // "tiny", "540p" or "720p"
+    pub video_fps: Arc<Mutex<u32>>,
+    pub video_codec: Arc<Mutex<String>>, // "h264", "vp9" or "av1"
+    pub ffmpeg_path: Arc<Mutex<Option<String>>>,
+    pub capture_mode: Arc<Mutex<String>>, // "primary" or "composite"
+    pub capture_backend: Arc<Mutex<String>>, // "auto", "xcap" or "portal"
+    pub cursor_overlay: Arc<Mutex<bool>>,
+    pub color_profile: Arc<Mutex<String>>, // "srgb", "display_p3" or "hdr"
+    pub sensitive_screen_action: Arc<Mutex<String>>, // "blur", "skip" or "off"
+    pub redaction_rules: Arc<Mutex<Vec<RedactionRule>>>, // 按应用设置的隐私规则
+    pub meeting_action: Arc<Mutex<String>>, // "pause", "local_only" or "off"
+    pub meeting: Arc<Mutex<Option<ActiveMeeting>>>, // 正在进行的会议，没有时为 None
+    pub summary_rate_limiter: Arc<RateLimiter>, // 所有总结请求共用的速率限制（包括手动重新生成）
+    pub primary_model_failures: Arc<AtomicU32>, // 主模型连续失败的次数，成功后清零
+    pub health: Arc<HealthMonitor>,
+    pub summary_loop: Arc<Mutex<Option<SummaryLoopHandle>>>, // 总结定时任务，停止后为 None
+    pub summary_worker: Arc<Mutex<Option<SummaryWorkerHandle>>>, // 总结任务 worker，停止后为 None
+    pub api_server: Arc<Mutex<Option<ApiServerHandle>>>,     // 本地 REST API，关闭时为 None
+    pub pending_deep_link: Arc<Mutex<Option<DeepLinkTarget>>>, // 前端还没有处理的 clarity:// 链接
+    pub ephemeral: bool, // 临时模式：数据库在内存中，截图写入临时目录，退出后全部删除
+    pub locked: Arc<Mutex<bool>>, // 应用锁：锁定时拒绝返回历史记录的命令
+    pub incognito: Arc<Mutex<bool>>, // 隐身模式：继续截图，但不上传给 AI
+    pub ai_upload_consent: Arc<Mutex<bool>>, // 用户是否同意上传给 AI，没有同意时截图只保存在本地
 }
 
 impl AppState {
     pub async fn new() -> Result<Self, sqlx::Error> {
-        let db_pool = db::init_db().await?;
+        // 数据库加密后密码保存在系统钥匙串中
+        let database_key = keychain::load_database_key().await;
+        let db_pool = db::init_db(database_key.as_deref()).await?;
+        Self::with_pool(db_pool, screenshot::get_app_data_dir()).await
+    }
+
+    // 临时模式：不保存任何数据，用于试用（--ephemeral）
+    pub async fn ephemeral() -> Result<Self, sqlx::Error> {
+        let db_pool = db::init_memory_db().await?;
+        // API key 只保存在内存数据库中，不写入系统钥匙串
+        settings::save_use_os_keychain_to_db(&db_pool, false).await?;
+        let storage_path =
+            std::env::temp_dir().join(format!("clarity-ephemeral-{}", std::process::id()));
+        log::warn!(
+            "Running in ephemeral mode, nothing will be kept after exit (screenshots in {})",
+            storage_path.display()
+        );
 
-        // 从数据库加载 API key
-        let api_key = settings::load_api_key_from_db(&db_pool).await.ok();
+        let mut state = Self::with_pool(db_pool, storage_path).await?;
+        state.ephemeral = true;
+        Ok(state)
+    }
+
+    // 使用指定的连接池和截图目录创建状态（连接池需要已经执行过迁移，init_db 和 init_memory_db 都会执行）
+    pub async fn with_pool(
+        db_pool: SqlitePool,
+        storage_path: PathBuf,
+    ) -> Result<Self, sqlx::Error> {
+        // 从数据库加载代理设置（在创建任何 HTTP 客户端之前）
+        let proxy_url = settings::load_proxy_url_from_db(&db_pool)
+            .await
+            .unwrap_or_default();
+        if !proxy_url.is_empty() {
+            log::info!("Using configured proxy for AI API calls");
+            proxy::set_proxy_url(&proxy_url);
+        }
+
+        // 旧版本只保存一个 API key，迁移到 Gemini key 列表
+        if db::get_gemini_api_keys(&db_pool).await?.is_empty() {
+            if let Ok(legacy_key) = settings::load_api_key_from_db(&db_pool).await {
+                if !legacy_key.is_empty() {
+                    db::insert_gemini_api_key(&db_pool, &legacy_key, None, 0).await?;
+                }
+                settings::delete_api_key_from_db(&db_pool).await?;
+            }
+        }
+
+        // 按设置把 API key 移到系统钥匙串（或移回数据库），失败时保持原样
+        let use_keychain = keychain::is_enabled(&db_pool).await;
+        if let Err(e) = keychain::migrate_gemini_api_keys(&db_pool, use_keychain).await {
+            log::warn!("Failed to migrate Gemini API keys: {}", e);
+        }
+
+        // 优先级最高的 key 作为默认 key
+        let api_key = match keychain::load_gemini_api_keys(&db_pool).await {
+            Ok(keys) => keys.into_iter().next().map(|k| k.api_key),
+            Err(e) => {
+                log::error!("Failed to load Gemini API keys: {}", e);
+                None
+            }
+        };
 
         // 从数据库加载总结间隔（默认 45 秒）
         let summary_interval = settings::load_summary_interval_from_db(&db_pool)
@@ -45,25 +213,92 @@ impl AppState {
             .await
             .unwrap_or_else(|_| "low".to_string());
 
-        // 从数据库加载 AI 提示词（默认根据系统语言，如果没有则使用中文）
-        // 优化后的 prompt：更聚焦于效率分析，减少不必要的描述
-        let default_prompt_zh = "分析这段屏幕活动视频，提供简洁的活动摘要。重点关注：1) 主要使用的应用/网站；2) 活动类型（工作/娱乐/学习等）；3) 是否有分心或低效行为。用中文回答，控制在100字以内。".to_string();
-        let _default_prompt_en = "Analyze this screen activity video and provide a concise activity summary. Focus on: 1) Main apps/websites used; 2) Activity type (work/entertainment/learning, etc.); 3) Any distractions or inefficient behaviors. Respond in English, keep it under 100 words.".to_string();
+        // 从数据库加载截图模式（默认只截主屏幕）
+        let capture_mode = settings::load_capture_mode_from_db(&db_pool)
+            .await
+            .unwrap_or_else(|_| "primary".to_string());
 
-        // 尝试加载中文提示词，如果没有则使用默认值
-        let ai_prompt = settings::load_ai_prompt_from_db(&db_pool, Some("zh"))
+        // 从数据库加载截图后端（默认自动选择）
+        let capture_backend = settings::load_capture_backend_from_db(&db_pool)
             .await
-            .unwrap_or_else(|_| default_prompt_zh.clone());
+            .unwrap_or_else(|_| "auto".to_string());
+
+        // 从数据库加载是否绘制鼠标指针（默认关闭，只记录坐标）
+        let cursor_overlay = settings::load_cursor_overlay_from_db(&db_pool)
+            .await
+            .unwrap_or(false);
+
+        // 从数据库加载截图色彩配置（默认 sRGB，不做转换）
+        let color_profile = settings::load_color_profile_from_db(&db_pool)
+            .await
+            .unwrap_or_else(|_| "srgb".to_string());
+
+        // 从数据库加载敏感画面的处理方式（默认模糊）
+        let sensitive_screen_action = settings::load_sensitive_screen_action_from_db(&db_pool)
+            .await
+            .unwrap_or_else(|_| sensitive::DEFAULT_SENSITIVE_SCREEN_ACTION.to_string());
+
+        // 从数据库加载按应用设置的隐私规则（默认没有规则）
+        let redaction_rules = redaction::load_rules(&db_pool).await;
+
+        // 从数据库加载检测到会议时的处理方式（默认不检测）
+        let meeting_action = settings::load_meeting_action_from_db(&db_pool)
+            .await
+            .unwrap_or_else(|_| meeting::DEFAULT_MEETING_ACTION.to_string());
+
+        // 从数据库加载总结视频预设和帧率（默认 tiny / 1fps）
+        let video_preset = settings::load_video_preset_from_db(&db_pool)
+            .await
+            .unwrap_or_else(|_| "tiny".to_string());
+        let video_fps = settings::load_video_fps_from_db(&db_pool)
+            .await
+            .unwrap_or(1);
+
+        // 从数据库加载总结视频编码格式（默认 h264，兼容性最好）
+        let video_codec = settings::load_video_codec_from_db(&db_pool)
+            .await
+            .unwrap_or_else(|_| "h264".to_string());
+
+        // 从数据库加载自定义 ffmpeg 路径（未设置时自动查找）
+        let ffmpeg_path = settings::load_ffmpeg_path_from_db(&db_pool).await.ok();
 
         // 从数据库加载语言设置（默认中文）
         let language = settings::load_language_from_db(&db_pool)
             .await
-            .unwrap_or_else(|_| "zh".to_string());
+            .unwrap_or_else(|_| locale::DEFAULT_LANGUAGE.to_string());
+
+        // 最近一次录制会话的截图数，重启后 get_status 仍然显示上次录制的数量
+        let screenshots_count =
+            match settings::load_recording_session_started_at_from_db(&db_pool).await {
+                Ok(started_at) => db::get_screenshot_count(&db_pool, Some(started_at), None)
+                    .await
+                    .unwrap_or(0)
+                    .max(0) as u64,
+                Err(_) => 0,
+            };
+
+        // 从数据库加载隐身模式（默认关闭），重启后保持，避免意外恢复上传
+        let incognito = settings::load_incognito_mode_from_db(&db_pool)
+            .await
+            .unwrap_or(false);
+
+        // 用户在引导流程中是否同意上传给 AI（没有选择过时不上传）
+        let ai_upload_consent =
+            onboarding::is_granted(&db_pool, onboarding::CONSENT_AI_UPLOAD).await;
+
+        // 设置了 PIN 时启动后处于锁定状态
+        let locked = matches!(
+            settings::load_app_lock_pin_hash_from_db(&db_pool).await,
+            Ok(Some(_))
+        );
+
+        // 当前语言的 AI 提示词（没有保存过时使用内置模板）
+        let ai_prompt = locale::summary_prompt(&db_pool, &language).await;
 
         Ok(Self {
-            is_recording: Arc::new(Mutex::new(false)),
-            screenshots_count: Arc::new(Mutex::new(0)),
-            storage_path: Arc::new(Mutex::new(screenshot::get_app_data_dir())),
+            recording: RecordingController::new(),
+            screenshots_count: Arc::new(Mutex::new(screenshots_count)),
+            storage_path: Arc::new(Mutex::new(storage_path)),
             handle: Arc::new(Mutex::new(None)),
             db_pool: db_pool.clone(),
             gemini_api_key: Arc::new(Mutex::new(api_key)),
@@ -73,13 +308,92 @@ impl AppState {
             _ai_prompt: Arc::new(Mutex::new(ai_prompt)),
             language: Arc::new(Mutex::new(language)),
             video_resolution: Arc::new(Mutex::new(video_resolution)),
+            video_preset: Arc::new(Mutex::new(video_preset)),
+            video_fps: Arc::new(Mutex::new(video_fps)),
+            video_codec: Arc::new(Mutex::new(video_codec)),
+            ffmpeg_path: Arc::new(Mutex::new(ffmpeg_path)),
+            capture_mode: Arc::new(Mutex::new(capture_mode)),
+            capture_backend: Arc::new(Mutex::new(capture_backend)),
+            cursor_overlay: Arc::new(Mutex::new(cursor_overlay)),
+            color_profile: Arc::new(Mutex::new(color_profile)),
+            sensitive_screen_action: Arc::new(Mutex::new(sensitive_screen_action)),
+            redaction_rules: Arc::new(Mutex::new(redaction_rules)),
+            meeting_action: Arc::new(Mutex::new(meeting_action)),
+            meeting: Arc::new(Mutex::new(None)),
+            summary_rate_limiter: Arc::new(RateLimiter::new(db_pool.clone())),
+            primary_model_failures: Arc::new(AtomicU32::new(0)),
+            health: Arc::new(HealthMonitor::new()),
+            summary_loop: Arc::new(Mutex::new(None)),
+            summary_worker: Arc::new(Mutex::new(None)),
+            api_server: Arc::new(Mutex::new(None)),
+            pending_deep_link: Arc::new(Mutex::new(None)),
+            ephemeral: false,
+            locked: Arc::new(Mutex::new(locked)),
+            incognito: Arc::new(Mutex::new(incognito)),
+            ai_upload_consent: Arc::new(Mutex::new(ai_upload_consent)),
         })
     }
 
-    // 发送统计更新事件
-    pub async fn emit_statistics_updated(&self) {
-        if let Some(handle) = self.app_handle.lock().await.as_ref() {
-            let _ = handle.emit("statistics-updated", ());
-        }
+    // 数据库中的设置被整体替换后（例如导入配置），重新加载内存中的设置，默认值与 new 相同
+    pub async fn reload_settings(&self) {
+        let pool = &self.db_pool;
+        *self.language.lock().await = settings::load_language_from_db(pool)
+            .await
+            .unwrap_or_else(|_| locale::DEFAULT_LANGUAGE.to_string());
+        *self.summary_interval_seconds.lock().await = settings::load_summary_interval_from_db(pool)
+            .await
+            .unwrap_or(45);
+        *self.ai_model.lock().await = settings::load_ai_model_from_db(pool)
+            .await
+            .unwrap_or_else(|_| "gemini-3-flash-preview".to_string());
+        *self.video_resolution.lock().await = settings::load_video_resolution_from_db(pool)
+            .await
+            .unwrap_or_else(|_| "low".to_string());
+        *self.video_preset.lock().await = settings::load_video_preset_from_db(pool)
+            .await
+            .unwrap_or_else(|_| "tiny".to_string());
+        *self.video_fps.lock().await = settings::load_video_fps_from_db(pool).await.unwrap_or(1);
+        *self.video_codec.lock().await = settings::load_video_codec_from_db(pool)
+            .await
+            .unwrap_or_else(|_| "h264".to_string());
+        *self.ffmpeg_path.lock().await = settings::load_ffmpeg_path_from_db(pool).await.ok();
+        *self.capture_mode.lock().await = settings::load_capture_mode_from_db(pool)
+            .await
+            .unwrap_or_else(|_| "primary".to_string());
+        *self.capture_backend.lock().await = settings::load_capture_backend_from_db(pool)
+            .await
+            .unwrap_or_else(|_| "auto".to_string());
+        *self.cursor_overlay.lock().await = settings::load_cursor_overlay_from_db(pool)
+            .await
+            .unwrap_or(false);
+        *self.color_profile.lock().await = settings::load_color_profile_from_db(pool)
+            .await
+            .unwrap_or_else(|_| "srgb".to_string());
+        *self.sensitive_screen_action.lock().await =
+            settings::load_sensitive_screen_action_from_db(pool)
+                .await
+                .unwrap_or_else(|_| sensitive::DEFAULT_SENSITIVE_SCREEN_ACTION.to_string());
+        *self.redaction_rules.lock().await = redaction::load_rules(pool).await;
+        *self.meeting_action.lock().await = settings::load_meeting_action_from_db(pool)
+            .await
+            .unwrap_or_else(|_| meeting::DEFAULT_MEETING_ACTION.to_string());
+        *self.ai_upload_consent.lock().await =
+            onboarding::is_granted(pool, onboarding::CONSENT_AI_UPLOAD).await;
+
+        proxy::set_proxy_url(
+            &settings::load_proxy_url_from_db(pool)
+                .await
+                .unwrap_or_default(),
+        );
+        logging::set_level(
+            &settings::load_log_level_from_db(pool)
+                .await
+                .unwrap_or_else(|_| logging::DEFAULT_LOG_LEVEL.to_string()),
+        );
+    }
+
+    // 发送数据变化事件（事件名和内容见 events.rs）
+    pub async fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        events::emit(self.app_handle.lock().await.as_ref(), event, payload);
     }
 }
@@ -0,0 +1,184 @@
+use crate::commands;
+use crate::db;
+use crate::locale;
+use crate::state::{AppState, RecordingState};
+use std::time::Duration as StdDuration;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+
+// 托盘中今天截图数量的刷新间隔（秒），录制状态变化时立即刷新
+const TRAY_REFRESH_SECONDS: u64 = 5;
+
+// 需要随状态更新文字的菜单项
+struct TrayMenu {
+    status: MenuItem<Wry>,
+    count: MenuItem<Wry>,
+    toggle: MenuItem<Wry>,
+    pause: MenuItem<Wry>,
+    incognito: MenuItem<Wry>,
+    open: MenuItem<Wry>,
+    quit: MenuItem<Wry>,
+}
+
+// 按界面语言选择托盘文字，中文以外的语言使用英文
+fn label(language: &str, en: &str, zh: &str) -> String {
+    if !locale::is_chinese(language) {
+        en.to_string()
+    } else {
+        zh.to_string()
+    }
+}
+
+// 创建托盘图标和菜单，需要在 AppState 注册之后调用
+pub fn setup_tray(app: &AppHandle) -> tauri::Result<()> {
+    let menu = TrayMenu {
+        status: MenuItem::with_id(app, "status", "", false, None::<&str>)?,
+        count: MenuItem::with_id(app, "count", "", false, None::<&str>)?,
+        toggle: MenuItem::with_id(app, "toggle", "", true, None::<&str>)?,
+        pause: MenuItem::with_id(app, "pause", "", false, None::<&str>)?,
+        incognito: MenuItem::with_id(app, "incognito", "", true, None::<&str>)?,
+        open: MenuItem::with_id(app, "open", "", true, None::<&str>)?,
+        quit: MenuItem::with_id(app, "quit", "", true, None::<&str>)?,
+    };
+    let items = Menu::with_items(
+        app,
+        &[
+            &menu.status,
+            &menu.count,
+            &PredefinedMenuItem::separator(app)?,
+            &menu.toggle,
+            &menu.pause,
+            &menu.incognito,
+            &PredefinedMenuItem::separator(app)?,
+            &menu.open,
+            &menu.quit,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&items)
+        .tooltip("Clarity")
+        .show_menu_on_left_click(true)
+        .on_menu_event(|app, event| handle_menu_event(app, event.id.as_ref()));
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    tauri::async_runtime::spawn(tray_update_loop(app.clone(), menu));
+    Ok(())
+}
+
+fn handle_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        "open" => show_main_window(app),
+        // 退出时会先停止录制并写完数据
+        "quit" => app.exit(0),
+        "incognito" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let state = app.state::<AppState>();
+                if let Err(e) = commands::toggle_incognito(&state).await {
+                    log::error!("Tray action incognito failed: {}", e);
+                }
+            });
+        }
+        "toggle" | "pause" => {
+            let app = app.clone();
+            let id = id.to_string();
+            tauri::async_runtime::spawn(async move {
+                let state = app.state::<AppState>();
+                let current = state.recording.state();
+                let result = match (id.as_str(), current) {
+                    ("toggle", RecordingState::Stopped) => commands::start_recording(state).await,
+                    ("toggle", _) => commands::stop_recording(state).await,
+                    (_, RecordingState::Paused) => commands::resume_recording(state).await,
+                    _ => commands::pause_recording(state).await,
+                };
+                if let Err(e) = result {
+                    log::error!("Tray action {} failed: {}", id, e);
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+// 显示并聚焦主窗口（托盘菜单、打开链接、启动第二个实例时）
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+// 录制状态变化时立即更新托盘，同时定时刷新今天的截图数量
+async fn tray_update_loop(app: AppHandle, menu: TrayMenu) {
+    let state = app.state::<AppState>();
+    let mut recording = state.recording.subscribe();
+    let mut interval = tokio::time::interval(StdDuration::from_secs(TRAY_REFRESH_SECONDS));
+
+    loop {
+        if let Err(e) = refresh_tray(&app, &state, &menu).await {
+            log::error!("Failed to update tray: {}", e);
+        }
+
+        tokio::select! {
+            _ = interval.tick() => {}
+            changed = recording.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn refresh_tray(app: &AppHandle, state: &AppState, menu: &TrayMenu) -> tauri::Result<()> {
+    let language = state.language.lock().await.clone();
+    let recording = state.recording.state();
+
+    let status = match recording {
+        RecordingState::Recording => label(&language, "● Recording", "● 录制中"),
+        RecordingState::Paused => label(&language, "❚❚ Paused", "❚❚ 已暂停"),
+        RecordingState::Stopped => label(&language, "Not recording", "未在录制"),
+    };
+    menu.status.set_text(&status)?;
+
+    // 退出收尾时数据库已关闭，保留上一次的数量
+    if let Ok(count) = db::get_today_screenshot_count(&state.db_pool).await {
+        menu.count.set_text(if !locale::is_chinese(&language) {
+            format!("Today: {} screenshots", count)
+        } else {
+            format!("今天：{} 张截图", count)
+        })?;
+    }
+
+    menu.toggle.set_text(match recording {
+        RecordingState::Stopped => label(&language, "Start recording", "开始录制"),
+        _ => label(&language, "Stop recording", "停止录制"),
+    })?;
+    menu.pause.set_text(match recording {
+        RecordingState::Paused => label(&language, "Resume recording", "继续录制"),
+        _ => label(&language, "Pause recording", "暂停录制"),
+    })?;
+    menu.pause
+        .set_enabled(recording != RecordingState::Stopped)?;
+    menu.incognito.set_text(if *state.incognito.lock().await {
+        label(&language, "Turn off incognito mode", "关闭隐身模式")
+    } else {
+        label(&language, "Turn on incognito mode", "开启隐身模式")
+    })?;
+    menu.open
+        .set_text(label(&language, "Open Clarity", "打开 Clarity"))?;
+    menu.quit.set_text(label(&language, "Quit", "退出"))?;
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_tooltip(Some(format!("Clarity - {}", status)))?;
+    }
+    Ok(())
+}
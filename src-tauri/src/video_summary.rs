@@ -1,34 +1,65 @@
+use crate::error::ClarityError;
+use crate::proxy;
 use log;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::process::Stdio;
 use std::time::Duration;
+use tauri::{AppHandle, Emitter};
 use tokio::fs::File;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::process::Command;
+use tokio_util::io::ReaderStream;
 
 // Google Gemini API 响应结构
 #[derive(Debug, Deserialize)]
 struct GeminiGenerateContentResponse {
+    #[serde(default)]
     candidates: Vec<GeminiCandidate>,
     #[serde(rename = "usageMetadata")]
     usage_metadata: Option<GeminiUsageMetadata>,
+    #[serde(rename = "promptFeedback")]
+    prompt_feedback: Option<GeminiPromptFeedback>,
 }
 
+// 被安全设置拦截时 candidate 没有 content，只有 finishReason
 #[derive(Debug, Deserialize)]
 struct GeminiCandidate {
+    #[serde(default)]
     content: GeminiContent,
+    #[serde(rename = "finishReason")]
+    finish_reason: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct GeminiContent {
+    #[serde(default)]
     parts: Vec<GeminiPart>,
 }
 
+// 整个提示被拦截时的原因
+#[derive(Debug, Deserialize)]
+struct GeminiPromptFeedback {
+    #[serde(rename = "blockReason")]
+    block_reason: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct GeminiPart {
     text: Option<String>,
 }
 
+// streamGenerateContent 每个 SSE 事件的结构（部分事件没有 candidates）
+#[derive(Debug, Deserialize)]
+struct GeminiStreamChunk {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    #[serde(rename = "usageMetadata")]
+    usage_metadata: Option<GeminiUsageMetadata>,
+    #[serde(rename = "promptFeedback")]
+    prompt_feedback: Option<GeminiPromptFeedback>,
+}
+
 #[derive(Debug, Deserialize)]
 struct GeminiUsageMetadata {
     #[serde(rename = "promptTokenCount")]
@@ -92,21 +123,30 @@ pub struct ApiRequestResult {
     pub prompt_tokens: Option<i64>,
     pub completion_tokens: Option<i64>,
     pub total_tokens: Option<i64>,
+    pub cost_usd: Option<f64>, // 服务商直接返回的费用（如 OpenRouter）
+    pub structured_data: Option<String>, // 结构化输出的完整 JSON（目前仅 Gemini）
     pub status_code: u16,
     pub duration_ms: u64,
 }
 
-// 从图片列表创建视频（使用 ffmpeg）
-pub async fn create_video_from_images(
-    image_paths: &[PathBuf],
-    output_path: &PathBuf,
-    fps: u32,
-) -> Result<(), String> {
-    if image_paths.is_empty() {
-        return Err("No images to create video from".to_string());
+// 检查 ffmpeg 是否可以执行
+async fn ffmpeg_available(path: &str) -> bool {
+    Command::new(path).arg("-version").output().await.is_ok()
+}
+
+// 查找可用的 ffmpeg 路径，优先使用用户配置的路径
+pub async fn find_ffmpeg(custom_path: Option<&str>) -> Result<String, String> {
+    if let Some(path) = custom_path.filter(|p| !p.is_empty()) {
+        if ffmpeg_available(path).await {
+            log::info!("Using configured ffmpeg at: {}", path);
+            return Ok(path.to_string());
+        }
+        log::warn!(
+            "Configured ffmpeg path is not usable: {}, falling back to defaults",
+            path
+        );
     }
 
-    // 检查 ffmpeg 是否可用
     // 在 macOS 上，尝试多个可能的路径
     let ffmpeg_paths = if cfg!(target_os = "macos") {
         vec![
@@ -118,109 +158,505 @@ pub async fn create_video_from_images(
         vec!["ffmpeg"]
     };
 
-    let mut ffmpeg_found = false;
-    let mut ffmpeg_path = String::from("ffmpeg");
-
     for path in &ffmpeg_paths {
-        let check = Command::new(path).arg("-version").output().await;
-
-        if check.is_ok() {
-            ffmpeg_found = true;
-            ffmpeg_path = path.to_string();
+        if ffmpeg_available(path).await {
             log::info!("Found ffmpeg at: {}", path);
-            break;
+            return Ok(path.to_string());
         }
     }
 
-    if !ffmpeg_found {
-        let error_msg = format!(
-            "ffmpeg not found. Please install ffmpeg to create videos. Tried paths: {:?}",
-            ffmpeg_paths
-        );
-        log::error!("{}", error_msg);
-        return Err(error_msg);
+    let error_msg = format!(
+        "ffmpeg not found. Please install ffmpeg to create videos. Tried paths: {:?}",
+        ffmpeg_paths
+    );
+    log::error!("{}", error_msg);
+    Err(error_msg)
+}
+
+// 总结视频分辨率预设：名称、宽、高
+// tiny (640x360) 对于屏幕活动分析已经足够，更高的预设便于识别小字
+pub const VIDEO_PRESETS: [(&str, u32, u32); 3] =
+    [("tiny", 640, 360), ("540p", 960, 540), ("720p", 1280, 720)];
+
+// 总结视频每秒包含的截图数范围
+pub const MIN_VIDEO_FPS: u32 = 1;
+pub const MAX_VIDEO_FPS: u32 = 4;
+
+// 获取预设对应的视频尺寸
+pub fn preset_dimensions(preset: &str) -> Option<(u32, u32)> {
+    VIDEO_PRESETS
+        .iter()
+        .find(|(name, _, _)| *name == preset)
+        .map(|(_, width, height)| (*width, *height))
+}
+
+// 估算一次总结消耗的视频 token
+// Gemini 按每秒 1 帧采样视频：低分辨率约 66 tokens/帧，默认分辨率约 258 tokens/帧
+// 每秒放入 fps 张截图会把视频时长缩短为 1/fps，采样帧数和 token 同比减少
+pub fn estimate_video_tokens(interval_seconds: u64, fps: u32, media_resolution: &str) -> u64 {
+    let tokens_per_frame = if media_resolution == "default" {
+        258
+    } else {
+        66
+    };
+    let fps = fps.max(1) as u64;
+    let sampled_frames = interval_seconds.div_ceil(fps);
+    sampled_frames * tokens_per_frame
+}
+
+// 截图模式下每次最多内联发送的截图数量和缩放宽度
+pub const MAX_GEMINI_FRAMES: usize = 32;
+const GEMINI_FRAME_WIDTH: u32 = 768;
+
+// 总结视频可选的编码格式
+// vp9/av1 输出 webm，同等画质下体积明显小于 h264，可缩短 File API 上传时间
+pub const VIDEO_CODECS: [&str; 3] = ["h264", "vp9", "av1"];
+
+// 编码格式对应的视频文件扩展名
+pub fn codec_extension(codec: &str) -> &'static str {
+    match codec {
+        "vp9" | "av1" => "webm",
+        _ => "mp4",
     }
+}
 
-    // 创建临时文件列表
-    let temp_list_path = output_path
-        .parent()
-        .ok_or("Invalid output path")?
-        .join("ffmpeg_list.txt");
+// 编码格式对应的 ffmpeg 编码参数
+fn codec_args(codec: &str) -> Vec<String> {
+    let args: &[&str] = match codec {
+        "vp9" => &[
+            "-c:v",
+            "libvpx-vp9",
+            "-deadline",
+            "realtime",
+            "-cpu-used",
+            "8",
+            "-row-mt",
+            "1",
+            "-crf",
+            "36",
+            "-b:v",
+            "0",
+        ],
+        "av1" => &["-c:v", "libsvtav1", "-preset", "10", "-crf", "38"],
+        _ => &["-c:v", "libx264", "-preset", "fast", "-crf", "23"],
+    };
+    args.iter().map(|arg| arg.to_string()).collect()
+}
 
-    // 写入文件列表（每张图片显示 1/fps 秒）
-    let mut list_content = String::new();
-    for path in image_paths {
-        list_content.push_str(&format!("file '{}'\n", path.display()));
-        list_content.push_str(&format!("duration {}\n", 1.0 / fps as f64));
+// 根据视频文件扩展名推断上传时使用的 MIME 类型
+fn video_mime_type(path: &PathBuf) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("webm") => "video/webm",
+        _ => "video/mp4",
+    }
+}
+
+// 总结视频的编码和分析设置
+#[derive(Debug, Clone)]
+pub struct VideoSettings {
+    pub preset: String,              // "tiny", "540p" or "720p"
+    pub fps: u32,                    // 每秒视频包含的截图数
+    pub codec: String,               // "h264", "vp9" or "av1"
+    pub media_resolution: String,    // "low" or "default"
+    pub ffmpeg_path: Option<String>, // 用户配置的 ffmpeg 路径
+    pub input_mode: String,          // "video" 编码视频上传，"frames" 直接发送抽样截图
+    pub generation: GeminiGenerationSettings,
+    pub categories: Vec<String>, // 活动分类（结构化输出只能从中选择）
+}
+
+// 默认的活动分类，用户可以在设置中修改
+pub const DEFAULT_ACTIVITY_CATEGORIES: [&str; 9] = [
+    "coding",
+    "meetings",
+    "email",
+    "writing",
+    "browsing",
+    "learning",
+    "social",
+    "entertainment",
+    "other",
+];
+
+// Gemini 安全设置可选的拦截级别（"default" 表示不发送 safetySettings，使用模型默认值）
+pub const GEMINI_SAFETY_THRESHOLDS: [&str; 6] = [
+    "default",
+    "OFF",
+    "BLOCK_NONE",
+    "BLOCK_ONLY_HIGH",
+    "BLOCK_MEDIUM_AND_ABOVE",
+    "BLOCK_LOW_AND_ABOVE",
+];
+
+// 安全设置应用到的内容类别
+const GEMINI_HARM_CATEGORIES: [&str; 4] = [
+    "HARM_CATEGORY_HARASSMENT",
+    "HARM_CATEGORY_HATE_SPEECH",
+    "HARM_CATEGORY_SEXUALLY_EXPLICIT",
+    "HARM_CATEGORY_DANGEROUS_CONTENT",
+];
+
+// Gemini 生成参数和安全设置（None 表示使用模型默认值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiGenerationSettings {
+    pub safety_threshold: String, // GEMINI_SAFETY_THRESHOLDS 之一，应用到所有类别
+    pub temperature: Option<f32>,
+    pub max_output_tokens: Option<u32>,
+    pub thinking_budget: Option<i32>, // -1 由模型决定，0 关闭思考
+}
+
+impl Default for GeminiGenerationSettings {
+    fn default() -> Self {
+        Self {
+            safety_threshold: "default".to_string(),
+            temperature: None,
+            max_output_tokens: None,
+            thinking_budget: None,
+        }
+    }
+}
+
+// 把生成参数和安全设置写入请求体（请求体需要已经包含 generationConfig 对象）
+fn apply_generation_settings(
+    request_body: &mut serde_json::Value,
+    settings: &GeminiGenerationSettings,
+) {
+    let config = &mut request_body["generationConfig"];
+    if let Some(temperature) = settings.temperature {
+        config["temperature"] = serde_json::json!(temperature);
+    }
+    if let Some(max_output_tokens) = settings.max_output_tokens {
+        config["maxOutputTokens"] = serde_json::json!(max_output_tokens);
+    }
+    if let Some(thinking_budget) = settings.thinking_budget {
+        config["thinkingConfig"] = serde_json::json!({ "thinkingBudget": thinking_budget });
+    }
+
+    if settings.safety_threshold != "default" {
+        request_body["safetySettings"] = GEMINI_HARM_CATEGORIES
+            .iter()
+            .map(|category| {
+                serde_json::json!({
+                    "category": category,
+                    "threshold": settings.safety_threshold
+                })
+            })
+            .collect();
+    }
+}
+
+// 没有生成任何文字时的错误信息（被安全设置拦截时说明原因）
+fn empty_response_error(block_reason: Option<&str>) -> ClarityError {
+    let message = match block_reason {
+        Some(reason @ ("SAFETY" | "PROHIBITED_CONTENT" | "BLOCKLIST")) => format!(
+            "Gemini blocked the response (reason: {}). Try lowering the safety threshold in settings",
+            reason
+        ),
+        Some(reason) if reason != "STOP" => {
+            format!("Gemini returned no content (reason: {})", reason)
+        }
+        _ => "No response from Gemini API".to_string(),
+    };
+    ClarityError::Ai(message)
+}
+
+// 总结流程所处的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryStage {
+    Collecting,
+    Encoding,
+    Uploading,
+    Processing,
+    Generating,
+    Saving,
+    Completed,
+    Failed,
+}
+
+// summary-progress 事件的内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryProgress {
+    pub stage: SummaryStage,
+    pub percent: u8,
+    pub message: Option<String>,
+}
+
+// summary-partial 事件的内容（流式生成时不断更新）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryPartial {
+    pub text: String, // 目前已生成的摘要文字
+}
+
+// 发送总结进度事件，供前端展示当前进行到哪一步
+pub fn emit_summary_progress(
+    app_handle: Option<&AppHandle>,
+    stage: SummaryStage,
+    percent: u8,
+    message: Option<String>,
+) {
+    if let Some(handle) = app_handle {
+        let _ = handle.emit(
+            "summary-progress",
+            SummaryProgress {
+                stage,
+                percent,
+                message,
+            },
+        );
     }
-    // 最后一张图片需要重复一次（ffmpeg 要求）
-    if let Some(last) = image_paths.last() {
-        list_content.push_str(&format!("file '{}'\n", last.display()));
+}
+
+// 从截图序列中均匀抽取最多 max_frames 张（保留首尾），用于直接发送图片的模型
+pub fn sample_frames(image_paths: &[PathBuf], max_frames: usize) -> Vec<PathBuf> {
+    if max_frames == 0 || image_paths.is_empty() {
+        return Vec::new();
     }
+    if image_paths.len() <= max_frames {
+        return image_paths.to_vec();
+    }
+    if max_frames == 1 {
+        return vec![image_paths[0].clone()];
+    }
+
+    let step = (image_paths.len() - 1) as f64 / (max_frames - 1) as f64;
+    (0..max_frames)
+        .map(|i| image_paths[(i as f64 * step).round() as usize].clone())
+        .collect()
+}
 
-    tokio::fs::write(&temp_list_path, list_content)
+// 读取截图并缩小为指定宽度的 JPEG，返回 base64 编码
+pub async fn encode_frame_base64(path: &PathBuf, max_width: u32) -> Result<String, String> {
+    use base64::{engine::general_purpose, Engine as _};
+
+    let data = tokio::fs::read(path)
         .await
-        .map_err(|e| format!("Failed to write file list: {}", e))?;
+        .map_err(|e| format!("Failed to read frame {}: {}", path.display(), e))?;
 
-    // 使用 ffmpeg 创建视频
-    log::info!(
-        "Running ffmpeg to create video from {} images",
-        image_paths.len()
-    );
-    let output = Command::new(&ffmpeg_path)
+    let jpeg = tokio::task::spawn_blocking(move || {
+        let img = image::load_from_memory(&data)
+            .map_err(|e| format!("Failed to decode frame: {}", e))?
+            .to_rgb8();
+
+        let (width, height) = img.dimensions();
+        let img = if width > max_width {
+            let new_height = ((height as u64 * max_width as u64) / width.max(1) as u64).max(1);
+            image::imageops::thumbnail(&img, max_width, new_height as u32)
+        } else {
+            img
+        };
+
+        let mut output = Vec::new();
+        {
+            let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut output, 75);
+            encoder
+                .encode(
+                    &img,
+                    img.width(),
+                    img.height(),
+                    image::ExtendedColorType::Rgb8,
+                )
+                .map_err(|e| format!("Failed to encode frame: {}", e))?;
+        }
+        Ok::<Vec<u8>, String>(output)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    Ok(general_purpose::STANDARD.encode(&jpeg))
+}
+
+// 从图片列表创建视频（使用 ffmpeg）
+pub async fn create_video_from_images(
+    image_paths: &[PathBuf],
+    output_path: &PathBuf,
+    settings: &VideoSettings,
+) -> Result<(), String> {
+    let (width, height) = preset_dimensions(&settings.preset).unwrap_or((640, 360));
+    let fps = settings.fps.clamp(MIN_VIDEO_FPS, MAX_VIDEO_FPS);
+    let ffmpeg_path = find_ffmpeg(settings.ffmpeg_path.as_deref()).await?;
+    encode_video(
+        &ffmpeg_path,
+        image_paths,
+        output_path,
+        fps,
+        width,
+        height,
+        &settings.codec,
+    )
+    .await
+}
+
+// 把截图通过 stdin 逐帧写入 ffmpeg（image2pipe），不再依赖临时文件列表
+// output_args 为输出相关的参数（滤镜、编码器等），输出路径由本函数追加
+async fn pipe_frames_to_ffmpeg(
+    ffmpeg_path: &str,
+    image_paths: &[PathBuf],
+    fps: u32,
+    output_args: &[String],
+    output_path: &PathBuf,
+) -> Result<(), String> {
+    let mut child = Command::new(ffmpeg_path)
+        .arg("-loglevel")
+        .arg("error")
         .arg("-f")
-        .arg("concat")
-        .arg("-safe")
-        .arg("0")
-        .arg("-i")
-        .arg(&temp_list_path)
-        // 降低分辨率以减少 token 消耗：640x360 对于屏幕活动分析已经足够
-        // 如果需要更高质量，可以改为 960x540
-        .arg("-vf")
-        .arg("scale=640:360:force_original_aspect_ratio=decrease,pad=640:360:(ow-iw)/2:(oh-ih)/2")
+        .arg("image2pipe")
+        .arg("-framerate")
+        .arg(fps.to_string())
         .arg("-c:v")
-        .arg("libx264")
-        .arg("-preset")
-        .arg("fast")
-        .arg("-crf")
-        .arg("23")
-        .arg("-pix_fmt")
-        .arg("yuv420p")
-        .arg("-r")
-        .arg(&fps.to_string())
+        .arg("mjpeg")
+        .arg("-i")
+        .arg("-")
+        .args(output_args)
         .arg("-y")
         .arg(output_path)
-        .output()
-        .await
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
         .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
 
-    // 清理临时文件
-    let _ = tokio::fs::remove_file(&temp_list_path).await;
+    // 并发读取 stderr，避免缓冲区写满导致 ffmpeg 阻塞
+    let mut stderr = child
+        .stderr
+        .take()
+        .ok_or("Failed to capture ffmpeg stderr")?;
+    let stderr_task = tokio::spawn(async move {
+        let mut buffer = Vec::new();
+        let _ = stderr.read_to_end(&mut buffer).await;
+        buffer
+    });
+
+    let mut stdin = child.stdin.take().ok_or("Failed to open ffmpeg stdin")?;
+    let mut written = 0usize;
+    for path in image_paths {
+        let frame = match tokio::fs::read(path).await {
+            Ok(data) => data,
+            Err(e) => {
+                // 截图文件丢失时跳过该帧，不影响整段视频
+                log::warn!("Skipping missing frame {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        if let Err(e) = stdin.write_all(&frame).await {
+            // ffmpeg 提前退出，具体原因见 stderr
+            log::error!("Failed to write frame to ffmpeg: {}", e);
+            break;
+        }
+        written += 1;
+    }
+    // 关闭 stdin，通知 ffmpeg 输入结束
+    drop(stdin);
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
+    let stderr = stderr_task.await.unwrap_or_default();
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("ffmpeg failed: {}", stderr));
+    if written == 0 {
+        return Err("No readable images to create video from".to_string());
+    }
+
+    if !status.success() {
+        return Err(format!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&stderr)
+        ));
     }
 
     Ok(())
 }
 
+// 把图片序列编码为指定分辨率和编码格式的视频（保持宽高比，不足部分补黑边）
+pub async fn encode_video(
+    ffmpeg_path: &str,
+    image_paths: &[PathBuf],
+    output_path: &PathBuf,
+    fps: u32,
+    width: u32,
+    height: u32,
+    codec: &str,
+) -> Result<(), String> {
+    if image_paths.is_empty() {
+        return Err("No images to create video from".to_string());
+    }
+
+    // 使用 ffmpeg 创建视频
+    log::info!(
+        "Running ffmpeg to create {} video from {} images",
+        codec,
+        image_paths.len()
+    );
+    let mut output_args = vec![
+        "-vf".to_string(),
+        format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2",
+            w = width,
+            h = height
+        ),
+    ];
+    output_args.extend(codec_args(codec));
+    output_args.extend([
+        "-pix_fmt".to_string(),
+        "yuv420p".to_string(),
+        "-r".to_string(),
+        fps.to_string(),
+    ]);
+
+    pipe_frames_to_ffmpeg(ffmpeg_path, image_paths, fps, &output_args, output_path).await
+}
+
+// 把图片序列编码为 GIF（使用调色板以保证画质，高度按比例缩放）
+pub async fn encode_gif(
+    ffmpeg_path: &str,
+    image_paths: &[PathBuf],
+    output_path: &PathBuf,
+    fps: u32,
+    width: u32,
+) -> Result<(), String> {
+    if image_paths.is_empty() {
+        return Err("No images to create GIF from".to_string());
+    }
+
+    log::info!(
+        "Running ffmpeg to create GIF from {} images",
+        image_paths.len()
+    );
+    let output_args = vec![
+        "-vf".to_string(),
+        format!(
+            "fps={},scale={}:-1:flags=lanczos,split[s0][s1];[s0]palettegen[p];[s1][p]paletteuse",
+            fps, width
+        ),
+        "-loop".to_string(),
+        "0".to_string(),
+    ];
+
+    pipe_frames_to_ffmpeg(ffmpeg_path, image_paths, fps, &output_args, output_path).await
+}
+
 // 上传文件到 Google Gemini File API
 pub async fn upload_file_to_gemini(
     api_key: &str,
     file_path: &PathBuf,
-) -> Result<GeminiFile, String> {
-    let client = reqwest::Client::new();
+) -> Result<GeminiFile, ClarityError> {
+    let client = proxy::client();
 
-    // 读取文件
-    let mut file = File::open(file_path)
+    // 打开文件，上传时按块读取，避免把整个视频读入内存
+    let file = File::open(file_path)
         .await
         .map_err(|e| format!("Failed to open file: {}", e))?;
 
-    let mut file_data = Vec::new();
-    file.read_to_end(&mut file_data)
+    let file_size = file
+        .metadata()
         .await
-        .map_err(|e| format!("Failed to read file: {}", e))?;
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?
+        .len();
 
     // 获取文件名和 MIME 类型
     let file_name = file_path
@@ -228,19 +664,26 @@ pub async fn upload_file_to_gemini(
         .and_then(|n| n.to_str())
         .unwrap_or("video.mp4");
 
-    let mime_type = "video/mp4"; // 默认使用 video/mp4
+    let mime_type = video_mime_type(file_path);
 
     // 创建 multipart form
     // Google Gemini API 期望文件数据在 "file" 字段中
     let form = reqwest::multipart::Form::new().part(
         "file",
-        reqwest::multipart::Part::bytes(file_data)
-            .file_name(file_name.to_string())
-            .mime_str(mime_type)
-            .map_err(|e| format!("Failed to set mime type: {}", e))?,
+        reqwest::multipart::Part::stream_with_length(
+            reqwest::Body::wrap_stream(ReaderStream::new(file)),
+            file_size,
+        )
+        .file_name(file_name.to_string())
+        .mime_str(mime_type)
+        .map_err(|e| format!("Failed to set mime type: {}", e))?,
     );
 
-    log::info!("Uploading file to Google Gemini File API: {}", file_name);
+    log::info!(
+        "Uploading file to Google Gemini File API: {} ({} bytes)",
+        file_name,
+        file_size
+    );
 
     // 上传文件
     let response = client
@@ -249,21 +692,21 @@ pub async fn upload_file_to_gemini(
         .multipart(form)
         .send()
         .await
-        .map_err(|e| format!("Failed to upload file: {}", e))?;
+        .map_err(|e| ClarityError::Network(format!("Failed to upload file: {}", e)))?;
 
     let status = response.status();
     if !status.is_success() {
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Gemini File API error: {} - {}",
-            status, error_text
+        return Err(ClarityError::from_api_status(
+            status,
+            format!("Gemini File API error: {} - {}", status, error_text),
         ));
     }
 
     let upload_response: GeminiFileUploadResponse = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse upload response: {}", e))?;
+        .map_err(|e| ClarityError::Ai(format!("Failed to parse upload response: {}", e)))?;
 
     log::info!("File uploaded successfully: {}", upload_response.file.name);
     log::info!(
@@ -281,8 +724,8 @@ pub async fn wait_until_active(
     file_name: &str,
     interval_ms: u64,
     timeout_ms: u64,
-) -> Result<GeminiFile, String> {
-    let client = reqwest::Client::new();
+) -> Result<GeminiFile, ClarityError> {
+    let client = proxy::client();
     let start_time = std::time::Instant::now();
 
     log::info!("Waiting for file to become ACTIVE: {}", file_name);
@@ -306,15 +749,15 @@ pub async fn wait_until_active(
             .query(&[("key", api_key)])
             .send()
             .await
-            .map_err(|e| format!("Failed to get file status: {}", e))?;
+            .map_err(|e| ClarityError::Network(format!("Failed to get file status: {}", e)))?;
 
         let status = response.status();
         if !status.is_success() {
             let error_text = response.text().await.unwrap_or_default();
             log::error!("Failed to get file status: {} - {}", status, error_text);
-            return Err(format!(
-                "Gemini File API error: {} - {}",
-                status, error_text
+            return Err(ClarityError::from_api_status(
+                status,
+                format!("Gemini File API error: {} - {}", status, error_text),
             ));
         }
 
@@ -322,13 +765,13 @@ pub async fn wait_until_active(
         let response_text = response
             .text()
             .await
-            .map_err(|e| format!("Failed to read response body: {}", e))?;
+            .map_err(|e| ClarityError::Network(format!("Failed to read response body: {}", e)))?;
 
         if response_text.is_empty() {
-            return Err(format!(
+            return Err(ClarityError::Ai(format!(
                 "Empty response body from Gemini File API for file: {}",
                 file_id
-            ));
+            )));
         }
 
         log::info!(
@@ -349,7 +792,7 @@ pub async fn wait_until_active(
                 let file_response: GeminiFileGetResponse = serde_json::from_str(&response_text)
                     .map_err(|e2| {
                         log::error!("Failed to parse as wrapped response: {}", e2);
-                        format!("Failed to parse file response. Direct parse error: {}. Wrapped parse error: {}. Response body: {}", e1, e2, response_text)
+                        ClarityError::Ai(format!("Failed to parse file response. Direct parse error: {}. Wrapped parse error: {}. Response body: {}", e1, e2, response_text))
                     })?;
                 file_response.file
             }
@@ -364,7 +807,10 @@ pub async fn wait_until_active(
                 return Ok(file);
             }
             "FAILED" => {
-                return Err(format!("File processing failed: {}", file.name));
+                return Err(ClarityError::Ai(format!(
+                    "File processing failed: {}",
+                    file.name
+                )));
             }
             "PROCESSING" | "STATE_UNSPECIFIED" | "" => {
                 // 文件正在处理中，继续等待
@@ -377,10 +823,10 @@ pub async fn wait_until_active(
 
         // 检查超时
         if elapsed > timeout_ms as u128 {
-            return Err(format!(
+            return Err(ClarityError::Ai(format!(
                 "Wait for file ACTIVE timeout after {}ms",
                 timeout_ms
-            ));
+            )));
         }
 
         // 等待一段时间后重试
@@ -388,6 +834,49 @@ pub async fn wait_until_active(
     }
 }
 
+// 删除已上传到 Gemini File API 的文件（否则会一直保留到过期）
+pub async fn delete_gemini_file(api_key: &str, file_name: &str) -> Result<(), ClarityError> {
+    let client = proxy::client();
+
+    // file_name 格式可能是 "files/xxx" 或只是 "xxx"，需要统一处理
+    let file_id = if file_name.starts_with("files/") {
+        file_name.to_string()
+    } else {
+        format!("files/{}", file_name)
+    };
+
+    let response = client
+        .delete(format!(
+            "https://generativelanguage.googleapis.com/v1beta/{}",
+            file_id
+        ))
+        .query(&[("key", api_key)])
+        .send()
+        .await
+        .map_err(|e| ClarityError::Network(format!("Failed to delete file: {}", e)))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(ClarityError::from_api_status(
+            status,
+            format!("Gemini File API error: {} - {}", status, error_text),
+        ));
+    }
+
+    log::info!("Deleted uploaded file: {}", file_id);
+    Ok(())
+}
+
+// Gemini 的 mediaResolution 级别
+fn media_resolution_level(resolution: &str) -> &'static str {
+    if resolution == "default" {
+        "MEDIA_RESOLUTION_DEFAULT"
+    } else {
+        "MEDIA_RESOLUTION_LOW"
+    }
+}
+
 // 使用文件 URI 生成内容
 pub async fn generate_content_with_file_uri(
     api_key: &str,
@@ -396,10 +885,10 @@ pub async fn generate_content_with_file_uri(
     mime_type: &str,
     prompt: &str,
     resolution: &str, // "low" or "default"
-) -> Result<ApiRequestResult, String> {
-    let client = reqwest::Client::new();
-    let start_time = std::time::Instant::now();
-
+    generation: &GeminiGenerationSettings,
+    categories: &[String],
+    app_handle: Option<&AppHandle>,
+) -> Result<ApiRequestResult, ClarityError> {
     // 构建请求体
     // 根据 Google 文档：
     // - 低分辨率 (low): 约 100 tokens/秒 (66 tokens/帧 + 32 tokens/秒音频)
@@ -407,88 +896,318 @@ pub async fn generate_content_with_file_uri(
     // 使用 low 分辨率可以减少约 66% 的 token 消耗
     // 使用 default 分辨率可以提高文字识别精度（如价格、数字等）
     // mediaResolution 应该在 part 对象中，与 fileData 同级
-    let media_resolution_level = if resolution == "default" {
-        "MEDIA_RESOLUTION_DEFAULT"
-    } else {
-        "MEDIA_RESOLUTION_LOW"
+    let parts = vec![
+        serde_json::json!({
+            "fileData": {
+                "fileUri": file_uri,
+                "mimeType": mime_type
+            },
+            "mediaResolution": {
+                "level": media_resolution_level(resolution)
+            }
+        }),
+        serde_json::json!({
+            "text": prompt
+        }),
+    ];
+
+    log::info!("Calling Google Gemini API with file URI: {}", file_uri);
+
+    generate_content_with_parts(api_key, model, parts, generation, categories, app_handle).await
+}
+
+// 直接内联发送抽样截图生成内容（不需要 ffmpeg 和 File API）
+pub async fn summarize_frames_with_gemini(
+    api_key: &str,
+    model: &str,
+    prompt: &str,
+    image_paths: &[PathBuf],
+    resolution: &str, // "low" or "default"
+    generation: &GeminiGenerationSettings,
+    categories: &[String],
+    app_handle: Option<&AppHandle>,
+) -> Result<ApiRequestResult, ClarityError> {
+    let frames = sample_frames(image_paths, MAX_GEMINI_FRAMES);
+    log::info!(
+        "Sending {} of {} screenshots inline to Google Gemini (resolution: {})",
+        frames.len(),
+        image_paths.len(),
+        resolution
+    );
+
+    emit_summary_progress(app_handle, SummaryStage::Uploading, 30, None);
+    let mut parts = Vec::new();
+    for path in &frames {
+        match encode_frame_base64(path, GEMINI_FRAME_WIDTH).await {
+            Ok(data) => parts.push(serde_json::json!({
+                "inlineData": {
+                    "mimeType": "image/jpeg",
+                    "data": data
+                },
+                "mediaResolution": {
+                    "level": media_resolution_level(resolution)
+                }
+            })),
+            Err(e) => {
+                // 截图文件丢失或损坏时跳过该帧
+                log::warn!("Skipping frame {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    if parts.is_empty() {
+        return Err(ClarityError::Internal(
+            "No readable screenshots to send".to_string(),
+        ));
+    }
+
+    // 截图按时间顺序排列，提示模型把它们当作连续的屏幕活动
+    parts.push(serde_json::json!({
+        "text": format!(
+            "The {} images above are screenshots sampled in chronological order from one period of screen activity.\n\n{}",
+            parts.len(),
+            prompt
+        )
+    }));
+
+    emit_summary_progress(app_handle, SummaryStage::Generating, 70, None);
+    generate_content_with_parts(api_key, model, parts, generation, categories, app_handle).await
+}
+
+// 摘要结构化输出的 JSON Schema（Gemini responseSchema 格式），分类只能从给定的分类中选择
+fn summary_response_schema(categories: &[String]) -> serde_json::Value {
+    serde_json::json!({
+        "type": "OBJECT",
+        "properties": {
+            "summary": {
+                "type": "STRING",
+                "description": "Prose summary of the screen activity, following the instructions above"
+            },
+            "apps": {
+                "type": "ARRAY",
+                "items": { "type": "STRING" },
+                "description": "Names of the applications or websites used"
+            },
+            "category": {
+                "type": "STRING",
+                "enum": categories,
+                "description": "Main activity category"
+            },
+            "categories": {
+                "type": "ARRAY",
+                "items": { "type": "STRING", "enum": categories },
+                "description": "All activity categories that apply to this period, main category first"
+            },
+            "focusScore": {
+                "type": "INTEGER",
+                "minimum": 0,
+                "maximum": 100,
+                "description": "How focused the user was, from 0 (scattered) to 100 (deep focus)"
+            },
+            "distractions": {
+                "type": "ARRAY",
+                "items": { "type": "STRING" },
+                "description": "Activities unrelated to the main task"
+            }
+        },
+        "required": ["summary", "apps", "category", "categories", "focusScore", "distractions"],
+        "propertyOrdering": ["summary", "apps", "category", "categories", "focusScore", "distractions"]
+    })
+}
+
+// 从结构化输出中取出文字摘要，解析失败时把原文当作摘要
+fn parse_structured_summary(text: &str) -> (String, Option<String>) {
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(value) => match value.get("summary").and_then(|s| s.as_str()) {
+            Some(summary) => (summary.to_string(), Some(value.to_string())),
+            None => (text.to_string(), None),
+        },
+        Err(e) => {
+            log::warn!("Failed to parse structured summary: {}", e);
+            (text.to_string(), None)
+        }
+    }
+}
+
+// 从尚未生成完的结构化 JSON 中取出目前的 summary 文字（schema 中 summary 排在第一位）
+fn partial_summary_text(json_prefix: &str) -> Option<String> {
+    let key_pos = json_prefix.find("\"summary\"")?;
+    let after_key = &json_prefix[key_pos + "\"summary\"".len()..];
+    let quote_pos = after_key.find('"')?;
+    if !after_key[..quote_pos]
+        .trim()
+        .trim_start_matches(':')
+        .trim()
+        .is_empty()
+    {
+        return None;
+    }
+    let raw = &after_key[quote_pos + 1..];
+
+    // 找到未转义的结束引号，没有则取到末尾
+    let mut end = raw.len();
+    let mut escaped = false;
+    for (i, c) in raw.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => {
+                end = i;
+                break;
+            }
+            _ => escaped = false,
+        }
+    }
+    let mut raw = &raw[..end];
+
+    // 去掉末尾不完整的转义序列（如 "\" 或 "\u00"）
+    if let Some(pos) = raw.rfind('\\') {
+        let tail = &raw[pos..];
+        let backslashes = raw[..=pos].chars().rev().take_while(|c| *c == '\\').count();
+        if backslashes % 2 == 1 && (tail.len() == 1 || (tail.starts_with("\\u") && tail.len() < 6))
+        {
+            raw = &raw[..pos];
+        }
+    }
+
+    serde_json::from_str::<String>(&format!("\"{}\"", raw)).ok()
+}
+
+// 处理一行 SSE 数据，把生成的文字追加到 text，并记录最新的 token 使用情况和结束原因
+fn handle_stream_line(
+    line: &[u8],
+    text: &mut String,
+    usage: &mut Option<GeminiUsageMetadata>,
+    finish_reason: &mut Option<String>,
+) {
+    let line = String::from_utf8_lossy(line);
+    let Some(data) = line.trim().strip_prefix("data:") else {
+        return;
     };
 
-    let request_body = serde_json::json!({
-        "contents": [{
-            "parts": [
-                {
-                    "fileData": {
-                        "fileUri": file_uri,
-                        "mimeType": mime_type
-                    },
-                    "mediaResolution": {
-                        "level": media_resolution_level
+    match serde_json::from_str::<GeminiStreamChunk>(data.trim()) {
+        Ok(chunk) => {
+            if let Some(candidate) = chunk.candidates.first() {
+                for part in &candidate.content.parts {
+                    if let Some(part_text) = &part.text {
+                        text.push_str(part_text);
                     }
-                },
-                {
-                    "text": prompt
                 }
-            ]
-        }]
+                if candidate.finish_reason.is_some() {
+                    *finish_reason = candidate.finish_reason.clone();
+                }
+            }
+            if let Some(reason) = chunk.prompt_feedback.and_then(|f| f.block_reason) {
+                *finish_reason = Some(reason);
+            }
+            if chunk.usage_metadata.is_some() {
+                *usage = chunk.usage_metadata;
+            }
+        }
+        Err(e) => log::warn!("Failed to parse stream chunk: {}", e),
+    }
+}
+
+// 调用 streamGenerateContent 接口，边生成边发送 summary-partial 事件，结束后解析结果和 token 使用情况
+async fn generate_content_with_parts(
+    api_key: &str,
+    model: &str,
+    parts: Vec<serde_json::Value>,
+    generation: &GeminiGenerationSettings,
+    categories: &[String],
+    app_handle: Option<&AppHandle>,
+) -> Result<ApiRequestResult, ClarityError> {
+    let client = proxy::client();
+    let start_time = std::time::Instant::now();
+
+    // 要求模型按 schema 返回 JSON，便于统计应用、分类和专注度
+    let mut request_body = serde_json::json!({
+        "contents": [{
+            "parts": parts
+        }],
+        "generationConfig": {
+            "responseMimeType": "application/json",
+            "responseSchema": summary_response_schema(categories)
+        }
     });
+    apply_generation_settings(&mut request_body, generation);
 
     log::debug!(
         "Request body: {}",
         serde_json::to_string_pretty(&request_body).unwrap_or_default()
     );
 
-    log::info!("Calling Google Gemini API with file URI: {}", file_uri);
-
-    let response = client
+    let mut response = client
         .post(&format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent",
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:streamGenerateContent",
             model
         ))
-        .query(&[("key", api_key)])
+        .query(&[("key", api_key), ("alt", "sse")])
         .header("Content-Type", "application/json")
         .json(&request_body)
         .send()
         .await
-        .map_err(|e| format!("Failed to send request: {}", e))?;
+        .map_err(|e| ClarityError::Network(format!("Failed to send request: {}", e)))?;
 
-    let duration_ms = start_time.elapsed().as_millis() as u64;
     let status = response.status();
 
     if !status.is_success() {
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Gemini API error: {} - {}", status, error_text));
+        return Err(ClarityError::from_api_status(
+            status,
+            format!("Gemini API error: {} - {}", status, error_text),
+        ));
     }
 
-    let api_response: GeminiGenerateContentResponse = response
-        .json()
+    // 按行读取 SSE 数据（网络分块可能在任意位置截断，包括 UTF-8 字符中间）
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut text = String::new();
+    let mut usage = None;
+    let mut finish_reason = None;
+    let mut last_partial = String::new();
+    while let Some(chunk) = response
+        .chunk()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .map_err(|e| ClarityError::Network(format!("Failed to read response stream: {}", e)))?
+    {
+        buffer.extend_from_slice(&chunk);
+        while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            handle_stream_line(&line, &mut text, &mut usage, &mut finish_reason);
+        }
 
-    if let Some(candidate) = api_response.candidates.first() {
-        if let Some(part) = candidate.content.parts.first() {
-            if let Some(text) = &part.text {
-                return Ok(ApiRequestResult {
-                    content: text.clone(),
-                    prompt_tokens: api_response
-                        .usage_metadata
-                        .as_ref()
-                        .and_then(|u| u.prompt_token_count),
-                    completion_tokens: api_response
-                        .usage_metadata
-                        .as_ref()
-                        .and_then(|u| u.candidates_token_count),
-                    total_tokens: api_response
-                        .usage_metadata
-                        .as_ref()
-                        .and_then(|u| u.total_token_count),
-                    status_code: status.as_u16(),
-                    duration_ms,
-                });
+        // 只在 summary 文字有变化时通知前端（转义序列不完整时等下一块数据）
+        if let (Some(handle), Some(partial)) = (app_handle, partial_summary_text(&text)) {
+            if partial != last_partial {
+                let _ = handle.emit(
+                    "summary-partial",
+                    SummaryPartial {
+                        text: partial.clone(),
+                    },
+                );
+                last_partial = partial;
             }
         }
     }
+    // 最后一行可能没有换行符
+    handle_stream_line(&buffer, &mut text, &mut usage, &mut finish_reason);
+
+    let duration_ms = start_time.elapsed().as_millis() as u64;
 
-    Err("No response from Gemini API".to_string())
+    if text.is_empty() {
+        return Err(empty_response_error(finish_reason.as_deref()));
+    }
+
+    let (content, structured_data) = parse_structured_summary(&text);
+    Ok(ApiRequestResult {
+        content,
+        prompt_tokens: usage.as_ref().and_then(|u| u.prompt_token_count),
+        completion_tokens: usage.as_ref().and_then(|u| u.candidates_token_count),
+        total_tokens: usage.as_ref().and_then(|u| u.total_token_count),
+        cost_usd: None,
+        structured_data,
+        status_code: status.as_u16(),
+        duration_ms,
+    })
 }
 
 // 主要的视频摘要函数：上传文件并生成摘要
@@ -497,44 +1216,68 @@ pub async fn summarize_video_with_gemini(
     video_path: &PathBuf,
     model: &str,
     prompt: &str,
-    resolution: &str, // "low" or "default"
-) -> Result<ApiRequestResult, String> {
+    settings: &VideoSettings,
+    app_handle: Option<&AppHandle>,
+) -> Result<ApiRequestResult, ClarityError> {
+    let resolution = settings.media_resolution.as_str();
     log::info!(
-        "Starting video summary with Google Gemini API (resolution: {})",
+        "Starting video summary with Google Gemini API (preset: {}, fps: {}, codec: {}, resolution: {})",
+        settings.preset,
+        settings.fps,
+        settings.codec,
         resolution
     );
 
     // 1. 上传文件
+    emit_summary_progress(app_handle, SummaryStage::Uploading, 30, None);
     let uploaded_file = upload_file_to_gemini(api_key, video_path).await?;
 
-    // 2. 等待文件处理完成
-    log::info!("Waiting for file to become ACTIVE: {}", uploaded_file.name);
-    let active_file = wait_until_active(
-        api_key,
-        &uploaded_file.name,
-        1000,    // 每 1 秒检查一次（视频文件处理可能需要更长时间）
-        120_000, // 120 秒超时（2分钟，视频文件处理可能需要更长时间）
-    )
-    .await?;
-
-    log::info!("File is ACTIVE, URI: {}", active_file.uri);
-
-    // 3. 使用文件 URI 生成内容
-    log::info!(
-        "Generating content with file URI: {} (resolution: {})",
-        active_file.uri,
-        resolution
-    );
-    let result = generate_content_with_file_uri(
-        api_key,
-        model,
-        &active_file.uri,
-        &active_file.mime_type,
-        prompt,
-        resolution,
-    )
-    .await?;
+    let result = async {
+        // 2. 等待文件处理完成
+        emit_summary_progress(app_handle, SummaryStage::Processing, 50, None);
+        log::info!("Waiting for file to become ACTIVE: {}", uploaded_file.name);
+        let active_file = wait_until_active(
+            api_key,
+            &uploaded_file.name,
+            1000,    // 每 1 秒检查一次（视频文件处理可能需要更长时间）
+            120_000, // 120 秒超时（2分钟，视频文件处理可能需要更长时间）
+        )
+        .await?;
+
+        log::info!("File is ACTIVE, URI: {}", active_file.uri);
+
+        // 3. 使用文件 URI 生成内容
+        emit_summary_progress(app_handle, SummaryStage::Generating, 70, None);
+        log::info!(
+            "Generating content with file URI: {} (resolution: {})",
+            active_file.uri,
+            resolution
+        );
+        generate_content_with_file_uri(
+            api_key,
+            model,
+            &active_file.uri,
+            &active_file.mime_type,
+            prompt,
+            resolution,
+            &settings.generation,
+            &settings.categories,
+            app_handle,
+        )
+        .await
+    }
+    .await;
+
+    // 4. 无论成功与否都删除已上传的文件（重试时会重新上传本地视频）
+    if let Err(e) = delete_gemini_file(api_key, &uploaded_file.name).await {
+        log::warn!(
+            "Failed to delete uploaded file {}: {}",
+            uploaded_file.name,
+            e
+        );
+    }
 
+    let result = result?;
     log::info!("Video summary completed successfully");
 
     Ok(result)
@@ -545,22 +1288,24 @@ pub async fn generate_text_summary_with_gemini(
     api_key: &str,
     model: &str,
     prompt: &str,
-) -> Result<String, String> {
-    use reqwest::Client;
+    generation: &GeminiGenerationSettings,
+) -> Result<String, ClarityError> {
     use std::time::Instant;
 
     let start_time = Instant::now();
-    let client = Client::new();
+    let client = proxy::client();
 
-    let request_body = serde_json::json!({
+    let mut request_body = serde_json::json!({
         "contents": [{
             "parts": [
                 {
                     "text": prompt
                 }
             ]
-        }]
+        }],
+        "generationConfig": {}
     });
+    apply_generation_settings(&mut request_body, generation);
 
     log::debug!(
         "Text summary request body: {}",
@@ -579,19 +1324,22 @@ pub async fn generate_text_summary_with_gemini(
         .json(&request_body)
         .send()
         .await
-        .map_err(|e| format!("Failed to send request: {}", e))?;
+        .map_err(|e| ClarityError::Network(format!("Failed to send request: {}", e)))?;
 
     let status = response.status();
 
     if !status.is_success() {
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Gemini API error: {} - {}", status, error_text));
+        return Err(ClarityError::from_api_status(
+            status,
+            format!("Gemini API error: {} - {}", status, error_text),
+        ));
     }
 
     let api_response: GeminiGenerateContentResponse = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .map_err(|e| ClarityError::Ai(format!("Failed to parse response: {}", e)))?;
 
     if let Some(candidate) = api_response.candidates.first() {
         if let Some(part) = candidate.content.parts.first() {
@@ -603,5 +1351,14 @@ pub async fn generate_text_summary_with_gemini(
         }
     }
 
-    Err("No response from Gemini API".to_string())
+    let block_reason = api_response
+        .prompt_feedback
+        .and_then(|f| f.block_reason)
+        .or_else(|| {
+            api_response
+                .candidates
+                .first()
+                .and_then(|c| c.finish_reason.clone())
+        });
+    Err(empty_response_error(block_reason.as_deref()))
 }
@@ -1,9 +1,11 @@
 import { useState, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/core'
+import { errorMessage } from './errors'
 
 function ScreenshotController () {
   const [isRecording, setIsRecording] = useState(false)
   const [screenshotsCount, setScreenshotsCount] = useState(0)
+  const [totalScreenshotsCount, setTotalScreenshotsCount] = useState(0)
   const [storagePath, setStoragePath] = useState('')
   const [loading, setLoading] = useState(false)
   const [error, setError] = useState(null)
@@ -30,10 +32,11 @@ function ScreenshotController () {
       const status = await invoke('get_status')
       setIsRecording(status.is_recording)
       setScreenshotsCount(status.screenshots_count)
+      setTotalScreenshotsCount(status.total_screenshots_count)
       setStoragePath(status.storage_path)
     } catch (err) {
       console.error('Failed to load status:', err)
-      setError(err.toString())
+      setError(errorMessage(err))
     }
   }
 
@@ -53,10 +56,11 @@ function ScreenshotController () {
       const status = await invoke('start_recording')
       setIsRecording(status.is_recording)
       setScreenshotsCount(status.screenshots_count)
+      setTotalScreenshotsCount(status.total_screenshots_count)
       setStoragePath(status.storage_path)
     } catch (err) {
       console.error('Failed to start recording:', err)
-      setError(err.toString())
+      setError(errorMessage(err))
     } finally {
       setLoading(false)
     }
@@ -69,9 +73,10 @@ function ScreenshotController () {
       const status = await invoke('stop_recording')
       setIsRecording(status.is_recording)
       setScreenshotsCount(status.screenshots_count)
+      setTotalScreenshotsCount(status.total_screenshots_count)
     } catch (err) {
       console.error('Failed to stop recording:', err)
-      setError(err.toString())
+      setError(errorMessage(err))
     } finally {
       setLoading(false)
     }
@@ -86,7 +91,7 @@ function ScreenshotController () {
       setTestResult(result)
     } catch (err) {
       console.error('Failed to test screenshot:', err)
-      setError(err.toString())
+      setError(errorMessage(err))
       setTestResult(null)
     } finally {
       setTesting(false)
@@ -163,6 +168,10 @@ function ScreenshotController () {
             <span className='font-medium text-gray-700'>Screenshots captured:</span>{' '}
             <span className='text-gray-900'>{screenshotsCount.toLocaleString()}</span>
           </div>
+          <div>
+            <span className='font-medium text-gray-700'>Screenshots stored:</span>{' '}
+            <span className='text-gray-900'>{totalScreenshotsCount.toLocaleString()}</span>
+          </div>
           <div>
             <span className='font-medium text-gray-700'>Storage path:</span>{' '}
             <code className='bg-gray-100 px-2 py-1 rounded text-xs text-gray-800'>
@@ -0,0 +1,54 @@
+import { useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { useTranslation } from '../i18n'
+import { errorMessage } from '../errors'
+
+// 应用锁定时代替页面显示，输入 PIN 解锁
+export default function LockScreen ({ onUnlock }) {
+  const { t } = useTranslation()
+  const [pin, setPin] = useState('')
+  const [unlocking, setUnlocking] = useState(false)
+  const [error, setError] = useState(null)
+
+  const unlock = async (e) => {
+    e.preventDefault()
+    setUnlocking(true)
+    setError(null)
+    try {
+      onUnlock(await invoke('unlock_app', { pin }))
+    } catch (err) {
+      setError(errorMessage(err))
+      setPin('')
+    } finally {
+      setUnlocking(false)
+    }
+  }
+
+  return (
+    <div className='flex-1 flex items-center justify-center bg-gray-50'>
+      <form onSubmit={unlock} className='w-72 bg-white border border-gray-200 rounded-lg p-6'>
+        <h2 className='text-lg font-semibold text-gray-900 mb-1'>{t('appLocked')}</h2>
+        <p className='text-sm text-gray-600 mb-4'>{t('appLockedDescription')}</p>
+        <input
+          type='password'
+          autoFocus
+          autoComplete='off'
+          value={pin}
+          onChange={(e) => setPin(e.target.value)}
+          placeholder={t('appLockPin')}
+          className='w-full px-4 py-2.5 border border-gray-300 rounded-lg bg-white text-gray-900 focus:ring-2 focus:ring-gray-900 focus:border-gray-900 transition-all'
+        />
+        {error && (
+          <p className='mt-2 text-sm text-red-600'>{error}</p>
+        )}
+        <button
+          type='submit'
+          disabled={unlocking || !pin}
+          className='mt-4 w-full px-4 py-2 bg-gray-900 text-white text-sm rounded-lg hover:bg-gray-800 disabled:opacity-50 disabled:cursor-not-allowed transition-colors font-medium'
+        >
+          {unlocking ? t('unlocking') : t('unlock')}
+        </button>
+      </form>
+    </div>
+  )
+}
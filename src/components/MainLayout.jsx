@@ -1,6 +1,11 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import Sidebar from './Sidebar'
 import TitleBar from './TitleBar'
 import RecordButton from './RecordButton'
+import LockScreen from './LockScreen'
+import Onboarding from './Onboarding'
 import Trace from '../pages/Trace'
 import Summary from '../pages/Summary'
 import Statistics from '../pages/Statistics'
@@ -9,6 +14,131 @@ import { useAppStore } from '../store'
 
 export default function MainLayout () {
   const currentPage = useAppStore((state) => state.currentPage)
+  const setCurrentPage = useAppStore((state) => state.setCurrentPage)
+  const setDeepLinkTarget = useAppStore((state) => state.setDeepLinkTarget)
+  // 状态读取完成前为 null，这时不显示页面，避免页面先请求数据
+  const [locked, setLocked] = useState(null)
+  const [onboarding, setOnboarding] = useState(null)
+
+  // 应用锁：启动时读取一次，之后跟随 app-lock-changed 事件（例如在设置中点击立即锁定）
+  useEffect(() => {
+    let unlisten = null
+    let isMounted = true
+
+    invoke('get_app_lock_status')
+      .then((status) => {
+        if (isMounted) {
+          setLocked(status.locked)
+        }
+      })
+      .catch((err) => {
+        console.error('Failed to load app lock status:', err)
+        if (isMounted) {
+          setLocked(false)
+        }
+      })
+    listen('app-lock-changed', (event) => setLocked(event.payload.locked)).then((fn) => {
+      unlisten = fn
+      if (!isMounted) {
+        unlisten()
+      }
+    })
+
+    return () => {
+      isMounted = false
+      if (unlisten) {
+        unlisten()
+      }
+    }
+  }, [])
+
+  // 首次运行的引导：完成前不显示页面，录制和上传需要先在引导中同意
+  useEffect(() => {
+    let unlisten = null
+    let isMounted = true
+
+    invoke('get_onboarding_state')
+      .then((state) => {
+        if (isMounted) {
+          setOnboarding(state)
+        }
+      })
+      .catch((err) => {
+        console.error('Failed to load onboarding state:', err)
+        if (isMounted) {
+          setOnboarding({ completed: true })
+        }
+      })
+    listen('onboarding-changed', (event) => setOnboarding(event.payload)).then((fn) => {
+      unlisten = fn
+      if (!isMounted) {
+        unlisten()
+      }
+    })
+
+    return () => {
+      isMounted = false
+      if (unlisten) {
+        unlisten()
+      }
+    }
+  }, [])
+
+  // clarity:// 链接：启动时读取一次（通过链接启动的情况），之后每次打开链接时读取
+  useEffect(() => {
+    let unlisten = null
+    let isMounted = true
+
+    const openDeepLink = async () => {
+      try {
+        const target = await invoke('take_deep_link')
+        if (target && isMounted) {
+          setDeepLinkTarget(target)
+          setCurrentPage('trace')
+        }
+      } catch (err) {
+        console.error('Failed to open deep link:', err)
+      }
+    }
+
+    openDeepLink()
+    listen('deep-link-opened', openDeepLink).then((fn) => {
+      unlisten = fn
+      if (!isMounted) {
+        unlisten()
+      }
+    })
+
+    return () => {
+      isMounted = false
+      if (unlisten) {
+        unlisten()
+      }
+    }
+  }, [])
+
+  // 所有数据已清除：清空本地保存的界面状态，重新加载回到首次运行的界面
+  useEffect(() => {
+    let unlisten = null
+    let isMounted = true
+
+    listen('data-wiped', () => {
+      useAppStore.persist.clearStorage()
+      window.location.reload()
+    }).then((fn) => {
+      unlisten = fn
+      if (!isMounted) {
+        unlisten()
+      }
+    })
+
+    return () => {
+      isMounted = false
+      if (unlisten) {
+        unlisten()
+      }
+    }
+  }, [])
 
   const renderPage = () => {
     switch (currentPage) {
@@ -28,16 +158,25 @@ export default function MainLayout () {
   return (
     <div className='flex flex-col h-screen bg-gray-50 overflow-hidden'>
       <TitleBar />
-      <div className='flex flex-1 overflow-hidden min-h-0'>
-        <Sidebar />
-        <main className='flex-1 overflow-y-auto bg-white relative min-w-0'>
-          {renderPage()}
-        </main>
-      </div>
-      {/* Footer Toolbar with Record Button */}
-      <footer className='h-14 bg-white border-t border-gray-200 flex items-center justify-center px-4 shrink-0'>
-        <RecordButton />
-      </footer>
+      {(locked === null || onboarding === null) && <div className='flex-1' />}
+      {locked && <LockScreen onUnlock={(status) => setLocked(status.locked)} />}
+      {locked === false && onboarding && !onboarding.completed && (
+        <Onboarding state={onboarding} onChange={setOnboarding} />
+      )}
+      {locked === false && onboarding?.completed && (
+        <>
+          <div className='flex flex-1 overflow-hidden min-h-0'>
+            <Sidebar />
+            <main className='flex-1 overflow-y-auto bg-white relative min-w-0'>
+              {renderPage()}
+            </main>
+          </div>
+          {/* Footer Toolbar with Record Button */}
+          <footer className='h-14 bg-white border-t border-gray-200 flex items-center justify-center px-4 shrink-0'>
+            <RecordButton />
+          </footer>
+        </>
+      )}
     </div>
   )
 }
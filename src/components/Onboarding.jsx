@@ -0,0 +1,74 @@
+import { useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { useTranslation } from '../i18n'
+import { errorMessage } from '../errors'
+
+// 首次运行的引导：欢迎 → 同意截图 → 同意上传给 AI，完成前代替页面显示
+export default function Onboarding ({ state, onChange }) {
+  const { t } = useTranslation()
+  const [saving, setSaving] = useState(false)
+  const [error, setError] = useState(null)
+
+  const advance = async (granted) => {
+    setSaving(true)
+    setError(null)
+    try {
+      onChange(await invoke('advance_onboarding', { step: state.step, granted }))
+    } catch (err) {
+      setError(errorMessage(err))
+    } finally {
+      setSaving(false)
+    }
+  }
+
+  const steps = {
+    welcome: {
+      title: t('onboardingWelcome'),
+      description: t('onboardingWelcomeDescription'),
+      accept: t('onboardingContinue')
+    },
+    screen_capture: {
+      title: t('onboardingScreenCapture'),
+      description: t('onboardingScreenCaptureDescription'),
+      accept: t('onboardingAllow'),
+      decline: t('onboardingNotNow')
+    },
+    ai_upload: {
+      title: t('onboardingAiUpload'),
+      description: t('onboardingAiUploadDescription'),
+      accept: t('onboardingAllow'),
+      decline: t('onboardingKeepLocal')
+    }
+  }
+  const step = steps[state.step] || steps.welcome
+
+  return (
+    <div className='flex-1 flex items-center justify-center bg-gray-50'>
+      <div className='w-96 bg-white border border-gray-200 rounded-lg p-6'>
+        <h2 className='text-lg font-semibold text-gray-900 mb-1'>{step.title}</h2>
+        <p className='text-sm text-gray-600 mb-4'>{step.description}</p>
+        {error && (
+          <p className='mb-2 text-sm text-red-600'>{error}</p>
+        )}
+        <div className='flex gap-2'>
+          {step.decline && (
+            <button
+              onClick={() => advance(false)}
+              disabled={saving}
+              className='flex-1 px-4 py-2 bg-white border border-gray-300 text-gray-700 text-sm rounded-lg hover:bg-gray-50 disabled:opacity-50 disabled:cursor-not-allowed transition-colors font-medium'
+            >
+              {step.decline}
+            </button>
+          )}
+          <button
+            onClick={() => advance(step.decline ? true : null)}
+            disabled={saving}
+            className='flex-1 px-4 py-2 bg-gray-900 text-white text-sm rounded-lg hover:bg-gray-800 disabled:opacity-50 disabled:cursor-not-allowed transition-colors font-medium'
+          >
+            {step.accept}
+          </button>
+        </div>
+      </div>
+    </div>
+  )
+}
@@ -1,12 +1,15 @@
 import { useState, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { useTranslation } from '../i18n'
+import { errorMessage } from '../errors'
 
 export default function RecordButton () {
   const { t } = useTranslation()
   const [isRecording, setIsRecording] = useState(false)
   const [loading, setLoading] = useState(false)
   const [error, setError] = useState(null)
+  const [incognito, setIncognito] = useState(false)
 
   const loadStatus = async () => {
     try {
@@ -15,7 +18,7 @@ export default function RecordButton () {
       setError(null)
     } catch (err) {
       console.error('Failed to load status:', err)
-      setError(err?.toString() || String(err))
+      setError(errorMessage(err))
     }
   }
 
@@ -25,6 +28,44 @@ export default function RecordButton () {
     return () => clearInterval(interval)
   }, [])
 
+  // 隐身模式也可以通过快捷键和托盘切换，跟随 incognito-changed 事件
+  useEffect(() => {
+    let unlisten = null
+    let isMounted = true
+
+    invoke('get_incognito_mode')
+      .then((status) => {
+        if (isMounted) {
+          setIncognito(status.enabled)
+        }
+      })
+      .catch((err) => console.error('Failed to load incognito mode:', err))
+    listen('incognito-changed', (event) => setIncognito(event.payload.enabled)).then((fn) => {
+      unlisten = fn
+      if (!isMounted) {
+        unlisten()
+      }
+    })
+
+    return () => {
+      isMounted = false
+      if (unlisten) {
+        unlisten()
+      }
+    }
+  }, [])
+
+  const toggleIncognito = async () => {
+    setError(null)
+    try {
+      const status = await invoke('set_incognito_mode', { enabled: !incognito })
+      setIncognito(status.enabled)
+    } catch (err) {
+      console.error('Failed to toggle incognito mode:', err)
+      setError(errorMessage(err))
+    }
+  }
+
   const handleToggle = async () => {
     if (loading) return
 
@@ -46,7 +87,7 @@ export default function RecordButton () {
       }, 500)
     } catch (err) {
       console.error('Failed to toggle recording:', err)
-      setError(err?.toString() || String(err))
+      setError(errorMessage(err))
     } finally {
       setLoading(false)
     }
@@ -106,6 +147,17 @@ export default function RecordButton () {
               : t('stopped')}
         </span>
       </div>
+      <button
+        onClick={toggleIncognito}
+        className={`ml-2 px-2 py-0.5 text-xs font-medium rounded border transition-colors ${
+          incognito
+            ? 'bg-gray-900 text-white border-gray-900 hover:bg-gray-800'
+            : 'bg-white text-gray-600 border-gray-300 hover:bg-gray-100'
+        }`}
+        title={t('incognitoModeHint')}
+      >
+        {incognito ? t('incognitoOn') : t('incognitoOff')}
+      </button>
     </div>
   )
 }
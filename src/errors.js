@@ -0,0 +1,16 @@
+// 后端命令的错误是 { kind, message, retryable } 对象，其他错误按字符串处理
+export function errorMessage (err) {
+  if (err && typeof err === 'object' && 'message' in err) {
+    return err.message
+  }
+  return String(err)
+}
+
+// 错误类型：database、network、ai、permission、ffmpegMissing、notFound、invalidInput、conflict、locked、consentRequired、internal
+export function errorKind (err) {
+  return err && typeof err === 'object' && 'kind' in err ? err.kind : 'internal'
+}
+
+export function isRetryable (err) {
+  return Boolean(err && typeof err === 'object' && err.retryable)
+}
@@ -1,12 +1,23 @@
 import { useAppStore } from '../store'
 import { translations } from './locales'
 
+// 按 zh-Hant-TW → zh-Hant → zh → en 依次查找，没有对应翻译的语言使用英文界面
+const fallbackChain = (language) => {
+  const subtags = language.split(/[-_]/)
+  const chain = subtags.map((_, i) => subtags.slice(0, subtags.length - i).join('-'))
+  return [...chain, 'en']
+}
+
 export const useTranslation = () => {
   // 订阅语言变化，确保组件在语言改变时重新渲染
   const language = useAppStore((state) => state.language || 'en')
 
   const t = (key) => {
-    return translations[language]?.[key] || translations.en[key] || key
+    for (const locale of fallbackChain(language)) {
+      const text = translations[locale]?.[key]
+      if (text) return text
+    }
+    return key
   }
 
   return { t, language }
@@ -139,6 +139,9 @@ export const translations = {
     storagePath: 'Storage Path:',
     language: 'Language',
     languageDescription: 'Select your preferred language for the application interface.',
+    languageFallbackNote: 'Languages without a translation use the English interface. AI prompts and summaries still use the selected language.',
+    aiPromptLanguageNote: 'Prompt will automatically switch based on current language. Current language:',
+    customPromptPlaceholder: 'Enter your custom prompt...',
     videoResolution: 'Video Resolution',
     videoResolutionDescription: 'Set the video resolution for AI analysis. Higher resolution improves text recognition (e.g., prices, numbers) but increases token usage.',
     resolution: 'Resolution',
@@ -149,9 +152,65 @@ export const translations = {
     resolutionNote: 'Low resolution: ~66 tokens per frame. Default resolution: ~258 tokens per frame. Use default resolution for better text recognition.',
     saveResolution: 'Save Resolution',
     resolutionSavedSuccessfully: 'Video resolution saved successfully',
+    diagnostics: 'Diagnostics',
+    diagnosticsDescription: 'Recent log entries, useful when something goes wrong. Logs are kept for 7 days in the logs folder next to the database.',
+    logLevel: 'Log Level',
+    showLogs: 'Show',
+    errorsOnly: 'Errors only',
+    warningsAndErrors: 'Warnings and errors',
+    allLogs: 'All',
+    noLogEntries: 'No log entries',
+    apiServer: 'Local API',
+    apiServerDescription: 'Let scripts, dashboards and launcher extensions read your status, traces, summaries and statistics over HTTP. Only this computer can connect, and every request needs the token.',
+    enableApiServer: 'Enable local API',
+    apiServerPort: 'Port',
+    apiServerToken: 'Token',
+    apiServerRunning: 'Running at',
+    apiServerNotRunning: 'Not running',
+    regenerateToken: 'Regenerate Token',
+    saveApiServer: 'Save',
+    apiServerSavedSuccessfully: 'Local API settings saved',
+    appLock: 'App Lock',
+    appLockDescription: 'Require a PIN before Clarity shows your history. Clarity starts locked when a PIN is set; recording continues while locked.',
+    appLockPin: 'PIN',
+    appLockCurrentPin: 'Current PIN',
+    appLockNewPin: 'New PIN',
+    appLockPinHint: 'At least 4 characters',
+    appLockSetPin: 'Set PIN',
+    appLockChangePin: 'Change PIN',
+    appLockRemovePin: 'Remove PIN',
+    appLockLockNow: 'Lock Now',
+    appLockPinSaved: 'PIN saved',
+    appLockPinRemoved: 'App lock turned off',
+    appLocked: 'Clarity is locked',
+    consent: 'Consent',
+    consentDescription: 'Clarity only records after you allow screen capture, and only sends screenshots to an AI provider after you allow uploads. Screenshots taken without upload consent stay on this device even if you allow uploads later.',
+    onboardingWelcome: 'Welcome to Clarity',
+    onboardingWelcomeDescription: 'Clarity takes a screenshot every second while recording and turns them into summaries of your day. Everything is stored on this device.',
+    onboardingContinue: 'Continue',
+    onboardingScreenCapture: 'Capture my screen',
+    onboardingScreenCaptureDescription: 'Allow Clarity to take screenshots while recording. You can pause or stop recording at any time, and change this later in Settings.',
+    onboardingAiUpload: 'Upload to an AI provider',
+    onboardingAiUploadDescription: 'Summaries are written by an AI provider, so screenshots are uploaded to it. If you keep everything local, Clarity records but does not summarize.',
+    onboardingAllow: 'Allow',
+    onboardingNotNow: 'Not now',
+    onboardingKeepLocal: 'Keep local',
+    appLockedDescription: 'Enter your PIN to view your history.',
+    unlock: 'Unlock',
+    unlocking: 'Unlocking...',
+    wipeData: 'Delete All Data',
+    wipeDataDescription: 'Permanently delete all screenshots, summary videos, summaries, settings, saved API keys, logs and all other profiles. API keys that other profiles saved in the system keychain are kept. Clarity returns to its first-run state. This cannot be undone.',
+    wipeDataConfirmHint: 'Type WIPE ALL DATA to confirm',
+    wipeDataButton: 'Delete Everything',
+    wiping: 'Deleting...',
 
     // RecordButton
     startRecording: 'Start Recording',
+    incognitoOn: 'Incognito on',
+    incognitoOff: 'Incognito off',
+    incognitoModeHint: 'Incognito mode keeps capturing for your own reference but never uploads to an AI provider (Ctrl/Cmd+Alt+I)',
+    privateTime: 'Private time (incognito mode)',
+    meetingTime: 'Meeting or screen sharing',
     stopRecording: 'Stop Recording'
   },
   zh: {
@@ -294,6 +353,9 @@ export const translations = {
     storagePath: '存储路径：',
     language: '语言',
     languageDescription: '选择您偏好的应用程序界面语言。',
+    languageFallbackNote: '没有翻译的语言使用英文界面，AI 提示词和总结仍使用所选语言。',
+    aiPromptLanguageNote: '提示词会根据当前语言自动切换。当前语言：',
+    customPromptPlaceholder: '输入您的自定义提示词...',
     videoResolution: '视频分辨率',
     videoResolutionDescription: '设置 AI 分析视频时的分辨率。更高分辨率可以提高文字识别精度（如价格、数字），但会增加 token 消耗。',
     resolution: '分辨率',
@@ -304,9 +366,65 @@ export const translations = {
     resolutionNote: '低分辨率：每帧约 66 tokens。默认分辨率：每帧约 258 tokens。需要识别文字细节（如价格）时建议使用默认分辨率。',
     saveResolution: '保存分辨率',
     resolutionSavedSuccessfully: '视频分辨率保存成功',
+    diagnostics: '诊断',
+    diagnosticsDescription: '最近的日志，出现问题时可以在这里查看原因。日志保存在数据库所在目录的 logs 文件夹中，保留 7 天。',
+    logLevel: '日志级别',
+    showLogs: '显示',
+    errorsOnly: '仅错误',
+    warningsAndErrors: '警告和错误',
+    allLogs: '全部',
+    noLogEntries: '没有日志',
+    apiServer: '本地 API',
+    apiServerDescription: '允许脚本、仪表盘和启动器插件通过 HTTP 读取录制状态、截图记录、摘要和统计。只有本机可以连接，每个请求都需要带上令牌。',
+    enableApiServer: '开启本地 API',
+    apiServerPort: '端口',
+    apiServerToken: '令牌',
+    apiServerRunning: '运行地址',
+    apiServerNotRunning: '未运行',
+    regenerateToken: '重新生成令牌',
+    saveApiServer: '保存',
+    apiServerSavedSuccessfully: '本地 API 设置已保存',
+    appLock: '应用锁',
+    appLockDescription: '查看历史记录前需要输入 PIN。设置 PIN 后 Clarity 启动时处于锁定状态，锁定时仍会继续录制。',
+    appLockPin: 'PIN',
+    appLockCurrentPin: '当前 PIN',
+    appLockNewPin: '新 PIN',
+    appLockPinHint: '至少 4 个字符',
+    appLockSetPin: '设置 PIN',
+    appLockChangePin: '修改 PIN',
+    appLockRemovePin: '删除 PIN',
+    appLockLockNow: '立即锁定',
+    appLockPinSaved: 'PIN 已保存',
+    appLockPinRemoved: '应用锁已关闭',
+    appLocked: 'Clarity 已锁定',
+    consent: '同意',
+    consentDescription: '同意截图后 Clarity 才会录制，同意上传后才会把截图发送给 AI 服务。没有同意上传时的截图即使之后同意也只保存在本机。',
+    onboardingWelcome: '欢迎使用 Clarity',
+    onboardingWelcomeDescription: '录制时 Clarity 每秒截取一张屏幕截图，并生成一天的活动摘要。所有数据都保存在本机。',
+    onboardingContinue: '继续',
+    onboardingScreenCapture: '截取屏幕',
+    onboardingScreenCaptureDescription: '允许 Clarity 在录制时截取屏幕。你可以随时暂停或停止录制，之后也可以在设置中修改。',
+    onboardingAiUpload: '上传给 AI 服务',
+    onboardingAiUploadDescription: '摘要由 AI 服务生成，需要把截图上传给它。选择只保存在本地时 Clarity 会继续录制，但不生成摘要。',
+    onboardingAllow: '允许',
+    onboardingNotNow: '暂不',
+    onboardingKeepLocal: '只保存在本地',
+    appLockedDescription: '输入 PIN 查看历史记录。',
+    unlock: '解锁',
+    unlocking: '正在解锁...',
+    wipeData: '清除全部数据',
+    wipeDataDescription: '永久删除所有截图、总结视频、摘要、设置、保存的 API key、日志和所有其他配置（其他配置保存在系统钥匙串中的 API key 会保留），Clarity 回到首次运行的状态。此操作无法撤销。',
+    wipeDataConfirmHint: '输入 WIPE ALL DATA 确认',
+    wipeDataButton: '全部删除',
+    wiping: '正在删除...',
 
     // RecordButton
     startRecording: '开始录制',
+    incognitoOn: '隐身模式已开启',
+    incognitoOff: '隐身模式已关闭',
+    incognitoModeHint: '隐身模式下继续截图供自己查看，但不会上传给任何 AI 服务（Ctrl/Cmd+Alt+I）',
+    privateTime: '私人时间（隐身模式）',
+    meetingTime: '会议或屏幕共享',
     stopRecording: '停止录制'
   }
 }
@@ -2,6 +2,10 @@ import { useState, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { useAppStore } from '../store'
 import { useTranslation } from '../i18n'
+import { errorMessage } from '../errors'
+
+// 清除全部数据前需要输入的确认文本（与后端一致）
+const WIPE_CONFIRM_TOKEN = 'WIPE ALL DATA'
 
 export default function Settings () {
   const { t } = useTranslation()
@@ -12,6 +16,22 @@ export default function Settings () {
   const [aiModel, setAiModel] = useState('gemini-3-flash-preview')
   const [aiPrompt, setAiPrompt] = useState('')
   const [videoResolution, setVideoResolution] = useState('low')
+  const [logLevel, setLogLevel] = useState('info')
+  const [logFilter, setLogFilter] = useState('warn')
+  const [logEntries, setLogEntries] = useState([])
+  const [apiServer, setApiServer] = useState({ enabled: false, port: 47600, token: '', running: false })
+  const [savingApiServer, setSavingApiServer] = useState(false)
+  const [apiServerMessage, setApiServerMessage] = useState(null)
+  const [appLock, setAppLock] = useState({ enabled: false, locked: false })
+  const [currentPin, setCurrentPin] = useState('')
+  const [newPin, setNewPin] = useState('')
+  const [savingAppLock, setSavingAppLock] = useState(false)
+  const [appLockMessage, setAppLockMessage] = useState(null)
+  const [consent, setConsent] = useState({ screenCapture: null, aiUpload: null })
+  const [consentMessage, setConsentMessage] = useState(null)
+  const [wipeConfirm, setWipeConfirm] = useState('')
+  const [wiping, setWiping] = useState(false)
+  const [wipeMessage, setWipeMessage] = useState(null)
   const [saving, setSaving] = useState(false)
   const [savingInterval, setSavingInterval] = useState(false)
   const [savingModel, setSavingModel] = useState(false)
@@ -23,47 +43,49 @@ export default function Settings () {
   const [promptMessage, setPromptMessage] = useState(null)
   const [resolutionMessage, setResolutionMessage] = useState(null)
 
-  // 默认提示词（按语言）
-  const defaultPrompts = {
-    zh: '分析这段屏幕活动视频，提供简洁的活动摘要。重点关注：1) 主要使用的应用/网站；2) 活动类型（工作/娱乐/学习等）；3) 是否有分心或低效行为。用中文回答，控制在100字以内。',
-    en: 'Analyze this screen activity video and provide a concise activity summary. Focus on: 1) Main apps/websites used; 2) Activity type (work/entertainment/learning, etc.); 3) Any distractions or inefficient behaviors. Respond in English, keep it under 100 words.'
-  }
+  // 可选的语言（其他 BCP-47 语言可以通过 update_settings 设置，会显示在列表末尾）
+  const languageOptions = [
+    { value: 'en', label: 'English' },
+    { value: 'zh', label: '中文' },
+    { value: 'ja', label: '日本語' },
+    { value: 'de', label: 'Deutsch' },
+    { value: 'es', label: 'Español' }
+  ]
+  const languageLabel = languageOptions.find((option) => option.value === language)?.label || language
 
   useEffect(() => {
     loadApiKey()
-    loadSummaryInterval()
-    loadAiModel()
+    loadSettings()
     loadAiPrompt()
-    loadLanguage()
-    loadVideoResolution()
+    loadApiServer()
+    loadAppLock()
+    loadConsent()
   }, [])
 
+  useEffect(() => {
+    loadLogs()
+  }, [logFilter])
+
   // 当语言切换时，重新加载对应语言的提示词
   // 注意：语言切换的保存已经在 select 的 onChange 中处理
   useEffect(() => {
     loadAiPrompt()
   }, [language])
 
-  const loadLanguage = async () => {
+  // 一次读取全部常用设置
+  const loadSettings = async () => {
     try {
-      const savedLanguage = await invoke('get_language')
-      if (savedLanguage && (savedLanguage === 'en' || savedLanguage === 'zh')) {
-        // 如果后端有语言设置且与前端不同，同步到前端
-        if (savedLanguage !== language) {
-          setLanguage(savedLanguage)
-        }
-      } else {
-        // 如果后端没有语言设置，将前端的语言设置保存到后端
-        await invoke('set_language', { language })
+      const settings = await invoke('get_settings')
+      setSummaryInterval(settings.summaryIntervalSeconds || 45)
+      setAiModel(settings.aiModel || 'gemini-3-flash-preview')
+      setVideoResolution(settings.videoResolution || 'low')
+      setLogLevel(settings.logLevel || 'info')
+      // 如果后端的语言设置与前端不同，同步到前端
+      if (settings.language !== language) {
+        setLanguage(settings.language)
       }
     } catch (err) {
-      console.error('Failed to load language:', err)
-      // 如果加载失败，尝试将前端语言保存到后端
-      try {
-        await invoke('set_language', { language })
-      } catch (saveErr) {
-        console.error('Failed to save language to backend:', saveErr)
-      }
+      console.error('Failed to load settings:', err)
     }
   }
 
@@ -76,55 +98,143 @@ export default function Settings () {
     }
   }
 
-  const loadSummaryInterval = async () => {
+  const loadAiPrompt = async () => {
     try {
-      const interval = await invoke('get_summary_interval')
-      setSummaryInterval(interval || 45)
+      // 后端在没有保存过提示词时返回该语言的默认提示词
+      const prompt = await invoke('get_ai_prompt', { language })
+      setAiPrompt(prompt || '')
     } catch (err) {
-      console.error('Failed to load summary interval:', err)
+      console.error('Failed to load AI prompt:', err)
     }
   }
 
-  const loadAiModel = async () => {
+  const saveVideoResolution = async () => {
+    setSavingResolution(true)
+    setResolutionMessage(null)
     try {
-      const model = await invoke('get_ai_model')
-      setAiModel(model || 'gemini-3-flash-preview')
+      await invoke('update_settings', { update: { videoResolution } })
+      setResolutionMessage({ type: 'success', text: t('resolutionSavedSuccessfully') })
+      setTimeout(() => setResolutionMessage(null), 3000)
     } catch (err) {
-      console.error('Failed to load AI model:', err)
+      console.error('Failed to save video resolution:', err)
+      setResolutionMessage({ type: 'error', text: errorMessage(err) })
+    } finally {
+      setSavingResolution(false)
     }
   }
 
-  const loadAiPrompt = async () => {
+  // 诊断：最近的警告和错误日志
+  const loadLogs = async () => {
     try {
-      const prompt = await invoke('get_ai_prompt', { language })
-      setAiPrompt(prompt || defaultPrompts[language] || defaultPrompts.zh)
+      const entries = await invoke('get_recent_logs', { lines: 100, level: logFilter })
+      setLogEntries(entries)
     } catch (err) {
-      console.error('Failed to load AI prompt:', err)
-      setAiPrompt(defaultPrompts[language] || defaultPrompts.zh)
+      console.error('Failed to load logs:', err)
     }
   }
 
-  const loadVideoResolution = async () => {
+  const saveLogLevel = async (level) => {
+    setLogLevel(level)
     try {
-      const resolution = await invoke('get_video_resolution')
-      setVideoResolution(resolution || 'low')
+      await invoke('update_settings', { update: { logLevel: level } })
     } catch (err) {
-      console.error('Failed to load video resolution:', err)
+      console.error('Failed to save log level:', err)
     }
   }
 
-  const saveVideoResolution = async () => {
-    setSavingResolution(true)
-    setResolutionMessage(null)
+  // 本地 REST API
+  const loadApiServer = async () => {
     try {
-      await invoke('set_video_resolution', { resolution: videoResolution })
-      setResolutionMessage({ type: 'success', text: t('resolutionSavedSuccessfully') })
-      setTimeout(() => setResolutionMessage(null), 3000)
+      setApiServer(await invoke('get_api_server_settings'))
     } catch (err) {
-      console.error('Failed to save video resolution:', err)
-      setResolutionMessage({ type: 'error', text: err.toString() })
+      console.error('Failed to load API server settings:', err)
+    }
+  }
+
+  const saveApiServer = async (command, args = {}) => {
+    setSavingApiServer(true)
+    setApiServerMessage(null)
+    try {
+      setApiServer(await invoke(command, args))
+      setApiServerMessage({ type: 'success', text: t('apiServerSavedSuccessfully') })
+      setTimeout(() => setApiServerMessage(null), 3000)
+    } catch (err) {
+      console.error('Failed to save API server settings:', err)
+      setApiServerMessage({ type: 'error', text: errorMessage(err) })
+      loadApiServer()
     } finally {
-      setSavingResolution(false)
+      setSavingApiServer(false)
+    }
+  }
+
+  // 应用锁
+  const loadAppLock = async () => {
+    try {
+      setAppLock(await invoke('get_app_lock_status'))
+    } catch (err) {
+      console.error('Failed to load app lock status:', err)
+    }
+  }
+
+  // pin 为 null 时删除 PIN
+  const saveAppLockPin = async (pin) => {
+    setSavingAppLock(true)
+    setAppLockMessage(null)
+    try {
+      setAppLock(await invoke('set_app_lock_pin', {
+        currentPin: appLock.enabled ? currentPin : null,
+        pin
+      }))
+      setCurrentPin('')
+      setNewPin('')
+      setAppLockMessage({ type: 'success', text: pin ? t('appLockPinSaved') : t('appLockPinRemoved') })
+      setTimeout(() => setAppLockMessage(null), 3000)
+    } catch (err) {
+      console.error('Failed to save app lock PIN:', err)
+      setAppLockMessage({ type: 'error', text: errorMessage(err) })
+    } finally {
+      setSavingAppLock(false)
+    }
+  }
+
+  const lockNow = async () => {
+    try {
+      setAppLock(await invoke('lock_app'))
+    } catch (err) {
+      console.error('Failed to lock app:', err)
+      setAppLockMessage({ type: 'error', text: errorMessage(err) })
+    }
+  }
+
+  // 引导流程中记录的同意，可以在这里修改
+  const loadConsent = async () => {
+    try {
+      setConsent(await invoke('get_onboarding_state'))
+    } catch (err) {
+      console.error('Failed to load consent:', err)
+    }
+  }
+
+  const saveConsent = async (name, granted) => {
+    setConsentMessage(null)
+    try {
+      setConsent(await invoke('set_onboarding_consent', { consent: name, granted }))
+    } catch (err) {
+      console.error('Failed to save consent:', err)
+      setConsentMessage({ type: 'error', text: errorMessage(err) })
+    }
+  }
+
+  // 清除全部数据，完成后 MainLayout 收到 data-wiped 事件并重新加载界面
+  const wipeAllData = async () => {
+    setWiping(true)
+    setWipeMessage(null)
+    try {
+      await invoke('wipe_all_data', { confirmToken: wipeConfirm })
+    } catch (err) {
+      console.error('Failed to wipe data:', err)
+      setWipeMessage({ type: 'error', text: errorMessage(err) })
+      setWiping(false)
     }
   }
 
@@ -137,7 +247,7 @@ export default function Settings () {
       setTimeout(() => setApiKeyMessage(null), 3000)
     } catch (err) {
       console.error('Failed to save API key:', err)
-      setApiKeyMessage({ type: 'error', text: err.toString() })
+      setApiKeyMessage({ type: 'error', text: errorMessage(err) })
     } finally {
       setSaving(false)
     }
@@ -147,12 +257,12 @@ export default function Settings () {
     setSavingInterval(true)
     setIntervalMessage(null)
     try {
-      await invoke('set_summary_interval', { intervalSeconds: summaryInterval })
+      await invoke('update_settings', { update: { summaryIntervalSeconds: summaryInterval } })
       setIntervalMessage({ type: 'success', text: t('intervalSavedSuccessfully') })
       setTimeout(() => setIntervalMessage(null), 3000)
     } catch (err) {
       console.error('Failed to save summary interval:', err)
-      setIntervalMessage({ type: 'error', text: err.toString() })
+      setIntervalMessage({ type: 'error', text: errorMessage(err) })
     } finally {
       setSavingInterval(false)
     }
@@ -162,12 +272,12 @@ export default function Settings () {
     setSavingModel(true)
     setModelMessage(null)
     try {
-      await invoke('set_ai_model', { model: aiModel })
+      await invoke('update_settings', { update: { aiModel } })
       setModelMessage({ type: 'success', text: t('modelSavedSuccessfully') })
       setTimeout(() => setModelMessage(null), 3000)
     } catch (err) {
       console.error('Failed to save AI model:', err)
-      setModelMessage({ type: 'error', text: err.toString() })
+      setModelMessage({ type: 'error', text: errorMessage(err) })
     } finally {
       setSavingModel(false)
     }
@@ -182,7 +292,7 @@ export default function Settings () {
       setTimeout(() => setPromptMessage(null), 3000)
     } catch (err) {
       console.error('Failed to save AI prompt:', err)
-      setPromptMessage({ type: 'error', text: err.toString() })
+      setPromptMessage({ type: 'error', text: errorMessage(err) })
     } finally {
       setSavingPrompt(false)
     }
@@ -198,7 +308,7 @@ export default function Settings () {
       setTimeout(() => setPromptMessage(null), 3000)
     } catch (err) {
       console.error('Failed to reset prompt:', err)
-      setPromptMessage({ type: 'error', text: err.toString() })
+      setPromptMessage({ type: 'error', text: errorMessage(err) })
     } finally {
       setSavingPrompt(false)
     }
@@ -234,7 +344,7 @@ export default function Settings () {
                   setLanguage(newLanguage)
                   // 保存语言设置到后端
                   try {
-                    await invoke('set_language', { language: newLanguage })
+                    await invoke('update_settings', { update: { language: newLanguage } })
                   } catch (err) {
                     console.error('Failed to save language to backend:', err)
                   }
@@ -243,9 +353,14 @@ export default function Settings () {
                 }}
                 className='w-full px-4 py-2.5 border border-gray-300 rounded-lg bg-white text-gray-900 focus:ring-2 focus:ring-gray-900 focus:border-gray-900 transition-all'
               >
-                <option value='en'>English</option>
-                <option value='zh'>中文</option>
+                {languageOptions.map((option) => (
+                  <option key={option.value} value={option.value}>{option.label}</option>
+                ))}
+                {!languageOptions.some((option) => option.value === language) && (
+                  <option value={language}>{language}</option>
+                )}
               </select>
+              <p className='mt-2 text-xs text-gray-500'>{t('languageFallbackNote')}</p>
             </div>
           </div>
         </div>
@@ -420,9 +535,7 @@ export default function Settings () {
           <p className='text-sm text-gray-600 mb-4'>
             {t('aiPromptDescription')}
             <span className='block mt-2 text-xs text-gray-500'>
-              {language === 'zh' 
-                ? '提示词会根据当前语言自动切换。当前语言：中文' 
-                : 'Prompt will automatically switch based on current language. Current language: English'}
+              {t('aiPromptLanguageNote')} {languageLabel}
             </span>
           </p>
 
@@ -451,7 +564,7 @@ export default function Settings () {
                 value={aiPrompt}
                 onChange={(e) => setAiPrompt(e.target.value)}
                 rows={6}
-                placeholder={language === 'zh' ? '输入您的自定义提示词...' : 'Enter your custom prompt...'}
+                placeholder={t('customPromptPlaceholder')}
                 className='w-full px-4 py-2.5 border border-gray-300 rounded-lg bg-white text-gray-900 focus:ring-2 focus:ring-gray-900 focus:border-gray-900 transition-all font-mono text-sm'
               />
             </div>
@@ -526,6 +639,266 @@ export default function Settings () {
           </div>
         </div>
 
+        {/* Diagnostics */}
+        <div className='bg-white border border-gray-200 rounded-lg p-4'>
+          <h3 className='text-base font-semibold text-gray-900 mb-3'>{t('diagnostics')}</h3>
+          <p className='text-sm text-gray-600 mb-4'>
+            {t('diagnosticsDescription')}
+          </p>
+
+          <div className='space-y-4'>
+            <div className='flex gap-4'>
+              <div className='flex-1'>
+                <label
+                  htmlFor='log-level'
+                  className='block text-sm font-medium text-gray-700 mb-2'
+                >
+                  {t('logLevel')}
+                </label>
+                <select
+                  id='log-level'
+                  value={logLevel}
+                  onChange={(e) => saveLogLevel(e.target.value)}
+                  className='w-full px-4 py-2.5 border border-gray-300 rounded-lg bg-white text-gray-900 focus:ring-2 focus:ring-gray-900 focus:border-gray-900 transition-all'
+                >
+                  {['error', 'warn', 'info', 'debug', 'trace'].map((level) => (
+                    <option key={level} value={level}>{level}</option>
+                  ))}
+                </select>
+              </div>
+              <div className='flex-1'>
+                <label
+                  htmlFor='log-filter'
+                  className='block text-sm font-medium text-gray-700 mb-2'
+                >
+                  {t('showLogs')}
+                </label>
+                <select
+                  id='log-filter'
+                  value={logFilter}
+                  onChange={(e) => setLogFilter(e.target.value)}
+                  className='w-full px-4 py-2.5 border border-gray-300 rounded-lg bg-white text-gray-900 focus:ring-2 focus:ring-gray-900 focus:border-gray-900 transition-all'
+                >
+                  <option value='error'>{t('errorsOnly')}</option>
+                  <option value='warn'>{t('warningsAndErrors')}</option>
+                  <option value='info'>{t('allLogs')}</option>
+                </select>
+              </div>
+            </div>
+
+            <div className='max-h-64 overflow-y-auto border border-gray-200 rounded-lg bg-gray-50 p-2 font-mono text-xs text-gray-800'>
+              {logEntries.length === 0
+                ? <p className='text-gray-500'>{t('noLogEntries')}</p>
+                : logEntries.map((entry, index) => (
+                  <div key={index} className='whitespace-pre-wrap break-all py-0.5'>
+                    <span className='text-gray-500'>{entry.timestamp}</span> {entry.level} {entry.message}
+                  </div>
+                ))}
+            </div>
+
+            <button
+              onClick={loadLogs}
+              className='px-4 py-2 bg-gray-200 text-gray-900 text-sm rounded-lg hover:bg-gray-300 transition-colors font-medium'
+            >
+              {t('refresh')}
+            </button>
+          </div>
+        </div>
+
+        {/* Local API */}
+        <div className='bg-white border border-gray-200 rounded-lg p-4'>
+          <h3 className='text-base font-semibold text-gray-900 mb-3'>{t('apiServer')}</h3>
+          <p className='text-sm text-gray-600 mb-4'>
+            {t('apiServerDescription')}
+          </p>
+
+          {apiServerMessage && (
+            <div className='mb-4 p-3 rounded-lg border bg-gray-50 text-gray-700 border-gray-200'>
+              {apiServerMessage.text}
+            </div>
+          )}
+
+          <div className='space-y-4'>
+            <label className='flex items-center gap-2 text-sm text-gray-700'>
+              <input
+                type='checkbox'
+                checked={apiServer.enabled}
+                onChange={(e) => setApiServer({ ...apiServer, enabled: e.target.checked })}
+              />
+              {t('enableApiServer')}
+            </label>
+
+            <div>
+              <label
+                htmlFor='api-server-port'
+                className='block text-sm font-medium text-gray-700 mb-2'
+              >
+                {t('apiServerPort')}
+              </label>
+              <input
+                id='api-server-port'
+                type='number'
+                min='1024'
+                max='65535'
+                value={apiServer.port}
+                onChange={(e) => setApiServer({ ...apiServer, port: parseInt(e.target.value) || 47600 })}
+                className='w-full px-4 py-2.5 border border-gray-300 rounded-lg bg-white text-gray-900 focus:ring-2 focus:ring-gray-900 focus:border-gray-900 transition-all'
+              />
+              <p className='mt-2 text-xs text-gray-500'>
+                {apiServer.running
+                  ? `${t('apiServerRunning')} http://127.0.0.1:${apiServer.port}/api/status`
+                  : t('apiServerNotRunning')}
+              </p>
+            </div>
+
+            <div>
+              <label
+                htmlFor='api-server-token'
+                className='block text-sm font-medium text-gray-700 mb-2'
+              >
+                {t('apiServerToken')}
+              </label>
+              <input
+                id='api-server-token'
+                type='text'
+                readOnly
+                value={apiServer.token}
+                onFocus={(e) => e.target.select()}
+                className='w-full px-4 py-2.5 border border-gray-300 rounded-lg bg-gray-50 text-gray-900 font-mono text-sm'
+              />
+            </div>
+
+            <div className='flex gap-2'>
+              <button
+                onClick={() => saveApiServer('set_api_server_settings', { enabled: apiServer.enabled, port: apiServer.port })}
+                disabled={savingApiServer}
+                className='px-4 py-2 bg-gray-900 text-white text-sm rounded-lg hover:bg-gray-800 disabled:opacity-50 disabled:cursor-not-allowed transition-colors font-medium'
+              >
+                {savingApiServer ? t('saving') : t('saveApiServer')}
+              </button>
+              <button
+                onClick={() => saveApiServer('regenerate_api_server_token')}
+                disabled={savingApiServer}
+                className='px-4 py-2 bg-gray-200 text-gray-900 text-sm rounded-lg hover:bg-gray-300 disabled:opacity-50 disabled:cursor-not-allowed transition-colors font-medium'
+              >
+                {t('regenerateToken')}
+              </button>
+            </div>
+          </div>
+        </div>
+
+        {/* App Lock */}
+        <div className='bg-white border border-gray-200 rounded-lg p-4'>
+          <h3 className='text-base font-semibold text-gray-900 mb-3'>{t('appLock')}</h3>
+          <p className='text-sm text-gray-600 mb-4'>
+            {t('appLockDescription')}
+          </p>
+
+          {appLockMessage && (
+            <div className='mb-4 p-3 rounded-lg border bg-gray-50 text-gray-700 border-gray-200'>
+              {appLockMessage.text}
+            </div>
+          )}
+
+          <div className='space-y-4'>
+            {appLock.enabled && (
+              <div>
+                <label
+                  htmlFor='app-lock-current-pin'
+                  className='block text-sm font-medium text-gray-700 mb-2'
+                >
+                  {t('appLockCurrentPin')}
+                </label>
+                <input
+                  id='app-lock-current-pin'
+                  type='password'
+                  autoComplete='off'
+                  value={currentPin}
+                  onChange={(e) => setCurrentPin(e.target.value)}
+                  className='w-full px-4 py-2.5 border border-gray-300 rounded-lg bg-white text-gray-900 focus:ring-2 focus:ring-gray-900 focus:border-gray-900 transition-all'
+                />
+              </div>
+            )}
+
+            <div>
+              <label
+                htmlFor='app-lock-new-pin'
+                className='block text-sm font-medium text-gray-700 mb-2'
+              >
+                {appLock.enabled ? t('appLockNewPin') : t('appLockPin')}
+              </label>
+              <input
+                id='app-lock-new-pin'
+                type='password'
+                autoComplete='off'
+                value={newPin}
+                onChange={(e) => setNewPin(e.target.value)}
+                className='w-full px-4 py-2.5 border border-gray-300 rounded-lg bg-white text-gray-900 focus:ring-2 focus:ring-gray-900 focus:border-gray-900 transition-all'
+              />
+              <p className='mt-2 text-xs text-gray-500'>{t('appLockPinHint')}</p>
+            </div>
+
+            <div className='flex gap-2'>
+              <button
+                onClick={() => saveAppLockPin(newPin)}
+                disabled={savingAppLock || !newPin}
+                className='px-4 py-2 bg-gray-900 text-white text-sm rounded-lg hover:bg-gray-800 disabled:opacity-50 disabled:cursor-not-allowed transition-colors font-medium'
+              >
+                {savingAppLock ? t('saving') : (appLock.enabled ? t('appLockChangePin') : t('appLockSetPin'))}
+              </button>
+              {appLock.enabled && (
+                <>
+                  <button
+                    onClick={() => saveAppLockPin(null)}
+                    disabled={savingAppLock || !currentPin}
+                    className='px-4 py-2 bg-gray-200 text-gray-900 text-sm rounded-lg hover:bg-gray-300 disabled:opacity-50 disabled:cursor-not-allowed transition-colors font-medium'
+                  >
+                    {t('appLockRemovePin')}
+                  </button>
+                  <button
+                    onClick={lockNow}
+                    disabled={savingAppLock}
+                    className='px-4 py-2 bg-gray-200 text-gray-900 text-sm rounded-lg hover:bg-gray-300 disabled:opacity-50 disabled:cursor-not-allowed transition-colors font-medium'
+                  >
+                    {t('appLockLockNow')}
+                  </button>
+                </>
+              )}
+            </div>
+          </div>
+        </div>
+
+        {/* Consent */}
+        <div className='bg-white border border-gray-200 rounded-lg p-4'>
+          <h3 className='text-base font-semibold text-gray-900 mb-3'>{t('consent')}</h3>
+          <p className='text-sm text-gray-600 mb-4'>
+            {t('consentDescription')}
+          </p>
+
+          {consentMessage && (
+            <div className='mb-4 p-3 rounded-lg border bg-gray-50 text-gray-700 border-gray-200'>
+              {consentMessage.text}
+            </div>
+          )}
+
+          <div className='space-y-3'>
+            {[
+              { name: 'screen_capture', label: t('onboardingScreenCapture'), record: consent.screenCapture },
+              { name: 'ai_upload', label: t('onboardingAiUpload'), record: consent.aiUpload }
+            ].map(({ name, label, record }) => (
+              <label key={name} className='flex items-center gap-2 text-sm text-gray-700'>
+                <input
+                  type='checkbox'
+                  checked={Boolean(record?.granted)}
+                  onChange={(e) => saveConsent(name, e.target.checked)}
+                  className='rounded border-gray-300 text-gray-900 focus:ring-gray-900'
+                />
+                {label}
+              </label>
+            ))}
+          </div>
+        </div>
+
         {/* Storage Info */}
         <div className='bg-white border border-gray-200 rounded-lg p-4'>
           <h3 className='text-base font-semibold text-gray-900 mb-3'>{t('storage')}</h3>
@@ -538,6 +911,48 @@ export default function Settings () {
             </div>
           </div>
         </div>
+
+        {/* Delete All Data */}
+        <div className='bg-white border border-red-200 rounded-lg p-4'>
+          <h3 className='text-base font-semibold text-red-700 mb-3'>{t('wipeData')}</h3>
+          <p className='text-sm text-gray-600 mb-4'>
+            {t('wipeDataDescription')}
+          </p>
+
+          {wipeMessage && (
+            <div className='mb-4 p-3 rounded-lg border bg-gray-50 text-gray-700 border-gray-200'>
+              {wipeMessage.text}
+            </div>
+          )}
+
+          <div className='space-y-4'>
+            <div>
+              <label
+                htmlFor='wipe-confirm'
+                className='block text-sm font-medium text-gray-700 mb-2'
+              >
+                {t('wipeDataConfirmHint')}
+              </label>
+              <input
+                id='wipe-confirm'
+                type='text'
+                autoComplete='off'
+                value={wipeConfirm}
+                onChange={(e) => setWipeConfirm(e.target.value)}
+                placeholder={WIPE_CONFIRM_TOKEN}
+                className='w-full px-4 py-2.5 border border-gray-300 rounded-lg bg-white text-gray-900 focus:ring-2 focus:ring-red-600 focus:border-red-600 transition-all'
+              />
+            </div>
+
+            <button
+              onClick={wipeAllData}
+              disabled={wiping || wipeConfirm !== WIPE_CONFIRM_TOKEN}
+              className='px-4 py-2 bg-red-600 text-white text-sm rounded-lg hover:bg-red-700 disabled:opacity-50 disabled:cursor-not-allowed transition-colors font-medium'
+            >
+              {wiping ? t('wiping') : t('wipeDataButton')}
+            </button>
+          </div>
+        </div>
       </div>
     </div>
   )
@@ -4,6 +4,24 @@ import { mdiCamera, mdiFileDocumentOutline, mdiWeb, mdiNumeric } from '@mdi/js'
 import { invoke } from '@tauri-apps/api/core'
 import { listen } from '@tauri-apps/api/event'
 import { useTranslation } from '../i18n'
+import { errorMessage } from '../errors'
+
+// 把一次新的 API 请求累加到今天的 API 统计
+function addApiRequest (apiStats, request) {
+  const stats = apiStats || {}
+  const totalRequests = (stats.totalRequests || 0) + 1
+  return {
+    ...stats,
+    totalRequests,
+    successfulRequests: (stats.successfulRequests || 0) + (request.success ? 1 : 0),
+    failedRequests: (stats.failedRequests || 0) + (request.success ? 0 : 1),
+    totalPromptTokens: (stats.totalPromptTokens || 0) + (request.promptTokens || 0),
+    totalCompletionTokens: (stats.totalCompletionTokens || 0) + (request.completionTokens || 0),
+    totalTokens: (stats.totalTokens || 0) + (request.totalTokens || 0),
+    avgDurationMs: ((stats.avgDurationMs || 0) * (totalRequests - 1) + request.durationMs) / totalRequests,
+    totalCostUsd: (stats.totalCostUsd || 0) + (request.costUsd || 0)
+  }
+}
 
 export default function Statistics () {
   const { t } = useTranslation()
@@ -29,7 +47,7 @@ export default function Statistics () {
         setError(tRef.current('failedToLoadStatistics'))
       }
     } catch (err) {
-      const errorMsg = err?.toString() || String(err)
+      const errorMsg = errorMessage(err)
       setError(errorMsg)
       console.error('Failed to load statistics:', err)
     } finally {
@@ -45,28 +63,44 @@ export default function Statistics () {
     // 初始加载
     loadStatistics()
 
-    // 监听统计更新事件
+    // 新截图和 API 请求直接累加到当前统计，摘要变化会影响分类时长，重新加载
     const setupListener = async () => {
       try {
-        const unlisten = await listen('statistics-updated', () => {
-          console.log('Statistics updated event received, refreshing...')
-          if (isMounted) {
-            loadStatistics()
-          }
-        })
-        unlistenFn = unlisten
+        const unlisteners = await Promise.all([
+          listen('screenshot-captured', () => {
+            if (isMounted) {
+              setStats((prev) => prev && { ...prev, screenshotCount: (prev.screenshotCount || 0) + 1 })
+            }
+          }),
+          listen('api-request-recorded', (event) => {
+            if (isMounted) {
+              setStats((prev) => prev && { ...prev, apiStatistics: addApiRequest(prev.apiStatistics, event.payload) })
+            }
+          }),
+          ...['summary-created', 'summary-updated', 'summary-deleted'].map((name) =>
+            listen(name, () => {
+              if (isMounted) {
+                loadStatistics()
+              }
+            })
+          )
+        ])
+        unlistenFn = () => unlisteners.forEach((unlisten) => unlisten())
+        if (!isMounted) {
+          unlistenFn()
+        }
       } catch (err) {
         console.error('Failed to setup statistics listener:', err)
       }
     }
     setupListener()
 
-    // 每5秒刷新一次
+    // 导入、清理等操作不发送事件，每分钟完整刷新一次
     interval = setInterval(() => {
       if (isMounted) {
         loadStatistics()
       }
-    }, 5000)
+    }, 60000)
 
     return () => {
       isMounted = false
@@ -17,6 +17,7 @@ import {
 import { useTranslation } from '../i18n'
 import Icon from '@mdi/react'
 import { mdiRefresh, mdiChartLine, mdiChartBar, mdiTrendingUp, mdiTrendingDown } from '@mdi/js'
+import { errorMessage } from '../errors'
 
 export default function Summary () {
   const { t } = useTranslation()
@@ -80,7 +81,7 @@ export default function Summary () {
       setYearlyStats(yearData || [])
     } catch (err) {
       console.error('Failed to load summary data:', err)
-      setError(err.toString())
+      setError(errorMessage(err))
     } finally {
       setLoading(false)
     }
@@ -96,7 +97,7 @@ export default function Summary () {
       await loadAllData()
     } catch (err) {
       console.error('Failed to generate daily summary:', err)
-      setError(err.toString())
+      setError(errorMessage(err))
     } finally {
       setGenerating(false)
     }
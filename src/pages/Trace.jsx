@@ -16,6 +16,7 @@ import {
 } from '@mdi/js'
 import { useTranslation } from '../i18n'
 import { useAppStore } from '../store'
+import { errorMessage } from '../errors'
 
 // 价值判断标签组件 - 使用 mdi 图标
 function ValueLabel ({ value, language }) {
@@ -98,6 +99,9 @@ export default function Trace () {
   const [viewMode, setViewMode] = useState('timeline') // timeline, tag, app, focus
   const [showSuggestions, setShowSuggestions] = useState(false)
   const mainRef = useRef(null)
+  const pendingSummaryId = useRef(null)
+  const deepLinkTarget = useAppStore((state) => state.deepLinkTarget)
+  const setDeepLinkTarget = useAppStore((state) => state.setDeepLinkTarget)
 
   // 获取指定日期的开始和结束时间
   const getDateRange = (date) => {
@@ -124,7 +128,7 @@ export default function Trace () {
       setSummaries(data || [])
     } catch (err) {
       console.error('Failed to load summaries:', err)
-      setError(err.toString())
+      setError(errorMessage(err))
     } finally {
       setLoading(false)
     }
@@ -159,17 +163,19 @@ export default function Trace () {
         const dataUrls = {}
         for (const trace of traces.slice(0, 5)) {
           const filePath = trace.filePath || trace.file_path
+          // 优先加载缩略图，避免读取整张原图
+          const previewPath = trace.thumbnailPath || filePath
           if (filePath) {
             try {
               // 优先使用 convertFileSrc（更快）
-              const assetUrl = convertFileSrc(filePath)
+              const assetUrl = convertFileSrc(previewPath)
               dataUrls[filePath] = assetUrl
               console.log('Converted file path to asset URL:', filePath, '->', assetUrl)
             } catch (err) {
               console.warn('convertFileSrc failed, trying backend command:', filePath, err)
               // 如果 convertFileSrc 失败，使用后端命令读取文件并转换为 base64
               try {
-                const base64DataUrl = await invoke('read_screenshot_file', { filePath })
+                const base64DataUrl = await invoke('read_screenshot_file', { filePath: previewPath })
                 dataUrls[filePath] = base64DataUrl
                 console.log('Loaded file via backend command:', filePath)
               } catch (readErr) {
@@ -198,6 +204,27 @@ export default function Trace () {
     loadSummaries(currentDate)
   }, [currentDate])
 
+  // 通过 clarity:// 链接打开：切换到对应日期，加载完成后展开并滚动到对应摘要
+  useEffect(() => {
+    if (!deepLinkTarget) return
+    pendingSummaryId.current = deepLinkTarget.summaryId ?? null
+    setCurrentDate(new Date(deepLinkTarget.date + 'T00:00:00'))
+    setDeepLinkTarget(null)
+  }, [deepLinkTarget])
+
+  useEffect(() => {
+    const summaryId = pendingSummaryId.current
+    const summary = summaryId !== null && summaries.find((s) => s.id === summaryId)
+    if (!summary) return
+    pendingSummaryId.current = null
+    if (!expandedCards.has(summaryId)) {
+      toggleCard(summaryId)
+    }
+    setTimeout(() => {
+      document.getElementById(`summary-${summaryId}`)?.scrollIntoView({ behavior: 'smooth', block: 'center' })
+    }, 0)
+  }, [summaries])
+
   const toggleCard = async (summaryId) => {
     const isCurrentlyExpanded = expandedCards.has(summaryId)
     
@@ -696,7 +723,8 @@ export default function Trace () {
                   const isFirst = index === 0
                   const isExpanded = expandedCards.has(summary.id)
                   const valueLabel = analyzeValueLabel(summary.content)
-                  const shortDesc = extractShortDescription(summary.content)
+                  const privateLabel = summary.structuredData?.category === 'meetings' ? t('meetingTime') : t('privateTime')
+                  const shortDesc = summary.private ? privateLabel : extractShortDescription(summary.content)
                   const previews = screenshotPreviews[summary.id] || []
 
                   return (
@@ -746,7 +774,7 @@ export default function Trace () {
                                       blockquote: ({ node, ...props }) => <blockquote className='border-l-2 border-gray-300 pl-3 italic text-sm text-gray-600 my-2.5' {...props} />
                                     }}
                                   >
-                                    {summary.content}
+                                    {summary.private ? privateLabel : summary.content}
                                   </ReactMarkdown>
                                 </div>
                               )}
@@ -6,11 +6,15 @@ export const useAppStore = create(
     (set) => ({
       currentPage: 'trace', // trace, summary, settings
       setCurrentPage: (page) => set({ currentPage: page }),
-      language: 'en', // en, zh
-      setLanguage: (lang) => set({ language: lang })
+      language: 'en', // BCP-47 语言标签，例如 en、zh、ja、de-AT
+      setLanguage: (lang) => set({ language: lang }),
+      // 通过 clarity:// 链接要打开的位置 { date, summaryId }，Trace 页面处理后清空
+      deepLinkTarget: null,
+      setDeepLinkTarget: (target) => set({ deepLinkTarget: target })
     }),
     {
-      name: 'clarity-storage' // localStorage key
+      name: 'clarity-storage', // localStorage key
+      partialize: (state) => ({ currentPage: state.currentPage, language: state.language })
     }
   )
 )